@@ -102,6 +102,7 @@ fn new_node(tokio_handle: Handle) -> node_cli::service::NewFullBase {
 		tracing_receiver: Default::default(),
 		max_runtime_instances: 8,
 		runtime_cache_size: 2,
+		shutdown_timeout: Duration::from_secs(60),
 		announce_block: true,
 		base_path: Some(base_path),
 		informant_output_format: Default::default(),
@@ -65,6 +65,10 @@ pub enum Subcommand {
 	#[command(subcommand)]
 	Key(sc_cli::KeySubcommand),
 
+	/// Configuration file utilities.
+	#[command(subcommand)]
+	Config(sc_cli::ConfigSubcommand),
+
 	/// Verify a signature for a message, provided on STDIN, with a given (public or secret) key.
 	Verify(sc_cli::VerifyCmd),
 
@@ -97,4 +101,8 @@ pub enum Subcommand {
 
 	/// Db meta columns information.
 	ChainInfo(sc_cli::ChainInfoCmd),
+
+	/// Database integrity check and repair.
+	#[command(subcommand)]
+	Db(sc_cli::DbSubcommand),
 }
@@ -353,6 +353,27 @@ pub fn new_full_base(
 		Vec::default(),
 	));
 
+	let contract_code_request_protocol_config = {
+		let (handler, protocol_config) =
+			sc_network_light::contract_code_requests::handler::ContractCodeRequestHandler::new(
+				&config.protocol_id(),
+				config.chain_spec.fork_id(),
+				client.clone(),
+				"Contracts",
+				Default::default(),
+			);
+		task_manager.spawn_handle().spawn(
+			"contract-code-request-handler",
+			Some("networking"),
+			handler.run(),
+		);
+		protocol_config
+	};
+	config
+		.network
+		.request_response_protocols
+		.push(contract_code_request_protocol_config);
+
 	let (network, system_rpc_tx, tx_handler_controller, network_starter) =
 		sc_service::build_network(sc_service::BuildNetworkParams {
 			config: &config,
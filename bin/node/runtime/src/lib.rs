@@ -383,8 +383,15 @@ parameter_types! {
 	//       Attempting to do so will brick block production.
 	pub const EpochDuration: u64 = EPOCH_DURATION_IN_SLOTS;
 	pub const ExpectedBlockTime: Moment = MILLISECS_PER_BLOCK;
-	pub const ReportLongevity: u64 =
-		BondingDuration::get() as u64 * SessionsPerEra::get() as u64 * EpochDuration::get();
+}
+
+/// Equivocation reports stay valid for as long as the offender's funds remain bonded, which
+/// follows the bonding duration currently set in staking.
+pub struct ReportLongevity;
+impl Get<u64> for ReportLongevity {
+	fn get() -> u64 {
+		Staking::bonding_duration() as u64 * SessionsPerEra::get() as u64 * EpochDuration::get()
+	}
 }
 
 impl pallet_babe::Config for Runtime {
@@ -517,12 +524,26 @@ impl pallet_session::Config for Runtime {
 	type SessionManager = pallet_session::historical::NoteHistoricalRoot<Self, Staking>;
 	type SessionHandler = <SessionKeys as OpaqueKeys>::KeyTypeIdProviders;
 	type Keys = SessionKeys;
+	// Keys of accounts that have not validated for four weeks are purged.
+	type KeyPurgeDelay = ConstU32<{ 28 * DAYS / EPOCH_DURATION_IN_BLOCKS }>;
+	type MaxPurgesPerSession = ConstU32<64>;
+	type PurgeKeysOrigin = EnsureRootOrHalfCouncil;
 	type WeightInfo = pallet_session::weights::SubstrateWeight<Runtime>;
 }
 
+/// Resolves a validator to the controller account that registered its session keys.
+pub struct ControllerOf;
+impl Convert<AccountId, Option<AccountId>> for ControllerOf {
+	fn convert(stash: AccountId) -> Option<AccountId> {
+		Staking::bonded(stash)
+	}
+}
+
 impl pallet_session::historical::Config for Runtime {
 	type FullIdentification = pallet_staking::Exposure<AccountId, Balance>;
 	type FullIdentificationOf = pallet_staking::ExposureOf<Runtime>;
+	type MaxStoredSessions = MaxStoredSessions;
+	type MaxKeyOwnershipProofSize = ConstU32<{ 64 * 1024 }>;
 }
 
 pallet_staking_reward_curve::build! {
@@ -539,9 +560,14 @@ pallet_staking_reward_curve::build! {
 parameter_types! {
 	pub const SessionsPerEra: sp_staking::SessionIndex = 6;
 	pub const BondingDuration: sp_staking::EraIndex = 24 * 28;
+	// Staking prunes historical sessions beyond the bonding duration. This is only a backstop and
+	// must cover the bonding duration set by governance as well, which is at most `HistoryDepth`.
+	pub MaxStoredSessions: sp_staking::SessionIndex =
+		(BondingDuration::get().max(HistoryDepth::get()) + 1) * SessionsPerEra::get();
 	pub const SlashDeferDuration: sp_staking::EraIndex = 24 * 7; // 1/4 the bonding duration.
 	pub const RewardCurve: &'static PiecewiseLinear<'static> = &REWARD_CURVE;
 	pub const MaxNominatorRewardedPerValidator: u32 = 256;
+	pub const MaxExposurePageSize: u32 = 256;
 	pub const OffendingValidatorsThreshold: Perbill = Perbill::from_percent(17);
 	pub OffchainRepeat: BlockNumber = 5;
 	pub HistoryDepth: u32 = 84;
@@ -573,8 +599,10 @@ impl pallet_staking::Config for Runtime {
 	>;
 	type SessionInterface = Self;
 	type EraPayout = pallet_staking::ConvertCurve<RewardCurve>;
+	type DustHandler = pallet_staking::DustToUnbalanced<Self, Treasury>; // send dust to the treasury.
 	type NextNewSession = Session;
 	type MaxNominatorRewardedPerValidator = MaxNominatorRewardedPerValidator;
+	type MaxExposurePageSize = MaxExposurePageSize;
 	type OffendingValidatorsThreshold = OffendingValidatorsThreshold;
 	type ElectionProvider = ElectionProviderMultiPhase;
 	type GenesisElectionProvider = onchain::OnChainExecution<OnChainSeqPhragmen>;
@@ -635,7 +663,9 @@ frame_election_provider_support::generate_solution_type!(
 
 parameter_types! {
 	pub MaxNominations: u32 = <NposSolution16 as frame_election_provider_support::NposSolution>::LIMIT as u32;
-	pub MaxElectingVoters: u32 = 40_000;
+	// The snapshot of this many voters and targets must fit into a block. This is checked by the
+	// `integrity_test` of the election provider.
+	pub MaxElectingVoters: u32 = 10_000;
 	pub MaxElectableTargets: u16 = 10_000;
 	// OnChain values are lower.
 	pub MaxOnChainElectingVoters: u32 = 5000;
@@ -914,6 +944,7 @@ impl pallet_ranked_collective::Config for Runtime {
 impl pallet_remark::Config for Runtime {
 	type WeightInfo = pallet_remark::weights::SubstrateWeight<Self>;
 	type RuntimeEvent = RuntimeEvent;
+	type WeightPerByte = ();
 }
 
 impl pallet_root_testing::Config for Runtime {}
@@ -1189,11 +1220,14 @@ parameter_types! {
 		.max_total
 		.unwrap_or(RuntimeBlockWeights::get().max_block);
 	pub Schedule: pallet_contracts::Schedule<Runtime> = Default::default();
+	pub const ContractsDebugging: pallet_contracts::DebugPolicy =
+		pallet_contracts::DebugPolicy::Disabled;
 }
 
 impl pallet_contracts::Config for Runtime {
 	type Time = Timestamp;
 	type Randomness = RandomnessCollectiveFlip;
+	type RandomnessEnabled = ConstBool<true>;
 	type Currency = Balances;
 	type RuntimeEvent = RuntimeEvent;
 	type RuntimeCall = RuntimeCall;
@@ -1206,18 +1240,45 @@ impl pallet_contracts::Config for Runtime {
 	type CallFilter = Nothing;
 	type DepositPerItem = DepositPerItem;
 	type DepositPerByte = DepositPerByte;
+	type DepositPerEventByte = ConstU128<0>;
 	type CallStack = [pallet_contracts::Frame<Self>; 5];
 	type WeightPrice = pallet_transaction_payment::Pallet<Self>;
 	type WeightInfo = pallet_contracts::weights::SubstrateWeight<Self>;
 	type ChainExtension = ();
+	type Builtins = ();
+	type Xcm = ();
+	type Assets = ();
 	type DeletionQueueDepth = DeletionQueueDepth;
 	type DeletionWeightLimit = DeletionWeightLimit;
 	type Schedule = Schedule;
 	type AddressGenerator = pallet_contracts::DefaultAddressGenerator;
 	type MaxCodeLen = ConstU32<{ 123 * 1024 }>;
+	type MaxCodeMetadataLen = ConstU32<128>;
 	type MaxStorageKeyLen = ConstU32<128>;
 	type UnsafeUnstableInterface = ConstBool<false>;
 	type MaxDebugBufferLen = ConstU32<{ 2 * 1024 * 1024 }>;
+	type Debugging = ContractsDebugging;
+	type MaxCallFilterLen = ConstU32<32>;
+	type MaxBatchedCalls = ConstU32<16>;
+	type MaxStoragePerContract = ();
+	type CallRuntimeOrigin = pallet_contracts::DispatchAsSigned;
+	type MaxDelegateDependencies = ConstU32<32>;
+	type CollectCodeStats = ConstBool<false>;
+	type CompressCode = ConstBool<false>;
+	type RemoveUnusedCode = ConstBool<false>;
+	type UploadOrigin = EnsureSigned<AccountId>;
+	type InstantiateOrigin = EnsureSigned<AccountId>;
+	type ScheduleOrigin = EnsureRoot<AccountId>;
+	type Migrations = (
+		pallet_contracts::migration::v11::Migration<Runtime>,
+		pallet_contracts::migration::v12::Migration<Runtime>,
+		pallet_contracts::migration::v13::Migration<Runtime>,
+		pallet_contracts::migration::v14::Migration<Runtime>,
+		pallet_contracts::migration::v15::Migration<Runtime>,
+		pallet_contracts::migration::v16::Migration<Runtime>,
+		pallet_contracts::migration::v17::Migration<Runtime>,
+		pallet_contracts::migration::v18::Migration<Runtime>,
+	);
 }
 
 impl pallet_sudo::Config for Runtime {
@@ -1795,6 +1856,8 @@ pub type UncheckedExtrinsic =
 pub type SignedPayload = generic::SignedPayload<RuntimeCall, SignedExtra>;
 /// Extrinsic type that has already been checked.
 pub type CheckedExtrinsic = generic::CheckedExtrinsic<AccountId, RuntimeCall, SignedExtra>;
+/// The event record type of the runtime.
+pub type EventRecord = frame_system::EventRecord<RuntimeEvent, Hash>;
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
 	Runtime,
@@ -1811,6 +1874,8 @@ type Migrations = (
 	pallet_nomination_pools::migration::v2::MigrateToV2<Runtime>,
 	pallet_alliance::migration::Migration<Runtime>,
 	pallet_contracts::Migration<Runtime>,
+	pallet_staking::migrations::v14::MigrateToV14<Runtime>,
+	pallet_session::migrations::key_registrar::MigrateToV1<Runtime, ControllerOf>,
 );
 
 /// MMR helper types.
@@ -1898,6 +1963,12 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl frame_support::runtime_api::PalletIntrospectionApi<Block> for Runtime {
+		fn pallets() -> Vec<frame_support::traits::PalletIntrospection> {
+			Runtime::pallets_introspection()
+		}
+	}
+
 	impl sp_block_builder::BlockBuilder<Block> for Runtime {
 		fn apply_extrinsic(extrinsic: <Block as BlockT>::Extrinsic) -> ApplyExtrinsicResult {
 			Executive::apply_extrinsic(extrinsic)
@@ -1974,6 +2045,86 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_staking_runtime_api::StakingApi<Block, AccountId, Balance> for Runtime {
+		fn simulate_slash(
+			validator: AccountId,
+			fraction: Perbill,
+			era: sp_staking::EraIndex,
+		) -> Option<pallet_staking_runtime_api::SlashSimulation<AccountId, Balance>> {
+			Staking::simulate_slash(validator, fraction, era)
+		}
+
+		fn nominations(stash: AccountId) -> Option<Vec<AccountId>> {
+			Staking::nominators(stash).map(|nominations| nominations.targets.into_inner())
+		}
+
+		fn eras_stakers(
+			era: sp_staking::EraIndex,
+			validator: AccountId,
+		) -> Option<pallet_staking_runtime_api::Exposure<AccountId, Balance>> {
+			pallet_staking::EraInfo::<Runtime>::get_full_exposure(era, &validator)
+		}
+
+		fn active_era() -> Option<pallet_staking_runtime_api::ActiveEraInfo> {
+			Staking::active_era()
+		}
+
+		fn pending_payout_pages(
+			era: sp_staking::EraIndex,
+			validator: AccountId,
+		) -> Vec<pallet_staking_runtime_api::PageIndex> {
+			Staking::pending_payout_pages(era, &validator)
+		}
+	}
+
+	impl pallet_utility_runtime_api::UtilityApi<Block, AccountId> for Runtime {
+		fn derivative_account_id(who: AccountId, index: u16) -> AccountId {
+			Utility::derivative_account_id(who, index)
+		}
+
+		fn derivative_account_ids(who: AccountId, start: u16, count: u16) -> Vec<(u16, AccountId)> {
+			Utility::derivative_account_ids(who, start, count)
+		}
+	}
+
+	impl pallet_election_provider_multi_phase_runtime_api::ElectionProviderMultiPhaseApi<
+		Block,
+		AccountId,
+		frame_election_provider_support::VoterOf<Staking>,
+	> for Runtime {
+		fn snapshot_size() -> Option<(u32, u32)> {
+			ElectionProviderMultiPhase::snapshot_metadata().map(|size| (size.voters, size.targets))
+		}
+
+		fn snapshot_voters(
+			start: u32,
+			count: u32,
+		) -> Vec<frame_election_provider_support::VoterOf<Staking>> {
+			ElectionProviderMultiPhase::snapshot_voters_paged(start, count)
+		}
+
+		fn snapshot_targets(start: u32, count: u32) -> Vec<AccountId> {
+			ElectionProviderMultiPhase::snapshot_targets_paged(start, count)
+		}
+
+		fn desired_targets() -> Option<u32> {
+			ElectionProviderMultiPhase::desired_targets()
+		}
+
+		fn minimum_untrusted_score() -> Option<sp_npos_elections::ElectionScore> {
+			ElectionProviderMultiPhase::minimum_untrusted_score()
+		}
+	}
+
+	impl pallet_offences_runtime_api::OffencesApi<Block, AccountId> for Runtime {
+		fn offences_of(validator: AccountId) -> Vec<sp_staking::offence::OffenceRecord<AccountId>> {
+			// Offences older than the bonding duration can't be slashed anymore.
+			let window = Staking::bonding_duration() * SessionsPerEra::get();
+			let since = Session::current_index().saturating_sub(window);
+			Offences::offences_of(|(who, _)| who == &validator, since)
+		}
+	}
+
 	impl sp_consensus_babe::BabeApi<Block> for Runtime {
 		fn configuration() -> sp_consensus_babe::BabeConfiguration {
 			let epoch_config = Babe::epoch_config().unwrap_or(BABE_GENESIS_EPOCH_CONFIG);
@@ -2035,7 +2186,8 @@ impl_runtime_apis! {
 		}
 	}
 
-	impl pallet_contracts::ContractsApi<Block, AccountId, Balance, BlockNumber, Hash> for Runtime
+	impl pallet_contracts::ContractsApi<Block, AccountId, Balance, BlockNumber, Hash, EventRecord>
+		for Runtime
 	{
 		fn call(
 			origin: AccountId,
@@ -2044,16 +2196,17 @@ impl_runtime_apis! {
 			gas_limit: Option<Weight>,
 			storage_deposit_limit: Option<Balance>,
 			input_data: Vec<u8>,
-		) -> pallet_contracts_primitives::ContractExecResult<Balance> {
+		) -> pallet_contracts_primitives::ContractExecResult<AccountId, Balance, EventRecord> {
 			let gas_limit = gas_limit.unwrap_or(RuntimeBlockWeights::get().max_block);
 			Contracts::bare_call(
-				origin,
+				pallet_contracts::CallOrigin::from_account_id(origin),
 				dest,
 				value,
 				gas_limit,
 				storage_deposit_limit,
 				input_data,
 				true,
+				pallet_contracts::CollectEvents::UnsafeCollect,
 				pallet_contracts::Determinism::Deterministic,
 			)
 		}
@@ -2066,7 +2219,7 @@ impl_runtime_apis! {
 			code: pallet_contracts_primitives::Code<Hash>,
 			data: Vec<u8>,
 			salt: Vec<u8>,
-		) -> pallet_contracts_primitives::ContractInstantiateResult<AccountId, Balance>
+		) -> pallet_contracts_primitives::ContractInstantiateResult<AccountId, Balance, EventRecord>
 		{
 			let gas_limit = gas_limit.unwrap_or(RuntimeBlockWeights::get().max_block);
 			Contracts::bare_instantiate(
@@ -2077,7 +2230,8 @@ impl_runtime_apis! {
 				code,
 				data,
 				salt,
-				true
+				true,
+				pallet_contracts::CollectEvents::UnsafeCollect,
 			)
 		}
 
@@ -2105,6 +2259,45 @@ impl_runtime_apis! {
 				key
 			)
 		}
+
+		fn get_storage_keys_paged(
+			address: AccountId,
+			prefix: Vec<u8>,
+			start_key: Option<Vec<u8>>,
+			count: u32,
+		) -> pallet_contracts_primitives::GetStorageKeysResult {
+			Contracts::get_storage_keys_paged(address, prefix, start_key, count)
+		}
+
+		fn code_stats(code_hash: Hash) -> Option<pallet_contracts::CodeStats> {
+			Contracts::code_stats(code_hash)
+		}
+
+		fn code_metadata(code_hash: Hash) -> Option<Vec<u8>> {
+			Contracts::code_metadata(code_hash)
+		}
+
+		fn code_hash(account: AccountId) -> Option<Hash> {
+			Contracts::code_hash(&account)
+		}
+
+		fn pristine_code(code_hash: Hash) -> Option<Vec<u8>> {
+			Contracts::pristine_code(code_hash)
+		}
+
+		fn environment() -> pallet_contracts_primitives::ContractsEnvironment<Balance> {
+			Contracts::environment()
+		}
+
+		fn host_functions() -> Vec<pallet_contracts_primitives::HostFnInfo> {
+			Contracts::host_functions()
+		}
+
+		fn contract_info(
+			address: AccountId,
+		) -> Option<pallet_contracts_primitives::ContractStorageInfo<Hash, Balance>> {
+			Contracts::contract_info(&address)
+		}
 	}
 
 	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<
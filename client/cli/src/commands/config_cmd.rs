@@ -0,0 +1,82 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Configuration file related CLI utilities
+
+use crate::{config_file, Error, RunCmd};
+use clap::{ArgMatches, Args, Command, FromArgMatches};
+
+/// Configuration file utilities for the cli.
+#[derive(Debug, clap::Subcommand)]
+pub enum ConfigSubcommand {
+	/// Print the effective configuration of the node in the format of a `--config` file.
+	///
+	/// Accepts the same options as running the node, including `--config`.
+	Dump(ConfigDumpCmd),
+}
+
+impl ConfigSubcommand {
+	/// run the config subcommands
+	pub fn run(&self) -> Result<(), Error> {
+		match self {
+			ConfigSubcommand::Dump(cmd) => cmd.run(),
+		}
+	}
+}
+
+/// The `config dump` command.
+///
+/// It accepts all the options of [`RunCmd`] and keeps their values as they were resolved from
+/// the command line, the configuration file and the defaults.
+#[derive(Debug, Clone)]
+pub struct ConfigDumpCmd {
+	effective: toml::value::Table,
+}
+
+impl ConfigDumpCmd {
+	/// Run the command
+	pub fn run(&self) -> Result<(), Error> {
+		let dump = toml::to_string(&self.effective).map_err(|e| Error::Input(e.to_string()))?;
+		print!("{}", dump);
+		Ok(())
+	}
+}
+
+impl FromArgMatches for ConfigDumpCmd {
+	fn from_arg_matches(matches: &ArgMatches) -> Result<Self, clap::Error> {
+		// Make sure the options are valid for running the node.
+		RunCmd::from_arg_matches(matches)?;
+		let command = RunCmd::augment_args(Command::new("dump"));
+		Ok(Self { effective: config_file::effective_config(&command, matches) })
+	}
+
+	fn update_from_arg_matches(&mut self, matches: &ArgMatches) -> Result<(), clap::Error> {
+		*self = Self::from_arg_matches(matches)?;
+		Ok(())
+	}
+}
+
+impl Args for ConfigDumpCmd {
+	fn augment_args(command: Command) -> Command {
+		RunCmd::augment_args(command)
+	}
+
+	fn augment_args_for_update(command: Command) -> Command {
+		RunCmd::augment_args_for_update(command)
+	}
+}
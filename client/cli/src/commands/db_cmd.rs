@@ -0,0 +1,404 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2018-2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Database integrity related CLI utilities
+
+use crate::{error, CliConfiguration, DatabaseParams, PruningParams, SharedParams};
+use log::{info, warn};
+use sc_client_api::{backend::Backend as BackendT, blockchain::HeaderBackend};
+use sp_runtime::{
+	traits::{Block as BlockT, Header as HeaderT, NumberFor, One, Zero},
+	SaturatedConversion,
+};
+use std::{fmt, sync::Arc};
+
+/// Database integrity utilities for the cli.
+#[derive(Debug, clap::Subcommand)]
+pub enum DbSubcommand {
+	/// Validate the consistency of the database.
+	///
+	/// Walks the canonical chain down from the best block and reports missing canonical
+	/// blocks, broken parent links and missing state.
+	Check(DbCheckCmd),
+
+	/// Roll the chain back to the last consistent block.
+	///
+	/// This is able to bring back a node whose database got partially corrupted without
+	/// requiring a full resync. Finalized blocks are reverted as well if needed. Consensus data
+	/// kept in the auxiliary storage is not rolled back.
+	Repair(DbRepairCmd),
+}
+
+/// The `db check` command.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct DbCheckCmd {
+	/// Number of blocks below the best block to check.
+	///
+	/// The whole chain down to genesis is checked when not given.
+	#[arg(long, value_name = "COUNT")]
+	pub depth: Option<u32>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+/// The `db repair` command.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct DbRepairCmd {
+	/// Number of blocks below the best block to check.
+	///
+	/// The whole chain down to genesis is checked when not given.
+	#[arg(long, value_name = "COUNT")]
+	pub depth: Option<u32>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+/// An inconsistency found in the database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Issue<B: BlockT> {
+	/// There is no canonical block stored for this number.
+	MissingCanonicalHash(NumberFor<B>),
+	/// The canonical block has no header.
+	MissingHeader(NumberFor<B>, B::Hash),
+	/// The header stored for the canonical block has another number.
+	NumberMismatch(NumberFor<B>, B::Hash, NumberFor<B>),
+	/// The canonical block is not the parent of the next canonical block.
+	BrokenParentLink(NumberFor<B>, B::Hash),
+	/// The state of a block that should never be pruned is missing.
+	MissingState(NumberFor<B>, B::Hash),
+}
+
+impl<B: BlockT> Issue<B> {
+	fn number(&self) -> NumberFor<B> {
+		match self {
+			Issue::MissingCanonicalHash(n) |
+			Issue::MissingHeader(n, _) |
+			Issue::NumberMismatch(n, _, _) |
+			Issue::BrokenParentLink(n, _) |
+			Issue::MissingState(n, _) => *n,
+		}
+	}
+}
+
+impl<B: BlockT> fmt::Display for Issue<B> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Issue::MissingCanonicalHash(n) => write!(f, "#{}: no canonical block", n),
+			Issue::MissingHeader(n, h) => write!(f, "#{} ({}): header is missing", n, h),
+			Issue::NumberMismatch(n, h, found) =>
+				write!(f, "#{} ({}): header has number #{}", n, h, found),
+			Issue::BrokenParentLink(n, h) =>
+				write!(f, "#{} ({}): not the parent of the next canonical block", n, h),
+			Issue::MissingState(n, h) => write!(f, "#{} ({}): state is missing", n, h),
+		}
+	}
+}
+
+/// The outcome of checking the database.
+struct Report<B: BlockT> {
+	best_number: NumberFor<B>,
+	finalized_number: NumberFor<B>,
+	/// Lowest block that was checked.
+	checked_from: NumberFor<B>,
+	/// Issues found, ordered from the best block downwards.
+	issues: Vec<Issue<B>>,
+}
+
+impl<B: BlockT> Report<B> {
+	/// The highest block below every issue found, if there is one.
+	///
+	/// `None` means that genesis itself is broken and the database can't be repaired.
+	fn last_consistent(&self) -> Option<NumberFor<B>> {
+		match self.issues.iter().map(Issue::number).min() {
+			Some(n) if n.is_zero() => None,
+			Some(n) => Some(n - One::one()),
+			None => Some(self.best_number),
+		}
+	}
+}
+
+impl<B: BlockT> fmt::Display for Report<B> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		writeln!(
+			f,
+			"Checked blocks #{}..=#{} (finalized: #{})",
+			self.checked_from, self.best_number, self.finalized_number
+		)?;
+		for issue in &self.issues {
+			writeln!(f, "  {}", issue)?;
+		}
+		match self.last_consistent() {
+			Some(n) if self.issues.is_empty() => writeln!(f, "No issues found, best block #{}", n),
+			Some(n) =>
+				writeln!(f, "Found {} issue(s), last consistent block #{}", self.issues.len(), n),
+			None => writeln!(f, "Found {} issue(s), genesis is corrupted", self.issues.len()),
+		}
+	}
+}
+
+/// Walk the canonical chain down from the best block and collect every inconsistency.
+///
+/// State is only required for the finalized block and above, since anything below might
+/// have been pruned.
+fn check<B, BA>(backend: &BA, depth: Option<u32>) -> error::Result<Report<B>>
+where
+	B: BlockT,
+	BA: BackendT<B>,
+{
+	let blockchain = backend.blockchain();
+	let info = blockchain.info();
+	let checked_from = match depth {
+		Some(depth) => info.best_number.saturating_sub(depth.saturated_into()),
+		None => Zero::zero(),
+	};
+
+	let mut issues = Vec::new();
+	// The parent hash of the block above, if it could be read.
+	let mut expected = None;
+	let mut number = info.best_number;
+	loop {
+		let parent = match blockchain.hash(number)? {
+			None => {
+				issues.push(Issue::MissingCanonicalHash(number));
+				None
+			},
+			Some(hash) => {
+				if expected.map_or(false, |expected| expected != hash) {
+					issues.push(Issue::BrokenParentLink(number, hash));
+				}
+				match blockchain.header(hash)? {
+					None => {
+						issues.push(Issue::MissingHeader(number, hash));
+						None
+					},
+					Some(header) => {
+						if *header.number() != number {
+							issues.push(Issue::NumberMismatch(number, hash, *header.number()));
+						}
+						if number >= info.finalized_number && !backend.have_state_at(hash, number) {
+							issues.push(Issue::MissingState(number, hash));
+						}
+						Some(*header.parent_hash())
+					},
+				}
+			},
+		};
+		expected = parent;
+
+		if number <= checked_from {
+			break
+		}
+		number -= One::one();
+	}
+
+	Ok(Report {
+		best_number: info.best_number,
+		finalized_number: info.finalized_number,
+		checked_from,
+		issues,
+	})
+}
+
+/// Roll the chain back to the last consistent block found by [`check`].
+fn repair<B, BA>(backend: &BA, depth: Option<u32>) -> error::Result<()>
+where
+	B: BlockT,
+	BA: BackendT<B>,
+{
+	let report = check(backend, depth)?;
+	for issue in &report.issues {
+		warn!("{}", issue);
+	}
+
+	let target = report
+		.last_consistent()
+		.ok_or("Genesis is corrupted, the database can't be repaired and needs a resync")?;
+	if report.issues.is_empty() {
+		info!("Database is consistent, nothing to repair.");
+		return Ok(())
+	}
+
+	let blocks = report.best_number - target;
+	if target < report.finalized_number {
+		warn!(
+			"Rolling back finalized blocks: finalized #{} -> #{}",
+			report.finalized_number, target
+		);
+	}
+	let (reverted, _) = backend.revert(blocks, true)?;
+	if reverted < blocks {
+		return Err(format!(
+			"Could only roll back {} of {} blocks, the database can't be repaired and needs a resync",
+			reverted, blocks
+		)
+		.into())
+	}
+
+	let info = backend.blockchain().info();
+	info!(
+		"Rolled back {} blocks. Best: #{} ({}), finalized: #{} ({})",
+		reverted, info.best_number, info.best_hash, info.finalized_number, info.finalized_hash
+	);
+	Ok(())
+}
+
+/// Open the database backend of `config` without building a client on top of it.
+fn open_backend<B: BlockT>(
+	config: &sc_service::Configuration,
+) -> error::Result<Arc<sc_client_db::Backend<B>>> {
+	let db_config = sc_client_db::DatabaseSettings {
+		trie_cache_maximum_size: config.trie_cache_maximum_size,
+		state_pruning: config.state_pruning.clone(),
+		source: config.database.clone(),
+		blocks_pruning: config.blocks_pruning,
+	};
+	Ok(sc_service::new_db_backend::<B>(db_config)?)
+}
+
+impl DbCheckCmd {
+	/// Run the `db check` command
+	pub fn run<B>(&self, config: &sc_service::Configuration) -> error::Result<()>
+	where
+		B: BlockT,
+	{
+		let backend = open_backend::<B>(config)?;
+		let report = check(&*backend, self.depth)?;
+		print!("{}", report);
+
+		if report.issues.is_empty() {
+			Ok(())
+		} else {
+			Err("Database is inconsistent, run `db repair` to roll back to a consistent block"
+				.into())
+		}
+	}
+}
+
+impl DbRepairCmd {
+	/// Run the `db repair` command
+	///
+	/// Only the database is opened, since building a client on top of a corrupted database might
+	/// fail.
+	pub fn run<B>(&self, config: &sc_service::Configuration) -> error::Result<()>
+	where
+		B: BlockT,
+	{
+		let backend = open_backend::<B>(config)?;
+		repair(&*backend, self.depth)
+	}
+}
+
+impl CliConfiguration for DbCheckCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
+
+impl CliConfiguration for DbRepairCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::executor::block_on;
+	use sc_block_builder::BlockBuilderProvider;
+	use sp_consensus::BlockOrigin;
+	use substrate_test_runtime_client::{prelude::*, runtime::Block, TestClientBuilder};
+
+	fn report(issues: Vec<Issue<Block>>) -> Report<Block> {
+		Report { best_number: 10, finalized_number: 5, checked_from: 0, issues }
+	}
+
+	#[test]
+	fn last_consistent_is_below_the_lowest_issue() {
+		assert_eq!(report(vec![]).last_consistent(), Some(10));
+		assert_eq!(
+			report(vec![
+				Issue::MissingState(9, Default::default()),
+				Issue::MissingCanonicalHash(7),
+				Issue::BrokenParentLink(8, Default::default()),
+			])
+			.last_consistent(),
+			Some(6)
+		);
+		assert_eq!(
+			report(vec![Issue::MissingHeader(0, Default::default())]).last_consistent(),
+			None
+		);
+	}
+
+	#[test]
+	fn check_finds_no_issues_in_a_consistent_chain() {
+		let (mut client, backend) = TestClientBuilder::new().build_with_backend();
+		for _ in 0..3 {
+			let block = client.new_block(Default::default()).unwrap().build().unwrap().block;
+			block_on(client.import(BlockOrigin::Own, block)).unwrap();
+		}
+
+		let report = check(&*backend, None).unwrap();
+		assert_eq!((report.best_number, report.checked_from), (3, 0));
+		assert!(report.issues.is_empty());
+
+		let report = check(&*backend, Some(1)).unwrap();
+		assert_eq!((report.best_number, report.checked_from), (3, 2));
+		assert!(report.issues.is_empty());
+
+		// Nothing is rolled back if the database is consistent.
+		assert!(repair(&*backend, None).is_ok());
+		assert_eq!(backend.blockchain().info().best_number, 3);
+	}
+}
@@ -32,7 +32,10 @@ use sc_service::{
 	ChainSpec, Role,
 };
 use sc_telemetry::TelemetryEndpoints;
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::{
+	net::{IpAddr, Ipv4Addr, SocketAddr},
+	time::Duration,
+};
 
 /// The `run` command used to run a node.
 #[derive(Debug, Clone, Parser)]
@@ -253,6 +256,13 @@ pub struct RunCmd {
 	#[arg(long, default_value_t = 2)]
 	pub runtime_cache_size: u8,
 
+	/// Maximum time in seconds the node is given to shut down gracefully.
+	///
+	/// On shutdown the RPC servers stop accepting connections and close the open ones before
+	/// the remaining tasks are stopped. Tasks still running after this time are abandoned.
+	#[arg(long, value_name = "SECONDS", default_value_t = 60)]
+	pub shutdown_timeout: u64,
+
 	/// Run a temporary node.
 	///
 	/// A temporary directory will be created to store the configuration and will be deleted
@@ -481,6 +491,10 @@ impl CliConfiguration for RunCmd {
 		Ok(self.runtime_cache_size)
 	}
 
+	fn shutdown_timeout(&self) -> Result<Duration> {
+		Ok(Duration::from_secs(self.shutdown_timeout))
+	}
+
 	fn base_path(&self) -> Result<Option<BasePath>> {
 		Ok(if self.tmp {
 			Some(BasePath::new_temp_dir()?)
@@ -0,0 +1,249 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Reading command line options from a TOML file passed with `--config`.
+//!
+//! Every key of the file is the long name of an option of the command that is executed, e.g.
+//! `base-path = "/var/lib/node"` or `rpc-cors = ["http://localhost"]`. Underscores are accepted
+//! in place of dashes. Flags take a boolean. Options that may be repeated take an array.
+//!
+//! An option given on the command line (or through its environment variable) always takes
+//! precedence over the file which in turn takes precedence over the default of the option.
+
+use clap::{
+	error::{ErrorKind, Result},
+	parser::ValueSource,
+	Arg, ArgAction, ArgMatches, Command,
+};
+use std::{ffi::OsString, path::PathBuf};
+use toml::{value::Table, Value};
+
+/// The id of the argument that points to the configuration file.
+const CONFIG_ARG: &str = "config";
+
+/// Arguments which are never read from or written to a configuration file.
+const IGNORED_ARGS: &[&str] = &[CONFIG_ARG, "help", "version"];
+
+/// Append the options of the configuration file passed to the executed (sub)command to `args`.
+///
+/// `args` are returned unchanged if no configuration file was passed. The options are appended
+/// so that they end up at the command they belong to.
+pub(crate) fn merge_config_file(command: &Command, args: Vec<OsString>) -> Result<Vec<OsString>> {
+	// Errors are reported by the actual parse, which might succeed once the file is merged.
+	let matches = match command.clone().ignore_errors(true).try_get_matches_from(args.clone()) {
+		Ok(matches) => matches,
+		Err(_) => return Ok(args),
+	};
+	let (command, matches) = leaf_command(command, &matches);
+	let path = match matches.try_get_one::<PathBuf>(CONFIG_ARG) {
+		Ok(Some(path)) => path,
+		_ => return Ok(args),
+	};
+
+	let error = |kind, msg: String| {
+		command
+			.clone()
+			.error(kind, format!("Configuration file {}: {}", path.display(), msg))
+	};
+	let content = std::fs::read_to_string(path).map_err(|e| error(ErrorKind::Io, e.to_string()))?;
+	let table =
+		toml::from_str::<Table>(&content).map_err(|e| error(ErrorKind::Format, e.to_string()))?;
+	let file_args = file_args(command, matches, &table).map_err(|(kind, msg)| error(kind, msg))?;
+
+	// Everything after `--` is passed on verbatim.
+	let end = args.iter().position(|arg| arg == "--").unwrap_or(args.len());
+	let mut args = args;
+	args.splice(end..end, file_args);
+	Ok(args)
+}
+
+/// Returns the effective configuration of `command` in the format of a configuration file.
+pub(crate) fn effective_config(command: &Command, matches: &ArgMatches) -> Table {
+	let mut table = Table::new();
+	for arg in command.get_arguments() {
+		let id = arg.get_id().as_str();
+		let long = match arg.get_long() {
+			Some(long) if !IGNORED_ARGS.contains(&id) => long,
+			_ => continue,
+		};
+		if is_flag(arg) {
+			table.insert(long.into(), Value::Boolean(matches.get_flag(id)));
+			continue
+		}
+		let mut values = match matches.get_raw(id) {
+			Ok(Some(values)) => values
+				.map(|v| Value::String(v.to_string_lossy().into_owned()))
+				.collect::<Vec<_>>(),
+			_ => continue,
+		};
+		let value = if is_multiple(arg) {
+			Value::Array(values)
+		} else {
+			match values.pop() {
+				Some(value) => value,
+				None => continue,
+			}
+		};
+		table.insert(long.into(), value);
+	}
+	table
+}
+
+/// Follow the subcommands of `matches` down to the command that is executed.
+fn leaf_command<'a>(
+	mut command: &'a Command,
+	mut matches: &'a ArgMatches,
+) -> (&'a Command, &'a ArgMatches) {
+	while let Some((name, sub_matches)) = matches.subcommand() {
+		match command.find_subcommand(name) {
+			Some(sub_command) => {
+				command = sub_command;
+				matches = sub_matches;
+			},
+			None => break,
+		}
+	}
+	(command, matches)
+}
+
+/// Convert `table` into arguments of `command` skipping those already given on the command line.
+fn file_args(
+	command: &Command,
+	matches: &ArgMatches,
+	table: &Table,
+) -> std::result::Result<Vec<OsString>, (ErrorKind, String)> {
+	let mut args = Vec::new();
+	for (key, value) in table {
+		let long = key.replace('_', "-");
+		let arg = command
+			.get_arguments()
+			.find(|arg| {
+				arg.get_long() == Some(long.as_str()) &&
+					!IGNORED_ARGS.contains(&arg.get_id().as_str())
+			})
+			.ok_or_else(|| (ErrorKind::UnknownArgument, format!("unknown option `{}`", key)))?;
+
+		if matches!(
+			matches.value_source(arg.get_id().as_str()),
+			Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+		) {
+			continue
+		}
+
+		let scalar = |value: &Value| match value {
+			Value::String(s) => Ok(s.clone()),
+			Value::Integer(i) => Ok(i.to_string()),
+			Value::Float(f) => Ok(f.to_string()),
+			Value::Boolean(b) => Ok(b.to_string()),
+			_ => Err((ErrorKind::InvalidValue, format!("unsupported value for `{}`", key))),
+		};
+		match value {
+			Value::Boolean(enabled) if is_flag(arg) =>
+				if *enabled {
+					args.push(format!("--{}", long).into());
+				},
+			Value::Array(values) if is_multiple(arg) =>
+				for value in values {
+					args.push(format!("--{}={}", long, scalar(value)?).into());
+				},
+			value => args.push(format!("--{}={}", long, scalar(value)?).into()),
+		}
+	}
+	Ok(args)
+}
+
+fn is_flag(arg: &Arg) -> bool {
+	matches!(arg.get_action(), ArgAction::SetTrue | ArgAction::SetFalse)
+}
+
+fn is_multiple(arg: &Arg) -> bool {
+	matches!(arg.get_action(), ArgAction::Append) ||
+		arg.get_num_args().map_or(false, |range| range.max_values() > 1)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use clap::{CommandFactory, Parser};
+	use std::io::Write;
+
+	#[derive(Parser, Debug, PartialEq)]
+	struct Cli {
+		#[arg(long)]
+		config: Option<PathBuf>,
+		#[arg(long, default_value_t = 30333)]
+		port: u16,
+		#[arg(long)]
+		name: Option<String>,
+		#[arg(long)]
+		validator: bool,
+		#[arg(long)]
+		bootnodes: Vec<String>,
+	}
+
+	fn parse(file: &str, args: &[&str]) -> Result<Cli> {
+		let mut config = tempfile::NamedTempFile::new().unwrap();
+		config.write_all(file.as_bytes()).unwrap();
+		let mut full_args = vec!["node".into(), "--config".into(), config.path().into()];
+		full_args.extend(args.iter().map(OsString::from));
+
+		let command = Cli::command();
+		let full_args = merge_config_file(&command, full_args)?;
+		Cli::try_parse_from(full_args)
+	}
+
+	#[test]
+	fn file_overrides_defaults() {
+		let cli = parse(
+			"port = 1234\nname = \"alice\"\nvalidator = true\nbootnodes = [\"a\", \"b\"]",
+			&[],
+		)
+		.unwrap();
+		assert_eq!(cli.port, 1234);
+		assert_eq!(cli.name, Some("alice".into()));
+		assert!(cli.validator);
+		assert_eq!(cli.bootnodes, vec!["a".to_string(), "b".to_string()]);
+	}
+
+	#[test]
+	fn command_line_overrides_file() {
+		let cli =
+			parse("port = 1234\nbootnodes = [\"a\"]", &["--port", "4321", "--bootnodes", "c"])
+				.unwrap();
+		assert_eq!(cli.port, 4321);
+		assert_eq!(cli.bootnodes, vec!["c".to_string()]);
+	}
+
+	#[test]
+	fn unknown_keys_are_rejected() {
+		let err = parse("no-such-option = 1", &[]).unwrap_err();
+		assert_eq!(err.kind(), ErrorKind::UnknownArgument);
+	}
+
+	#[test]
+	fn effective_config_round_trips() {
+		let cli = parse("name = \"alice\"\nbootnodes = [\"a\"]", &["--validator"]).unwrap();
+		let matches = Cli::command()
+			.try_get_matches_from(["node", "--name=alice", "--bootnodes=a", "--validator"])
+			.unwrap();
+		let dumped = toml::to_string(&effective_config(&Cli::command(), &matches)).unwrap();
+
+		let reparsed = parse(&dumped, &[]).unwrap();
+		assert_eq!(Cli { config: None, ..reparsed }, Cli { config: None, ..cli });
+	}
+}
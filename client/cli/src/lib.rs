@@ -28,6 +28,7 @@ use sc_service::Configuration;
 pub mod arg_enums;
 mod commands;
 mod config;
+mod config_file;
 mod error;
 mod params;
 mod runner;
@@ -136,7 +137,9 @@ pub trait SubstrateCli: Sized {
 			.args_conflicts_with_subcommands(true)
 			.subcommand_negates_reqs(true);
 
-		let matches = app.try_get_matches_from(iter).unwrap_or_else(|e| e.exit());
+		let args = iter.into_iter().map(Into::into).collect();
+		let args = config_file::merge_config_file(&app, args).unwrap_or_else(|e| e.exit());
+		let matches = app.try_get_matches_from(args).unwrap_or_else(|e| e.exit());
 
 		<Self as FromArgMatches>::from_arg_matches(&matches).unwrap_or_else(|e| e.exit())
 	}
@@ -171,7 +174,9 @@ pub trait SubstrateCli: Sized {
 		let about = Self::description();
 		let app = app.name(name).author(author).about(about).version(full_version);
 
-		let matches = app.try_get_matches_from(iter)?;
+		let args = iter.into_iter().map(Into::into).collect();
+		let args = config_file::merge_config_file(&app, args)?;
+		let matches = app.try_get_matches_from(args)?;
 
 		<Self as FromArgMatches>::from_arg_matches(&matches)
 	}
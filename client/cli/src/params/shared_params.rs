@@ -42,6 +42,14 @@ pub struct SharedParams {
 	#[arg(long, short = 'd', value_name = "PATH")]
 	pub base_path: Option<PathBuf>,
 
+	/// Read further options of this command from a TOML file.
+	///
+	/// Every key is the long name of an option, e.g. `base-path = "/var/lib/node"`. Flags take a
+	/// boolean and options that can be repeated take an array. Options passed on the command
+	/// line take precedence over the file. Unknown keys are rejected.
+	#[arg(long, value_name = "PATH")]
+	pub config: Option<PathBuf>,
+
 	/// Sets a custom logging filter. Syntax is `<target>=<level>`, e.g. -lsync=debug.
 	///
 	/// Log levels (least to most verbose) are error, warn, info, debug, and trace.
@@ -22,7 +22,7 @@ use futures::{future, future::FutureExt, pin_mut, select, Future};
 use log::info;
 use sc_service::{Configuration, Error as ServiceError, TaskManager};
 use sc_utils::metrics::{TOKIO_THREADS_ALIVE, TOKIO_THREADS_TOTAL};
-use std::{marker::PhantomData, time::Duration};
+use std::{marker::PhantomData, time::Instant};
 
 #[cfg(target_family = "unix")]
 async fn main<F, E>(func: F) -> std::result::Result<(), E>
@@ -136,6 +136,9 @@ impl<C: SubstrateCli> Runner<C> {
 
 	/// A helper function that runs a node with tokio and stops if the process receives the signal
 	/// `SIGTERM` or `SIGINT`.
+	///
+	/// On exit the shutdown hooks of the node are run before all remaining tasks are stopped. The
+	/// whole shutdown takes at most [`Configuration::shutdown_timeout`].
 	pub fn run_node_until_exit<F, E>(
 		self,
 		initialize: impl FnOnce(Configuration) -> F,
@@ -146,17 +149,27 @@ impl<C: SubstrateCli> Runner<C> {
 	{
 		self.print_node_infos();
 
+		let shutdown_timeout = self.config.shutdown_timeout;
 		let mut task_manager = self.tokio_runtime.block_on(initialize(self.config))?;
 		let res = self.tokio_runtime.block_on(main(task_manager.future().fuse()));
+
+		let shutdown_started = Instant::now();
+		if !self.tokio_runtime.block_on(task_manager.graceful_shutdown(shutdown_timeout)) {
+			log::warn!(
+				"Graceful shutdown did not finish within {} seconds.",
+				shutdown_timeout.as_secs(),
+			);
+		}
+
 		// We need to drop the task manager here to inform all tasks that they should shut down.
 		//
 		// This is important to be done before we instruct the tokio runtime to shutdown. Otherwise
-		// the tokio runtime will wait the full 60 seconds for all tasks to stop.
+		// the tokio runtime will wait the full shutdown timeout for all tasks to stop.
 		let task_registry = task_manager.into_task_registry();
 
-		// Give all futures 60 seconds to shutdown, before tokio "leaks" them.
-		let shutdown_timeout = Duration::from_secs(60);
-		self.tokio_runtime.shutdown_timeout(shutdown_timeout);
+		// Give all futures the rest of the shutdown time to stop, before tokio "leaks" them.
+		self.tokio_runtime
+			.shutdown_timeout(shutdown_timeout.saturating_sub(shutdown_started.elapsed()));
 
 		let running_tasks = task_registry.running_tasks();
 
@@ -248,6 +261,7 @@ mod tests {
 	use std::{
 		path::PathBuf,
 		sync::atomic::{AtomicU64, Ordering},
+		time::Duration,
 	};
 
 	use sc_network::config::NetworkConfiguration;
@@ -367,6 +381,7 @@ mod tests {
 				base_path: None,
 				informant_output_format: Default::default(),
 				runtime_cache_size: 2,
+				shutdown_timeout: Duration::from_secs(60),
 			},
 			runtime,
 		)
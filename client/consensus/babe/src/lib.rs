@@ -141,6 +141,9 @@ pub use sp_consensus_babe::{
 
 pub use aux_schema::load_block_weight as block_weight;
 
+use metrics::Metrics;
+
+mod metrics;
 mod migration;
 mod verification;
 
@@ -977,6 +980,7 @@ pub struct BabeVerifier<Block: BlockT, Client, SelectChain, CIDP> {
 	config: BabeConfiguration,
 	epoch_changes: SharedEpochChanges<Block, Epoch>,
 	telemetry: Option<TelemetryHandle>,
+	metrics: Option<Metrics>,
 }
 
 impl<Block, Client, SelectChain, CIDP> BabeVerifier<Block, Client, SelectChain, CIDP>
@@ -1140,10 +1144,10 @@ where
 			// Verification for imported blocks is skipped in two cases:
 			// 1. When importing blocks below the last finalized block during network initial
 			//    synchronization.
-			// 2. When importing whole state we don't calculate epoch descriptor, but rather
-			//    read it from the state after import. We also skip all verifications
-			//    because there's no parent state and we trust the sync module to verify
-			//    that the state is correct and finalized.
+			// 2. When importing whole state we don't calculate epoch descriptor, but rather read it
+			//    from the state after import. We also skip all verifications because there's no
+			//    parent state and we trust the sync module to verify that the state is correct and
+			//    finalized.
 			return Ok((block, Default::default()))
 		}
 
@@ -1167,7 +1171,7 @@ where
 			.map_err(Error::<Block>::FetchParentHeader)?;
 
 		let pre_digest = find_pre_digest::<Block>(&block.header)?;
-		let (check_header, epoch_descriptor) = {
+		let (check_header, epoch_descriptor, epoch_index) = {
 			let epoch_changes = self.epoch_changes.shared_data();
 			let epoch_descriptor = epoch_changes
 				.epoch_descriptor_for_child_of(
@@ -1182,6 +1186,8 @@ where
 				.viable_epoch(&epoch_descriptor, |slot| Epoch::genesis(&self.config, slot))
 				.ok_or(Error::<Block>::FetchEpoch(parent_hash))?;
 
+			let epoch_index = viable_epoch.as_ref().epoch_index;
+
 			// We add one to the current slot to allow for some small drift.
 			// FIXME #1019 in the future, alter this queue to allow deferring of headers
 			let v_params = verification::VerificationParams {
@@ -1191,7 +1197,7 @@ where
 				epoch: viable_epoch.as_ref(),
 			};
 
-			(verification::check_header::<Block>(v_params)?, epoch_descriptor)
+			(verification::check_header::<Block>(v_params)?, epoch_descriptor, epoch_index)
 		};
 
 		match check_header {
@@ -1202,6 +1208,10 @@ where
 					.expect("check_header always returns a pre-digest digest item; qed");
 				let slot = babe_pre_digest.slot();
 
+				if let Some(metrics) = &self.metrics {
+					metrics.report_verified(&babe_pre_digest, epoch_index);
+				}
+
 				// the header is valid but let's check if there was something else already
 				// proposed at the same slot by the given author. if there was, we will
 				// report the equivocation to the runtime.
@@ -1824,6 +1834,13 @@ where
 		epoch_changes: babe_link.epoch_changes,
 		telemetry,
 		client,
+		metrics: registry.and_then(|registry| {
+			Metrics::register(registry)
+				.map_err(
+					|err| warn!(target: LOG_TARGET, "Failed to register BABE metrics: {}", err),
+				)
+				.ok()
+		}),
 	};
 
 	Ok(BasicQueue::new(verifier, Box::new(block_import), justification_import, spawner, registry))
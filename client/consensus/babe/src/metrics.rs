@@ -0,0 +1,90 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Prometheus metrics for BABE.
+
+use prometheus_endpoint::{
+	register, CounterVec, Gauge, GaugeVec, Opts, PrometheusError, Registry, U64,
+};
+use sp_consensus_babe::digests::PreDigest;
+
+/// Counts the blocks verified by BABE by the kind of their slot assignment.
+///
+/// This allows monitoring how often secondary slots are used to keep the chain live.
+#[derive(Clone)]
+pub(crate) struct Metrics {
+	verified_blocks: CounterVec<U64>,
+	epoch_blocks: GaugeVec<U64>,
+	epoch_index: Gauge<U64>,
+}
+
+impl Metrics {
+	pub(crate) fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			verified_blocks: register(
+				CounterVec::new(
+					Opts::new(
+						"substrate_babe_verified_blocks_total",
+						"Blocks verified by BABE by the kind of their slot assignment",
+					),
+					&["slot_type"],
+				)?,
+				registry,
+			)?,
+			epoch_blocks: register(
+				GaugeVec::new(
+					Opts::new(
+						"substrate_babe_epoch_blocks",
+						"Blocks of the latest epoch verified by BABE by the kind of their slot \
+						assignment",
+					),
+					&["slot_type"],
+				)?,
+				registry,
+			)?,
+			epoch_index: register(
+				Gauge::new(
+					"substrate_babe_epoch_index",
+					"The latest epoch a block was verified in by BABE",
+				)?,
+				registry,
+			)?,
+		})
+	}
+
+	/// Report a verified block authored in `epoch_index`.
+	///
+	/// Blocks of epochs before the latest reported one only count towards the total.
+	pub(crate) fn report_verified(&self, pre_digest: &PreDigest, epoch_index: u64) {
+		let label = match pre_digest {
+			PreDigest::Primary(_) => "primary",
+			PreDigest::SecondaryPlain(_) => "secondary_plain",
+			PreDigest::SecondaryVRF(_) => "secondary_vrf",
+		};
+		self.verified_blocks.with_label_values(&[label]).inc();
+
+		let latest = self.epoch_index.get();
+		if epoch_index > latest {
+			self.epoch_index.set(epoch_index);
+			self.epoch_blocks.reset();
+		} else if epoch_index < latest {
+			return
+		}
+		self.epoch_blocks.with_label_values(&[label]).inc();
+	}
+}
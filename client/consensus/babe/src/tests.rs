@@ -337,6 +337,7 @@ impl TestNetFactory for BabeTestNet {
 				config: data.link.config.clone(),
 				epoch_changes: data.link.epoch_changes.clone(),
 				telemetry: None,
+				metrics: None,
 			},
 			mutator: MUTATOR.with(|m| m.borrow().clone()),
 		}
@@ -698,6 +698,7 @@ pub fn grandpa_peers_set_config(
 		// Notifications reach ~256kiB in size at the time of writing on Kusama and Polkadot.
 		max_notification_size: 1024 * 1024,
 		handshake: None,
+		notifications_queue_size: None,
 		set_config: sc_network_common::config::SetConfig {
 			in_peers: 0,
 			out_peers: 0,
@@ -251,6 +251,12 @@ pub struct NonDefaultSetConfig {
 	pub handshake: Option<NotificationHandshake>,
 	/// Maximum allowed size of single notifications.
 	pub max_notification_size: u64,
+	/// Number of notifications that can be queued for a peer before the connection to the peer
+	/// is closed. If `None`, the default of the networking code is used.
+	///
+	/// A small queue detects slow peers early, at the cost of disconnecting peers that are only
+	/// briefly congested.
+	pub notifications_queue_size: Option<usize>,
 	/// Base configuration.
 	pub set_config: SetConfig,
 }
@@ -263,6 +269,7 @@ impl NonDefaultSetConfig {
 			max_notification_size,
 			fallback_names: Vec::new(),
 			handshake: None,
+			notifications_queue_size: None,
 			set_config: SetConfig {
 				in_peers: 0,
 				out_peers: 0,
@@ -290,6 +297,13 @@ impl NonDefaultSetConfig {
 	pub fn add_fallback_names(&mut self, fallback_names: Vec<protocol::ProtocolName>) {
 		self.fallback_names.extend(fallback_names);
 	}
+
+	/// Limit the number of notifications that can be queued for a peer.
+	///
+	/// See the explanations in [`NonDefaultSetConfig::notifications_queue_size`].
+	pub fn set_notifications_queue_size(&mut self, size: usize) {
+		self.notifications_queue_size = Some(size);
+	}
 }
 
 /// Configuration for the transport layer.
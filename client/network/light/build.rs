@@ -1,4 +1,4 @@
-const PROTOS: &[&str] = &["src/schema/light.v1.proto"];
+const PROTOS: &[&str] = &["src/schema/light.v1.proto", "src/schema/contracts.v1.proto"];
 
 fn main() {
 	prost_build::compile_protos(PROTOS, &["src/schema"]).unwrap();
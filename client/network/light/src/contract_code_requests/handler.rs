@@ -0,0 +1,478 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Helper for incoming contract code requests.
+//!
+//! Handle (i.e. answer) incoming contract code requests from a remote peer received via
+//! `crate::request_responses::RequestResponsesBehaviour` with
+//! [`ContractCodeRequestHandler`](handler::ContractCodeRequestHandler).
+
+use crate::schema::v1::contracts as schema;
+use codec::{self, Decode, Encode};
+use futures::{channel::mpsc, prelude::*};
+use libp2p::PeerId;
+use log::{debug, trace};
+use prost::Message;
+use sc_client_api::{backend::Backend, BlockBackend, ProofProvider, StorageProvider};
+use sc_network_common::{
+	config::ProtocolId,
+	request_responses::{IncomingRequest, OutgoingResponse, ProtocolConfig},
+};
+use sc_peerset::ReputationChange;
+use sp_core::{
+	hexdisplay::HexDisplay,
+	storage::{ChildInfo, StorageKey},
+	twox_128, twox_64,
+};
+use sp_runtime::traits::Block;
+use std::{
+	collections::HashMap,
+	marker::PhantomData,
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+const LOG_TARGET: &str = "contract-code-request-handler";
+
+/// Name of the storage item holding the pristine code of `pallet-contracts`.
+const PRISTINE_CODE: &[u8] = b"PristineCode";
+/// Name of the storage item holding the contract info of `pallet-contracts`.
+const CONTRACT_INFO_OF: &[u8] = b"ContractInfoOf";
+
+/// Maximum number of child trie keys a single request may ask a proof for.
+const MAX_CHILD_KEYS: usize = 64;
+
+/// Maximum size of decompressed code that is served. Matches the maximum response size.
+const CODE_BOMB_LIMIT: usize = 16 * 1024 * 1024;
+
+/// Limits how many requests a single peer may send within a time window.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimit {
+	/// Maximum number of requests served per peer within `window`.
+	pub max_requests: u32,
+	/// Length of the time window.
+	pub window: Duration,
+}
+
+impl Default for RateLimit {
+	fn default() -> Self {
+		Self { max_requests: 32, window: Duration::from_secs(10) }
+	}
+}
+
+/// Fixed window request counter for each peer.
+struct PeerRateLimiter {
+	limit: RateLimit,
+	peers: HashMap<PeerId, (Instant, u32)>,
+}
+
+impl PeerRateLimiter {
+	fn new(limit: RateLimit) -> Self {
+		Self { limit, peers: HashMap::new() }
+	}
+
+	/// Account for a request from `peer` made at `now`.
+	///
+	/// Returns `false` if the peer exceeded its allowance for the current window.
+	fn check(&mut self, peer: &PeerId, now: Instant) -> bool {
+		let window = self.limit.window;
+		// Forget about peers whose window already passed so that the map can't grow unbounded.
+		if self.peers.len() > 1024 {
+			self.peers
+				.retain(|_, (start, _)| now.saturating_duration_since(*start) < window);
+		}
+
+		let (start, count) = self.peers.entry(*peer).or_insert((now, 0));
+		if now.saturating_duration_since(*start) >= window {
+			*start = now;
+			*count = 0;
+		}
+		if *count >= self.limit.max_requests {
+			return false
+		}
+		*count += 1;
+		true
+	}
+}
+
+/// Handler for incoming contract code requests from a remote peer.
+pub struct ContractCodeRequestHandler<B, BE, Client> {
+	request_receiver: mpsc::Receiver<IncomingRequest>,
+	/// Blockchain client.
+	client: Arc<Client>,
+	/// `twox_128` hash of the name `pallet-contracts` is registered under in the runtime.
+	pallet_prefix: [u8; 16],
+	rate_limiter: PeerRateLimiter,
+	_phantom: PhantomData<(B, BE)>,
+}
+
+impl<B, BE, Client> ContractCodeRequestHandler<B, BE, Client>
+where
+	B: Block,
+	BE: Backend<B>,
+	Client: BlockBackend<B> + ProofProvider<B> + StorageProvider<B, BE> + Send + Sync + 'static,
+{
+	/// Create a new [`ContractCodeRequestHandler`].
+	///
+	/// `pallet_name` is the name `pallet-contracts` is registered under in `construct_runtime!`.
+	pub fn new(
+		protocol_id: &ProtocolId,
+		fork_id: Option<&str>,
+		client: Arc<Client>,
+		pallet_name: &str,
+		rate_limit: RateLimit,
+	) -> (Self, ProtocolConfig) {
+		// Match the light client request limit.
+		let (tx, request_receiver) = mpsc::channel(20);
+
+		let mut protocol_config = super::generate_protocol_config(
+			protocol_id,
+			client
+				.block_hash(0u32.into())
+				.ok()
+				.flatten()
+				.expect("Genesis block exists; qed"),
+			fork_id,
+		);
+		protocol_config.inbound_queue = Some(tx);
+
+		(
+			Self {
+				client,
+				request_receiver,
+				pallet_prefix: twox_128(pallet_name.as_bytes()),
+				rate_limiter: PeerRateLimiter::new(rate_limit),
+				_phantom: PhantomData::default(),
+			},
+			protocol_config,
+		)
+	}
+
+	/// Run [`ContractCodeRequestHandler`].
+	pub async fn run(mut self) {
+		while let Some(request) = self.request_receiver.next().await {
+			let IncomingRequest { peer, payload, pending_response } = request;
+
+			match self.handle_request(peer, payload) {
+				Ok(response_data) => {
+					let response = OutgoingResponse {
+						result: Ok(response_data),
+						reputation_changes: Vec::new(),
+						sent_feedback: None,
+					};
+
+					match pending_response.send(response) {
+						Ok(()) => trace!(
+							target: LOG_TARGET,
+							"Handled contract code request from {}.",
+							peer,
+						),
+						Err(_) => debug!(
+							target: LOG_TARGET,
+							"Failed to handle contract code request from {}: {}",
+							peer,
+							HandleRequestError::SendResponse,
+						),
+					};
+				},
+				Err(e) => {
+					debug!(
+						target: LOG_TARGET,
+						"Failed to handle contract code request from {}: {}", peer, e,
+					);
+
+					let reputation_changes = match e {
+						HandleRequestError::BadRequest(_) => {
+							vec![ReputationChange::new(-(1 << 12), "bad request")]
+						},
+						HandleRequestError::RateLimited => {
+							vec![ReputationChange::new(-(1 << 8), "rate limited")]
+						},
+						_ => Vec::new(),
+					};
+
+					let response = OutgoingResponse {
+						result: Err(()),
+						reputation_changes,
+						sent_feedback: None,
+					};
+
+					if pending_response.send(response).is_err() {
+						debug!(
+							target: LOG_TARGET,
+							"Failed to handle contract code request from {}: {}",
+							peer,
+							HandleRequestError::SendResponse,
+						);
+					};
+				},
+			}
+		}
+	}
+
+	fn handle_request(
+		&mut self,
+		peer: PeerId,
+		payload: Vec<u8>,
+	) -> Result<Vec<u8>, HandleRequestError> {
+		if !self.rate_limiter.check(&peer, Instant::now()) {
+			return Err(HandleRequestError::RateLimited)
+		}
+
+		let request = schema::Request::decode(&payload[..])?;
+
+		let response = match &request.request {
+			Some(schema::request::Request::CodeRequest(r)) => self.on_code_request(&peer, r)?,
+			Some(schema::request::Request::ContractStorageRequest(r)) =>
+				self.on_contract_storage_request(&peer, r)?,
+			None =>
+				return Err(HandleRequestError::BadRequest("Remote request without request data.")),
+		};
+
+		let mut data = Vec::new();
+		response.encode(&mut data)?;
+
+		Ok(data)
+	}
+
+	fn on_code_request(
+		&mut self,
+		peer: &PeerId,
+		request: &schema::CodeRequest,
+	) -> Result<schema::Response, HandleRequestError> {
+		trace!(
+			target: LOG_TARGET,
+			"Code request from {} ({} at {:?}).",
+			peer,
+			HexDisplay::from(&request.code_hash),
+			request.block,
+		);
+
+		let block = Decode::decode(&mut request.block.as_ref())?;
+		let key = self.pristine_code_key(&request.code_hash);
+
+		let code = self.client.storage(block, &StorageKey(key.clone())).and_then(|code| {
+			self.client
+				.read_proof(block, &mut std::iter::once(&key[..]))
+				.map(|proof| (code, proof))
+		});
+		let response = match code {
+			Ok((code, proof)) => schema::CodeResponse {
+				// The value is stored as `BoundedVec<u8>`, strip the length prefix. The code might
+				// be stored compressed, but it is always served decompressed.
+				code: code
+					.and_then(|data| <Vec<u8> as Decode>::decode(&mut &data.0[..]).ok())
+					.and_then(|code| {
+						sp_maybe_compressed_blob::decompress(&code, CODE_BOMB_LIMIT)
+							.ok()
+							.map(|code| code.into_owned())
+					}),
+				proof: Some(proof.encode()),
+			},
+			Err(error) => {
+				trace!(
+					target: LOG_TARGET,
+					"code request from {} ({} at {:?}) failed with: {}",
+					peer,
+					HexDisplay::from(&request.code_hash),
+					request.block,
+					error,
+				);
+				schema::CodeResponse { code: None, proof: None }
+			},
+		};
+
+		Ok(schema::Response { response: Some(schema::response::Response::CodeResponse(response)) })
+	}
+
+	fn on_contract_storage_request(
+		&mut self,
+		peer: &PeerId,
+		request: &schema::ContractStorageRequest,
+	) -> Result<schema::Response, HandleRequestError> {
+		if request.keys.len() > MAX_CHILD_KEYS {
+			debug!(target: LOG_TARGET, "Invalid contract storage request sent by {}.", peer);
+			return Err(HandleRequestError::BadRequest("Too many keys in contract storage request."))
+		}
+
+		trace!(
+			target: LOG_TARGET,
+			"Contract storage request from {} ({} with {} keys at {:?}).",
+			peer,
+			HexDisplay::from(&request.address),
+			request.keys.len(),
+			request.block,
+		);
+
+		let block = Decode::decode(&mut request.block.as_ref())?;
+		let key = self.contract_info_key(&request.address);
+
+		let result =
+			self.client.read_proof(block, &mut std::iter::once(&key[..])).and_then(|proof| {
+				let child_proof = match self.client.storage(block, &StorageKey(key.clone()))? {
+					// The trie id is the first field of `ContractInfo`.
+					Some(info) if !request.keys.is_empty() =>
+						match <Vec<u8> as Decode>::decode(&mut &info.0[..]) {
+							Ok(trie_id) => Some(self.client.read_child_proof(
+								block,
+								&ChildInfo::new_default(&trie_id),
+								&mut request.keys.iter().map(AsRef::as_ref),
+							)?),
+							Err(_) => None,
+						},
+					_ => None,
+				};
+				Ok((proof, child_proof))
+			});
+		let response = match result {
+			Ok((proof, child_proof)) => schema::ContractStorageResponse {
+				proof: Some(proof.encode()),
+				child_proof: child_proof.map(|proof| proof.encode()),
+			},
+			Err(error) => {
+				trace!(
+					target: LOG_TARGET,
+					"contract storage request from {} ({} at {:?}) failed with: {}",
+					peer,
+					HexDisplay::from(&request.address),
+					request.block,
+					error,
+				);
+				schema::ContractStorageResponse { proof: None, child_proof: None }
+			},
+		};
+
+		Ok(schema::Response {
+			response: Some(schema::response::Response::ContractStorageResponse(response)),
+		})
+	}
+
+	/// Storage key of `PristineCode` for the given code hash (`Identity` hasher).
+	fn pristine_code_key(&self, code_hash: &[u8]) -> Vec<u8> {
+		let mut key = Vec::with_capacity(32 + code_hash.len());
+		key.extend_from_slice(&self.pallet_prefix);
+		key.extend_from_slice(&twox_128(PRISTINE_CODE));
+		key.extend_from_slice(code_hash);
+		key
+	}
+
+	/// Storage key of `ContractInfoOf` for the given address (`Twox64Concat` hasher).
+	fn contract_info_key(&self, address: &[u8]) -> Vec<u8> {
+		let mut key = Vec::with_capacity(40 + address.len());
+		key.extend_from_slice(&self.pallet_prefix);
+		key.extend_from_slice(&twox_128(CONTRACT_INFO_OF));
+		key.extend_from_slice(&twox_64(address));
+		key.extend_from_slice(address);
+		key
+	}
+}
+
+#[derive(Debug, thiserror::Error)]
+enum HandleRequestError {
+	#[error("Failed to decode request: {0}.")]
+	DecodeProto(#[from] prost::DecodeError),
+	#[error("Failed to encode response: {0}.")]
+	EncodeProto(#[from] prost::EncodeError),
+	#[error("Failed to send response.")]
+	SendResponse,
+	/// A bad request has been received.
+	#[error("bad request: {0}")]
+	BadRequest(&'static str),
+	/// The peer sent more requests than allowed by the [`RateLimit`].
+	#[error("rate limit exceeded")]
+	RateLimited,
+	/// Encoding or decoding of some data failed.
+	#[error("codec error: {0}")]
+	Codec(#[from] codec::Error),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_blockchain::HeaderBackend;
+	use substrate_test_runtime_client::{
+		prelude::*, runtime::Block as TestBlock, Backend as TestBackend,
+	};
+
+	const PALLET_NAME: &str = "Contracts";
+
+	/// Request the code stored as `stored` from a node and return the code it responds with.
+	fn serve_code(stored: Vec<u8>) -> Option<Vec<u8>> {
+		let code_hash = [1u8; 32];
+		let key =
+			[&twox_128(PALLET_NAME.as_bytes())[..], &twox_128(PRISTINE_CODE), &code_hash].concat();
+		let client = Arc::new(
+			TestClientBuilder::new().add_extra_storage(key, Encode::encode(&stored)).build(),
+		);
+		let (mut handler, _) =
+			ContractCodeRequestHandler::<TestBlock, TestBackend, TestClient>::new(
+				&ProtocolId::from("test"),
+				None,
+				client.clone(),
+				PALLET_NAME,
+				RateLimit::default(),
+			);
+
+		let request = schema::Request {
+			request: Some(schema::request::Request::CodeRequest(schema::CodeRequest {
+				block: client.info().genesis_hash.encode(),
+				code_hash: code_hash.to_vec(),
+			})),
+		};
+		let response = handler.handle_request(PeerId::random(), request.encode_to_vec()).unwrap();
+		match schema::Response::decode(&response[..]).unwrap().response {
+			Some(schema::response::Response::CodeResponse(response)) => {
+				assert!(response.proof.is_some());
+				response.code
+			},
+			_ => panic!("Expected a code response"),
+		}
+	}
+
+	#[test]
+	fn code_request_serves_uncompressed_code() {
+		let code = b"\0asm\x01\0\0\0".to_vec();
+		assert_eq!(serve_code(code.clone()), Some(code));
+	}
+
+	#[test]
+	fn code_request_serves_compressed_code_decompressed() {
+		let code = [&b"\0asm\x01\0\0\0"[..], &[0u8; 1024]].concat();
+		let compressed = sp_maybe_compressed_blob::compress(&code, CODE_BOMB_LIMIT).unwrap();
+		assert_ne!(compressed, code);
+
+		assert_eq!(serve_code(compressed), Some(code));
+	}
+
+	#[test]
+	fn rate_limiter_resets_after_window() {
+		let mut limiter =
+			PeerRateLimiter::new(RateLimit { max_requests: 2, window: Duration::from_secs(1) });
+		let peer = PeerId::random();
+		let other = PeerId::random();
+		let now = Instant::now();
+
+		assert!(limiter.check(&peer, now));
+		assert!(limiter.check(&peer, now));
+		assert!(!limiter.check(&peer, now));
+		// Other peers have their own allowance.
+		assert!(limiter.check(&other, now));
+
+		assert!(limiter.check(&peer, now + Duration::from_secs(1)));
+	}
+}
@@ -18,5 +18,6 @@
 
 //! Light client data structures of the networking layer.
 
+pub mod contract_code_requests;
 pub mod light_client_requests;
 mod schema;
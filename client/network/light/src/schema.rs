@@ -22,6 +22,9 @@ pub(crate) mod v1 {
 	pub(crate) mod light {
 		include!(concat!(env!("OUT_DIR"), "/api.v1.light.rs"));
 	}
+	pub(crate) mod contracts {
+		include!(concat!(env!("OUT_DIR"), "/api.v1.contracts.rs"));
+	}
 }
 
 #[cfg(test)]
@@ -316,13 +316,22 @@ where
 					fallback_names: block_announces_protocol.fallback_names.clone(),
 					handshake: block_announces_protocol.handshake.as_ref().unwrap().to_vec(),
 					max_notification_size: block_announces_protocol.max_notification_size,
+					notifications_queue_size: block_announces_protocol
+						.notifications_queue_size
+						.unwrap_or(notifications::SYNC_NOTIFICATIONS_BUFFER_SIZE),
 				})
-				.chain(network_config.extra_sets.iter().map(|s| notifications::ProtocolConfig {
-					name: s.notifications_protocol.clone(),
-					fallback_names: s.fallback_names.clone(),
-					handshake: s.handshake.as_ref().map_or(roles.encode(), |h| (*h).to_vec()),
-					max_notification_size: s.max_notification_size,
+				.chain(network_config.extra_sets.iter().map(|s| {
+					notifications::ProtocolConfig {
+						name: s.notifications_protocol.clone(),
+						fallback_names: s.fallback_names.clone(),
+						handshake: s.handshake.as_ref().map_or(roles.encode(), |h| (*h).to_vec()),
+						max_notification_size: s.max_notification_size,
+						notifications_queue_size: s
+							.notifications_queue_size
+							.unwrap_or(notifications::SYNC_NOTIFICATIONS_BUFFER_SIZE),
+					}
 				})),
+				metrics_registry.map(notifications::Metrics::register).transpose()?,
 			)
 		};
 
@@ -21,10 +21,12 @@
 
 pub use self::{
 	behaviour::{Notifications, NotificationsOut, ProtocolConfig},
-	handler::{NotificationsSink, NotifsHandlerError, Ready},
+	handler::{NotificationsSink, NotifsHandlerError, Ready, SYNC_NOTIFICATIONS_BUFFER_SIZE},
+	metrics::Metrics,
 };
 
 mod behaviour;
 mod handler;
+mod metrics;
 mod tests;
 mod upgrade;
@@ -16,8 +16,9 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::protocol::notifications::handler::{
-	self, NotificationsSink, NotifsHandlerIn, NotifsHandlerOut, NotifsHandlerProto,
+use crate::protocol::notifications::{
+	handler::{self, NotificationsSink, NotifsHandlerIn, NotifsHandlerOut, NotifsHandlerProto},
+	metrics::Metrics,
 };
 
 use bytes::BytesMut;
@@ -82,19 +83,17 @@ use std::{
 /// the API of this behaviour and towards the peerset manager is aggregated in
 /// the following way:
 ///
-///   1. The enabled/disabled status is the same across all connections, as
-///      decided by the peerset manager.
-///   2. `send_packet` and `write_notification` always send all data over
-///      the same connection to preserve the ordering provided by the transport,
-///      as long as that connection is open. If it closes, a second open
-///      connection may take over, if one exists, but that case should be no
-///      different than a single connection failing and being re-established
-///      in terms of potential reordering and dropped messages. Messages can
-///      be received on any connection.
-///   3. The behaviour reports `NotificationsOut::CustomProtocolOpen` when the
-///      first connection reports `NotifsHandlerOut::OpenResultOk`.
-///   4. The behaviour reports `NotificationsOut::CustomProtocolClosed` when the
-///      last connection reports `NotifsHandlerOut::ClosedResult`.
+///   1. The enabled/disabled status is the same across all connections, as decided by the peerset
+///      manager.
+///   2. `send_packet` and `write_notification` always send all data over the same connection to
+///      preserve the ordering provided by the transport, as long as that connection is open. If it
+///      closes, a second open connection may take over, if one exists, but that case should be no
+///      different than a single connection failing and being re-established in terms of potential
+///      reordering and dropped messages. Messages can be received on any connection.
+///   3. The behaviour reports `NotificationsOut::CustomProtocolOpen` when the first connection
+///      reports `NotifsHandlerOut::OpenResultOk`.
+///   4. The behaviour reports `NotificationsOut::CustomProtocolClosed` when the last connection
+///      reports `NotifsHandlerOut::ClosedResult`.
 ///
 /// In this way, the number of actual established connections to the peer is
 /// an implementation detail of this behaviour. Note that, in practice and at
@@ -135,6 +134,9 @@ pub struct Notifications {
 
 	/// Events to produce from `poll()`.
 	events: VecDeque<NetworkBehaviourAction<NotificationsOut, NotifsHandlerProto>>,
+
+	/// Prometheus metrics, shared with the handlers of all connections.
+	metrics: Option<Metrics>,
 }
 
 /// Configuration for a notifications protocol.
@@ -148,6 +150,9 @@ pub struct ProtocolConfig {
 	pub handshake: Vec<u8>,
 	/// Maximum allowed size for a notification.
 	pub max_notification_size: u64,
+	/// Number of notifications sent with [`NotificationsSink::send_sync_notification`] that can
+	/// be pending for a peer before the connection to it is closed.
+	pub notifications_queue_size: usize,
 }
 
 /// Identifier for a delay firing.
@@ -359,6 +364,7 @@ impl Notifications {
 	pub fn new(
 		peerset: sc_peerset::Peerset,
 		notif_protocols: impl Iterator<Item = ProtocolConfig>,
+		metrics: Option<Metrics>,
 	) -> Self {
 		let notif_protocols = notif_protocols
 			.map(|cfg| handler::ProtocolConfig {
@@ -366,6 +372,7 @@ impl Notifications {
 				fallback_names: cfg.fallback_names,
 				handshake: Arc::new(RwLock::new(cfg.handshake)),
 				max_notification_size: cfg.max_notification_size,
+				notifications_queue_size: cfg.notifications_queue_size,
 			})
 			.collect::<Vec<_>>();
 
@@ -380,6 +387,7 @@ impl Notifications {
 			incoming: SmallVec::new(),
 			next_incoming_index: sc_peerset::IncomingIndex(0),
 			events: VecDeque::new(),
+			metrics,
 		}
 	}
 
@@ -1055,7 +1063,7 @@ impl NetworkBehaviour for Notifications {
 	type OutEvent = NotificationsOut;
 
 	fn new_handler(&mut self) -> Self::ConnectionHandler {
-		NotifsHandlerProto::new(self.notif_protocols.clone())
+		NotifsHandlerProto::new(self.notif_protocols.clone(), self.metrics.clone())
 	}
 
 	fn addresses_of_peer(&mut self, _: &PeerId) -> Vec<Multiaddr> {
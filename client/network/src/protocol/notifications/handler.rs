@@ -57,9 +57,12 @@
 //! It is illegal to send a [`NotifsHandlerIn::Open`] before a previously-emitted
 //! [`NotifsHandlerIn::Open`] has gotten an answer.
 
-use crate::protocol::notifications::upgrade::{
-	NotificationsHandshakeError, NotificationsIn, NotificationsInSubstream, NotificationsOut,
-	NotificationsOutSubstream, UpgradeCollec,
+use crate::protocol::notifications::{
+	metrics::{Metrics, QueueMetrics},
+	upgrade::{
+		NotificationsHandshakeError, NotificationsIn, NotificationsInSubstream, NotificationsOut,
+		NotificationsOutSubstream, UpgradeCollec,
+	},
 };
 
 use bytes::BytesMut;
@@ -67,10 +70,11 @@ use futures::{
 	channel::mpsc,
 	lock::{Mutex as FuturesMutex, MutexGuard as FuturesMutexGuard},
 	prelude::*,
+	task::noop_waker_ref,
 };
 use libp2p::{
 	core::{
-		upgrade::{InboundUpgrade, OutboundUpgrade},
+		upgrade::{InboundUpgrade, OutboundUpgrade, UpgradeError},
 		ConnectedPoint, PeerId,
 	},
 	swarm::{
@@ -94,8 +98,10 @@ use std::{
 /// See [`NotificationsSink::reserve_notification`] for context.
 const ASYNC_NOTIFICATIONS_BUFFER_SIZE: usize = 8;
 
-/// Number of pending notifications in synchronous contexts.
-const SYNC_NOTIFICATIONS_BUFFER_SIZE: usize = 2048;
+/// Default number of pending notifications in synchronous contexts.
+///
+/// Can be overridden for each protocol through [`ProtocolConfig::notifications_queue_size`].
+pub const SYNC_NOTIFICATIONS_BUFFER_SIZE: usize = 2048;
 
 /// Maximum duration to open a substream and receive the handshake message. After that, we
 /// consider that we failed to open the substream.
@@ -117,6 +123,9 @@ pub struct NotifsHandlerProto {
 	/// Name of protocols, prototypes for upgrades for inbound substreams, and the message we
 	/// send or respond with in the handshake.
 	protocols: Vec<ProtocolConfig>,
+
+	/// Prometheus metrics.
+	metrics: Option<Metrics>,
 }
 
 /// The actual handler once the connection has been established.
@@ -139,6 +148,9 @@ pub struct NotifsHandler {
 	events_queue: VecDeque<
 		ConnectionHandlerEvent<NotificationsOut, usize, NotifsHandlerOut, NotifsHandlerError>,
 	>,
+
+	/// Prometheus metrics.
+	metrics: Option<Metrics>,
 }
 
 /// Configuration for a notifications protocol.
@@ -152,6 +164,9 @@ pub struct ProtocolConfig {
 	pub handshake: Arc<RwLock<Vec<u8>>>,
 	/// Maximum allowed size for a notification.
 	pub max_notification_size: u64,
+	/// Number of notifications sent in synchronous contexts that can be pending before the
+	/// connection to the peer is closed.
+	pub notifications_queue_size: usize,
 }
 
 /// Fields specific for each individual protocol.
@@ -196,17 +211,9 @@ enum State {
 
 	/// Protocol is in the "Open" state.
 	Open {
-		/// Contains the two `Receiver`s connected to the [`NotificationsSink`] that has been
-		/// sent out. The notifications to send out can be pulled from this receivers.
-		/// We use two different channels in order to have two different channel sizes, but from
-		/// the receiving point of view, the two channels are the same.
-		/// The receivers are fused in case the user drops the [`NotificationsSink`] entirely.
-		notifications_sink_rx: stream::Peekable<
-			stream::Select<
-				stream::Fuse<mpsc::Receiver<NotificationsSinkMessage>>,
-				stream::Fuse<mpsc::Receiver<NotificationsSinkMessage>>,
-			>,
-		>,
+		/// Receiving side of the [`NotificationsSink`] that has been sent out. The notifications
+		/// to send out can be pulled from it.
+		notifications_sink_rx: NotificationsSinkRx,
 
 		/// Outbound substream that has been accepted by the remote.
 		///
@@ -259,6 +266,7 @@ impl IntoConnectionHandler for NotifsHandlerProto {
 			endpoint: connected_point.clone(),
 			when_connection_open: Instant::now(),
 			events_queue: VecDeque::with_capacity(16),
+			metrics: self.metrics,
 		}
 	}
 }
@@ -370,6 +378,8 @@ struct NotificationsSinkInner {
 	/// back-pressure cannot be properly exerted.
 	/// It will be removed in a future version.
 	sync_channel: Mutex<Option<mpsc::Sender<NotificationsSinkMessage>>>,
+	/// Prometheus metrics of the protocol.
+	metrics: Option<QueueMetrics>,
 }
 
 /// Message emitted through the [`NotificationsSink`] and processed by the background task
@@ -403,10 +413,21 @@ impl NotificationsSink {
 		let mut lock = self.inner.sync_channel.lock();
 
 		if let Some(tx) = lock.as_mut() {
+			// The depth is increased before sending, so that it can never be decreased by the
+			// handler before being increased here.
+			if let Some(metrics) = &self.inner.metrics {
+				metrics.depth.inc();
+			}
+
 			let result =
 				tx.try_send(NotificationsSinkMessage::Notification { message: message.into() });
 
 			if result.is_err() {
+				if let Some(metrics) = &self.inner.metrics {
+					metrics.depth.dec();
+					metrics.dropped.inc();
+				}
+
 				// Cloning the `mpsc::Sender` guarantees the allocation of an extra spot in the
 				// buffer, and therefore `try_send` will succeed.
 				let _result2 = tx.clone().try_send(NotificationsSinkMessage::ForceClose);
@@ -415,6 +436,8 @@ impl NotificationsSink {
 				// Destroy the sender in order to not send more `ForceClose` messages.
 				*lock = None;
 			}
+		} else if let Some(metrics) = &self.inner.metrics {
+			metrics.dropped.inc();
 		}
 	}
 
@@ -429,7 +452,7 @@ impl NotificationsSink {
 
 		let poll_ready = future::poll_fn(|cx| lock.poll_ready(cx)).await;
 		if poll_ready.is_ok() {
-			Ok(Ready { lock })
+			Ok(Ready { lock, metrics: self.inner.metrics.as_ref() })
 		} else {
 			Err(())
 		}
@@ -442,6 +465,8 @@ impl NotificationsSink {
 pub struct Ready<'a> {
 	/// Guarded channel. The channel inside is guaranteed to not be full.
 	lock: FuturesMutexGuard<'a, mpsc::Sender<NotificationsSinkMessage>>,
+	/// Prometheus metrics of the protocol.
+	metrics: Option<&'a QueueMetrics>,
 }
 
 impl<'a> Ready<'a> {
@@ -449,9 +474,77 @@ impl<'a> Ready<'a> {
 	///
 	/// Returns an error if the substream has been closed.
 	pub fn send(mut self, notification: impl Into<Vec<u8>>) -> Result<(), ()> {
+		if let Some(metrics) = self.metrics {
+			metrics.depth.inc();
+		}
+
 		self.lock
 			.start_send(NotificationsSinkMessage::Notification { message: notification.into() })
-			.map_err(|_| ())
+			.map_err(|_| {
+				if let Some(metrics) = self.metrics {
+					metrics.depth.dec();
+				}
+			})
+	}
+}
+
+/// Receiving side of a [`NotificationsSink`].
+///
+/// We use two different channels in order to have two different channel sizes, but from the
+/// receiving point of view, the two channels are the same. The receivers are fused in case the
+/// user drops the [`NotificationsSink`] entirely.
+///
+/// Notifications still queued when this is dropped are removed from the queue depth metric.
+struct NotificationsSinkRx {
+	inner: stream::Peekable<
+		stream::Select<
+			stream::Fuse<mpsc::Receiver<NotificationsSinkMessage>>,
+			stream::Fuse<mpsc::Receiver<NotificationsSinkMessage>>,
+		>,
+	>,
+	metrics: Option<QueueMetrics>,
+}
+
+impl NotificationsSinkRx {
+	fn new(
+		async_rx: mpsc::Receiver<NotificationsSinkMessage>,
+		sync_rx: mpsc::Receiver<NotificationsSinkMessage>,
+		metrics: Option<QueueMetrics>,
+	) -> Self {
+		Self { inner: stream::select(async_rx.fuse(), sync_rx.fuse()).peekable(), metrics }
+	}
+
+	/// Peek at the next message without removing it from the queue.
+	fn poll_peek(&mut self, cx: &mut Context) -> Poll<Option<&NotificationsSinkMessage>> {
+		Pin::new(&mut self.inner).poll_peek(cx)
+	}
+
+	/// Remove the next message from the queue.
+	fn poll_next_unpin(&mut self, cx: &mut Context) -> Poll<Option<NotificationsSinkMessage>> {
+		let next = self.inner.poll_next_unpin(cx);
+		if let (Poll::Ready(Some(NotificationsSinkMessage::Notification { .. })), Some(metrics)) =
+			(&next, &self.metrics)
+		{
+			metrics.depth.dec();
+		}
+		next
+	}
+}
+
+impl Drop for NotificationsSinkRx {
+	fn drop(&mut self) {
+		if self.metrics.is_none() {
+			return
+		}
+
+		// Closing the channels makes further sends fail, so that everything that has been
+		// counted is either drained here or decreased again by the sender.
+		let (async_rx, sync_rx) = self.inner.get_mut().get_mut();
+		async_rx.get_mut().close();
+		sync_rx.get_mut().close();
+
+		let mut cx = Context::from_waker(noop_waker_ref());
+		while let Poll::Ready(Some(_)) = self.poll_next_unpin(&mut cx) {}
 	}
 }
 
@@ -469,8 +562,8 @@ impl NotifsHandlerProto {
 	/// handshake, and the maximum allowed size of a notification. At the moment, the message
 	/// is always the same whether we open a substream ourselves or respond to handshake from
 	/// the remote.
-	pub fn new(list: impl Into<Vec<ProtocolConfig>>) -> Self {
-		Self { protocols: list.into() }
+	pub fn new(list: impl Into<Vec<ProtocolConfig>>, metrics: Option<Metrics>) -> Self {
+		Self { protocols: list.into(), metrics }
 	}
 }
 
@@ -544,6 +637,10 @@ impl ConnectionHandler for NotifsHandler {
 		new_open: <Self::OutboundProtocol as OutboundUpgrade<NegotiatedSubstream>>::Output,
 		protocol_index: Self::OutboundOpenInfo,
 	) {
+		let config = &self.protocols[protocol_index].config;
+		let metrics = self.metrics.as_ref().map(|m| m.queue_metrics(&config.name));
+		let notifications_queue_size = config.notifications_queue_size;
+
 		match self.protocols[protocol_index].state {
 			State::Closed { ref mut pending_opening } |
 			State::OpenDesiredByRemote { ref mut pending_opening, .. } => {
@@ -556,18 +653,18 @@ impl ConnectionHandler for NotifsHandler {
 			},
 			State::Opening { ref mut in_substream } => {
 				let (async_tx, async_rx) = mpsc::channel(ASYNC_NOTIFICATIONS_BUFFER_SIZE);
-				let (sync_tx, sync_rx) = mpsc::channel(SYNC_NOTIFICATIONS_BUFFER_SIZE);
+				let (sync_tx, sync_rx) = mpsc::channel(notifications_queue_size);
 				let notifications_sink = NotificationsSink {
 					inner: Arc::new(NotificationsSinkInner {
 						peer_id: self.peer_id,
 						async_channel: FuturesMutex::new(async_tx),
 						sync_channel: Mutex::new(Some(sync_tx)),
+						metrics: metrics.clone(),
 					}),
 				};
 
 				self.protocols[protocol_index].state = State::Open {
-					notifications_sink_rx: stream::select(async_rx.fuse(), sync_rx.fuse())
-						.peekable(),
+					notifications_sink_rx: NotificationsSinkRx::new(async_rx, sync_rx, metrics),
 					out_substream: Some(new_open.substream),
 					in_substream: in_substream.take(),
 				};
@@ -679,8 +776,22 @@ impl ConnectionHandler for NotifsHandler {
 	fn inject_dial_upgrade_error(
 		&mut self,
 		num: usize,
-		_: ConnectionHandlerUpgrErr<NotificationsHandshakeError>,
+		err: ConnectionHandlerUpgrErr<NotificationsHandshakeError>,
 	) {
+		if let Some(metrics) = &self.metrics {
+			let reason = match err {
+				ConnectionHandlerUpgrErr::Timeout => "timeout",
+				ConnectionHandlerUpgrErr::Timer => "timer",
+				ConnectionHandlerUpgrErr::Upgrade(UpgradeError::Select(_)) => "unsupported",
+				ConnectionHandlerUpgrErr::Upgrade(UpgradeError::Apply(err)) => match err {
+					NotificationsHandshakeError::Io(_) => "io",
+					NotificationsHandshakeError::TooLarge { .. } => "too-large",
+					NotificationsHandshakeError::VarintDecode(_) => "invalid-length",
+				},
+			};
+			metrics.report_handshake_failure(&self.protocols[num].config.name, reason);
+		}
+
 		match self.protocols[num].state {
 			State::Closed { ref mut pending_opening } |
 			State::OpenDesiredByRemote { ref mut pending_opening, .. } => {
@@ -738,7 +849,7 @@ impl ConnectionHandler for NotifsHandler {
 					// Only proceed with `out_substream.poll_ready_unpin` if there is an element
 					// available in `notifications_sink_rx`. This avoids waking up the task when
 					// a substream is ready to send if there isn't actually something to send.
-					match Pin::new(&mut *notifications_sink_rx).as_mut().poll_peek(cx) {
+					match notifications_sink_rx.poll_peek(cx) {
 						Poll::Ready(Some(&NotificationsSinkMessage::ForceClose)) =>
 							return Poll::Ready(ConnectionHandlerEvent::Close(
 								NotifsHandlerError::SyncNotificationsClogged,
@@ -0,0 +1,99 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Prometheus metrics of the notifications protocols.
+//!
+//! All metrics are labelled with the name of the protocol and aggregated over all peers, so that
+//! operators can tell which protocol suffers from slow peers.
+
+use prometheus_endpoint::{
+	register, Counter, CounterVec, Gauge, GaugeVec, Opts, PrometheusError, Registry, U64,
+};
+
+/// Metrics shared by all connections of the notifications behaviour.
+#[derive(Debug, Clone)]
+pub struct Metrics {
+	/// Number of notifications queued for sending but not yet written to a substream.
+	queue_depth: GaugeVec<U64>,
+	/// Number of notifications discarded because the queue of a peer was full.
+	dropped_total: CounterVec<U64>,
+	/// Number of failed attempts to open an outbound substream, by reason.
+	handshake_failures_total: CounterVec<U64>,
+}
+
+impl Metrics {
+	/// Register the metrics in `registry`.
+	pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			queue_depth: register(
+				GaugeVec::new(
+					Opts::new(
+						"substrate_sub_libp2p_notifications_queue_depth",
+						"Number of outbound notifications waiting to be sent, summed over all peers",
+					),
+					&["protocol"],
+				)?,
+				registry,
+			)?,
+			dropped_total: register(
+				CounterVec::new(
+					Opts::new(
+						"substrate_sub_libp2p_notifications_dropped_total",
+						"Total number of outbound notifications dropped because the queue of \
+						the peer was full",
+					),
+					&["protocol"],
+				)?,
+				registry,
+			)?,
+			handshake_failures_total: register(
+				CounterVec::new(
+					Opts::new(
+						"substrate_sub_libp2p_notifications_handshake_failures_total",
+						"Total number of outbound notifications substreams that failed to open, \
+						by reason",
+					),
+					&["protocol", "reason"],
+				)?,
+				registry,
+			)?,
+		})
+	}
+
+	/// The metrics updated when sending notifications over `protocol`.
+	pub(super) fn queue_metrics(&self, protocol: &str) -> QueueMetrics {
+		QueueMetrics {
+			depth: self.queue_depth.with_label_values(&[protocol]),
+			dropped: self.dropped_total.with_label_values(&[protocol]),
+		}
+	}
+
+	/// Note that opening an outbound substream for `protocol` failed.
+	pub(super) fn report_handshake_failure(&self, protocol: &str, reason: &str) {
+		self.handshake_failures_total.with_label_values(&[protocol, reason]).inc();
+	}
+}
+
+/// Metrics of the notifications queue of a single protocol.
+#[derive(Debug, Clone)]
+pub(super) struct QueueMetrics {
+	/// See [`Metrics::queue_depth`].
+	pub depth: Gauge<U64>,
+	/// See [`Metrics::dropped_total`].
+	pub dropped: Counter<U64>,
+}
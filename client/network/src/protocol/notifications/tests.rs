@@ -18,7 +18,9 @@
 
 #![cfg(test)]
 
-use crate::protocol::notifications::{Notifications, NotificationsOut, ProtocolConfig};
+use crate::protocol::notifications::{
+	Notifications, NotificationsOut, ProtocolConfig, SYNC_NOTIFICATIONS_BUFFER_SIZE,
+};
 
 use futures::prelude::*;
 use libp2p::{
@@ -89,7 +91,9 @@ fn build_nodes() -> (Swarm<CustomProtoWithAddr>, Swarm<CustomProtoWithAddr>) {
 					fallback_names: Vec::new(),
 					handshake: Vec::new(),
 					max_notification_size: 1024 * 1024,
+					notifications_queue_size: SYNC_NOTIFICATIONS_BUFFER_SIZE,
 				}),
+				None,
 			),
 			addrs: addrs
 				.iter()
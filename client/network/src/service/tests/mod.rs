@@ -167,6 +167,7 @@ impl TestNetworkBuilder {
 				fallback_names: Vec::new(),
 				max_notification_size: 1024 * 1024,
 				handshake: None,
+				notifications_queue_size: None,
 				set_config: self.set_config.unwrap_or_default(),
 			}],
 			listen_addresses: self.listen_addresses,
@@ -259,6 +260,7 @@ impl TestNetworkBuilder {
 					.flatten()
 					.expect("Genesis block exists; qed"),
 			))),
+			notifications_queue_size: None,
 			set_config: SetConfig {
 				in_peers: 0,
 				out_peers: 0,
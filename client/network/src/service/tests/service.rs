@@ -337,6 +337,7 @@ async fn fallback_name_working() {
 				fallback_names: vec![PROTOCOL_NAME.into()],
 				max_notification_size: 1024 * 1024,
 				handshake: None,
+				notifications_queue_size: None,
 				set_config: Default::default(),
 			}],
 			listen_addresses: vec![listen_addr.clone()],
@@ -2003,6 +2003,7 @@ where
 			))),
 			// NOTE: `set_config` will be ignored by `protocol.rs` as the block announcement
 			// protocol is still hardcoded into the peerset.
+			notifications_queue_size: None,
 			set_config: SetConfig {
 				in_peers: 0,
 				out_peers: 0,
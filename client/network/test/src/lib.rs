@@ -824,6 +824,7 @@ where
 				fallback_names: Vec::new(),
 				max_notification_size: 1024 * 1024,
 				handshake: None,
+				notifications_queue_size: None,
 				set_config: Default::default(),
 			})
 			.collect();
@@ -144,6 +144,7 @@ impl TransactionsHandlerPrototype {
 			fallback_names: self.fallback_protocol_names.clone(),
 			max_notification_size: MAX_TRANSACTIONS_SIZE,
 			handshake: None,
+			notifications_queue_size: None,
 			set_config: SetConfig {
 				in_peers: 0,
 				out_peers: 0,
@@ -197,7 +197,8 @@ impl TransactionState {
 			TransactionStatus::Usurped(_) => Some(TransactionEvent::Invalid(TransactionError {
 				error: "Extrinsic was rendered invalid by another extrinsic".into(),
 			})),
-			TransactionStatus::Dropped => Some(TransactionEvent::Invalid(TransactionError {
+			TransactionStatus::Dropped => Some(TransactionEvent::Dropped(TransactionDropped {
+				broadcasted: self.broadcasted,
 				error: "Extrinsic dropped from the pool due to exceeding limits".into(),
 			})),
 			TransactionStatus::Invalid => Some(TransactionEvent::Invalid(TransactionError {
@@ -206,3 +207,63 @@ impl TransactionState {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::H256;
+
+	type Status = TransactionStatus<H256, H256>;
+
+	#[test]
+	fn lifecycle_events_are_reported() {
+		let mut state = TransactionState::new();
+		let block = H256::from_low_u64_be(1);
+
+		assert_eq!(state.handle_event(Status::Ready), Some(TransactionEvent::Validated));
+		assert_eq!(
+			state.handle_event(Status::Broadcast(vec!["peer".into()])),
+			Some(TransactionEvent::Broadcasted(TransactionBroadcasted { num_peers: 1 }))
+		);
+		assert_eq!(
+			state.handle_event(Status::InBlock((block, 2))),
+			Some(TransactionEvent::BestChainBlockIncluded(Some(TransactionBlock {
+				hash: block,
+				index: 2
+			})))
+		);
+		assert_eq!(
+			state.handle_event(Status::Retracted(block)),
+			Some(TransactionEvent::BestChainBlockIncluded(None))
+		);
+		assert_eq!(
+			state.handle_event(Status::Finalized((block, 3))),
+			Some(TransactionEvent::Finalized(TransactionBlock { hash: block, index: 3 }))
+		);
+	}
+
+	#[test]
+	fn dropped_reports_broadcast_state() {
+		let mut state = TransactionState::new();
+		assert_eq!(
+			state.handle_event(Status::Dropped),
+			Some(TransactionEvent::Dropped(TransactionDropped {
+				broadcasted: false,
+				error: "Extrinsic dropped from the pool due to exceeding limits".into(),
+			}))
+		);
+
+		// Broadcasting to no peers does not count.
+		state.handle_event(Status::Broadcast(vec![]));
+		assert!(!state.broadcasted);
+
+		state.handle_event(Status::Broadcast(vec!["peer".into()]));
+		assert_eq!(
+			state.handle_event(Status::Dropped),
+			Some(TransactionEvent::Dropped(TransactionDropped {
+				broadcasted: true,
+				error: "Extrinsic dropped from the pool due to exceeding limits".into(),
+			}))
+		);
+	}
+}
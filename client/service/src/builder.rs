@@ -553,7 +553,7 @@ where
 		)
 	};
 
-	let rpc = start_rpc_servers(&config, gen_rpc_module, rpc_id_provider)?;
+	let (http_server, ws_server) = start_rpc_servers(&config, gen_rpc_module, rpc_id_provider)?;
 	let rpc_handlers = RpcHandlers(Arc::new(gen_rpc_module(sc_rpc::DenyUnsafe::No)?.into()));
 
 	// Spawn informant task
@@ -563,7 +563,12 @@ where
 		sc_informant::build(client.clone(), network, config.informant_output_format),
 	);
 
-	task_manager.keep_alive((config.base_path, rpc));
+	// Close the RPC connections before anything else is stopped, so that clients don't observe a
+	// half shut down node.
+	task_manager.on_shutdown("rpc-servers", async move {
+		futures::future::join(http_server.stop(), ws_server.stop()).await;
+	});
+	task_manager.keep_alive(config.base_path);
 
 	Ok(rpc_handlers)
 }
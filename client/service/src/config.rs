@@ -42,6 +42,7 @@ use std::{
 	io, iter,
 	net::SocketAddr,
 	path::{Path, PathBuf},
+	time::Duration,
 };
 use tempfile::TempDir;
 
@@ -149,6 +150,10 @@ pub struct Configuration {
 	pub informant_output_format: sc_informant::OutputFormat,
 	/// Maximum number of different runtime versions that can be cached.
 	pub runtime_cache_size: u8,
+	/// Maximum time the node is given to shut down gracefully.
+	///
+	/// Tasks that are still running after this time are abandoned.
+	pub shutdown_timeout: Duration,
 }
 
 /// Type for tasks spawned by the executor.
@@ -298,6 +298,19 @@ async fn build_network_future<
 mod waiting {
 	pub struct Server(pub Option<sc_rpc_server::Server>);
 
+	impl Server {
+		/// Stop accepting new connections and wait until all open connections are closed.
+		///
+		/// In-flight calls are answered before a connection is closed. WebSocket clients are
+		/// notified about the closed connection, which also terminates their subscriptions.
+		pub async fn stop(mut self) {
+			if let Some(server) = self.0.take() {
+				let _ = server.stop();
+				server.stopped().await;
+			}
+		}
+	}
+
 	impl Drop for Server {
 		fn drop(&mut self) {
 			if let Some(server) = self.0.take() {
@@ -313,7 +326,7 @@ fn start_rpc_servers<R>(
 	config: &Configuration,
 	gen_rpc_module: R,
 	rpc_id_provider: Option<Box<dyn RpcSubscriptionIdProvider>>,
-) -> Result<Box<dyn std::any::Any + Send + Sync>, error::Error>
+) -> Result<(waiting::Server, waiting::Server), error::Error>
 where
 	R: Fn(sc_rpc::DenyUnsafe) -> Result<RpcModule<()>, Error>,
 {
@@ -375,7 +388,7 @@ where
 	match tokio::task::block_in_place(|| {
 		config.tokio_handle.block_on(futures::future::try_join(http_fut, ws_fut))
 	}) {
-		Ok((http, ws)) => Ok(Box::new((waiting::Server(Some(http)), waiting::Server(Some(ws))))),
+		Ok((http, ws)) => Ok((waiting::Server(Some(http)), waiting::Server(Some(ws)))),
 		Err(e) => Err(Error::Application(e)),
 	}
 }
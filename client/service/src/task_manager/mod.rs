@@ -36,6 +36,7 @@ use std::{
 	pin::Pin,
 	result::Result,
 	sync::Arc,
+	time::Duration,
 };
 use tokio::runtime::Handle;
 use tracing_futures::Instrument;
@@ -313,6 +314,8 @@ pub struct TaskManager {
 	children: Vec<TaskManager>,
 	/// The registry of all running tasks.
 	task_registry: TaskRegistry,
+	/// Futures to run on a graceful shutdown, in order.
+	shutdown_hooks: Vec<(&'static str, BoxFuture<'static, ()>)>,
 }
 
 impl TaskManager {
@@ -340,6 +343,7 @@ impl TaskManager {
 			keep_alive: Box::new(()),
 			children: Vec::new(),
 			task_registry: Default::default(),
+			shutdown_hooks: Vec::new(),
 		})
 	}
 
@@ -403,6 +407,41 @@ impl TaskManager {
 		self.children.push(child);
 	}
 
+	/// Register a future that is run when the node shuts down gracefully.
+	///
+	/// Hooks are run one after another in the order they were registered, after the hooks of all
+	/// children. They are dropped without being run if the task manager is dropped without calling
+	/// [`Self::graceful_shutdown`].
+	pub fn on_shutdown(
+		&mut self,
+		name: &'static str,
+		hook: impl Future<Output = ()> + Send + 'static,
+	) {
+		self.shutdown_hooks.push((name, hook.boxed()));
+	}
+
+	/// Run the shutdown hooks of this task manager and all its children.
+	///
+	/// This should be called before the task manager is dropped. Returns `false` if the hooks did
+	/// not finish within `timeout`, in which case the remaining hooks are dropped.
+	pub async fn graceful_shutdown(&mut self, timeout: Duration) -> bool {
+		let hooks = self.take_shutdown_hooks();
+		let run_hooks = async move {
+			for (name, hook) in hooks {
+				log::debug!(target: "sc_service", "Running shutdown hook `{}`", name);
+				hook.await;
+			}
+		};
+		tokio::time::timeout(timeout, run_hooks).await.is_ok()
+	}
+
+	fn take_shutdown_hooks(&mut self) -> Vec<(&'static str, BoxFuture<'static, ()>)> {
+		let mut hooks: Vec<_> =
+			self.children.iter_mut().flat_map(|child| child.take_shutdown_hooks()).collect();
+		hooks.append(&mut self.shutdown_hooks);
+		hooks
+	}
+
 	/// Consume `self` and return the [`TaskRegistry`].
 	///
 	/// This [`TaskRegistry`] can be used to check for still running tasks after this task manager
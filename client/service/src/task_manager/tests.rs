@@ -250,3 +250,40 @@ fn ensure_task_manager_future_continues_when_childs_not_essential_task_fails() {
 	}
 	drop_tester.wait_on_drop();
 }
+
+#[test]
+fn shutdown_hooks_run_in_order() {
+	let runtime = tokio::runtime::Runtime::new().unwrap();
+	let handle = runtime.handle().clone();
+	let ran = Arc::new(Mutex::new(Vec::new()));
+	let hook = |name: &'static str| {
+		let ran = ran.clone();
+		async move { ran.lock().push(name) }
+	};
+
+	let mut task_manager = new_task_manager(handle.clone());
+	let mut child = new_task_manager(handle);
+	task_manager.on_shutdown("parent1", hook("parent1"));
+	task_manager.on_shutdown("parent2", hook("parent2"));
+	child.on_shutdown("child", hook("child"));
+	task_manager.add_child(child);
+
+	assert!(runtime.block_on(task_manager.graceful_shutdown(Duration::from_secs(1))));
+	assert_eq!(*ran.lock(), vec!["child", "parent1", "parent2"]);
+
+	// hooks only run once
+	assert!(runtime.block_on(task_manager.graceful_shutdown(Duration::from_secs(1))));
+	assert_eq!(ran.lock().len(), 3);
+}
+
+#[test]
+fn graceful_shutdown_is_bounded_by_timeout() {
+	let runtime = tokio::runtime::Runtime::new().unwrap();
+	let mut task_manager = new_task_manager(runtime.handle().clone());
+	let drop_tester = DropTester::new();
+	task_manager.on_shutdown("stalled", run_background_task(drop_tester.new_ref()));
+
+	assert!(!runtime.block_on(task_manager.graceful_shutdown(Duration::from_millis(100))));
+	// the stalled hook is dropped once the timeout hits
+	assert_eq!(drop_tester, 0);
+}
@@ -265,6 +265,7 @@ fn node_config<
 		base_path: Some(BasePath::new(root)),
 		informant_output_format: Default::default(),
 		runtime_cache_size: 2,
+		shutdown_timeout: Duration::from_secs(60),
 	}
 }
 
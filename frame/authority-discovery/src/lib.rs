@@ -191,7 +191,7 @@ mod tests {
 			UncheckedExtrinsic = UncheckedExtrinsic,
 		{
 			System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
-			Session: pallet_session::{Pallet, Call, Storage, Event, Config<T>},
+			Session: pallet_session::{Pallet, Call, Storage, Event<T>, Config<T>},
 			AuthorityDiscovery: pallet_authority_discovery::{Pallet, Config},
 		}
 	);
@@ -207,6 +207,9 @@ mod tests {
 	impl pallet_session::Config for Test {
 		type SessionManager = ();
 		type Keys = UintAuthorityId;
+		type KeyPurgeDelay = ConstU32<0>;
+		type MaxPurgesPerSession = ConstU32<0>;
+		type PurgeKeysOrigin = frame_system::EnsureRoot<Self::AccountId>;
 		type ShouldEndSession = pallet_session::PeriodicSessions<Period, Offset>;
 		type SessionHandler = TestSessionHandler;
 		type RuntimeEvent = RuntimeEvent;
@@ -219,6 +222,8 @@ mod tests {
 	impl pallet_session::historical::Config for Test {
 		type FullIdentification = ();
 		type FullIdentificationOf = ();
+		type MaxStoredSessions = ConstU32<1_000>;
+		type MaxKeyOwnershipProofSize = ConstU32<{ 64 * 1024 }>;
 	}
 
 	pub type BlockNumber = u64;
@@ -53,7 +53,7 @@ construct_runtime!(
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
 		Beefy: pallet_beefy::{Pallet, Config<T>, Storage},
-		Session: pallet_session::{Pallet, Call, Storage, Event, Config<T>},
+		Session: pallet_session::{Pallet, Call, Storage, Event<T>, Config<T>},
 	}
 );
 
@@ -103,6 +103,9 @@ impl pallet_session::Config for Test {
 	type SessionManager = MockSessionManager;
 	type SessionHandler = <MockSessionKeys as OpaqueKeys>::KeyTypeIdProviders;
 	type Keys = MockSessionKeys;
+	type KeyPurgeDelay = ConstU32<0>;
+	type MaxPurgesPerSession = ConstU32<0>;
+	type PurgeKeysOrigin = frame_system::EnsureRoot<Self::AccountId>;
 	type WeightInfo = ();
 }
 
@@ -0,0 +1,62 @@
+;; Calls the account passed in the input and returns the output of the call.
+;; The input is the address of the callee followed by the input that is forwarded to it.
+;; If the call fails the return code is returned with the revert flag set.
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal1" "seal_call" (func $seal_call (param i32 i32 i64 i32 i32 i32 i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 4) size of the input buffer
+	(data (i32.const 0) "\00\02")
+
+	;; [4, 516) input buffer: callee address followed by the forwarded input
+
+	;; [516, 524) value to transfer: zero
+
+	;; [524, 528) size of the output buffer
+	(data (i32.const 524) "\00\01")
+
+	;; [528, 784) output buffer
+
+	;; [784, 788) return code of the call
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		(call $seal_input (i32.const 4) (i32.const 0))
+
+		(i32.store
+			(i32.const 784)
+			(call $seal_call
+				(i32.const 0)	;; No flags
+				(i32.const 4)	;; Pointer to "callee" address
+				(i64.const 0)	;; How much gas to devote for the execution. 0 = all.
+				(i32.const 516)	;; Pointer to the buffer with value to transfer
+				(i32.const 36)	;; Pointer to input data buffer address
+				(i32.sub	;; Length of input data buffer
+					(i32.load (i32.const 0))
+					(i32.const 32)
+				)
+				(i32.const 528)	;; Pointer to the output buffer
+				(i32.const 524)	;; Pointer to the length of the output buffer
+			)
+		)
+
+		(if (i32.load (i32.const 784))
+			(then
+				(call $seal_return
+					(i32.const 1)	;; REVERT flag
+					(i32.const 784)	;; Pointer to the return code
+					(i32.const 4)	;; Size of the return code
+				)
+			)
+		)
+
+		(call $seal_return
+			(i32.const 0)	;; flags
+			(i32.const 528)	;; Pointer to the output
+			(i32.load (i32.const 524))	;; Size of the output
+		)
+	)
+)
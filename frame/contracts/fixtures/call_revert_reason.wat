@@ -0,0 +1,47 @@
+;; This expects [account_id, callee_input] as input and calls the account_id forwarding the
+;; 4 byte `callee_input`. It returns the return code of the call followed by whatever the call
+;; wrote into the output buffer.
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal1" "seal_call" (func $seal_call (param i32 i32 i64 i32 i32 i32 i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 4) size of the input buffer
+	(data (i32.const 0) "\40")
+
+	;; [4, 36) address of the callee
+	;; [36, 40) input forwarded to the callee
+	;; [40, 48) value to transfer: zero
+
+	;; [48, 52) size of the output buffer
+	(data (i32.const 48) "\40")
+
+	;; [52, 56) return code of the call
+	;; [56, 120) output buffer
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		(call $seal_input (i32.const 4) (i32.const 0))
+		(i32.store
+			(i32.const 52)
+			(call $seal_call
+				(i32.const 0) ;; No flags
+				(i32.const 4) ;; Pointer to "callee" address.
+				(i64.const 0) ;; How much gas to devote for the execution. 0 = all.
+				(i32.const 40) ;; Pointer to the buffer with value to transfer
+				(i32.const 36) ;; Pointer to input data buffer address
+				(i32.const 4) ;; Length of input data buffer
+				(i32.const 56) ;; Pointer to the output buffer
+				(i32.const 48) ;; Pointer to the length of the output buffer
+			)
+		)
+		;; return the return code followed by the output
+		(call $seal_return
+			(i32.const 0)
+			(i32.const 52)
+			(i32.add (i32.load (i32.const 48)) (i32.const 4))
+		)
+	)
+)
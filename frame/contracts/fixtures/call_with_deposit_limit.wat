@@ -0,0 +1,40 @@
+;; This expects [deposit_limit, account_id, callee_input] as input and calls the account_id
+;; with the supplied storage deposit limit. The 4 byte `callee_input` is forwarded to the callee.
+;; It returns the return code of the call as output data so that the caller can be observed to
+;; continue after a failed call.
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal2" "call" (func $seal_call (param i32 i32 i64 i32 i32 i32 i32 i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 4) size of the input buffer
+	(data (i32.const 0) "\00\02")
+
+	;; [4, 12) storage deposit limit of the callee
+	;; [12, 44) address of the callee
+	;; [44, 48) input forwarded to the callee
+	;; [48, 52) return code of the call
+	;; [64, 72) value to transfer: zero
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		(call $seal_input (i32.const 4) (i32.const 0))
+		(i32.store
+			(i32.const 48)
+			(call $seal_call
+				(i32.const 0) ;; No flags
+				(i32.const 12) ;; Pointer to "callee" address.
+				(i64.const 0) ;; How much gas to devote for the execution. 0 = all.
+				(i32.const 4) ;; Pointer to the buffer with the deposit limit
+				(i32.const 64) ;; Pointer to the buffer with value to transfer
+				(i32.const 44) ;; Pointer to input data buffer address
+				(i32.const 4) ;; Length of input data buffer
+				(i32.const 0xffffffff) ;; u32 max sentinel value: do not copy output
+				(i32.const 0) ;; Length is ignored in this case
+			)
+		)
+		(call $seal_return (i32.const 0) (i32.const 48) (i32.const 4))
+	)
+)
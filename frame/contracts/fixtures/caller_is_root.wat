@@ -0,0 +1,15 @@
+;; Returns the result of `caller_is_root` as a little endian u32.
+(module
+	(import "seal0" "caller_is_root" (func $caller_is_root (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 4) the return value of `caller_is_root`
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		(i32.store (i32.const 0) (call $caller_is_root))
+		(call $seal_return (i32.const 0) (i32.const 0) (i32.const 4))
+	)
+)
@@ -0,0 +1,45 @@
+;; This contract:
+;; 1) Reads a compressed ECDSA public key from the input
+;; 2) Calls ecdsa_to_eth_address
+;; 3) Validates that result is Success
+;; 4) Returns the Ethereum address
+(module
+	(import "seal0" "seal_ecdsa_to_eth_address" (func $seal_ecdsa_to_eth_address (param i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok
+				(get_local 0)
+			)
+			(unreachable)
+		)
+	)
+
+	(func (export "deploy"))
+
+	;; [4, 8) len of the compressed public key - 33 bytes
+	(data (i32.const 4) "\21")
+
+	;; Memory layout during `call`
+	;; [10, 43) compressed public key
+	;; [50, 70) ethereum address
+	(func (export "call")
+		;; Read the compressed public key - 33 bytes
+		(call $seal_input (i32.const 10) (i32.const 4))
+		(call $assert
+			(i32.eq
+				(call $seal_ecdsa_to_eth_address
+					(i32.const 10) ;; Pointer to the compressed public key
+					(i32.const 50) ;; Pointer for output - ethereum address
+				)
+				(i32.const 0x0) ;; 0x0 - Success result
+			)
+		)
+
+		;; exit with success and return the ethereum address
+		(call $seal_return (i32.const 0) (i32.const 50) (i32.const 20))
+	)
+)
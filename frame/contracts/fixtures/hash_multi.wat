@@ -0,0 +1,48 @@
+;; Hashes the input, except for its first byte, split into two buffers.
+;;
+;; The first byte of the input selects the hash function:
+;; 0 for `hash_keccak_256_multi` and 1 for `hash_blake2_128_multi`.
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "seal0" "hash_keccak_256_multi" (func $hash_keccak_256_multi (param i32 i32 i32)))
+	(import "seal0" "hash_blake2_128_multi" (func $hash_blake2_128_multi (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 4) length of the input buffer
+	(data (i32.const 0) "\00\01")
+
+	;; [4, 260) input buffer
+
+	;; [260, 276) list of the two buffers to hash
+
+	;; [276, 308) output buffer
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		(local $data_len i32)
+		(local $half i32)
+
+		(call $seal_input (i32.const 4) (i32.const 0))
+		(local.set $data_len (i32.sub (i32.load (i32.const 0)) (i32.const 1)))
+		(local.set $half (i32.div_u (local.get $data_len) (i32.const 2)))
+
+		;; First half of the data
+		(i32.store (i32.const 260) (i32.const 5))
+		(i32.store (i32.const 264) (local.get $half))
+
+		;; Second half of the data
+		(i32.store (i32.const 268) (i32.add (i32.const 5) (local.get $half)))
+		(i32.store (i32.const 272) (i32.sub (local.get $data_len) (local.get $half)))
+
+		(if (i32.eqz (i32.load8_u (i32.const 4)))
+			(then
+				(call $hash_keccak_256_multi (i32.const 260) (i32.const 2) (i32.const 276))
+				(call $seal_return (i32.const 0) (i32.const 276) (i32.const 32))
+			)
+		)
+		(call $hash_blake2_128_multi (i32.const 260) (i32.const 2) (i32.const 276))
+		(call $seal_return (i32.const 0) (i32.const 276) (i32.const 16))
+	)
+)
@@ -0,0 +1,60 @@
+;; Calls the contract passed in the input and returns the storage deposit consumed by the call.
+;; The input is the address of the callee followed by the input that is forwarded to it.
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal1" "seal_call" (func $seal_call (param i32 i32 i64 i32 i32 i32 i32 i32) (result i32)))
+	(import "seal0" "last_subcall_deposit" (func $last_subcall_deposit (param i32 i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 4) size of the input buffer
+	(data (i32.const 0) "\00\01")
+
+	;; [4, 260) input buffer: callee address followed by the forwarded input
+
+	;; [260, 268) value to transfer: zero
+
+	;; [268, 272) size of the deposit buffer
+	(data (i32.const 268) "\40")
+
+	;; [272, 336) buffer where the deposit is copied to
+
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok
+				(get_local 0)
+			)
+			(unreachable)
+		)
+	)
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		(call $seal_input (i32.const 4) (i32.const 0))
+
+		(call $assert (i32.eqz
+			(call $seal_call
+				(i32.const 0)	;; No flags
+				(i32.const 4)	;; Pointer to "callee" address
+				(i64.const 0)	;; How much gas to devote for the execution. 0 = all.
+				(i32.const 260)	;; Pointer to the buffer with value to transfer
+				(i32.const 36)	;; Pointer to input data buffer address
+				(i32.sub	;; Length of input data buffer
+					(i32.load (i32.const 0))
+					(i32.const 32)
+				)
+				(i32.const 4294967295)	;; u32 max sentinel value: do not copy output
+				(i32.const 0)	;; Length is ignored in this case
+			)
+		))
+
+		(call $last_subcall_deposit (i32.const 272) (i32.const 268))
+
+		(call $seal_return
+			(i32.const 0)	;; flags
+			(i32.const 272)	;; Pointer to the deposit
+			(i32.load (i32.const 268))	;; Size of the deposit
+		)
+	)
+)
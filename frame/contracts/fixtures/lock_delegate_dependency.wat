@@ -0,0 +1,22 @@
+;; Locks the code hash passed as input on deploy and unlocks it on call.
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "lock_delegate_dependency" (func $lock_delegate_dependency (param i32)))
+	(import "seal0" "unlock_delegate_dependency" (func $unlock_delegate_dependency (param i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 32) here we store the code hash
+
+	;; [32, 36) input size
+	(data (i32.const 32) "\20")
+
+	(func (export "deploy")
+		(call $seal_input (i32.const 0) (i32.const 32))
+		(call $lock_delegate_dependency (i32.const 0))
+	)
+
+	(func (export "call")
+		(call $seal_input (i32.const 0) (i32.const 32))
+		(call $unlock_delegate_dependency (i32.const 0))
+	)
+)
@@ -20,10 +20,10 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use bitflags::bitflags;
-use codec::{Decode, Encode};
+use codec::{Decode, DecodeAll, Encode};
 use sp_runtime::{
 	traits::{Saturating, Zero},
-	DispatchError, RuntimeDebug,
+	DispatchError, ModuleError, RuntimeDebug,
 };
 use sp_std::prelude::*;
 use sp_weights::Weight;
@@ -32,7 +32,7 @@ use sp_weights::Weight;
 ///
 /// It contains the execution result together with some auxiliary information.
 #[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
-pub struct ContractResult<R, Balance> {
+pub struct ContractResult<R, AccountId, Balance, EventRecord> {
 	/// How much weight was consumed during execution.
 	pub gas_consumed: Weight,
 	/// How much weight is required as gas limit in order to execute this call.
@@ -66,32 +66,149 @@ pub struct ContractResult<R, Balance> {
 	/// The debug message is never generated during on-chain execution. It is reserved for
 	/// RPC calls.
 	pub debug_message: Vec<u8>,
+	/// Whether [`Self::debug_message`] is incomplete because it hit the size limit.
+	///
+	/// The output is cut off at the point where the limit was reached and ends with a marker
+	/// that names the contract which emitted the truncated message.
+	pub debug_message_truncated: bool,
+	/// The storage keys of contracts that were written or removed by the execution.
+	///
+	/// Every key appears at most once with the last change made to it. Changes made by calls
+	/// that were reverted are not included. Just as the debug message this is only filled when
+	/// explicitly requested and never during on-chain execution.
+	pub storage_changes: Vec<ContractStorageChange<AccountId>>,
+	/// How much storage and code the execution accessed.
+	///
+	/// Accesses of calls that were reverted are still counted as they were paid for. Just as
+	/// the debug message this is only filled when explicitly requested.
+	pub io_counters: IoCounters,
 	/// The execution result of the wasm code.
 	pub result: R,
+	/// The events that were emitted during execution.
+	///
+	/// # Note
+	///
+	/// Events are only collected when explicitly requested by the code that calls into the
+	/// contract. Otherwise this is `None`. Just as the debug message they are never collected
+	/// during on-chain execution.
+	pub events: Option<Vec<EventRecord>>,
 }
 
 /// Result type of a `bare_call` call.
-pub type ContractExecResult<Balance> =
-	ContractResult<Result<ExecReturnValue, DispatchError>, Balance>;
+pub type ContractExecResult<AccountId, Balance, EventRecord> =
+	ContractResult<Result<ExecReturnValue, DispatchError>, AccountId, Balance, EventRecord>;
 
 /// Result type of a `bare_instantiate` call.
-pub type ContractInstantiateResult<AccountId, Balance> =
-	ContractResult<Result<InstantiateReturnValue<AccountId>, DispatchError>, Balance>;
+pub type ContractInstantiateResult<AccountId, Balance, EventRecord> = ContractResult<
+	Result<InstantiateReturnValue<AccountId>, DispatchError>,
+	AccountId,
+	Balance,
+	EventRecord,
+>;
 
 /// Result type of a `bare_code_upload` call.
 pub type CodeUploadResult<CodeHash, Balance> =
 	Result<CodeUploadReturnValue<CodeHash, Balance>, DispatchError>;
 
+/// A change to the storage of a contract made by a dry-run.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct ContractStorageChange<AccountId> {
+	/// The contract whose storage was changed.
+	pub contract: AccountId,
+	/// The key as supplied by the contract.
+	///
+	/// `None` if the contract terminated which removes all of its storage.
+	pub key: Option<Vec<u8>>,
+	/// Whether the key was removed instead of written.
+	pub removed: bool,
+}
+
+/// Counters for the storage and code accesses of a dry-run.
+#[derive(Clone, Copy, Default, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct IoCounters {
+	/// How many times a storage item of a contract was read.
+	///
+	/// Querying only the size of an item counts as a read of zero bytes.
+	pub storage_reads: u32,
+	/// The number of bytes returned by all storage reads.
+	pub storage_bytes_read: u32,
+	/// How many times a storage item of a contract was written or removed.
+	pub storage_writes: u32,
+	/// The number of bytes written to storage. Removals do not add to this.
+	pub storage_bytes_written: u32,
+	/// The accumulated size of all contract code that was loaded for execution.
+	pub code_bytes_loaded: u32,
+}
+
 /// Result type of a `get_storage` call.
 pub type GetStorageResult = Result<Option<Vec<u8>>, ContractAccessError>;
 
+/// Result type of a `get_storage_keys_paged` call.
+pub type GetStorageKeysResult = Result<Vec<Vec<u8>>, ContractAccessError>;
+
+/// The concrete types and limits a chain configured `pallet-contracts` with.
+///
+/// Allows generic tooling to encode and decode values for the chain without hardcoding them.
+/// All lengths are the maximum encoded length of the respective type in bytes.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct ContractsEnvironment<Balance> {
+	/// The length of an `AccountId`.
+	pub account_id_len: u32,
+	/// The length of a `Balance`.
+	pub balance_len: u32,
+	/// The length of a `Hash`, which is also the type of code hashes.
+	pub hash_len: u32,
+	/// The length of a `BlockNumber`.
+	pub block_number_len: u32,
+	/// The maximum length of an uploaded code blob.
+	pub max_code_len: u32,
+	/// The existential deposit of the currency used by the pallet.
+	pub existential_deposit: Balance,
+}
+
+/// A host function that contracts can import.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct HostFnInfo {
+	/// The module the function is imported from, e.g. `seal1`.
+	pub module: Vec<u8>,
+	/// The name of the function within its module.
+	pub name: Vec<u8>,
+	/// The version of the function, i.e. the `N` of its `sealN` module.
+	pub version: u16,
+	/// Whether the function is part of the stable interface.
+	pub stable: bool,
+}
+
+/// The storage a contract occupies and the deposit it holds for it.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct ContractStorageInfo<Hash, Balance> {
+	/// The code hash the contract is executing.
+	pub code_hash: Hash,
+	/// How many bytes are stored in the contract's child trie.
+	pub storage_bytes: u32,
+	/// How many items are stored in the contract's child trie.
+	pub storage_items: u32,
+	/// The deposit held for `storage_bytes`.
+	pub storage_byte_deposit: Balance,
+	/// The deposit held for `storage_items`.
+	pub storage_item_deposit: Balance,
+	/// The deposit held for `storage_bytes` and `storage_items`.
+	pub storage_deposit: Balance,
+	/// The deposit held for the contract including the part paying for the contract itself.
+	pub total_deposit: Balance,
+}
+
 /// The possible errors that can happen querying the storage of a contract.
 #[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
 pub enum ContractAccessError {
-	/// The given address doesn't point to a contract.
+	/// There is no account at the given address.
 	DoesntExist,
 	/// Storage key cannot be decoded from the provided input data.
 	KeyDecodingFailed,
+	/// There is an account at the given address but it is not a contract.
+	NotAContract,
+	/// The contract at the given address was terminated and its storage is still being deleted.
+	PendingDeletion,
 }
 
 bitflags! {
@@ -117,6 +234,75 @@ impl ExecReturnValue {
 	pub fn did_revert(&self) -> bool {
 		self.flags.contains(ReturnFlags::REVERT)
 	}
+
+	/// Decode the [`RevertReason`] carried in the output buffer of a reverted execution.
+	///
+	/// Returns `None` if the execution did not revert or if the output buffer does not
+	/// contain a standardized revert reason envelope.
+	pub fn revert_reason(&self) -> Option<RevertReason> {
+		if !self.did_revert() {
+			return None
+		}
+		RevertReason::from_envelope(&self.data)
+	}
+}
+
+/// Prefix marking a buffer as a SCALE encoded [`RevertReason`] envelope.
+///
+/// It allows to tell a standardized revert reason apart from arbitrary output data.
+pub const REVERT_REASON_PREFIX: [u8; 4] = *b"RVRT";
+
+/// Standardized description of why a contract execution failed.
+///
+/// It is used by the runtime to describe failures of sub calls that did not originate
+/// from contract code and can be used by contracts themselves when reverting. This allows
+/// clients to display a consistent failure reason regardless of the contract language.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub enum RevertReason {
+	/// The execution failed because of an error emitted by a runtime module.
+	Module {
+		/// Index of the module within the runtime.
+		index: u8,
+		/// Module specific error code.
+		error: [u8; 4],
+	},
+	/// A contract defined error identified by its selector.
+	Custom {
+		/// Selector identifying the error.
+		selector: [u8; 4],
+		/// SCALE encoded arguments of the error.
+		data: Vec<u8>,
+	},
+	/// The execution panicked with the contained UTF-8 message.
+	Panic(Vec<u8>),
+}
+
+impl RevertReason {
+	/// Encode `self` prefixed with [`REVERT_REASON_PREFIX`].
+	pub fn to_envelope(&self) -> Vec<u8> {
+		let mut envelope = REVERT_REASON_PREFIX.to_vec();
+		self.encode_to(&mut envelope);
+		envelope
+	}
+
+	/// Decode a revert reason from a buffer created by [`Self::to_envelope`].
+	///
+	/// Returns `None` if the buffer is not prefixed with [`REVERT_REASON_PREFIX`] or if the
+	/// remaining bytes are not exactly one encoded [`RevertReason`].
+	pub fn from_envelope(buf: &[u8]) -> Option<Self> {
+		let mut payload = buf.strip_prefix(&REVERT_REASON_PREFIX[..])?;
+		Self::decode_all(&mut payload).ok()
+	}
+}
+
+impl From<DispatchError> for RevertReason {
+	fn from(error: DispatchError) -> Self {
+		match error {
+			DispatchError::Module(ModuleError { index, error, .. }) =>
+				RevertReason::Module { index, error },
+			error => RevertReason::Panic(<&'static str>::from(error).as_bytes().to_vec()),
+		}
+	}
 }
 
 /// The result of a successful contract instantiation.
@@ -126,6 +312,14 @@ pub struct InstantiateReturnValue<AccountId> {
 	pub result: ExecReturnValue,
 	/// The account id of the new contract.
 	pub account_id: AccountId,
+	/// The id of the child trie that holds the storage of the new contract.
+	///
+	/// It is derived from the address and a global nonce. A contract that is terminated and
+	/// instantiated at the same address again therefore receives a fresh trie. `None` if the
+	/// constructor reverted and no contract was created.
+	pub trie_id: Option<Vec<u8>>,
+	/// The account that holds the storage deposit of the new contract.
+	pub deposit_account: AccountId,
 }
 
 /// The result of succesfully uploading a contract.
@@ -479,9 +479,34 @@ fn expand_docs(def: &mut EnvDef) -> TokenStream2 {
 fn expand_env(def: &mut EnvDef, docs: bool) -> TokenStream2 {
 	let impls = expand_impls(def);
 	let docs = docs.then_some(expand_docs(def)).unwrap_or(TokenStream2::new());
+	let max_module_version = def
+		.host_funcs
+		.iter()
+		.filter_map(|f| f.module.strip_prefix("seal").and_then(|v| v.parse::<u16>().ok()))
+		.max()
+		.unwrap_or_default();
+	let host_functions = def.host_funcs.iter().map(|f| {
+		let (module, name, is_stable) = (&f.module, &f.name, f.is_stable);
+		let version = f
+			.module
+			.strip_prefix("seal")
+			.and_then(|v| v.parse::<u16>().ok())
+			.unwrap_or_default();
+		quote! { (#module, #name, #version, #is_stable) }
+	});
 
 	quote! {
 		pub struct Env;
+		impl Env {
+			/// The highest `N` of all the `sealN` modules that define host functions.
+			pub const MAX_MODULE_VERSION: u16 = #max_module_version;
+
+			/// Every host function as `(module, name, version, is_stable)`.
+			///
+			/// The version is the `N` of the `sealN` module the function is defined in.
+			pub const HOST_FUNCTIONS: &'static [(&'static str, &'static str, u16, bool)] =
+				&[#( #host_functions ),*];
+		}
 		#impls
 		/// Contains the documentation of the API available to contracts.
 		///
@@ -550,7 +575,7 @@ fn expand_functions(
 		let inner = if expand_blocks {
 			quote! { || #output {
 				let (memory, ctx) = __caller__
-					.host_data()
+					.data()
 					.memory()
 					.expect("Memory must be set when setting up host data; qed")
 					.data_and_store_mut(&mut __caller__);
@@ -567,7 +592,7 @@ fn expand_functions(
 		let map_err = if expand_blocks {
 			quote! {
 				|reason| {
-					::wasmi::core::Trap::host(reason)
+					::wasmi::core::Trap::from(reason)
 				}
 			}
 		} else {
@@ -575,6 +600,29 @@ fn expand_functions(
 				|reason| { reason }
 			}
 		};
+		// The real implementation keeps the gas meter and the engine fuel in sync: fuel consumed
+		// by the engine is charged before the host function runs and the gas charged by the host
+		// function is burned from the engine fuel afterwards.
+		let call = if expand_blocks {
+			quote! {
+				let __gas_before__ = crate::wasm::Runtime::charge_engine_fuel(&mut __caller__)
+					.map_err(#map_err)?;
+				let result = {
+					let mut func = #inner;
+					func()
+				};
+				if result.is_ok() {
+					crate::wasm::Runtime::sync_host_fuel(&mut __caller__, __gas_before__)
+						.map_err(#map_err)?;
+				}
+				result
+			}
+		} else {
+			quote! {
+				let mut func = #inner;
+				func()
+			}
+		};
 		let allow_unused =  if expand_blocks {
 			quote! { }
 		} else {
@@ -587,8 +635,8 @@ fn expand_functions(
 			if ::core::cfg!(feature = "runtime-benchmarks") || #is_stable || allow_unstable {
 				#allow_unused
 				linker.define(#module, #name, ::wasmi::Func::wrap(&mut*store, |mut __caller__: ::wasmi::Caller<#host_state>, #( #params, )*| -> #wasm_output {
-					let mut func = #inner;
-					func()
+					let result = { #call };
+					result
 						.map_err(#map_err)
 						.map(::core::convert::Into::into)
 				}))?;
@@ -0,0 +1,148 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A standardized way for contracts to interact with the fungible assets of a chain.
+//!
+//! Contracts can query and transfer assets through the `seal_asset_balance_of`,
+//! `seal_asset_total_supply` and `seal_asset_transfer` host functions. The runtime author
+//! implements the [`Assets`] trait and declares it in this pallet's
+//! [configuration Trait](crate::Config). Chains that implement the `fungibles` traits can use
+//! [`FungiblesAdapter`] instead of writing their own implementation.
+//!
+//! Asset ids and balances are handed over as SCALE encoded bytes exactly as the contract
+//! supplied them. With [`FungiblesAdapter`] they are the SCALE encoding of the `AssetId` and
+//! `Balance` types of the `fungibles` implementation.
+//!
+//! There is an implementation on `()` which can be used to signal that assets are not available.
+//! Calling one of the asset host functions traps the contract with [`Error::AssetsDisabled`] in
+//! this case.
+
+use crate::{Config, Error};
+use codec::{Decode, DecodeAll, Encode};
+use frame_support::{
+	dispatch::DispatchResult,
+	traits::{tokens::fungibles, Get},
+	weights::Weight,
+};
+use sp_runtime::DispatchError;
+use sp_std::{marker::PhantomData, prelude::*};
+
+/// Allows contracts to query and transfer fungible assets.
+///
+/// Consult the [module documentation](self) for a general explanation.
+pub trait Assets<T: Config> {
+	/// Whether contracts are allowed to use assets at all.
+	const ENABLED: bool = true;
+
+	/// The weight of a call to [`Self::balance_of`] or [`Self::total_supply`].
+	fn weigh_query() -> Weight;
+
+	/// The weight of a call to [`Self::transfer`].
+	fn weigh_transfer() -> Weight;
+
+	/// The encoded balance of `who` in the encoded `asset`.
+	fn balance_of(asset: &[u8], who: &T::AccountId) -> Result<Vec<u8>, DispatchError>;
+
+	/// The encoded total supply of the encoded `asset`.
+	fn total_supply(asset: &[u8]) -> Result<Vec<u8>, DispatchError>;
+
+	/// Transfer the encoded `amount` of the encoded `asset` from `from` to `to`.
+	///
+	/// The account of `from` must be kept alive.
+	fn transfer(
+		asset: &[u8],
+		from: &T::AccountId,
+		to: &T::AccountId,
+		amount: &[u8],
+	) -> DispatchResult;
+}
+
+/// Implementation that indicates that assets are not available to contracts.
+impl<T: Config> Assets<T> for () {
+	const ENABLED: bool = false;
+
+	fn weigh_query() -> Weight {
+		Weight::zero()
+	}
+
+	fn weigh_transfer() -> Weight {
+		Weight::zero()
+	}
+
+	fn balance_of(_asset: &[u8], _who: &T::AccountId) -> Result<Vec<u8>, DispatchError> {
+		Err(Error::<T>::AssetsDisabled.into())
+	}
+
+	fn total_supply(_asset: &[u8]) -> Result<Vec<u8>, DispatchError> {
+		Err(Error::<T>::AssetsDisabled.into())
+	}
+
+	fn transfer(
+		_asset: &[u8],
+		_from: &T::AccountId,
+		_to: &T::AccountId,
+		_amount: &[u8],
+	) -> DispatchResult {
+		Err(Error::<T>::AssetsDisabled.into())
+	}
+}
+
+/// Implements [`Assets`] for a type implementing the `fungibles` traits.
+///
+/// `QueryWeight` and `TransferWeight` are the weights returned by [`Assets::weigh_query`] and
+/// [`Assets::weigh_transfer`] respectively.
+pub struct FungiblesAdapter<F, QueryWeight, TransferWeight>(
+	PhantomData<(F, QueryWeight, TransferWeight)>,
+);
+
+impl<T, F, QueryWeight, TransferWeight> Assets<T>
+	for FungiblesAdapter<F, QueryWeight, TransferWeight>
+where
+	T: Config,
+	F: fungibles::Transfer<T::AccountId>,
+	QueryWeight: Get<Weight>,
+	TransferWeight: Get<Weight>,
+{
+	fn weigh_query() -> Weight {
+		QueryWeight::get()
+	}
+
+	fn weigh_transfer() -> Weight {
+		TransferWeight::get()
+	}
+
+	fn balance_of(asset: &[u8], who: &T::AccountId) -> Result<Vec<u8>, DispatchError> {
+		Ok(F::balance(decode::<T, _>(asset)?, who).encode())
+	}
+
+	fn total_supply(asset: &[u8]) -> Result<Vec<u8>, DispatchError> {
+		Ok(F::total_issuance(decode::<T, _>(asset)?).encode())
+	}
+
+	fn transfer(
+		asset: &[u8],
+		from: &T::AccountId,
+		to: &T::AccountId,
+		amount: &[u8],
+	) -> DispatchResult {
+		F::transfer(decode::<T, _>(asset)?, from, to, decode::<T, _>(amount)?, true).map(|_| ())
+	}
+}
+
+fn decode<T: Config, V: Decode>(mut input: &[u8]) -> Result<V, DispatchError> {
+	V::decode_all(&mut input).map_err(|_| Error::<T>::DecodingFailed.into())
+}
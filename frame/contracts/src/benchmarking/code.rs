@@ -24,18 +24,15 @@
 //! we define this simple definition of a contract that can be passed to `create_code` that
 //! compiles it down into a `WasmModule` that can be used as a contract's code.
 
-use crate::{Config, Determinism};
+use crate::Config;
 use frame_support::traits::Get;
 use sp_runtime::traits::Hash;
 use sp_std::{borrow::ToOwned, prelude::*};
-use wasm_instrument::{
-	gas_metering,
-	parity_wasm::{
-		builder,
-		elements::{
-			self, BlockType, CustomSection, External, FuncBody, Instruction, Instructions, Module,
-			Section, ValueType,
-		},
+use wasm_instrument::parity_wasm::{
+	builder,
+	elements::{
+		self, BlockType, CustomSection, External, FuncBody, Instruction, Instructions, Module,
+		Section, ValueType,
 	},
 };
 
@@ -240,15 +237,9 @@ impl<T: Config> From<ModuleDefinition> for WasmModule<T> {
 }
 
 impl<T: Config> WasmModule<T> {
-	/// Uses the supplied wasm module and instruments it when requested.
-	pub fn instrumented(code: &[u8], inject_gas: bool) -> Self {
-		let module = {
-			let mut module = Module::from_bytes(code).unwrap();
-			if inject_gas {
-				module = inject_gas_metering::<T>(module);
-			}
-			module
-		};
+	/// Uses the supplied wasm module as is.
+	pub fn from_code(code: &[u8]) -> Self {
+		let module = Module::from_bytes(code).unwrap();
 		let limits = *module
 			.import_section()
 			.unwrap()
@@ -286,7 +277,7 @@ impl<T: Config> WasmModule<T> {
 
 	/// Creates a wasm module of `target_bytes` size. Used to benchmark the performance of
 	/// `instantiate_with_code` for different sizes of wasm modules. The generated module maximizes
-	/// instrumentation runtime by nesting blocks as deeply as possible given the byte budget.
+	/// validation runtime by nesting blocks as deeply as possible given the byte budget.
 	/// `code_location`: Whether to place the code into `deploy` or `call`.
 	pub fn sized(target_bytes: u32, code_location: Location) -> Self {
 		use self::elements::Instruction::{End, I32Const, If, Return};
@@ -509,10 +500,3 @@ pub mod body {
 pub fn max_pages<T: Config>() -> u32 {
 	T::Schedule::get().limits.memory_pages
 }
-
-fn inject_gas_metering<T: Config>(module: Module) -> Module {
-	let schedule = T::Schedule::get();
-	let gas_rules = schedule.rules(&module, Determinism::Deterministic);
-	let backend = gas_metering::host_function::Injector::new("seal0", "gas");
-	gas_metering::inject(module, backend, &gas_rules).unwrap()
-}
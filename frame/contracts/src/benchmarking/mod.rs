@@ -31,13 +31,14 @@ use self::{
 };
 use crate::{
 	exec::{AccountIdOf, FixSizedKey, VarSizedKey},
+	migration::{v11, IsFinished, MigrationStep},
 	schedule::{API_BENCHMARK_BATCH_SIZE, INSTR_BENCHMARK_BATCH_SIZE},
 	storage::Storage,
 	wasm::CallFlags,
 	Pallet as Contracts, *,
 };
 use codec::{Encode, MaxEncodedLen};
-use frame_benchmarking::{account, benchmarks, whitelisted_caller};
+use frame_benchmarking::{account, benchmarks, whitelisted_caller, BenchmarkError};
 use frame_support::weights::Weight;
 use frame_system::RawOrigin;
 use sp_runtime::{
@@ -148,7 +149,7 @@ where
 
 	/// Get the `ContractInfo` of the `addr` or an error if it no longer exists.
 	fn address_info(addr: &T::AccountId) -> Result<ContractInfo<T>, &'static str> {
-		ContractInfoOf::<T>::get(addr).ok_or("Expected contract to exist at this point.")
+		ContractInfo::<T>::load(addr).ok_or("Expected contract to exist at this point.")
 	}
 
 	/// Get the `ContractInfo` of this contract or an error if it no longer exists.
@@ -163,16 +164,12 @@ where
 
 	/// Returns `true` iff all storage entries related to code storage exist.
 	fn code_exists(hash: &CodeHash<T>) -> bool {
-		<PristineCode<T>>::contains_key(hash) &&
-			<CodeStorage<T>>::contains_key(&hash) &&
-			<OwnerInfoOf<T>>::contains_key(&hash)
+		<PristineCode<T>>::contains_key(hash) && <CodeInfoOf<T>>::contains_key(&hash)
 	}
 
 	/// Returns `true` iff no storage entry related to code storage exist.
 	fn code_removed(hash: &CodeHash<T>) -> bool {
-		!<PristineCode<T>>::contains_key(hash) &&
-			!<CodeStorage<T>>::contains_key(&hash) &&
-			!<OwnerInfoOf<T>>::contains_key(&hash)
+		!<PristineCode<T>>::contains_key(hash) && !<CodeInfoOf<T>>::contains_key(&hash)
 	}
 }
 
@@ -209,11 +206,31 @@ benchmarks! {
 		Storage::<T>::process_deletion_queue_batch(Weight::MAX)
 	}
 
+	// The base weight of checking for a multi block migration. The steps themselves are
+	// accounted for by `MigrationStep::step`.
+	migrate {
+		<MigrationInProgress<T>>::kill();
+	}: {
+		Migration::<T>::migrate(Weight::MAX)
+	}
+
+	// Moving the storage deposit of one contract to its deposit account.
+	v11_migration_step {
+		let instance = Contract::<T>::with_caller(whitelisted_caller(), WasmModule::dummy(), vec![])?;
+		v11::store_old_contract_info::<T>(&instance.account_id, &instance.info()?);
+		let mut migration = v11::Migration::<T>::default();
+	}: {
+		migration.step()
+	}
+	verify {
+		assert_eq!(migration.step().0, IsFinished::Yes);
+	}
+
 	#[skip_meta]
 	on_initialize_per_trie_key {
 		let k in 0..1024;
 		let instance = Contract::<T>::with_storage(WasmModule::dummy(), k, T::Schedule::get().limits.payload_len)?;
-		Storage::<T>::queue_trie_for_deletion(&instance.info()?)?;
+		Storage::<T>::queue_trie_for_deletion(&instance.info()?, &instance.account_id)?;
 	}: {
 		Storage::<T>::process_deletion_queue_batch(Weight::MAX)
 	}
@@ -222,27 +239,13 @@ benchmarks! {
 		let q in 0..1024.min(T::DeletionQueueDepth::get());
 		for i in 0 .. q {
 			let instance = Contract::<T>::with_index(i, WasmModule::dummy(), vec![])?;
-			Storage::<T>::queue_trie_for_deletion(&instance.info()?)?;
+			Storage::<T>::queue_trie_for_deletion(&instance.info()?, &instance.account_id)?;
 			ContractInfoOf::<T>::remove(instance.account_id);
 		}
 	}: {
 		Storage::<T>::process_deletion_queue_batch(Weight::MAX)
 	}
 
-	// This benchmarks the additional weight that is charged when a contract is executed the
-	// first time after a new schedule was deployed: For every new schedule a contract needs
-	// to re-run the instrumentation once.
-	reinstrument {
-		let c in 0 .. Perbill::from_percent(49).mul_ceil(T::MaxCodeLen::get());
-		let WasmModule { code, hash, .. } = WasmModule::<T>::sized(c, Location::Call);
-		Contracts::<T>::store_code_raw(code, whitelisted_caller())?;
-		let schedule = T::Schedule::get();
-		let mut gas_meter = GasMeter::new(Weight::MAX);
-		let mut module = PrefabWasmModule::from_storage(hash, &schedule, &mut gas_meter)?;
-	}: {
-		Contracts::<T>::reinstrument_module(&mut module, &schedule)?;
-	}
-
 	// This benchmarks the overhead of loading a code of size `c` byte from storage and into
 	// the sandbox. This does **not** include the actual execution for which the gas meter
 	// is responsible. This is achieved by generating all code to the `deploy` function
@@ -287,18 +290,18 @@ benchmarks! {
 		let addr = Contracts::<T>::contract_address(&caller, &hash, &input, &salt);
 	}: _(origin, value, Weight::MAX, None, code, input, salt)
 	verify {
-		// the contract itself does not trigger any reserves
-		let deposit = T::Currency::reserved_balance(&addr);
+		// instantiate should leave a contract
+		let deposit_account = Contract::<T>::address_info(&addr)?.deposit_account;
+		// the storage deposit is held by the deposit account
+		let deposit = T::Currency::free_balance(&deposit_account);
 		// uploading the code reserves some balance in the callers account
 		let code_deposit = T::Currency::reserved_balance(&caller);
 		assert_eq!(
 			T::Currency::free_balance(&caller),
-			caller_funding::<T>() - value - deposit - code_deposit,
+			caller_funding::<T>() - value - deposit - code_deposit - Pallet::<T>::min_balance(),
 		);
-		// contract has the full value
-		assert_eq!(T::Currency::free_balance(&addr), value);
-		// instantiate should leave a contract
-		Contract::<T>::address_info(&addr)?;
+		// contract has the full value on top of its minimum balance
+		assert_eq!(T::Currency::free_balance(&addr), value + Pallet::<T>::min_balance());
 	}
 
 	// Instantiate uses a dummy contract constructor to measure the overhead of the instantiate.
@@ -318,14 +321,17 @@ benchmarks! {
 		Contracts::<T>::store_code_raw(code, caller.clone())?;
 	}: _(origin, value, Weight::MAX, None, hash, input, salt)
 	verify {
-		// the contract itself does not trigger any reserves
-		let deposit = T::Currency::reserved_balance(&addr);
-		// value was removed from the caller
-		assert_eq!(T::Currency::free_balance(&caller), caller_funding::<T>() - value - deposit);
-		// contract has the full value
-		assert_eq!(T::Currency::free_balance(&addr), value);
 		// instantiate should leave a contract
-		Contract::<T>::address_info(&addr)?;
+		let deposit_account = Contract::<T>::address_info(&addr)?.deposit_account;
+		// the storage deposit is held by the deposit account
+		let deposit = T::Currency::free_balance(&deposit_account);
+		// value was removed from the caller
+		assert_eq!(
+			T::Currency::free_balance(&caller),
+			caller_funding::<T>() - value - deposit - Pallet::<T>::min_balance(),
+		);
+		// contract has the full value on top of its minimum balance
+		assert_eq!(T::Currency::free_balance(&addr), value + Pallet::<T>::min_balance());
 	}
 
 	// We just call a dummy contract to measure the overhead of the call extrinsic.
@@ -346,12 +352,12 @@ benchmarks! {
 		let before = T::Currency::free_balance(&instance.account_id);
 	}: _(origin, callee, value, Weight::MAX, None, data)
 	verify {
-		// the contract itself does not trigger any reserves
-		let deposit = T::Currency::reserved_balance(&instance.account_id);
+		// the storage deposit is held by the deposit account
+		let deposit = T::Currency::free_balance(&instance.info()?.deposit_account);
 		// value and value transfered via call should be removed from the caller
 		assert_eq!(
 			T::Currency::free_balance(&instance.caller),
-			caller_funding::<T>() - instance.value - value - deposit,
+			caller_funding::<T>() - instance.value - value - deposit - Pallet::<T>::min_balance(),
 		);
 		// contract should have received the value
 		assert_eq!(T::Currency::free_balance(&instance.account_id), before + value);
@@ -382,7 +388,7 @@ benchmarks! {
 
 	// Removing code does not depend on the size of the contract because all the information
 	// needed to verify the removal claim (refcount, owner) is stored in a separate storage
-	// item (`OwnerInfoOf`).
+	// item (`CodeInfoOf`).
 	remove_code {
 		let caller = whitelisted_caller();
 		T::Currency::make_free_balance_be(&caller, caller_funding::<T>());
@@ -413,6 +419,79 @@ benchmarks! {
 		assert_eq!(instance.info()?.code_hash, hash);
 	}
 
+	set_call_filter {
+		let instance = <Contract<T>>::with_caller(
+			whitelisted_caller(), WasmModule::dummy(), vec![],
+		)?;
+		let call_filter: CallFilter<T> = (0..T::MaxCallFilterLen::get())
+			.map(|i| (i as u16).to_le_bytes())
+			.collect::<Vec<_>>()
+			.try_into()
+			.unwrap();
+		let callee = instance.addr.clone();
+	}: _(RawOrigin::Root, callee, Some(call_filter.clone()))
+	verify {
+		assert_eq!(instance.info()?.call_filter, Some(call_filter));
+	}
+
+	pause_contract {
+		let instance = <Contract<T>>::with_caller(
+			whitelisted_caller(), WasmModule::dummy(), vec![],
+		)?;
+		let callee = instance.addr.clone();
+	}: _(RawOrigin::Root, callee)
+	verify {
+		assert!(instance.info()?.paused);
+	}
+
+	unpause_contract {
+		let instance = <Contract<T>>::with_caller(
+			whitelisted_caller(), WasmModule::dummy(), vec![],
+		)?;
+		let callee = instance.addr.clone();
+		<Contracts<T>>::pause_contract(RawOrigin::Root.into(), callee.clone())?;
+	}: _(RawOrigin::Root, callee)
+	verify {
+		assert!(!instance.info()?.paused);
+	}
+
+	set_schedule {
+		let origin = T::ScheduleOrigin::try_successful_origin()
+			.map_err(|_| BenchmarkError::Weightless)?;
+		let mut schedule = T::Schedule::get();
+		schedule.instruction_weights.version += 1;
+	}: _<T::RuntimeOrigin>(origin, Some(Box::new(schedule.clone())))
+	verify {
+		assert_eq!(Contracts::<T>::schedule(), schedule);
+	}
+
+	// The code is still in use by a contract.
+	force_remove_code {
+		let instance = <Contract<T>>::with_caller(
+			whitelisted_caller(), WasmModule::dummy(), vec![],
+		)?;
+		let hash = instance.info()?.code_hash;
+	}: _(RawOrigin::Root, hash)
+	verify {
+		assert!(<Contract<T>>::code_removed(&hash));
+		assert!(<TombstonedCode<T>>::contains_key(&hash));
+	}
+
+	// The worst case is replacing existing metadata as this refunds the old deposit.
+	set_code_metadata {
+		let l in 0 .. T::MaxCodeMetadataLen::get();
+		let caller = whitelisted_caller();
+		T::Currency::make_free_balance_be(&caller, caller_funding::<T>());
+		let WasmModule { code, hash, .. } = WasmModule::<T>::dummy();
+		let origin = RawOrigin::Signed(caller.clone());
+		<Contracts<T>>::bare_upload_code(caller.clone(), code, None, Determinism::Deterministic)?;
+		<Contracts<T>>::set_code_metadata(origin.clone().into(), hash, Some(Default::default()))?;
+		let metadata: CodeMetadataVec<T> = vec![42u8; l as usize].try_into().unwrap();
+	}: _(origin, hash, Some(metadata.clone()))
+	verify {
+		assert_eq!(<Contracts<T>>::code_metadata(hash), Some(metadata.into_inner()));
+	}
+
 	seal_caller {
 		let r in 0 .. API_BENCHMARK_BATCHES;
 		let instance = Contract::<T>::new(WasmModule::getter(
@@ -614,26 +693,6 @@ benchmarks! {
 		let origin = RawOrigin::Signed(instance.caller.clone());
 	}: call(origin, instance.addr, 0u32.into(), Weight::MAX, None, vec![])
 
-	seal_gas {
-		let r in 0 .. API_BENCHMARK_BATCHES;
-		let code = WasmModule::<T>::from(ModuleDefinition {
-			imported_functions: vec![ImportedFunction {
-				module: "seal0",
-				name: "gas",
-				params: vec![ValueType::I64],
-				return_type: None,
-			}],
-			call_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
-				Instruction::I64Const(42),
-				Instruction::Call(0),
-			])),
-			.. Default::default()
-		});
-		let instance = Contract::<T>::new(code, vec![])?;
-		let origin = RawOrigin::Signed(instance.caller.clone());
-
-	}: call(origin, instance.addr, 0u32.into(), Weight::MAX, None, vec![])
-
 	seal_input {
 		let r in 0 .. API_BENCHMARK_BATCHES;
 		let code = WasmModule::<T>::from(ModuleDefinition {
@@ -768,14 +827,22 @@ benchmarks! {
 		});
 		let instance = Contract::<T>::new(code, vec![])?;
 		let origin = RawOrigin::Signed(instance.caller.clone());
+		let deposit_account = instance.info()?.deposit_account;
 		assert_eq!(T::Currency::total_balance(&beneficiary), 0u32.into());
-		assert_eq!(T::Currency::free_balance(&instance.account_id), Pallet::<T>::min_balance());
-		assert_ne!(T::Currency::reserved_balance(&instance.account_id), 0u32.into());
+		assert_eq!(
+			T::Currency::free_balance(&instance.account_id),
+			Pallet::<T>::min_balance() * 2u32.into(),
+		);
+		assert_ne!(T::Currency::free_balance(&deposit_account), 0u32.into());
 	}: call(origin, instance.addr.clone(), 0u32.into(), Weight::MAX, None, vec![])
 	verify {
 		if r > 0 {
 			assert_eq!(T::Currency::total_balance(&instance.account_id), 0u32.into());
-			assert_eq!(T::Currency::total_balance(&beneficiary), Pallet::<T>::min_balance());
+			assert_eq!(T::Currency::total_balance(&deposit_account), 0u32.into());
+			assert_eq!(
+				T::Currency::total_balance(&beneficiary),
+				Pallet::<T>::min_balance() * 2u32.into(),
+			);
 		}
 	}
 
@@ -844,7 +911,7 @@ benchmarks! {
 	// `n`: Size of event payload in kb
 	seal_deposit_event_per_topic_and_kb {
 		let t in 0 .. T::Schedule::get().limits.event_topics;
-		let n in 0 .. T::Schedule::get().limits.payload_len / 1024;
+		let n in 0 .. T::Schedule::get().limits.event_data_len / 1024;
 		let mut topics = (0..API_BENCHMARK_BATCH_SIZE)
 			.map(|n| (n * t..n * t + t).map(|i| T::Hashing::hash_of(&i)).collect::<Vec<_>>().encode())
 			.peekable();
@@ -2161,6 +2228,118 @@ benchmarks! {
 		let origin = RawOrigin::Signed(instance.caller.clone());
 	}: call(origin, instance.addr, 0u32.into(), Weight::MAX, None, vec![])
 
+	seal_storage_deposit_limit_left {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let instance = Contract::<T>::new(WasmModule::getter(
+			"seal0", "seal_storage_deposit_limit_left", r * API_BENCHMARK_BATCH_SIZE
+		), vec![])?;
+		let origin = RawOrigin::Signed(instance.caller.clone());
+	}: call(origin, instance.addr, 0u32.into(), Weight::MAX, None, vec![])
+
+	seal_lock_delegate_dependency {
+		let r in 0 .. T::MaxDelegateDependencies::get();
+		// Smaller modules would all be padded to the same code hash.
+		let code_hashes = (0..r)
+			.map(|i| {
+				let new_code = WasmModule::<T>::dummy_with_bytes(65 + i);
+				Contracts::<T>::store_code_raw(new_code.code, whitelisted_caller())?;
+				Ok(new_code.hash)
+			})
+			.collect::<Result<Vec<_>, &'static str>>()?;
+		let code_hash_len = code_hashes.get(0).map(|x| x.encode().len()).unwrap_or(0);
+		let code_hashes_bytes = code_hashes.iter().flat_map(|x| x.encode()).collect::<Vec<_>>();
+
+		let code = WasmModule::<T>::from(ModuleDefinition {
+			memory: Some(ImportedMemory::max::<T>()),
+			imported_functions: vec![ImportedFunction {
+				module: "seal0",
+				name: "lock_delegate_dependency",
+				params: vec![ValueType::I32],
+				return_type: None,
+			}],
+			data_segments: vec![
+				DataSegment {
+					offset: 0,
+					value: code_hashes_bytes,
+				},
+			],
+			call_body: Some(body::repeated_dyn(r, vec![
+				Counter(0, code_hash_len as u32), // code_hash_ptr
+				Regular(Instruction::Call(0)),
+			])),
+			.. Default::default()
+		});
+		let instance = Contract::<T>::new(code, vec![])?;
+		let origin = RawOrigin::Signed(instance.caller.clone());
+	}: call(origin, instance.addr, 0u32.into(), Weight::MAX, None, vec![])
+
+	seal_unlock_delegate_dependency {
+		let r in 0 .. T::MaxDelegateDependencies::get();
+		// Smaller modules would all be padded to the same code hash.
+		let code_hashes = (0..r)
+			.map(|i| {
+				let new_code = WasmModule::<T>::dummy_with_bytes(65 + i);
+				Contracts::<T>::store_code_raw(new_code.code, whitelisted_caller())?;
+				Ok(new_code.hash)
+			})
+			.collect::<Result<Vec<_>, &'static str>>()?;
+		let code_hash_len = code_hashes.get(0).map(|x| x.encode().len()).unwrap_or(0);
+		let code_hashes_bytes = code_hashes.iter().flat_map(|x| x.encode()).collect::<Vec<_>>();
+
+		// The dependencies are locked by the constructor so that only unlocking is measured.
+		let code = WasmModule::<T>::from(ModuleDefinition {
+			memory: Some(ImportedMemory::max::<T>()),
+			imported_functions: vec![ImportedFunction {
+				module: "seal0",
+				name: "unlock_delegate_dependency",
+				params: vec![ValueType::I32],
+				return_type: None,
+			}, ImportedFunction {
+				module: "seal0",
+				name: "lock_delegate_dependency",
+				params: vec![ValueType::I32],
+				return_type: None,
+			}],
+			data_segments: vec![
+				DataSegment {
+					offset: 0,
+					value: code_hashes_bytes,
+				},
+			],
+			deploy_body: Some(body::repeated_dyn(r, vec![
+				Counter(0, code_hash_len as u32), // code_hash_ptr
+				Regular(Instruction::Call(1)),
+			])),
+			call_body: Some(body::repeated_dyn(r, vec![
+				Counter(0, code_hash_len as u32), // code_hash_ptr
+				Regular(Instruction::Call(0)),
+			])),
+			.. Default::default()
+		});
+		let instance = Contract::<T>::new(code, vec![])?;
+		let origin = RawOrigin::Signed(instance.caller.clone());
+	}: call(origin, instance.addr, 0u32.into(), Weight::MAX, None, vec![])
+
+	seal_set_deny_reentry {
+		let r in 0 .. API_BENCHMARK_BATCHES;
+		let code = WasmModule::<T>::from(ModuleDefinition {
+			memory: Some(ImportedMemory::max::<T>()),
+			imported_functions: vec![ImportedFunction {
+				module: "seal0",
+				name: "seal_set_deny_reentry",
+				params: vec![ValueType::I32],
+				return_type: None,
+			}],
+			call_body: Some(body::repeated(r * API_BENCHMARK_BATCH_SIZE, &[
+				Instruction::I32Const(1),
+				Instruction::Call(0),
+			])),
+			.. Default::default()
+		});
+		let instance = Contract::<T>::new(code, vec![])?;
+		let origin = RawOrigin::Signed(instance.caller.clone());
+	}: call(origin, instance.addr, 0u32.into(), Weight::MAX, None, vec![])
+
 	// We make the assumption that pushing a constant and dropping a value takes roughly
 	// the same amount of time. We follow that `t.load` and `drop` both have the weight
 	// of this benchmark / 2. We need to make this assumption because there is no way
@@ -2952,20 +3131,15 @@ benchmarks! {
 	}: {}
 
 	// Execute one erc20 transfer using the ink! erc20 example contract.
-	//
-	// `g` is used to enable gas instrumentation to compare the performance impact of
-	// that instrumentation at runtime.
 	#[extra]
 	ink_erc20_transfer {
-		let g in 0 .. 1;
-		let gas_metering = g != 0;
 		let code = load_benchmark!("ink_erc20");
 		let data = {
 			let new: ([u8; 4], BalanceOf<T>) = ([0x9b, 0xae, 0x9d, 0x5e], 1000u32.into());
 			new.encode()
 		};
 		let instance = Contract::<T>::new(
-			WasmModule::instrumented(code, gas_metering), data,
+			WasmModule::from_code(code), data,
 		)?;
 		let data = {
 			let transfer: ([u8; 4], AccountIdOf<T>, BalanceOf<T>) = (
@@ -2977,26 +3151,22 @@ benchmarks! {
 		};
 	}: {
 		<Contracts<T>>::bare_call(
-			instance.caller,
+			CallOrigin::from_account_id(instance.caller),
 			instance.account_id,
 			0u32.into(),
 			Weight::MAX,
 			None,
 			data,
 			false,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		)
 		.result?;
 	}
 
 	// Execute one erc20 transfer using the open zeppelin erc20 contract compiled with solang.
-	//
-	// `g` is used to enable gas instrumentation to compare the performance impact of
-	// that instrumentation at runtime.
 	#[extra]
 	solang_erc20_transfer {
-		let g in 0 .. 1;
-		let gas_metering = g != 0;
 		let code = include_bytes!("../../benchmarks/solang_erc20.wasm");
 		let caller = account::<T::AccountId>("instantiator", 0, 0);
 		let mut balance = [0u8; 32];
@@ -3012,7 +3182,7 @@ benchmarks! {
 			new.encode()
 		};
 		let instance = Contract::<T>::with_caller(
-			caller, WasmModule::instrumented(code, gas_metering), data,
+			caller, WasmModule::from_code(code), data,
 		)?;
 		balance[0] = 1;
 		let data = {
@@ -3025,13 +3195,14 @@ benchmarks! {
 		};
 	}: {
 		<Contracts<T>>::bare_call(
-			instance.caller,
+			CallOrigin::from_account_id(instance.caller),
 			instance.account_id,
 			0u32.into(),
 			Weight::MAX,
 			None,
 			data,
 			false,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		)
 		.result?;
@@ -19,8 +19,9 @@
 /// ! sandbox to execute the wasm code. This is because we do not need the full
 /// ! environment that provides the seal interface as imported functions.
 use super::{code::WasmModule, Config};
-use crate::wasm::{Environment, PrefabWasmModule};
-use wasmi::{errors::LinkerError, Func, Linker, StackLimits, Store};
+use crate::wasm::{Determinism, Environment, PrefabWasmModule};
+use frame_support::traits::Get;
+use wasmi::{errors::LinkerError, Func, Linker, Store};
 
 /// Minimal execution environment without any imported functions.
 pub struct Sandbox {
@@ -38,19 +39,20 @@ impl Sandbox {
 impl<T: Config> From<&WasmModule<T>> for Sandbox {
 	/// Creates an instance from the supplied module and supplies as much memory
 	/// to the instance as the module declares as imported.
+	///
+	/// The instance gets all the fuel it could ever need so that we measure the
+	/// instructions and not running out of fuel.
 	fn from(module: &WasmModule<T>) -> Self {
-		let memory = module
-			.memory
-			.as_ref()
-			.map(|mem| (mem.min_pages, mem.max_pages))
-			.unwrap_or((0, 0));
-		let (store, _memory, instance) = PrefabWasmModule::<T>::instantiate::<EmptyEnv, _>(
+		let limits = T::Schedule::get().limits;
+		let (mut store, _memory, instance) = PrefabWasmModule::<T>::instantiate::<EmptyEnv, _>(
 			&module.code,
 			(),
-			memory,
-			StackLimits::default(),
+			Determinism::Deterministic,
+			limits.stack_limits(),
+			limits.memory_pages,
 		)
 		.expect("Failed to create benchmarking Sandbox instance");
+		store.add_fuel(u64::MAX).expect("Fuel metering is enabled for contracts; qed");
 		let entry_point = instance.get_export(&store, "call").unwrap().into_func().unwrap();
 		Self { entry_point, store }
 	}
@@ -0,0 +1,99 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Native code that contracts call as if it was another contract.
+//!
+//! A builtin lives at a reserved address. When a contract uses `seal_call` on that address the
+//! native [`Builtin::call`] is executed instead of looking up a contract. The input of the call
+//! is passed in and the returned bytes are copied into the output buffer of the caller. This
+//! gives contracts cheap access to primitives that would be expensive in wasm, such as hashing
+//! large amounts of data or verifying a batch of signatures, much like precompiles of the EVM.
+//!
+//! Builtins can be called like any other contract: From contracts, by the
+//! [`Pallet::call`](crate::Pallet::call) dispatchable and through the `call` runtime API. No wasm
+//! frame is created in any case. A call made directly by an origin is still subject to its
+//! storage deposit limit, emits [`Event::Called`](crate::Event::Called) on success and reports
+//! failures to the debug buffer.
+//!
+//! The runtime author implements [`Builtin`] for each builtin and declares a tuple of them in
+//! this pallet's [configuration Trait](crate::Config). `()` means that no builtins are available.
+//!
+//! # Security
+//!
+//! The weight returned by [`Builtin::weight`] is charged from the gas meter of the caller
+//! **before** the builtin is executed. It should be derived from benchmarks of the
+//! builtin using the largest input that fits into the given length. The address must not be
+//! controllable by any user or contract. Otherwise the builtin would shadow it.
+//!
+//! Builtins can't receive any value. A call that transfers value fails with
+//! [`Error::BuiltinNotPayable`](crate::Error::BuiltinNotPayable).
+
+use crate::{exec::AccountIdOf, Config};
+use frame_support::weights::Weight;
+use sp_runtime::DispatchError;
+use sp_std::prelude::*;
+
+/// A single builtin which is reachable under [`Self::address`].
+pub trait Builtin<T: Config> {
+	/// The address under which contracts can call this builtin.
+	fn address() -> AccountIdOf<T>;
+
+	/// The weight of calling this builtin with an input of `input_len` bytes.
+	fn weight(input_len: u32) -> Weight;
+
+	/// Execute the builtin and return its output.
+	///
+	/// An error traps the call which reverts all changes made by the builtin.
+	fn call(input: &[u8]) -> Result<Vec<u8>, DispatchError>;
+}
+
+/// The set of [`Builtin`]s that contracts can call.
+///
+/// This is implemented for tuples of builtins. Their addresses must be distinct.
+pub trait Builtins<T: Config> {
+	/// The weight of calling the builtin at `address` or `None` if there is no builtin.
+	fn weight(address: &AccountIdOf<T>, input_len: u32) -> Option<Weight>;
+
+	/// Execute the builtin at `address` or `None` if there is no builtin.
+	fn call(address: &AccountIdOf<T>, input: &[u8]) -> Option<Result<Vec<u8>, DispatchError>>;
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(10)]
+#[tuple_types_custom_trait_bound(Builtin<T>)]
+impl<T: Config> Builtins<T> for Tuple {
+	fn weight(address: &AccountIdOf<T>, input_len: u32) -> Option<Weight> {
+		for_tuples!(
+			#(
+				if *address == Tuple::address() {
+					return Some(Tuple::weight(input_len))
+				}
+			)*
+		);
+		None
+	}
+
+	fn call(address: &AccountIdOf<T>, input: &[u8]) -> Option<Result<Vec<u8>, DispatchError>> {
+		for_tuples!(
+			#(
+				if *address == Tuple::address() {
+					return Some(Tuple::call(input))
+				}
+			)*
+		);
+		None
+	}
+}
@@ -45,6 +45,9 @@
 //! **Chain specific extensions must use the reserved `ID = 0` so that they can't be registered with
 //! the registry.**
 //!
+//! Every `ID` must only appear once within a tuple. Otherwise all but the first extension using
+//! it would be unreachable. The pallet checks this as part of its `integrity_test`.
+//!
 //! # Security
 //!
 //! The chain author alone is responsible for the security of the chain extension.
@@ -56,6 +59,13 @@
 //! is. Check whether using [`bare_call`](crate::Pallet::bare_call) suffices for the
 //! use case at hand.
 //!
+//! # Off-the-shelf extensions
+//!
+//! Extensions that are generally useful are shipped as part of this module. They can be
+//! used on their own or as part of a tuple:
+//!
+//! - [`staking::StakingExtension`]: Lets contracts bond and nominate with their own balance.
+//!
 //! # Benchmarking
 //!
 //! The builtin contract callable functions that pallet-contracts provides all have
@@ -64,13 +74,25 @@
 //! by a chain extension benchmarks must be written, too. In the near future this crate
 //! will provide the means for easier creation of those specialized benchmarks.
 //!
+//! # Testing
+//!
+//! With the `test-helpers` feature enabled the [`testing`] module allows calling into a chain
+//! extension from a unit test. It supplies a mock of the calling contract and its memory and
+//! reports the weight that was charged. No mock runtime with a deployed fixture contract is
+//! required.
+//!
 //! # Example
 //!
 //! The ink! repository maintains an
 //! [end-to-end example](https://github.com/paritytech/ink/tree/master/examples/rand-extension)
 //! on how to use a chain extension in order to provide new features to ink! contracts.
 
+pub mod staking;
+#[cfg(any(test, feature = "test-helpers"))]
+pub mod testing;
+
 use crate::{
+	exec::TopicOf,
 	gas::ChargedAmount,
 	wasm::{Runtime, RuntimeCosts},
 	Error,
@@ -125,6 +147,15 @@ pub trait ChainExtension<C: Config> {
 	fn enabled() -> bool {
 		true
 	}
+
+	/// The [`RegisteredChainExtension::ID`]s this extension dispatches on.
+	///
+	/// Only the tuple implementation returns a non empty list. It is used to make sure that
+	/// no two extensions within a tuple share the same `ID`. There is no need to overwrite it.
+	#[doc(hidden)]
+	fn registered_ids() -> Vec<u16> {
+		Vec::new()
+	}
 }
 
 /// A [`ChainExtension`] that can be composed with other extensions using a tuple.
@@ -170,6 +201,11 @@ impl<C: Config> ChainExtension<C> for Tuple {
 		);
 		false
 	}
+
+	fn registered_ids() -> Vec<u16> {
+		let ids: &[u16] = &[for_tuples!( #( Tuple::ID ),* )];
+		ids.to_vec()
+	}
 }
 
 /// Determines the exit behaviour and return value of a chain extension.
@@ -240,6 +276,28 @@ impl<'a, 'b, E: Ext, S: State> Environment<'a, 'b, E, S> {
 			.adjust_gas(charged, RuntimeCosts::ChainExtension(actual_weight))
 	}
 
+	/// Emit an event on behalf of the calling contract.
+	///
+	/// The event is recorded as [`ContractEmitted`](crate::Event::ContractEmitted) with the
+	/// address of the calling contract attached. This makes actions carried out by the
+	/// extension visible to anyone following the events of the contract. The same gas is
+	/// charged and the same limits apply as if the contract called `seal_deposit_event` itself.
+	pub fn deposit_event(&mut self, topics: Vec<TopicOf<E::T>>, data: Vec<u8>) -> Result<()> {
+		self.inner.runtime.charge_gas(RuntimeCosts::DepositEvent {
+			num_topic: topics.len() as u32,
+			len: data.len() as u32,
+		})?;
+		let ext = self.inner.runtime.ext();
+		if data.len() > ext.schedule().limits.event_data_len as usize {
+			return Err(Error::<E::T>::ValueTooLarge.into())
+		}
+		if topics.len() > ext.schedule().limits.event_topics as usize {
+			return Err(Error::<E::T>::TooManyTopics.into())
+		}
+		ext.deposit_event(topics, data);
+		Ok(())
+	}
+
 	/// Grants access to the execution environment of the current contract call.
 	///
 	/// Consult the functions on the returned type before re-implementing those functions.
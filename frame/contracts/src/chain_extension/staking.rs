@@ -0,0 +1,176 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An off-the-shelf chain extension which lets contracts stake their own balance.
+//!
+//! [`StakingExtension`] forwards calls to any implementation of [`StakingInterface`]. It
+//! allows contracts like liquid staking derivatives to be written without requiring each
+//! chain to ship its own privileged extension.
+//!
+//! # Functions
+//!
+//! The function id is the lower half of the `id` passed to `seal_call_chain_extension`. All
+//! inputs and outputs are SCALE encoded.
+//!
+//! | `func_id` | Function            | Input                | Output            |
+//! |-----------|---------------------|----------------------|-------------------|
+//! | 0         | `current_era`       | -                    | `u32`             |
+//! | 1         | `active_stake`      | `AccountId`          | `Option<Balance>` |
+//! | 2         | `bond`              | `Balance`            | -                 |
+//! | 3         | `bond_extra`        | `Balance`            | -                 |
+//! | 4         | `unbond`            | `Balance`            | -                 |
+//! | 5         | `withdraw_unbonded` | `u32` slashing spans | -                 |
+//! | 6         | `nominate`          | `Vec<AccountId>`     | -                 |
+//! | 7         | `chill`             | -                    | -                 |
+//!
+//! Calling an unknown function traps the contract.
+//!
+//! # Security
+//!
+//! A contract can only ever act on behalf of its own account which is used as stash,
+//! controller and payee alike. On top of that, the runtime supplies a `Filter` which decides
+//! which contracts may use the mutating functions (`bond` to `chill`). A contract that isn't
+//! contained in the filter is trapped with [`DispatchError::BadOrigin`]. The queries are
+//! available to every contract.
+//!
+//! The mutating functions return `0` on success and `1` if the staking system rejected the
+//! operation. In the latter case no state is changed.
+//!
+//! # Weights
+//!
+//! The weight of every function is taken from the supplied [`WeightInfo`] and charged
+//! before anything else happens. Runtimes should implement it by forwarding to the weights
+//! generated by the benchmarks of their staking pallet for the corresponding dispatchables.
+
+use crate::chain_extension::{
+	ChainExtension, Environment, Ext, InitState, RegisteredChainExtension, Result, RetVal,
+	SysConfig,
+};
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{traits::Contains, weights::Weight};
+use sp_runtime::DispatchError;
+use sp_staking::StakingInterface;
+use sp_std::{marker::PhantomData, vec::Vec};
+
+/// The weights of the functions exposed by [`StakingExtension`].
+pub trait WeightInfo {
+	fn current_era() -> Weight;
+	fn active_stake() -> Weight;
+	fn bond() -> Weight;
+	fn bond_extra() -> Weight;
+	fn unbond() -> Weight;
+	fn withdraw_unbonded(s: u32) -> Weight;
+	fn nominate(n: u32) -> Weight;
+	fn chill() -> Weight;
+}
+
+/// A [`ChainExtension`] exposing `Staking` to contracts.
+///
+/// Consult the [module documentation](self) for a general explanation.
+pub struct StakingExtension<Staking, Weights, Filter, const ID: u16>(
+	PhantomData<(Staking, Weights, Filter)>,
+);
+
+impl<Staking, Weights, Filter, const ID: u16> Default
+	for StakingExtension<Staking, Weights, Filter, ID>
+{
+	fn default() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<C, Staking, Weights, Filter, const ID: u16> ChainExtension<C>
+	for StakingExtension<Staking, Weights, Filter, ID>
+where
+	C: crate::Config,
+	Staking: StakingInterface<AccountId = C::AccountId>,
+	Staking::Balance: Encode + Decode + MaxEncodedLen,
+	Weights: WeightInfo,
+	Filter: Contains<C::AccountId>,
+{
+	fn call<E: Ext<T = C>>(&mut self, env: Environment<E, InitState>) -> Result<RetVal> {
+		let mut env = env.buf_in_buf_out();
+		let func_id = env.func_id();
+
+		if func_id == 0 {
+			env.charge_weight(Weights::current_era())?;
+			env.write(&Staking::current_era().encode(), false, None)?;
+			return Ok(RetVal::Converging(0))
+		}
+		if func_id == 1 {
+			env.charge_weight(Weights::active_stake())?;
+			let who: <C as SysConfig>::AccountId = env.read_as()?;
+			env.write(&Staking::active_stake(&who).ok().encode(), false, None)?;
+			return Ok(RetVal::Converging(0))
+		}
+
+		if !Filter::contains(env.ext().address()) {
+			return Err(DispatchError::BadOrigin)
+		}
+
+		let result = match func_id {
+			2 => {
+				env.charge_weight(Weights::bond())?;
+				let value: Staking::Balance = env.read_as()?;
+				let contract = env.ext().address().clone();
+				Staking::bond(&contract, value, &contract)
+			},
+			3 => {
+				env.charge_weight(Weights::bond_extra())?;
+				let extra: Staking::Balance = env.read_as()?;
+				Staking::bond_extra(env.ext().address(), extra)
+			},
+			4 => {
+				env.charge_weight(Weights::unbond())?;
+				let value: Staking::Balance = env.read_as()?;
+				Staking::unbond(env.ext().address(), value)
+			},
+			5 => {
+				let spans: u32 = env.read_as()?;
+				env.charge_weight(Weights::withdraw_unbonded(spans))?;
+				Staking::withdraw_unbonded(env.ext().address().clone(), spans).map(|_| ())
+			},
+			6 => {
+				// Every encoded target takes at least as much space as its account id.
+				let max_targets = env.in_len() / C::AccountId::max_encoded_len().max(1) as u32;
+				let charged = env.charge_weight(Weights::nominate(max_targets))?;
+				let targets: Vec<C::AccountId> = env.read_as_unbounded(env.in_len())?;
+				env.adjust_weight(charged, Weights::nominate(targets.len() as u32));
+				Staking::nominate(env.ext().address(), targets)
+			},
+			7 => {
+				env.charge_weight(Weights::chill())?;
+				Staking::chill(env.ext().address())
+			},
+			_ => return Err(DispatchError::Other("Unknown staking extension function")),
+		};
+
+		Ok(RetVal::Converging(result.is_err() as u32))
+	}
+}
+
+impl<C, Staking, Weights, Filter, const ID: u16> RegisteredChainExtension<C>
+	for StakingExtension<Staking, Weights, Filter, ID>
+where
+	C: crate::Config,
+	Staking: StakingInterface<AccountId = C::AccountId>,
+	Staking::Balance: Encode + Decode + MaxEncodedLen,
+	Weights: WeightInfo,
+	Filter: Contains<C::AccountId>,
+{
+	const ID: u16 = ID;
+}
@@ -0,0 +1,654 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Utilities to unit test chain extensions.
+//!
+//! Testing a [`ChainExtension`] end to end requires a mock runtime that configures this pallet
+//! and a wasm fixture contract that calls `seal_call_chain_extension`. This module allows
+//! calling into an extension directly instead:
+//!
+//! - [`MockExt`] stands in for the contract that is executing. Its fields can be set up front and
+//!   everything the extension does to it (events, transfers, calls, ...) is recorded.
+//! - [`MockEnvironment`] simulates the contract memory the input is read from and the output is
+//!   written to, and measures the weight that the extension charged.
+//!
+//! This module is only available with the `test-helpers` feature.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let mut env = MockEnvironment::<Runtime>::new(ALICE, CONTRACT);
+//! let outcome = env.call(&mut MyExtension::default(), 0x0001_0002, &input.encode());
+//! assert!(matches!(outcome.result, Ok(RetVal::Converging(0))));
+//! assert_eq!(outcome.output, expected.encode());
+//! outcome.assert_weight_charged(<Runtime as MyConfig>::WeightInfo::my_function());
+//! ```
+
+use super::{ChainExtension, Environment, Result, RetVal};
+use crate::{
+	exec::{
+		AccountIdOf, BlockNumberOf, ExecError, Ext, FixSizedKey, MomentOf, SeedOf, TopicOf,
+		VarSizedKey,
+	},
+	gas::GasMeter,
+	storage::{meter::DepositOf, WriteOutcome},
+	wasm::Runtime,
+	xcm::XcmHash,
+	BalanceOf, CallOrigin, CodeHash, Config, Error, Pallet, Schedule,
+};
+use codec::{Decode, Encode};
+use frame_support::{
+	crypto::ecdsa::ECDSAExt,
+	dispatch::{DispatchResult, DispatchResultWithPostInfo},
+	ensure,
+	traits::{ExistenceRequirement, Get},
+	weights::Weight,
+};
+use pallet_contracts_primitives::{ExecReturnValue, ReturnFlags};
+use sp_core::ecdsa::Public as ECDSAPublic;
+use sp_io::crypto::secp256k1_ecdsa_recover_compressed;
+use sp_runtime::{
+	traits::{Convert, Hash, Saturating},
+	DispatchError,
+};
+use sp_std::{
+	cell::RefCell,
+	collections::{btree_map::BTreeMap, btree_set::BTreeSet},
+	prelude::*,
+};
+
+/// A call or delegate call made by the extension through [`Ext`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MockCall<T: Config> {
+	/// A call into another account.
+	Call { to: AccountIdOf<T>, value: BalanceOf<T>, data: Vec<u8> },
+	/// A delegate call into some code.
+	DelegateCall { code_hash: CodeHash<T>, data: Vec<u8> },
+	/// The instantiation of a new contract.
+	Instantiate { code_hash: CodeHash<T>, value: BalanceOf<T>, data: Vec<u8>, salt: Vec<u8> },
+}
+
+/// A stand-in for the contract that calls into a chain extension.
+///
+/// The public fields describe the calling contract and can be changed freely before calling the
+/// extension. Everything that would change the state of the chain is recorded instead of being
+/// carried out. Calls made through [`Ext`] return [`MockExt::call_return`].
+pub struct MockExt<T: Config> {
+	/// The account that called the contract.
+	pub caller: AccountIdOf<T>,
+	/// The address of the contract.
+	pub address: AccountIdOf<T>,
+	/// The code hash of the contract.
+	pub code_hash: CodeHash<T>,
+	/// The free balance of the contract.
+	pub balance: BalanceOf<T>,
+	/// The value transferred along with the call to the contract.
+	pub value_transferred: BalanceOf<T>,
+	/// The existential deposit.
+	pub minimum_balance: BalanceOf<T>,
+	/// The current timestamp.
+	pub now: MomentOf<T>,
+	/// The current block number.
+	pub block_number: BlockNumberOf<T>,
+	/// The nonce returned by [`Ext::nonce`].
+	pub nonce: u64,
+	/// The schedule used to charge for [`Environment::deposit_event`].
+	pub schedule: Schedule<T>,
+	/// The storage of the contract.
+	pub storage: BTreeMap<Vec<u8>, Vec<u8>>,
+	/// The value returned by every call or instantiation.
+	pub call_return: Vec<u8>,
+	/// Events emitted as `(topics, data)`.
+	pub events: Vec<(Vec<TopicOf<T>>, Vec<u8>)>,
+	/// Calls, delegate calls and instantiations made.
+	pub calls: Vec<MockCall<T>>,
+	/// Transfers made as `(to, value)`.
+	pub transfers: Vec<(AccountIdOf<T>, BalanceOf<T>)>,
+	/// Beneficiaries of the contract being terminated.
+	pub terminations: Vec<AccountIdOf<T>>,
+	/// Runtime calls dispatched.
+	pub runtime_calls: RefCell<Vec<<T as Config>::RuntimeCall>>,
+	/// Xcm messages executed or sent as `(dest, message)`.
+	pub xcm_messages: RefCell<Vec<(Option<Vec<u8>>, Vec<u8>)>>,
+	/// Assets transferred as `(asset, to, amount)`.
+	pub asset_transfers: RefCell<Vec<(Vec<u8>, AccountIdOf<T>, Vec<u8>)>>,
+	/// Everything appended to the debug buffer.
+	pub debug_buffer: Vec<u8>,
+	gas_meter: GasMeter<T>,
+	deny_reentry: bool,
+	delegate_dependencies: BTreeSet<CodeHash<T>>,
+}
+
+impl<T: Config> MockExt<T> {
+	/// A contract at `address` that was called by `caller`.
+	///
+	/// The gas limit is the maximum weight of a block.
+	pub fn new(caller: AccountIdOf<T>, address: AccountIdOf<T>) -> Self {
+		Self {
+			caller,
+			address,
+			code_hash: Default::default(),
+			balance: Default::default(),
+			value_transferred: Default::default(),
+			minimum_balance: Default::default(),
+			now: Default::default(),
+			block_number: Default::default(),
+			nonce: Default::default(),
+			schedule: Default::default(),
+			storage: Default::default(),
+			call_return: Default::default(),
+			events: Default::default(),
+			calls: Default::default(),
+			transfers: Default::default(),
+			terminations: Default::default(),
+			runtime_calls: Default::default(),
+			xcm_messages: Default::default(),
+			asset_transfers: Default::default(),
+			debug_buffer: Default::default(),
+			gas_meter: GasMeter::new(T::BlockWeights::get().max_block),
+			deny_reentry: false,
+			delegate_dependencies: Default::default(),
+		}
+	}
+
+	/// Replace the gas meter by one that is limited to `gas_limit`.
+	pub fn set_gas_limit(&mut self, gas_limit: Weight) {
+		self.gas_meter = GasMeter::new(gas_limit);
+	}
+
+	/// The weight that is left to be charged.
+	pub fn gas_left(&self) -> Weight {
+		self.gas_meter.gas_left()
+	}
+
+	/// Whether the contract asked to not be re-entered.
+	pub fn deny_reentry(&self) -> bool {
+		self.deny_reentry
+	}
+
+	fn call_return(&self) -> ExecReturnValue {
+		ExecReturnValue { flags: ReturnFlags::empty(), data: self.call_return.clone() }
+	}
+
+	fn write(&mut self, key: Vec<u8>, value: Option<Vec<u8>>, take_old: bool) -> WriteOutcome {
+		let old = match value {
+			Some(value) => self.storage.insert(key, value),
+			None => self.storage.remove(&key),
+		};
+		match (old, take_old) {
+			(None, _) => WriteOutcome::New,
+			(Some(old), false) => WriteOutcome::Overwritten(old.len() as u32),
+			(Some(old), true) => WriteOutcome::Taken(old),
+		}
+	}
+}
+
+impl<T: Config> Ext for MockExt<T> {
+	type T = T;
+
+	fn call(
+		&mut self,
+		_gas_limit: Weight,
+		_deposit_limit: BalanceOf<T>,
+		to: AccountIdOf<T>,
+		value: BalanceOf<T>,
+		data: Vec<u8>,
+		_allows_reentry: bool,
+	) -> sp_std::result::Result<ExecReturnValue, ExecError> {
+		self.calls.push(MockCall::Call { to, value, data });
+		Ok(self.call_return())
+	}
+
+	fn delegate_call(
+		&mut self,
+		_gas_limit: Weight,
+		_deposit_limit: BalanceOf<T>,
+		code_hash: CodeHash<T>,
+		data: Vec<u8>,
+	) -> sp_std::result::Result<ExecReturnValue, ExecError> {
+		self.calls.push(MockCall::DelegateCall { code_hash, data });
+		Ok(self.call_return())
+	}
+
+	fn instantiate(
+		&mut self,
+		_gas_limit: Weight,
+		_deposit_limit: BalanceOf<T>,
+		code_hash: CodeHash<T>,
+		value: BalanceOf<T>,
+		data: Vec<u8>,
+		salt: &[u8],
+	) -> sp_std::result::Result<(AccountIdOf<T>, ExecReturnValue), ExecError> {
+		let address = Pallet::<T>::contract_address(&self.address, &code_hash, &data, salt);
+		self.calls
+			.push(MockCall::Instantiate { code_hash, value, data, salt: salt.to_vec() });
+		Ok((address, self.call_return()))
+	}
+
+	fn terminate(&mut self, beneficiary: &AccountIdOf<T>) -> DispatchResult {
+		self.terminations.push(beneficiary.clone());
+		Ok(())
+	}
+
+	fn transfer(
+		&mut self,
+		to: &AccountIdOf<T>,
+		value: BalanceOf<T>,
+		_existence_requirement: ExistenceRequirement,
+	) -> DispatchResult {
+		self.transfers.push((to.clone(), value));
+		Ok(())
+	}
+
+	fn transfer_all(
+		&mut self,
+		to: &AccountIdOf<T>,
+		existence_requirement: ExistenceRequirement,
+	) -> DispatchResult {
+		let value = match existence_requirement {
+			ExistenceRequirement::KeepAlive => self.balance.saturating_sub(self.minimum_balance),
+			ExistenceRequirement::AllowDeath => self.balance,
+		};
+		self.transfer(to, value, existence_requirement)
+	}
+
+	fn get_storage(&mut self, key: &FixSizedKey) -> Option<Vec<u8>> {
+		self.storage.get(&key.to_vec()).cloned()
+	}
+
+	fn get_storage_transparent(&mut self, key: &VarSizedKey<T>) -> Option<Vec<u8>> {
+		self.storage.get(&key.to_vec()).cloned()
+	}
+
+	fn get_storage_size(&mut self, key: &FixSizedKey) -> Option<u32> {
+		self.storage.get(&key.to_vec()).map(|val| val.len() as u32)
+	}
+
+	fn get_storage_size_transparent(&mut self, key: &VarSizedKey<T>) -> Option<u32> {
+		self.storage.get(&key.to_vec()).map(|val| val.len() as u32)
+	}
+
+	fn set_storage(
+		&mut self,
+		key: &FixSizedKey,
+		value: Option<Vec<u8>>,
+		take_old: bool,
+	) -> sp_std::result::Result<WriteOutcome, DispatchError> {
+		Ok(self.write(key.to_vec(), value, take_old))
+	}
+
+	fn set_storage_transparent(
+		&mut self,
+		key: &VarSizedKey<T>,
+		value: Option<Vec<u8>>,
+		take_old: bool,
+	) -> sp_std::result::Result<WriteOutcome, DispatchError> {
+		Ok(self.write(key.to_vec(), value, take_old))
+	}
+
+	fn caller(&self) -> CallOrigin<T> {
+		CallOrigin::from_account_id(self.caller.clone())
+	}
+
+	fn is_contract(&self, address: &AccountIdOf<T>) -> bool {
+		*address == self.address
+	}
+
+	fn code_hash(&self, address: &AccountIdOf<T>) -> Option<CodeHash<T>> {
+		self.is_contract(address).then_some(self.code_hash)
+	}
+
+	fn own_code_hash(&mut self) -> &CodeHash<T> {
+		&self.code_hash
+	}
+
+	fn caller_is_origin(&self) -> bool {
+		true
+	}
+
+	fn caller_is_root(&self) -> bool {
+		false
+	}
+
+	fn address(&self) -> &AccountIdOf<T> {
+		&self.address
+	}
+
+	fn balance(&self) -> BalanceOf<T> {
+		self.balance
+	}
+
+	fn value_transferred(&self) -> BalanceOf<T> {
+		self.value_transferred
+	}
+
+	fn last_subcall_deposit(&self) -> DepositOf<T> {
+		Default::default()
+	}
+
+	fn now(&self) -> &MomentOf<T> {
+		&self.now
+	}
+
+	fn minimum_balance(&self) -> BalanceOf<T> {
+		self.minimum_balance
+	}
+
+	fn random(&self, subject: &[u8]) -> (SeedOf<T>, BlockNumberOf<T>) {
+		(T::Hashing::hash(subject), self.block_number)
+	}
+
+	fn deposit_event(&mut self, topics: Vec<TopicOf<T>>, data: Vec<u8>) {
+		self.events.push((topics, data));
+	}
+
+	fn block_number(&self) -> BlockNumberOf<T> {
+		self.block_number
+	}
+
+	fn max_value_size(&self) -> u32 {
+		self.schedule.limits.payload_len
+	}
+
+	fn get_weight_price(&self, weight: Weight) -> BalanceOf<T> {
+		T::WeightPrice::convert(weight)
+	}
+
+	fn schedule(&self) -> &Schedule<T> {
+		&self.schedule
+	}
+
+	fn gas_meter(&mut self) -> &mut GasMeter<T> {
+		&mut self.gas_meter
+	}
+
+	fn append_debug_buffer(&mut self, msg: &str) -> bool {
+		self.debug_buffer.extend(msg.as_bytes());
+		true
+	}
+
+	fn call_runtime(&self, call: <T as Config>::RuntimeCall) -> DispatchResultWithPostInfo {
+		self.runtime_calls.borrow_mut().push(call);
+		Ok(Default::default())
+	}
+
+	fn ecdsa_recover(
+		&self,
+		signature: &[u8; 65],
+		message_hash: &[u8; 32],
+	) -> sp_std::result::Result<[u8; 33], ()> {
+		secp256k1_ecdsa_recover_compressed(signature, message_hash).map_err(|_| ())
+	}
+
+	fn ecdsa_to_eth_address(&self, pk: &[u8; 33]) -> sp_std::result::Result<[u8; 20], ()> {
+		ECDSAPublic(*pk).to_eth_address()
+	}
+
+	fn xcm_execute(&self, message: &[u8], _weight_limit: Weight) -> DispatchResultWithPostInfo {
+		self.xcm_messages.borrow_mut().push((None, message.to_vec()));
+		Ok(Default::default())
+	}
+
+	fn xcm_send(
+		&self,
+		dest: &[u8],
+		message: &[u8],
+	) -> sp_std::result::Result<XcmHash, DispatchError> {
+		self.xcm_messages.borrow_mut().push((Some(dest.to_vec()), message.to_vec()));
+		Ok(sp_io::hashing::blake2_256(message))
+	}
+
+	fn asset_transfer(&self, asset: &[u8], to: &AccountIdOf<T>, amount: &[u8]) -> DispatchResult {
+		self.asset_transfers
+			.borrow_mut()
+			.push((asset.to_vec(), to.clone(), amount.to_vec()));
+		Ok(())
+	}
+
+	#[cfg(test)]
+	fn contract_info(&mut self) -> &mut crate::ContractInfo<T> {
+		unimplemented!()
+	}
+
+	fn set_code_hash(&mut self, hash: CodeHash<T>) -> DispatchResult {
+		self.code_hash = hash;
+		Ok(())
+	}
+
+	fn reentrance_count(&self) -> u32 {
+		0
+	}
+
+	fn account_reentrance_count(&self, _account_id: &AccountIdOf<T>) -> u32 {
+		0
+	}
+
+	fn nonce(&mut self) -> u64 {
+		self.nonce
+	}
+
+	fn storage_deposit_limit_left(&mut self) -> BalanceOf<T> {
+		self.balance
+	}
+
+	fn set_deny_reentry(&mut self, deny: bool) {
+		self.deny_reentry = deny;
+	}
+
+	fn lock_delegate_dependency(&mut self, code_hash: CodeHash<T>) -> DispatchResult {
+		ensure!(
+			self.delegate_dependencies.insert(code_hash),
+			Error::<T>::DelegateDependencyAlreadyExists
+		);
+		Ok(())
+	}
+
+	fn unlock_delegate_dependency(&mut self, code_hash: &CodeHash<T>) -> DispatchResult {
+		ensure!(
+			self.delegate_dependencies.remove(code_hash),
+			Error::<T>::DelegateDependencyNotFound
+		);
+		Ok(())
+	}
+}
+
+/// The result of calling into a chain extension through a [`MockEnvironment`].
+pub struct MockOutcome {
+	/// What the extension returned.
+	pub result: Result<RetVal>,
+	/// The output buffer as the contract would see it after the call.
+	///
+	/// Its length is the one stored at `output_len_ptr`. Empty for
+	/// [`MockEnvironment::call_with_args`].
+	pub output: Vec<u8>,
+	/// The weight charged by the extension.
+	pub weight_charged: Weight,
+}
+
+impl MockOutcome {
+	/// Panics unless the extension charged exactly `expected`.
+	#[track_caller]
+	pub fn assert_weight_charged(&self, expected: Weight) {
+		assert_eq!(self.weight_charged, expected, "unexpected weight charged by the extension");
+	}
+
+	/// Panics unless the extension charged at most `limit`.
+	#[track_caller]
+	pub fn assert_weight_charged_at_most(&self, limit: Weight) {
+		assert!(
+			self.weight_charged.all_lte(limit),
+			"extension charged {:?} which exceeds {:?}",
+			self.weight_charged,
+			limit,
+		);
+	}
+}
+
+/// Calls into a chain extension the same way a contract calling `seal_call_chain_extension`
+/// would.
+///
+/// The input is placed into a simulated contract memory, followed by the output length and an
+/// output buffer of [`Self::output_capacity`] bytes. The extension sees those as `input_ptr`,
+/// `input_len`, `output_ptr` and `output_len_ptr`.
+pub struct MockEnvironment<T: Config> {
+	/// The contract calling into the extension.
+	pub ext: MockExt<T>,
+	/// Size of the output buffer the contract supplies.
+	pub output_capacity: u32,
+}
+
+impl<T: Config> MockEnvironment<T> {
+	/// Default size of the output buffer. The same as ink! uses.
+	pub const DEFAULT_OUTPUT_CAPACITY: u32 = 16 * 1024;
+
+	/// An environment for a contract at `address` that was called by `caller`.
+	pub fn new(caller: AccountIdOf<T>, address: AccountIdOf<T>) -> Self {
+		Self { ext: MockExt::new(caller, address), output_capacity: Self::DEFAULT_OUTPUT_CAPACITY }
+	}
+
+	/// Call `extension` with `input` as buffer input.
+	pub fn call<X: ChainExtension<T>>(
+		&mut self,
+		extension: &mut X,
+		id: u32,
+		input: &[u8],
+	) -> MockOutcome {
+		let input_len = input.len() as u32;
+		let output_len_ptr = input_len;
+		let output_ptr = output_len_ptr + 4;
+		let mut memory = vec![0u8; (output_ptr + self.output_capacity) as usize];
+		memory[..input.len()].copy_from_slice(input);
+		memory[output_len_ptr as usize..output_ptr as usize]
+			.copy_from_slice(&self.output_capacity.encode());
+
+		let (result, weight_charged) =
+			self.invoke(extension, &mut memory, id, [0, input_len, output_ptr, output_len_ptr]);
+		let output_len = u32::decode(&mut &memory[output_len_ptr as usize..output_ptr as usize])
+			.expect("four bytes always decode as u32; qed")
+			.min(self.output_capacity);
+		let output = memory[output_ptr as usize..(output_ptr + output_len) as usize].to_vec();
+		MockOutcome { result, output, weight_charged }
+	}
+
+	/// Call `extension` with the four arguments passed verbatim.
+	///
+	/// This is meant for functions that only use primitive arguments. The simulated contract
+	/// memory is empty, so any attempt to use the arguments as pointers fails.
+	pub fn call_with_args<X: ChainExtension<T>>(
+		&mut self,
+		extension: &mut X,
+		id: u32,
+		args: [u32; 4],
+	) -> MockOutcome {
+		let (result, weight_charged) = self.invoke(extension, &mut [], id, args);
+		MockOutcome { result, output: Vec::new(), weight_charged }
+	}
+
+	fn invoke<X: ChainExtension<T>>(
+		&mut self,
+		extension: &mut X,
+		memory: &mut [u8],
+		id: u32,
+		[input_ptr, input_len, output_ptr, output_len_ptr]: [u32; 4],
+	) -> (Result<RetVal>, Weight) {
+		let gas_before = self.ext.gas_left();
+		let result = {
+			let mut runtime = Runtime::new(&mut self.ext, Vec::new());
+			let env = Environment::new(
+				&mut runtime,
+				memory,
+				id,
+				input_ptr,
+				input_len,
+				output_ptr,
+				output_len_ptr,
+			);
+			extension.call(env)
+		};
+		(result, gas_before.saturating_sub(self.ext.gas_left()))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		chain_extension::InitState,
+		tests::{Test, ALICE, BOB},
+	};
+
+	const DOUBLE_WEIGHT: Weight = Weight::from_parts(1_000, 10);
+
+	/// Doubles the `u32` passed in, or adds `val0` and `val1` for `func_id` 1.
+	#[derive(Default)]
+	struct Doubler;
+
+	impl ChainExtension<Test> for Doubler {
+		fn call<E: Ext<T = Test>>(&mut self, env: Environment<E, InitState>) -> Result<RetVal> {
+			match env.func_id() {
+				0 => {
+					let mut env = env.buf_in_buf_out();
+					env.charge_weight(DOUBLE_WEIGHT)?;
+					let value: u32 = env.read_as()?;
+					env.write(&(value * 2).encode(), false, None)?;
+				},
+				1 => {
+					let mut env = env.only_in();
+					env.charge_weight(DOUBLE_WEIGHT)?;
+					return Ok(RetVal::Converging(env.val0() + env.val1()))
+				},
+				_ => return Err(DispatchError::Other("unknown function")),
+			}
+			Ok(RetVal::Converging(0))
+		}
+	}
+
+	#[test]
+	fn mock_environment_passes_buffers() {
+		let mut env = MockEnvironment::<Test>::new(ALICE, BOB);
+		let outcome = env.call(&mut Doubler, 0, &21u32.encode());
+		assert!(matches!(outcome.result, Ok(RetVal::Converging(0))));
+		assert_eq!(outcome.output, 42u32.encode());
+		outcome.assert_weight_charged(DOUBLE_WEIGHT);
+
+		// output buffer is too small
+		env.output_capacity = 2;
+		let outcome = env.call(&mut Doubler, 0, &21u32.encode());
+		assert!(
+			matches!(outcome.result, Err(e) if e == Error::<Test>::OutputBufferTooSmall.into())
+		);
+	}
+
+	#[test]
+	fn mock_environment_passes_args() {
+		let mut env = MockEnvironment::<Test>::new(ALICE, BOB);
+		let outcome = env.call_with_args(&mut Doubler, 1, [40, 2, 0, 0]);
+		assert!(matches!(outcome.result, Ok(RetVal::Converging(42))));
+		assert!(outcome.output.is_empty());
+		outcome.assert_weight_charged_at_most(DOUBLE_WEIGHT);
+	}
+
+	#[test]
+	fn mock_environment_runs_out_of_gas() {
+		let mut env = MockEnvironment::<Test>::new(ALICE, BOB);
+		env.ext.set_gas_limit(DOUBLE_WEIGHT / 2);
+		let outcome = env.call(&mut Doubler, 0, &21u32.encode());
+		assert!(matches!(outcome.result, Err(e) if e == Error::<Test>::OutOfGas.into()));
+		outcome.assert_weight_charged(Weight::zero());
+		assert_eq!(env.ext.gas_left(), DOUBLE_WEIGHT / 2);
+	}
+}
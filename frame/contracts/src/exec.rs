@@ -16,26 +16,36 @@
 // limitations under the License.
 
 use crate::{
-	gas::GasMeter,
-	storage::{self, Storage, WriteOutcome},
-	BalanceOf, CodeHash, Config, ContractInfo, ContractInfoOf, DebugBufferVec, Determinism, Error,
-	Event, Nonce, Pallet as Contracts, Schedule,
+	assets::Assets,
+	builtin::Builtins,
+	gas::{GasMeter, Token},
+	storage::{
+		self,
+		meter::{DepositOf, Diff},
+		DepositAccount, Storage, WriteOutcome,
+	},
+	xcm::{Xcm, XcmHash},
+	AddressGenerator, BalanceOf, CallOrigin, CodeHash, CodeStatsOf, Config, ContractInfo,
+	ContractInfoOf, DebugBufferVec, DebugPolicy, Determinism, Error, Event, Nonce,
+	Pallet as Contracts, Schedule, TombstonedCode,
 };
 use frame_support::{
 	crypto::ecdsa::ECDSAExt,
 	dispatch::{DispatchError, DispatchResult, DispatchResultWithPostInfo, Dispatchable},
+	ensure,
 	storage::{with_transaction, TransactionOutcome},
-	traits::{Contains, Currency, ExistenceRequirement, OriginTrait, Randomness, Time},
+	traits::{Contains, Currency, ExistenceRequirement, Get, OriginTrait, Randomness, Time},
 	weights::Weight,
 	Blake2_128Concat, BoundedVec, StorageHasher,
 };
-use frame_system::RawOrigin;
-use pallet_contracts_primitives::ExecReturnValue;
+use pallet_contracts_primitives::{
+	ContractStorageChange, ExecReturnValue, IoCounters, ReturnFlags, RevertReason,
+};
 use smallvec::{Array, SmallVec};
 use sp_core::ecdsa::Public as ECDSAPublic;
 use sp_io::{crypto::secp256k1_ecdsa_recover_compressed, hashing::blake2_256};
-use sp_runtime::traits::{Convert, Hash};
-use sp_std::{marker::PhantomData, mem, prelude::*};
+use sp_runtime::traits::{Convert, Hash, Saturating, Zero};
+use sp_std::{collections::btree_set::BTreeSet, marker::PhantomData, mem, prelude::*};
 
 pub type AccountIdOf<T> = <T as frame_system::Config>::AccountId;
 pub type MomentOf<T> = <<T as Config>::Time as Time>::Moment;
@@ -52,6 +62,39 @@ pub type FixSizedKey = [u8; 32];
 /// Type for variable sized storage key. Used for transparent hashing.
 pub type VarSizedKey<T> = BoundedVec<u8, <T as Config>::MaxStorageKeyLen>;
 
+/// The cost of updating the [`crate::CodeStats`] of an executed code hash.
+#[cfg_attr(test, derive(Debug, PartialEq, Eq))]
+#[derive(Clone, Copy)]
+struct CodeStatsToken;
+
+impl<T: Config> Token<T> for CodeStatsToken {
+	fn weight(&self) -> Weight {
+		T::DbWeight::get().reads_writes(1, 1)
+	}
+}
+
+/// The cost of reading the [`ContractInfo`] of a contract on the call stack that is not cached.
+#[cfg_attr(test, derive(Debug, PartialEq, Eq))]
+#[derive(Clone, Copy)]
+struct ContractInfoReadToken;
+
+impl<T: Config> Token<T> for ContractInfoReadToken {
+	fn weight(&self) -> Weight {
+		T::DbWeight::get().reads(1)
+	}
+}
+
+/// The cost of executing a [`crate::builtin::Builtin`] as returned by its `weight`.
+#[cfg_attr(test, derive(Debug, PartialEq, Eq))]
+#[derive(Clone, Copy)]
+struct BuiltinToken(Weight);
+
+impl<T: Config> Token<T> for BuiltinToken {
+	fn weight(&self) -> Weight {
+		self.0
+	}
+}
+
 /// Trait for hashing storage keys.
 pub trait StorageKey<T>
 where
@@ -106,6 +149,16 @@ impl<T: Into<DispatchError>> From<T> for ExecError {
 	}
 }
 
+impl ExecError {
+	/// The standardized [`RevertReason`] describing this error.
+	///
+	/// Errors emitted by runtime modules map to [`RevertReason::Module`] while all other
+	/// errors are reported as [`RevertReason::Panic`] carrying the name of the error.
+	pub fn revert_reason(&self) -> RevertReason {
+		self.error.clone().into()
+	}
+}
+
 /// An interface that provides access to the external environment in which the
 /// smart-contract is executed.
 ///
@@ -120,10 +173,14 @@ pub trait Ext: sealing::Sealed {
 
 	/// Call (possibly transferring some amount of funds) into the specified account.
 	///
+	/// A non zero `deposit_limit` caps the storage deposit the callee may consume. Zero means
+	/// that the callee can use whatever is left of the current frame's limit.
+	///
 	/// Returns the original code size of the called contract.
 	fn call(
 		&mut self,
 		gas_limit: Weight,
+		deposit_limit: BalanceOf<Self::T>,
 		to: AccountIdOf<Self::T>,
 		value: BalanceOf<Self::T>,
 		input_data: Vec<u8>,
@@ -132,9 +189,14 @@ pub trait Ext: sealing::Sealed {
 
 	/// Execute code in the current frame.
 	///
+	/// `gas_limit` and `deposit_limit` work the same as for [`Self::call`]: passing zero lets
+	/// the delegated code use whatever is left of the current frame's limits.
+	///
 	/// Returns the original code size of the called contract.
 	fn delegate_call(
 		&mut self,
+		gas_limit: Weight,
+		deposit_limit: BalanceOf<Self::T>,
 		code: CodeHash<Self::T>,
 		input_data: Vec<u8>,
 	) -> Result<ExecReturnValue, ExecError>;
@@ -143,10 +205,12 @@ pub trait Ext: sealing::Sealed {
 	///
 	/// Returns the original code size of the called contract.
 	/// The newly created account will be associated with `code`. `value` specifies the amount of
-	/// value transferred from this to the newly created account.
+	/// value transferred from this to the newly created account. `deposit_limit` works the same
+	/// as for [`Self::call`].
 	fn instantiate(
 		&mut self,
 		gas_limit: Weight,
+		deposit_limit: BalanceOf<Self::T>,
 		code: CodeHash<Self::T>,
 		value: BalanceOf<Self::T>,
 		input_data: Vec<u8>,
@@ -163,7 +227,24 @@ pub trait Ext: sealing::Sealed {
 	fn terminate(&mut self, beneficiary: &AccountIdOf<Self::T>) -> Result<(), DispatchError>;
 
 	/// Transfer some amount of funds into the specified account.
-	fn transfer(&mut self, to: &AccountIdOf<Self::T>, value: BalanceOf<Self::T>) -> DispatchResult;
+	///
+	/// With [`ExistenceRequirement::AllowDeath`] the free balance of the contract may drop below
+	/// the existential deposit. The transfer still fails if it would remove the contract's
+	/// account. Use [`Self::terminate`] for that.
+	fn transfer(
+		&mut self,
+		to: &AccountIdOf<Self::T>,
+		value: BalanceOf<Self::T>,
+		existence_requirement: ExistenceRequirement,
+	) -> DispatchResult;
+
+	/// Transfer as much of the contract's free balance as `existence_requirement` allows into
+	/// the specified account.
+	fn transfer_all(
+		&mut self,
+		to: &AccountIdOf<Self::T>,
+		existence_requirement: ExistenceRequirement,
+	) -> DispatchResult;
 
 	/// Returns the storage entry of the executing account by the given `key`.
 	///
@@ -211,8 +292,10 @@ pub trait Ext: sealing::Sealed {
 		take_old: bool,
 	) -> Result<WriteOutcome, DispatchError>;
 
-	/// Returns a reference to the account id of the caller.
-	fn caller(&self) -> &AccountIdOf<Self::T>;
+	/// Returns the caller.
+	///
+	/// This is only ever root for the first contract of a call stack started by root.
+	fn caller(&self) -> CallOrigin<Self::T>;
 
 	/// Check if a contract lives at the specified `address`.
 	fn is_contract(&self, address: &AccountIdOf<Self::T>) -> bool;
@@ -227,21 +310,32 @@ pub trait Ext: sealing::Sealed {
 
 	/// Check if the caller of the current contract is the origin of the whole call stack.
 	///
-	/// This can be checked with `is_contract(self.caller())` as well.
+	/// This can be checked with `is_contract` on the account of the caller as well.
 	/// However, this function does not require any storage lookup and therefore uses less weight.
 	fn caller_is_origin(&self) -> bool;
 
+	/// Check if the caller is root.
+	fn caller_is_root(&self) -> bool;
+
 	/// Returns a reference to the account id of the current contract.
 	fn address(&self) -> &AccountIdOf<Self::T>;
 
 	/// Returns the balance of the current contract.
 	///
-	/// The `value_transferred` is already added.
+	/// The `value_transferred` is already added. The minimum balance that keeps the contract's
+	/// account alive is not included as it can't be spent.
 	fn balance(&self) -> BalanceOf<Self::T>;
 
 	/// Returns the value transferred along with this call.
 	fn value_transferred(&self) -> BalanceOf<Self::T>;
 
+	/// Returns the storage deposit consumed by the last call or instantiation made by the
+	/// current frame.
+	///
+	/// This includes the deposits of all nested calls made by the callee. It is zero if no
+	/// sub call was made yet or if the last one failed or was reverted.
+	fn last_subcall_deposit(&self) -> DepositOf<Self::T>;
+
 	/// Returns a reference to the timestamp of the current block
 	fn now(&self) -> &MomentOf<Self::T>;
 
@@ -290,6 +384,20 @@ pub trait Ext: sealing::Sealed {
 	/// Returns Ethereum address from the ECDSA compressed public key.
 	fn ecdsa_to_eth_address(&self, pk: &[u8; 33]) -> Result<[u8; 20], ()>;
 
+	/// Execute an encoded XCM message locally with the current contract as origin.
+	fn xcm_execute(&self, message: &[u8], weight_limit: Weight) -> DispatchResultWithPostInfo;
+
+	/// Send an encoded XCM message to `dest` with the current contract as origin.
+	fn xcm_send(&self, dest: &[u8], message: &[u8]) -> Result<XcmHash, DispatchError>;
+
+	/// Transfer the encoded `amount` of the encoded `asset` from the current contract to `to`.
+	fn asset_transfer(
+		&self,
+		asset: &[u8],
+		to: &AccountIdOf<Self::T>,
+		amount: &[u8],
+	) -> DispatchResult;
+
 	/// Tests sometimes need to modify and inspect the contract info directly.
 	#[cfg(test)]
 	fn contract_info(&mut self) -> &mut ContractInfo<Self::T>;
@@ -308,6 +416,38 @@ pub trait Ext: sealing::Sealed {
 
 	/// Returns a nonce that is incremented for every instantiated contract.
 	fn nonce(&mut self) -> u64;
+
+	/// Returns how much of the storage deposit limit is left for the current frame.
+	///
+	/// Storage changes made so far by the current frame and its finished sub calls are
+	/// accounted for.
+	fn storage_deposit_limit_left(&mut self) -> BalanceOf<Self::T>;
+
+	/// Sets whether the current contract denies to be reentered.
+	///
+	/// While set, any call into the contract is rejected as long as it is already on the call
+	/// stack. This takes precedence over the `allows_reentry` flag passed by the caller.
+	fn set_deny_reentry(&mut self, deny: bool);
+
+	/// Locks `code_hash` as a delegate dependency of the current contract.
+	///
+	/// The code can't be removed as long as it is locked. This allows a contract to safely
+	/// delegate to code it does not own.
+	///
+	/// # Errors
+	///
+	/// - [`Error::MaxDelegateDependenciesReached`]
+	/// - [`Error::CannotAddSelfAsDelegateDependency`]
+	/// - [`Error::DelegateDependencyAlreadyExists`]
+	/// - [`Error::CodeNotFound`]
+	fn lock_delegate_dependency(&mut self, code_hash: CodeHash<Self::T>) -> DispatchResult;
+
+	/// Releases a delegate dependency previously locked by the current contract.
+	///
+	/// # Errors
+	///
+	/// - [`Error::DelegateDependencyNotFound`]
+	fn unlock_delegate_dependency(&mut self, code_hash: &CodeHash<Self::T>) -> DispatchResult;
 }
 
 /// Describes the different functions that can be exported by an [`Executable`].
@@ -327,7 +467,7 @@ pub trait Executable<T: Config>: Sized {
 	/// Load the executable from storage.
 	///
 	/// # Note
-	/// Charges size base load and instrumentation weight from the gas meter.
+	/// Charges size base load weight from the gas meter.
 	fn from_storage(
 		code_hash: CodeHash<T>,
 		schedule: &Schedule<T>,
@@ -365,27 +505,132 @@ pub trait Executable<T: Config>: Sized {
 	/// The code hash of the executable.
 	fn code_hash(&self) -> &CodeHash<T>;
 
-	/// Size of the instrumented code in bytes.
+	/// Size of the code in bytes.
 	fn code_len(&self) -> u32;
 
 	/// The code does not contain any instructions which could lead to indeterminism.
 	fn is_deterministic(&self) -> bool;
 }
 
+/// The debugging output of an execution that is only collected off-chain.
+///
+/// It consists of a text buffer for human readable information, the storage changes made by
+/// the execution and counters for the storage and code it accessed. All the bytes added to the text
+/// buffer are valid UTF-8. Once a message does not fit anymore it is truncated and followed by a
+/// marker. All messages after that are dropped so that the start of the output is always preserved.
+pub struct DebugBuffer<T: Config> {
+	/// The output collected so far.
+	pub(crate) message: DebugBufferVec<T>,
+	/// Whether a message had to be truncated.
+	pub(crate) truncated: bool,
+	/// The storage changes in the order they were made.
+	pub(crate) storage_changes: Vec<ContractStorageChange<T::AccountId>>,
+	/// The storage and code accesses made so far.
+	pub(crate) io_counters: IoCounters,
+}
+
+impl<T: Config> Default for DebugBuffer<T> {
+	fn default() -> Self {
+		Self {
+			message: Default::default(),
+			truncated: false,
+			storage_changes: Vec::new(),
+			io_counters: Default::default(),
+		}
+	}
+}
+
+impl<T: Config> sp_std::ops::Deref for DebugBuffer<T> {
+	type Target = DebugBufferVec<T>;
+
+	fn deref(&self) -> &Self::Target {
+		&self.message
+	}
+}
+
+impl<T: Config> DebugBuffer<T> {
+	/// Append `msg` to the buffer.
+	///
+	/// If `msg` does not fit, as much of it is kept as leaves room for the marker returned by
+	/// `marker`. The output collected so far is shortened if even the marker doesn't fit.
+	pub fn append(
+		&mut self,
+		msg: &str,
+		marker: impl FnOnce() -> scale_info::prelude::string::String,
+	) {
+		if self.truncated {
+			return
+		}
+		let bound = DebugBufferVec::<T>::bound();
+		if self.message.len().saturating_add(msg.len()) <= bound {
+			self.message.try_extend(&mut msg.bytes()).ok();
+			return
+		}
+
+		let marker = marker();
+		let keep = bound.saturating_sub(marker.len());
+		if self.message.len() > keep {
+			let keep = core::str::from_utf8(&self.message)
+				.map_or(keep, |output| floor_char_boundary(output, keep));
+			self.message.drain(keep..);
+		}
+		let msg = &msg[..floor_char_boundary(msg, keep - self.message.len())];
+		self.message.try_extend(&mut msg.bytes()).ok();
+		let marker = &marker[..floor_char_boundary(&marker, bound - self.message.len())];
+		self.message.try_extend(&mut marker.bytes()).ok();
+		self.truncated = true;
+	}
+
+	/// Returns the message, whether it was truncated, the last change to every storage key and
+	/// the I/O counters.
+	pub fn into_parts(
+		self,
+	) -> (Vec<u8>, bool, Vec<ContractStorageChange<T::AccountId>>, IoCounters) {
+		let mut seen = BTreeSet::new();
+		let mut changes: Vec<_> = self
+			.storage_changes
+			.into_iter()
+			.rev()
+			.filter(|change| seen.insert((change.contract.clone(), change.key.clone())))
+			.collect();
+		changes.reverse();
+		(self.message.into_inner(), self.truncated, changes, self.io_counters)
+	}
+}
+
+/// The largest index not greater than `index` that is at a char boundary of `s`.
+fn floor_char_boundary(s: &str, index: usize) -> usize {
+	(0..=index.min(s.len())).rev().find(|i| s.is_char_boundary(*i)).unwrap_or(0)
+}
+
+/// Deposit the event of `caller` calling into `contract`.
+fn deposit_called_event<T: Config>(caller: CallOrigin<T>, contract: &T::AccountId) {
+	// Signed callers keep the topic of their account so that existing subscriptions continue to
+	// match.
+	let caller_topic = match &caller {
+		CallOrigin::Signed(account) => T::Hashing::hash_of(account),
+		CallOrigin::Root => T::Hashing::hash_of(&caller),
+	};
+	Contracts::<T>::deposit_event(
+		vec![caller_topic, T::Hashing::hash_of(contract)],
+		Event::Called { caller, contract: contract.clone() },
+	);
+}
+
 /// The complete call stack of a contract execution.
 ///
 /// The call stack is initiated by either a signed origin or one of the contract RPC calls.
 /// This type implements `Ext` and by that exposes the business logic of contract execution to
 /// the runtime module which interfaces with the contract (the wasm blob) itself.
 pub struct Stack<'a, T: Config, E> {
-	/// The account id of a plain account that initiated the call stack.
+	/// The plain account or root that initiated the call stack.
 	///
 	/// # Note
 	///
 	/// Please note that it is possible that the id belongs to a contract rather than a plain
 	/// account when being called through one of the contract RPCs where the client can freely
 	/// choose the origin. This usually makes no sense but is still possible.
-	origin: T::AccountId,
+	origin: CallOrigin<T>,
 	/// The cost schedule used when charging from the gas meter.
 	schedule: &'a Schedule<T>,
 	/// The gas meter where costs are charged to.
@@ -407,9 +652,9 @@ pub struct Stack<'a, T: Config, E> {
 	first_frame: Frame<T>,
 	/// A text buffer used to output human readable information.
 	///
-	/// All the bytes added to this field should be valid UTF-8. The buffer has no defined
-	/// structure and is intended to be shown to users as-is for debugging purposes.
-	debug_message: Option<&'a mut DebugBufferVec<T>>,
+	/// The buffer has no defined structure and is intended to be shown to users as-is for
+	/// debugging purposes.
+	debug_message: Option<&'a mut DebugBuffer<T>>,
 	/// The determinism requirement of this call stack.
 	determinism: Determinism,
 	/// No executable is held by the struct but influences its behaviour.
@@ -441,15 +686,21 @@ pub struct Frame<T: Config> {
 	/// If `false` the contract enabled its defense against reentrance attacks.
 	allows_reentry: bool,
 	/// The caller of the currently executing frame which was spawned by `delegate_call`.
-	delegate_caller: Option<T::AccountId>,
+	delegate_caller: Option<CallOrigin<T>>,
+	/// The number of storage changes recorded in the debug buffer when this frame was pushed.
+	///
+	/// All changes after that are discarded if the frame is reverted.
+	storage_changes_checkpoint: usize,
+	/// The storage deposit consumed by the last sub call or instantiation of this frame.
+	last_subcall_deposit: DepositOf<T>,
 }
 
 /// Used in a delegate call frame arguments in order to override the executable and caller.
 struct DelegatedCall<T: Config, E> {
 	/// The executable which is run instead of the contracts own `executable`.
 	executable: E,
-	/// The account id of the caller contract.
-	caller: T::AccountId,
+	/// The caller of the caller contract.
+	caller: CallOrigin<T>,
 }
 
 /// Parameter passed in when creating a new `Frame`.
@@ -491,8 +742,9 @@ enum CachedContract<T: Config> {
 	/// The current contract executed `terminate` and removed the contract.
 	///
 	/// In this case a reload is neither allowed nor possible. Please note that recursive
-	/// calls cannot remove a contract as this is checked and denied.
-	Terminated,
+	/// calls cannot remove a contract as this is checked and denied. We keep the deposit
+	/// account in order to refund the deposit.
+	Terminated(DepositAccount<T>),
 }
 
 impl<T: Config> CachedContract<T> {
@@ -513,6 +765,18 @@ impl<T: Config> CachedContract<T> {
 			None
 		}
 	}
+
+	/// Return the deposit account of the contract.
+	///
+	/// This assumes that `load` was executed before calling this function.
+	fn deposit_account(&self) -> DepositAccount<T> {
+		match self {
+			CachedContract::Cached(contract) => contract.deposit_account.clone(),
+			CachedContract::Terminated(deposit_account) => deposit_account.clone(),
+			CachedContract::Invalidated =>
+				panic!("A contract on the call stack is always loadable; qed"),
+		}
+	}
 }
 
 impl<T: Config> Frame<T> {
@@ -575,7 +839,7 @@ impl<T: Config> CachedContract<T> {
 	/// Load the `contract_info` from storage if necessary.
 	fn load(&mut self, account_id: &T::AccountId) {
 		if let CachedContract::Invalidated = self {
-			let contract = <ContractInfoOf<T>>::get(&account_id);
+			let contract = ContractInfo::<T>::load(account_id);
 			if let Some(contract) = contract {
 				*self = CachedContract::Cached(contract);
 			}
@@ -590,8 +854,8 @@ impl<T: Config> CachedContract<T> {
 
 	/// Terminate and return the contract info.
 	fn terminate(&mut self, account_id: &T::AccountId) -> ContractInfo<T> {
-		self.load(account_id);
-		get_cached_or_panic_after_load!(mem::replace(self, Self::Terminated))
+		let deposit_account = self.get(account_id).deposit_account.clone();
+		get_cached_or_panic_after_load!(mem::replace(self, Self::Terminated(deposit_account)))
 	}
 }
 
@@ -611,16 +875,28 @@ where
 	///
 	/// Result<(ExecReturnValue, CodeSize), (ExecError, CodeSize)>
 	pub fn run_call(
-		origin: T::AccountId,
+		origin: CallOrigin<T>,
 		dest: T::AccountId,
 		gas_meter: &'a mut GasMeter<T>,
 		storage_meter: &'a mut storage::meter::Meter<T>,
 		schedule: &'a Schedule<T>,
 		value: BalanceOf<T>,
 		input_data: Vec<u8>,
-		debug_message: Option<&'a mut DebugBufferVec<T>>,
+		debug_message: Option<&'a mut DebugBuffer<T>>,
 		determinism: Determinism,
 	) -> Result<ExecReturnValue, ExecError> {
+		if let Some(weight) = T::Builtins::weight(&dest, input_data.len() as u32) {
+			return Self::run_builtin(
+				origin,
+				dest,
+				weight,
+				gas_meter,
+				storage_meter,
+				value,
+				&input_data,
+				debug_message,
+			)
+		}
 		let (mut stack, executable) = Self::new(
 			FrameArgs::Call { dest, cached_info: None, delegated_call: None },
 			origin,
@@ -653,7 +929,7 @@ where
 		value: BalanceOf<T>,
 		input_data: Vec<u8>,
 		salt: &[u8],
-		debug_message: Option<&'a mut DebugBufferVec<T>>,
+		debug_message: Option<&'a mut DebugBuffer<T>>,
 	) -> Result<(T::AccountId, ExecReturnValue), ExecError> {
 		let (mut stack, executable) = Self::new(
 			FrameArgs::Instantiate {
@@ -663,7 +939,7 @@ where
 				salt,
 				input_data: input_data.as_ref(),
 			},
-			origin,
+			CallOrigin::from_account_id(origin),
 			gas_meter,
 			storage_meter,
 			schedule,
@@ -678,12 +954,12 @@ where
 	/// Create a new call stack.
 	fn new(
 		args: FrameArgs<T, E>,
-		origin: T::AccountId,
+		origin: CallOrigin<T>,
 		gas_meter: &'a mut GasMeter<T>,
 		storage_meter: &'a mut storage::meter::Meter<T>,
 		schedule: &'a Schedule<T>,
 		value: BalanceOf<T>,
-		debug_message: Option<&'a mut DebugBufferVec<T>>,
+		debug_message: Option<&'a mut DebugBuffer<T>>,
 		determinism: Determinism,
 	) -> Result<(Self, E), ExecError> {
 		let (first_frame, executable, nonce) = Self::new_frame(
@@ -692,6 +968,7 @@ where
 			gas_meter,
 			storage_meter,
 			Weight::zero(),
+			BalanceOf::<T>::zero(),
 			schedule,
 			determinism,
 		)?;
@@ -723,6 +1000,7 @@ where
 		gas_meter: &mut GasMeter<T>,
 		storage_meter: &mut storage::meter::GenericMeter<T, S>,
 		gas_limit: Weight,
+		deposit_limit: BalanceOf<T>,
 		schedule: &Schedule<T>,
 		determinism: Determinism,
 	) -> Result<(Frame<T>, E, Option<u64>), ExecError> {
@@ -732,14 +1010,25 @@ where
 					let contract = if let Some(contract) = cached_info {
 						contract
 					} else {
-						<ContractInfoOf<T>>::get(&dest).ok_or(<Error<T>>::ContractNotFound)?
+						ContractInfo::<T>::load(&dest).ok_or(<Error<T>>::ContractNotFound)?
 					};
+					ensure!(!contract.paused, <Error<T>>::ContractPaused);
 
 					let (executable, delegate_caller) =
 						if let Some(DelegatedCall { executable, caller }) = delegated_call {
 							(executable, Some(caller))
 						} else {
-							(E::from_storage(contract.code_hash, schedule, gas_meter)?, None)
+							let executable =
+								E::from_storage(contract.code_hash, schedule, gas_meter).map_err(
+									|err| {
+										if <TombstonedCode<T>>::contains_key(contract.code_hash) {
+											<Error<T>>::CodeTombstoned.into()
+										} else {
+											err
+										}
+									},
+								)?;
+							(executable, None)
 						};
 
 					(dest, contract, executable, delegate_caller, ExportedFunction::Call, None)
@@ -781,8 +1070,10 @@ where
 			account_id,
 			entry_point,
 			nested_gas: gas_meter.nested(gas_limit)?,
-			nested_storage: storage_meter.nested(),
+			nested_storage: storage_meter.nested(deposit_limit),
 			allows_reentry: true,
+			storage_changes_checkpoint: 0,
+			last_subcall_deposit: Default::default(),
 		};
 
 		Ok((frame, executable, nonce))
@@ -794,6 +1085,7 @@ where
 		frame_args: FrameArgs<T, E>,
 		value_transferred: BalanceOf<T>,
 		gas_limit: Weight,
+		deposit_limit: BalanceOf<T>,
 	) -> Result<E, ExecError> {
 		if self.frames.len() == T::CallStack::size() {
 			return Err(Error::<T>::MaxCallDepthReached.into())
@@ -819,9 +1111,17 @@ where
 			nested_gas,
 			nested_storage,
 			gas_limit,
+			deposit_limit,
 			self.schedule,
 			self.determinism,
 		)?;
+		let frame = Frame {
+			storage_changes_checkpoint: self
+				.debug_message
+				.as_ref()
+				.map_or(0, |buffer| buffer.storage_changes.len()),
+			..frame
+		};
 		self.frames.push(frame);
 		Ok(executable)
 	}
@@ -830,20 +1130,40 @@ where
 	///
 	/// This can be either a call or an instantiate.
 	fn run(&mut self, executable: E, input_data: Vec<u8>) -> Result<ExecReturnValue, ExecError> {
+		let code_len = executable.code_len();
+		self.record_io(|io| io.code_bytes_loaded.saturating_accrue(code_len));
 		let frame = self.top_frame();
 		let entry_point = frame.entry_point;
+		let code_hash = *executable.code_hash();
 		let delegated_code_hash =
-			if frame.delegate_caller.is_some() { Some(*executable.code_hash()) } else { None };
+			if frame.delegate_caller.is_some() { Some(code_hash) } else { None };
+		let mut collect_stats = false;
 		let do_transaction = || {
+			if T::CollectCodeStats::get() {
+				top_frame_mut!(self).nested_gas.charge(CodeStatsToken)?;
+				collect_stats = true;
+			}
+
 			// We need to charge the storage deposit before the initial transfer so that
 			// it can create the account in case the initial transfer is < ed.
 			if entry_point == ExportedFunction::Constructor {
+				// Stateful address generators advance within the transaction so that a failed
+				// instantiation does not use up an address.
+				let caller = self.caller();
+				T::AddressGenerator::on_contract_created(caller.account_id()?);
+
+				let origin = self.origin.account_id()?;
 				let frame = top_frame_mut!(self);
-				frame.nested_storage.charge_instantiate(
-					&self.origin,
-					&frame.account_id,
-					frame.contract_info.get(&frame.account_id),
-				)?;
+				let contract = frame.contract_info.get(&frame.account_id);
+				frame.nested_storage.charge_instantiate(origin, &frame.account_id, contract)?;
+				Contracts::<T>::deposit_event(
+					vec![T::Hashing::hash_of(&frame.account_id), T::Hashing::hash_of(origin)],
+					Event::StorageBaseDepositCharged {
+						contract: frame.account_id.clone(),
+						payer: origin.clone(),
+						amount: contract.storage_base_deposit,
+					},
+				);
 			}
 
 			// Every non delegate call or instantiate also optionally transfers the balance.
@@ -859,6 +1179,12 @@ where
 				return Ok(output)
 			}
 
+			// Every contract must stay below the storage ceiling regardless of who pays for it.
+			let frame = top_frame_mut!(self);
+			frame.contract_info.load(&frame.account_id);
+			let contract = frame.contract_info.as_contract();
+			frame.nested_storage.enforce_storage_ceiling(contract.map(|c| &*c))?;
+
 			// Storage limit is enforced as late as possible (when the last frame returns) so that
 			// the ordering of storage accesses does not matter.
 			if self.frames.is_empty() {
@@ -866,6 +1192,17 @@ where
 				frame.contract_info.load(&frame.account_id);
 				let contract = frame.contract_info.as_contract();
 				frame.nested_storage.enforce_limit(contract)?;
+			} else {
+				// A sub call with its own deposit limit must not exceed it. Changes made by the
+				// caller after the sub call returns can't offset its usage. This is a failure of
+				// the callee so that the caller can handle it like a trap.
+				let frame = top_frame_mut!(self);
+				frame.contract_info.load(&frame.account_id);
+				let contract = frame.contract_info.as_contract();
+				frame
+					.nested_storage
+					.enforce_subcall_limit(contract.map(|c| &*c))
+					.map_err(|error| ExecError { error, origin: ErrorOrigin::Callee })?;
 			}
 
 			let frame = self.top_frame();
@@ -873,15 +1210,18 @@ where
 			match (entry_point, delegated_code_hash) {
 				(ExportedFunction::Constructor, _) => {
 					// It is not allowed to terminate a contract inside its constructor.
-					if matches!(frame.contract_info, CachedContract::Terminated) {
+					if matches!(frame.contract_info, CachedContract::Terminated(_)) {
 						return Err(Error::<T>::TerminatedInConstructor.into())
 					}
 
-					// Deposit an instantiation event.
+					// Deposit an instantiation event. Root can't instantiate so that the
+					// deployer is always an account.
+					let caller = self.caller();
+					let deployer = caller.account_id()?;
 					Contracts::<T>::deposit_event(
-						vec![T::Hashing::hash_of(self.caller()), T::Hashing::hash_of(account_id)],
+						vec![T::Hashing::hash_of(deployer), T::Hashing::hash_of(account_id)],
 						Event::Instantiated {
-							deployer: self.caller().clone(),
+							deployer: deployer.clone(),
 							contract: account_id.clone(),
 						},
 					);
@@ -893,11 +1233,7 @@ where
 					);
 				},
 				(ExportedFunction::Call, None) => {
-					let caller = self.caller();
-					Contracts::<T>::deposit_event(
-						vec![T::Hashing::hash_of(caller), T::Hashing::hash_of(account_id)],
-						Event::Called { caller: caller.clone(), contract: account_id.clone() },
-					);
+					deposit_called_event::<T>(self.caller(), account_id);
 				},
 			}
 
@@ -928,15 +1264,116 @@ where
 			Err(error) => (false, Err(error.into())),
 		};
 
+		if collect_stats {
+			let gas_consumed = self.top_frame().nested_gas.gas_consumed();
+			<CodeStatsOf<T>>::mutate(code_hash, |stats| {
+				stats.calls.saturating_inc();
+				if !success {
+					stats.reverts.saturating_inc();
+				}
+				stats.gas_consumed.saturating_accrue(gas_consumed);
+			});
+		}
+
 		self.pop_frame(success);
 		output
 	}
 
+	/// Execute the builtin at `address` on behalf of the current frame.
+	///
+	/// No frame is pushed for the builtin. Its `weight` is charged from the gas meter of the
+	/// current frame.
+	fn call_builtin(
+		&mut self,
+		weight: Weight,
+		address: &T::AccountId,
+		value: BalanceOf<T>,
+		input_data: &[u8],
+	) -> Result<ExecReturnValue, ExecError> {
+		let gas_meter = &mut self.top_frame_mut().nested_gas;
+		Self::execute_builtin(gas_meter, weight, address, value, input_data)
+	}
+
+	/// Run the builtin at `dest` as the only callee of a new call stack.
+	///
+	/// No frame is created, but the builtin is metered, emits events and reports to the debug
+	/// buffer just like a contract that `origin` calls directly.
+	fn run_builtin(
+		origin: CallOrigin<T>,
+		dest: T::AccountId,
+		weight: Weight,
+		gas_meter: &mut GasMeter<T>,
+		storage_meter: &mut storage::meter::Meter<T>,
+		value: BalanceOf<T>,
+		input_data: &[u8],
+		debug_message: Option<&mut DebugBuffer<T>>,
+	) -> Result<ExecReturnValue, ExecError> {
+		let mut nested_gas = gas_meter.nested(Weight::zero())?;
+		let mut nested_storage = storage_meter.nested(BalanceOf::<T>::zero());
+		let output = Self::execute_builtin(&mut nested_gas, weight, &dest, value, input_data)
+			.and_then(|output| {
+				// A builtin has no storage of its own. The limit of the origin still applies.
+				nested_storage.enforce_limit(None)?;
+				Ok(output)
+			});
+		gas_meter.absorb_nested(nested_gas);
+
+		match &output {
+			Ok(_) => {
+				storage_meter.absorb(nested_storage, DepositAccount::new(&dest), None);
+				deposit_called_event::<T>(origin, &dest);
+			},
+			Err(err) =>
+				if let Some(buffer) = debug_message {
+					let msg = scale_info::prelude::format!(
+						"Builtin {:?} failed: {}",
+						dest,
+						<&'static str>::from(err.error),
+					);
+					buffer.append(&msg, || "\n<debug buffer full>".into());
+				},
+		}
+		output
+	}
+
+	/// Charge `weight` from `gas_meter` and execute the builtin at `address`.
+	///
+	/// This is used for calls from contracts as well as for calls made directly by an origin.
+	/// The storage changes of the builtin are rolled back if it fails.
+	fn execute_builtin(
+		gas_meter: &mut GasMeter<T>,
+		weight: Weight,
+		address: &T::AccountId,
+		value: BalanceOf<T>,
+		input_data: &[u8],
+	) -> Result<ExecReturnValue, ExecError> {
+		ensure!(value.is_zero(), Error::<T>::BuiltinNotPayable);
+		gas_meter.charge(BuiltinToken(weight))?;
+		let result = with_transaction(|| -> TransactionOutcome<Result<_, DispatchError>> {
+			match T::Builtins::call(address, input_data) {
+				Some(Ok(data)) => TransactionOutcome::Commit(Ok(Ok(data))),
+				Some(Err(err)) => TransactionOutcome::Rollback(Ok(Err(err))),
+				None => TransactionOutcome::Rollback(Err(Error::<T>::ContractNotFound.into())),
+			}
+		})?;
+		match result {
+			Ok(data) => Ok(ExecReturnValue { flags: ReturnFlags::empty(), data }),
+			Err(error) => Err(ExecError { error, origin: ErrorOrigin::Callee }),
+		}
+	}
+
 	/// Remove the current (top) frame from the stack.
 	///
 	/// This is called after running the current frame. It commits cached values to storage
 	/// and invalidates all stale references to it that might exist further down the call stack.
 	fn pop_frame(&mut self, persist: bool) {
+		if !persist {
+			let checkpoint = self.top_frame().storage_changes_checkpoint;
+			if let Some(buffer) = &mut self.debug_message {
+				buffer.storage_changes.truncate(checkpoint);
+			}
+		}
+
 		// Revert changes to the nonce in case of a failed instantiation.
 		if !persist && self.top_frame().entry_point == ExportedFunction::Constructor {
 			self.nonce.as_mut().map(|c| *c = c.wrapping_sub(1));
@@ -965,8 +1402,13 @@ where
 			// in its contract info. The load is necessary to to pull it from storage in case
 			// it was invalidated.
 			frame.contract_info.load(account_id);
+			let deposit_account = frame.contract_info.deposit_account();
 			let mut contract = frame.contract_info.into_contract();
-			prev.nested_storage.absorb(frame.nested_storage, account_id, contract.as_mut());
+			prev.last_subcall_deposit = prev.nested_storage.absorb(
+				frame.nested_storage,
+				deposit_account,
+				contract.as_mut(),
+			);
 
 			// In case the contract wasn't terminated we need to persist changes made to it.
 			if let Some(contract) = contract {
@@ -1001,10 +1443,12 @@ where
 			if !persist {
 				return
 			}
+			self.first_frame.contract_info.load(&self.first_frame.account_id);
+			let deposit_account = self.first_frame.contract_info.deposit_account();
 			let mut contract = self.first_frame.contract_info.as_contract();
 			self.storage_meter.absorb(
 				mem::take(&mut self.first_frame.nested_storage),
-				&self.first_frame.account_id,
+				deposit_account,
 				contract.as_deref_mut(),
 			);
 			if let Some(contract) = contract {
@@ -1028,6 +1472,14 @@ where
 		Ok(())
 	}
 
+	/// The amount that can be taken from `account` without removing it.
+	///
+	/// Unlike a [`ExistenceRequirement::KeepAlive`] transfer this includes balance that is only
+	/// kept alive by reserved deposits.
+	fn max_removable(account: &T::AccountId) -> BalanceOf<T> {
+		T::Currency::total_balance(account).saturating_sub(Contracts::<T>::min_balance())
+	}
+
 	// The transfer as performed by a call or instantiate.
 	fn initial_transfer(&self) -> DispatchResult {
 		let frame = self.top_frame();
@@ -1039,7 +1491,17 @@ where
 		}
 
 		let value = frame.value_transferred;
-		Self::transfer(ExistenceRequirement::KeepAlive, self.caller(), &frame.account_id, value)
+		let caller = self.caller();
+		// Root has no account to transfer from.
+		if caller.is_root() && value.is_zero() {
+			return Ok(())
+		}
+		Self::transfer(
+			ExistenceRequirement::KeepAlive,
+			caller.account_id()?,
+			&frame.account_id,
+			value,
+		)
 	}
 
 	/// Reference to the current (top) frame.
@@ -1064,6 +1526,44 @@ where
 		sp_std::iter::once(&mut self.first_frame).chain(&mut self.frames).rev()
 	}
 
+	/// Records a change to the storage of the current contract in the debug buffer.
+	///
+	/// A `key` of `None` denotes that the whole storage was removed.
+	fn record_storage_change(&mut self, key: Option<&[u8]>, removed: bool) {
+		if let Some(buffer) = &mut self.debug_message {
+			let contract = top_frame!(self).account_id.clone();
+			buffer.storage_changes.push(ContractStorageChange {
+				contract,
+				key: key.map(|key| key.to_vec()),
+				removed,
+			});
+		}
+	}
+
+	/// Updates the I/O counters of the debug buffer if there is one.
+	fn record_io(&mut self, f: impl FnOnce(&mut IoCounters)) {
+		if let Some(buffer) = &mut self.debug_message {
+			f(&mut buffer.io_counters);
+		}
+	}
+
+	/// Records a storage read which returned `value_len` bytes.
+	fn record_storage_read(&mut self, value_len: Option<u32>) {
+		self.record_io(|io| {
+			io.storage_reads.saturating_inc();
+			io.storage_bytes_read.saturating_accrue(value_len.unwrap_or(0));
+		});
+	}
+
+	/// Records a write of `value` to storage.
+	fn record_storage_write(&mut self, value: &Option<Vec<u8>>) {
+		let len = value.as_ref().map_or(0, |value| value.len() as u32);
+		self.record_io(|io| {
+			io.storage_writes.saturating_inc();
+			io.storage_bytes_written.saturating_accrue(len);
+		});
+	}
+
 	/// Returns whether the current contract is on the stack multiple times.
 	fn is_recursive(&self) -> bool {
 		let account_id = &self.top_frame().account_id;
@@ -1075,6 +1575,20 @@ where
 		!self.frames().any(|f| &f.account_id == id && !f.allows_reentry)
 	}
 
+	/// Returns whether the specified contract is on the stack and denies to be reentered.
+	///
+	/// The topmost frame of the contract holds its most recent contract info.
+	fn denies_reentry(&mut self, id: &AccountIdOf<T>) -> Result<bool, DispatchError> {
+		match self.frames().find(|f| &f.account_id == id).map(|f| &f.contract_info) {
+			Some(CachedContract::Cached(contract)) => return Ok(contract.deny_reentry),
+			Some(CachedContract::Invalidated) => (),
+			Some(CachedContract::Terminated(_)) | None => return Ok(false),
+		}
+		// The cached contract info is stale and needs to be read from storage again.
+		self.top_frame_mut().nested_gas.charge(ContractInfoReadToken)?;
+		Ok(ContractInfo::<T>::load(id).map_or(false, |contract| contract.deny_reentry))
+	}
+
 	/// Increments and returns the next nonce. Pulls it from storage if it isn't in cache.
 	fn next_nonce(&mut self) -> u64 {
 		let next = self.nonce().wrapping_add(1);
@@ -1093,6 +1607,7 @@ where
 	fn call(
 		&mut self,
 		gas_limit: Weight,
+		deposit_limit: BalanceOf<T>,
 		to: T::AccountId,
 		value: BalanceOf<T>,
 		input_data: Vec<u8>,
@@ -1101,10 +1616,15 @@ where
 		// Before pushing the new frame: Protect the caller contract against reentrancy attacks.
 		// It is important to do this before calling `allows_reentry` so that a direct recursion
 		// is caught by it.
-		self.top_frame_mut().allows_reentry = allows_reentry;
+		let frame = self.top_frame_mut();
+		frame.allows_reentry = allows_reentry;
+		frame.last_subcall_deposit = Default::default();
 
 		let try_call = || {
-			if !self.allows_reentry(&to) {
+			if let Some(weight) = T::Builtins::weight(&to, input_data.len() as u32) {
+				return self.call_builtin(weight, &to, value, &input_data)
+			}
+			if !self.allows_reentry(&to) || self.denies_reentry(&to)? {
 				return Err(<Error<T>>::ReentranceDenied.into())
 			}
 			// We ignore instantiate frames in our search for a cached contract.
@@ -1121,6 +1641,7 @@ where
 				FrameArgs::Call { dest: to, cached_info, delegated_call: None },
 				value,
 				gas_limit,
+				deposit_limit,
 			)?;
 			self.run(executable, input_data)
 		};
@@ -1136,9 +1657,12 @@ where
 
 	fn delegate_call(
 		&mut self,
+		gas_limit: Weight,
+		deposit_limit: BalanceOf<Self::T>,
 		code_hash: CodeHash<Self::T>,
 		input_data: Vec<u8>,
 	) -> Result<ExecReturnValue, ExecError> {
+		self.top_frame_mut().last_subcall_deposit = Default::default();
 		let executable = E::from_storage(code_hash, self.schedule, self.gas_meter())?;
 		let top_frame = self.top_frame_mut();
 		let contract_info = top_frame.contract_info().clone();
@@ -1148,10 +1672,11 @@ where
 			FrameArgs::Call {
 				dest: account_id,
 				cached_info: Some(contract_info),
-				delegated_call: Some(DelegatedCall { executable, caller: self.caller().clone() }),
+				delegated_call: Some(DelegatedCall { executable, caller: self.caller() }),
 			},
 			value,
-			Weight::zero(),
+			gas_limit,
+			deposit_limit,
 		)?;
 		self.run(executable, input_data)
 	}
@@ -1159,11 +1684,13 @@ where
 	fn instantiate(
 		&mut self,
 		gas_limit: Weight,
+		deposit_limit: BalanceOf<Self::T>,
 		code_hash: CodeHash<T>,
 		value: BalanceOf<T>,
 		input_data: Vec<u8>,
 		salt: &[u8],
 	) -> Result<(AccountIdOf<T>, ExecReturnValue), ExecError> {
+		self.top_frame_mut().last_subcall_deposit = Default::default();
 		let executable = E::from_storage(code_hash, self.schedule, self.gas_meter())?;
 		let nonce = self.next_nonce();
 		let executable = self.push_frame(
@@ -1176,6 +1703,7 @@ where
 			},
 			value,
 			gas_limit,
+			deposit_limit,
 		)?;
 		let account_id = self.top_frame().account_id.clone();
 		self.run(executable, input_data).map(|ret| (account_id, ret))
@@ -1185,10 +1713,11 @@ where
 		if self.is_recursive() {
 			return Err(Error::<T>::TerminatedWhileReentrant.into())
 		}
+		let origin = self.origin.account_id()?.clone();
 		let frame = self.top_frame_mut();
 		let info = frame.terminate();
 		frame.nested_storage.terminate(&info);
-		Storage::<T>::queue_trie_for_deletion(&info)?;
+		Storage::<T>::queue_trie_for_deletion(&info, &frame.account_id)?;
 		<Stack<'a, T, E>>::transfer(
 			ExistenceRequirement::AllowDeath,
 			&frame.account_id,
@@ -1197,6 +1726,17 @@ where
 		)?;
 		ContractInfoOf::<T>::remove(&frame.account_id);
 		E::remove_user(info.code_hash);
+		for code_hash in info.delegate_dependencies.keys() {
+			E::remove_user(*code_hash);
+		}
+		Contracts::<T>::deposit_event(
+			vec![T::Hashing::hash_of(&frame.account_id), T::Hashing::hash_of(&origin)],
+			Event::StorageBaseDepositRefunded {
+				contract: frame.account_id.clone(),
+				payee: origin,
+				amount: info.storage_base_deposit,
+			},
+		);
 		Contracts::<T>::deposit_event(
 			vec![T::Hashing::hash_of(&frame.account_id), T::Hashing::hash_of(&beneficiary)],
 			Event::Terminated {
@@ -1204,27 +1744,63 @@ where
 				beneficiary: beneficiary.clone(),
 			},
 		);
+		self.record_storage_change(None, true);
 		Ok(())
 	}
 
-	fn transfer(&mut self, to: &T::AccountId, value: BalanceOf<T>) -> DispatchResult {
-		Self::transfer(ExistenceRequirement::KeepAlive, &self.top_frame().account_id, to, value)
+	fn transfer(
+		&mut self,
+		to: &T::AccountId,
+		value: BalanceOf<T>,
+		existence_requirement: ExistenceRequirement,
+	) -> DispatchResult {
+		let from = &self.top_frame().account_id;
+		if existence_requirement == ExistenceRequirement::AllowDeath {
+			// The account must outlive the contract which is only removed by `terminate`.
+			ensure!(value <= Self::max_removable(from), Error::<T>::TransferFailed);
+		}
+		Self::transfer(existence_requirement, from, to, value)
+	}
+
+	fn transfer_all(
+		&mut self,
+		to: &T::AccountId,
+		existence_requirement: ExistenceRequirement,
+	) -> DispatchResult {
+		use frame_support::traits::tokens::fungible::Inspect;
+		let from = &self.top_frame().account_id;
+		let value = match existence_requirement {
+			ExistenceRequirement::KeepAlive =>
+				<T::Currency as Inspect<_>>::reducible_balance(from, true),
+			ExistenceRequirement::AllowDeath =>
+				<T::Currency as Inspect<_>>::reducible_balance(from, false)
+					.min(Self::max_removable(from)),
+		};
+		Self::transfer(existence_requirement, from, to, value)
 	}
 
 	fn get_storage(&mut self, key: &FixSizedKey) -> Option<Vec<u8>> {
-		Storage::<T>::read(&self.top_frame_mut().contract_info().trie_id, key)
+		let value = Storage::<T>::read(&self.top_frame_mut().contract_info().trie_id, key);
+		self.record_storage_read(value.as_ref().map(|value| value.len() as u32));
+		value
 	}
 
 	fn get_storage_transparent(&mut self, key: &VarSizedKey<T>) -> Option<Vec<u8>> {
-		Storage::<T>::read(&self.top_frame_mut().contract_info().trie_id, key)
+		let value = Storage::<T>::read(&self.top_frame_mut().contract_info().trie_id, key);
+		self.record_storage_read(value.as_ref().map(|value| value.len() as u32));
+		value
 	}
 
 	fn get_storage_size(&mut self, key: &FixSizedKey) -> Option<u32> {
-		Storage::<T>::size(&self.top_frame_mut().contract_info().trie_id, key)
+		let size = Storage::<T>::size(&self.top_frame_mut().contract_info().trie_id, key);
+		self.record_storage_read(None);
+		size
 	}
 
 	fn get_storage_size_transparent(&mut self, key: &VarSizedKey<T>) -> Option<u32> {
-		Storage::<T>::size(&self.top_frame_mut().contract_info().trie_id, key)
+		let size = Storage::<T>::size(&self.top_frame_mut().contract_info().trie_id, key);
+		self.record_storage_read(None);
+		size
 	}
 
 	fn set_storage(
@@ -1233,14 +1809,21 @@ where
 		value: Option<Vec<u8>>,
 		take_old: bool,
 	) -> Result<WriteOutcome, DispatchError> {
+		let removed = value.is_none();
+		self.record_storage_write(&value);
 		let frame = self.top_frame_mut();
-		Storage::<T>::write(
+		let outcome = Storage::<T>::write(
 			&frame.contract_info.get(&frame.account_id).trie_id,
 			key,
 			value,
 			Some(&mut frame.nested_storage),
 			take_old,
-		)
+		)?;
+		self.record_storage_change(Some(key.as_slice()), removed);
+		if let WriteOutcome::Taken(old) = &outcome {
+			self.record_storage_read(Some(old.len() as u32));
+		}
+		Ok(outcome)
 	}
 
 	fn set_storage_transparent(
@@ -1249,25 +1832,35 @@ where
 		value: Option<Vec<u8>>,
 		take_old: bool,
 	) -> Result<WriteOutcome, DispatchError> {
+		let removed = value.is_none();
+		self.record_storage_write(&value);
 		let frame = self.top_frame_mut();
-		Storage::<T>::write(
+		let outcome = Storage::<T>::write(
 			&frame.contract_info.get(&frame.account_id).trie_id,
 			key,
 			value,
 			Some(&mut frame.nested_storage),
 			take_old,
-		)
+		)?;
+		self.record_storage_change(Some(key.as_slice()), removed);
+		if let WriteOutcome::Taken(old) = &outcome {
+			self.record_storage_read(Some(old.len() as u32));
+		}
+		Ok(outcome)
 	}
 
 	fn address(&self) -> &T::AccountId {
 		&self.top_frame().account_id
 	}
 
-	fn caller(&self) -> &T::AccountId {
+	fn caller(&self) -> CallOrigin<T> {
 		if let Some(caller) = &self.top_frame().delegate_caller {
-			caller
+			caller.clone()
 		} else {
-			self.frames().nth(1).map(|f| &f.account_id).unwrap_or(&self.origin)
+			self.frames()
+				.nth(1)
+				.map(|f| CallOrigin::from_account_id(f.account_id.clone()))
+				.unwrap_or_else(|| self.origin.clone())
 		}
 	}
 
@@ -1276,7 +1869,7 @@ where
 	}
 
 	fn code_hash(&self, address: &T::AccountId) -> Option<CodeHash<Self::T>> {
-		<ContractInfoOf<T>>::get(&address).map(|contract| contract.code_hash)
+		ContractInfo::<T>::load(address).map(|contract| contract.code_hash)
 	}
 
 	fn own_code_hash(&mut self) -> &CodeHash<Self::T> {
@@ -1284,17 +1877,26 @@ where
 	}
 
 	fn caller_is_origin(&self) -> bool {
-		self.caller() == &self.origin
+		self.caller() == self.origin
+	}
+
+	fn caller_is_root(&self) -> bool {
+		self.caller().is_root()
 	}
 
 	fn balance(&self) -> BalanceOf<T> {
 		T::Currency::free_balance(&self.top_frame().account_id)
+			.saturating_sub(Contracts::<T>::min_balance())
 	}
 
 	fn value_transferred(&self) -> BalanceOf<T> {
 		self.top_frame().value_transferred
 	}
 
+	fn last_subcall_deposit(&self) -> DepositOf<T> {
+		self.top_frame().last_subcall_deposit.clone()
+	}
+
 	fn random(&self, subject: &[u8]) -> (SeedOf<T>, BlockNumberOf<T>) {
 		T::Randomness::random(subject)
 	}
@@ -1308,9 +1910,13 @@ where
 	}
 
 	fn deposit_event(&mut self, topics: Vec<T::Hash>, data: Vec<u8>) {
+		let frame = self.top_frame_mut();
+		frame
+			.nested_storage
+			.charge(&Diff { event_bytes: data.len() as u32, ..Default::default() });
 		Contracts::<Self::T>::deposit_event(
 			topics,
-			Event::ContractEmitted { contract: self.top_frame().account_id.clone(), data },
+			Event::ContractEmitted { contract: frame.account_id.clone(), data },
 		);
 	}
 
@@ -1336,31 +1942,26 @@ where
 
 	fn append_debug_buffer(&mut self, msg: &str) -> bool {
 		if let Some(buffer) = &mut self.debug_message {
-			let err_msg = scale_info::prelude::format!(
-				"Debug message too big (size={}) for debug buffer (bound={})",
-				msg.len(),
-				DebugBufferVec::<T>::bound(),
-			);
-
-			let mut msg = if msg.len() > DebugBufferVec::<T>::bound() {
-				err_msg.bytes()
-			} else {
-				msg.bytes()
-			};
-
-			let num_drain = {
-				let capacity = DebugBufferVec::<T>::bound().checked_sub(buffer.len()).expect(
-					"
-					`buffer` is of type `DebugBufferVec`,
-					`DebugBufferVec` is a `BoundedVec`,
-					`BoundedVec::len()` <= `BoundedVec::bound()`;
-					qed
-				",
+			// Borrow the fields directly as `self.debug_message` is already borrowed mutably.
+			let depth = self.frames.len() + 1;
+			let address = &self.frames.last().unwrap_or(&self.first_frame).account_id;
+			buffer.append(msg, || {
+				scale_info::prelude::format!(
+					"\n<debug buffer full: truncated in frame {} of {:?}>",
+					depth,
+					address,
+				)
+			});
+			true
+		} else if T::Debugging::get() == DebugPolicy::Log {
+			if !msg.is_empty() {
+				log::debug!(
+					target: "runtime::contracts::debug",
+					"{:?}: {}",
+					self.frames.last().unwrap_or(&self.first_frame).account_id,
+					msg,
 				);
-				msg.len().saturating_sub(capacity).min(buffer.len())
-			};
-			buffer.drain(0..num_drain);
-			buffer.try_extend(&mut msg).ok();
+			}
 			true
 		} else {
 			false
@@ -1368,8 +1969,16 @@ where
 	}
 
 	fn call_runtime(&self, call: <Self::T as Config>::RuntimeCall) -> DispatchResultWithPostInfo {
-		let mut origin: T::RuntimeOrigin = RawOrigin::Signed(self.address().clone()).into();
+		let mut origin = T::CallRuntimeOrigin::convert(self.address().clone());
 		origin.add_filter(T::CallFilter::contains);
+		// The cached info is only stale if a reentrant call changed it.
+		let contract = match &self.top_frame().contract_info {
+			CachedContract::Cached(contract) => Some(contract.clone()),
+			_ => ContractInfo::<T>::load(self.address()),
+		};
+		if let Some(contract) = contract.filter(|contract| contract.call_filter.is_some()) {
+			origin.add_filter(move |call| contract.call_allowed(call));
+		}
 		call.dispatch(origin)
 	}
 
@@ -1381,6 +1990,18 @@ where
 		ECDSAPublic(*pk).to_eth_address()
 	}
 
+	fn xcm_execute(&self, message: &[u8], weight_limit: Weight) -> DispatchResultWithPostInfo {
+		T::Xcm::execute(self.address(), message, weight_limit)
+	}
+
+	fn xcm_send(&self, dest: &[u8], message: &[u8]) -> Result<XcmHash, DispatchError> {
+		T::Xcm::send(self.address(), dest, message)
+	}
+
+	fn asset_transfer(&self, asset: &[u8], to: &T::AccountId, amount: &[u8]) -> DispatchResult {
+		T::Assets::transfer(asset, self.address(), to, amount)
+	}
+
 	#[cfg(test)]
 	fn contract_info(&mut self) -> &mut ContractInfo<Self::T> {
 		self.top_frame_mut().contract_info()
@@ -1388,7 +2009,11 @@ where
 
 	fn set_code_hash(&mut self, hash: CodeHash<Self::T>) -> Result<(), DispatchError> {
 		let frame = top_frame_mut!(self);
-		if !E::from_storage(hash, self.schedule, &mut frame.nested_gas)?.is_deterministic() {
+		let executable = E::from_storage(hash, self.schedule, &mut frame.nested_gas)?;
+		if let Some(buffer) = &mut self.debug_message {
+			buffer.io_counters.code_bytes_loaded.saturating_accrue(executable.code_len());
+		}
+		if !executable.is_deterministic() {
 			return Err(<Error<T>>::Indeterministic.into())
 		}
 		E::add_user(hash)?;
@@ -1426,6 +2051,46 @@ where
 			current
 		}
 	}
+
+	fn storage_deposit_limit_left(&mut self) -> BalanceOf<T> {
+		let frame = top_frame_mut!(self);
+		let info = frame.contract_info.get(&frame.account_id);
+		frame.nested_storage.available_with(info)
+	}
+
+	fn set_deny_reentry(&mut self, deny: bool) {
+		top_frame_mut!(self).contract_info().deny_reentry = deny;
+	}
+
+	fn lock_delegate_dependency(&mut self, code_hash: CodeHash<Self::T>) -> DispatchResult {
+		let info = top_frame_mut!(self).contract_info();
+		ensure!(code_hash != info.code_hash, <Error<T>>::CannotAddSelfAsDelegateDependency);
+		let max_reached = !info.delegate_dependencies.contains_key(&code_hash) &&
+			info.delegate_dependencies.len() >= T::MaxDelegateDependencies::get() as usize;
+		ensure!(!max_reached, <Error<T>>::MaxDelegateDependenciesReached);
+		// The entry is only inserted once the code is referenced successfully. The bound was
+		// checked above, so the insertion itself can't fail after `add_user`.
+		info.delegate_dependencies
+			.try_mutate_entry(code_hash, |entry| -> DispatchResult {
+				ensure!(entry.is_none(), <Error<T>>::DelegateDependencyAlreadyExists);
+				E::add_user(code_hash)?;
+				*entry = Some(());
+				Ok(())
+			})
+			.unwrap_or_else(|err| err)
+	}
+
+	fn unlock_delegate_dependency(&mut self, code_hash: &CodeHash<Self::T>) -> DispatchResult {
+		let info = top_frame_mut!(self).contract_info();
+		info.delegate_dependencies
+			.try_mutate_entry(*code_hash, |entry| -> DispatchResult {
+				entry.take().ok_or(<Error<T>>::DelegateDependencyNotFound)?;
+				E::remove_user(*code_hash);
+				Ok(())
+			})
+			// Removing an entry never exceeds the bound.
+			.unwrap_or_else(|err| err)
+	}
 }
 
 mod sealing {
@@ -1440,6 +2105,9 @@ mod sealing {
 
 	#[cfg(test)]
 	impl Sealed for &mut crate::wasm::MockExt {}
+
+	#[cfg(any(test, feature = "test-helpers"))]
+	impl<T: Config> Sealed for crate::chain_extension::testing::MockExt<T> {}
 }
 
 /// These tests exercise the executive layer.
@@ -1455,9 +2123,9 @@ mod tests {
 		gas::GasMeter,
 		storage::Storage,
 		tests::{
-			test_utils::{get_balance, hash, place_contract, set_balance},
-			ExtBuilder, RuntimeCall, RuntimeEvent as MetaEvent, Test, TestFilter, ALICE, BOB,
-			CHARLIE, GAS_LIMIT,
+			test_utils::{get_balance, get_contract, hash, place_contract, set_balance},
+			ExtBuilder, HashBuiltin, RuntimeCall, RuntimeEvent as MetaEvent, Test, TestAssets,
+			TestFilter, XcmMessages, ALICE, BOB, CHARLIE, GAS_LIMIT,
 		},
 		Error,
 	};
@@ -1467,7 +2135,7 @@ mod tests {
 	use frame_system::{EventRecord, Phase};
 	use pallet_contracts_primitives::ReturnFlags;
 	use pretty_assertions::assert_eq;
-	use sp_runtime::{traits::Hash, DispatchError};
+	use sp_runtime::{traits::Hash, DispatchError, ModuleError};
 	use std::{
 		cell::RefCell,
 		collections::hash_map::{Entry, HashMap},
@@ -1626,11 +2294,13 @@ mod tests {
 		ExtBuilder::default().build().execute_with(|| {
 			let schedule = <Test as Config>::Schedule::get();
 			place_contract(&BOB, exec_ch);
-			let mut storage_meter = storage::meter::Meter::new(&ALICE, Some(0), value).unwrap();
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(0), value)
+					.unwrap();
 
 			assert_matches!(
 				MockStack::run_call(
-					ALICE,
+					CallOrigin::from_account_id(ALICE),
 					BOB,
 					&mut gas_meter,
 					&mut storage_meter,
@@ -1666,10 +2336,49 @@ mod tests {
 	}
 
 	#[test]
-	fn correct_transfer_on_call() {
-		let origin = ALICE;
-		let dest = BOB;
-		let value = 55;
+	fn contract_transfer_never_removes_account() {
+		let code_bob = MockLoader::insert(Call, |ctx, _| {
+			// Would leave less than the existential deposit.
+			assert_err!(
+				ctx.ext.transfer(&ALICE, 140, ExistenceRequirement::AllowDeath),
+				<Error<Test>>::TransferFailed,
+			);
+			assert_ok!(ctx.ext.transfer_all(&ALICE, ExistenceRequirement::KeepAlive));
+			assert_eq!(get_balance(&BOB), 15);
+			assert_ok!(ctx.ext.transfer_all(&ALICE, ExistenceRequirement::AllowDeath));
+			assert_eq!(get_balance(&BOB), 15);
+			exec_success()
+		});
+
+		ExtBuilder::default().existential_deposit(15).build().execute_with(|| {
+			let schedule = <Test as Config>::Schedule::get();
+			// gives the contract a balance of 150.
+			place_contract(&BOB, code_bob);
+			set_balance(&ALICE, 100);
+
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(0), 0)
+					.unwrap();
+			assert_ok!(MockStack::run_call(
+				CallOrigin::from_account_id(ALICE),
+				BOB,
+				&mut GasMeter::<Test>::new(GAS_LIMIT),
+				&mut storage_meter,
+				&schedule,
+				0,
+				vec![],
+				None,
+				Determinism::Deterministic,
+			));
+			assert_eq!(get_balance(&ALICE), 235);
+		});
+	}
+
+	#[test]
+	fn correct_transfer_on_call() {
+		let origin = ALICE;
+		let dest = BOB;
+		let value = 55;
 
 		let success_ch = MockLoader::insert(Call, move |ctx, _| {
 			assert_eq!(ctx.ext.value_transferred(), value);
@@ -1681,10 +2390,15 @@ mod tests {
 			place_contract(&dest, success_ch);
 			set_balance(&origin, 100);
 			let balance = get_balance(&dest);
-			let mut storage_meter = storage::meter::Meter::new(&origin, Some(0), 55).unwrap();
+			let mut storage_meter = storage::meter::Meter::new(
+				&CallOrigin::from_account_id(origin.clone()),
+				Some(0),
+				55,
+			)
+			.unwrap();
 
 			let _ = MockStack::run_call(
-				origin.clone(),
+				CallOrigin::from_account_id(origin.clone()),
 				dest.clone(),
 				&mut GasMeter::<Test>::new(GAS_LIMIT),
 				&mut storage_meter,
@@ -1714,7 +2428,7 @@ mod tests {
 
 		let delegate_ch = MockLoader::insert(Call, move |ctx, _| {
 			assert_eq!(ctx.ext.value_transferred(), value);
-			let _ = ctx.ext.delegate_call(success_ch, Vec::new())?;
+			let _ = ctx.ext.delegate_call(Weight::zero(), 0, success_ch, Vec::new())?;
 			Ok(ExecReturnValue { flags: ReturnFlags::empty(), data: Vec::new() })
 		});
 
@@ -1723,10 +2437,15 @@ mod tests {
 			place_contract(&dest, delegate_ch);
 			set_balance(&origin, 100);
 			let balance = get_balance(&dest);
-			let mut storage_meter = storage::meter::Meter::new(&origin, Some(0), 55).unwrap();
+			let mut storage_meter = storage::meter::Meter::new(
+				&CallOrigin::from_account_id(origin.clone()),
+				Some(0),
+				55,
+			)
+			.unwrap();
 
 			let _ = MockStack::run_call(
-				origin.clone(),
+				CallOrigin::from_account_id(origin.clone()),
 				dest.clone(),
 				&mut GasMeter::<Test>::new(GAS_LIMIT),
 				&mut storage_meter,
@@ -1759,10 +2478,15 @@ mod tests {
 			place_contract(&dest, return_ch);
 			set_balance(&origin, 100);
 			let balance = get_balance(&dest);
-			let mut storage_meter = storage::meter::Meter::new(&origin, Some(0), 55).unwrap();
+			let mut storage_meter = storage::meter::Meter::new(
+				&CallOrigin::from_account_id(origin.clone()),
+				Some(0),
+				55,
+			)
+			.unwrap();
 
 			let output = MockStack::run_call(
-				origin.clone(),
+				CallOrigin::from_account_id(origin.clone()),
 				dest.clone(),
 				&mut GasMeter::<Test>::new(GAS_LIMIT),
 				&mut storage_meter,
@@ -1810,11 +2534,16 @@ mod tests {
 
 		ExtBuilder::default().build().execute_with(|| {
 			let schedule = <Test as Config>::Schedule::get();
-			let mut storage_meter = storage::meter::Meter::new(&origin, Some(0), 0).unwrap();
+			let mut storage_meter = storage::meter::Meter::new(
+				&CallOrigin::from_account_id(origin.clone()),
+				Some(0),
+				0,
+			)
+			.unwrap();
 			place_contract(&BOB, return_ch);
 
 			let result = MockStack::run_call(
-				origin,
+				CallOrigin::from_account_id(origin),
 				dest,
 				&mut GasMeter::<Test>::new(GAS_LIMIT),
 				&mut storage_meter,
@@ -1844,10 +2573,15 @@ mod tests {
 		ExtBuilder::default().build().execute_with(|| {
 			let schedule = <Test as Config>::Schedule::get();
 			place_contract(&BOB, return_ch);
-			let mut storage_meter = storage::meter::Meter::new(&origin, Some(0), 0).unwrap();
+			let mut storage_meter = storage::meter::Meter::new(
+				&CallOrigin::from_account_id(origin.clone()),
+				Some(0),
+				0,
+			)
+			.unwrap();
 
 			let result = MockStack::run_call(
-				origin,
+				CallOrigin::from_account_id(origin),
 				dest,
 				&mut GasMeter::<Test>::new(GAS_LIMIT),
 				&mut storage_meter,
@@ -1875,10 +2609,12 @@ mod tests {
 		ExtBuilder::default().build().execute_with(|| {
 			let schedule = <Test as Config>::Schedule::get();
 			place_contract(&BOB, input_data_ch);
-			let mut storage_meter = storage::meter::Meter::new(&ALICE, Some(0), 0).unwrap();
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(0), 0)
+					.unwrap();
 
 			let result = MockStack::run_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
 				BOB,
 				&mut GasMeter::<Test>::new(GAS_LIMIT),
 				&mut storage_meter,
@@ -1892,6 +2628,44 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn builtins_are_not_payable() {
+		let code_bob = MockLoader::insert(Call, |ctx, _| {
+			// The builtin runs without a frame of its own.
+			assert_matches!(
+				ctx.ext.call(Weight::zero(), 0, HashBuiltin::ADDRESS, 0, vec![1], true),
+				Ok(ExecReturnValue { data, .. }) if data.len() == 32
+			);
+			assert_err!(
+				ctx.ext.call(Weight::zero(), 0, HashBuiltin::ADDRESS, 1, vec![1], true),
+				<Error<Test>>::BuiltinNotPayable,
+			);
+			exec_success()
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let schedule = <Test as Config>::Schedule::get();
+			place_contract(&BOB, code_bob);
+			set_balance(&BOB, 100);
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(0), 0)
+					.unwrap();
+
+			let result = MockStack::run_call(
+				CallOrigin::from_account_id(ALICE),
+				BOB,
+				&mut GasMeter::<Test>::new(GAS_LIMIT),
+				&mut storage_meter,
+				&schedule,
+				0,
+				vec![],
+				None,
+				Determinism::Deterministic,
+			);
+			assert_matches!(result, Ok(_));
+		});
+	}
+
 	#[test]
 	fn input_data_to_instantiate() {
 		let input_data_ch = MockLoader::insert(Constructor, |ctx, _| {
@@ -1907,8 +2681,12 @@ mod tests {
 			let executable =
 				MockExecutable::from_storage(input_data_ch, &schedule, &mut gas_meter).unwrap();
 			set_balance(&ALICE, min_balance * 1000);
-			let mut storage_meter =
-				storage::meter::Meter::new(&ALICE, Some(min_balance * 100), min_balance).unwrap();
+			let mut storage_meter = storage::meter::Meter::new(
+				&CallOrigin::from_account_id(ALICE),
+				Some(min_balance * 200),
+				min_balance,
+			)
+			.unwrap();
 
 			let result = MockStack::run_instantiate(
 				ALICE,
@@ -1935,7 +2713,7 @@ mod tests {
 		let value = Default::default();
 		let recurse_ch = MockLoader::insert(Call, |ctx, _| {
 			// Try to call into yourself.
-			let r = ctx.ext.call(Weight::zero(), BOB, 0, vec![], true);
+			let r = ctx.ext.call(Weight::zero(), 0, BOB, 0, vec![], true);
 
 			ReachedBottom::mutate(|reached_bottom| {
 				if !*reached_bottom {
@@ -1956,10 +2734,12 @@ mod tests {
 			let schedule = <Test as Config>::Schedule::get();
 			set_balance(&BOB, 1);
 			place_contract(&BOB, recurse_ch);
-			let mut storage_meter = storage::meter::Meter::new(&ALICE, Some(0), value).unwrap();
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(0), value)
+					.unwrap();
 
 			let result = MockStack::run_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
 				BOB,
 				&mut GasMeter::<Test>::new(GAS_LIMIT),
 				&mut storage_meter,
@@ -1986,15 +2766,19 @@ mod tests {
 
 		let bob_ch = MockLoader::insert(Call, |ctx, _| {
 			// Record the caller for bob.
-			WitnessedCallerBob::mutate(|caller| *caller = Some(ctx.ext.caller().clone()));
+			WitnessedCallerBob::mutate(|caller| {
+				*caller = Some(ctx.ext.caller().account_id().unwrap().clone())
+			});
 
 			// Call into CHARLIE contract.
-			assert_matches!(ctx.ext.call(Weight::zero(), CHARLIE, 0, vec![], true), Ok(_));
+			assert_matches!(ctx.ext.call(Weight::zero(), 0, CHARLIE, 0, vec![], true), Ok(_));
 			exec_success()
 		});
 		let charlie_ch = MockLoader::insert(Call, |ctx, _| {
 			// Record the caller for charlie.
-			WitnessedCallerCharlie::mutate(|caller| *caller = Some(ctx.ext.caller().clone()));
+			WitnessedCallerCharlie::mutate(|caller| {
+				*caller = Some(ctx.ext.caller().account_id().unwrap().clone())
+			});
 			exec_success()
 		});
 
@@ -2002,10 +2786,15 @@ mod tests {
 			let schedule = <Test as Config>::Schedule::get();
 			place_contract(&dest, bob_ch);
 			place_contract(&CHARLIE, charlie_ch);
-			let mut storage_meter = storage::meter::Meter::new(&origin, Some(0), 0).unwrap();
+			let mut storage_meter = storage::meter::Meter::new(
+				&CallOrigin::from_account_id(origin.clone()),
+				Some(0),
+				0,
+			)
+			.unwrap();
 
 			let result = MockStack::run_call(
-				origin.clone(),
+				CallOrigin::from_account_id(origin.clone()),
 				dest.clone(),
 				&mut GasMeter::<Test>::new(GAS_LIMIT),
 				&mut storage_meter,
@@ -2037,9 +2826,11 @@ mod tests {
 			let schedule = <Test as Config>::Schedule::get();
 			place_contract(&BOB, bob_ch);
 
-			let mut storage_meter = storage::meter::Meter::new(&ALICE, Some(0), 0).unwrap();
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(0), 0)
+					.unwrap();
 			let result = MockStack::run_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
 				BOB,
 				&mut GasMeter::<Test>::new(GAS_LIMIT),
 				&mut storage_meter,
@@ -2066,10 +2857,12 @@ mod tests {
 		ExtBuilder::default().build().execute_with(|| {
 			let schedule = <Test as Config>::Schedule::get();
 			place_contract(&BOB, code_bob);
-			let mut storage_meter = storage::meter::Meter::new(&ALICE, Some(0), 0).unwrap();
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(0), 0)
+					.unwrap();
 			// ALICE (not contract) -> BOB (contract)
 			let result = MockStack::run_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
 				BOB,
 				&mut GasMeter::<Test>::new(GAS_LIMIT),
 				&mut storage_meter,
@@ -2094,10 +2887,12 @@ mod tests {
 		ExtBuilder::default().build().execute_with(|| {
 			let schedule = <Test as Config>::Schedule::get();
 			place_contract(&BOB, bob_ch);
-			let mut storage_meter = storage::meter::Meter::new(&ALICE, Some(0), 0).unwrap();
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(0), 0)
+					.unwrap();
 			// ALICE (not contract) -> BOB (contract)
 			let result = MockStack::run_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
 				BOB,
 				&mut GasMeter::<Test>::new(GAS_LIMIT),
 				&mut storage_meter,
@@ -2123,17 +2918,19 @@ mod tests {
 			// ALICE is the origin of the call stack
 			assert!(ctx.ext.caller_is_origin());
 			// BOB calls CHARLIE
-			ctx.ext.call(Weight::zero(), CHARLIE, 0, vec![], true)
+			ctx.ext.call(Weight::zero(), 0, CHARLIE, 0, vec![], true)
 		});
 
 		ExtBuilder::default().build().execute_with(|| {
 			let schedule = <Test as Config>::Schedule::get();
 			place_contract(&BOB, code_bob);
 			place_contract(&CHARLIE, code_charlie);
-			let mut storage_meter = storage::meter::Meter::new(&ALICE, Some(0), 0).unwrap();
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(0), 0)
+					.unwrap();
 			// ALICE -> BOB (caller is origin) -> CHARLIE (caller is not origin)
 			let result = MockStack::run_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
 				BOB,
 				&mut GasMeter::<Test>::new(GAS_LIMIT),
 				&mut storage_meter,
@@ -2154,7 +2951,7 @@ mod tests {
 			assert_eq!(*ctx.ext.address(), BOB);
 
 			// Call into charlie contract.
-			assert_matches!(ctx.ext.call(Weight::zero(), CHARLIE, 0, vec![], true), Ok(_));
+			assert_matches!(ctx.ext.call(Weight::zero(), 0, CHARLIE, 0, vec![], true), Ok(_));
 			exec_success()
 		});
 		let charlie_ch = MockLoader::insert(Call, |ctx, _| {
@@ -2166,10 +2963,12 @@ mod tests {
 			let schedule = <Test as Config>::Schedule::get();
 			place_contract(&BOB, bob_ch);
 			place_contract(&CHARLIE, charlie_ch);
-			let mut storage_meter = storage::meter::Meter::new(&ALICE, Some(0), 0).unwrap();
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(0), 0)
+					.unwrap();
 
 			let result = MockStack::run_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
 				BOB,
 				&mut GasMeter::<Test>::new(GAS_LIMIT),
 				&mut storage_meter,
@@ -2193,7 +2992,9 @@ mod tests {
 			let mut gas_meter = GasMeter::<Test>::new(GAS_LIMIT);
 			let executable =
 				MockExecutable::from_storage(dummy_ch, &schedule, &mut gas_meter).unwrap();
-			let mut storage_meter = storage::meter::Meter::new(&ALICE, Some(0), 0).unwrap();
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(0), 0)
+					.unwrap();
 
 			assert_matches!(
 				MockStack::run_instantiate(
@@ -2225,8 +3026,12 @@ mod tests {
 			let executable =
 				MockExecutable::from_storage(dummy_ch, &schedule, &mut gas_meter).unwrap();
 			set_balance(&ALICE, min_balance * 1000);
-			let mut storage_meter =
-				storage::meter::Meter::new(&ALICE, Some(min_balance * 100), min_balance).unwrap();
+			let mut storage_meter = storage::meter::Meter::new(
+				&CallOrigin::from_account_id(ALICE),
+				Some(min_balance * 100),
+				min_balance,
+			)
+			.unwrap();
 
 			let instantiated_contract_address = assert_matches!(
 				MockStack::run_instantiate(
@@ -2249,9 +3054,22 @@ mod tests {
 				Storage::<Test>::code_hash(&instantiated_contract_address).unwrap(),
 				dummy_ch
 			);
+			let base_deposit = ContractInfoOf::<Test>::get(&instantiated_contract_address)
+				.unwrap()
+				.storage_base_deposit;
 			assert_eq!(
 				&events(),
-				&[Event::Instantiated { deployer: ALICE, contract: instantiated_contract_address }]
+				&[
+					Event::StorageBaseDepositCharged {
+						contract: instantiated_contract_address.clone(),
+						payer: ALICE,
+						amount: base_deposit,
+					},
+					Event::Instantiated {
+						deployer: ALICE,
+						contract: instantiated_contract_address
+					}
+				]
 			);
 		});
 	}
@@ -2269,8 +3087,12 @@ mod tests {
 			let executable =
 				MockExecutable::from_storage(dummy_ch, &schedule, &mut gas_meter).unwrap();
 			set_balance(&ALICE, min_balance * 1000);
-			let mut storage_meter =
-				storage::meter::Meter::new(&ALICE, Some(min_balance * 100), min_balance).unwrap();
+			let mut storage_meter = storage::meter::Meter::new(
+				&CallOrigin::from_account_id(ALICE),
+				Some(min_balance * 100),
+				min_balance,
+			)
+			.unwrap();
 
 			let instantiated_contract_address = assert_matches!(
 				MockStack::run_instantiate(
@@ -2305,6 +3127,7 @@ mod tests {
 					.ext
 					.instantiate(
 						Weight::zero(),
+						0,
 						dummy_ch,
 						<Test as Config>::Currency::minimum_balance(),
 						vec![],
@@ -2322,13 +3145,16 @@ mod tests {
 			let min_balance = <Test as Config>::Currency::minimum_balance();
 			set_balance(&ALICE, min_balance * 100);
 			place_contract(&BOB, instantiator_ch);
-			let mut storage_meter =
-				storage::meter::Meter::new(&ALICE, Some(min_balance * 10), min_balance * 10)
-					.unwrap();
+			let mut storage_meter = storage::meter::Meter::new(
+				&CallOrigin::from_account_id(ALICE),
+				Some(min_balance * 10),
+				min_balance * 10,
+			)
+			.unwrap();
 
 			assert_matches!(
 				MockStack::run_call(
-					ALICE,
+					CallOrigin::from_account_id(ALICE),
 					BOB,
 					&mut GasMeter::<Test>::new(GAS_LIMIT),
 					&mut storage_meter,
@@ -2350,11 +3176,19 @@ mod tests {
 				Storage::<Test>::code_hash(&instantiated_contract_address).unwrap(),
 				dummy_ch
 			);
+			let base_deposit = ContractInfoOf::<Test>::get(&instantiated_contract_address)
+				.unwrap()
+				.storage_base_deposit;
 			assert_eq!(
 				&events(),
 				&[
+					Event::StorageBaseDepositCharged {
+						contract: instantiated_contract_address.clone(),
+						payer: ALICE,
+						amount: base_deposit,
+					},
 					Event::Instantiated { deployer: BOB, contract: instantiated_contract_address },
-					Event::Called { caller: ALICE, contract: BOB },
+					Event::Called { caller: CallOrigin::from_account_id(ALICE), contract: BOB },
 				]
 			);
 		});
@@ -2369,6 +3203,7 @@ mod tests {
 				assert_matches!(
 					ctx.ext.instantiate(
 						Weight::zero(),
+						0,
 						dummy_ch,
 						<Test as Config>::Currency::minimum_balance(),
 						vec![],
@@ -2389,11 +3224,13 @@ mod tests {
 			set_balance(&ALICE, 1000);
 			set_balance(&BOB, 100);
 			place_contract(&BOB, instantiator_ch);
-			let mut storage_meter = storage::meter::Meter::new(&ALICE, Some(100), 0).unwrap();
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(200), 0)
+					.unwrap();
 
 			assert_matches!(
 				MockStack::run_call(
-					ALICE,
+					CallOrigin::from_account_id(ALICE),
 					BOB,
 					&mut GasMeter::<Test>::new(GAS_LIMIT),
 					&mut storage_meter,
@@ -2408,7 +3245,10 @@ mod tests {
 
 			// The contract wasn't instantiated so we don't expect to see an instantiation
 			// event here.
-			assert_eq!(&events(), &[Event::Called { caller: ALICE, contract: BOB },]);
+			assert_eq!(
+				&events(),
+				&[Event::Called { caller: CallOrigin::from_account_id(ALICE), contract: BOB },]
+			);
 		});
 	}
 
@@ -2425,7 +3265,9 @@ mod tests {
 			let executable =
 				MockExecutable::from_storage(terminate_ch, &schedule, &mut gas_meter).unwrap();
 			set_balance(&ALICE, 1000);
-			let mut storage_meter = storage::meter::Meter::new(&ALICE, Some(100), 100).unwrap();
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(200), 100)
+					.unwrap();
 
 			assert_eq!(
 				MockStack::run_instantiate(
@@ -2461,13 +3303,16 @@ mod tests {
 				let info = ctx.ext.contract_info();
 				assert_eq!(info.storage_byte_deposit, 0);
 				info.storage_byte_deposit = 42;
-				assert_eq!(ctx.ext.call(Weight::zero(), CHARLIE, 0, vec![], true), exec_trapped());
+				assert_eq!(
+					ctx.ext.call(Weight::zero(), 0, CHARLIE, 0, vec![], true),
+					exec_trapped()
+				);
 				assert_eq!(ctx.ext.contract_info().storage_byte_deposit, 42);
 			}
 			exec_success()
 		});
 		let code_charlie = MockLoader::insert(Call, |ctx, _| {
-			assert!(ctx.ext.call(Weight::zero(), BOB, 0, vec![99], true).is_ok());
+			assert!(ctx.ext.call(Weight::zero(), 0, BOB, 0, vec![99], true).is_ok());
 			exec_trapped()
 		});
 
@@ -2476,10 +3321,12 @@ mod tests {
 			let schedule = <Test as Config>::Schedule::get();
 			place_contract(&BOB, code_bob);
 			place_contract(&CHARLIE, code_charlie);
-			let mut storage_meter = storage::meter::Meter::new(&ALICE, Some(0), 0).unwrap();
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(0), 0)
+					.unwrap();
 
 			let result = MockStack::run_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
 				BOB,
 				&mut GasMeter::<Test>::new(GAS_LIMIT),
 				&mut storage_meter,
@@ -2493,11 +3340,60 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn revert_reason_of_failed_call() {
+		let code_bob = MockLoader::insert(Call, |ctx, _| {
+			let err = ctx.ext.call(Weight::zero(), 0, CHARLIE, 0, vec![], true).unwrap_err();
+			let reason = err.revert_reason();
+			let ModuleError { index, error, .. } =
+				match DispatchError::from(<Error<Test>>::ContractNotFound) {
+					DispatchError::Module(err) => err,
+					_ => unreachable!("Pallet errors are module errors; qed"),
+				};
+			assert_eq!(reason, RevertReason::Module { index, error });
+			assert_eq!(RevertReason::from_envelope(&reason.to_envelope()), Some(reason));
+			exec_success()
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let schedule = <Test as Config>::Schedule::get();
+			place_contract(&BOB, code_bob);
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(0), 0)
+					.unwrap();
+
+			let result = MockStack::run_call(
+				CallOrigin::from_account_id(ALICE),
+				BOB,
+				&mut GasMeter::<Test>::new(GAS_LIMIT),
+				&mut storage_meter,
+				&schedule,
+				0,
+				vec![],
+				None,
+				Determinism::Deterministic,
+			);
+			assert_matches!(result, Ok(_));
+		});
+	}
+
+	#[test]
+	fn revert_reason_envelope_is_recognized() {
+		let reason = RevertReason::Panic(b"ContractTrapped".to_vec());
+		let envelope = reason.to_envelope();
+		assert!(envelope.starts_with(&pallet_contracts_primitives::REVERT_REASON_PREFIX));
+		assert_eq!(RevertReason::from_envelope(&envelope), Some(reason));
+		// Missing prefix, trailing bytes and plain output are rejected.
+		assert_eq!(RevertReason::from_envelope(&envelope[4..]), None);
+		assert_eq!(RevertReason::from_envelope(&[&envelope[..], &[0]].concat()), None);
+		assert_eq!(RevertReason::from_envelope(&[1, 2, 3]), None);
+	}
+
 	#[test]
 	fn recursive_call_during_constructor_fails() {
 		let code = MockLoader::insert(Constructor, |ctx, _| {
 			assert_matches!(
-				ctx.ext.call(Weight::zero(), ctx.ext.address().clone(), 0, vec![], true),
+				ctx.ext.call(Weight::zero(), 0, ctx.ext.address().clone(), 0, vec![], true),
 				Err(ExecError{error, ..}) if error == <Error<Test>>::ContractNotFound.into()
 			);
 			exec_success()
@@ -2510,8 +3406,12 @@ mod tests {
 			let mut gas_meter = GasMeter::<Test>::new(GAS_LIMIT);
 			let executable = MockExecutable::from_storage(code, &schedule, &mut gas_meter).unwrap();
 			set_balance(&ALICE, min_balance * 1000);
-			let mut storage_meter =
-				storage::meter::Meter::new(&ALICE, Some(min_balance * 100), min_balance).unwrap();
+			let mut storage_meter = storage::meter::Meter::new(
+				&CallOrigin::from_account_id(ALICE),
+				Some(min_balance * 200),
+				min_balance,
+			)
+			.unwrap();
 
 			let result = MockStack::run_instantiate(
 				ALICE,
@@ -2536,7 +3436,7 @@ mod tests {
 			exec_success()
 		});
 
-		let mut debug_buffer = DebugBufferVec::<Test>::try_from(Vec::new()).unwrap();
+		let mut debug_buffer = DebugBuffer::<Test>::default();
 
 		ExtBuilder::default().build().execute_with(|| {
 			let min_balance = <Test as Config>::Currency::minimum_balance();
@@ -2544,9 +3444,11 @@ mod tests {
 			let mut gas_meter = GasMeter::<Test>::new(GAS_LIMIT);
 			set_balance(&ALICE, min_balance * 10);
 			place_contract(&BOB, code_hash);
-			let mut storage_meter = storage::meter::Meter::new(&ALICE, Some(0), 0).unwrap();
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(0), 0)
+					.unwrap();
 			MockStack::run_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
 				BOB,
 				&mut gas_meter,
 				&mut storage_meter,
@@ -2570,7 +3472,7 @@ mod tests {
 			exec_trapped()
 		});
 
-		let mut debug_buffer = DebugBufferVec::<Test>::try_from(Vec::new()).unwrap();
+		let mut debug_buffer = DebugBuffer::<Test>::default();
 
 		ExtBuilder::default().build().execute_with(|| {
 			let min_balance = <Test as Config>::Currency::minimum_balance();
@@ -2578,9 +3480,11 @@ mod tests {
 			let mut gas_meter = GasMeter::<Test>::new(GAS_LIMIT);
 			set_balance(&ALICE, min_balance * 10);
 			place_contract(&BOB, code_hash);
-			let mut storage_meter = storage::meter::Meter::new(&ALICE, Some(0), 0).unwrap();
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(0), 0)
+					.unwrap();
 			let result = MockStack::run_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
 				BOB,
 				&mut gas_meter,
 				&mut storage_meter,
@@ -2604,8 +3508,13 @@ mod tests {
 		});
 
 		// Pre-fill the buffer up to its limit
-		let mut debug_buffer =
-			DebugBufferVec::<Test>::try_from(vec![0u8; DebugBufferVec::<Test>::bound()]).unwrap();
+		let bound = DebugBufferVec::<Test>::bound();
+		let mut debug_buffer = DebugBuffer::<Test> {
+			message: vec![b'a'; bound].try_into().unwrap(),
+			truncated: false,
+			storage_changes: Vec::new(),
+			io_counters: Default::default(),
+		};
 
 		ExtBuilder::default().build().execute_with(|| {
 			let schedule: Schedule<Test> = <Test as Config>::Schedule::get();
@@ -2613,9 +3522,56 @@ mod tests {
 			let mut gas_meter = GasMeter::<Test>::new(GAS_LIMIT);
 			set_balance(&ALICE, min_balance * 10);
 			place_contract(&BOB, code_hash);
-			let mut storage_meter = storage::meter::Meter::new(&ALICE, Some(0), 0).unwrap();
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(0), 0)
+					.unwrap();
 			MockStack::run_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
+				BOB,
+				&mut gas_meter,
+				&mut storage_meter,
+				&schedule,
+				0,
+				vec![],
+				Some(&mut debug_buffer),
+				Determinism::Deterministic,
+			)
+			.unwrap();
+			let marker = format!("\n<debug buffer full: truncated in frame 1 of {:?}>", BOB);
+			assert!(debug_buffer.truncated);
+			assert_eq!(debug_buffer.len(), bound);
+			assert_eq!(&debug_buffer[bound - marker.len()..], marker.as_bytes());
+			assert!(debug_buffer[..bound - marker.len()].iter().all(|b| *b == b'a'));
+		});
+	}
+
+	#[test]
+	fn debug_buffer_truncates_with_marker() {
+		let code_hash = MockLoader::insert(Call, move |ctx, _| {
+			ctx.ext.append_debug_buffer(&"b".repeat(300));
+			ctx.ext.append_debug_buffer("dropped");
+			exec_success()
+		});
+
+		let bound = DebugBufferVec::<Test>::bound();
+		let mut debug_buffer = DebugBuffer::<Test> {
+			message: vec![b'a'; bound - 200].try_into().unwrap(),
+			truncated: false,
+			storage_changes: Vec::new(),
+			io_counters: Default::default(),
+		};
+
+		ExtBuilder::default().build().execute_with(|| {
+			let schedule: Schedule<Test> = <Test as Config>::Schedule::get();
+			let min_balance = <Test as Config>::Currency::minimum_balance();
+			let mut gas_meter = GasMeter::<Test>::new(GAS_LIMIT);
+			set_balance(&ALICE, min_balance * 10);
+			place_contract(&BOB, code_hash);
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(0), 0)
+					.unwrap();
+			MockStack::run_call(
+				CallOrigin::from_account_id(ALICE),
 				BOB,
 				&mut gas_meter,
 				&mut storage_meter,
@@ -2626,12 +3582,14 @@ mod tests {
 				Determinism::Deterministic,
 			)
 			.unwrap();
-			assert_eq!(
-				&String::from_utf8(debug_buffer[DebugBufferVec::<Test>::bound() - 17..].to_vec())
-					.unwrap(),
-				"overflowing bytes"
-			);
 		});
+
+		// the message is cut off to leave room for the marker and later messages are dropped
+		let marker = format!("\n<debug buffer full: truncated in frame 1 of {:?}>", BOB);
+		let expected =
+			format!("{}{}{}", "a".repeat(bound - 200), "b".repeat(200 - marker.len()), marker);
+		assert!(debug_buffer.truncated);
+		assert_eq!(&String::from_utf8(debug_buffer.to_vec()).unwrap(), &expected);
 	}
 
 	#[test]
@@ -2639,7 +3597,7 @@ mod tests {
 		// call the contract passed as input with disabled reentry
 		let code_bob = MockLoader::insert(Call, |ctx, _| {
 			let dest = Decode::decode(&mut ctx.input_data.as_ref()).unwrap();
-			ctx.ext.call(Weight::zero(), dest, 0, vec![], false)
+			ctx.ext.call(Weight::zero(), 0, dest, 0, vec![], false)
 		});
 
 		let code_charlie = MockLoader::insert(Call, |_, _| exec_success());
@@ -2648,11 +3606,13 @@ mod tests {
 			let schedule = <Test as Config>::Schedule::get();
 			place_contract(&BOB, code_bob);
 			place_contract(&CHARLIE, code_charlie);
-			let mut storage_meter = storage::meter::Meter::new(&ALICE, Some(0), 0).unwrap();
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(0), 0)
+					.unwrap();
 
 			// Calling another contract should succeed
 			assert_ok!(MockStack::run_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
 				BOB,
 				&mut GasMeter::<Test>::new(GAS_LIMIT),
 				&mut storage_meter,
@@ -2666,7 +3626,7 @@ mod tests {
 			// Calling into oneself fails
 			assert_err!(
 				MockStack::run_call(
-					ALICE,
+					CallOrigin::from_account_id(ALICE),
 					BOB,
 					&mut GasMeter::<Test>::new(GAS_LIMIT),
 					&mut storage_meter,
@@ -2686,26 +3646,85 @@ mod tests {
 	fn call_deny_reentry() {
 		let code_bob = MockLoader::insert(Call, |ctx, _| {
 			if ctx.input_data[0] == 0 {
-				ctx.ext.call(Weight::zero(), CHARLIE, 0, vec![], false)
+				ctx.ext.call(Weight::zero(), 0, CHARLIE, 0, vec![], false)
 			} else {
 				exec_success()
 			}
 		});
 
 		// call BOB with input set to '1'
-		let code_charlie =
-			MockLoader::insert(Call, |ctx, _| ctx.ext.call(Weight::zero(), BOB, 0, vec![1], true));
+		let code_charlie = MockLoader::insert(Call, |ctx, _| {
+			ctx.ext.call(Weight::zero(), 0, BOB, 0, vec![1], true)
+		});
 
 		ExtBuilder::default().build().execute_with(|| {
 			let schedule = <Test as Config>::Schedule::get();
 			place_contract(&BOB, code_bob);
 			place_contract(&CHARLIE, code_charlie);
-			let mut storage_meter = storage::meter::Meter::new(&ALICE, Some(0), 0).unwrap();
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(0), 0)
+					.unwrap();
 
 			// BOB -> CHARLIE -> BOB fails as BOB denies reentry.
 			assert_err!(
 				MockStack::run_call(
-					ALICE,
+					CallOrigin::from_account_id(ALICE),
+					BOB,
+					&mut GasMeter::<Test>::new(GAS_LIMIT),
+					&mut storage_meter,
+					&schedule,
+					0,
+					vec![0],
+					None,
+					Determinism::Deterministic
+				)
+				.map_err(|e| e.error),
+				<Error<Test>>::ReentranceDenied,
+			);
+		});
+	}
+
+	#[test]
+	fn contract_deny_reentry() {
+		let code_bob = MockLoader::insert(Call, |ctx, _| match ctx.input_data[0] {
+			0 => {
+				ctx.ext.set_deny_reentry(true);
+				ctx.ext.call(Weight::zero(), 0, CHARLIE, 0, vec![], true)
+			},
+			1 => ctx.ext.call(Weight::zero(), 0, CHARLIE, 0, vec![], true),
+			_ => exec_success(),
+		});
+
+		// call BOB with input set to '2'
+		let code_charlie = MockLoader::insert(Call, |ctx, _| {
+			ctx.ext.call(Weight::zero(), 0, BOB, 0, vec![2], true)
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let schedule = <Test as Config>::Schedule::get();
+			place_contract(&BOB, code_bob);
+			place_contract(&CHARLIE, code_charlie);
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(0), 0)
+					.unwrap();
+
+			// BOB -> CHARLIE -> BOB works as long as BOB does not deny reentry.
+			assert_ok!(MockStack::run_call(
+				CallOrigin::from_account_id(ALICE),
+				BOB,
+				&mut GasMeter::<Test>::new(GAS_LIMIT),
+				&mut storage_meter,
+				&schedule,
+				0,
+				vec![1],
+				None,
+				Determinism::Deterministic
+			));
+
+			// BOB denies reentry which overrides the flag passed by BOB and CHARLIE.
+			assert_err!(
+				MockStack::run_call(
+					CallOrigin::from_account_id(ALICE),
 					BOB,
 					&mut GasMeter::<Test>::new(GAS_LIMIT),
 					&mut storage_meter,
@@ -2737,10 +3756,12 @@ mod tests {
 			let mut gas_meter = GasMeter::<Test>::new(GAS_LIMIT);
 			set_balance(&ALICE, min_balance * 10);
 			place_contract(&BOB, code_hash);
-			let mut storage_meter = storage::meter::Meter::new(&ALICE, Some(0), 0).unwrap();
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(0), 0)
+					.unwrap();
 			System::reset_events();
 			MockStack::run_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
 				BOB,
 				&mut gas_meter,
 				&mut storage_meter,
@@ -2767,7 +3788,7 @@ mod tests {
 					EventRecord {
 						phase: Phase::Initialization,
 						event: MetaEvent::Contracts(crate::Event::Called {
-							caller: ALICE,
+							caller: CallOrigin::from_account_id(ALICE),
 							contract: BOB,
 						}),
 						topics: vec![hash(&ALICE), hash(&BOB)],
@@ -2820,10 +3841,12 @@ mod tests {
 			let mut gas_meter = GasMeter::<Test>::new(GAS_LIMIT);
 			set_balance(&ALICE, min_balance * 10);
 			place_contract(&BOB, code_hash);
-			let mut storage_meter = storage::meter::Meter::new(&ALICE, Some(0), 0).unwrap();
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(0), 0)
+					.unwrap();
 			System::reset_events();
 			MockStack::run_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
 				BOB,
 				&mut gas_meter,
 				&mut storage_meter,
@@ -2863,7 +3886,7 @@ mod tests {
 					EventRecord {
 						phase: Phase::Initialization,
 						event: MetaEvent::Contracts(crate::Event::Called {
-							caller: ALICE,
+							caller: CallOrigin::from_account_id(ALICE),
 							contract: BOB,
 						}),
 						topics: vec![hash(&ALICE), hash(&BOB)],
@@ -2873,6 +3896,56 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn call_runtime_contract_filter() {
+		let remark = RuntimeCall::System(frame_system::Call::remark_with_event {
+			remark: b"Hello".to_vec(),
+		});
+		let transfer =
+			RuntimeCall::Balances(pallet_balances::Call::transfer { dest: CHARLIE, value: 22 });
+		let code_hash = MockLoader::insert(Call, {
+			let (remark, transfer) = (remark.clone(), transfer.clone());
+			move |ctx, _| {
+				assert_ok!(ctx.ext.call_runtime(remark.clone()));
+				// allowed by the global filter but not by the contract's own filter
+				assert_err!(
+					ctx.ext.call_runtime(transfer.clone()),
+					frame_system::Error::<Test>::CallFiltered
+				);
+				exec_success()
+			}
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let min_balance = <Test as Config>::Currency::minimum_balance();
+			let schedule = <Test as Config>::Schedule::get();
+			let mut gas_meter = GasMeter::<Test>::new(GAS_LIMIT);
+			set_balance(&ALICE, min_balance * 10);
+			set_balance(&BOB, min_balance * 10);
+			place_contract(&BOB, code_hash);
+			let mut contract = ContractInfo::<Test>::load(&BOB).unwrap();
+			let remark_index: [u8; 2] = remark.encode()[..2].try_into().unwrap();
+			contract.call_filter = Some(vec![remark_index].try_into().unwrap());
+			<ContractInfoOf<Test>>::insert(&BOB, contract);
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(0), 0)
+					.unwrap();
+			let result = MockStack::run_call(
+				CallOrigin::from_account_id(ALICE),
+				BOB,
+				&mut gas_meter,
+				&mut storage_meter,
+				&schedule,
+				0,
+				vec![],
+				None,
+				Determinism::Deterministic,
+			);
+			assert_matches!(result, Ok(_));
+			assert_eq!(get_balance(&CHARLIE), 0);
+		});
+	}
+
 	#[test]
 	fn nonce() {
 		let fail_code = MockLoader::insert(Constructor, |_, _| exec_trapped());
@@ -2881,6 +3954,7 @@ mod tests {
 			ctx.ext
 				.instantiate(
 					Weight::zero(),
+					0,
 					fail_code,
 					ctx.ext.minimum_balance() * 100,
 					vec![],
@@ -2894,6 +3968,7 @@ mod tests {
 				.ext
 				.instantiate(
 					Weight::zero(),
+					0,
 					success_code,
 					ctx.ext.minimum_balance() * 100,
 					vec![],
@@ -2902,7 +3977,7 @@ mod tests {
 				.unwrap();
 
 			// a plain call should not influence the account counter
-			ctx.ext.call(Weight::zero(), account_id, 0, vec![], false).unwrap();
+			ctx.ext.call(Weight::zero(), 0, account_id, 0, vec![], false).unwrap();
 
 			exec_success()
 		});
@@ -2919,10 +3994,13 @@ mod tests {
 				MockExecutable::from_storage(succ_fail_code, &schedule, &mut gas_meter).unwrap();
 			let succ_succ_executable =
 				MockExecutable::from_storage(succ_succ_code, &schedule, &mut gas_meter).unwrap();
-			set_balance(&ALICE, min_balance * 1000);
-			let mut storage_meter =
-				storage::meter::Meter::new(&ALICE, Some(min_balance * 500), min_balance * 100)
-					.unwrap();
+			set_balance(&ALICE, min_balance * 2000);
+			let mut storage_meter = storage::meter::Meter::new(
+				&CallOrigin::from_account_id(ALICE),
+				Some(min_balance * 1000),
+				min_balance * 100,
+			)
+			.unwrap();
 
 			MockStack::run_instantiate(
 				ALICE,
@@ -3025,9 +4103,96 @@ mod tests {
 			let mut gas_meter = GasMeter::<Test>::new(GAS_LIMIT);
 			set_balance(&ALICE, min_balance * 1000);
 			place_contract(&BOB, code_hash);
-			let mut storage_meter = storage::meter::Meter::new(&ALICE, None, 0).unwrap();
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), None, 0).unwrap();
 			assert_ok!(MockStack::run_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
+				BOB,
+				&mut gas_meter,
+				&mut storage_meter,
+				&schedule,
+				0,
+				vec![],
+				None,
+				Determinism::Deterministic
+			));
+		});
+	}
+
+	#[test]
+	fn storage_deposit_limit_left_works() {
+		let code_hash = MockLoader::insert(Call, |ctx, _| {
+			assert_eq!(ctx.ext.storage_deposit_limit_left(), 100);
+
+			// 3 bytes and one item
+			assert_ok!(ctx.ext.set_storage(&[1; 32], Some(vec![1, 2, 3]), false));
+			assert_eq!(ctx.ext.storage_deposit_limit_left(), 95);
+
+			// Shrinking the item is offset against the earlier write.
+			assert_ok!(ctx.ext.set_storage(&[1; 32], Some(vec![42]), false));
+			assert_eq!(ctx.ext.storage_deposit_limit_left(), 97);
+
+			assert_ok!(ctx.ext.set_storage(&[1; 32], None, false));
+			assert_eq!(ctx.ext.storage_deposit_limit_left(), 100);
+
+			exec_success()
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let min_balance = <Test as Config>::Currency::minimum_balance();
+			let schedule = <Test as Config>::Schedule::get();
+			let mut gas_meter = GasMeter::<Test>::new(GAS_LIMIT);
+			set_balance(&ALICE, min_balance * 1000);
+			place_contract(&BOB, code_hash);
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(100), 0)
+					.unwrap();
+			assert_ok!(MockStack::run_call(
+				CallOrigin::from_account_id(ALICE),
+				BOB,
+				&mut gas_meter,
+				&mut storage_meter,
+				&schedule,
+				0,
+				vec![],
+				None,
+				Determinism::Deterministic
+			));
+		});
+	}
+
+	#[test]
+	fn subcall_deposit_limit_is_enforced() {
+		let callee_ch = MockLoader::insert(Call, |ctx, _| {
+			// 3 bytes and one item
+			assert_ok!(ctx.ext.set_storage(&[1; 32], Some(vec![1, 2, 3]), false));
+			exec_success()
+		});
+		let caller_ch = MockLoader::insert(Call, |ctx, _| {
+			let err = ctx.ext.call(Weight::zero(), 4, CHARLIE, 0, vec![], true).unwrap_err();
+			assert_eq!(err.error, <Error<Test>>::StorageDepositLimitExhausted.into());
+			assert_eq!(err.origin, ErrorOrigin::Callee);
+
+			// The failed call was rolled back and didn't consume the callers limit.
+			assert_eq!(ctx.ext.storage_deposit_limit_left(), 100);
+			assert_ok!(ctx.ext.call(Weight::zero(), 5, CHARLIE, 0, vec![], true));
+			assert_eq!(ctx.ext.storage_deposit_limit_left(), 95);
+
+			exec_success()
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let min_balance = <Test as Config>::Currency::minimum_balance();
+			let schedule = <Test as Config>::Schedule::get();
+			let mut gas_meter = GasMeter::<Test>::new(GAS_LIMIT);
+			set_balance(&ALICE, min_balance * 1000);
+			place_contract(&BOB, caller_ch);
+			place_contract(&CHARLIE, callee_ch);
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(100), 0)
+					.unwrap();
+			assert_ok!(MockStack::run_call(
+				CallOrigin::from_account_id(ALICE),
 				BOB,
 				&mut gas_meter,
 				&mut storage_meter,
@@ -3152,9 +4317,10 @@ mod tests {
 			let mut gas_meter = GasMeter::<Test>::new(GAS_LIMIT);
 			set_balance(&ALICE, min_balance * 1000);
 			place_contract(&BOB, code_hash);
-			let mut storage_meter = storage::meter::Meter::new(&ALICE, None, 0).unwrap();
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), None, 0).unwrap();
 			assert_ok!(MockStack::run_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
 				BOB,
 				&mut gas_meter,
 				&mut storage_meter,
@@ -3188,9 +4354,10 @@ mod tests {
 			let mut gas_meter = GasMeter::<Test>::new(GAS_LIMIT);
 			set_balance(&ALICE, min_balance * 1000);
 			place_contract(&BOB, code_hash);
-			let mut storage_meter = storage::meter::Meter::new(&ALICE, None, 0).unwrap();
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), None, 0).unwrap();
 			assert_ok!(MockStack::run_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
 				BOB,
 				&mut gas_meter,
 				&mut storage_meter,
@@ -3224,9 +4391,10 @@ mod tests {
 			let mut gas_meter = GasMeter::<Test>::new(GAS_LIMIT);
 			set_balance(&ALICE, min_balance * 1000);
 			place_contract(&BOB, code_hash);
-			let mut storage_meter = storage::meter::Meter::new(&ALICE, None, 0).unwrap();
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), None, 0).unwrap();
 			assert_ok!(MockStack::run_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
 				BOB,
 				&mut gas_meter,
 				&mut storage_meter,
@@ -3286,9 +4454,10 @@ mod tests {
 			let mut gas_meter = GasMeter::<Test>::new(GAS_LIMIT);
 			set_balance(&ALICE, min_balance * 1000);
 			place_contract(&BOB, code_hash);
-			let mut storage_meter = storage::meter::Meter::new(&ALICE, None, 0).unwrap();
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), None, 0).unwrap();
 			assert_ok!(MockStack::run_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
 				BOB,
 				&mut gas_meter,
 				&mut storage_meter,
@@ -3348,9 +4517,10 @@ mod tests {
 			let mut gas_meter = GasMeter::<Test>::new(GAS_LIMIT);
 			set_balance(&ALICE, min_balance * 1000);
 			place_contract(&BOB, code_hash);
-			let mut storage_meter = storage::meter::Meter::new(&ALICE, None, 0).unwrap();
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), None, 0).unwrap();
 			assert_ok!(MockStack::run_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
 				BOB,
 				&mut gas_meter,
 				&mut storage_meter,
@@ -3363,6 +4533,66 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn delegate_dependencies_are_locked() {
+		let refcount = |code_hash| Loader::mutate(|loader| loader.map[&code_hash].refcount);
+		let code_dep = MockLoader::insert(Call, |_, _| exec_success());
+		let code_bob = MockLoader::insert(Call, move |ctx, _| {
+			let own_hash = ctx.ext.contract_info().code_hash;
+			assert_err!(
+				ctx.ext.lock_delegate_dependency(own_hash),
+				<Error<Test>>::CannotAddSelfAsDelegateDependency,
+			);
+			assert_err!(
+				ctx.ext.lock_delegate_dependency(
+					<Test as frame_system::Config>::Hash::from_low_u64_be(u64::MAX)
+				),
+				<Error<Test>>::CodeNotFound,
+			);
+			assert_err!(
+				ctx.ext.unlock_delegate_dependency(&code_dep),
+				<Error<Test>>::DelegateDependencyNotFound,
+			);
+
+			assert_ok!(ctx.ext.lock_delegate_dependency(code_dep));
+			assert_err!(
+				ctx.ext.lock_delegate_dependency(code_dep),
+				<Error<Test>>::DelegateDependencyAlreadyExists,
+			);
+			assert_eq!(refcount(code_dep), 2);
+			assert_ok!(ctx.ext.unlock_delegate_dependency(&code_dep));
+			assert_eq!(refcount(code_dep), 1);
+
+			assert_ok!(ctx.ext.lock_delegate_dependency(code_dep));
+			exec_success()
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let schedule = <Test as Config>::Schedule::get();
+			place_contract(&BOB, code_bob);
+
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(0), 0)
+					.unwrap();
+			assert_ok!(MockStack::run_call(
+				CallOrigin::from_account_id(ALICE),
+				BOB,
+				&mut GasMeter::<Test>::new(GAS_LIMIT),
+				&mut storage_meter,
+				&schedule,
+				0,
+				vec![],
+				None,
+				Determinism::Deterministic,
+			));
+
+			// the lock survives the call and only the dependency is stored
+			let dependencies = get_contract(&BOB).delegate_dependencies;
+			assert_eq!(dependencies.keys().copied().collect::<Vec<_>>(), vec![code_dep]);
+			assert_eq!(refcount(code_dep), 2);
+		});
+	}
+
 	#[test]
 	fn ecdsa_to_eth_address_returns_proper_value() {
 		let bob_ch = MockLoader::insert(Call, |ctx, _| {
@@ -3380,9 +4610,78 @@ mod tests {
 			let schedule = <Test as Config>::Schedule::get();
 			place_contract(&BOB, bob_ch);
 
-			let mut storage_meter = storage::meter::Meter::new(&ALICE, Some(0), 0).unwrap();
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(0), 0)
+					.unwrap();
 			let result = MockStack::run_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
+				BOB,
+				&mut GasMeter::<Test>::new(GAS_LIMIT),
+				&mut storage_meter,
+				&schedule,
+				0,
+				vec![],
+				None,
+				Determinism::Deterministic,
+			);
+			assert_matches!(result, Ok(_));
+		});
+	}
+
+	#[test]
+	fn asset_transfer_uses_contract_as_source() {
+		let code_hash = MockLoader::insert(Call, |ctx, _| {
+			assert_ok!(ctx.ext.asset_transfer(&1u32.encode(), &CHARLIE, &30u64.encode()));
+			assert_err!(
+				ctx.ext.asset_transfer(&1u32.encode(), &CHARLIE, &[1]),
+				<Error<Test>>::DecodingFailed,
+			);
+			exec_success()
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let schedule = <Test as Config>::Schedule::get();
+			place_contract(&BOB, code_hash);
+			TestAssets::set_balance(1, &BOB, 100);
+
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(0), 0)
+					.unwrap();
+			assert_ok!(MockStack::run_call(
+				CallOrigin::from_account_id(ALICE),
+				BOB,
+				&mut GasMeter::<Test>::new(GAS_LIMIT),
+				&mut storage_meter,
+				&schedule,
+				0,
+				vec![],
+				None,
+				Determinism::Deterministic,
+			));
+			assert_eq!(TestAssets::balance(1, &BOB), 70);
+			assert_eq!(TestAssets::balance(1, &CHARLIE), 30);
+		});
+	}
+
+	#[test]
+	fn xcm_uses_contract_as_origin() {
+		let code_hash = MockLoader::insert(Call, |ctx, _| {
+			let weight_limit = Weight::from_ref_time(4_000);
+			let post_info = ctx.ext.xcm_execute(&[1, 2, 3, 4], weight_limit).unwrap();
+			assert_eq!(post_info.actual_weight, Some(weight_limit / 2));
+			assert_eq!(ctx.ext.xcm_send(&[7, 8], &[5, 6]), Ok(blake2_256(&[5, 6])));
+			exec_success()
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let schedule = <Test as Config>::Schedule::get();
+			place_contract(&BOB, code_hash);
+
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), Some(0), 0)
+					.unwrap();
+			let result = MockStack::run_call(
+				CallOrigin::from_account_id(ALICE),
 				BOB,
 				&mut GasMeter::<Test>::new(GAS_LIMIT),
 				&mut storage_meter,
@@ -3393,6 +4692,10 @@ mod tests {
 				Determinism::Deterministic,
 			);
 			assert_matches!(result, Ok(_));
+			assert_eq!(
+				XcmMessages::get(),
+				vec![(BOB, None, vec![1, 2, 3, 4]), (BOB, Some(vec![7, 8]), vec![5, 6])],
+			);
 		});
 	}
 
@@ -3407,7 +4710,7 @@ mod tests {
 			assert_eq!(ctx.ext.nonce(), 1);
 			// Should not change with a failed instantiation
 			assert_err!(
-				ctx.ext.instantiate(Weight::zero(), fail_code, 0, vec![], &[],),
+				ctx.ext.instantiate(Weight::zero(), 0, fail_code, 0, vec![], &[],),
 				ExecError {
 					error: <Error<Test>>::ContractTrapped.into(),
 					origin: ErrorOrigin::Callee
@@ -3415,7 +4718,7 @@ mod tests {
 			);
 			assert_eq!(ctx.ext.nonce(), 1);
 			// Successful instantation increments
-			ctx.ext.instantiate(Weight::zero(), success_code, 0, vec![], &[]).unwrap();
+			ctx.ext.instantiate(Weight::zero(), 0, success_code, 0, vec![], &[]).unwrap();
 			assert_eq!(ctx.ext.nonce(), 2);
 			exec_success()
 		});
@@ -3426,9 +4729,10 @@ mod tests {
 			let mut gas_meter = GasMeter::<Test>::new(GAS_LIMIT);
 			set_balance(&ALICE, min_balance * 1000);
 			place_contract(&BOB, code_hash);
-			let mut storage_meter = storage::meter::Meter::new(&ALICE, None, 0).unwrap();
+			let mut storage_meter =
+				storage::meter::Meter::new(&CallOrigin::from_account_id(ALICE), None, 0).unwrap();
 			assert_ok!(MockStack::run_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
 				BOB,
 				&mut gas_meter,
 				&mut storage_meter,
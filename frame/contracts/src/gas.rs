@@ -80,6 +80,8 @@ pub struct GasMeter<T: Config> {
 	gas_left: Weight,
 	/// Due to `adjust_gas` and `nested` the `gas_left` can temporarily dip below its final value.
 	gas_left_lowest: Weight,
+	/// Amount of fuel consumed by the wasm engine as of the last sync with this meter.
+	engine_consumed: u64,
 	_phantom: PhantomData<T>,
 	#[cfg(test)]
 	tokens: Vec<ErasedToken>,
@@ -91,6 +93,7 @@ impl<T: Config> GasMeter<T> {
 			gas_limit,
 			gas_left: gas_limit,
 			gas_left_lowest: gas_limit,
+			engine_consumed: 0,
 			_phantom: PhantomData,
 			#[cfg(test)]
 			tokens: Vec::new(),
@@ -101,11 +104,12 @@ impl<T: Config> GasMeter<T> {
 	///
 	/// # Note
 	///
-	/// Passing `0` as amount is interpreted as "all remaining gas".
+	/// Passing `0` as amount is interpreted as "all remaining gas". This is done separately for
+	/// `ref_time` and `proof_size`.
 	pub fn nested(&mut self, amount: Weight) -> Result<Self, DispatchError> {
 		// NOTE that it is ok to allocate all available gas since it still ensured
 		// by `charge` that it doesn't reach zero.
-		let amount = Weight::from_parts(
+		let limit = Weight::from_parts(
 			if amount.ref_time().is_zero() {
 				self.gas_left().ref_time()
 			} else {
@@ -117,32 +121,29 @@ impl<T: Config> GasMeter<T> {
 				amount.proof_size()
 			},
 		);
-		self.gas_left = self.gas_left.checked_sub(&amount).ok_or_else(|| <Error<T>>::OutOfGas)?;
-		Ok(GasMeter::new(amount))
+		self.gas_left = self.gas_left.checked_sub(&limit).ok_or_else(|| <Error<T>>::OutOfGas)?;
+		let mut nested = GasMeter::new(limit);
+		// A fixed amount is pre charged from the parent. It is therefore required in full no
+		// matter how much of it the nested meter ends up consuming.
+		if !amount.ref_time().is_zero() {
+			*nested.gas_left_lowest.ref_time_mut() = 0;
+		}
+		if !amount.proof_size().is_zero() {
+			*nested.gas_left_lowest.proof_size_mut() = 0;
+		}
+		Ok(nested)
 	}
 
 	/// Absorb the remaining gas of a nested meter after we are done using it.
 	pub fn absorb_nested(&mut self, nested: Self) {
-		if self.gas_left.ref_time().is_zero() {
-			// All of the remaining gas was inherited by the nested gas meter. When absorbing
-			// we can therefore safely inherit the lowest gas that the nested gas meter experienced
-			// as long as it is lower than the lowest gas that was experienced by the parent.
-			// We cannot call `self.gas_left_lowest()` here because in the state that this
-			// code is run the parent gas meter has `0` gas left.
-			*self.gas_left_lowest.ref_time_mut() =
-				nested.gas_left_lowest().ref_time().min(self.gas_left_lowest.ref_time());
-		} else {
-			// The nested gas meter was created with a fixed amount that did not consume all of the
-			// parents (self) gas. The lowest gas that self will experience is when the nested
-			// gas was pre charged with the fixed amount.
-			*self.gas_left_lowest.ref_time_mut() = self.gas_left_lowest().ref_time();
-		}
-		if self.gas_left.proof_size().is_zero() {
-			*self.gas_left_lowest.proof_size_mut() =
-				nested.gas_left_lowest().proof_size().min(self.gas_left_lowest.proof_size());
-		} else {
-			*self.gas_left_lowest.proof_size_mut() = self.gas_left_lowest().proof_size();
-		}
+		// The lowest gas that self experienced while the nested meter was in use is what was
+		// left before the nested meter was created minus what the nested meter required. This
+		// includes any gas that was refunded within the nested meter later on. We cannot call
+		// `self.gas_left_lowest()` here because the parent has handed the nested limit over and
+		// its `gas_left` does not reflect what it had left before.
+		self.gas_left_lowest = (self.gas_left + nested.gas_limit)
+			.saturating_sub(nested.gas_required())
+			.min(self.gas_left_lowest);
 		self.gas_left += nested.gas_left;
 	}
 
@@ -171,6 +172,42 @@ impl<T: Config> GasMeter<T> {
 		Ok(ChargedAmount(amount))
 	}
 
+	/// Charge the `ref_time` for the fuel consumed by the wasm engine since the last sync.
+	///
+	/// `engine_consumed_total` is the total amount of fuel the engine reports as consumed.
+	/// Every unit of fuel is worth `ref_time_per_fuel` of `ref_time`. Returns the gas left
+	/// after charging. Normally this never fails because the engine runs out of fuel first.
+	pub fn charge_fuel(
+		&mut self,
+		ref_time_per_fuel: u64,
+		engine_consumed_total: u64,
+	) -> Result<Weight, DispatchError> {
+		let fuel = engine_consumed_total.saturating_sub(self.engine_consumed);
+		if !fuel.is_zero() {
+			self.engine_consumed = engine_consumed_total;
+			let ref_time_left = self
+				.gas_left
+				.ref_time()
+				.checked_sub(fuel.saturating_mul(ref_time_per_fuel))
+				.ok_or_else(|| Error::<T>::OutOfGas)?;
+			*self.gas_left.ref_time_mut() = ref_time_left;
+		}
+		Ok(self.gas_left)
+	}
+
+	/// Record fuel that was consumed from the engine on behalf of a host function.
+	///
+	/// Host functions charge this meter directly. The engine fuel burned afterwards to
+	/// keep both sides in sync must not be charged a second time by [`Self::charge_fuel`].
+	pub fn record_host_fuel(&mut self, fuel: u64) {
+		self.engine_consumed = self.engine_consumed.saturating_add(fuel);
+	}
+
+	/// The amount of fuel the wasm engine may consume with the `ref_time` that is left.
+	pub fn engine_fuel_left(&self, ref_time_per_fuel: u64) -> u64 {
+		self.gas_left.ref_time().checked_div(ref_time_per_fuel).unwrap_or_default()
+	}
+
 	/// Adjust a previously charged amount down to its actual amount.
 	///
 	/// This is when a maximum a priori amount was charged and then should be partially
@@ -320,4 +357,31 @@ mod tests {
 		let mut gas_meter = GasMeter::<Test>::new(Weight::from_ref_time(25));
 		assert!(!gas_meter.charge(SimpleToken(25)).is_err());
 	}
+	#[test]
+	fn nested_requirement_is_propagated() {
+		let mut gas_meter = GasMeter::<Test>::new(Weight::from_parts(1000, 1000));
+		assert!(gas_meter.charge(SimpleToken(100)).is_ok());
+
+		// The nested meter inherits all the gas. Gas that is refunded later is still required.
+		let mut nested = gas_meter.nested(Weight::zero()).unwrap();
+		let charged = nested.charge(SimpleToken(500)).unwrap();
+		nested.adjust_gas(charged, SimpleToken(200));
+		gas_meter.absorb_nested(nested);
+
+		assert_eq!(gas_meter.gas_consumed(), Weight::from_parts(300, 0));
+		assert_eq!(gas_meter.gas_required(), Weight::from_parts(600, 0));
+	}
+
+	#[test]
+	fn nested_fixed_limit_is_required_in_full() {
+		let mut gas_meter = GasMeter::<Test>::new(Weight::from_parts(1000, 1000));
+
+		// A fixed `ref_time` limit is pre charged while `proof_size` is inherited.
+		let mut nested = gas_meter.nested(Weight::from_parts(1000, 0)).unwrap();
+		assert!(nested.charge(SimpleToken(100)).is_ok());
+		gas_meter.absorb_nested(nested);
+
+		assert_eq!(gas_meter.gas_consumed(), Weight::from_parts(100, 0));
+		assert_eq!(gas_meter.gas_required(), Weight::from_parts(1000, 0));
+	}
 }
@@ -81,57 +81,73 @@
 //! WebAssembly based smart contracts in the Rust programming language. This is a work in progress.
 
 #![cfg_attr(not(feature = "std"), no_std)]
-#![cfg_attr(feature = "runtime-benchmarks", recursion_limit = "512")]
+#![cfg_attr(feature = "runtime-benchmarks", recursion_limit = "1024")]
 
 #[macro_use]
 mod gas;
 mod benchmarking;
 mod exec;
-mod migration;
+mod origin;
 mod schedule;
 mod storage;
 mod wasm;
 
+pub mod assets;
+pub mod builtin;
 pub mod chain_extension;
+pub mod migration;
 pub mod weights;
+pub mod xcm;
 
 #[cfg(test)]
 mod tests;
 
 use crate::{
-	exec::{AccountIdOf, ExecError, Executable, Stack as ExecStack},
+	exec::{AccountIdOf, DebugBuffer, ExecError, Executable, Stack as ExecStack},
 	gas::GasMeter,
+	migration::{MigrateResult, MigrateSequence},
 	storage::{meter::Meter as StorageMeter, ContractInfo, DeletedContract, Storage},
-	wasm::{OwnerInfo, PrefabWasmModule, TryInstantiate},
+	wasm::{CodeInfo, PrefabWasmModule, TryInstantiate},
 	weights::WeightInfo,
 };
-use codec::{Codec, Decode, Encode, HasCompact};
+use codec::{Codec, Decode, Encode, HasCompact, MaxEncodedLen};
 use frame_support::{
-	dispatch::{Dispatchable, GetDispatchInfo, Pays, PostDispatchInfo},
+	dispatch::{
+		DispatchError, DispatchErrorWithPostInfo, DispatchResult, Dispatchable, GetDispatchInfo,
+		Pays, PostDispatchInfo,
+	},
 	ensure,
 	traits::{
-		tokens::fungible::Inspect, ConstU32, Contains, Currency, Get, Randomness,
+		tokens::fungible::Inspect, ConstU32, Contains, Currency, EnsureOrigin, Get, Randomness,
 		ReservableCurrency, Time,
 	},
 	weights::{OldWeight, Weight},
-	BoundedVec, WeakBoundedVec,
+	BoundedVec,
 };
-use frame_system::Pallet as System;
+use frame_system::{EventRecord, Pallet as System};
 use pallet_contracts_primitives::{
 	Code, CodeUploadResult, CodeUploadReturnValue, ContractAccessError, ContractExecResult,
-	ContractInstantiateResult, ExecReturnValue, GetStorageResult, InstantiateReturnValue,
-	StorageDeposit,
+	ContractInstantiateResult, ContractStorageInfo, ContractsEnvironment, ExecReturnValue,
+	GetStorageKeysResult, GetStorageResult, HostFnInfo, InstantiateReturnValue, StorageDeposit,
 };
 use scale_info::TypeInfo;
 use smallvec::Array;
-use sp_runtime::traits::{Convert, Hash, Saturating, StaticLookup, TrailingZeroInput};
+use sp_runtime::{
+	traits::{CheckedSub, Convert, Hash, Saturating, StaticLookup, TrailingZeroInput, Zero},
+	RuntimeDebug,
+};
 use sp_std::{fmt::Debug, marker::PhantomData, prelude::*};
 
 pub use crate::{
 	exec::{Frame, VarSizedKey as StorageKey},
-	migration::Migration,
+	migration::{CheckMigrationDone, Migration},
+	origin::{
+		CallOrigin, Caller, DispatchAsContract, DispatchAsSigned, EnsureContract,
+		EnsureSignedOrContract, RawOrigin,
+	},
 	pallet::*,
-	schedule::{HostFnWeights, InstructionWeights, Limits, Schedule},
+	schedule::{HostFnWeights, InstructionWeights, Limits, Schedule, ValidationRules},
+	storage::{meter::CheckStorageDepositLimit, CallFilter, CallIndex, CodeStats, StorageCeiling},
 	wasm::Determinism,
 };
 
@@ -143,9 +159,11 @@ type TrieId = BoundedVec<u8, ConstU32<128>>;
 type BalanceOf<T> =
 	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 type CodeVec<T> = BoundedVec<u8, <T as Config>::MaxCodeLen>;
-type RelaxedCodeVec<T> = WeakBoundedVec<u8, <T as Config>::MaxCodeLen>;
+type CodeMetadataVec<T> = BoundedVec<u8, <T as Config>::MaxCodeMetadataLen>;
 type AccountIdLookupOf<T> = <<T as frame_system::Config>::Lookup as StaticLookup>::Source;
 type DebugBufferVec<T> = BoundedVec<u8, <T as Config>::MaxDebugBufferLen>;
+type EventRecordOf<T> =
+	EventRecord<<T as frame_system::Config>::RuntimeEvent, <T as frame_system::Config>::Hash>;
 
 /// Used as a sentinel value when reading and writing contract memory.
 ///
@@ -155,6 +173,48 @@ type DebugBufferVec<T> = BoundedVec<u8, <T as Config>::MaxDebugBufferLen>;
 /// that this value makes sense for a memory location or length.
 const SENTINEL: u32 = u32::MAX;
 
+/// Determines whether events should be collected during execution.
+#[derive(PartialEq, Eq, Clone, Copy, RuntimeDebug)]
+pub enum CollectEvents {
+	/// Collect events.
+	///
+	/// # Note
+	///
+	/// Events should only be collected when called off-chain, as this would otherwise
+	/// read all the events emitted in the block so far and put them into the PoV.
+	///
+	/// **Never** use this mode for on-chain execution.
+	UnsafeCollect,
+	/// Skip event collection.
+	Skip,
+}
+
+/// Determines what happens to the debug messages of contracts executed on-chain.
+///
+/// Dry runs that ask for a debug buffer always collect the messages into it.
+#[derive(PartialEq, Eq, Clone, Copy, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum DebugPolicy {
+	/// Debug messages are discarded and `seal_debug_message` returns `LoggingDisabled`.
+	Disabled,
+	/// Debug messages are written to the node log with the `runtime::contracts::debug` target.
+	///
+	/// This lets developers follow contracts on a live dev chain or testnet without replaying
+	/// the calls through RPC. It should not be used on production chains as contracts can
+	/// write arbitrary amounts of text to the log of every node.
+	Log,
+}
+
+/// A single call executed by [`Pallet::call_batch`].
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct BatchedCall<AccountId, Balance> {
+	/// Address of the contract to call.
+	pub dest: AccountId,
+	/// The balance to transfer from the origin to `dest`.
+	pub value: Balance,
+	/// The input data to pass to the contract.
+	pub data: Vec<u8>,
+}
+
 /// Provides the contract address generation method.
 ///
 /// See [`DefaultAddressGenerator`] for the default implementation.
@@ -172,14 +232,30 @@ pub trait AddressGenerator<T: Config> {
 		input_data: &[u8],
 		salt: &[u8],
 	) -> T::AccountId;
+
+	/// Called after a contract was created at the address returned by [`Self::generate_address`].
+	///
+	/// This allows stateful generators to advance their state. Does nothing by default.
+	fn on_contract_created(_deploying_address: &T::AccountId) {}
+
+	/// Generate the address of the account that holds the storage deposit of a contract.
+	///
+	/// The same rules as for [`Self::generate_address`] apply. Additionally, the result must
+	/// never collide with a contract address.
+	/// Formula: `hash("contract_depo_v1" ++ contract_addr)`
+	fn deposit_address(contract_addr: &T::AccountId) -> T::AccountId {
+		let entropy = (b"contract_depo_v1", contract_addr).using_encoded(T::Hashing::hash);
+		Decode::decode(&mut TrailingZeroInput::new(entropy.as_ref()))
+			.expect("infinite length input; no invalid inputs for type; qed")
+	}
 }
 
 /// Default address generator.
 ///
 /// This is the default address generator used by contract instantiation. Its result
 /// is only dependant on its inputs. It can therefore be used to reliably predict the
-/// address of a contract. This is akin to the formula of eth's CREATE2 opcode. There
-/// is no CREATE equivalent because CREATE2 is strictly more powerful.
+/// address of a contract. This is akin to the formula of eth's CREATE2 opcode. See
+/// [`SequentialAddressGenerator`] for a CREATE equivalent.
 /// Formula:
 /// `hash("contract_addr_v1" ++ deploying_address ++ code_hash ++ input_data ++ salt)`
 pub struct DefaultAddressGenerator;
@@ -198,6 +274,38 @@ impl<T: Config> AddressGenerator<T> for DefaultAddressGenerator {
 	}
 }
 
+/// Sequential address generator.
+///
+/// Derives the address of a contract from the deploying address and the number of contracts
+/// it instantiated before. This is akin to the formula of eth's CREATE opcode and is meant
+/// for tooling which relies on nonce based addresses. Code hash, input data and salt do
+/// not influence the address.
+///
+/// The per deployer nonce is kept in the `DeployerNonce` storage item and incremented
+/// whenever a contract is created.
+/// Formula:
+/// `hash("contract_addr_seq_v1" ++ deploying_address ++ deployer_nonce)`
+pub struct SequentialAddressGenerator;
+
+impl<T: Config> AddressGenerator<T> for SequentialAddressGenerator {
+	fn generate_address(
+		deploying_address: &T::AccountId,
+		_code_hash: &CodeHash<T>,
+		_input_data: &[u8],
+		_salt: &[u8],
+	) -> T::AccountId {
+		let nonce = <DeployerNonce<T>>::get(deploying_address);
+		let entropy =
+			(b"contract_addr_seq_v1", deploying_address, nonce).using_encoded(T::Hashing::hash);
+		Decode::decode(&mut TrailingZeroInput::new(entropy.as_ref()))
+			.expect("infinite length input; no invalid inputs for type; qed")
+	}
+
+	fn on_contract_created(deploying_address: &T::AccountId) {
+		<DeployerNonce<T>>::mutate(deploying_address, |nonce| *nonce = nonce.wrapping_add(1));
+	}
+}
+
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
@@ -205,7 +313,7 @@ pub mod pallet {
 	use frame_system::pallet_prelude::*;
 
 	/// The current storage version.
-	const STORAGE_VERSION: StorageVersion = StorageVersion::new(9);
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(18);
 
 	#[pallet::pallet]
 	#[pallet::storage_version(STORAGE_VERSION)]
@@ -219,6 +327,15 @@ pub mod pallet {
 		/// The generator used to supply randomness to contracts through `seal_random`
 		type Randomness: Randomness<Self::Hash, Self::BlockNumber>;
 
+		/// Make the `seal_random` host functions available to contracts.
+		///
+		/// Chains without a secure source of randomness should set this to `false`. Code that
+		/// imports `seal_random` is then rejected when it is uploaded, rather than being served
+		/// predictable values by [`Self::Randomness`]. Contracts that were uploaded before the
+		/// switch was turned off keep working.
+		#[pallet::constant]
+		type RandomnessEnabled: Get<bool>;
+
 		/// The currency in which fees are paid and contract balances are held.
 		type Currency: ReservableCurrency<Self::AccountId>
 			+ Inspect<Self::AccountId, Balance = BalanceOf<Self>>;
@@ -253,6 +370,13 @@ pub mod pallet {
 		/// be exploited to drive the runtime into a panic.
 		type CallFilter: Contains<<Self as frame_system::Config>::RuntimeCall>;
 
+		/// The origin that contracts dispatch runtime calls with.
+		///
+		/// Use [`DispatchAsSigned`] to dispatch with a signed origin of the contract's address.
+		/// Use [`DispatchAsContract`] to dispatch with [`Origin`] so that other pallets can tell
+		/// contracts apart from accounts controlled by a key. See [`EnsureSignedOrContract`].
+		type CallRuntimeOrigin: Convert<Self::AccountId, Self::RuntimeOrigin>;
+
 		/// Used to answer contracts' queries regarding the current weight price. This is **not**
 		/// used to calculate the actual fee and is only for informational purposes.
 		type WeightPrice: Convert<Weight, BalanceOf<Self>>;
@@ -264,7 +388,26 @@ pub mod pallet {
 		/// Type that allows the runtime authors to add new host functions for a contract to call.
 		type ChainExtension: chain_extension::ChainExtension<Self> + Default;
 
+		/// Native code that contracts can call at reserved addresses like another contract.
+		///
+		/// Use `()` if no builtins are available. See [`builtin`] for details.
+		type Builtins: builtin::Builtins<Self>;
+
+		/// Type that allows contracts to execute and send XCM messages.
+		///
+		/// Use `()` on chains that don't support XCM. See the [`xcm`] module for details.
+		type Xcm: xcm::Xcm<Self>;
+
+		/// Type that allows contracts to query and transfer fungible assets.
+		///
+		/// Use `()` on chains that don't offer assets to contracts. See the [`assets`] module for
+		/// details.
+		type Assets: assets::Assets<Self>;
+
 		/// Cost schedule and limits.
+		///
+		/// This is the default which can be overridden by [`Config::ScheduleOrigin`] through
+		/// [`Pallet::set_schedule`]. Use [`Pallet::schedule`] to get the schedule in effect.
 		#[pallet::constant]
 		type Schedule: Get<Schedule<Self>>;
 
@@ -321,12 +464,19 @@ pub mod pallet {
 		#[pallet::constant]
 		type DepositPerItem: Get<BalanceOf<Self>>;
 
+		/// The amount of balance a caller has to pay for each byte of event data emitted by a
+		/// contract.
+		///
+		/// The deposit is added to the base deposit of the emitting contract and hence only
+		/// refunded when the contract is terminated. Set this to zero in order to not charge
+		/// any deposit for events.
+		#[pallet::constant]
+		type DepositPerEventByte: Get<BalanceOf<Self>>;
+
 		/// The address generator used to generate the addresses of contracts.
 		type AddressGenerator: AddressGenerator<Self>;
 
-		/// The maximum length of a contract code in bytes. This limit applies to the instrumented
-		/// version of the code. Therefore `instantiate_with_code` can fail even when supplying
-		/// a wasm binary below this maximum size.
+		/// The maximum length of a contract code in bytes.
 		///
 		/// The value should be chosen carefully taking into the account the overall memory limit
 		/// your runtime has, as well as the [maximum allowed callstack
@@ -334,6 +484,12 @@ pub mod pallet {
 		#[pallet::constant]
 		type MaxCodeLen: Get<u32>;
 
+		/// The maximum length in bytes of the metadata that can be attached to an uploaded code.
+		///
+		/// See [`Pallet::set_code_metadata`].
+		#[pallet::constant]
+		type MaxCodeMetadataLen: Get<u32>;
+
 		/// The maximum allowable length in bytes for storage keys.
 		#[pallet::constant]
 		type MaxStorageKeyLen: Get<u32>;
@@ -353,13 +509,104 @@ pub mod pallet {
 		/// The maximum length of the debug buffer in bytes.
 		#[pallet::constant]
 		type MaxDebugBufferLen: Get<u32>;
+
+		/// What happens to debug messages emitted during on-chain execution.
+		///
+		/// See [`DebugPolicy`].
+		#[pallet::constant]
+		type Debugging: Get<DebugPolicy>;
+
+		/// The maximum number of dispatchables in the call filter of a single contract.
+		///
+		/// See [`Pallet::set_call_filter`].
+		#[pallet::constant]
+		type MaxCallFilterLen: Get<u32>;
+
+		/// The maximum number of calls that can be executed by a single [`Pallet::call_batch`].
+		#[pallet::constant]
+		type MaxBatchedCalls: Get<u32>;
+
+		/// The most storage a single contract can hold, if limited.
+		///
+		/// This is enforced in addition to the storage deposit. It keeps a contract from growing
+		/// its storage without bounds on chains where deposits are cheap. A call that would grow
+		/// a contract's storage beyond it fails. Contracts that already hold more, for example
+		/// because the ceiling was lowered, can still remove storage.
+		#[pallet::constant]
+		type MaxStoragePerContract: Get<Option<StorageCeiling>>;
+
+		/// The maximum number of code hashes a single contract can lock as delegate dependencies.
+		///
+		/// See `seal_lock_delegate_dependency`.
+		#[pallet::constant]
+		type MaxDelegateDependencies: Get<u32>;
+
+		/// Whether to collect [`CodeStats`] for every code hash.
+		///
+		/// The statistics can be queried through [`Pallet::code_stats`]. Collecting them costs
+		/// one storage read and write per call or instantiation which is charged as gas.
+		#[pallet::constant]
+		type CollectCodeStats: Get<bool>;
+
+		/// Whether to store newly uploaded code compressed with zstd.
+		///
+		/// The storage deposit is charged on the compressed size. Code is only stored compressed
+		/// if this actually makes it smaller. Code stored before this was enabled keeps working
+		/// and so does compressed code after it is disabled again.
+		///
+		/// # Note
+		///
+		/// This uses the `compression` host functions of `sp-io`. Nodes need to provide them
+		/// before this is enabled.
+		#[pallet::constant]
+		type CompressCode: Get<bool>;
+
+		/// Whether to remove code as soon as no contract uses it anymore.
+		///
+		/// When the last contract using a code hash is terminated or switches to another code
+		/// through `seal_set_code_hash` the code is removed and the deposit is refunded to its
+		/// uploader. Otherwise the code stays around until [`Pallet::remove_code`] is called.
+		/// Code that was merely uploaded and never instantiated is not affected.
+		#[pallet::constant]
+		type RemoveUnusedCode: Get<bool>;
+
+		/// Origin allowed to upload code.
+		///
+		/// This is checked by [`Pallet::upload_code`] and [`Pallet::instantiate_with_code`].
+		/// Use [`frame_system::EnsureSigned`] to allow every signed account.
+		type UploadOrigin: EnsureOrigin<Self::RuntimeOrigin, Success = Self::AccountId>;
+
+		/// Origin allowed to instantiate contracts.
+		///
+		/// This is checked by [`Pallet::instantiate`] and [`Pallet::instantiate_with_code`].
+		/// Use [`frame_system::EnsureSigned`] to allow every signed account.
+		///
+		/// # Note
+		///
+		/// This only applies to extrinsics. Contracts can still instantiate other contracts.
+		type InstantiateOrigin: EnsureOrigin<Self::RuntimeOrigin, Success = Self::AccountId>;
+
+		/// Origin allowed to override [`Config::Schedule`] through [`Pallet::set_schedule`].
+		type ScheduleOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// The sequence of multi block migrations applied by [`Migration`].
+		///
+		/// Migrations are listed in ascending order of their version. This should be set to
+		/// `()` if the pallet was never upgraded to a version that requires such a migration.
+		type Migrations: MigrateSequence;
 	}
 
 	#[pallet::hooks]
 	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
 		fn on_idle(_block: T::BlockNumber, remaining_weight: Weight) -> Weight {
-			Storage::<T>::process_deletion_queue_batch(remaining_weight)
+			let (result, weight) = Migration::<T>::migrate(remaining_weight);
+			// The deletion queue must not be processed while the storage is being migrated.
+			if !matches!(result, MigrateResult::NoMigrationInProgress | MigrateResult::Completed) {
+				return weight
+			}
+			Storage::<T>::process_deletion_queue_batch(remaining_weight.saturating_sub(weight))
 				.saturating_add(T::WeightInfo::on_process_deletion_queue_batch())
+				.saturating_add(weight)
 		}
 
 		fn on_initialize(_block: T::BlockNumber) -> Weight {
@@ -382,51 +629,13 @@ pub mod pallet {
 		}
 
 		fn integrity_test() {
-			// Total runtime memory is expected to have 128Mb upper limit
-			const MAX_RUNTIME_MEM: u32 = 1024 * 1024 * 128;
-			// Memory limits for a single contract:
-			// Value stack size: 1Mb per contract, default defined in wasmi
-			const MAX_STACK_SIZE: u32 = 1024 * 1024;
-			// Heap limit is normally 16 mempages of 64kb each = 1Mb per contract
-			let max_heap_size = T::Schedule::get().limits.max_memory_size();
-			// Max call depth is CallStack::size() + 1
-			let max_call_depth = u32::try_from(T::CallStack::size().saturating_add(1))
-				.expect("CallStack size is too big");
+			// Storage is not available here, so this checks `Config::Schedule`. An override is
+			// checked by `set_schedule` before it is stored.
 
 			// Check that given configured `MaxCodeLen`, runtime heap memory limit can't be broken.
-			//
-			// In worst case, the decoded wasm contract code would be `x16` times larger than the
-			// encoded one. This is because even a single-byte wasm instruction has 16-byte size in
-			// wasmi. This gives us `MaxCodeLen*16` safety margin.
-			//
-			// Next, the pallet keeps both the original and instrumented wasm blobs for each
-			// contract, hence we add up `MaxCodeLen*2` more to the safety margin.
-			//
-			// Finally, the inefficiencies of the freeing-bump allocator
-			// being used in the client for the runtime memory allocations, could lead to possible
-			// memory allocations for contract code grow up to `x4` times in some extreme cases,
-			// which gives us total multiplier of `18*4` for `MaxCodeLen`.
-			//
-			// That being said, for every contract executed in runtime, at least `MaxCodeLen*18*4`
-			// memory should be available. Note that maximum allowed heap memory and stack size per
-			// each contract (stack frame) should also be counted.
-			//
-			// Finally, we allow 50% of the runtime memory to be utilized by the contracts call
-			// stack, keeping the rest for other facilities, such as PoV, etc.
-			//
-			// This gives us the following formula:
-			//
-			// `(MaxCodeLen * 18 * 4 + MAX_STACK_SIZE + max_heap_size) * max_call_depth <
-			// MAX_RUNTIME_MEM/2`
-			//
-			// Hence the upper limit for the `MaxCodeLen` can be defined as follows:
-			let code_len_limit = MAX_RUNTIME_MEM
-				.saturating_div(2)
-				.saturating_div(max_call_depth)
-				.saturating_sub(max_heap_size)
-				.saturating_sub(MAX_STACK_SIZE)
-				.saturating_div(18 * 4);
-
+			let max_call_depth = u32::try_from(T::CallStack::size().saturating_add(1))
+				.expect("CallStack size is too big");
+			let code_len_limit = Self::code_len_limit(&T::Schedule::get());
 			assert!(
 				T::MaxCodeLen::get() < code_len_limit,
 				"Given `CallStack` height {:?}, `MaxCodeLen` should be set less than {:?} \
@@ -436,6 +645,24 @@ pub mod pallet {
 				T::MaxCodeLen::get(),
 			);
 
+			// Every function the schedule allows must fit onto the stack
+			let limits = T::Schedule::get().limits;
+			assert!(
+				limits.stack_height >= limits.locals.saturating_add(limits.parameters),
+				"`Schedule::limits.stack_height` ({}) must at least hold the maximum number of \
+				 locals ({}) and parameters ({}) of a function",
+				limits.stack_height,
+				limits.locals,
+				limits.parameters,
+			);
+
+			// Forbidden imports which are not host functions could never match
+			assert!(
+				Self::forbidden_imports_are_valid(&T::Schedule::get().validation.forbidden_imports),
+				"`Schedule::validation.forbidden_imports` must only list host functions and each \
+				 of them only once",
+			);
+
 			// Debug buffer should at least be large enough to accomodate a simple error message
 			const MIN_DEBUG_BUF_SIZE: u32 = 256;
 			assert!(
@@ -443,7 +670,24 @@ pub mod pallet {
 				"Debug buffer should have minimum size of {} (current setting is {})",
 				MIN_DEBUG_BUF_SIZE,
 				T::MaxDebugBufferLen::get(),
-			)
+			);
+
+			// Functions with changed semantics are added to a new `sealN` module
+			assert_eq!(
+				wasm::API_VERSION,
+				wasm::Env::MAX_MODULE_VERSION,
+				"`API_VERSION` must be bumped when a new `sealN` module is introduced",
+			);
+
+			// Chain extensions sharing an ID would shadow each other
+			let mut ext_ids =
+				<T::ChainExtension as chain_extension::ChainExtension<T>>::registered_ids();
+			ext_ids.sort_unstable();
+			assert!(
+				ext_ids.windows(2).all(|w| w[0] != w[1]),
+				"Every chain extension in `Config::ChainExtension` must have a unique ID (IDs: {:?})",
+				ext_ids,
+			);
 		}
 	}
 
@@ -536,11 +780,10 @@ pub mod pallet {
 		///
 		/// If the code does not already exist a deposit is reserved from the caller
 		/// and unreserved only when [`Self::remove_code`] is called. The size of the reserve
-		/// depends on the instrumented size of the the supplied `code`.
+		/// depends on the size of the supplied `code`.
 		///
-		/// If the code already exists in storage it will still return `Ok` and upgrades
-		/// the in storage version to the current
-		/// [`InstructionWeights::version`](InstructionWeights).
+		/// If the code already exists in storage it will still return `Ok` and leaves
+		/// the stored code untouched.
 		///
 		/// - `determinism`: If this is set to any other value but [`Determinism::Deterministic`]
 		///   then the only way to use this code is to delegate call into it from an offchain
@@ -560,7 +803,8 @@ pub mod pallet {
 			storage_deposit_limit: Option<<BalanceOf<T> as codec::HasCompact>::Type>,
 			determinism: Determinism,
 		) -> DispatchResult {
-			let origin = ensure_signed(origin)?;
+			Migration::<T>::ensure_migrated()?;
+			let origin = T::UploadOrigin::ensure_origin(origin)?;
 			Self::bare_upload_code(origin, code, storage_deposit_limit.map(Into::into), determinism)
 				.map(|_| ())
 		}
@@ -575,12 +819,33 @@ pub mod pallet {
 			origin: OriginFor<T>,
 			code_hash: CodeHash<T>,
 		) -> DispatchResultWithPostInfo {
+			Migration::<T>::ensure_migrated()?;
 			let origin = ensure_signed(origin)?;
 			<PrefabWasmModule<T>>::remove(&origin, code_hash)?;
 			// we waive the fee because removing unused code is beneficial
 			Ok(Pays::No.into())
 		}
 
+		/// Attach metadata to the code stored under `code_hash` or clear it by passing `None`.
+		///
+		/// Only the owner of the code can set its metadata. A deposit proportional to the
+		/// length of the metadata is reserved from the owner and refunded when the metadata
+		/// is replaced, cleared or the code is removed. Batch this with [`Self::upload_code`]
+		/// to register the metadata together with the code.
+		#[pallet::call_index(16)]
+		#[pallet::weight(T::WeightInfo::set_code_metadata(
+			metadata.as_ref().map_or(0, |m| m.len() as u32)
+		))]
+		pub fn set_code_metadata(
+			origin: OriginFor<T>,
+			code_hash: CodeHash<T>,
+			metadata: Option<CodeMetadataVec<T>>,
+		) -> DispatchResult {
+			Migration::<T>::ensure_migrated()?;
+			let origin = ensure_signed(origin)?;
+			<PrefabWasmModule<T>>::set_metadata(&origin, code_hash, metadata)
+		}
+
 		/// Privileged function that changes the code of an existing contract.
 		///
 		/// This takes care of updating refcounts and all other necessary operations. Returns
@@ -598,27 +863,170 @@ pub mod pallet {
 			dest: AccountIdLookupOf<T>,
 			code_hash: CodeHash<T>,
 		) -> DispatchResult {
+			Migration::<T>::ensure_migrated()?;
 			ensure_root(origin)?;
 			let dest = T::Lookup::lookup(dest)?;
-			<ContractInfoOf<T>>::try_mutate(&dest, |contract| {
-				let contract = if let Some(contract) = contract {
-					contract
-				} else {
-					return Err(<Error<T>>::ContractNotFound.into())
-				};
-				<PrefabWasmModule<T>>::add_user(code_hash)?;
-				<PrefabWasmModule<T>>::remove_user(contract.code_hash);
-				Self::deposit_event(
-					vec![T::Hashing::hash_of(&dest), code_hash, contract.code_hash],
-					Event::ContractCodeUpdated {
-						contract: dest.clone(),
-						new_code_hash: code_hash,
-						old_code_hash: contract.code_hash,
-					},
+			let mut contract =
+				ContractInfo::<T>::load(&dest).ok_or(<Error<T>>::ContractNotFound)?;
+			<PrefabWasmModule<T>>::add_user(code_hash)?;
+			<PrefabWasmModule<T>>::remove_user(contract.code_hash);
+			Self::deposit_event(
+				vec![T::Hashing::hash_of(&dest), code_hash, contract.code_hash],
+				Event::ContractCodeUpdated {
+					contract: dest.clone(),
+					new_code_hash: code_hash,
+					old_code_hash: contract.code_hash,
+				},
+			);
+			contract.code_hash = code_hash;
+			<ContractInfoOf<T>>::insert(&dest, contract);
+			Ok(())
+		}
+
+		/// Privileged function that sets the call filter of an existing contract.
+		///
+		/// A contract with a call filter can only dispatch calls through `seal_call_runtime`
+		/// that are in its filter and also pass [`Config::CallFilter`]. This allows granting
+		/// specific contracts access to specific dispatchables without widening the global
+		/// filter. Passing `None` removes the filter so that only [`Config::CallFilter`]
+		/// applies.
+		///
+		/// Returns an error if `dest` does not exist.
+		#[pallet::call_index(9)]
+		#[pallet::weight(T::WeightInfo::set_call_filter())]
+		pub fn set_call_filter(
+			origin: OriginFor<T>,
+			dest: AccountIdLookupOf<T>,
+			call_filter: Option<CallFilter<T>>,
+		) -> DispatchResult {
+			Migration::<T>::ensure_migrated()?;
+			ensure_root(origin)?;
+			let dest = T::Lookup::lookup(dest)?;
+			let mut contract =
+				ContractInfo::<T>::load(&dest).ok_or(<Error<T>>::ContractNotFound)?;
+			contract.call_filter = call_filter;
+			<ContractInfoOf<T>>::insert(&dest, contract);
+			Self::deposit_event(
+				vec![T::Hashing::hash_of(&dest)],
+				Event::ContractCallFilterUpdated { contract: dest },
+			);
+			Ok(())
+		}
+
+		/// Privileged function that pauses an existing contract.
+		///
+		/// Any call into a paused contract fails with [`Error::ContractPaused`]. This includes
+		/// calls made by other contracts. The storage and balance of the contract are left
+		/// untouched so that it can resume operation after [`Self::unpause_contract`].
+		///
+		/// Returns an error if `dest` does not exist or is already paused.
+		#[pallet::call_index(10)]
+		#[pallet::weight(T::WeightInfo::pause_contract())]
+		pub fn pause_contract(origin: OriginFor<T>, dest: AccountIdLookupOf<T>) -> DispatchResult {
+			Migration::<T>::ensure_migrated()?;
+			ensure_root(origin)?;
+			let dest = T::Lookup::lookup(dest)?;
+			let mut contract =
+				ContractInfo::<T>::load(&dest).ok_or(<Error<T>>::ContractNotFound)?;
+			ensure!(!contract.paused, <Error<T>>::ContractPaused);
+			contract.paused = true;
+			<ContractInfoOf<T>>::insert(&dest, contract);
+			Self::deposit_event(
+				vec![T::Hashing::hash_of(&dest)],
+				Event::ContractPaused { contract: dest },
+			);
+			Ok(())
+		}
+
+		/// Privileged function that resumes a contract paused by [`Self::pause_contract`].
+		///
+		/// Returns an error if `dest` does not exist or is not paused.
+		#[pallet::call_index(11)]
+		#[pallet::weight(T::WeightInfo::unpause_contract())]
+		pub fn unpause_contract(
+			origin: OriginFor<T>,
+			dest: AccountIdLookupOf<T>,
+		) -> DispatchResult {
+			Migration::<T>::ensure_migrated()?;
+			ensure_root(origin)?;
+			let dest = T::Lookup::lookup(dest)?;
+			let mut contract =
+				ContractInfo::<T>::load(&dest).ok_or(<Error<T>>::ContractNotFound)?;
+			ensure!(contract.paused, <Error<T>>::ContractNotPaused);
+			contract.paused = false;
+			<ContractInfoOf<T>>::insert(&dest, contract);
+			Self::deposit_event(
+				vec![T::Hashing::hash_of(&dest)],
+				Event::ContractUnpaused { contract: dest },
+			);
+			Ok(())
+		}
+
+		/// Privileged function that overrides [`Config::Schedule`].
+		///
+		/// The new schedule must have a greater `instruction_weights.version` than the schedule
+		/// in effect. Its limits are checked against [`Config::MaxCodeLen`] and
+		/// [`Config::CallStack`] just like the default schedule is checked by `integrity_test`.
+		/// The stack height must fit the maximum locals and parameters of a function and every
+		/// forbidden import must name a distinct host function. Passing
+		/// `None` reverts to [`Config::Schedule`] without any checks. The override stops
+		/// being used once a runtime upgrade ships a [`Config::Schedule`] with the same or a
+		/// greater version.
+		#[pallet::call_index(13)]
+		#[pallet::weight(T::WeightInfo::set_schedule())]
+		pub fn set_schedule(
+			origin: OriginFor<T>,
+			schedule: Option<Box<Schedule<T>>>,
+		) -> DispatchResult {
+			Migration::<T>::ensure_migrated()?;
+			T::ScheduleOrigin::ensure_origin(origin)?;
+			if let Some(schedule) = &schedule {
+				ensure!(
+					schedule.instruction_weights.version >
+						Self::schedule().instruction_weights.version,
+					<Error<T>>::InvalidScheduleVersion,
 				);
-				contract.code_hash = code_hash;
-				Ok(())
-			})
+				Self::ensure_schedule_is_valid(schedule)?;
+			}
+			<ScheduleOverride<T>>::set(schedule.map(|schedule| *schedule));
+			Self::deposit_event(vec![], Event::ScheduleUpdated);
+			Ok(())
+		}
+
+		/// Privileged function that removes code even if it is still in use.
+		///
+		/// This is a kill switch for code containing exploits. The deposit is refunded to the
+		/// owner of the code. Contracts still using the code are tombstoned: Their storage and
+		/// balance are left untouched but any call into them fails with
+		/// [`Error::CodeTombstoned`]. The same code can't be uploaded again.
+		#[pallet::call_index(14)]
+		#[pallet::weight(T::WeightInfo::force_remove_code())]
+		pub fn force_remove_code(origin: OriginFor<T>, code_hash: CodeHash<T>) -> DispatchResult {
+			Migration::<T>::ensure_migrated()?;
+			ensure_root(origin)?;
+			<PrefabWasmModule<T>>::force_remove(code_hash)
+		}
+
+		/// Runs steps of the multi block migration that is currently in progress.
+		///
+		/// While a migration is in progress all other dispatchables of this pallet fail. The
+		/// migration also progresses in `on_idle`. This dispatchable can be used to speed it up.
+		/// The fee is waived if at least one step was executed.
+		#[pallet::call_index(12)]
+		#[pallet::weight(T::WeightInfo::migrate().saturating_add(*weight_limit))]
+		pub fn migrate(origin: OriginFor<T>, weight_limit: Weight) -> DispatchResultWithPostInfo {
+			ensure_signed(origin)?;
+			let (result, weight) = Migration::<T>::migrate(weight_limit);
+			let actual_weight = Some(T::WeightInfo::migrate().saturating_add(weight));
+			match result {
+				MigrateResult::InProgress { .. } | MigrateResult::Completed =>
+					Ok(PostDispatchInfo { actual_weight, pays_fee: Pays::No }),
+				MigrateResult::NoMigrationInProgress | MigrateResult::NoMigrationPerformed =>
+					Err(DispatchErrorWithPostInfo {
+						post_info: PostDispatchInfo { actual_weight, pays_fee: Pays::Yes },
+						error: <Error<T>>::NoMigrationPerformed.into(),
+					}),
+			}
 		}
 
 		/// Makes a call to an account, optionally transferring some balance.
@@ -637,6 +1045,8 @@ pub mod pallet {
 		/// * If the account is a regular account, any value will be transferred.
 		/// * If no account exists and the call value is not less than `existential_deposit`,
 		/// a regular account will be created and any value will be transferred.
+		///
+		/// The `origin` can be signed or root. See [`CallOrigin`] for what root can't do.
 		#[pallet::call_index(6)]
 		#[pallet::weight(T::WeightInfo::call().saturating_add(*gas_limit))]
 		pub fn call(
@@ -647,8 +1057,9 @@ pub mod pallet {
 			storage_deposit_limit: Option<<BalanceOf<T> as codec::HasCompact>::Type>,
 			data: Vec<u8>,
 		) -> DispatchResultWithPostInfo {
+			Migration::<T>::ensure_migrated()?;
 			let gas_limit: Weight = gas_limit.into();
-			let origin = ensure_signed(origin)?;
+			let origin = CallOrigin::from_runtime_origin(origin)?;
 			let dest = T::Lookup::lookup(dest)?;
 			let mut output = Self::internal_call(
 				origin,
@@ -658,6 +1069,7 @@ pub mod pallet {
 				storage_deposit_limit.map(Into::into),
 				data,
 				None,
+				CollectEvents::Skip,
 				Determinism::Deterministic,
 			);
 			if let Ok(retval) = &output.result {
@@ -687,7 +1099,7 @@ pub mod pallet {
 		///
 		/// Instantiation is executed as follows:
 		///
-		/// - The supplied `code` is instrumented, deployed, and a `code_hash` is created for that
+		/// - The supplied `code` is validated, deployed, and a `code_hash` is created for that
 		///   code.
 		/// - If the `code_hash` already exists on the chain the underlying `code` will be shared.
 		/// - The destination address is computed based on the sender, code_hash and the salt.
@@ -708,7 +1120,9 @@ pub mod pallet {
 			data: Vec<u8>,
 			salt: Vec<u8>,
 		) -> DispatchResultWithPostInfo {
-			let origin = ensure_signed(origin)?;
+			Migration::<T>::ensure_migrated()?;
+			T::UploadOrigin::ensure_origin(origin.clone())?;
+			let origin = T::InstantiateOrigin::ensure_origin(origin)?;
 			let code_len = code.len() as u32;
 			let data_len = data.len() as u32;
 			let salt_len = salt.len() as u32;
@@ -721,6 +1135,7 @@ pub mod pallet {
 				data,
 				salt,
 				None,
+				CollectEvents::Skip,
 			);
 			if let Ok(retval) = &output.result {
 				if retval.1.did_revert() {
@@ -751,7 +1166,8 @@ pub mod pallet {
 			data: Vec<u8>,
 			salt: Vec<u8>,
 		) -> DispatchResultWithPostInfo {
-			let origin = ensure_signed(origin)?;
+			Migration::<T>::ensure_migrated()?;
+			let origin = T::InstantiateOrigin::ensure_origin(origin)?;
 			let data_len = data.len() as u32;
 			let salt_len = salt.len() as u32;
 			let mut output = Self::internal_instantiate(
@@ -763,6 +1179,7 @@ pub mod pallet {
 				data,
 				salt,
 				None,
+				CollectEvents::Skip,
 			);
 			if let Ok(retval) = &output.result {
 				if retval.1.did_revert() {
@@ -774,8 +1191,52 @@ pub mod pallet {
 				T::WeightInfo::instantiate(data_len, salt_len),
 			)
 		}
+
+		/// Makes a list of calls sequentially with a shared gas and storage deposit limit.
+		///
+		/// All calls are charged against the same `gas_limit` and `storage_deposit_limit`. This
+		/// is different from batching [`Self::call`] through `pallet-utility` where every call
+		/// needs its own limits. The batch is all-or-nothing: If any call fails or reverts all
+		/// calls are rolled back and the error of the failing call is returned.
+		///
+		/// See [`Self::call`] for a description of the parameters and the allowed origins.
+		#[pallet::call_index(15)]
+		#[pallet::weight(
+			T::WeightInfo::call().saturating_mul(calls.len() as u64).saturating_add(*gas_limit)
+		)]
+		pub fn call_batch(
+			origin: OriginFor<T>,
+			calls: BoundedVec<BatchedCall<AccountIdLookupOf<T>, BalanceOf<T>>, T::MaxBatchedCalls>,
+			gas_limit: Weight,
+			storage_deposit_limit: Option<<BalanceOf<T> as codec::HasCompact>::Type>,
+		) -> DispatchResultWithPostInfo {
+			Migration::<T>::ensure_migrated()?;
+			let origin = CallOrigin::from_runtime_origin(origin)?;
+			let base_weight = T::WeightInfo::call().saturating_mul(calls.len() as u64);
+			let calls = calls
+				.into_iter()
+				.map(|call| {
+					Ok(BatchedCall {
+						dest: T::Lookup::lookup(call.dest)?,
+						value: call.value,
+						data: call.data,
+					})
+				})
+				.collect::<Result<Vec<_>, DispatchError>>()?;
+			let output = Self::internal_call_batch(
+				origin,
+				calls,
+				gas_limit,
+				storage_deposit_limit.map(Into::into),
+			);
+			output.gas_meter.into_dispatch_result(output.result, base_weight)
+		}
 	}
 
+	/// The origin of runtime calls dispatched by contracts with [`DispatchAsContract`].
+	#[pallet::origin]
+	pub type Origin<T> = RawOrigin<<T as frame_system::Config>::AccountId>;
+
 	#[pallet::event]
 	pub enum Event<T: Config> {
 		/// Contract deployed by address at the specified address.
@@ -827,8 +1288,8 @@ pub mod pallet {
 		/// calls. This is because on failure all storage changes including events are
 		/// rolled back.
 		Called {
-			/// The account that called the `contract`.
-			caller: T::AccountId,
+			/// The account that called the `contract` or root.
+			caller: CallOrigin<T>,
 			/// The contract that was called.
 			contract: T::AccountId,
 		},
@@ -847,12 +1308,70 @@ pub mod pallet {
 			/// The code hash that was delegate called.
 			code_hash: CodeHash<T>,
 		},
+
+		/// The base deposit of a newly instantiated contract was charged.
+		///
+		/// The base deposit pays for the contract's own account data. It is held in the
+		/// contract's deposit account and does not include the minimum balance of the contract.
+		StorageBaseDepositCharged {
+			/// The contract that was instantiated.
+			contract: T::AccountId,
+			/// The account that paid the base deposit.
+			payer: T::AccountId,
+			/// The amount that was charged.
+			amount: BalanceOf<T>,
+		},
+
+		/// The base deposit of a terminated contract was refunded.
+		StorageBaseDepositRefunded {
+			/// The contract that was terminated.
+			contract: T::AccountId,
+			/// The account that receives the base deposit.
+			payee: T::AccountId,
+			/// The amount that was refunded.
+			amount: BalanceOf<T>,
+		},
+
+		/// The call filter of a contract was set or removed.
+		ContractCallFilterUpdated {
+			/// The contract whose call filter has been updated.
+			contract: T::AccountId,
+		},
+
+		/// A contract was paused. Calls into it fail until it is unpaused.
+		ContractPaused {
+			/// The contract that was paused.
+			contract: T::AccountId,
+		},
+
+		/// A paused contract was unpaused.
+		ContractUnpaused {
+			/// The contract that was unpaused.
+			contract: T::AccountId,
+		},
+
+		/// The schedule in effect was changed through [`Pallet::set_schedule`].
+		ScheduleUpdated,
+
+		/// Code was removed by [`Pallet::force_remove_code`].
+		CodeForceRemoved {
+			code_hash: T::Hash,
+			/// The number of contracts that were still using the code and are now tombstoned.
+			refcount: u64,
+		},
+
+		/// The metadata of a code was set or cleared by [`Pallet::set_code_metadata`].
+		CodeMetadataUpdated { code_hash: T::Hash },
 	}
 
 	#[pallet::error]
 	pub enum Error<T> {
 		/// A new schedule must have a greater version than the current one.
 		InvalidScheduleVersion,
+		/// The limits of a new schedule allow contracts to exhaust the runtime memory.
+		///
+		/// See [`Pallet::set_schedule`].
+		ScheduleLimitsTooHigh,
 		/// Invalid combination of flags supplied to `seal_call` or `seal_delegate_call`.
 		InvalidCallFlags,
 		/// The executed contract exhausted its gas limit.
@@ -920,7 +1439,7 @@ pub mod pallet {
 		/// or via RPC an `Ok` will be returned. In this case the caller needs to inspect the flags
 		/// to determine whether a reversion has taken place.
 		ContractReverted,
-		/// The contract's code was found to be invalid during validation or instrumentation.
+		/// The contract's code was found to be invalid during validation.
 		///
 		/// The most likely cause of this is that an API was used which is not supported by the
 		/// node. This hapens if an older node is used with a new version of ink!. Try updating
@@ -931,20 +1450,103 @@ pub mod pallet {
 		CodeRejected,
 		/// An indetermistic code was used in a context where this is not permitted.
 		Indeterministic,
+		/// The base deposit of a contract that is being instantiated could not be covered.
+		///
+		/// Either the storage deposit limit or the free balance of the origin is too low to pay
+		/// the base deposit and the minimum balance of the new contract.
+		StorageBaseDepositNotCovered,
+		/// A contract tried to use XCM but the chain does not allow contracts to use XCM.
+		XcmDisabled,
+		/// The called contract is paused. See [`Pallet::pause_contract`].
+		ContractPaused,
+		/// The contract is not paused and hence can't be unpaused.
+		ContractNotPaused,
+		/// A multi block migration is in progress. See [`Pallet::migrate`].
+		MigrationInProgress,
+		/// [`Pallet::migrate`] did not execute any migration step.
+		///
+		/// Either no migration is in progress or the weight limit is too low for a single step.
+		NoMigrationPerformed,
+		/// The stored code could not be decompressed.
+		CodeDecompressionFailed,
+		/// The contract already locks the maximum number of delegate dependencies.
+		MaxDelegateDependenciesReached,
+		/// The contract does not lock the code hash it tried to unlock.
+		DelegateDependencyNotFound,
+		/// The contract already locks this code hash.
+		DelegateDependencyAlreadyExists,
+		/// A contract can't lock its own code hash as a delegate dependency.
+		CannotAddSelfAsDelegateDependency,
+		/// Invalid combination of flags supplied to `seal_transfer`.
+		InvalidTransferFlags,
+		/// A contract tried to use assets but the chain does not offer assets to contracts.
+		AssetsDisabled,
+		/// The code was removed by [`Pallet::force_remove_code`].
+		///
+		/// Contracts using it can't be called and the code can't be uploaded again.
+		CodeTombstoned,
+		/// The contract would hold more storage than [`Config::MaxStoragePerContract`] allows.
+		MaxStoragePerContractExceeded,
+		/// An action that needs an account was attempted by a call stack started by root.
+		///
+		/// See [`CallOrigin`] for what root can't do.
+		RootNotAllowed,
+		/// Value was sent along with a call to a builtin which can't receive any.
+		BuiltinNotPayable,
+		/// The limits of a new schedule contradict each other.
+		///
+		/// See [`Pallet::set_schedule`].
+		InconsistentScheduleLimits,
+		/// The validation rules of a new schedule forbid an unknown import or list an import
+		/// twice.
+		///
+		/// See [`Pallet::set_schedule`].
+		InvalidScheduleValidationRules,
 	}
 
-	/// A mapping from an original code hash to the original code, untouched by instrumentation.
+	/// A mapping from a code hash to the validated code.
+	///
+	/// It is executed as is unless it was compressed because of [`Config::CompressCode`].
+	/// Use [`Pallet::pristine_code`] to retrieve the code as it was uploaded.
 	#[pallet::storage]
 	pub(crate) type PristineCode<T: Config> = StorageMap<_, Identity, CodeHash<T>, CodeVec<T>>;
 
-	/// A mapping between an original code hash and instrumented wasm code, ready for execution.
+	/// A mapping between a code hash and the information stored alongside the code.
+	#[pallet::storage]
+	pub(crate) type CodeInfoOf<T: Config> = StorageMap<_, Identity, CodeHash<T>, CodeInfo<T>>;
+
+	/// The encoded state of the multi block migration in progress. See [`Pallet::migrate`].
+	#[pallet::storage]
+	pub(crate) type MigrationInProgress<T: Config> =
+		StorageValue<_, migration::Cursor, OptionQuery>;
+
+	/// The schedule set through [`Pallet::set_schedule`]. See [`Pallet::schedule`].
+	///
+	/// Changing the layout of [`Schedule`] requires a migration that removes this value.
+	#[pallet::storage]
+	#[pallet::unbounded]
+	pub(crate) type ScheduleOverride<T: Config> = StorageValue<_, Schedule<T>, OptionQuery>;
+
+	/// Code hashes removed by [`Pallet::force_remove_code`] while they were still in use.
+	#[pallet::storage]
+	pub(crate) type TombstonedCode<T: Config> = StorageMap<_, Identity, CodeHash<T>, ()>;
+
+	/// Execution statistics of a code hash. Only populated if [`Config::CollectCodeStats`] is set.
 	#[pallet::storage]
-	pub(crate) type CodeStorage<T: Config> =
-		StorageMap<_, Identity, CodeHash<T>, PrefabWasmModule<T>>;
+	pub(crate) type CodeStatsOf<T: Config> =
+		StorageMap<_, Identity, CodeHash<T>, CodeStats, ValueQuery>;
 
-	/// A mapping between an original code hash and its owner information.
+	/// Metadata attached to a code hash by its owner together with the deposit reserved for it.
+	#[pallet::storage]
+	pub(crate) type CodeMetadataOf<T: Config> =
+		StorageMap<_, Identity, CodeHash<T>, (CodeMetadataVec<T>, BalanceOf<T>)>;
+
+	/// The storage deposit the current transaction may still charge in total.
+	///
+	/// Set by [`CheckStorageDepositLimit`] before the transaction is dispatched and removed
+	/// after it was dispatched.
 	#[pallet::storage]
-	pub(crate) type OwnerInfoOf<T: Config> = StorageMap<_, Identity, CodeHash<T>, OwnerInfo<T>>;
+	pub(crate) type TransactionDepositLimit<T: Config> = StorageValue<_, BalanceOf<T>>;
 
 	/// This is a **monotonic** counter incremented on contract instantiation.
 	///
@@ -978,13 +1580,26 @@ pub mod pallet {
 	pub(crate) type ContractInfoOf<T: Config> =
 		StorageMap<_, Twox64Concat, T::AccountId, ContractInfo<T>>;
 
+	/// The number of contracts created by a given account.
+	///
+	/// This is only maintained when [`SequentialAddressGenerator`] is used as
+	/// [`Config::AddressGenerator`]. It is never decremented.
+	///
+	/// TWOX-NOTE: SAFE since `AccountId` is a secure hash.
+	#[pallet::storage]
+	pub(crate) type DeployerNonce<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, u64, ValueQuery>;
+
 	/// Evicted contracts that await child trie deletion.
 	///
 	/// Child trie deletion is a heavy operation depending on the amount of storage items
 	/// stored in said trie. Therefore this operation is performed lazily in `on_initialize`.
 	#[pallet::storage]
-	pub(crate) type DeletionQueue<T: Config> =
-		StorageValue<_, BoundedVec<DeletedContract, T::DeletionQueueDepth>, ValueQuery>;
+	pub(crate) type DeletionQueue<T: Config> = StorageValue<
+		_,
+		BoundedVec<DeletedContract<T::AccountId>, T::DeletionQueueDepth>,
+		ValueQuery,
+	>;
 }
 
 /// Return type of the private [`Pallet::internal_call`] function.
@@ -1001,32 +1616,39 @@ struct InternalOutput<T: Config, O> {
 	storage_deposit: StorageDeposit<BalanceOf<T>>,
 	/// The result of the call.
 	result: Result<O, ExecError>,
+	/// The events that were emitted during execution if collection was requested.
+	events: Option<Vec<EventRecordOf<T>>>,
 }
 
 impl<T: Config> Pallet<T> {
 	/// Perform a call to a specified contract.
 	///
 	/// This function is similar to [`Self::call`], but doesn't perform any address lookups
-	/// and better suitable for calling directly from Rust.
+	/// and better suitable for calling directly from Rust. Just as [`Self::call`] it can be
+	/// called by root.
 	///
 	/// # Note
 	///
 	/// `debug` should only ever be set to `true` when executing as an RPC because
 	/// it adds allocations and could be abused to drive the runtime into an OOM panic.
-	/// If set to `true` it returns additional human readable debugging information.
+	/// If set to `true` it returns additional human readable debugging information and the
+	/// storage keys changed by the execution together with counters of the I/O it performed.
+	///
+	/// The same is true for `collect_events`: See [`CollectEvents`].
 	///
 	/// It returns the execution result and the amount of used weight.
 	pub fn bare_call(
-		origin: T::AccountId,
+		origin: CallOrigin<T>,
 		dest: T::AccountId,
 		value: BalanceOf<T>,
 		gas_limit: Weight,
 		storage_deposit_limit: Option<BalanceOf<T>>,
 		data: Vec<u8>,
 		debug: bool,
+		collect_events: CollectEvents,
 		determinism: Determinism,
-	) -> ContractExecResult<BalanceOf<T>> {
-		let mut debug_message = if debug { Some(DebugBufferVec::<T>::default()) } else { None };
+	) -> ContractExecResult<T::AccountId, BalanceOf<T>, EventRecordOf<T>> {
+		let mut debug_message = if debug { Some(DebugBuffer::<T>::default()) } else { None };
 		let output = Self::internal_call(
 			origin,
 			dest,
@@ -1035,14 +1657,21 @@ impl<T: Config> Pallet<T> {
 			storage_deposit_limit,
 			data,
 			debug_message.as_mut(),
+			collect_events,
 			determinism,
 		);
+		let (debug_message, debug_message_truncated, storage_changes, io_counters) =
+			debug_message.map(DebugBuffer::into_parts).unwrap_or_default();
 		ContractExecResult {
 			result: output.result.map_err(|r| r.error),
 			gas_consumed: output.gas_meter.gas_consumed(),
 			gas_required: output.gas_meter.gas_required(),
 			storage_deposit: output.storage_deposit,
-			debug_message: debug_message.unwrap_or_default().to_vec(),
+			debug_message,
+			debug_message_truncated,
+			storage_changes,
+			io_counters,
+			events: output.events,
 		}
 	}
 
@@ -1057,7 +1686,10 @@ impl<T: Config> Pallet<T> {
 	///
 	/// `debug` should only ever be set to `true` when executing as an RPC because
 	/// it adds allocations and could be abused to drive the runtime into an OOM panic.
-	/// If set to `true` it returns additional human readable debugging information.
+	/// If set to `true` it returns additional human readable debugging information and the
+	/// storage keys changed by the execution together with counters of the I/O it performed.
+	///
+	/// The same is true for `collect_events`: See [`CollectEvents`].
 	pub fn bare_instantiate(
 		origin: T::AccountId,
 		value: BalanceOf<T>,
@@ -1067,8 +1699,9 @@ impl<T: Config> Pallet<T> {
 		data: Vec<u8>,
 		salt: Vec<u8>,
 		debug: bool,
-	) -> ContractInstantiateResult<T::AccountId, BalanceOf<T>> {
-		let mut debug_message = if debug { Some(DebugBufferVec::<T>::default()) } else { None };
+		collect_events: CollectEvents,
+	) -> ContractInstantiateResult<T::AccountId, BalanceOf<T>, EventRecordOf<T>> {
+		let mut debug_message = if debug { Some(DebugBuffer::<T>::default()) } else { None };
 		let output = Self::internal_instantiate(
 			origin,
 			value,
@@ -1078,16 +1711,28 @@ impl<T: Config> Pallet<T> {
 			data,
 			salt,
 			debug_message.as_mut(),
+			collect_events,
 		);
+		let (debug_message, debug_message_truncated, storage_changes, io_counters) =
+			debug_message.map(DebugBuffer::into_parts).unwrap_or_default();
 		ContractInstantiateResult {
 			result: output
 				.result
-				.map(|(account_id, result)| InstantiateReturnValue { result, account_id })
+				.map(|(account_id, result)| {
+					let trie_id = <ContractInfoOf<T>>::get(&account_id)
+						.map(|contract| contract.trie_id.into_inner());
+					let deposit_account = T::AddressGenerator::deposit_address(&account_id);
+					InstantiateReturnValue { result, account_id, trie_id, deposit_account }
+				})
 				.map_err(|e| e.error),
 			gas_consumed: output.gas_meter.gas_consumed(),
 			gas_required: output.gas_meter.gas_required(),
 			storage_deposit: output.storage_deposit,
-			debug_message: debug_message.unwrap_or_default().to_vec(),
+			debug_message,
+			debug_message_truncated,
+			storage_changes,
+			io_counters,
+			events: output.events,
 		}
 	}
 
@@ -1101,7 +1746,7 @@ impl<T: Config> Pallet<T> {
 		storage_deposit_limit: Option<BalanceOf<T>>,
 		determinism: Determinism,
 	) -> CodeUploadResult<CodeHash<T>, BalanceOf<T>> {
-		let schedule = T::Schedule::get();
+		let schedule = Self::schedule();
 		let module = PrefabWasmModule::from_code(
 			code,
 			&schedule,
@@ -1111,18 +1756,23 @@ impl<T: Config> Pallet<T> {
 		)
 		.map_err(|(err, _)| err)?;
 		let deposit = module.open_deposit();
-		if let Some(storage_deposit_limit) = storage_deposit_limit {
+		if let Some(storage_deposit_limit) =
+			Self::transaction_deposit_limit(storage_deposit_limit, Zero::zero())?
+		{
 			ensure!(storage_deposit_limit >= deposit, <Error<T>>::StorageDepositLimitExhausted);
 		}
 		let result = CodeUploadReturnValue { code_hash: *module.code_hash(), deposit };
 		module.store()?;
+		Self::consume_transaction_deposit_limit(&StorageDeposit::Charge(deposit));
 		Ok(result)
 	}
 
 	/// Query storage of a specified contract under a specified key.
+	///
+	/// If there is no contract at `address` the error tells whether it was terminated and its
+	/// storage is still being deleted, whether the account isn't a contract or doesn't exist.
 	pub fn get_storage(address: T::AccountId, key: Vec<u8>) -> GetStorageResult {
-		let contract_info =
-			ContractInfoOf::<T>::get(&address).ok_or(ContractAccessError::DoesntExist)?;
+		let contract_info = Self::contract_to_query(&address)?;
 
 		let maybe_value = Storage::<T>::read(
 			&contract_info.trie_id,
@@ -1131,6 +1781,70 @@ impl<T: Config> Pallet<T> {
 		Ok(maybe_value)
 	}
 
+	/// Query up to `count` storage keys of a specified contract that start with `prefix`.
+	///
+	/// The keys are returned hashed, as they are stored in the contract's child trie. Pass the
+	/// last key of a page as `start_key` to get the next one. See [`Storage::keys_paged`].
+	pub fn get_storage_keys_paged(
+		address: T::AccountId,
+		prefix: Vec<u8>,
+		start_key: Option<Vec<u8>>,
+		count: u32,
+	) -> GetStorageKeysResult {
+		let contract_info = Self::contract_to_query(&address)?;
+		Ok(Storage::<T>::keys_paged(&contract_info.trie_id, &prefix, start_key, count))
+	}
+
+	/// Load the contract whose storage is queried.
+	///
+	/// If there is no contract at `address` the error tells whether the contract was terminated
+	/// but its storage is still being deleted, whether the account isn't a contract or whether
+	/// there is no account at all.
+	fn contract_to_query(address: &T::AccountId) -> Result<ContractInfo<T>, ContractAccessError> {
+		if let Some(contract_info) = ContractInfo::<T>::load(address) {
+			return Ok(contract_info)
+		}
+		if Storage::<T>::is_queued_for_deletion(address) {
+			Err(ContractAccessError::PendingDeletion)
+		} else if System::<T>::account_exists(address) {
+			Err(ContractAccessError::NotAContract)
+		} else {
+			Err(ContractAccessError::DoesntExist)
+		}
+	}
+
+	/// Query the storage usage and deposit of the contract at `address`.
+	///
+	/// Returns `None` if there is no contract at `address`.
+	pub fn contract_info(
+		address: &T::AccountId,
+	) -> Option<ContractStorageInfo<CodeHash<T>, BalanceOf<T>>> {
+		ContractInfo::<T>::load(address).map(|info| ContractStorageInfo {
+			code_hash: info.code_hash,
+			storage_bytes: info.storage_bytes,
+			storage_items: info.storage_items,
+			storage_byte_deposit: info.storage_byte_deposit,
+			storage_item_deposit: info.storage_item_deposit,
+			storage_deposit: info.extra_deposit(),
+			total_deposit: info.total_deposit(),
+		})
+	}
+
+	/// Query the execution statistics of a code hash.
+	///
+	/// Returns `None` if no statistics were collected for `code_hash`. See
+	/// [`Config::CollectCodeStats`].
+	pub fn code_stats(code_hash: CodeHash<T>) -> Option<CodeStats> {
+		<CodeStatsOf<T>>::contains_key(code_hash).then(|| <CodeStatsOf<T>>::get(code_hash))
+	}
+
+	/// Query the metadata attached to a code hash.
+	///
+	/// Returns `None` if the code has no metadata. See [`Pallet::set_code_metadata`].
+	pub fn code_metadata(code_hash: CodeHash<T>) -> Option<Vec<u8>> {
+		<CodeMetadataOf<T>>::get(code_hash).map(|(metadata, _)| metadata.into_inner())
+	}
+
 	/// Determine the address of a contract.
 	///
 	/// This is the address generation function used by contract instantiation. See
@@ -1149,50 +1863,183 @@ impl<T: Config> Pallet<T> {
 		Storage::<T>::code_hash(account)
 	}
 
-	/// Store code for benchmarks which does not check nor instrument the code.
+	/// Returns the code stored under `code_hash` as it was uploaded.
+	///
+	/// Compressed code is decompressed. Returns `None` if there is no such code.
+	pub fn pristine_code(code_hash: CodeHash<T>) -> Option<Vec<u8>> {
+		wasm::pristine_code::<T>(code_hash).ok().map(Into::into)
+	}
+
+	/// Describes the types and limits this pallet is configured with.
+	///
+	/// See [`ContractsEnvironment`].
+	pub fn environment() -> ContractsEnvironment<BalanceOf<T>> {
+		let len = |len: usize| len.try_into().unwrap_or(u32::MAX);
+		ContractsEnvironment {
+			account_id_len: len(T::AccountId::max_encoded_len()),
+			balance_len: len(BalanceOf::<T>::max_encoded_len()),
+			hash_len: len(CodeHash::<T>::max_encoded_len()),
+			block_number_len: len(T::BlockNumber::max_encoded_len()),
+			max_code_len: T::MaxCodeLen::get(),
+			existential_deposit: Self::min_balance(),
+		}
+	}
+
+	/// Lists the host functions contracts can import on this chain.
+	///
+	/// Unstable functions are only included if [`Config::UnsafeUnstableInterface`] is set and
+	/// `seal_random` only if [`Config::RandomnessEnabled`] is set.
+	pub fn host_functions() -> Vec<HostFnInfo> {
+		let allow_unstable = T::UnsafeUnstableInterface::get();
+		let allow_random = T::RandomnessEnabled::get();
+		wasm::Env::HOST_FUNCTIONS
+			.iter()
+			.filter(|(_, _, _, stable)| *stable || allow_unstable)
+			.filter(|(_, name, _, _)| allow_random || !matches!(*name, "seal_random" | "random"))
+			.map(|(module, name, version, stable)| HostFnInfo {
+				module: module.as_bytes().to_vec(),
+				name: name.as_bytes().to_vec(),
+				version: *version,
+				stable: *stable,
+			})
+			.collect()
+	}
+
+	/// The cost schedule and limits in effect.
+	///
+	/// This is the schedule set through [`Self::set_schedule`] unless [`Config::Schedule`] has
+	/// the same or a greater `instruction_weights.version`. This way a schedule shipped with a
+	/// runtime upgrade supersedes an older override.
+	pub fn schedule() -> Schedule<T> {
+		let default = T::Schedule::get();
+		match <ScheduleOverride<T>>::get() {
+			Some(schedule)
+				if schedule.instruction_weights.version > default.instruction_weights.version =>
+				schedule,
+			_ => default,
+		}
+	}
+
+	/// Check the limits of a `schedule` that is about to be put into effect.
+	///
+	/// These are the same checks `integrity_test` applies to [`Config::Schedule`].
+	fn ensure_schedule_is_valid(schedule: &Schedule<T>) -> DispatchResult {
+		ensure!(
+			T::MaxCodeLen::get() < Self::code_len_limit(schedule),
+			<Error<T>>::ScheduleLimitsTooHigh,
+		);
+		// Every function the schedule allows must fit onto the stack.
+		let limits = &schedule.limits;
+		ensure!(
+			limits.stack_height >= limits.locals.saturating_add(limits.parameters),
+			<Error<T>>::InconsistentScheduleLimits,
+		);
+		ensure!(
+			Self::forbidden_imports_are_valid(&schedule.validation.forbidden_imports),
+			<Error<T>>::InvalidScheduleValidationRules,
+		);
+		Ok(())
+	}
+
+	/// Whether every forbidden import names a host function and appears only once.
+	///
+	/// Any other name could never match an import and is most likely a typo.
+	fn forbidden_imports_are_valid(forbidden: &[Vec<u8>]) -> bool {
+		forbidden.iter().enumerate().all(|(i, name)| {
+			!forbidden[..i].contains(name) &&
+				wasm::Env::HOST_FUNCTIONS
+					.iter()
+					.any(|(_, host_fn, _, _)| host_fn.as_bytes() == name.as_slice())
+		})
+	}
+
+	/// The upper limit for [`Config::MaxCodeLen`] that keeps the runtime from running out of
+	/// memory when executing contracts with the limits of `schedule`.
+	fn code_len_limit(schedule: &Schedule<T>) -> u32 {
+		// Total runtime memory is expected to have 128Mb upper limit
+		const MAX_RUNTIME_MEM: u32 = 1024 * 1024 * 128;
+		// Memory limits for a single contract:
+		// Value stack size: 1Mb per contract with the default `stack_height`
+		let max_stack_size = schedule.limits.max_stack_size();
+		// Heap limit is normally 16 mempages of 64kb each = 1Mb per contract
+		let max_heap_size = schedule.limits.max_memory_size();
+		// Max call depth is CallStack::size() + 1
+		let max_call_depth = u32::try_from(T::CallStack::size().saturating_add(1))
+			.expect("CallStack size is too big");
+
+		// In worst case, the decoded wasm contract code would be `x16` times larger than the
+		// encoded one. This is because even a single-byte wasm instruction has 16-byte size in
+		// wasmi. This gives us `MaxCodeLen*16` safety margin.
+		//
+		// Next, the pallet keeps the original wasm blob for each contract, hence we add up
+		// `MaxCodeLen` more to the safety margin.
+		//
+		// Finally, the inefficiencies of the freeing-bump allocator
+		// being used in the client for the runtime memory allocations, could lead to possible
+		// memory allocations for contract code grow up to `x4` times in some extreme cases,
+		// which gives us total multiplier of `17*4` for `MaxCodeLen`.
+		//
+		// That being said, for every contract executed in runtime, at least `MaxCodeLen*17*4`
+		// memory should be available. Note that maximum allowed heap memory and stack size per
+		// each contract (stack frame) should also be counted.
+		//
+		// Finally, we allow 50% of the runtime memory to be utilized by the contracts call
+		// stack, keeping the rest for other facilities, such as PoV, etc.
+		//
+		// This gives us the following formula:
+		//
+		// `(MaxCodeLen * 17 * 4 + max_stack_size + max_heap_size) * max_call_depth <
+		// MAX_RUNTIME_MEM/2`
+		//
+		// Hence the upper limit for the `MaxCodeLen` can be defined as follows:
+		MAX_RUNTIME_MEM
+			.saturating_div(2)
+			.saturating_div(max_call_depth)
+			.saturating_sub(max_heap_size)
+			.saturating_sub(max_stack_size)
+			.saturating_div(17 * 4)
+	}
+
+	/// Store code for benchmarks which does not check the code.
 	#[cfg(feature = "runtime-benchmarks")]
 	fn store_code_raw(
 		code: Vec<u8>,
 		owner: T::AccountId,
 	) -> frame_support::dispatch::DispatchResult {
-		let schedule = T::Schedule::get();
+		let schedule = Self::schedule();
 		PrefabWasmModule::store_code_unchecked(code, &schedule, owner)?;
 		Ok(())
 	}
 
-	/// This exists so that benchmarks can determine the weight of running an instrumentation.
-	#[cfg(feature = "runtime-benchmarks")]
-	fn reinstrument_module(
-		module: &mut PrefabWasmModule<T>,
-		schedule: &Schedule<T>,
-	) -> frame_support::dispatch::DispatchResult {
-		self::wasm::reinstrument(module, schedule).map(|_| ())
-	}
-
 	/// Internal function that does the actual call.
 	///
 	/// Called by dispatchables and public functions.
 	fn internal_call(
-		origin: T::AccountId,
+		origin: CallOrigin<T>,
 		dest: T::AccountId,
 		value: BalanceOf<T>,
 		gas_limit: Weight,
 		storage_deposit_limit: Option<BalanceOf<T>>,
 		data: Vec<u8>,
-		debug_message: Option<&mut DebugBufferVec<T>>,
+		debug_message: Option<&mut DebugBuffer<T>>,
+		collect_events: CollectEvents,
 		determinism: Determinism,
 	) -> InternalCallOutput<T> {
 		let mut gas_meter = GasMeter::new(gas_limit);
-		let mut storage_meter = match StorageMeter::new(&origin, storage_deposit_limit, value) {
+		let storage_meter = Self::transaction_deposit_limit(storage_deposit_limit, Zero::zero())
+			.and_then(|limit| StorageMeter::new(&origin, limit, value));
+		let mut storage_meter = match storage_meter {
 			Ok(meter) => meter,
 			Err(err) =>
 				return InternalCallOutput {
 					result: Err(err.into()),
 					gas_meter,
 					storage_deposit: Default::default(),
+					events: None,
 				},
 		};
-		let schedule = T::Schedule::get();
+		let events_before = Self::events_before(collect_events);
+		let schedule = Self::schedule();
 		let result = ExecStack::<T, PrefabWasmModule<T>>::run_call(
 			origin.clone(),
 			dest,
@@ -1204,13 +2051,72 @@ impl<T: Config> Pallet<T> {
 			debug_message,
 			determinism,
 		);
+		let storage_deposit = storage_meter.into_deposit(&origin);
+		Self::consume_transaction_deposit_limit(&storage_deposit);
 		InternalCallOutput {
 			result,
 			gas_meter,
-			storage_deposit: storage_meter.into_deposit(&origin),
+			storage_deposit,
+			events: Self::events_since(events_before),
 		}
 	}
 
+	/// Internal function that executes the calls of [`Self::call_batch`].
+	///
+	/// All calls share a single gas meter and storage meter. Execution stops at the first call
+	/// that fails or reverts.
+	fn internal_call_batch(
+		origin: CallOrigin<T>,
+		calls: Vec<BatchedCall<T::AccountId, BalanceOf<T>>>,
+		gas_limit: Weight,
+		storage_deposit_limit: Option<BalanceOf<T>>,
+	) -> InternalOutput<T, ()> {
+		let mut gas_meter = GasMeter::new(gas_limit);
+		let value = calls
+			.iter()
+			.fold(BalanceOf::<T>::zero(), |total, call| total.saturating_add(call.value));
+		let storage_meter = Self::transaction_deposit_limit(storage_deposit_limit, Zero::zero())
+			.and_then(|limit| StorageMeter::new(&origin, limit, value));
+		let mut storage_meter = match storage_meter {
+			Ok(meter) => meter,
+			Err(err) =>
+				return InternalOutput {
+					result: Err(err.into()),
+					gas_meter,
+					storage_deposit: Default::default(),
+					events: None,
+				},
+		};
+		let schedule = Self::schedule();
+		let mut result = Ok(());
+		for call in calls {
+			match ExecStack::<T, PrefabWasmModule<T>>::run_call(
+				origin.clone(),
+				call.dest,
+				&mut gas_meter,
+				&mut storage_meter,
+				&schedule,
+				call.value,
+				call.data,
+				None,
+				Determinism::Deterministic,
+			) {
+				Ok(retval) if !retval.did_revert() => (),
+				Ok(_) => {
+					result = Err(<Error<T>>::ContractReverted.into());
+					break
+				},
+				Err(err) => {
+					result = Err(err);
+					break
+				},
+			}
+		}
+		let storage_deposit = storage_meter.into_deposit(&origin);
+		Self::consume_transaction_deposit_limit(&storage_deposit);
+		InternalOutput { result, gas_meter, storage_deposit, events: None }
+	}
+
 	/// Internal function that does the actual instantiation.
 	///
 	/// Called by dispatchables and public functions.
@@ -1222,12 +2128,14 @@ impl<T: Config> Pallet<T> {
 		code: Code<CodeHash<T>>,
 		data: Vec<u8>,
 		salt: Vec<u8>,
-		mut debug_message: Option<&mut DebugBufferVec<T>>,
+		mut debug_message: Option<&mut DebugBuffer<T>>,
+		collect_events: CollectEvents,
 	) -> InternalInstantiateOutput<T> {
 		let mut storage_deposit = Default::default();
 		let mut gas_meter = GasMeter::new(gas_limit);
+		let events_before = Self::events_before(collect_events);
 		let try_exec = || {
-			let schedule = T::Schedule::get();
+			let schedule = Self::schedule();
 			let (extra_deposit, executable) = match code {
 				Code::Upload(binary) => {
 					let executable = PrefabWasmModule::from_code(
@@ -1238,7 +2146,9 @@ impl<T: Config> Pallet<T> {
 						TryInstantiate::Skip,
 					)
 					.map_err(|(err, msg)| {
-						debug_message.as_mut().map(|buffer| buffer.try_extend(&mut msg.bytes()));
+						if let Some(buffer) = debug_message.as_mut() {
+							buffer.append(msg, || "\n<debug buffer full: truncated>".into());
+						}
 						err
 					})?;
 					// The open deposit will be charged during execution when the
@@ -1252,9 +2162,10 @@ impl<T: Config> Pallet<T> {
 					PrefabWasmModule::from_storage(hash, &schedule, &mut gas_meter)?,
 				),
 			};
+			let payer = CallOrigin::from_account_id(origin.clone());
 			let mut storage_meter = StorageMeter::new(
-				&origin,
-				storage_deposit_limit,
+				&payer,
+				Self::transaction_deposit_limit(storage_deposit_limit, extra_deposit)?,
 				value.saturating_add(extra_deposit),
 			)?;
 			let result = ExecStack::<T, PrefabWasmModule<T>>::run_instantiate(
@@ -1269,11 +2180,67 @@ impl<T: Config> Pallet<T> {
 				debug_message,
 			);
 			storage_deposit = storage_meter
-				.into_deposit(&origin)
+				.into_deposit(&payer)
 				.saturating_add(&StorageDeposit::Charge(extra_deposit));
 			result
 		};
-		InternalInstantiateOutput { result: try_exec(), gas_meter, storage_deposit }
+		let result = try_exec();
+		Self::consume_transaction_deposit_limit(&storage_deposit);
+		InternalInstantiateOutput {
+			result,
+			gas_meter,
+			storage_deposit,
+			events: Self::events_since(events_before),
+		}
+	}
+
+	/// Lower `limit` to the storage deposit that the current transaction may still charge.
+	///
+	/// `extra_deposit` is charged outside of the storage meter and hence deducted from what is
+	/// left first. See [`CheckStorageDepositLimit`].
+	fn transaction_deposit_limit(
+		limit: Option<BalanceOf<T>>,
+		extra_deposit: BalanceOf<T>,
+	) -> Result<Option<BalanceOf<T>>, DispatchError> {
+		let left = match <TransactionDepositLimit<T>>::get() {
+			Some(left) => left,
+			None => return Ok(limit),
+		};
+		let left = left
+			.checked_sub(&extra_deposit)
+			.ok_or(<Error<T>>::StorageDepositLimitExhausted)?;
+		Ok(Some(limit.map_or(left, |limit| limit.min(left))))
+	}
+
+	/// Apply a `deposit` to what the current transaction may still charge.
+	///
+	/// Charges are deducted and refunds are added back so that the limit bounds the net amount
+	/// charged.
+	fn consume_transaction_deposit_limit(deposit: &StorageDeposit<BalanceOf<T>>) {
+		if let Some(left) = <TransactionDepositLimit<T>>::get() {
+			let left = match deposit {
+				StorageDeposit::Charge(amount) => left.saturating_sub(*amount),
+				StorageDeposit::Refund(amount) => left.saturating_add(*amount),
+			};
+			<TransactionDepositLimit<T>>::put(left);
+		}
+	}
+
+	/// Returns the number of events deposited so far if `collect_events` requests collection.
+	fn events_before(collect_events: CollectEvents) -> Option<u32> {
+		(collect_events == CollectEvents::UnsafeCollect).then(<System<T>>::event_count)
+	}
+
+	/// Returns all events that were deposited after `events_before` were counted.
+	///
+	/// Reading the events is expensive. See [`CollectEvents::UnsafeCollect`].
+	fn events_since(events_before: Option<u32>) -> Option<Vec<EventRecordOf<T>>> {
+		events_before.map(|skip| {
+			<System<T>>::read_events_no_consensus()
+				.skip(skip as usize)
+				.map(|e| *e)
+				.collect()
+		})
 	}
 
 	/// Deposit a pallet contracts event. Handles the conversion to the overarching event type.
@@ -1300,15 +2267,19 @@ impl<T: Config> Pallet<T> {
 
 sp_api::decl_runtime_apis! {
 	/// The API used to dry-run contract interactions.
-	#[api_version(2)]
-	pub trait ContractsApi<AccountId, Balance, BlockNumber, Hash> where
+	#[api_version(16)]
+	pub trait ContractsApi<AccountId, Balance, BlockNumber, Hash, EventRecord> where
 		AccountId: Codec,
 		Balance: Codec,
 		BlockNumber: Codec,
 		Hash: Codec,
+		EventRecord: Codec,
 	{
 		/// Perform a call from a specified account to a given contract.
 		///
+		/// If `gas_limit` is `None` the call is executed with the maximum block weight. The
+		/// returned `gas_required` is then the estimate to use when submitting the call.
+		///
 		/// See [`crate::Pallet::bare_call`].
 		fn call(
 			origin: AccountId,
@@ -1317,10 +2288,12 @@ sp_api::decl_runtime_apis! {
 			gas_limit: Option<Weight>,
 			storage_deposit_limit: Option<Balance>,
 			input_data: Vec<u8>,
-		) -> ContractExecResult<Balance>;
+		) -> ContractExecResult<AccountId, Balance, EventRecord>;
 
 		/// Instantiate a new contract.
 		///
+		/// If `gas_limit` is `None` the instantiation is executed with the maximum block weight.
+		///
 		/// See `[crate::Pallet::bare_instantiate]`.
 		fn instantiate(
 			origin: AccountId,
@@ -1330,7 +2303,7 @@ sp_api::decl_runtime_apis! {
 			code: Code<Hash>,
 			data: Vec<u8>,
 			salt: Vec<u8>,
-		) -> ContractInstantiateResult<AccountId, Balance>;
+		) -> ContractInstantiateResult<AccountId, Balance, EventRecord>;
 
 
 		/// Upload new code without instantiating a contract from it.
@@ -1346,11 +2319,64 @@ sp_api::decl_runtime_apis! {
 		/// Query a given storage key in a given contract.
 		///
 		/// Returns `Ok(Some(Vec<u8>))` if the storage value exists under the given key in the
-		/// specified account and `Ok(None)` if it doesn't. If the account specified by the address
-		/// doesn't exist, or doesn't have a contract then `Err` is returned.
+		/// specified account and `Ok(None)` if it doesn't. If there is no contract at the address
+		/// then `Err` is returned. It tells whether the contract was terminated and its storage is
+		/// still being deleted, whether the account isn't a contract or whether it doesn't exist.
 		fn get_storage(
 			address: AccountId,
 			key: Vec<u8>,
 		) -> GetStorageResult;
+
+		/// Query up to `count` keys of the storage of a given contract that start with `prefix`.
+		///
+		/// The keys are returned in lexicographic order and hashed, as they are stored in the
+		/// contract's child trie. Iteration begins after `start_key` if it is given which allows
+		/// to page through the storage of a contract. If there is no contract at the address then
+		/// `Err` is returned just like for [`Self::get_storage`].
+		///
+		/// See [`crate::Pallet::get_storage_keys_paged`].
+		fn get_storage_keys_paged(
+			address: AccountId,
+			prefix: Vec<u8>,
+			start_key: Option<Vec<u8>>,
+			count: u32,
+		) -> GetStorageKeysResult;
+
+		/// Query the execution statistics of a code hash.
+		///
+		/// See [`crate::Pallet::code_stats`].
+		fn code_stats(code_hash: Hash) -> Option<CodeStats>;
+
+		/// Query the metadata attached to a code hash.
+		///
+		/// See [`crate::Pallet::code_metadata`].
+		fn code_metadata(code_hash: Hash) -> Option<Vec<u8>>;
+
+		/// Query the code hash of the contract at `account`.
+		///
+		/// Returns `None` if there is no contract at `account`. See [`crate::Pallet::code_hash`].
+		fn code_hash(account: AccountId) -> Option<Hash>;
+
+		/// Query the code stored under `code_hash` as it was uploaded.
+		///
+		/// This allows to compare the code of a contract against a locally compiled artifact.
+		/// Returns `None` if there is no such code. See [`crate::Pallet::pristine_code`].
+		fn pristine_code(code_hash: Hash) -> Option<Vec<u8>>;
+
+		/// Describe the types and limits the pallet is configured with.
+		///
+		/// See [`crate::Pallet::environment`].
+		fn environment() -> ContractsEnvironment<Balance>;
+
+		/// List the host functions contracts can import.
+		///
+		/// See [`crate::Pallet::host_functions`].
+		fn host_functions() -> Vec<HostFnInfo>;
+
+		/// Query how much storage the contract at `address` occupies and the deposit it holds.
+		///
+		/// Returns `None` if there is no contract at `address`. See
+		/// [`crate::Pallet::contract_info`].
+		fn contract_info(address: AccountId) -> Option<ContractStorageInfo<Hash, Balance>>;
 	}
 }
@@ -15,18 +15,135 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{BalanceOf, CodeHash, Config, Pallet, TrieId, Weight};
-use codec::{Decode, Encode};
+//! Storage migrations of this pallet.
+//!
+//! Old migrations are applied at once by [`Migration`] when it is executed as part of a runtime
+//! upgrade. Migrations that need to touch every contract are instead implemented as a
+//! [`MigrationStep`] which is executed over multiple blocks. See [`crate::Pallet::migrate`].
+
+use crate::{BalanceOf, CodeHash, Config, Error, MigrationInProgress, Pallet, TrieId, Weight};
+use codec::{Codec, Decode, DecodeAll, Encode, HasCompact};
 use frame_support::{
 	codec,
+	dispatch::DispatchResult,
 	pallet_prelude::*,
 	storage::migration,
 	storage_alias,
-	traits::{Get, OnRuntimeUpgrade},
+	traits::{Get, IsSubType, OnRuntimeUpgrade},
 	Identity, Twox64Concat,
 };
-use sp_runtime::traits::Saturating;
-use sp_std::{marker::PhantomData, prelude::*};
+use sp_runtime::traits::{DispatchInfoOf, Saturating, SignedExtension, Zero};
+use sp_std::{fmt, marker::PhantomData, prelude::*};
+
+/// The encoded state of the [`MigrationStep`] that is currently in progress.
+pub type Cursor = BoundedVec<u8, ConstU32<1024>>;
+
+const CURSOR_ERR: &str = "The encoded `MigrationStep` must fit into a `Cursor`; qed";
+
+/// Whether a [`MigrationStep`] is done after a call to [`MigrationStep::step`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum IsFinished {
+	/// The migration is done.
+	Yes,
+	/// More steps are required.
+	No,
+}
+
+/// A storage migration that is executed in multiple steps spread over several blocks.
+///
+/// Migrations that need to touch every contract can't be executed in a single block. Instead,
+/// they are implemented as this trait and listed in [`Config::Migrations`]. The state of a
+/// migration in progress is kept as its encoding in [`MigrationInProgress`].
+pub trait MigrationStep: Codec + MaxEncodedLen + Default {
+	/// The storage version this migration migrates to.
+	const VERSION: u16;
+
+	/// The maximum weight a single call to [`Self::step`] can consume.
+	fn max_step_weight() -> Weight;
+
+	/// Process one step of the migration.
+	///
+	/// Returns whether the migration is finished and the weight consumed by this step.
+	fn step(&mut self) -> (IsFinished, Weight);
+}
+
+/// The result of [`MigrateSequence::steps`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum StepResult {
+	/// The migration is not finished yet and needs to continue from `cursor`.
+	InProgress { cursor: Cursor, steps_done: u32 },
+	/// The migration is finished.
+	Completed { steps_done: u32 },
+}
+
+/// A sequence of [`MigrationStep`]s ordered by their version.
+///
+/// This is implemented for tuples of [`MigrationStep`]s which must list their migrations in
+/// ascending order of their [`MigrationStep::VERSION`] without gaps.
+pub trait MigrateSequence {
+	/// The cursor of the first migration newer than `version` or `None` if there is none.
+	fn new(version: StorageVersion) -> Option<Cursor>;
+
+	/// Run as many steps of the migration to `version + 1` as fit into `weight_left`.
+	///
+	/// `cursor` is the state of the migration as returned by [`Self::new`] or a previous call.
+	fn steps(version: StorageVersion, cursor: &[u8], weight_left: &mut Weight) -> StepResult;
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(10)]
+#[tuple_types_custom_trait_bound(MigrationStep)]
+impl MigrateSequence for Tuple {
+	fn new(version: StorageVersion) -> Option<Cursor> {
+		for_tuples!(
+			#(
+				if version < Tuple::VERSION {
+					return Some(Tuple::default().encode().try_into().expect(CURSOR_ERR))
+				}
+			)*
+		);
+		None
+	}
+
+	fn steps(version: StorageVersion, cursor: &[u8], weight_left: &mut Weight) -> StepResult {
+		let next_version = u16::from(version) + 1;
+		for_tuples!(
+			#(
+				if next_version == Tuple::VERSION {
+					let mut migration = <Tuple as Decode>::decode(&mut &cursor[..])
+						.expect("The cursor was encoded from this migration; qed");
+					let max_weight = Tuple::max_step_weight();
+					let mut steps_done = 0;
+					while weight_left.all_gte(max_weight) {
+						let (finished, weight) = migration.step();
+						steps_done.saturating_inc();
+						*weight_left = weight_left.saturating_sub(weight);
+						if finished == IsFinished::Yes {
+							return StepResult::Completed { steps_done }
+						}
+					}
+					return StepResult::InProgress {
+						cursor: migration.encode().try_into().expect(CURSOR_ERR),
+						steps_done,
+					}
+				}
+			)*
+		);
+		panic!("A migration is in progress but no migration to {:?} exists", next_version)
+	}
+}
+
+/// The result of [`Migration::migrate`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum MigrateResult {
+	/// There is no migration in progress.
+	NoMigrationInProgress,
+	/// A migration is in progress but the weight limit did not allow for a single step.
+	NoMigrationPerformed,
+	/// Some steps were executed but the migration is still in progress.
+	InProgress { steps_done: u32 },
+	/// All migrations are done.
+	Completed,
+}
 
 /// Performs all necessary migrations based on `StorageVersion`.
 pub struct Migration<T: Config>(PhantomData<T>);
@@ -59,8 +176,28 @@ impl<T: Config> OnRuntimeUpgrade for Migration<T> {
 			v9::migrate::<T>(&mut weight);
 		}
 
-		StorageVersion::new(9).put::<Pallet<T>>();
-		weight.saturating_accrue(T::DbWeight::get().writes(1));
+		if version < 10 {
+			v10::migrate::<T>(&mut weight);
+		}
+
+		// Starting with `v11` every migration is a `MigrationStep` listed in `Config::Migrations`.
+		let version = if version < 10 {
+			StorageVersion::new(10).put::<Pallet<T>>();
+			weight.saturating_accrue(T::DbWeight::get().writes(1));
+			StorageVersion::new(10)
+		} else {
+			version
+		};
+
+		// Newer migrations are executed in multiple steps through `Migration::migrate`.
+		weight.saturating_accrue(T::DbWeight::get().reads(1));
+		if !<MigrationInProgress<T>>::exists() {
+			if let Some(cursor) = T::Migrations::new(version) {
+				log::info!(target: "runtime::contracts", "Starting multi block migration");
+				<MigrationInProgress<T>>::put(cursor);
+				weight.saturating_accrue(T::DbWeight::get().writes(1));
+			}
+		}
 
 		weight
 	}
@@ -83,6 +220,122 @@ impl<T: Config> OnRuntimeUpgrade for Migration<T> {
 	}
 }
 
+impl<T: Config> Migration<T> {
+	/// Execute as many steps of the ongoing multi block migration as fit into `weight_limit`.
+	///
+	/// Once a [`MigrationStep`] is finished the storage version is bumped and the next migration
+	/// of [`Config::Migrations`] is started. Returns the consumed weight.
+	pub fn migrate(weight_limit: Weight) -> (MigrateResult, Weight) {
+		let mut weight_left = weight_limit;
+		let db_weight = T::DbWeight::get();
+
+		weight_left = weight_left.saturating_sub(db_weight.reads(1));
+		let cursor = match <MigrationInProgress<T>>::get() {
+			Some(cursor) => cursor,
+			None => return (MigrateResult::NoMigrationInProgress, db_weight.reads(1)),
+		};
+
+		// Reading the version and writing back the cursor and version.
+		weight_left = weight_left.saturating_sub(db_weight.reads_writes(1, 2));
+		let version = <Pallet<T>>::on_chain_storage_version();
+		let result = match T::Migrations::steps(version, &cursor, &mut weight_left) {
+			StepResult::InProgress { steps_done: 0, .. } => MigrateResult::NoMigrationPerformed,
+			StepResult::InProgress { cursor, steps_done } => {
+				<MigrationInProgress<T>>::put(cursor);
+				MigrateResult::InProgress { steps_done }
+			},
+			StepResult::Completed { steps_done } => {
+				let version = StorageVersion::new(u16::from(version) + 1);
+				version.put::<Pallet<T>>();
+				log::info!(
+					target: "runtime::contracts",
+					"Migration to {:?} completed in {} steps",
+					version, steps_done,
+				);
+				match T::Migrations::new(version) {
+					Some(cursor) => {
+						<MigrationInProgress<T>>::put(cursor);
+						MigrateResult::InProgress { steps_done }
+					},
+					None => {
+						<MigrationInProgress<T>>::kill();
+						MigrateResult::Completed
+					},
+				}
+			},
+		};
+
+		(result, weight_limit.saturating_sub(weight_left))
+	}
+
+	/// Returns an error if a multi block migration is in progress.
+	pub(crate) fn ensure_migrated() -> DispatchResult {
+		ensure!(!<MigrationInProgress<T>>::exists(), Error::<T>::MigrationInProgress);
+		Ok(())
+	}
+}
+
+/// Rejects all transactions calling into this pallet while a migration is in progress.
+///
+/// The dispatchables of this pallet already fail in this case. This extension moves the check
+/// into the transaction pool so that these transactions are not included in the first place.
+/// Only [`Pallet::migrate`] is still allowed.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct CheckMigrationDone<T: Config + Send + Sync>(PhantomData<T>);
+
+impl<T: Config + Send + Sync> Default for CheckMigrationDone<T> {
+	fn default() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<T: Config + Send + Sync> fmt::Debug for CheckMigrationDone<T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "CheckMigrationDone")
+	}
+}
+
+impl<T: Config + Send + Sync> SignedExtension for CheckMigrationDone<T>
+where
+	<T as frame_system::Config>::RuntimeCall: IsSubType<crate::Call<T>>,
+	<BalanceOf<T> as HasCompact>::Type: Clone + Eq + PartialEq + fmt::Debug + TypeInfo + Encode,
+{
+	const IDENTIFIER: &'static str = "CheckContractsMigrationDone";
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::RuntimeCall;
+	type AdditionalSigned = ();
+	type Pre = ();
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		_who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		match call.is_sub_type() {
+			Some(crate::Call::migrate { .. }) | None => Ok(Default::default()),
+			Some(_) if <MigrationInProgress<T>>::exists() => Err(InvalidTransaction::Call.into()),
+			Some(_) => Ok(Default::default()),
+		}
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		self.validate(who, call, info, len).map(|_| ())
+	}
+}
+
 /// V4: `Schedule` is changed to be a config item rather than an in-storage value.
 mod v4 {
 	use super::*;
@@ -220,11 +473,11 @@ mod v6 {
 
 	#[derive(Encode, Decode)]
 	pub struct OwnerInfo<T: Config> {
-		owner: T::AccountId,
+		pub owner: T::AccountId,
 		#[codec(compact)]
-		deposit: BalanceOf<T>,
+		pub deposit: BalanceOf<T>,
 		#[codec(compact)]
-		refcount: u64,
+		pub refcount: u64,
 	}
 
 	pub type ContractInfo<T> = RawContractInfo<CodeHash<T>, BalanceOf<T>>;
@@ -400,6 +653,632 @@ mod v9 {
 	}
 }
 
+/// Only keep the pristine code: `CodeStorage` is removed and `OwnerInfoOf` becomes `CodeInfoOf`.
+mod v10 {
+	use super::*;
+	use crate::{storage::meter::Diff, Determinism};
+	use codec::MaxEncodedLen;
+	use frame_support::traits::ReservableCurrency;
+	use v6::OwnerInfo;
+	use v9::PrefabWasmModule;
+
+	#[derive(Encode, Decode, MaxEncodedLen)]
+	#[codec(mel_bound())]
+	pub struct CodeInfo<T: Config> {
+		pub owner: T::AccountId,
+		#[codec(compact)]
+		pub deposit: BalanceOf<T>,
+		#[codec(compact)]
+		pub refcount: u64,
+		pub determinism: Determinism,
+		pub code_len: u32,
+	}
+
+	#[storage_alias]
+	type CodeStorage<T: Config> = StorageMap<Pallet<T>, Identity, CodeHash<T>, PrefabWasmModule>;
+
+	#[storage_alias]
+	type OwnerInfoOf<T: Config> = StorageMap<Pallet<T>, Identity, CodeHash<T>, OwnerInfo<T>>;
+
+	#[storage_alias]
+	type PristineCode<T: Config> = StorageMap<Pallet<T>, Identity, CodeHash<T>, Vec<u8>>;
+
+	#[storage_alias]
+	type CodeInfoOf<T: Config> = StorageMap<Pallet<T>, Identity, CodeHash<T>, CodeInfo<T>>;
+
+	pub fn migrate<T: Config>(weight: &mut Weight) {
+		for (code_hash, old) in <OwnerInfoOf<T>>::drain() {
+			weight.saturating_accrue(T::DbWeight::get().reads_writes(3, 3));
+			let determinism = <CodeStorage<T>>::take(&code_hash)
+				.map(|module| module.determinism)
+				.unwrap_or(Determinism::Deterministic);
+			let code_len = <PristineCode<T>>::decode_len(&code_hash).unwrap_or_default() as u32;
+			// The deposit no longer covers an instrumented copy of the code. Refund the excess.
+			let bytes_added = code_len.saturating_add(<CodeInfo<T>>::max_encoded_len() as u32);
+			let deposit = Diff { bytes_added, items_added: 2, ..Default::default() }
+				.update_contract::<T>(None)
+				.charge_or_zero()
+				.min(old.deposit);
+			T::Currency::unreserve(&old.owner, old.deposit.saturating_sub(deposit));
+			<CodeInfoOf<T>>::insert(
+				&code_hash,
+				CodeInfo {
+					owner: old.owner,
+					deposit,
+					refcount: old.refcount,
+					determinism,
+					code_len,
+				},
+			);
+		}
+
+		// Instrumented code without an owner can't be reached anymore.
+		#[allow(deprecated)]
+		migration::remove_storage_prefix(<Pallet<T>>::name().as_bytes(), b"CodeStorage", b"");
+		weight.saturating_accrue(T::DbWeight::get().writes(1));
+	}
+}
+
+/// Migrate the contract following `last_account` from the `Old` to the `New` layout.
+///
+/// The value is decoded exactly as `Old`. A contract in any other layout is logged and left
+/// untouched. Returns whether all contracts were visited.
+fn migrate_next_contract<T: Config, Old: DecodeAll, New: Encode>(
+	last_account: &mut Option<T::AccountId>,
+	migrate: impl FnOnce(&T::AccountId, Old) -> New,
+) -> IsFinished {
+	use crate::ContractInfoOf;
+	use frame_support::storage::unhashed;
+
+	let mut iter = match last_account.take() {
+		Some(last) =>
+			<ContractInfoOf<T>>::iter_keys_from(<ContractInfoOf<T>>::hashed_key_for(last)),
+		None => <ContractInfoOf<T>>::iter_keys(),
+	};
+	let account = match iter.next() {
+		Some(account) => account,
+		None => return IsFinished::Yes,
+	};
+
+	let key = <ContractInfoOf<T>>::hashed_key_for(&account);
+	match unhashed::get_raw(&key).map(|raw| Old::decode_all(&mut &raw[..])) {
+		Some(Ok(old)) => unhashed::put(&key, &migrate(&account, old)),
+		Some(Err(err)) => log::error!(
+			target: "runtime::contracts",
+			"Contract info of {:?} is not in the expected layout: {:?}",
+			account, err,
+		),
+		None => (),
+	}
+	*last_account = Some(account);
+	IsFinished::No
+}
+
+/// V11: Storage deposits are held by a separate deposit account per contract instead of being
+/// reserved on the contract's account.
+///
+/// One contract is migrated per step.
+pub mod v11 {
+	use super::*;
+	use crate::{storage::DepositAccount, weights::WeightInfo, Inspect};
+	use frame_support::{
+		traits::{Currency, ExistenceRequirement, ReservableCurrency},
+		DefaultNoBound,
+	};
+	use v8::ContractInfo as OldContractInfo;
+
+	#[derive(Encode, Decode)]
+	pub struct ContractInfo<T: Config> {
+		pub trie_id: TrieId,
+		pub deposit_account: DepositAccount<T>,
+		pub code_hash: CodeHash<T>,
+		pub storage_bytes: u32,
+		pub storage_items: u32,
+		pub storage_byte_deposit: BalanceOf<T>,
+		pub storage_item_deposit: BalanceOf<T>,
+		pub storage_base_deposit: BalanceOf<T>,
+	}
+
+	/// Move the deposit of `account` from its reserved balance to its deposit account.
+	fn migrate_contract<T: Config>(
+		account: &T::AccountId,
+		old: OldContractInfo<T>,
+	) -> ContractInfo<T> {
+		let deposit_account = DepositAccount::<T>::new(account);
+		let total = old
+			.storage_base_deposit
+			.saturating_add(old.storage_byte_deposit)
+			.saturating_add(old.storage_item_deposit);
+
+		// Slashing might have reduced the reserved balance below what we track.
+		let unreserved = total.saturating_sub(T::Currency::unreserve(account, total));
+
+		// The reserved base deposit kept the contract's account alive. From now on its own free
+		// balance needs to do that. Whatever is missing is taken from the base deposit.
+		let mut moved = unreserved.min(T::Currency::reducible_balance(account, true));
+		if let Err(err) =
+			T::Currency::transfer(account, &deposit_account, moved, ExistenceRequirement::KeepAlive)
+		{
+			// This happens if the deposit is too small to create the deposit account. The funds
+			// stay reserved so that the contract can't spend its deposit.
+			log::error!(
+				target: "runtime::contracts",
+				"Failed to move storage deposit {:?} of {:?} to {:?}: {:?}",
+				moved, account, deposit_account, err,
+			);
+			let _ = T::Currency::reserve(account, moved);
+			moved = Zero::zero();
+		}
+
+		ContractInfo {
+			trie_id: old.trie_id,
+			deposit_account,
+			code_hash: old.code_hash,
+			storage_bytes: old.storage_bytes,
+			storage_items: old.storage_items,
+			storage_byte_deposit: old.storage_byte_deposit,
+			storage_item_deposit: old.storage_item_deposit,
+			storage_base_deposit: old
+				.storage_base_deposit
+				.saturating_sub(total.saturating_sub(moved)),
+		}
+	}
+
+	/// Store `info` of `account` in the layout before this migration and reserve its deposit
+	/// on `account` again.
+	#[cfg(any(test, feature = "runtime-benchmarks"))]
+	pub fn store_old_contract_info<T: Config>(
+		account: &T::AccountId,
+		info: &crate::storage::ContractInfo<T>,
+	) {
+		let total = info.total_deposit();
+		T::Currency::transfer(
+			&info.deposit_account,
+			account,
+			total,
+			ExistenceRequirement::AllowDeath,
+		)
+		.expect("The deposit account holds the deposit; qed");
+		T::Currency::reserve(account, total).expect("The deposit was just transferred; qed");
+		let old = OldContractInfo::<T> {
+			trie_id: info.trie_id.clone(),
+			code_hash: info.code_hash,
+			storage_bytes: info.storage_bytes,
+			storage_items: info.storage_items,
+			storage_byte_deposit: info.storage_byte_deposit,
+			storage_item_deposit: info.storage_item_deposit,
+			storage_base_deposit: info.storage_base_deposit,
+		};
+		frame_support::storage::unhashed::put(
+			&<crate::ContractInfoOf<T>>::hashed_key_for(account),
+			&old,
+		);
+	}
+
+	/// Moves the deposit of every contract to its deposit account.
+	#[derive(Encode, Decode, MaxEncodedLen, DefaultNoBound)]
+	#[codec(mel_bound())]
+	pub struct Migration<T: Config> {
+		/// The last contract that was migrated.
+		last_account: Option<T::AccountId>,
+	}
+
+	impl<T: Config> MigrationStep for Migration<T> {
+		const VERSION: u16 = 11;
+
+		fn max_step_weight() -> Weight {
+			T::WeightInfo::v11_migration_step()
+		}
+
+		fn step(&mut self) -> (IsFinished, Weight) {
+			match migrate_next_contract::<T, OldContractInfo<T>, _>(
+				&mut self.last_account,
+				migrate_contract::<T>,
+			) {
+				IsFinished::No => (IsFinished::No, Self::max_step_weight()),
+				IsFinished::Yes => (IsFinished::Yes, T::DbWeight::get().reads(1)),
+			}
+		}
+	}
+}
+
+/// V12: Adds the per contract call filter to the contract info.
+///
+/// One contract is migrated per step.
+pub mod v12 {
+	use super::*;
+	use crate::{storage::DepositAccount, CallFilter};
+	use frame_support::DefaultNoBound;
+
+	#[derive(Encode, Decode)]
+	pub struct ContractInfo<T: Config> {
+		pub trie_id: TrieId,
+		pub deposit_account: DepositAccount<T>,
+		pub code_hash: CodeHash<T>,
+		pub storage_bytes: u32,
+		pub storage_items: u32,
+		pub storage_byte_deposit: BalanceOf<T>,
+		pub storage_item_deposit: BalanceOf<T>,
+		pub storage_base_deposit: BalanceOf<T>,
+		pub call_filter: Option<CallFilter<T>>,
+	}
+
+	fn migrate_contract<T: Config>(
+		_account: &T::AccountId,
+		old: v11::ContractInfo<T>,
+	) -> ContractInfo<T> {
+		ContractInfo {
+			trie_id: old.trie_id,
+			deposit_account: old.deposit_account,
+			code_hash: old.code_hash,
+			storage_bytes: old.storage_bytes,
+			storage_items: old.storage_items,
+			storage_byte_deposit: old.storage_byte_deposit,
+			storage_item_deposit: old.storage_item_deposit,
+			storage_base_deposit: old.storage_base_deposit,
+			call_filter: None,
+		}
+	}
+
+	/// Adds an empty `call_filter` to every `ContractInfo` in the `v11` layout.
+	#[derive(Encode, Decode, MaxEncodedLen, DefaultNoBound)]
+	#[codec(mel_bound())]
+	pub struct Migration<T: Config> {
+		/// The last contract that was migrated.
+		last_account: Option<T::AccountId>,
+	}
+
+	impl<T: Config> MigrationStep for Migration<T> {
+		const VERSION: u16 = 12;
+
+		fn max_step_weight() -> Weight {
+			T::DbWeight::get().reads_writes(2, 1)
+		}
+
+		fn step(&mut self) -> (IsFinished, Weight) {
+			match migrate_next_contract::<T, v11::ContractInfo<T>, _>(
+				&mut self.last_account,
+				migrate_contract::<T>,
+			) {
+				IsFinished::No => (IsFinished::No, Self::max_step_weight()),
+				IsFinished::Yes => (IsFinished::Yes, T::DbWeight::get().reads(1)),
+			}
+		}
+	}
+}
+
+/// V13: Adds the paused flag to the contract info.
+///
+/// One contract is migrated per step.
+pub mod v13 {
+	use super::*;
+	use crate::{storage::DepositAccount, CallFilter};
+	use frame_support::DefaultNoBound;
+
+	#[derive(Encode, Decode)]
+	pub struct ContractInfo<T: Config> {
+		pub trie_id: TrieId,
+		pub deposit_account: DepositAccount<T>,
+		pub code_hash: CodeHash<T>,
+		pub storage_bytes: u32,
+		pub storage_items: u32,
+		pub storage_byte_deposit: BalanceOf<T>,
+		pub storage_item_deposit: BalanceOf<T>,
+		pub storage_base_deposit: BalanceOf<T>,
+		pub call_filter: Option<CallFilter<T>>,
+		pub paused: bool,
+	}
+
+	fn migrate_contract<T: Config>(
+		_account: &T::AccountId,
+		old: v12::ContractInfo<T>,
+	) -> ContractInfo<T> {
+		ContractInfo {
+			trie_id: old.trie_id,
+			deposit_account: old.deposit_account,
+			code_hash: old.code_hash,
+			storage_bytes: old.storage_bytes,
+			storage_items: old.storage_items,
+			storage_byte_deposit: old.storage_byte_deposit,
+			storage_item_deposit: old.storage_item_deposit,
+			storage_base_deposit: old.storage_base_deposit,
+			call_filter: old.call_filter,
+			paused: false,
+		}
+	}
+
+	/// Adds `paused` to every `ContractInfo` in the `v12` layout.
+	#[derive(Encode, Decode, MaxEncodedLen, DefaultNoBound)]
+	#[codec(mel_bound())]
+	pub struct Migration<T: Config> {
+		/// The last contract that was migrated.
+		last_account: Option<T::AccountId>,
+	}
+
+	impl<T: Config> MigrationStep for Migration<T> {
+		const VERSION: u16 = 13;
+
+		fn max_step_weight() -> Weight {
+			T::DbWeight::get().reads_writes(2, 1)
+		}
+
+		fn step(&mut self) -> (IsFinished, Weight) {
+			match migrate_next_contract::<T, v12::ContractInfo<T>, _>(
+				&mut self.last_account,
+				migrate_contract::<T>,
+			) {
+				IsFinished::No => (IsFinished::No, Self::max_step_weight()),
+				IsFinished::Yes => (IsFinished::Yes, T::DbWeight::get().reads(1)),
+			}
+		}
+	}
+}
+
+/// V14: The [`crate::wasm::API_VERSION`] is recorded in the `CodeInfo` of every code.
+///
+/// Code uploaded before this migration is marked with version `0`. One code is migrated per step.
+pub mod v14 {
+	use super::*;
+	use crate::Determinism;
+	use frame_support::{storage::unhashed, DefaultNoBound};
+	use v10::CodeInfo as OldCodeInfo;
+
+	#[derive(Encode, Decode)]
+	struct CodeInfo<T: Config> {
+		owner: T::AccountId,
+		#[codec(compact)]
+		deposit: BalanceOf<T>,
+		#[codec(compact)]
+		refcount: u64,
+		determinism: Determinism,
+		code_len: u32,
+		api_version: u16,
+	}
+
+	#[storage_alias]
+	type CodeInfoOf<T: Config> = StorageMap<Pallet<T>, Identity, CodeHash<T>, OldCodeInfo<T>>;
+
+	/// Adds the `api_version` to every `CodeInfo`.
+	#[derive(Encode, Decode, MaxEncodedLen, DefaultNoBound)]
+	#[codec(mel_bound())]
+	pub struct Migration<T: Config> {
+		/// The last code that was migrated.
+		last_code_hash: Option<CodeHash<T>>,
+	}
+
+	impl<T: Config> MigrationStep for Migration<T> {
+		const VERSION: u16 = 14;
+
+		fn max_step_weight() -> Weight {
+			T::DbWeight::get().reads_writes(2, 1)
+		}
+
+		fn step(&mut self) -> (IsFinished, Weight) {
+			let mut iter = match self.last_code_hash.take() {
+				Some(last) => <CodeInfoOf<T>>::iter_from(<CodeInfoOf<T>>::hashed_key_for(last)),
+				None => <CodeInfoOf<T>>::iter(),
+			};
+
+			match iter.next() {
+				Some((code_hash, old)) => {
+					let info = CodeInfo::<T> {
+						owner: old.owner,
+						deposit: old.deposit,
+						refcount: old.refcount,
+						determinism: old.determinism,
+						code_len: old.code_len,
+						api_version: 0,
+					};
+					unhashed::put(&<CodeInfoOf<T>>::hashed_key_for(&code_hash), &info);
+					self.last_code_hash = Some(code_hash);
+					(IsFinished::No, Self::max_step_weight())
+				},
+				None => (IsFinished::Yes, T::DbWeight::get().reads(1)),
+			}
+		}
+	}
+}
+
+/// V15: Adds the reentrancy protection flag to the contract info.
+///
+/// One contract is migrated per step.
+pub mod v15 {
+	use super::*;
+	use crate::{storage::DepositAccount, CallFilter};
+	use frame_support::DefaultNoBound;
+
+	#[derive(Encode, Decode)]
+	pub struct ContractInfo<T: Config> {
+		pub trie_id: TrieId,
+		pub deposit_account: DepositAccount<T>,
+		pub code_hash: CodeHash<T>,
+		pub storage_bytes: u32,
+		pub storage_items: u32,
+		pub storage_byte_deposit: BalanceOf<T>,
+		pub storage_item_deposit: BalanceOf<T>,
+		pub storage_base_deposit: BalanceOf<T>,
+		pub call_filter: Option<CallFilter<T>>,
+		pub paused: bool,
+		pub deny_reentry: bool,
+	}
+
+	fn migrate_contract<T: Config>(
+		_account: &T::AccountId,
+		old: v13::ContractInfo<T>,
+	) -> ContractInfo<T> {
+		ContractInfo {
+			trie_id: old.trie_id,
+			deposit_account: old.deposit_account,
+			code_hash: old.code_hash,
+			storage_bytes: old.storage_bytes,
+			storage_items: old.storage_items,
+			storage_byte_deposit: old.storage_byte_deposit,
+			storage_item_deposit: old.storage_item_deposit,
+			storage_base_deposit: old.storage_base_deposit,
+			call_filter: old.call_filter,
+			paused: old.paused,
+			deny_reentry: false,
+		}
+	}
+
+	/// Adds `deny_reentry` to every `ContractInfo` in the `v13` layout.
+	#[derive(Encode, Decode, MaxEncodedLen, DefaultNoBound)]
+	#[codec(mel_bound())]
+	pub struct Migration<T: Config> {
+		/// The last contract that was migrated.
+		last_account: Option<T::AccountId>,
+	}
+
+	impl<T: Config> MigrationStep for Migration<T> {
+		const VERSION: u16 = 15;
+
+		fn max_step_weight() -> Weight {
+			T::DbWeight::get().reads_writes(2, 1)
+		}
+
+		fn step(&mut self) -> (IsFinished, Weight) {
+			match migrate_next_contract::<T, v13::ContractInfo<T>, _>(
+				&mut self.last_account,
+				migrate_contract::<T>,
+			) {
+				IsFinished::No => (IsFinished::No, Self::max_step_weight()),
+				IsFinished::Yes => (IsFinished::Yes, T::DbWeight::get().reads(1)),
+			}
+		}
+	}
+}
+
+/// V16: Adds the delegate dependencies to the contract info.
+///
+/// One contract is migrated per step.
+pub mod v16 {
+	use super::*;
+	use crate::storage::ContractInfo;
+	use frame_support::DefaultNoBound;
+
+	fn migrate_contract<T: Config>(
+		_account: &T::AccountId,
+		old: v15::ContractInfo<T>,
+	) -> ContractInfo<T> {
+		ContractInfo {
+			trie_id: old.trie_id,
+			deposit_account: old.deposit_account,
+			code_hash: old.code_hash,
+			storage_bytes: old.storage_bytes,
+			storage_items: old.storage_items,
+			storage_byte_deposit: old.storage_byte_deposit,
+			storage_item_deposit: old.storage_item_deposit,
+			storage_base_deposit: old.storage_base_deposit,
+			call_filter: old.call_filter,
+			paused: old.paused,
+			deny_reentry: old.deny_reentry,
+			delegate_dependencies: Default::default(),
+		}
+	}
+
+	/// Adds empty `delegate_dependencies` to every `ContractInfo` in the `v15` layout.
+	#[derive(Encode, Decode, MaxEncodedLen, DefaultNoBound)]
+	#[codec(mel_bound())]
+	pub struct Migration<T: Config> {
+		/// The last contract that was migrated.
+		last_account: Option<T::AccountId>,
+	}
+
+	impl<T: Config> MigrationStep for Migration<T> {
+		const VERSION: u16 = 16;
+
+		fn max_step_weight() -> Weight {
+			T::DbWeight::get().reads_writes(2, 1)
+		}
+
+		fn step(&mut self) -> (IsFinished, Weight) {
+			match migrate_next_contract::<T, v15::ContractInfo<T>, _>(
+				&mut self.last_account,
+				migrate_contract::<T>,
+			) {
+				IsFinished::No => (IsFinished::No, Self::max_step_weight()),
+				IsFinished::Yes => (IsFinished::Yes, T::DbWeight::get().reads(1)),
+			}
+		}
+	}
+}
+
+/// V17: Records the address of terminated contracts in the deletion queue.
+///
+/// Contracts that were queued before this migration don't know their address.
+pub mod v17 {
+	use super::*;
+	use crate::{storage::DeletedContract, weights::WeightInfo, DeletionQueue};
+	use frame_support::DefaultNoBound;
+
+	#[derive(Encode, Decode)]
+	struct OldDeletedContract {
+		trie_id: TrieId,
+	}
+
+	/// Adds an empty `account_id` to every entry of the deletion queue.
+	#[derive(Encode, Decode, MaxEncodedLen, DefaultNoBound)]
+	#[codec(mel_bound())]
+	pub struct Migration<T: Config> {
+		_phantom: PhantomData<T>,
+	}
+
+	impl<T: Config> MigrationStep for Migration<T> {
+		const VERSION: u16 = 17;
+
+		fn max_step_weight() -> Weight {
+			T::DbWeight::get().reads_writes(1, 1).saturating_add(
+				T::WeightInfo::on_initialize_per_queue_item(T::DeletionQueueDepth::get()),
+			)
+		}
+
+		fn step(&mut self) -> (IsFinished, Weight) {
+			<DeletionQueue<T>>::translate(|old: Option<Vec<OldDeletedContract>>| {
+				old.map(|old| {
+					BoundedVec::truncate_from(
+						old.into_iter()
+							.map(|old| DeletedContract { trie_id: old.trie_id, account_id: None })
+							.collect(),
+					)
+				})
+			})
+			.ok();
+			(IsFinished::Yes, Self::max_step_weight())
+		}
+	}
+}
+
+/// V18: Removes the schedule override.
+///
+/// The layout of [`crate::Schedule`] changed since [`Pallet::set_schedule`] was introduced, e.g.
+/// by adding `Limits::stack_height` and the `ValidationRules`. An override stored before can't be
+/// decoded anymore and [`Config::Schedule`] is in effect until a new one is set.
+pub mod v18 {
+	use super::*;
+	use crate::ScheduleOverride;
+	use frame_support::DefaultNoBound;
+
+	/// Removes the `ScheduleOverride`.
+	#[derive(Encode, Decode, MaxEncodedLen, DefaultNoBound)]
+	#[codec(mel_bound())]
+	pub struct Migration<T: Config> {
+		_phantom: PhantomData<T>,
+	}
+
+	impl<T: Config> MigrationStep for Migration<T> {
+		const VERSION: u16 = 18;
+
+		fn max_step_weight() -> Weight {
+			T::DbWeight::get().writes(1)
+		}
+
+		fn step(&mut self) -> (IsFinished, Weight) {
+			<ScheduleOverride<T>>::kill();
+			(IsFinished::Yes, Self::max_step_weight())
+		}
+	}
+}
+
 // Post checks always need to be run against the latest storage version. This is why we
 // do not scope them in the per version modules. They always need to be ported to the latest
 // version.
@@ -408,11 +1287,14 @@ mod post_checks {
 	use super::*;
 	use crate::Determinism;
 	use sp_io::default_child_storage as child;
+	use v10::CodeInfo;
 	use v8::ContractInfo;
-	use v9::PrefabWasmModule;
 
 	#[storage_alias]
-	type CodeStorage<T: Config> = StorageMap<Pallet<T>, Identity, CodeHash<T>, PrefabWasmModule>;
+	type CodeInfoOf<T: Config> = StorageMap<Pallet<T>, Identity, CodeHash<T>, CodeInfo<T>>;
+
+	#[storage_alias]
+	type PristineCode<T: Config> = StorageMap<Pallet<T>, Identity, CodeHash<T>, Vec<u8>>;
 
 	#[storage_alias]
 	type ContractInfoOf<T: Config, V> =
@@ -431,6 +1313,10 @@ mod post_checks {
 			v9::<T>()?;
 		}
 
+		if old_version < 10 {
+			v10::<T>()?;
+		}
+
 		Ok(())
 	}
 
@@ -462,7 +1348,7 @@ mod post_checks {
 	}
 
 	fn v9<T: Config>() -> Result<(), &'static str> {
-		for value in CodeStorage::<T>::iter_values() {
+		for value in CodeInfoOf::<T>::iter_values() {
 			ensure!(
 				value.determinism == Determinism::Deterministic,
 				"All pre-existing codes need to be deterministic."
@@ -470,4 +1356,86 @@ mod post_checks {
 		}
 		Ok(())
 	}
+
+	fn v10<T: Config>() -> Result<(), &'static str> {
+		for (code_hash, value) in CodeInfoOf::<T>::iter() {
+			let code_len = PristineCode::<T>::decode_len(&code_hash)
+				.ok_or("Code info without pristine code.")?;
+			ensure!(code_len as u32 == value.code_len, "Code length does not match.");
+		}
+		ensure!(
+			migration::storage_iter::<()>(<Pallet<T>>::name().as_bytes(), b"CodeStorage")
+				.next()
+				.is_none(),
+			"Instrumented code was not removed."
+		);
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+	use super::*;
+
+	/// A migration that finishes after `N` steps.
+	#[derive(Encode, Decode, MaxEncodedLen, Default)]
+	pub struct MockMigration<const N: u16> {
+		steps_done: u16,
+	}
+
+	impl<const N: u16> MigrationStep for MockMigration<N> {
+		const VERSION: u16 = N;
+
+		fn max_step_weight() -> Weight {
+			Weight::from_ref_time(1)
+		}
+
+		fn step(&mut self) -> (IsFinished, Weight) {
+			self.steps_done += 1;
+			let finished = if self.steps_done == N { IsFinished::Yes } else { IsFinished::No };
+			(finished, Weight::from_ref_time(1))
+		}
+	}
+
+	type Migrations = (MockMigration<1>, MockMigration<2>);
+
+	#[test]
+	fn new_starts_the_next_migration() {
+		assert_eq!(
+			<Migrations as MigrateSequence>::new(StorageVersion::new(0)),
+			Some(MockMigration::<1>::default().encode().try_into().unwrap())
+		);
+		assert_eq!(
+			<Migrations as MigrateSequence>::new(StorageVersion::new(1)),
+			Some(MockMigration::<2>::default().encode().try_into().unwrap())
+		);
+		assert_eq!(<Migrations as MigrateSequence>::new(StorageVersion::new(2)), None);
+	}
+
+	#[test]
+	fn steps_respect_the_weight_limit() {
+		let version = StorageVersion::new(1);
+		let cursor = <Migrations as MigrateSequence>::new(version).unwrap();
+
+		let mut weight_left = Weight::zero();
+		assert_eq!(
+			<Migrations as MigrateSequence>::steps(version, &cursor, &mut weight_left),
+			StepResult::InProgress { cursor: cursor.clone(), steps_done: 0 }
+		);
+
+		let mut weight_left = Weight::from_ref_time(1);
+		let cursor =
+			match <Migrations as MigrateSequence>::steps(version, &cursor, &mut weight_left) {
+				StepResult::InProgress { cursor, steps_done: 1 } => cursor,
+				result => panic!("Unexpected result: {:?}", result),
+			};
+		assert_eq!(weight_left, Weight::zero());
+
+		let mut weight_left = Weight::from_ref_time(5);
+		assert_eq!(
+			<Migrations as MigrateSequence>::steps(version, &cursor, &mut weight_left),
+			StepResult::Completed { steps_done: 1 }
+		);
+		assert_eq!(weight_left, Weight::from_ref_time(4));
+	}
 }
@@ -0,0 +1,205 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Origins that let other pallets tell contracts apart from accounts controlled by a key.
+//!
+//! The runtime decides with [`Config::CallRuntimeOrigin`] which origin contracts dispatch runtime
+//! calls with. [`DispatchAsSigned`] keeps the signed origin that every pallet already accepts.
+//! [`DispatchAsContract`] uses this pallet's [`Origin`](crate::Origin) instead so that a call
+//! made by a contract can't be mistaken for a transaction.
+//!
+//! Pallets that want to treat contracts differently use [`EnsureContract`] or
+//! [`EnsureSignedOrContract`]. Both recognize a contract no matter which of the two converters
+//! is configured. A signed origin belongs to a contract if there is a contract at that address.
+//! Contract addresses are derived by hashing, so no key can sign for them.
+//!
+//! The other direction is covered by [`CallOrigin`]: It names who started a call stack, which
+//! is either a signed account or root.
+
+use crate::{Config, ContractInfoOf, Error};
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{
+	traits::EnsureOrigin, CloneNoBound, EqNoBound, PartialEqNoBound, RuntimeDebug,
+	RuntimeDebugNoBound,
+};
+use frame_system::pallet_prelude::OriginFor;
+use scale_info::TypeInfo;
+use sp_runtime::{traits::Convert, DispatchError};
+use sp_std::marker::PhantomData;
+
+/// The origin a contract call stack is started with.
+///
+/// Root can call contracts, e.g. to let governance use an admin entry point. As root has no
+/// account it can neither transfer value nor pay storage deposit. It can't instantiate or
+/// terminate contracts either.
+#[derive(
+	CloneNoBound, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound, Encode, Decode, TypeInfo,
+)]
+#[scale_info(skip_type_params(T))]
+pub enum CallOrigin<T: Config> {
+	/// The call stack was started by root.
+	Root,
+	/// The call stack was started by this account.
+	Signed(T::AccountId),
+}
+
+impl<T: Config> CallOrigin<T> {
+	/// Create a signed origin of `account_id`.
+	pub fn from_account_id(account_id: T::AccountId) -> Self {
+		Self::Signed(account_id)
+	}
+
+	/// Convert a dispatch origin. Fails for anything but a signed or root origin.
+	pub fn from_runtime_origin(o: OriginFor<T>) -> Result<Self, DispatchError> {
+		match frame_system::ensure_signed_or_root(o)? {
+			Some(who) => Ok(Self::Signed(who)),
+			None => Ok(Self::Root),
+		}
+	}
+
+	/// The account of a signed origin. Fails with [`Error::RootNotAllowed`] for root.
+	pub fn account_id(&self) -> Result<&T::AccountId, DispatchError> {
+		match self {
+			Self::Signed(who) => Ok(who),
+			Self::Root => Err(<Error<T>>::RootNotAllowed.into()),
+		}
+	}
+
+	/// Whether this is the root origin.
+	pub fn is_root(&self) -> bool {
+		matches!(self, Self::Root)
+	}
+}
+
+/// The origin of a runtime call dispatched by a contract.
+#[derive(PartialEq, Eq, Clone, RuntimeDebug, Encode, Decode, TypeInfo, MaxEncodedLen)]
+pub enum RawOrigin<AccountId> {
+	/// The call was dispatched by the contract at this address.
+	Contract(AccountId),
+}
+
+/// Who dispatched a call accepted by [`EnsureSignedOrContract`].
+#[derive(PartialEq, Eq, Clone, RuntimeDebug)]
+pub enum Caller<AccountId> {
+	/// An account that signed a transaction.
+	Signed(AccountId),
+	/// A contract.
+	Contract(AccountId),
+}
+
+impl<AccountId> Caller<AccountId> {
+	/// The account of the caller regardless of its kind.
+	pub fn account(&self) -> &AccountId {
+		match self {
+			Caller::Signed(account) | Caller::Contract(account) => account,
+		}
+	}
+
+	/// Whether the caller is a contract.
+	pub fn is_contract(&self) -> bool {
+		matches!(self, Caller::Contract(_))
+	}
+}
+
+/// Contracts dispatch runtime calls with a signed origin of their own address.
+pub struct DispatchAsSigned;
+
+impl<AccountId, O> Convert<AccountId, O> for DispatchAsSigned
+where
+	O: From<frame_system::RawOrigin<AccountId>>,
+{
+	fn convert(address: AccountId) -> O {
+		frame_system::RawOrigin::Signed(address).into()
+	}
+}
+
+/// Contracts dispatch runtime calls with [`RawOrigin::Contract`].
+///
+/// Pallets that use `ensure_signed` reject those calls. They need to use
+/// [`EnsureSignedOrContract`] in order to accept them.
+pub struct DispatchAsContract;
+
+impl<AccountId, O> Convert<AccountId, O> for DispatchAsContract
+where
+	O: From<RawOrigin<AccountId>>,
+{
+	fn convert(address: AccountId) -> O {
+		RawOrigin::Contract(address).into()
+	}
+}
+
+/// Ensure that the origin is a contract and return its address.
+pub struct EnsureContract<T>(PhantomData<T>);
+
+impl<T, O> EnsureOrigin<O> for EnsureContract<T>
+where
+	T: Config,
+	O: Into<Result<RawOrigin<T::AccountId>, O>>
+		+ From<RawOrigin<T::AccountId>>
+		+ Into<Result<frame_system::RawOrigin<T::AccountId>, O>>
+		+ From<frame_system::RawOrigin<T::AccountId>>,
+{
+	type Success = T::AccountId;
+
+	fn try_origin(o: O) -> Result<Self::Success, O> {
+		match EnsureSignedOrContract::<T>::try_origin(o)? {
+			Caller::Contract(address) => Ok(address),
+			Caller::Signed(who) => Err(frame_system::RawOrigin::Signed(who).into()),
+		}
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn try_successful_origin() -> Result<O, ()> {
+		EnsureSignedOrContract::<T>::try_successful_origin()
+	}
+}
+
+/// Ensure that the origin is either signed or a contract and return which one it is.
+pub struct EnsureSignedOrContract<T>(PhantomData<T>);
+
+impl<T, O> EnsureOrigin<O> for EnsureSignedOrContract<T>
+where
+	T: Config,
+	O: Into<Result<RawOrigin<T::AccountId>, O>>
+		+ From<RawOrigin<T::AccountId>>
+		+ Into<Result<frame_system::RawOrigin<T::AccountId>, O>>
+		+ From<frame_system::RawOrigin<T::AccountId>>,
+{
+	type Success = Caller<T::AccountId>;
+
+	fn try_origin(o: O) -> Result<Self::Success, O> {
+		let o = match Into::<Result<RawOrigin<T::AccountId>, O>>::into(o) {
+			Ok(RawOrigin::Contract(address)) => return Ok(Caller::Contract(address)),
+			Err(o) => o,
+		};
+		match Into::<Result<frame_system::RawOrigin<T::AccountId>, O>>::into(o) {
+			Ok(frame_system::RawOrigin::Signed(who)) if ContractInfoOf::<T>::contains_key(&who) =>
+				Ok(Caller::Contract(who)),
+			Ok(frame_system::RawOrigin::Signed(who)) => Ok(Caller::Signed(who)),
+			Ok(other) => Err(other.into()),
+			Err(o) => Err(o),
+		}
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn try_successful_origin() -> Result<O, ()> {
+		use sp_runtime::traits::TrailingZeroInput;
+		let address = T::AccountId::decode(&mut TrailingZeroInput::zeroes())
+			.expect("infinite length input; no invalid inputs for type; qed");
+		Ok(RawOrigin::Contract(address).into())
+	}
+}
@@ -18,17 +18,17 @@
 //! This module contains the cost schedule and supporting code that constructs a
 //! sane default schedule from a `WeightInfo` implementation.
 
-use crate::{wasm::Determinism, weights::WeightInfo, Config};
+use crate::{storage::ContractInfo, wasm::CodeInfo, weights::WeightInfo, Config};
 
-use codec::{Decode, Encode};
-use frame_support::{weights::Weight, DefaultNoBound};
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{traits::Get, weights::Weight, DefaultNoBound};
 use pallet_contracts_proc_macro::{ScheduleDebug, WeightDebug};
 use scale_info::TypeInfo;
 #[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
 use sp_runtime::RuntimeDebug;
 use sp_std::{marker::PhantomData, vec::Vec};
-use wasm_instrument::{gas_metering, parity_wasm::elements};
+use wasmi::StackLimits;
 
 /// How many API calls are executed in a single batch. The reason for increasing the amount
 /// of API calls in batches (per benchmark component increase) is so that the linear regression
@@ -66,11 +66,6 @@ pub const INSTR_BENCHMARK_BATCH_SIZE: u32 = 100;
 ///     }
 /// }
 /// ```
-///
-/// # Note
-///
-/// Please make sure to bump the [`InstructionWeights::version`] whenever substantial
-/// changes are made to its values.
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "std", serde(bound(serialize = "", deserialize = "")))]
 #[derive(Clone, Encode, Decode, PartialEq, Eq, ScheduleDebug, DefaultNoBound, TypeInfo)]
@@ -79,7 +74,10 @@ pub struct Schedule<T: Config> {
 	/// Describes the upper limits on various metrics.
 	pub limits: Limits,
 
-	/// The weights for individual wasm instructions.
+	/// Decides which wasm modules are accepted as contract code.
+	pub validation: ValidationRules,
+
+	/// The weights for executing wasm instructions.
 	pub instruction_weights: InstructionWeights<T>,
 
 	/// The weights for each imported function a contract is allowed to call.
@@ -91,8 +89,8 @@ pub struct Schedule<T: Config> {
 /// # Note
 ///
 /// The values in this struct should never be decreased. The reason is that decreasing those
-/// values will break existing contracts which are above the new limits when a
-/// re-instrumentation is triggered.
+/// values will break existing contracts which are above the new limits when they are
+/// loaded for execution.
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 #[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo)]
 pub struct Limits {
@@ -123,6 +121,14 @@ pub struct Limits {
 	/// Maximum number of memory pages allowed for a contract.
 	pub memory_pages: u32,
 
+	/// Maximum height of the value stack of a contract in number of values.
+	///
+	/// Every value occupies 8 bytes regardless of its type. The engine traps deterministically
+	/// once a contract exceeds this height. Code with a function whose parameters and locals
+	/// alone exceed it is rejected on upload. The `integrity_test` checks that this leaves
+	/// enough memory for [`Config::MaxCodeLen`] at the maximum call depth.
+	pub stack_height: u32,
+
 	/// Maximum number of elements allowed in a table.
 	///
 	/// Currently, the only type of element that is allowed in a table is funcref.
@@ -134,8 +140,14 @@ pub struct Limits {
 	/// The maximum length of a subject in bytes used for PRNG generation.
 	pub subject_len: u32,
 
-	/// The maximum size of a storage value and event payload in bytes.
+	/// The maximum size of a storage value in bytes.
 	pub payload_len: u32,
+
+	/// The maximum size of the data attached to an event in bytes.
+	///
+	/// The topics of an event are not counted towards this limit. They are limited by
+	/// [`Self::event_topics`] instead.
+	pub event_data_len: u32,
 }
 
 impl Limits {
@@ -143,28 +155,63 @@ impl Limits {
 	pub fn max_memory_size(&self) -> u32 {
 		self.memory_pages * 64 * 1024
 	}
+
+	/// The maximum size in bytes of the value stack of a contract.
+	pub fn max_stack_size(&self) -> u32 {
+		self.stack_height.saturating_mul(8)
+	}
+
+	/// The stack limits the engine executes contracts with.
+	pub(crate) fn stack_limits(&self) -> StackLimits {
+		// The engine allocates this many values up front and grows the stack on demand.
+		const INITIAL_STACK_HEIGHT: u32 = 1024;
+		// The default of the engine.
+		const MAX_RECURSION_DEPTH: usize = 1024;
+		StackLimits::new(
+			INITIAL_STACK_HEIGHT.min(self.stack_height) as usize,
+			self.stack_height as usize,
+			MAX_RECURSION_DEPTH,
+		)
+		.expect("initial <= max; qed")
+	}
 }
 
-/// Describes the weight for all categories of supported wasm instructions.
+/// Rules that decide which wasm modules are accepted as contract code.
 ///
-/// There there is one field for each wasm instruction that describes the weight to
-/// execute one instruction of that name. There are a few execptions:
+/// The rules are only applied when code is uploaded. Code that was accepted once keeps working
+/// when the rules are tightened afterwards. The [`Default`] only accepts what the pallet has
+/// always accepted.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct ValidationRules {
+	/// Whether the sign extension operators (e.g. `i64.extend32_s`) can be used.
+	pub sign_extension: bool,
+
+	/// Whether floating point types and instructions can be used by code uploaded with
+	/// [`Determinism::AllowIndeterminism`](crate::wasm::Determinism::AllowIndeterminism).
+	///
+	/// Deterministic code can never use floating point.
+	pub relaxed_floats: bool,
+
+	/// Whether the imported memory must declare its maximum number of pages.
+	///
+	/// If not, a memory without a maximum is limited to [`Limits::memory_pages`].
+	pub memory_maximum_required: bool,
+
+	/// Names of the host functions that a module must not import.
+	///
+	/// This is in addition to the functions disabled by [`Config::ChainExtension`] and
+	/// [`Config::RandomnessEnabled`].
+	pub forbidden_imports: Vec<Vec<u8>>,
+}
+
+/// Describes the weight for executing wasm instructions.
 ///
-/// 1. If there is a i64 and a i32 variant of an instruction we use the weight
-///    of the former for both.
-/// 2. The following instructions are free of charge because they merely structure the
-///    wasm module and cannot be spammed without making the module invalid (and rejected):
-///    End, Unreachable, Return, Else
-/// 3. The following instructions cannot be benchmarked because they are removed by any
-///    real world execution engine as a preprocessing step and therefore don't yield a
-///    meaningful benchmark result. However, in contrast to the instructions mentioned
-///    in 2. they can be spammed. We price them with the same weight as the "default"
-///    instruction (i64.const): Block, Loop, Nop
-/// 4. We price both i64.const and drop as InstructionWeights.i64const / 2. The reason
-///    for that is that we cannot benchmark either of them on its own but we need their
-///    individual values to derive (by subtraction) the weight of all other instructions
-///    that use them as supporting instructions. Supporting means mainly pushing arguments
-///    and dropping return values in order to maintain a valid module.
+/// Instructions are metered by the wasm engine at execution time using fuel. Every
+/// instruction consumes at least one unit of fuel while some instructions consume
+/// additional fuel depending on the amount of work they do (e.g. calls into functions with many
+/// locals). The weight of a unit of fuel is derived from the `i64.const` instruction which is
+/// the cheapest instruction we benchmark.
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 #[derive(Clone, Encode, Decode, PartialEq, Eq, ScheduleDebug, TypeInfo)]
 #[scale_info(skip_type_params(T))]
@@ -173,79 +220,24 @@ pub struct InstructionWeights<T: Config> {
 	///
 	/// # Note
 	///
-	/// Should be incremented whenever any instruction weight is changed. The
-	/// reason is that changes to instruction weights require a re-instrumentation
-	/// in order to apply the changes to an already deployed code. The re-instrumentation
-	/// is triggered by comparing the version of the current schedule with the version the code was
-	/// instrumented with. Changes usually happen when pallet_contracts is re-benchmarked.
-	///
-	/// Changes to other parts of the schedule should not increment the version in
-	/// order to avoid unnecessary re-instrumentations.
+	/// Instructions are metered by the engine when executing the pristine code. Hence
+	/// changing the instruction weights does not require to touch any deployed code. The
+	/// version merely identifies the benchmark results the weights were derived from.
 	pub version: u32,
-	/// Weight to be used for instructions which don't have benchmarks assigned.
-	///
-	/// This weight is used whenever a code is uploaded with [`Determinism::AllowIndeterminism`]
-	/// and an instruction (usually a float instruction) is encountered. This weight is **not**
-	/// used if a contract is uploaded with [`Determinism::Deterministic`]. If this field is set to
-	/// `0` (the default) only deterministic codes are allowed to be uploaded.
-	pub fallback: u32,
-	pub i64const: u32,
-	pub i64load: u32,
-	pub i64store: u32,
-	pub select: u32,
-	pub r#if: u32,
-	pub br: u32,
-	pub br_if: u32,
-	pub br_table: u32,
-	pub br_table_per_entry: u32,
-	pub call: u32,
-	pub call_indirect: u32,
-	pub call_indirect_per_param: u32,
-	pub call_per_local: u32,
-	pub local_get: u32,
-	pub local_set: u32,
-	pub local_tee: u32,
-	pub global_get: u32,
-	pub global_set: u32,
-	pub memory_current: u32,
-	pub memory_grow: u32,
-	pub i64clz: u32,
-	pub i64ctz: u32,
-	pub i64popcnt: u32,
-	pub i64eqz: u32,
-	pub i64extendsi32: u32,
-	pub i64extendui32: u32,
-	pub i32wrapi64: u32,
-	pub i64eq: u32,
-	pub i64ne: u32,
-	pub i64lts: u32,
-	pub i64ltu: u32,
-	pub i64gts: u32,
-	pub i64gtu: u32,
-	pub i64les: u32,
-	pub i64leu: u32,
-	pub i64ges: u32,
-	pub i64geu: u32,
-	pub i64add: u32,
-	pub i64sub: u32,
-	pub i64mul: u32,
-	pub i64divs: u32,
-	pub i64divu: u32,
-	pub i64rems: u32,
-	pub i64remu: u32,
-	pub i64and: u32,
-	pub i64or: u32,
-	pub i64xor: u32,
-	pub i64shl: u32,
-	pub i64shrs: u32,
-	pub i64shru: u32,
-	pub i64rotl: u32,
-	pub i64rotr: u32,
+	/// Weight of one unit of fuel consumed by the wasm engine.
+	pub base: u32,
 	/// The type parameter is used in the default implementation.
 	#[codec(skip)]
 	pub _phantom: PhantomData<T>,
 }
 
+impl<T: Config> InstructionWeights<T> {
+	/// The `ref_time` weight charged for one unit of fuel consumed by the wasm engine.
+	pub(crate) fn ref_time_per_fuel(&self) -> u64 {
+		u64::from(self.base).max(1)
+	}
+}
+
 /// Describes the weight for each imported function that a contract is allowed to call.
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 #[derive(Clone, Encode, Decode, PartialEq, Eq, WeightDebug, TypeInfo)]
@@ -266,6 +258,9 @@ pub struct HostFnWeights<T: Config> {
 	/// Weight of calling `seal_caller_is_origin`.
 	pub caller_is_origin: Weight,
 
+	/// Weight of calling `caller_is_root`.
+	pub caller_is_root: Weight,
+
 	/// Weight of calling `seal_address`.
 	pub address: Weight,
 
@@ -278,6 +273,9 @@ pub struct HostFnWeights<T: Config> {
 	/// Weight of calling `seal_value_transferred`.
 	pub value_transferred: Weight,
 
+	/// Weight of calling `last_subcall_deposit`.
+	pub last_subcall_deposit: Weight,
+
 	/// Weight of calling `seal_minimum_balance`.
 	pub minimum_balance: Weight,
 
@@ -290,9 +288,6 @@ pub struct HostFnWeights<T: Config> {
 	/// Weight of calling `seal_weight_to_fee`.
 	pub weight_to_fee: Weight,
 
-	/// Weight of calling `gas`.
-	pub gas: Weight,
-
 	/// Weight of calling `seal_input`.
 	pub input: Weight,
 
@@ -425,6 +420,18 @@ pub struct HostFnWeights<T: Config> {
 	/// Weight of calling `instantiation_nonce`.
 	pub instantiation_nonce: Weight,
 
+	/// Weight of calling `seal_storage_deposit_limit_left`.
+	pub storage_deposit_limit_left: Weight,
+
+	/// Weight of calling `seal_set_deny_reentry`.
+	pub set_deny_reentry: Weight,
+
+	/// Weight of calling `seal_lock_delegate_dependency`.
+	pub lock_delegate_dependency: Weight,
+
+	/// Weight of calling `seal_unlock_delegate_dependency`.
+	pub unlock_delegate_dependency: Weight,
+
 	/// The type parameter is used in the default implementation.
 	#[codec(skip)]
 	pub _phantom: PhantomData<T>,
@@ -525,78 +532,35 @@ impl Default for Limits {
 			locals: 1024,
 			parameters: 128,
 			memory_pages: 16,
+			// 1MB of 8 byte values.
+			stack_height: 128 * 1024,
 			// 4k function pointers (This is in count not bytes).
 			table_size: 4096,
 			br_table_size: 256,
 			subject_len: 32,
 			payload_len: 16 * 1024,
+			event_data_len: 16 * 1024,
 		}
 	}
 }
 
-impl<T: Config> Default for InstructionWeights<T> {
+impl Default for ValidationRules {
 	fn default() -> Self {
-		let max_pages = Limits::default().memory_pages;
 		Self {
-			version: 4,
-			fallback: 0,
-			i64const: cost_instr!(instr_i64const, 1),
-			i64load: cost_instr!(instr_i64load, 2),
-			i64store: cost_instr!(instr_i64store, 2),
-			select: cost_instr!(instr_select, 4),
-			r#if: cost_instr!(instr_if, 3),
-			br: cost_instr!(instr_br, 2),
-			br_if: cost_instr!(instr_br_if, 3),
-			br_table: cost_instr!(instr_br_table, 3),
-			br_table_per_entry: cost_instr!(instr_br_table_per_entry, 0),
-			call: cost_instr!(instr_call, 2),
-			call_indirect: cost_instr!(instr_call_indirect, 3),
-			call_indirect_per_param: cost_instr!(instr_call_indirect_per_param, 1),
-			call_per_local: cost_instr!(instr_call_per_local, 1),
-			local_get: cost_instr!(instr_local_get, 1),
-			local_set: cost_instr!(instr_local_set, 1),
-			local_tee: cost_instr!(instr_local_tee, 2),
-			global_get: cost_instr!(instr_global_get, 1),
-			global_set: cost_instr!(instr_global_set, 1),
-			memory_current: cost_instr!(instr_memory_current, 1),
-			memory_grow: cost_instr_with_batch_size!(instr_memory_grow, 1, max_pages),
-			i64clz: cost_instr!(instr_i64clz, 2),
-			i64ctz: cost_instr!(instr_i64ctz, 2),
-			i64popcnt: cost_instr!(instr_i64popcnt, 2),
-			i64eqz: cost_instr!(instr_i64eqz, 2),
-			i64extendsi32: cost_instr!(instr_i64extendsi32, 2),
-			i64extendui32: cost_instr!(instr_i64extendui32, 2),
-			i32wrapi64: cost_instr!(instr_i32wrapi64, 2),
-			i64eq: cost_instr!(instr_i64eq, 3),
-			i64ne: cost_instr!(instr_i64ne, 3),
-			i64lts: cost_instr!(instr_i64lts, 3),
-			i64ltu: cost_instr!(instr_i64ltu, 3),
-			i64gts: cost_instr!(instr_i64gts, 3),
-			i64gtu: cost_instr!(instr_i64gtu, 3),
-			i64les: cost_instr!(instr_i64les, 3),
-			i64leu: cost_instr!(instr_i64leu, 3),
-			i64ges: cost_instr!(instr_i64ges, 3),
-			i64geu: cost_instr!(instr_i64geu, 3),
-			i64add: cost_instr!(instr_i64add, 3),
-			i64sub: cost_instr!(instr_i64sub, 3),
-			i64mul: cost_instr!(instr_i64mul, 3),
-			i64divs: cost_instr!(instr_i64divs, 3),
-			i64divu: cost_instr!(instr_i64divu, 3),
-			i64rems: cost_instr!(instr_i64rems, 3),
-			i64remu: cost_instr!(instr_i64remu, 3),
-			i64and: cost_instr!(instr_i64and, 3),
-			i64or: cost_instr!(instr_i64or, 3),
-			i64xor: cost_instr!(instr_i64xor, 3),
-			i64shl: cost_instr!(instr_i64shl, 3),
-			i64shrs: cost_instr!(instr_i64shrs, 3),
-			i64shru: cost_instr!(instr_i64shru, 3),
-			i64rotl: cost_instr!(instr_i64rotl, 3),
-			i64rotr: cost_instr!(instr_i64rotr, 3),
-			_phantom: PhantomData,
+			sign_extension: false,
+			relaxed_floats: true,
+			memory_maximum_required: true,
+			forbidden_imports: Vec::new(),
 		}
 	}
 }
 
+impl<T: Config> Default for InstructionWeights<T> {
+	fn default() -> Self {
+		Self { version: 5, base: cost_instr!(instr_i64const, 1), _phantom: PhantomData }
+	}
+}
+
 impl<T: Config> Default for HostFnWeights<T> {
 	/// PoV should contain all trie nodes that are read during state transition (i.e. block
 	/// production). Hence we need to charge the `proof_size` weight for every host function which
@@ -610,22 +574,35 @@ impl<T: Config> Default for HostFnWeights<T> {
 	/// The last two functions write to storage, but they also do read storage in order to return
 	/// the size of the pre-existed value. Till we have PoV benchmarks implemented, we approximate
 	/// `proof_size` as being equal to the size of storage read.
+	///
+	/// On top of the value itself every access needs to prove the key it reads. This is why
+	/// the base weight of these functions includes the maximum key length. The same applies to
+	/// host functions that read the bookkeeping of other contracts or codes: `is_contract`,
+	/// `code_hash`, `call`, `delegate_call`, `instantiate`, `set_code_hash` and the delegate
+	/// dependency functions charge the maximum encoded length of what they read.
 	fn default() -> Self {
+		let key = u64::from(T::MaxStorageKeyLen::get());
+		let contract_info = ContractInfo::<T>::max_encoded_len() as u64;
+		let code_info = CodeInfo::<T>::max_encoded_len() as u64;
+		let nonce = u64::max_encoded_len() as u64;
 		Self {
 			caller: to_weight!(cost_batched!(seal_caller)),
-			is_contract: to_weight!(cost_batched!(seal_is_contract)),
-			code_hash: to_weight!(cost_batched!(seal_code_hash)),
+			is_contract: to_weight!(cost_batched!(seal_is_contract), contract_info),
+			code_hash: to_weight!(cost_batched!(seal_code_hash), contract_info),
 			own_code_hash: to_weight!(cost_batched!(seal_own_code_hash)),
 			caller_is_origin: to_weight!(cost_batched!(seal_caller_is_origin)),
+			// Does the same amount of work as `caller_is_origin`.
+			caller_is_root: to_weight!(cost_batched!(seal_caller_is_origin)),
 			address: to_weight!(cost_batched!(seal_address)),
 			gas_left: to_weight!(cost_batched!(seal_gas_left)),
 			balance: to_weight!(cost_batched!(seal_balance)),
 			value_transferred: to_weight!(cost_batched!(seal_value_transferred)),
+			// Does the same amount of work as `value_transferred`.
+			last_subcall_deposit: to_weight!(cost_batched!(seal_value_transferred)),
 			minimum_balance: to_weight!(cost_batched!(seal_minimum_balance)),
 			block_number: to_weight!(cost_batched!(seal_block_number)),
 			now: to_weight!(cost_batched!(seal_now)),
 			weight_to_fee: to_weight!(cost_batched!(seal_weight_to_fee)),
-			gas: to_weight!(cost_batched!(seal_gas)),
 			input: to_weight!(cost_batched!(seal_input)),
 			input_per_byte: to_weight!(cost_byte_batched!(seal_input_per_kb)),
 			r#return: to_weight!(cost!(seal_return)),
@@ -644,27 +621,27 @@ impl<T: Config> Default for HostFnWeights<T> {
 				1
 			)),
 			debug_message: to_weight!(cost_batched!(seal_debug_message)),
-			set_storage: to_weight!(cost_batched!(seal_set_storage)),
-			set_code_hash: to_weight!(cost_batched!(seal_set_code_hash)),
+			set_storage: to_weight!(cost_batched!(seal_set_storage), key),
+			set_code_hash: to_weight!(cost_batched!(seal_set_code_hash), code_info),
 			set_storage_per_new_byte: to_weight!(cost_byte_batched!(seal_set_storage_per_new_kb)),
 			set_storage_per_old_byte: to_weight!(
 				cost_byte_batched!(seal_set_storage_per_old_kb),
 				1u64
 			),
-			clear_storage: to_weight!(cost_batched!(seal_clear_storage)),
+			clear_storage: to_weight!(cost_batched!(seal_clear_storage), key),
 			clear_storage_per_byte: to_weight!(cost_byte_batched!(seal_clear_storage_per_kb), 1u64),
-			contains_storage: to_weight!(cost_batched!(seal_contains_storage)),
+			contains_storage: to_weight!(cost_batched!(seal_contains_storage), key),
 			contains_storage_per_byte: to_weight!(
 				cost_byte_batched!(seal_contains_storage_per_kb),
 				1u64
 			),
-			get_storage: to_weight!(cost_batched!(seal_get_storage)),
+			get_storage: to_weight!(cost_batched!(seal_get_storage), key),
 			get_storage_per_byte: to_weight!(cost_byte_batched!(seal_get_storage_per_kb), 1u64),
-			take_storage: to_weight!(cost_batched!(seal_take_storage)),
+			take_storage: to_weight!(cost_batched!(seal_take_storage), key),
 			take_storage_per_byte: to_weight!(cost_byte_batched!(seal_take_storage_per_kb), 1u64),
 			transfer: to_weight!(cost_batched!(seal_transfer)),
-			call: to_weight!(cost_batched!(seal_call)),
-			delegate_call: to_weight!(cost_batched!(seal_delegate_call)),
+			call: to_weight!(cost_batched!(seal_call), contract_info + code_info),
+			delegate_call: to_weight!(cost_batched!(seal_delegate_call), code_info),
 			call_transfer_surcharge: to_weight!(cost_batched_args!(
 				seal_call_per_transfer_clone_kb,
 				1,
@@ -675,7 +652,10 @@ impl<T: Config> Default for HostFnWeights<T> {
 				0,
 				1
 			)),
-			instantiate: to_weight!(cost_batched!(seal_instantiate)),
+			instantiate: to_weight!(
+				cost_batched!(seal_instantiate),
+				contract_info + code_info + nonce
+			),
 			instantiate_transfer_surcharge: to_weight!(cost_byte_batched_args!(
 				seal_instantiate_per_transfer_input_salt_kb,
 				1,
@@ -707,137 +687,18 @@ impl<T: Config> Default for HostFnWeights<T> {
 			reentrance_count: to_weight!(cost_batched!(seal_reentrance_count)),
 			account_reentrance_count: to_weight!(cost_batched!(seal_account_reentrance_count)),
 			instantiation_nonce: to_weight!(cost_batched!(seal_instantiation_nonce)),
+			storage_deposit_limit_left: to_weight!(cost_batched!(seal_storage_deposit_limit_left)),
+			set_deny_reentry: to_weight!(cost_batched!(seal_set_deny_reentry)),
+			lock_delegate_dependency: to_weight!(cost!(seal_lock_delegate_dependency), code_info),
+			unlock_delegate_dependency: to_weight!(
+				cost!(seal_unlock_delegate_dependency),
+				code_info
+			),
 			_phantom: PhantomData,
 		}
 	}
 }
 
-struct ScheduleRules<'a, T: Config> {
-	schedule: &'a Schedule<T>,
-	params: Vec<u32>,
-	determinism: Determinism,
-}
-
-impl<T: Config> Schedule<T> {
-	pub(crate) fn rules(
-		&self,
-		module: &elements::Module,
-		determinism: Determinism,
-	) -> impl gas_metering::Rules + '_ {
-		ScheduleRules {
-			schedule: self,
-			params: module
-				.type_section()
-				.iter()
-				.flat_map(|section| section.types())
-				.map(|func| {
-					let elements::Type::Function(func) = func;
-					func.params().len() as u32
-				})
-				.collect(),
-			determinism,
-		}
-	}
-}
-
-impl<'a, T: Config> gas_metering::Rules for ScheduleRules<'a, T> {
-	fn instruction_cost(&self, instruction: &elements::Instruction) -> Option<u32> {
-		use self::elements::Instruction::*;
-		let w = &self.schedule.instruction_weights;
-		let max_params = self.schedule.limits.parameters;
-
-		let weight = match *instruction {
-			End | Unreachable | Return | Else => 0,
-			I32Const(_) | I64Const(_) | Block(_) | Loop(_) | Nop | Drop => w.i64const,
-			I32Load(_, _) |
-			I32Load8S(_, _) |
-			I32Load8U(_, _) |
-			I32Load16S(_, _) |
-			I32Load16U(_, _) |
-			I64Load(_, _) |
-			I64Load8S(_, _) |
-			I64Load8U(_, _) |
-			I64Load16S(_, _) |
-			I64Load16U(_, _) |
-			I64Load32S(_, _) |
-			I64Load32U(_, _) => w.i64load,
-			I32Store(_, _) |
-			I32Store8(_, _) |
-			I32Store16(_, _) |
-			I64Store(_, _) |
-			I64Store8(_, _) |
-			I64Store16(_, _) |
-			I64Store32(_, _) => w.i64store,
-			Select => w.select,
-			If(_) => w.r#if,
-			Br(_) => w.br,
-			BrIf(_) => w.br_if,
-			Call(_) => w.call,
-			GetLocal(_) => w.local_get,
-			SetLocal(_) => w.local_set,
-			TeeLocal(_) => w.local_tee,
-			GetGlobal(_) => w.global_get,
-			SetGlobal(_) => w.global_set,
-			CurrentMemory(_) => w.memory_current,
-			GrowMemory(_) => w.memory_grow,
-			CallIndirect(idx, _) => *self.params.get(idx as usize).unwrap_or(&max_params),
-			BrTable(ref data) => w
-				.br_table
-				.saturating_add(w.br_table_per_entry.saturating_mul(data.table.len() as u32)),
-			I32Clz | I64Clz => w.i64clz,
-			I32Ctz | I64Ctz => w.i64ctz,
-			I32Popcnt | I64Popcnt => w.i64popcnt,
-			I32Eqz | I64Eqz => w.i64eqz,
-			I64ExtendSI32 => w.i64extendsi32,
-			I64ExtendUI32 => w.i64extendui32,
-			I32WrapI64 => w.i32wrapi64,
-			I32Eq | I64Eq => w.i64eq,
-			I32Ne | I64Ne => w.i64ne,
-			I32LtS | I64LtS => w.i64lts,
-			I32LtU | I64LtU => w.i64ltu,
-			I32GtS | I64GtS => w.i64gts,
-			I32GtU | I64GtU => w.i64gtu,
-			I32LeS | I64LeS => w.i64les,
-			I32LeU | I64LeU => w.i64leu,
-			I32GeS | I64GeS => w.i64ges,
-			I32GeU | I64GeU => w.i64geu,
-			I32Add | I64Add => w.i64add,
-			I32Sub | I64Sub => w.i64sub,
-			I32Mul | I64Mul => w.i64mul,
-			I32DivS | I64DivS => w.i64divs,
-			I32DivU | I64DivU => w.i64divu,
-			I32RemS | I64RemS => w.i64rems,
-			I32RemU | I64RemU => w.i64remu,
-			I32And | I64And => w.i64and,
-			I32Or | I64Or => w.i64or,
-			I32Xor | I64Xor => w.i64xor,
-			I32Shl | I64Shl => w.i64shl,
-			I32ShrS | I64ShrS => w.i64shrs,
-			I32ShrU | I64ShrU => w.i64shru,
-			I32Rotl | I64Rotl => w.i64rotl,
-			I32Rotr | I64Rotr => w.i64rotr,
-
-			// Returning None makes the gas instrumentation fail which we intend for
-			// unsupported or unknown instructions. Offchain we might allow indeterminism and hence
-			// use the fallback weight for those instructions.
-			_ if matches!(self.determinism, Determinism::AllowIndeterminism) && w.fallback > 0 =>
-				w.fallback,
-			_ => return None,
-		};
-		Some(weight)
-	}
-
-	fn memory_grow_cost(&self) -> gas_metering::MemoryGrowCost {
-		// We benchmarked the memory.grow instruction with the maximum allowed pages.
-		// The cost for growing is therefore already included in the instruction cost.
-		gas_metering::MemoryGrowCost::Free
-	}
-
-	fn call_per_local_cost(&self) -> u32 {
-		self.schedule.instruction_weights.call_per_local
-	}
-}
-
 #[cfg(test)]
 mod test {
 	use super::*;
@@ -848,4 +709,29 @@ mod test {
 		let schedule = Schedule::<Test>::default();
 		println!("{:#?}", schedule);
 	}
+
+	#[test]
+	fn storage_accessing_host_fns_charge_proof_size() {
+		let host_fn_weights = Schedule::<Test>::default().host_fn_weights;
+		for weight in [
+			host_fn_weights.is_contract,
+			host_fn_weights.code_hash,
+			host_fn_weights.set_storage,
+			host_fn_weights.set_code_hash,
+			host_fn_weights.clear_storage,
+			host_fn_weights.contains_storage,
+			host_fn_weights.get_storage,
+			host_fn_weights.take_storage,
+			host_fn_weights.call,
+			host_fn_weights.delegate_call,
+			host_fn_weights.instantiate,
+			host_fn_weights.lock_delegate_dependency,
+			host_fn_weights.unlock_delegate_dependency,
+		] {
+			assert!(weight.proof_size() > 0);
+		}
+		// Functions which don't touch storage stay free of proof size.
+		assert_eq!(host_fn_weights.caller.proof_size(), 0);
+		assert_eq!(host_fn_weights.hash_blake2_256.proof_size(), 0);
+	}
 }
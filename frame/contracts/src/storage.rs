@@ -22,13 +22,15 @@ pub mod meter;
 use crate::{
 	exec::{AccountIdOf, StorageKey},
 	weights::WeightInfo,
-	BalanceOf, CodeHash, Config, ContractInfoOf, DeletionQueue, Error, TrieId, SENTINEL,
+	AddressGenerator, BalanceOf, CodeHash, Config, ContractInfoOf, DeletionQueue, Error, TrieId,
+	SENTINEL,
 };
 use codec::{Decode, Encode, MaxEncodedLen};
 use frame_support::{
 	dispatch::{DispatchError, DispatchResult},
 	storage::child::{self, ChildInfo},
 	weights::Weight,
+	BoundedBTreeMap, BoundedVec, CloneNoBound, EqNoBound, PartialEqNoBound, RuntimeDebugNoBound,
 };
 use scale_info::TypeInfo;
 use sp_io::KillStorageResult;
@@ -36,7 +38,48 @@ use sp_runtime::{
 	traits::{Hash, Saturating, Zero},
 	RuntimeDebug,
 };
-use sp_std::{marker::PhantomData, prelude::*};
+use sp_std::{marker::PhantomData, ops::Deref, prelude::*};
+
+/// Identifies a dispatchable by the index of its pallet and its index within that pallet.
+///
+/// These are the first two bytes of the SCALE encoding of a runtime call.
+pub type CallIndex = [u8; 2];
+
+/// The dispatchables a single contract is allowed to call through `seal_call_runtime`.
+pub type CallFilter<T> = BoundedVec<CallIndex, <T as Config>::MaxCallFilterLen>;
+
+/// The code hashes a single contract locked through `seal_lock_delegate_dependency`.
+///
+/// Encoded exactly like a `BoundedBTreeSet` of the code hashes, which is how they were stored
+/// before.
+pub type DelegateDependencies<T> =
+	BoundedBTreeMap<CodeHash<T>, (), <T as Config>::MaxDelegateDependencies>;
+
+/// Execution statistics of a single code hash.
+///
+/// Only collected if [`Config::CollectCodeStats`] is enabled. Every call, instantiation or
+/// delegate call into the code counts as one execution. Statistics of an execution are rolled
+/// back together with any other changes if one of its callers fails.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct CodeStats {
+	/// The number of executions of this code.
+	pub calls: u64,
+	/// The number of executions that reverted or trapped.
+	pub reverts: u64,
+	/// The gas consumed by all executions, including the gas consumed by their sub calls.
+	pub gas_consumed: Weight,
+}
+
+/// The most storage a single contract is allowed to hold.
+///
+/// See [`Config::MaxStoragePerContract`].
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct StorageCeiling {
+	/// The maximum number of bytes of all storage items together.
+	pub bytes: u32,
+	/// The maximum number of storage items.
+	pub items: u32,
+}
 
 /// Information for managing an account and its sub trie abstraction.
 /// This is the required info to cache for an account.
@@ -45,6 +88,10 @@ use sp_std::{marker::PhantomData, prelude::*};
 pub struct ContractInfo<T: Config> {
 	/// Unique ID for the subtree encoded as a bytes vector.
 	pub trie_id: TrieId,
+	/// The account that holds this contract's storage deposit.
+	///
+	/// This is held in a separate account to prevent the contract from spending it.
+	pub deposit_account: DepositAccount<T>,
 	/// The code associated with a given account.
 	pub code_hash: CodeHash<T>,
 	/// How many bytes of storage are accumulated in this contract's child trie.
@@ -60,9 +107,41 @@ pub struct ContractInfo<T: Config> {
 	/// We need to store this information separately so it is not used when calculating any refunds
 	/// since the base deposit can only ever be refunded on contract termination.
 	pub storage_base_deposit: BalanceOf<T>,
+	/// The dispatchables this contract is allowed to call through `seal_call_runtime`.
+	///
+	/// `None` means that only [`Config::CallFilter`] applies. Otherwise a call needs to pass
+	/// both filters.
+	pub call_filter: Option<CallFilter<T>>,
+	/// Whether calls into this contract are rejected.
+	///
+	/// See [`crate::Pallet::pause_contract`].
+	pub paused: bool,
+	/// Whether calls into this contract are rejected while it is already on the call stack.
+	///
+	/// This applies even if the caller allows reentry. It is set by the contract itself.
+	pub deny_reentry: bool,
+	/// The code hashes this contract delegates to and which therefore can't be removed.
+	///
+	/// Every entry holds a reference on its code just like the contract's own `code_hash`.
+	pub delegate_dependencies: DelegateDependencies<T>,
 }
 
 impl<T: Config> ContractInfo<T> {
+	/// Load the contract info of `account` from storage.
+	pub fn load(account: &AccountIdOf<T>) -> Option<Self> {
+		<ContractInfoOf<T>>::get(account)
+	}
+
+	/// Whether the contract's own call filter allows it to dispatch `call`.
+	pub fn call_allowed(&self, call: &impl Encode) -> bool {
+		let Some(filter) = &self.call_filter else { return true };
+		call.using_encoded(|encoded| {
+			encoded
+				.get(..2)
+				.map_or(false, |index| filter.iter().any(|allowed| allowed == index))
+		})
+	}
+
 	/// Associated child trie unique id is built from the hash part of the trie id.
 	#[cfg(test)]
 	pub fn child_trie_info(&self) -> ChildInfo {
@@ -80,14 +159,51 @@ impl<T: Config> ContractInfo<T> {
 	}
 }
 
+/// The account that holds the storage deposit of a contract.
+///
+/// It is derived from the contract's address using [`AddressGenerator::deposit_address`].
+/// Nobody owns the private key of this account. Balance only ever leaves it as a refund of
+/// storage deposit.
+#[derive(
+	Encode,
+	Decode,
+	CloneNoBound,
+	PartialEqNoBound,
+	EqNoBound,
+	RuntimeDebugNoBound,
+	TypeInfo,
+	MaxEncodedLen,
+)]
+#[scale_info(skip_type_params(T))]
+pub struct DepositAccount<T: Config>(AccountIdOf<T>);
+
+impl<T: Config> DepositAccount<T> {
+	/// Derive the deposit account of the contract at `contract`.
+	pub fn new(contract: &AccountIdOf<T>) -> Self {
+		Self(T::AddressGenerator::deposit_address(contract))
+	}
+}
+
+impl<T: Config> Deref for DepositAccount<T> {
+	type Target = AccountIdOf<T>;
+
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
 /// Associated child trie unique id is built from the hash part of the trie id.
 fn child_trie_info(trie_id: &[u8]) -> ChildInfo {
 	ChildInfo::new_default(trie_id)
 }
 
 #[derive(Encode, Decode, TypeInfo, MaxEncodedLen)]
-pub struct DeletedContract {
+pub struct DeletedContract<AccountId> {
 	pub(crate) trie_id: TrieId,
+	/// The address of the terminated contract.
+	///
+	/// `None` for contracts that were queued before the address was recorded.
+	pub(crate) account_id: Option<AccountId>,
 }
 
 /// Information about what happended to the pre-existing value when calling [`Storage::write`].
@@ -151,6 +267,45 @@ impl<T: Config> Storage<T> {
 		child::len(&child_trie_info(trie_id), key.hash().as_slice())
 	}
 
+	/// Returns up to `count` keys of a contract's child trie that start with `prefix`.
+	///
+	/// The keys are returned in lexicographic order and as they are stored in the trie, i.e.
+	/// already hashed. Iteration begins right after `start_key` which allows to fetch the
+	/// next page by passing the last key of the previous one. If `start_key` is `None` (or
+	/// lies before `prefix`) iteration begins at `prefix` itself.
+	pub fn keys_paged(
+		trie_id: &TrieId,
+		prefix: &[u8],
+		start_key: Option<Vec<u8>>,
+		count: u32,
+	) -> Vec<Vec<u8>> {
+		let child_info = child_trie_info(trie_id);
+		let count = count as usize;
+		let mut keys = Vec::new();
+		let mut previous_key = match start_key {
+			Some(start_key) if start_key.as_slice() >= prefix => start_key,
+			_ => {
+				// `next_key` never returns the key it is passed.
+				if count > 0 && child::exists(&child_info, prefix) {
+					keys.push(prefix.to_vec());
+				}
+				prefix.to_vec()
+			},
+		};
+		while keys.len() < count {
+			match sp_io::default_child_storage::next_key(child_info.storage_key(), &previous_key)
+				.filter(|key| key.starts_with(prefix))
+			{
+				Some(key) => {
+					keys.push(key.clone());
+					previous_key = key;
+				},
+				None => break,
+			}
+		}
+		keys
+	}
+
 	/// Update a storage entry into a contract's kv storage.
 	///
 	/// If the `new_value` is `None` then the kv pair is removed. If `take` is true
@@ -224,11 +379,16 @@ impl<T: Config> Storage<T> {
 		let contract = ContractInfo::<T> {
 			code_hash,
 			trie_id,
+			deposit_account: DepositAccount::new(account),
 			storage_bytes: 0,
 			storage_items: 0,
 			storage_byte_deposit: Zero::zero(),
 			storage_item_deposit: Zero::zero(),
 			storage_base_deposit: Zero::zero(),
+			call_filter: None,
+			paused: false,
+			deny_reentry: false,
+			delegate_dependencies: Default::default(),
 		};
 
 		Ok(contract)
@@ -237,9 +397,23 @@ impl<T: Config> Storage<T> {
 	/// Push a contract's trie to the deletion queue for lazy removal.
 	///
 	/// You must make sure that the contract is also removed when queuing the trie for deletion.
-	pub fn queue_trie_for_deletion(contract: &ContractInfo<T>) -> DispatchResult {
-		<DeletionQueue<T>>::try_append(DeletedContract { trie_id: contract.trie_id.clone() })
-			.map_err(|_| <Error<T>>::DeletionQueueFull.into())
+	pub fn queue_trie_for_deletion(
+		contract: &ContractInfo<T>,
+		account_id: &AccountIdOf<T>,
+	) -> DispatchResult {
+		<DeletionQueue<T>>::try_append(DeletedContract {
+			trie_id: contract.trie_id.clone(),
+			account_id: Some(account_id.clone()),
+		})
+		.map_err(|_| <Error<T>>::DeletionQueueFull.into())
+	}
+
+	/// Returns whether the storage of the terminated contract at `account_id` is still being
+	/// deleted.
+	pub fn is_queued_for_deletion(account_id: &AccountIdOf<T>) -> bool {
+		<DeletionQueue<T>>::get()
+			.iter()
+			.any(|contract| contract.account_id.as_ref() == Some(account_id))
 	}
 
 	/// Calculates the weight that is necessary to remove one key from the trie and how many
@@ -323,15 +497,15 @@ impl<T: Config> Storage<T> {
 
 	/// Returns the code hash of the contract specified by `account` ID.
 	pub fn code_hash(account: &AccountIdOf<T>) -> Option<CodeHash<T>> {
-		<ContractInfoOf<T>>::get(account).map(|i| i.code_hash)
+		ContractInfo::<T>::load(account).map(|i| i.code_hash)
 	}
 
 	/// Fill up the queue in order to exercise the limits during testing.
 	#[cfg(test)]
 	pub fn fill_queue_with_dummies() {
 		use frame_support::{traits::Get, BoundedVec};
-		let queue: Vec<DeletedContract> = (0..T::DeletionQueueDepth::get())
-			.map(|_| DeletedContract { trie_id: TrieId::default() })
+		let queue: Vec<DeletedContract<AccountIdOf<T>>> = (0..T::DeletionQueueDepth::get())
+			.map(|_| DeletedContract { trie_id: TrieId::default(), account_id: None })
 			.collect();
 		let bounded: BoundedVec<_, _> = queue.try_into().map_err(|_| ()).unwrap();
 		<DeletionQueue<T>>::put(bounded);
@@ -17,23 +17,27 @@
 
 //! This module contains functions to meter the storage deposit.
 
-use crate::{storage::ContractInfo, BalanceOf, Config, Error, Inspect, Pallet};
-use codec::Encode;
+use crate::{
+	storage::{ContractInfo, DepositAccount},
+	wasm::PrefabWasmModule,
+	BalanceOf, CallOrigin, Config, Error, Inspect, Pallet, TransactionDepositLimit,
+};
+use codec::{Decode, Encode, HasCompact};
 use frame_support::{
-	dispatch::DispatchError,
+	dispatch::{DispatchError, DispatchResult},
 	ensure,
-	traits::{
-		tokens::{BalanceStatus, WithdrawConsequence},
-		Currency, ExistenceRequirement, Get, ReservableCurrency,
-	},
+	traits::{tokens::WithdrawConsequence, Currency, ExistenceRequirement, Get, IsSubType},
+	weights::Weight,
 	DefaultNoBound, RuntimeDebugNoBound,
 };
 use pallet_contracts_primitives::StorageDeposit as Deposit;
+use scale_info::TypeInfo;
 use sp_runtime::{
-	traits::{Saturating, Zero},
+	traits::{DispatchInfoOf, PostDispatchInfoOf, Saturating, SignedExtension, Zero},
+	transaction_validity::{InvalidTransaction, TransactionValidity, TransactionValidityError},
 	FixedPointNumber, FixedU128,
 };
-use sp_std::{marker::PhantomData, vec::Vec};
+use sp_std::{fmt, marker::PhantomData, vec::Vec};
 
 /// Deposit that uses the native currency's balance type.
 pub type DepositOf<T> = Deposit<BalanceOf<T>>;
@@ -70,16 +74,16 @@ pub trait Ext<T: Config> {
 		min_leftover: BalanceOf<T>,
 	) -> Result<BalanceOf<T>, DispatchError>;
 	/// This is called to inform the implementer that some balance should be charged due to
-	/// some interaction of the `origin` with a `contract`.
+	/// some interaction of the `origin` with a contract.
 	///
-	/// The balance transfer can either flow from `origin` to `contract` or the other way
-	/// around depending on whether `amount` constitutes a `Charge` or a `Refund`.
-	/// It is guaranteed that that this succeeds because no more balance than returned by
-	/// `check_limit` is ever charged. This is why this function is infallible.
-	/// `terminated` designates whether the `contract` was terminated.
+	/// The balance transfer can either flow from `origin` to the contract's `deposit_account`
+	/// or the other way around depending on whether `amount` constitutes a `Charge` or a
+	/// `Refund`. It is guaranteed that that this succeeds because no more balance than returned
+	/// by `check_limit` is ever charged. This is why this function is infallible.
+	/// `terminated` designates whether the contract was terminated.
 	fn charge(
 		origin: &T::AccountId,
-		contract: &T::AccountId,
+		deposit_account: &T::AccountId,
 		amount: &DepositOf<T>,
 		terminated: bool,
 	);
@@ -87,7 +91,8 @@ pub trait Ext<T: Config> {
 
 /// This [`Ext`] is used for actual on-chain execution when balance needs to be charged.
 ///
-/// It uses [`ReservableCurrency`] in order to do accomplish the reserves.
+/// It transfers the deposit to and from the [`DepositAccount`] of a contract. Nothing is
+/// reserved so that the deposit can't interfere with reserves made by other pallets.
 pub enum ReservingExt {}
 
 /// Used to implement a type state pattern for the meter.
@@ -109,6 +114,11 @@ impl State for Nested {}
 pub struct RawMeter<T: Config, E, S: State> {
 	/// The limit of how much balance this meter is allowed to consume.
 	limit: BalanceOf<T>,
+	/// True if the limit was explicitly requested for the sub call this meter belongs to.
+	///
+	/// Such a limit is enforced as soon as the sub call returns. See
+	/// [`RawMeter::enforce_subcall_limit`].
+	own_limit: bool,
 	/// The amount of balance that was used in this meter and all of its already absorbed children.
 	total_deposit: DepositOf<T>,
 	/// The amount of storage changes that were recorded in this meter alone.
@@ -133,6 +143,8 @@ pub struct Diff {
 	pub items_added: u32,
 	/// How many storage items were removed from storage.
 	pub items_removed: u32,
+	/// How many bytes of event data were emitted.
+	pub event_bytes: u32,
 }
 
 impl Diff {
@@ -151,6 +163,7 @@ impl Diff {
 		let items_added = self.items_added.saturating_sub(self.items_removed);
 		let mut bytes_deposit = Deposit::Charge(per_byte.saturating_mul((bytes_added).into()));
 		let mut items_deposit = Deposit::Charge(per_item.saturating_mul((items_added).into()));
+		let event_deposit = T::DepositPerEventByte::get().saturating_mul(self.event_bytes.into());
 
 		// Without any contract info we can only calculate diffs which add storage
 		let info = if let Some(info) = info {
@@ -158,7 +171,9 @@ impl Diff {
 		} else {
 			debug_assert_eq!(self.bytes_removed, 0);
 			debug_assert_eq!(self.items_removed, 0);
-			return bytes_deposit.saturating_add(&items_deposit)
+			return bytes_deposit
+				.saturating_add(&items_deposit)
+				.saturating_add(&Deposit::Charge(event_deposit))
 		};
 
 		// Refunds are calculated pro rata based on the accumulated storage within the contract
@@ -193,7 +208,13 @@ impl Diff {
 				info.storage_item_deposit = info.storage_item_deposit.saturating_sub(*amount),
 		}
 
-		bytes_deposit.saturating_add(&items_deposit)
+		// Event data does not occupy contract storage. Its deposit is therefore not refunded
+		// when storage is removed but only once the contract is terminated.
+		info.storage_base_deposit = info.storage_base_deposit.saturating_add(event_deposit);
+
+		bytes_deposit
+			.saturating_add(&items_deposit)
+			.saturating_add(&Deposit::Charge(event_deposit))
 	}
 }
 
@@ -204,6 +225,7 @@ impl Diff {
 			bytes_removed: self.bytes_removed.saturating_add(rhs.bytes_removed),
 			items_added: self.items_added.saturating_add(rhs.items_added),
 			items_removed: self.items_removed.saturating_add(rhs.items_removed),
+			event_bytes: self.event_bytes.saturating_add(rhs.event_bytes),
 		}
 	}
 }
@@ -216,7 +238,7 @@ impl Diff {
 /// essentially makes the order of storage changes irrelevant with regard to the deposit system.
 #[derive(RuntimeDebugNoBound, Clone)]
 struct Charge<T: Config> {
-	contract: T::AccountId,
+	deposit_account: DepositAccount<T>,
 	amount: DepositOf<T>,
 	terminated: bool,
 }
@@ -262,16 +284,24 @@ where
 	/// This is called whenever a new subcall is initiated in order to track the storage
 	/// usage for this sub call separately. This is necessary because we want to exchange balance
 	/// with the current contract we are interacting with.
-	pub fn nested(&self) -> RawMeter<T, E, Nested> {
+	///
+	/// A non zero `limit` caps the child's limit further. It is clamped to what is remaining
+	/// so that a sub call can never consume more than its caller.
+	pub fn nested(&self, limit: BalanceOf<T>) -> RawMeter<T, E, Nested> {
 		debug_assert!(self.is_alive());
-		RawMeter { limit: self.available(), ..Default::default() }
+		let available = self.available();
+		if limit.is_zero() {
+			RawMeter { limit: available, ..Default::default() }
+		} else {
+			RawMeter { limit: available.min(limit), own_limit: true, ..Default::default() }
+		}
 	}
 
 	/// Absorb a child that was spawned to handle a sub call.
 	///
 	/// This should be called whenever a sub call comes to its end and it is **not** reverted.
-	/// This does the actual balance transfer from/to `origin` and `contract` based on the overall
-	/// storage consumption of the call. It also updates the supplied contract info.
+	/// This does the actual balance transfer from/to `origin` and `deposit_account` based on the
+	/// overall storage consumption of the call. It also updates the supplied contract info.
 	///
 	/// In case a contract reverted the child meter should just be dropped in order to revert
 	/// any changes it recorded.
@@ -279,28 +309,28 @@ where
 	/// # Parameters
 	///
 	/// - `absorbed`: The child storage meter that should be absorbed.
-	/// - `origin`: The origin that spawned the original root meter.
-	/// - `contract`: The contract that this sub call belongs to.
+	/// - `deposit_account`: The deposit account of the contract that this sub call belongs to.
 	/// - `info`: The info of the contract in question. `None` if the contract was terminated.
+	///
+	/// Returns the deposit consumed by the absorbed sub call including all of its children.
 	pub fn absorb(
 		&mut self,
 		absorbed: RawMeter<T, E, Nested>,
-		contract: &T::AccountId,
+		deposit_account: DepositAccount<T>,
 		info: Option<&mut ContractInfo<T>>,
-	) {
+	) -> DepositOf<T> {
 		let own_deposit = absorbed.own_contribution.update_contract(info);
-		self.total_deposit = self
-			.total_deposit
-			.saturating_add(&absorbed.total_deposit)
-			.saturating_add(&own_deposit);
+		let consumed = absorbed.total_deposit.saturating_add(&own_deposit);
+		self.total_deposit = self.total_deposit.saturating_add(&consumed);
 		if !own_deposit.is_zero() {
 			self.charges.extend_from_slice(&absorbed.charges);
 			self.charges.push(Charge {
-				contract: contract.clone(),
+				deposit_account,
 				amount: own_deposit,
 				terminated: absorbed.is_terminated(),
 			});
 		}
+		consumed
 	}
 
 	/// The amount of balance that is still available from the original `limit`.
@@ -328,12 +358,17 @@ where
 	/// Create new storage meter for the specified `origin` and `limit`.
 	///
 	/// This tries to [`Ext::check_limit`] on `origin` and fails if this is not possible.
+	/// Root has no account to take a deposit from. Its meter therefore has a limit of zero
+	/// regardless of the requested `limit`.
 	pub fn new(
-		origin: &T::AccountId,
+		origin: &CallOrigin<T>,
 		limit: Option<BalanceOf<T>>,
 		min_leftover: BalanceOf<T>,
 	) -> Result<Self, DispatchError> {
-		let limit = E::check_limit(origin, limit, min_leftover)?;
+		let limit = match origin {
+			CallOrigin::Signed(origin) => E::check_limit(origin, limit, min_leftover)?,
+			CallOrigin::Root => Zero::zero(),
+		};
 		Ok(Self { limit, ..Default::default() })
 	}
 
@@ -342,13 +377,18 @@ where
 	/// in the whole contract stack.
 	///
 	/// This drops the root meter in order to make sure it is only called when the whole
-	/// execution did finish.
-	pub fn into_deposit(self, origin: &T::AccountId) -> DepositOf<T> {
+	/// execution did finish. Nothing is charged for root: As its limit is zero the only
+	/// charges left are refunds. They stay in the deposit accounts of the contracts.
+	pub fn into_deposit(self, origin: &CallOrigin<T>) -> DepositOf<T> {
+		let origin = match origin {
+			CallOrigin::Signed(origin) => origin,
+			CallOrigin::Root => return self.total_deposit,
+		};
 		for charge in self.charges.iter().filter(|c| matches!(c.amount, Deposit::Refund(_))) {
-			E::charge(origin, &charge.contract, &charge.amount, charge.terminated);
+			E::charge(origin, &charge.deposit_account, &charge.amount, charge.terminated);
 		}
 		for charge in self.charges.iter().filter(|c| matches!(c.amount, Deposit::Charge(_))) {
-			E::charge(origin, &charge.contract, &charge.amount, charge.terminated);
+			E::charge(origin, &charge.deposit_account, &charge.amount, charge.terminated);
 		}
 		self.total_deposit
 	}
@@ -371,7 +411,8 @@ where
 
 	/// Charge from `origin` a storage deposit for contract instantiation.
 	///
-	/// This immediately transfers the balance in order to create the account.
+	/// This immediately transfers the balance in order to create the contract's account
+	/// and its deposit account.
 	pub fn charge_instantiate(
 		&mut self,
 		origin: &T::AccountId,
@@ -379,27 +420,32 @@ where
 		info: &mut ContractInfo<T>,
 	) -> Result<DepositOf<T>, DispatchError> {
 		debug_assert!(self.is_alive());
+		let ed = Deposit::Charge(Pallet::<T>::min_balance());
 		let mut deposit =
 			Diff { bytes_added: info.encoded_size() as u32, items_added: 1, ..Default::default() }
 				.update_contract::<T>(None);
 
 		// Instantiate needs to transfer the minimum balance at least in order to pull the
-		// contract's account into existence.
-		deposit = deposit.max(Deposit::Charge(Pallet::<T>::min_balance()));
-		if deposit.charge_or_zero() > self.limit {
-			return Err(<Error<T>>::StorageDepositLimitExhausted.into())
+		// deposit account into existence. Another minimum balance is needed for the contract's
+		// own account.
+		deposit = deposit.max(ed.clone());
+		let total_deposit = deposit.saturating_add(&ed);
+		if total_deposit.charge_or_zero() > self.limit {
+			return Err(<Error<T>>::StorageBaseDepositNotCovered.into())
 		}
 
 		// We do not increase `own_contribution` because this will be charged later when the
 		// contract execution does conclude and hence would lead to a double charge.
-		self.total_deposit = deposit.clone();
+		self.total_deposit = total_deposit.clone();
 		info.storage_base_deposit = deposit.charge_or_zero();
-		if !deposit.is_zero() {
-			// We need to charge immediately so that the account is created before the `value`
-			// is transferred from the caller to the contract.
-			E::charge(origin, contract, &deposit, false);
-		}
-		Ok(deposit)
+
+		// We need to charge immediately so that the accounts are created before the `value`
+		// is transferred from the caller to the contract. The minimum balance of the contract's
+		// account is not tracked as a deposit: It is sent to the beneficiary on termination.
+		E::charge(origin, &info.deposit_account, &deposit, false);
+		E::charge(origin, contract, &ed, false);
+
+		Ok(total_deposit)
 	}
 
 	/// Call to tell the meter that the currently executing contract was executed.
@@ -411,6 +457,61 @@ where
 		self.own_contribution = Contribution::Terminated(Deposit::Refund(info.total_deposit()));
 	}
 
+	/// The amount of balance that is still available from the original `limit` after taking
+	/// the storage changes recorded by this meter so far into account.
+	///
+	/// `info` must belong to the contract this meter is metering. It is only used to calculate
+	/// refunds and left unchanged.
+	pub fn available_with(&self, info: &ContractInfo<T>) -> BalanceOf<T> {
+		let own_deposit = self.own_contribution.update_contract(Some(&mut info.clone()));
+		self.total_deposit.saturating_add(&own_deposit).available(&self.limit)
+	}
+
+	/// Fail if applying the storage changes recorded by this meter would grow the contract
+	/// beyond [`Config::MaxStoragePerContract`].
+	///
+	/// `info` must belong to the contract this meter is metering. It is left unchanged.
+	pub fn enforce_storage_ceiling(
+		&self,
+		info: Option<&ContractInfo<T>>,
+	) -> Result<(), DispatchError> {
+		let (ceiling, info, diff) =
+			match (T::MaxStoragePerContract::get(), info, &self.own_contribution) {
+				(Some(ceiling), Some(info), Contribution::Alive(diff)) => (ceiling, info, diff),
+				_ => return Ok(()),
+			};
+		let mut after = info.clone();
+		diff.update_contract::<T>(Some(&mut after));
+		let exceeds = |before: u32, after: u32, max: u32| after > max && after > before;
+		if exceeds(info.storage_bytes, after.storage_bytes, ceiling.bytes) ||
+			exceeds(info.storage_items, after.storage_items, ceiling.items)
+		{
+			return Err(<Error<T>>::MaxStoragePerContractExceeded.into())
+		}
+		Ok(())
+	}
+
+	/// Enforce the limit of a sub call that was given its own limit by [`RawMeter::nested`].
+	///
+	/// This is a no-op for meters that just inherited what was remaining of their parent. Their
+	/// limit is only enforced by [`Self::enforce_limit`] at the end of the call stack. Unlike
+	/// that function this does not change the meter or the supplied `info`.
+	pub fn enforce_subcall_limit(
+		&self,
+		info: Option<&ContractInfo<T>>,
+	) -> Result<(), DispatchError> {
+		if !self.own_limit {
+			return Ok(())
+		}
+		let deposit = self.own_contribution.update_contract(info.cloned().as_mut());
+		if let Deposit::Charge(amount) = self.total_deposit.saturating_add(&deposit) {
+			if amount > self.limit {
+				return Err(<Error<T>>::StorageDepositLimitExhausted.into())
+			}
+		}
+		Ok(())
+	}
+
 	/// [`Self::charge`] does not enforce the storage limit since we want to do this check as late
 	/// as possible to allow later refunds to offset earlier charges.
 	///
@@ -455,17 +556,17 @@ impl<T: Config> Ext<T> for ReservingExt {
 
 	fn charge(
 		origin: &T::AccountId,
-		contract: &T::AccountId,
+		deposit_account: &T::AccountId,
 		amount: &DepositOf<T>,
 		terminated: bool,
 	) {
 		// There is nothing we can do when this fails as this constitutes a bug in the runtime:
-		// Either the runtime does not hold up the invariant of never deleting a contract's account
-		// or it does not honor reserved balances. We need to settle for emitting an error log
+		// Either the runtime does not hold up the invariant of never deleting a deposit account
+		// or someone moved balance out of it. We need to settle for emitting an error log
 		// in this case.
 		match amount {
 			Deposit::Charge(amount) => {
-				// This will never fail because a contract's account is required to exist
+				// This will never fail because a deposit account is required to exist
 				// at all times. The pallet enforces this invariant by depositing at least the
 				// existential deposit when instantiating and never refunds it unless the contract
 				// is removed. This means the receiver always exists except when instantiating a
@@ -474,16 +575,15 @@ impl<T: Config> Ext<T> for ReservingExt {
 				// balance when instantiating the storage meter.
 				let result = T::Currency::transfer(
 					origin,
-					contract,
+					deposit_account,
 					*amount,
 					ExistenceRequirement::KeepAlive,
-				)
-				.and_then(|_| T::Currency::reserve(contract, *amount));
+				);
 				if let Err(err) = result {
 					log::error!(
 						target: "runtime::contracts",
-						"Failed to transfer storage deposit {:?} from origin {:?} to contract {:?}: {:?}",
-						amount, origin, contract, err,
+						"Failed to transfer storage deposit {:?} from origin {:?} to deposit account {:?}: {:?}",
+						amount, origin, deposit_account, err,
 					);
 					if cfg!(debug_assertions) {
 						panic!("Unable to collect storage deposit. This is a bug.");
@@ -491,31 +591,35 @@ impl<T: Config> Ext<T> for ReservingExt {
 				}
 			},
 			// For `Refund(_)` no error happen because the initial value transfer from the
-			// origin to the contract has a keep alive existence requirement and when reserving we
-			// make sure to leave at least the ed in the free balance. Therefore the receiver always
-			// exists because there is no way for it to be removed in between. The sender always has
-			// enough reserved balance because we track it in the `ContractInfo` and never send more
-			// back than we have.
+			// origin to the contract has a keep alive existence requirement. Therefore the
+			// receiver always exists because there is no way for it to be removed in between.
+			// The sender always has enough balance because we track it in the `ContractInfo` and
+			// never send more back than we have.
 			Deposit::Refund(amount) => {
-				let amount = if terminated {
-					*amount
+				// The deposit tracked inside the contract info can be out of sync with the actual
+				// balance when a contract was migrated from reserving its deposit. We make sure to
+				// never dust the deposit account through a refund unless the contract is removed.
+				let (amount, existence_requirement) = if terminated {
+					(
+						*amount.min(&T::Currency::free_balance(deposit_account)),
+						ExistenceRequirement::AllowDeath,
+					)
 				} else {
-					// This is necessary when the `storage_deposit` tracked inside the account
-					// info is out of sync with the actual balance. That can only happen due to
-					// slashing. We make sure to never dust the contract's account through a
-					// refund because we consider this unexpected behaviour.
-					*amount.min(
-						&T::Currency::reserved_balance(contract)
-							.saturating_sub(Pallet::<T>::min_balance()),
+					(
+						*amount.min(
+							&T::Currency::free_balance(deposit_account)
+								.saturating_sub(Pallet::<T>::min_balance()),
+						),
+						ExistenceRequirement::KeepAlive,
 					)
 				};
 				let result =
-					T::Currency::repatriate_reserved(contract, origin, amount, BalanceStatus::Free);
-				if matches!(result, Ok(val) if !val.is_zero()) || matches!(result, Err(_)) {
+					T::Currency::transfer(deposit_account, origin, amount, existence_requirement);
+				if let Err(err) = result {
 					log::error!(
 						target: "runtime::contracts",
-						"Failed to repatriate storage deposit {:?} from contract {:?} to origin {:?}: {:?}",
-						amount, contract, origin, result,
+						"Failed to refund storage deposit {:?} from deposit account {:?} to origin {:?}: {:?}",
+						amount, deposit_account, origin, err,
 					);
 					if cfg!(debug_assertions) {
 						panic!("Unable to refund storage deposit. This is a bug.");
@@ -526,6 +630,120 @@ impl<T: Config> Ext<T> for ReservingExt {
 	}
 }
 
+/// Limit the storage deposit that a transaction can charge in total.
+///
+/// The limit covers all contract calls, instantiations and code uploads done by the transaction.
+/// This includes calls batched through [`Pallet::call_batch`](crate::Pallet::call_batch) or
+/// another pallet as well as all nested calls. The `storage_deposit_limit` of every call is
+/// lowered to what is left of this limit. This allows wallets to give a hard guarantee about
+/// the deposit regardless of the parameters of the individual calls.
+///
+/// Like the `storage_deposit_limit` of a single call, the limit bounds the net amount charged:
+/// refunds that a call receives are added back to what is left of the limit.
+///
+/// A transaction that uploads code is rejected by the transaction pool if the limit can't even
+/// cover the smallest deposit that this upload will be charged.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct CheckStorageDepositLimit<T: Config + Send + Sync>(pub Option<BalanceOf<T>>);
+
+impl<T: Config + Send + Sync> CheckStorageDepositLimit<T> {
+	/// Create a new extension that limits the storage deposit of the transaction to `limit`.
+	pub fn new(limit: Option<BalanceOf<T>>) -> Self {
+		Self(limit)
+	}
+
+	/// The smallest deposit that `call` will be charged for sure.
+	fn min_deposit(call: &<T as frame_system::Config>::RuntimeCall) -> BalanceOf<T>
+	where
+		<T as frame_system::Config>::RuntimeCall: IsSubType<crate::Call<T>>,
+		<BalanceOf<T> as HasCompact>::Type: Clone + Eq + PartialEq + fmt::Debug + TypeInfo + Encode,
+	{
+		match call.is_sub_type() {
+			Some(crate::Call::upload_code { code, .. }) |
+			Some(crate::Call::instantiate_with_code { code, .. }) |
+			Some(crate::Call::instantiate_with_code_old_weight { code, .. }) =>
+				PrefabWasmModule::<T>::min_upload_deposit(code),
+			_ => Zero::zero(),
+		}
+	}
+}
+
+impl<T: Config + Send + Sync> fmt::Debug for CheckStorageDepositLimit<T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "CheckStorageDepositLimit({:?})", self.0)
+	}
+}
+
+impl<T: Config + Send + Sync> SignedExtension for CheckStorageDepositLimit<T>
+where
+	<T as frame_system::Config>::RuntimeCall: IsSubType<crate::Call<T>>,
+	<BalanceOf<T> as HasCompact>::Type: Clone + Eq + PartialEq + fmt::Debug + TypeInfo + Encode,
+	BalanceOf<T>: Send + Sync,
+{
+	const IDENTIFIER: &'static str = "CheckStorageDepositLimit";
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::RuntimeCall;
+	type AdditionalSigned = ();
+	/// Whether a limit was put into storage.
+	type Pre = bool;
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		_who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		if let Some(limit) = self.0 {
+			ensure!(limit >= Self::min_deposit(call), InvalidTransaction::Payment);
+		}
+		Ok(Default::default())
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		self.validate(who, call, info, len)?;
+		if let Some(limit) = self.0 {
+			<TransactionDepositLimit<T>>::put(limit);
+		}
+		Ok(self.0.is_some())
+	}
+
+	fn post_dispatch(
+		pre: Option<Self::Pre>,
+		_info: &DispatchInfoOf<Self::Call>,
+		_post_info: &PostDispatchInfoOf<Self::Call>,
+		_len: usize,
+		_result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		if let Some(true) = pre {
+			<TransactionDepositLimit<T>>::kill();
+		}
+		Ok(())
+	}
+
+	fn weight(&self) -> Weight {
+		if self.0.is_some() {
+			// Reading the code info of an upload in `validate` as well as putting the limit in
+			// `pre_dispatch` and killing it in `post_dispatch`. Accesses during dispatch hit the
+			// overlay.
+			T::DbWeight::get().reads_writes(1, 2)
+		} else {
+			Weight::zero()
+		}
+	}
+}
+
 mod private {
 	pub trait Sealed {}
 	impl Sealed for super::Root {}
@@ -539,7 +757,7 @@ mod tests {
 		exec::AccountIdOf,
 		tests::{Test, ALICE, BOB, CHARLIE},
 	};
-	use frame_support::parameter_types;
+	use frame_support::{assert_err, assert_ok, parameter_types};
 	use pretty_assertions::assert_eq;
 
 	type TestMeter = RawMeter<Test, TestExt, Root>;
@@ -558,7 +776,7 @@ mod tests {
 	#[derive(Debug, PartialEq, Eq, Clone)]
 	struct Charge {
 		origin: AccountIdOf<Test>,
-		contract: AccountIdOf<Test>,
+		deposit_account: AccountIdOf<Test>,
 		amount: DepositOf<Test>,
 		terminated: bool,
 	}
@@ -592,14 +810,14 @@ mod tests {
 
 		fn charge(
 			origin: &AccountIdOf<Test>,
-			contract: &AccountIdOf<Test>,
+			deposit_account: &AccountIdOf<Test>,
 			amount: &DepositOf<Test>,
 			terminated: bool,
 		) {
 			TestExtTestValue::mutate(|ext| {
 				ext.charges.push(Charge {
 					origin: origin.clone(),
-					contract: contract.clone(),
+					deposit_account: deposit_account.clone(),
 					amount: amount.clone(),
 					terminated,
 				})
@@ -625,12 +843,17 @@ mod tests {
 
 		ContractInfo::<Test> {
 			trie_id: <Storage<Test>>::generate_trie_id(&ALICE, 42),
+			deposit_account: DepositAccount(ALICE),
 			code_hash: <Test as frame_system::Config>::Hashing::hash(b"42"),
 			storage_bytes: info.bytes,
 			storage_items: info.items,
 			storage_byte_deposit: info.bytes_deposit,
 			storage_item_deposit: info.items_deposit,
 			storage_base_deposit: Default::default(),
+			call_filter: None,
+			paused: false,
+			deny_reentry: false,
+			delegate_dependencies: Default::default(),
 		}
 	}
 
@@ -638,7 +861,7 @@ mod tests {
 	fn new_reserves_balance_works() {
 		clear_ext();
 
-		TestMeter::new(&ALICE, Some(1_000), 0).unwrap();
+		TestMeter::new(&CallOrigin::from_account_id(ALICE), Some(1_000), 0).unwrap();
 
 		assert_eq!(
 			TestExtTestValue::get(),
@@ -653,13 +876,14 @@ mod tests {
 	fn empty_charge_works() {
 		clear_ext();
 
-		let mut meter = TestMeter::new(&ALICE, Some(1_000), 0).unwrap();
+		let mut meter =
+			TestMeter::new(&CallOrigin::from_account_id(ALICE), Some(1_000), 0).unwrap();
 		assert_eq!(meter.available(), 1_000);
 
 		// an empty charge does not create a `Charge` entry
-		let mut nested0 = meter.nested();
+		let mut nested0 = meter.nested(0);
 		nested0.charge(&Default::default());
-		meter.absorb(nested0, &BOB, None);
+		meter.absorb(nested0, DepositAccount(BOB), None);
 
 		assert_eq!(
 			TestExtTestValue::get(),
@@ -670,40 +894,67 @@ mod tests {
 		)
 	}
 
+	#[test]
+	fn subcall_limit_works() {
+		clear_ext();
+
+		let meter = TestMeter::new(&CallOrigin::from_account_id(ALICE), Some(100), 0).unwrap();
+
+		// Without an own limit only the end of the call stack enforces the limit.
+		let mut nested0 = meter.nested(0);
+		nested0.charge(&Diff { bytes_added: 200, ..Default::default() });
+		assert_ok!(nested0.enforce_subcall_limit(None));
+
+		// The own limit is clamped to what is available.
+		let nested1 = nested0.nested(1_000);
+		assert_eq!(nested1.available(), 100);
+
+		let mut nested1 = meter.nested(10);
+		assert_eq!(nested1.available(), 10);
+		nested1.charge(&Diff { bytes_added: 10, ..Default::default() });
+		assert_ok!(nested1.enforce_subcall_limit(None));
+		nested1.charge(&Diff { bytes_added: 1, ..Default::default() });
+		assert_err!(
+			nested1.enforce_subcall_limit(None),
+			<Error<Test>>::StorageDepositLimitExhausted,
+		);
+	}
+
 	#[test]
 	fn charging_works() {
 		clear_ext();
 
-		let mut meter = TestMeter::new(&ALICE, Some(100), 0).unwrap();
+		let mut meter = TestMeter::new(&CallOrigin::from_account_id(ALICE), Some(100), 0).unwrap();
 		assert_eq!(meter.available(), 100);
 
 		let mut nested0_info =
 			new_info(StorageInfo { bytes: 100, items: 5, bytes_deposit: 100, items_deposit: 10 });
-		let mut nested0 = meter.nested();
+		let mut nested0 = meter.nested(0);
 		nested0.charge(&Diff {
 			bytes_added: 108,
 			bytes_removed: 5,
 			items_added: 1,
 			items_removed: 2,
+			..Default::default()
 		});
 		nested0.charge(&Diff { bytes_removed: 99, ..Default::default() });
 
 		let mut nested1_info =
 			new_info(StorageInfo { bytes: 100, items: 10, bytes_deposit: 100, items_deposit: 20 });
-		let mut nested1 = nested0.nested();
+		let mut nested1 = nested0.nested(0);
 		nested1.charge(&Diff { items_removed: 5, ..Default::default() });
-		nested0.absorb(nested1, &CHARLIE, Some(&mut nested1_info));
+		nested0.absorb(nested1, DepositAccount(CHARLIE), Some(&mut nested1_info));
 
 		let mut nested2_info =
 			new_info(StorageInfo { bytes: 100, items: 7, bytes_deposit: 100, items_deposit: 20 });
-		let mut nested2 = nested0.nested();
+		let mut nested2 = nested0.nested(0);
 		nested2.charge(&Diff { items_removed: 7, ..Default::default() });
-		nested0.absorb(nested2, &CHARLIE, Some(&mut nested2_info));
+		nested0.absorb(nested2, DepositAccount(CHARLIE), Some(&mut nested2_info));
 
 		nested0.enforce_limit(Some(&mut nested0_info)).unwrap();
-		meter.absorb(nested0, &BOB, Some(&mut nested0_info));
+		meter.absorb(nested0, DepositAccount(BOB), Some(&mut nested0_info));
 
-		meter.into_deposit(&ALICE);
+		meter.into_deposit(&CallOrigin::from_account_id(ALICE));
 
 		assert_eq!(nested0_info.extra_deposit(), 112);
 		assert_eq!(nested1_info.extra_deposit(), 110);
@@ -716,19 +967,19 @@ mod tests {
 				charges: vec![
 					Charge {
 						origin: ALICE,
-						contract: CHARLIE,
+						deposit_account: CHARLIE,
 						amount: Deposit::Refund(10),
 						terminated: false
 					},
 					Charge {
 						origin: ALICE,
-						contract: CHARLIE,
+						deposit_account: CHARLIE,
 						amount: Deposit::Refund(20),
 						terminated: false
 					},
 					Charge {
 						origin: ALICE,
-						contract: BOB,
+						deposit_account: BOB,
 						amount: Deposit::Charge(2),
 						terminated: false
 					}
@@ -741,29 +992,31 @@ mod tests {
 	fn termination_works() {
 		clear_ext();
 
-		let mut meter = TestMeter::new(&ALICE, Some(1_000), 0).unwrap();
+		let mut meter =
+			TestMeter::new(&CallOrigin::from_account_id(ALICE), Some(1_000), 0).unwrap();
 		assert_eq!(meter.available(), 1_000);
 
-		let mut nested0 = meter.nested();
+		let mut nested0 = meter.nested(0);
 		nested0.charge(&Diff {
 			bytes_added: 5,
 			bytes_removed: 1,
 			items_added: 3,
 			items_removed: 1,
+			..Default::default()
 		});
 		nested0.charge(&Diff { items_added: 2, ..Default::default() });
 
 		let mut nested1_info =
 			new_info(StorageInfo { bytes: 100, items: 10, bytes_deposit: 100, items_deposit: 20 });
-		let mut nested1 = nested0.nested();
+		let mut nested1 = nested0.nested(0);
 		nested1.charge(&Diff { items_removed: 5, ..Default::default() });
 		nested1.charge(&Diff { bytes_added: 20, ..Default::default() });
 		nested1.terminate(&nested1_info);
 		nested0.enforce_limit(Some(&mut nested1_info)).unwrap();
-		nested0.absorb(nested1, &CHARLIE, None);
+		nested0.absorb(nested1, DepositAccount(CHARLIE), None);
 
-		meter.absorb(nested0, &BOB, None);
-		meter.into_deposit(&ALICE);
+		meter.absorb(nested0, DepositAccount(BOB), None);
+		meter.into_deposit(&CallOrigin::from_account_id(ALICE));
 
 		assert_eq!(
 			TestExtTestValue::get(),
@@ -772,13 +1025,13 @@ mod tests {
 				charges: vec![
 					Charge {
 						origin: ALICE,
-						contract: CHARLIE,
+						deposit_account: CHARLIE,
 						amount: Deposit::Refund(120),
 						terminated: true
 					},
 					Charge {
 						origin: ALICE,
-						contract: BOB,
+						deposit_account: BOB,
 						amount: Deposit::Charge(12),
 						terminated: false
 					}
@@ -18,38 +18,55 @@
 use self::test_utils::hash;
 use crate::{
 	chain_extension::{
+		staking::{StakingExtension, WeightInfo as StakingWeightInfo},
 		ChainExtension, Environment, Ext, InitState, RegisteredChainExtension,
 		Result as ExtensionResult, RetVal, ReturnFlags, SysConfig,
 	},
-	exec::{FixSizedKey, Frame},
+	exec::{FixSizedKey, Frame, StorageKey},
+	migration::{
+		v11, v12, v13, v14, v15, v16, v17, v18, CheckMigrationDone, IsFinished, MigrationStep,
+	},
 	storage::Storage,
 	tests::test_utils::{get_contract, get_contract_checked},
-	wasm::{Determinism, PrefabWasmModule, ReturnCode as RuntimeReturnCode},
+	wasm::{Determinism, ReturnCode as RuntimeReturnCode, API_VERSION},
 	weights::WeightInfo,
-	BalanceOf, Code, CodeStorage, Config, ContractInfoOf, DefaultAddressGenerator, DeletionQueue,
-	Error, Pallet, Schedule,
+	AddressGenerator, BalanceOf, BatchedCall, CallOrigin, Caller, CheckStorageDepositLimit, Code,
+	CodeHash, CodeInfoOf, CollectEvents, Config, ContractInfoOf, DebugPolicy,
+	DefaultAddressGenerator, DeletionQueue, DeployerNonce, DispatchAsContract, DispatchAsSigned,
+	EnsureContract, EnsureSignedOrContract, Error, Migration, MigrationInProgress, Pallet,
+	Schedule, SequentialAddressGenerator, StorageCeiling, TombstonedCode, TransactionDepositLimit,
 };
 use assert_matches::assert_matches;
-use codec::Encode;
+use codec::{Decode, Encode};
 use frame_support::{
 	assert_err, assert_err_ignore_postinfo, assert_noop, assert_ok,
-	dispatch::{DispatchClass, DispatchErrorWithPostInfo, PostDispatchInfo},
+	dispatch::{
+		DispatchClass, DispatchErrorWithPostInfo, DispatchResult, DispatchResultWithPostInfo,
+		GetDispatchInfo, GetStorageVersion, Pays, PostDispatchInfo,
+	},
 	parameter_types,
-	storage::child,
+	storage::{child, unhashed},
 	traits::{
-		BalanceStatus, ConstU32, ConstU64, Contains, Currency, Get, LockableCurrency, OnIdle,
-		OnInitialize, ReservableCurrency, WithdrawReasons,
+		ConstU32, ConstU64, Contains, Currency, EnsureOrigin, ExistenceRequirement, Get,
+		LockableCurrency, OnIdle, OnInitialize, OnRuntimeUpgrade, ReservableCurrency,
+		StorageVersion, WithdrawReasons,
 	},
 	weights::{constants::WEIGHT_REF_TIME_PER_SECOND, Weight},
+	BoundedVec,
+};
+use frame_system::{self as system, EnsureRoot, EnsureSigned, EventRecord, Phase};
+use pallet_contracts_primitives::{
+	ContractAccessError, ContractStorageChange, ContractsEnvironment, HostFnInfo, IoCounters,
+	RevertReason, StorageDeposit,
 };
-use frame_system::{self as system, EventRecord, Phase};
 use pretty_assertions::{assert_eq, assert_ne};
 use sp_io::hashing::blake2_256;
 use sp_keystore::{testing::KeyStore, KeystoreExt};
 use sp_runtime::{
 	testing::{Header, H256},
-	traits::{BlakeTwo256, Convert, Hash, IdentityLookup},
-	AccountId32,
+	traits::{BlakeTwo256, Convert, Hash, IdentityLookup, SignedExtension},
+	transaction_validity::InvalidTransaction,
+	AccountId32, DispatchError, ModuleError,
 };
 use std::sync::Arc;
 
@@ -68,8 +85,8 @@ frame_support::construct_runtime!(
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
 		Timestamp: pallet_timestamp::{Pallet, Call, Storage, Inherent},
 		Randomness: pallet_randomness_collective_flip::{Pallet, Storage},
-		Utility: pallet_utility::{Pallet, Call, Storage, Event},
-		Contracts: pallet_contracts::{Pallet, Call, Storage, Event<T>},
+		Utility: pallet_utility::{Pallet, Call, Storage, Event<T>},
+		Contracts: pallet_contracts::{Pallet, Call, Storage, Event<T>, Origin<T>},
 	}
 );
 
@@ -90,6 +107,7 @@ pub mod test_utils {
 		let trie_id = Storage::<Test>::generate_trie_id(address, nonce);
 		set_balance(address, <Test as Config>::Currency::minimum_balance() * 10);
 		let contract = Storage::<Test>::new_contract(&address, trie_id, code_hash).unwrap();
+		set_balance(&contract.deposit_account, <Test as Config>::Currency::minimum_balance());
 		<ContractInfoOf<Test>>::insert(address, contract);
 	}
 	pub fn set_balance(who: &AccountIdOf<Test>, amount: u64) {
@@ -116,7 +134,7 @@ pub mod test_utils {
 
 	macro_rules! assert_refcount {
 		( $code_hash:expr , $should:expr $(,)? ) => {{
-			let is = crate::OwnerInfoOf::<Test>::get($code_hash).map(|m| m.refcount()).unwrap();
+			let is = crate::CodeInfoOf::<Test>::get($code_hash).map(|m| m.refcount()).unwrap();
 			assert_eq!(is, $should);
 		}};
 	}
@@ -150,6 +168,12 @@ pub struct TempStorageExtension {
 	storage: u32,
 }
 
+/// [`StakingExtension`] that is switched off together with the other test extensions.
+#[derive(Default)]
+pub struct TestStakingExtension(
+	StakingExtension<TestStaking, TestStakingWeights, TestStakingFilter, 4>,
+);
+
 impl TestExtension {
 	fn disable() {
 		TestExtensionTestValue::mutate(|e| e.enabled = false)
@@ -199,6 +223,12 @@ impl ChainExtension<Test> for TestExtension {
 				Ok(RetVal::Converging(id))
 			},
 			3 => Ok(RetVal::Diverging { flags: ReturnFlags::REVERT, data: vec![42, 99] }),
+			4 => {
+				let mut env = env.buf_in_buf_out();
+				let input = env.read(5)?;
+				env.deposit_event(vec![hash(&input[4])], input)?;
+				Ok(RetVal::Converging(id))
+			},
 			_ => {
 				panic!("Passed unknown id to test chain extension: {}", func_id);
 			},
@@ -278,6 +308,315 @@ impl RegisteredChainExtension<Test> for TempStorageExtension {
 	const ID: u16 = 3;
 }
 
+impl ChainExtension<Test> for TestStakingExtension {
+	fn call<E>(&mut self, env: Environment<E, InitState>) -> ExtensionResult<RetVal>
+	where
+		E: Ext<T = Test>,
+	{
+		self.0.call(env)
+	}
+
+	fn enabled() -> bool {
+		TestExtensionTestValue::get().enabled
+	}
+}
+
+impl RegisteredChainExtension<Test> for TestStakingExtension {
+	const ID: u16 = 4;
+}
+
+parameter_types! {
+	/// Active stake of the accounts bonded through [`TestStaking`].
+	pub static StakingBonded: Vec<(AccountId32, u64)> = vec![];
+	/// The last nominations made through [`TestStaking`].
+	pub static StakingNominations: Vec<(AccountId32, Vec<AccountId32>)> = vec![];
+	pub static StakingEra: sp_staking::EraIndex = 0;
+	/// Whether contracts are allowed to call the mutating functions of the staking extension.
+	pub static StakingAllowed: bool = true;
+}
+
+/// Only tracks the active stake. Bonding an already bonded account fails.
+pub struct TestStaking;
+
+impl TestStaking {
+	fn mutate_bonded(who: &AccountId32, f: impl FnOnce(&mut u64)) -> DispatchResult {
+		StakingBonded::mutate(|bonded| {
+			let (_, value) = bonded
+				.iter_mut()
+				.find(|(acc, _)| acc == who)
+				.ok_or(DispatchError::Other("not bonded"))?;
+			f(value);
+			Ok(())
+		})
+	}
+}
+
+impl sp_staking::StakingInterface for TestStaking {
+	type Balance = u64;
+	type AccountId = AccountId32;
+
+	fn minimum_nominator_bond() -> u64 {
+		1
+	}
+
+	fn minimum_validator_bond() -> u64 {
+		1
+	}
+
+	fn stash_by_ctrl(controller: &AccountId32) -> Result<AccountId32, DispatchError> {
+		Ok(controller.clone())
+	}
+
+	fn bonding_duration() -> sp_staking::EraIndex {
+		1
+	}
+
+	fn current_era() -> sp_staking::EraIndex {
+		StakingEra::get()
+	}
+
+	fn stake(who: &AccountId32) -> Result<sp_staking::Stake<Self>, DispatchError> {
+		StakingBonded::get()
+			.into_iter()
+			.find(|(acc, _)| acc == who)
+			.map(|(stash, active)| sp_staking::Stake { stash, total: active, active })
+			.ok_or(DispatchError::Other("not bonded"))
+	}
+
+	fn bond(stash: &AccountId32, value: u64, _payee: &AccountId32) -> DispatchResult {
+		if Self::stake(stash).is_ok() {
+			return Err(DispatchError::Other("already bonded"))
+		}
+		StakingBonded::mutate(|bonded| bonded.push((stash.clone(), value)));
+		Ok(())
+	}
+
+	fn nominate(who: &AccountId32, validators: Vec<AccountId32>) -> DispatchResult {
+		Self::stake(who)?;
+		StakingNominations::mutate(|n| n.push((who.clone(), validators)));
+		Ok(())
+	}
+
+	fn chill(who: &AccountId32) -> DispatchResult {
+		StakingNominations::mutate(|n| n.retain(|(acc, _)| acc != who));
+		Ok(())
+	}
+
+	fn bond_extra(who: &AccountId32, extra: u64) -> DispatchResult {
+		Self::mutate_bonded(who, |value| *value += extra)
+	}
+
+	fn unbond(stash: &AccountId32, value: u64) -> DispatchResult {
+		Self::mutate_bonded(stash, |active| *active = active.saturating_sub(value))
+	}
+
+	fn withdraw_unbonded(stash: AccountId32, _: u32) -> Result<bool, DispatchError> {
+		Self::stake(&stash).map(|_| false)
+	}
+
+	fn desired_validator_count() -> u32 {
+		unimplemented!("method currently not used in testing")
+	}
+
+	fn election_ongoing() -> bool {
+		unimplemented!("method currently not used in testing")
+	}
+
+	fn force_unstake(_who: AccountId32) -> DispatchResult {
+		unimplemented!("method currently not used in testing")
+	}
+
+	fn is_exposed_in_era(_who: &AccountId32, _era: &sp_staking::EraIndex) -> bool {
+		unimplemented!("method currently not used in testing")
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn nominations(_who: AccountId32) -> Option<Vec<AccountId32>> {
+		unimplemented!("method currently not used in testing")
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn add_era_stakers(
+		_current_era: &sp_staking::EraIndex,
+		_stash: &AccountId32,
+		_exposures: Vec<(AccountId32, u64)>,
+	) {
+		unimplemented!("method currently not used in testing")
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn set_current_era(_era: sp_staking::EraIndex) {
+		unimplemented!("method currently not used in testing")
+	}
+}
+
+/// Every staking function costs `1_000` ref time plus `100` per item.
+pub struct TestStakingWeights;
+
+impl StakingWeightInfo for TestStakingWeights {
+	fn current_era() -> Weight {
+		Weight::from_ref_time(1_000)
+	}
+	fn active_stake() -> Weight {
+		Weight::from_ref_time(1_000)
+	}
+	fn bond() -> Weight {
+		Weight::from_ref_time(1_000)
+	}
+	fn bond_extra() -> Weight {
+		Weight::from_ref_time(1_000)
+	}
+	fn unbond() -> Weight {
+		Weight::from_ref_time(1_000)
+	}
+	fn withdraw_unbonded(s: u32) -> Weight {
+		Weight::from_ref_time(1_000 + 100 * s as u64)
+	}
+	fn nominate(n: u32) -> Weight {
+		Weight::from_ref_time(1_000 + 100 * n as u64)
+	}
+	fn chill() -> Weight {
+		Weight::from_ref_time(1_000)
+	}
+}
+
+pub struct TestStakingFilter;
+
+impl Contains<AccountId32> for TestStakingFilter {
+	fn contains(_contract: &AccountId32) -> bool {
+		StakingAllowed::get()
+	}
+}
+
+parameter_types! {
+	/// Messages passed to [`TestXcm`] as `(origin, dest, message)`.
+	pub static XcmMessages: Vec<(AccountId32, Option<Vec<u8>>, Vec<u8>)> = vec![];
+}
+
+/// Records all messages. Executing a message costs `1_000` ref time per byte of which half
+/// is refunded.
+pub struct TestXcm;
+
+impl crate::xcm::Xcm<Test> for TestXcm {
+	fn weigh_execute(message: &[u8]) -> Result<Weight, DispatchError> {
+		Ok(Weight::from_ref_time(message.len() as u64 * 1_000))
+	}
+
+	fn execute(
+		origin: &AccountId32,
+		message: &[u8],
+		weight_limit: Weight,
+	) -> DispatchResultWithPostInfo {
+		XcmMessages::mutate(|m| m.push((origin.clone(), None, message.to_vec())));
+		Ok(Some(weight_limit / 2).into())
+	}
+
+	fn weigh_send(_dest: &[u8], _message: &[u8]) -> Result<Weight, DispatchError> {
+		Ok(Weight::from_ref_time(1_000))
+	}
+
+	fn send(
+		origin: &AccountId32,
+		dest: &[u8],
+		message: &[u8],
+	) -> Result<crate::xcm::XcmHash, DispatchError> {
+		XcmMessages::mutate(|m| m.push((origin.clone(), Some(dest.to_vec()), message.to_vec())));
+		Ok(blake2_256(message))
+	}
+}
+
+/// Builtin which returns the blake2-256 hash of its non empty input.
+pub struct HashBuiltin;
+
+impl HashBuiltin {
+	pub const ADDRESS: AccountId32 = AccountId32::new([0xfe; 32]);
+}
+
+impl crate::builtin::Builtin<Test> for HashBuiltin {
+	fn address() -> AccountId32 {
+		Self::ADDRESS
+	}
+
+	fn weight(input_len: u32) -> Weight {
+		Weight::from_ref_time(1_000).saturating_mul(input_len.into())
+	}
+
+	fn call(input: &[u8]) -> Result<Vec<u8>, DispatchError> {
+		if input.is_empty() {
+			return Err(DispatchError::Other("empty input"))
+		}
+		Ok(blake2_256(input).to_vec())
+	}
+}
+
+parameter_types! {
+	/// Balances held in [`TestAssets`] as `((asset, who), balance)`.
+	pub static AssetBalances: Vec<((u32, AccountId32), u64)> = vec![];
+}
+
+/// Assets are encoded as `u32` and balances as `u64`. Queries cost `1_000` ref time and
+/// transfers `10_000`.
+pub struct TestAssets;
+
+impl TestAssets {
+	pub fn balance(asset: u32, who: &AccountId32) -> u64 {
+		AssetBalances::get()
+			.iter()
+			.find(|((a, w), _)| *a == asset && w == who)
+			.map_or(0, |(_, balance)| *balance)
+	}
+
+	pub fn set_balance(asset: u32, who: &AccountId32, balance: u64) {
+		AssetBalances::mutate(|balances| {
+			balances.retain(|((a, w), _)| *a != asset || w != who);
+			balances.push(((asset, who.clone()), balance));
+		});
+	}
+
+	fn decode<V: codec::Decode>(mut input: &[u8]) -> Result<V, DispatchError> {
+		V::decode(&mut input).map_err(|_| Error::<Test>::DecodingFailed.into())
+	}
+}
+
+impl crate::assets::Assets<Test> for TestAssets {
+	fn weigh_query() -> Weight {
+		Weight::from_ref_time(1_000)
+	}
+
+	fn weigh_transfer() -> Weight {
+		Weight::from_ref_time(10_000)
+	}
+
+	fn balance_of(asset: &[u8], who: &AccountId32) -> Result<Vec<u8>, DispatchError> {
+		Ok(Self::balance(Self::decode(asset)?, who).encode())
+	}
+
+	fn total_supply(asset: &[u8]) -> Result<Vec<u8>, DispatchError> {
+		let asset: u32 = Self::decode(asset)?;
+		Ok(AssetBalances::get()
+			.iter()
+			.filter(|((a, _), _)| *a == asset)
+			.map(|(_, balance)| balance)
+			.sum::<u64>()
+			.encode())
+	}
+
+	fn transfer(
+		asset: &[u8],
+		from: &AccountId32,
+		to: &AccountId32,
+		amount: &[u8],
+	) -> DispatchResult {
+		let (asset, amount): (u32, u64) = (Self::decode(asset)?, Self::decode(amount)?);
+		let from_balance = Self::balance(asset, from)
+			.checked_sub(amount)
+			.ok_or(DispatchError::Other("funds"))?;
+		Self::set_balance(asset, from, from_balance);
+		Self::set_balance(asset, to, Self::balance(asset, to) + amount);
+		Ok(())
+	}
+}
+
 parameter_types! {
 	pub BlockWeights: frame_system::limits::BlockWeights =
 		frame_system::limits::BlockWeights::simple_max(
@@ -337,13 +676,72 @@ impl pallet_utility::Config for Test {
 	type WeightInfo = ();
 }
 parameter_types! {
-	pub MySchedule: Schedule<Test> = {
-		let mut schedule = <Schedule<Test>>::default();
-		schedule.instruction_weights.fallback = 1;
-		schedule
-	};
+	pub MySchedule: Schedule<Test> = <Schedule<Test>>::default();
 	pub static DepositPerByte: BalanceOf<Test> = 1;
 	pub const DepositPerItem: BalanceOf<Test> = 2;
+	pub static DepositPerEventByte: BalanceOf<Test> = 0;
+	pub static CollectCodeStats: bool = false;
+	pub static CompressCode: bool = false;
+	pub static RemoveUnusedCode: bool = false;
+	pub static UploadAccount: Option<AccountId32> = None;
+	pub static InstantiateAccount: Option<AccountId32> = None;
+	pub static SequentialAddresses: bool = false;
+}
+
+/// Uses [`SequentialAddressGenerator`] if [`SequentialAddresses`] is set and
+/// [`DefaultAddressGenerator`] otherwise.
+pub struct TestAddressGenerator;
+impl AddressGenerator<Test> for TestAddressGenerator {
+	fn generate_address(
+		deploying_address: &AccountId32,
+		code_hash: &CodeHash<Test>,
+		input_data: &[u8],
+		salt: &[u8],
+	) -> AccountId32 {
+		if SequentialAddresses::get() {
+			<SequentialAddressGenerator as AddressGenerator<Test>>::generate_address(
+				deploying_address,
+				code_hash,
+				input_data,
+				salt,
+			)
+		} else {
+			<DefaultAddressGenerator as AddressGenerator<Test>>::generate_address(
+				deploying_address,
+				code_hash,
+				input_data,
+				salt,
+			)
+		}
+	}
+
+	fn on_contract_created(deploying_address: &AccountId32) {
+		if SequentialAddresses::get() {
+			<SequentialAddressGenerator as AddressGenerator<Test>>::on_contract_created(
+				deploying_address,
+			);
+		}
+	}
+}
+
+/// Allows any signed origin if `A` is `None`. Otherwise only the account returned by `A`.
+pub struct EnsureAccount<A>(std::marker::PhantomData<A>);
+
+impl<A: Get<Option<AccountId32>>> EnsureOrigin<RuntimeOrigin> for EnsureAccount<A> {
+	type Success = AccountId32;
+
+	fn try_origin(o: RuntimeOrigin) -> Result<Self::Success, RuntimeOrigin> {
+		let who = <EnsureSigned<_> as EnsureOrigin<_>>::try_origin(o.clone())?;
+		if matches!(A::get(), Some(ref allowed) if *allowed != who) {
+			return Err(o)
+		}
+		Ok(who)
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn try_successful_origin() -> Result<RuntimeOrigin, ()> {
+		Err(())
+	}
 }
 
 impl Convert<Weight, BalanceOf<Self>> for Test {
@@ -385,11 +783,29 @@ impl Contains<RuntimeCall> for TestFilter {
 parameter_types! {
 	pub const DeletionWeightLimit: Weight = Weight::from_ref_time(500_000_000_000);
 	pub static UnstableInterface: bool = true;
+	pub static RandomnessEnabled: bool = true;
+	pub static OnChainDebugging: DebugPolicy = DebugPolicy::Disabled;
+	pub static CallRuntimeAsContract: bool = false;
+	pub static MaxStoragePerContract: Option<StorageCeiling> = None;
+}
+
+/// Dispatches with the contract origin when [`CallRuntimeAsContract`] is set.
+pub struct TestCallRuntimeOrigin;
+
+impl Convert<AccountId32, RuntimeOrigin> for TestCallRuntimeOrigin {
+	fn convert(address: AccountId32) -> RuntimeOrigin {
+		if CallRuntimeAsContract::get() {
+			DispatchAsContract::convert(address)
+		} else {
+			DispatchAsSigned::convert(address)
+		}
+	}
 }
 
 impl Config for Test {
 	type Time = Timestamp;
 	type Randomness = Randomness;
+	type RandomnessEnabled = RandomnessEnabled;
 	type Currency = Balances;
 	type RuntimeEvent = RuntimeEvent;
 	type RuntimeCall = RuntimeCall;
@@ -397,18 +813,50 @@ impl Config for Test {
 	type CallStack = [Frame<Self>; 5];
 	type WeightPrice = Self;
 	type WeightInfo = ();
-	type ChainExtension =
-		(TestExtension, DisabledExtension, RevertingExtension, TempStorageExtension);
+	type ChainExtension = (
+		TestExtension,
+		DisabledExtension,
+		RevertingExtension,
+		TempStorageExtension,
+		TestStakingExtension,
+	);
+	type Builtins = (HashBuiltin,);
+	type Xcm = TestXcm;
+	type Assets = TestAssets;
 	type DeletionQueueDepth = ConstU32<1024>;
 	type DeletionWeightLimit = DeletionWeightLimit;
 	type Schedule = MySchedule;
 	type DepositPerByte = DepositPerByte;
 	type DepositPerItem = DepositPerItem;
-	type AddressGenerator = DefaultAddressGenerator;
+	type DepositPerEventByte = DepositPerEventByte;
+	type AddressGenerator = TestAddressGenerator;
 	type MaxCodeLen = ConstU32<{ 123 * 1024 }>;
+	type MaxCodeMetadataLen = ConstU32<128>;
 	type MaxStorageKeyLen = ConstU32<128>;
 	type UnsafeUnstableInterface = UnstableInterface;
 	type MaxDebugBufferLen = ConstU32<{ 2 * 1024 * 1024 }>;
+	type Debugging = OnChainDebugging;
+	type MaxCallFilterLen = ConstU32<16>;
+	type MaxBatchedCalls = ConstU32<8>;
+	type MaxStoragePerContract = MaxStoragePerContract;
+	type CallRuntimeOrigin = TestCallRuntimeOrigin;
+	type MaxDelegateDependencies = ConstU32<32>;
+	type CollectCodeStats = CollectCodeStats;
+	type CompressCode = CompressCode;
+	type RemoveUnusedCode = RemoveUnusedCode;
+	type UploadOrigin = EnsureAccount<UploadAccount>;
+	type InstantiateOrigin = EnsureAccount<InstantiateAccount>;
+	type ScheduleOrigin = EnsureRoot<AccountId32>;
+	type Migrations = (
+		crate::migration::test::MockMigration<11>,
+		crate::migration::test::MockMigration<12>,
+		crate::migration::test::MockMigration<13>,
+		crate::migration::test::MockMigration<14>,
+		crate::migration::test::MockMigration<15>,
+		crate::migration::test::MockMigration<16>,
+		crate::migration::test::MockMigration<17>,
+		crate::migration::test::MockMigration<18>,
+	);
 }
 
 pub const ALICE: AccountId32 = AccountId32::new([1u8; 32]);
@@ -545,15 +993,40 @@ fn instantiate_and_call_and_deposit_event() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
 		.account_id;
-		assert!(ContractInfoOf::<Test>::contains_key(&addr));
+		let deposit_account = (*get_contract(&addr).deposit_account).clone();
 
 		assert_eq!(
 			System::events(),
 			vec![
+				EventRecord {
+					phase: Phase::Initialization,
+					event: RuntimeEvent::System(frame_system::Event::NewAccount {
+						account: deposit_account.clone(),
+					}),
+					topics: vec![],
+				},
+				EventRecord {
+					phase: Phase::Initialization,
+					event: RuntimeEvent::Balances(pallet_balances::Event::Endowed {
+						account: deposit_account.clone(),
+						free_balance: min_balance,
+					}),
+					topics: vec![],
+				},
+				EventRecord {
+					phase: Phase::Initialization,
+					event: RuntimeEvent::Balances(pallet_balances::Event::Transfer {
+						from: ALICE,
+						to: deposit_account.clone(),
+						amount: min_balance,
+					}),
+					topics: vec![],
+				},
 				EventRecord {
 					phase: Phase::Initialization,
 					event: RuntimeEvent::System(frame_system::Event::NewAccount {
@@ -580,11 +1053,12 @@ fn instantiate_and_call_and_deposit_event() {
 				},
 				EventRecord {
 					phase: Phase::Initialization,
-					event: RuntimeEvent::Balances(pallet_balances::Event::Reserved {
-						who: addr.clone(),
+					event: RuntimeEvent::Contracts(crate::Event::StorageBaseDepositCharged {
+						contract: addr.clone(),
+						payer: ALICE,
 						amount: min_balance,
 					}),
-					topics: vec![],
+					topics: vec![hash(&addr), hash(&ALICE)],
 				},
 				EventRecord {
 					phase: Phase::Initialization,
@@ -616,6 +1090,60 @@ fn instantiate_and_call_and_deposit_event() {
 	});
 }
 
+#[test]
+fn collect_events_works() {
+	let (wasm, code_hash) = compile_module::<Test>("event_and_return_on_deploy").unwrap();
+
+	ExtBuilder::default().existential_deposit(500).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Contracts::upload_code(
+			RuntimeOrigin::signed(ALICE),
+			wasm,
+			None,
+			Determinism::Deterministic
+		));
+
+		// Events deposited before the call must not be part of the result.
+		let events_before = System::events().len();
+		assert!(events_before > 0);
+
+		let result = Contracts::bare_instantiate(
+			ALICE,
+			100,
+			GAS_LIMIT,
+			None,
+			Code::Existing(code_hash),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::UnsafeCollect,
+		);
+		let addr = result.result.unwrap().account_id;
+		let events = result.events.unwrap();
+		assert_eq!(events, System::events()[events_before..].to_vec());
+		assert!(events.iter().any(|record| record.event ==
+			RuntimeEvent::Contracts(crate::Event::ContractEmitted {
+				contract: addr.clone(),
+				data: vec![1, 2, 3, 4]
+			})));
+
+		// Nothing is collected when not requested.
+		let result = Contracts::bare_instantiate(
+			ALICE,
+			100,
+			GAS_LIMIT,
+			None,
+			Code::Existing(code_hash),
+			vec![],
+			vec![1],
+			false,
+			CollectEvents::Skip,
+		);
+		assert_ok!(result.result);
+		assert!(result.events.is_none());
+	});
+}
+
 #[test]
 fn deposit_event_max_value_limit() {
 	let (wasm, _code_hash) = compile_module::<Test>("event_size").unwrap();
@@ -632,6 +1160,7 @@ fn deposit_event_max_value_limit() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -644,7 +1173,7 @@ fn deposit_event_max_value_limit() {
 			0,
 			GAS_LIMIT.set_ref_time(GAS_LIMIT.ref_time() * 2), // we are copying a huge buffer,
 			None,
-			<Test as Config>::Schedule::get().limits.payload_len.encode(),
+			<Test as Config>::Schedule::get().limits.event_data_len.encode(),
 		));
 
 		// Call contract with too large a storage value.
@@ -655,7 +1184,7 @@ fn deposit_event_max_value_limit() {
 				0,
 				GAS_LIMIT,
 				None,
-				(<Test as Config>::Schedule::get().limits.payload_len + 1).encode(),
+				(<Test as Config>::Schedule::get().limits.event_data_len + 1).encode(),
 			),
 			Error::<Test>::ValueTooLarge,
 		);
@@ -663,43 +1192,173 @@ fn deposit_event_max_value_limit() {
 }
 
 #[test]
-fn run_out_of_gas() {
-	let (wasm, _code_hash) = compile_module::<Test>("run_out_of_gas").unwrap();
+fn deposit_event_charges_deposit_per_byte() {
+	let (wasm, _code_hash) = compile_module::<Test>("event_size").unwrap();
+
 	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
-		let min_balance = <Test as Config>::Currency::minimum_balance();
 		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-
 		let addr = Contracts::bare_instantiate(
 			ALICE,
-			100 * min_balance,
+			30_000,
 			GAS_LIMIT,
 			None,
 			Code::Upload(wasm),
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
 		.account_id;
+		let base_deposit = get_contract(&addr).storage_base_deposit;
 
-		// Call the contract with a fixed gas limit. It must run out of gas because it just
-		// loops forever.
+		// Without a configured price emitting events is free.
+		assert_ok!(Contracts::call(
+			RuntimeOrigin::signed(ALICE),
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			100u32.encode(),
+		));
+		assert_eq!(get_contract(&addr).storage_base_deposit, base_deposit);
+
+		// The deposit is charged from the caller and added to the base deposit.
+		DEPOSIT_PER_EVENT_BYTE.with(|c| *c.borrow_mut() = 3);
+		let balance = Balances::free_balance(&ALICE);
+		assert_ok!(Contracts::call(
+			RuntimeOrigin::signed(ALICE),
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			100u32.encode(),
+		));
+		assert_eq!(get_contract(&addr).storage_base_deposit, base_deposit + 300);
+		assert_eq!(Balances::free_balance(&ALICE), balance - 300);
+
+		// The storage deposit limit also covers the event deposit.
 		assert_err_ignore_postinfo!(
 			Contracts::call(
 				RuntimeOrigin::signed(ALICE),
-				addr, // newly created account
+				addr,
 				0,
-				Weight::from_ref_time(1_000_000_000_000).set_proof_size(u64::MAX),
-				None,
-				vec![],
+				GAS_LIMIT,
+				Some(codec::Compact(299)),
+				100u32.encode(),
 			),
-			Error::<Test>::OutOfGas,
+			Error::<Test>::StorageDepositLimitExhausted,
 		);
 	});
 }
 
-/// Check that contracts with the same account id have different trie ids.
+#[test]
+fn run_out_of_gas() {
+	let (wasm, _code_hash) = compile_module::<Test>("run_out_of_gas").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let min_balance = <Test as Config>::Currency::minimum_balance();
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		let addr = Contracts::bare_instantiate(
+			ALICE,
+			100 * min_balance,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+
+		// Call the contract with a fixed gas limit. It must run out of gas because it just
+		// loops forever.
+		assert_err_ignore_postinfo!(
+			Contracts::call(
+				RuntimeOrigin::signed(ALICE),
+				addr, // newly created account
+				0,
+				Weight::from_ref_time(1_000_000_000_000).set_proof_size(u64::MAX),
+				None,
+				vec![],
+			),
+			Error::<Test>::OutOfGas,
+		);
+	});
+}
+
+#[test]
+fn sequential_address_generator_works() {
+	type Generator = SequentialAddressGenerator;
+	let generate = |deployer: &AccountId32, salt: &[u8]| {
+		<Generator as AddressGenerator<Test>>::generate_address(deployer, &H256::zero(), &[], salt)
+	};
+
+	ExtBuilder::default().build().execute_with(|| {
+		// The address only depends on the deployer and its nonce.
+		let alice_first = generate(&ALICE, &[]);
+		let bob_first = generate(&BOB, &[]);
+		assert_eq!(generate(&ALICE, &[1, 2, 3]), alice_first);
+		assert_ne!(bob_first, alice_first);
+
+		// Creating a contract only advances the nonce of its deployer.
+		<Generator as AddressGenerator<Test>>::on_contract_created(&ALICE);
+		assert_eq!(DeployerNonce::<Test>::get(&ALICE), 1);
+		assert_ne!(generate(&ALICE, &[]), alice_first);
+		assert_eq!(generate(&BOB, &[]), bob_first);
+	});
+}
+
+#[test]
+fn failed_instantiation_does_not_advance_deployer_nonce() {
+	let (wasm, code_hash) = compile_module::<Test>("ok_trap_revert").unwrap();
+	SequentialAddresses::set(true);
+
+	ExtBuilder::default().existential_deposit(1).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let expected = <TestAddressGenerator as AddressGenerator<Test>>::generate_address(
+			&ALICE,
+			&code_hash,
+			&[],
+			&[],
+		);
+		let instantiate = |input: Vec<u8>| {
+			Contracts::bare_instantiate(
+				ALICE,
+				0,
+				GAS_LIMIT,
+				None,
+				Code::Upload(wasm.clone()),
+				input,
+				vec![],
+				false,
+				CollectEvents::Skip,
+			)
+			.result
+		};
+
+		// A reverted constructor does not use up the nonce.
+		let result = instantiate(vec![1]).unwrap();
+		assert!(result.result.did_revert());
+		assert_eq!(DeployerNonce::<Test>::get(&ALICE), 0);
+
+		// Neither does a trapped one.
+		assert!(instantiate(vec![2]).is_err());
+		assert_eq!(DeployerNonce::<Test>::get(&ALICE), 0);
+
+		// The first successful instantiation gets the address of nonce zero.
+		let result = instantiate(vec![0]).unwrap();
+		assert!(!result.result.did_revert());
+		assert_eq!(result.account_id, expected);
+		assert_eq!(DeployerNonce::<Test>::get(&ALICE), 1);
+	});
+}
+
+/// Check that contracts with the same account id have different trie ids.
 /// Check the `Nonce` storage item for more information.
 #[test]
 fn instantiate_unique_trie_id() {
@@ -725,6 +1384,7 @@ fn instantiate_unique_trie_id() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -787,6 +1447,7 @@ fn storage_max_value_limit() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -837,6 +1498,7 @@ fn deploy_and_call_other_contract() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -850,6 +1512,7 @@ fn deploy_and_call_other_contract() {
 			0u32.to_le_bytes().encode(),
 			vec![42],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap();
@@ -874,6 +1537,7 @@ fn deploy_and_call_other_contract() {
 			None,
 			callee_code_hash.as_ref().to_vec(),
 		));
+		let callee_deposit_account = (*get_contract(&callee_addr).deposit_account).clone();
 
 		assert_eq!(
 			System::events(),
@@ -881,7 +1545,31 @@ fn deploy_and_call_other_contract() {
 				EventRecord {
 					phase: Phase::Initialization,
 					event: RuntimeEvent::System(frame_system::Event::NewAccount {
-						account: callee_addr.clone()
+						account: callee_deposit_account.clone(),
+					}),
+					topics: vec![],
+				},
+				EventRecord {
+					phase: Phase::Initialization,
+					event: RuntimeEvent::Balances(pallet_balances::Event::Endowed {
+						account: callee_deposit_account.clone(),
+						free_balance: min_balance,
+					}),
+					topics: vec![],
+				},
+				EventRecord {
+					phase: Phase::Initialization,
+					event: RuntimeEvent::Balances(pallet_balances::Event::Transfer {
+						from: ALICE,
+						to: callee_deposit_account.clone(),
+						amount: min_balance,
+					}),
+					topics: vec![],
+				},
+				EventRecord {
+					phase: Phase::Initialization,
+					event: RuntimeEvent::System(frame_system::Event::NewAccount {
+						account: callee_addr.clone(),
 					}),
 					topics: vec![],
 				},
@@ -904,11 +1592,12 @@ fn deploy_and_call_other_contract() {
 				},
 				EventRecord {
 					phase: Phase::Initialization,
-					event: RuntimeEvent::Balances(pallet_balances::Event::Reserved {
-						who: callee_addr.clone(),
+					event: RuntimeEvent::Contracts(crate::Event::StorageBaseDepositCharged {
+						contract: callee_addr.clone(),
+						payer: ALICE,
 						amount: min_balance,
 					}),
-					topics: vec![],
+					topics: vec![hash(&callee_addr), hash(&ALICE)],
 				},
 				EventRecord {
 					phase: Phase::Initialization,
@@ -939,7 +1628,7 @@ fn deploy_and_call_other_contract() {
 				EventRecord {
 					phase: Phase::Initialization,
 					event: RuntimeEvent::Contracts(crate::Event::Called {
-						caller: caller_addr.clone(),
+						caller: CallOrigin::from_account_id(caller_addr.clone()),
 						contract: callee_addr.clone(),
 					}),
 					topics: vec![hash(&caller_addr), hash(&callee_addr)],
@@ -947,7 +1636,7 @@ fn deploy_and_call_other_contract() {
 				EventRecord {
 					phase: Phase::Initialization,
 					event: RuntimeEvent::Contracts(crate::Event::Called {
-						caller: ALICE,
+						caller: CallOrigin::from_account_id(ALICE),
 						contract: caller_addr.clone(),
 					}),
 					topics: vec![hash(&ALICE), hash(&caller_addr)],
@@ -975,6 +1664,7 @@ fn delegate_call() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -1014,6 +1704,7 @@ fn cannot_self_destruct_through_draning() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -1058,15 +1749,18 @@ fn cannot_self_destruct_through_storage_refund_after_price_change() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
 		.account_id;
 
 		// Check that the BOB contract has been instantiated and has the minimum balance
+		let deposit_account = (*get_contract(&addr).deposit_account).clone();
 		assert_eq!(get_contract(&addr).total_deposit(), min_balance);
 		assert_eq!(get_contract(&addr).extra_deposit(), 0);
 		assert_eq!(<Test as Config>::Currency::total_balance(&addr), min_balance);
+		assert_eq!(<Test as Config>::Currency::total_balance(&deposit_account), min_balance);
 
 		// Create 100 bytes of storage with a price of per byte and a single storage item of price 2
 		assert_ok!(Contracts::call(
@@ -1093,7 +1787,7 @@ fn cannot_self_destruct_through_storage_refund_after_price_change() {
 
 		// Make sure the account wasn't removed by the refund
 		assert_eq!(
-			<Test as Config>::Currency::total_balance(&addr),
+			<Test as Config>::Currency::total_balance(&deposit_account),
 			get_contract(&addr).total_deposit(),
 		);
 		assert_eq!(get_contract(&addr).extra_deposit(), 2,);
@@ -1116,12 +1810,14 @@ fn cannot_self_destruct_by_refund_after_slash() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
 		.account_id;
+		let deposit_account = (*get_contract(&addr).deposit_account).clone();
 
-		// create 100 more reserved balance
+		// create 100 more storage deposit
 		assert_ok!(Contracts::call(
 			RuntimeOrigin::signed(ALICE),
 			addr.clone(),
@@ -1136,8 +1832,8 @@ fn cannot_self_destruct_by_refund_after_slash() {
 
 		// slash parts of the 100 so that the next refund ould remove the account
 		// because it the value it stored for `storage_deposit` becomes out of sync
-		let _ = <Test as Config>::Currency::slash(&addr, 90);
-		assert_eq!(<Test as Config>::Currency::total_balance(&addr), min_balance + 10);
+		let _ = <Test as Config>::Currency::slash(&deposit_account, 90);
+		assert_eq!(<Test as Config>::Currency::total_balance(&deposit_account), min_balance + 10);
 
 		// trigger a refund of 50 which would bring the contract below min when actually refunded
 		assert_ok!(Contracts::call(
@@ -1150,7 +1846,7 @@ fn cannot_self_destruct_by_refund_after_slash() {
 		));
 
 		// Make sure the account kept the minimum balance and was not destroyed
-		assert_eq!(<Test as Config>::Currency::total_balance(&addr), min_balance);
+		assert_eq!(<Test as Config>::Currency::total_balance(&deposit_account), min_balance);
 
 		assert_eq!(
 			System::events(),
@@ -1158,7 +1854,7 @@ fn cannot_self_destruct_by_refund_after_slash() {
 				EventRecord {
 					phase: Phase::Initialization,
 					event: RuntimeEvent::Balances(pallet_balances::Event::Slashed {
-						who: addr.clone(),
+						who: deposit_account.clone(),
 						amount: 90,
 					}),
 					topics: vec![],
@@ -1166,18 +1862,17 @@ fn cannot_self_destruct_by_refund_after_slash() {
 				EventRecord {
 					phase: Phase::Initialization,
 					event: RuntimeEvent::Contracts(crate::Event::Called {
-						caller: ALICE,
+						caller: CallOrigin::from_account_id(ALICE),
 						contract: addr.clone(),
 					}),
 					topics: vec![hash(&ALICE), hash(&addr)],
 				},
 				EventRecord {
 					phase: Phase::Initialization,
-					event: RuntimeEvent::Balances(pallet_balances::Event::ReserveRepatriated {
-						from: addr.clone(),
+					event: RuntimeEvent::Balances(pallet_balances::Event::Transfer {
+						from: deposit_account.clone(),
 						to: ALICE,
 						amount: 10,
-						destination_status: BalanceStatus::Free,
 					}),
 					topics: vec![],
 				},
@@ -1202,6 +1897,7 @@ fn cannot_self_destruct_while_live() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -1246,13 +1942,14 @@ fn self_destruct_works() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
 		.account_id;
 
 		// Check that the BOB contract has been instantiated.
-		get_contract(&addr);
+		let deposit_account = (*get_contract(&addr).deposit_account).clone();
 
 		// Drop all previous events
 		initialize_block(2);
@@ -1266,25 +1963,43 @@ fn self_destruct_works() {
 		// Check that code is still there but refcount dropped to zero.
 		assert_refcount!(&code_hash, 0);
 
-		// Check that account is gone
+		// Check that account and deposit account are gone
 		assert!(get_contract_checked(&addr).is_none());
 		assert_eq!(Balances::total_balance(&addr), 0);
+		assert_eq!(Balances::total_balance(&deposit_account), 0);
 
-		// check that the beneficiary (django) got remaining balance
-		assert_eq!(Balances::free_balance(DJANGO), 1_000_000 + 100_000);
+		// check that the beneficiary (django) got remaining balance including the contract's
+		// minimum balance
+		assert_eq!(Balances::free_balance(DJANGO), 1_000_000 + 100_000 + 1_000);
 
 		pretty_assertions::assert_eq!(
 			System::events(),
 			vec![
+				EventRecord {
+					phase: Phase::Initialization,
+					event: RuntimeEvent::System(frame_system::Event::KilledAccount {
+						account: addr.clone()
+					}),
+					topics: vec![],
+				},
 				EventRecord {
 					phase: Phase::Initialization,
 					event: RuntimeEvent::Balances(pallet_balances::Event::Transfer {
 						from: addr.clone(),
 						to: DJANGO,
-						amount: 100_000,
+						amount: 100_000 + 1_000,
 					}),
 					topics: vec![],
 				},
+				EventRecord {
+					phase: Phase::Initialization,
+					event: RuntimeEvent::Contracts(crate::Event::StorageBaseDepositRefunded {
+						contract: addr.clone(),
+						payee: ALICE,
+						amount: 1_000,
+					}),
+					topics: vec![hash(&addr), hash(&ALICE)],
+				},
 				EventRecord {
 					phase: Phase::Initialization,
 					event: RuntimeEvent::Contracts(crate::Event::Terminated {
@@ -1296,7 +2011,7 @@ fn self_destruct_works() {
 				EventRecord {
 					phase: Phase::Initialization,
 					event: RuntimeEvent::Contracts(crate::Event::Called {
-						caller: ALICE,
+						caller: CallOrigin::from_account_id(ALICE),
 						contract: addr.clone(),
 					}),
 					topics: vec![hash(&ALICE), hash(&addr)],
@@ -1304,17 +2019,16 @@ fn self_destruct_works() {
 				EventRecord {
 					phase: Phase::Initialization,
 					event: RuntimeEvent::System(frame_system::Event::KilledAccount {
-						account: addr.clone()
+						account: deposit_account.clone()
 					}),
 					topics: vec![],
 				},
 				EventRecord {
 					phase: Phase::Initialization,
-					event: RuntimeEvent::Balances(pallet_balances::Event::ReserveRepatriated {
-						from: addr.clone(),
+					event: RuntimeEvent::Balances(pallet_balances::Event::Transfer {
+						from: deposit_account.clone(),
 						to: ALICE,
 						amount: 1_000,
-						destination_status: BalanceStatus::Free,
 					}),
 					topics: vec![],
 				},
@@ -1323,6 +2037,76 @@ fn self_destruct_works() {
 	});
 }
 
+#[test]
+fn unused_code_is_removed_when_enabled() {
+	let (wasm, code_hash) = compile_module::<Test>("self_destruct").unwrap();
+	ExtBuilder::default().existential_deposit(1_000).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let _ = Balances::deposit_creating(&DJANGO, 1_000_000);
+		RemoveUnusedCode::set(true);
+
+		let addr = Contracts::bare_instantiate(
+			ALICE,
+			100_000,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+		assert_refcount!(&code_hash, 1);
+		assert!(Balances::reserved_balance(&ALICE) > 0);
+
+		// The removal of the contract's code and of all its delegate dependencies is charged.
+		let gas_consumed = |remove: bool| {
+			RemoveUnusedCode::set(remove);
+			frame_support::storage::with_transaction(|| {
+				let gas_consumed = Contracts::bare_call(
+					CallOrigin::from_account_id(ALICE),
+					addr.clone(),
+					0,
+					GAS_LIMIT,
+					None,
+					vec![],
+					false,
+					CollectEvents::Skip,
+					Determinism::Deterministic,
+				)
+				.gas_consumed;
+				sp_runtime::TransactionOutcome::Rollback(Ok::<_, DispatchError>(gas_consumed))
+			})
+			.unwrap()
+		};
+		assert_eq!(
+			gas_consumed(true),
+			gas_consumed(false)
+				.saturating_add(<Test as Config>::WeightInfo::remove_code().saturating_mul(33)),
+		);
+		RemoveUnusedCode::set(true);
+
+		// Terminating the only user of the code removes it and refunds the uploader.
+		assert_ok!(Contracts::call(
+			RuntimeOrigin::signed(ALICE),
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			vec![],
+		));
+		assert!(get_contract_checked(&addr).is_none());
+		assert!(!crate::CodeInfoOf::<Test>::contains_key(&code_hash));
+		assert!(!crate::PristineCode::<Test>::contains_key(&code_hash));
+		assert_eq!(Balances::reserved_balance(&ALICE), 0);
+		assert!(System::events().iter().any(|record| record.event ==
+			RuntimeEvent::Contracts(crate::Event::CodeRemoved { code_hash })));
+	});
+}
+
 // This tests that one contract cannot prevent another from self-destructing by sending it
 // additional funds after it has been drained.
 #[test]
@@ -1346,6 +2130,7 @@ fn destroy_contract_and_transfer_funds() {
 			callee_code_hash.as_ref().to_vec(),
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -1410,6 +2195,7 @@ fn crypto_hashes() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -1436,13 +2222,14 @@ fn crypto_hashes() {
 			let mut params = vec![(n + 1) as u8];
 			params.extend_from_slice(input);
 			let result = <Pallet<Test>>::bare_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
 				addr.clone(),
 				0,
 				GAS_LIMIT,
 				None,
 				params,
 				false,
+				CollectEvents::Skip,
 				Determinism::Deterministic,
 			)
 			.result
@@ -1454,6 +2241,53 @@ fn crypto_hashes() {
 	})
 }
 
+#[test]
+fn crypto_hashes_multi() {
+	let (wasm, _code_hash) = compile_module::<Test>("hash_multi").unwrap();
+
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let addr = Contracts::bare_instantiate(
+			ALICE,
+			100_000,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+
+		let input = b"_DEAD_BEEF";
+		let call = |selector: u8| {
+			let mut params = vec![selector];
+			params.extend_from_slice(input);
+			<Pallet<Test>>::bare_call(
+				CallOrigin::from_account_id(ALICE),
+				addr.clone(),
+				0,
+				GAS_LIMIT,
+				None,
+				params,
+				false,
+				CollectEvents::Skip,
+				Determinism::Deterministic,
+			)
+			.result
+			.unwrap()
+			.data
+		};
+
+		// The two halves of the input are hashed as one.
+		assert_eq!(call(0), sp_io::hashing::keccak_256(input).to_vec());
+		assert_eq!(call(1), sp_io::hashing::blake2_128(input).to_vec());
+	})
+}
+
 #[test]
 fn transfer_return_code() {
 	let (wasm, _code_hash) = compile_module::<Test>("transfer_return_code").unwrap();
@@ -1470,6 +2304,7 @@ fn transfer_return_code() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -1478,13 +2313,14 @@ fn transfer_return_code() {
 		// Contract has only the minimal balance so any transfer will fail.
 		Balances::make_free_balance_be(&addr, min_balance);
 		let result = Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			addr.clone(),
 			0,
 			GAS_LIMIT,
 			None,
 			vec![],
 			false,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		)
 		.result
@@ -1497,13 +2333,14 @@ fn transfer_return_code() {
 		Balances::make_free_balance_be(&addr, min_balance + 100);
 		Balances::reserve(&addr, min_balance + 100).unwrap();
 		let result = Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			addr,
 			0,
 			GAS_LIMIT,
 			None,
 			vec![],
 			false,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		)
 		.result
@@ -1530,6 +2367,7 @@ fn call_return_code() {
 			vec![0],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -1538,13 +2376,14 @@ fn call_return_code() {
 
 		// Contract calls into Django which is no valid contract
 		let result = Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			addr_bob.clone(),
 			0,
 			GAS_LIMIT,
 			None,
 			AsRef::<[u8]>::as_ref(&DJANGO).to_vec(),
 			false,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		)
 		.result
@@ -1560,6 +2399,7 @@ fn call_return_code() {
 			vec![0],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -1568,7 +2408,7 @@ fn call_return_code() {
 
 		// Contract has only the minimal balance so any transfer will fail.
 		let result = Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			addr_bob.clone(),
 			0,
 			GAS_LIMIT,
@@ -1579,6 +2419,7 @@ fn call_return_code() {
 				.cloned()
 				.collect(),
 			false,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		)
 		.result
@@ -1591,7 +2432,7 @@ fn call_return_code() {
 		Balances::make_free_balance_be(&addr_bob, min_balance + 100);
 		Balances::reserve(&addr_bob, min_balance + 100).unwrap();
 		let result = Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			addr_bob.clone(),
 			0,
 			GAS_LIMIT,
@@ -1602,6 +2443,7 @@ fn call_return_code() {
 				.cloned()
 				.collect(),
 			false,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		)
 		.result
@@ -1611,7 +2453,7 @@ fn call_return_code() {
 		// Contract has enough balance but callee reverts because "1" is passed.
 		Balances::make_free_balance_be(&addr_bob, min_balance + 1000);
 		let result = Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			addr_bob.clone(),
 			0,
 			GAS_LIMIT,
@@ -1622,6 +2464,7 @@ fn call_return_code() {
 				.cloned()
 				.collect(),
 			false,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		)
 		.result
@@ -1630,7 +2473,7 @@ fn call_return_code() {
 
 		// Contract has enough balance but callee traps because "2" is passed.
 		let result = Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			addr_bob,
 			0,
 			GAS_LIMIT,
@@ -1641,6 +2484,7 @@ fn call_return_code() {
 				.cloned()
 				.collect(),
 			false,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		)
 		.result
@@ -1649,6 +2493,90 @@ fn call_return_code() {
 	});
 }
 
+#[test]
+fn failed_call_writes_revert_reason() {
+	let (caller_code, _caller_hash) = compile_module::<Test>("call_revert_reason").unwrap();
+	let (callee_code, _callee_hash) = compile_module::<Test>("ok_trap_revert").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		let addr_caller = Contracts::bare_instantiate(
+			ALICE,
+			0,
+			GAS_LIMIT,
+			None,
+			Code::Upload(caller_code),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+		let addr_callee = Contracts::bare_instantiate(
+			ALICE,
+			0,
+			GAS_LIMIT,
+			None,
+			Code::Upload(callee_code),
+			vec![0],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+		let call = |callee: &AccountId32, callee_input: u8| {
+			Contracts::bare_call(
+				CallOrigin::from_account_id(ALICE),
+				addr_caller.clone(),
+				0,
+				GAS_LIMIT,
+				None,
+				AsRef::<[u8]>::as_ref(callee)
+					.iter()
+					.cloned()
+					.chain([callee_input, 0, 0, 0])
+					.collect(),
+				false,
+				CollectEvents::Skip,
+				Determinism::Deterministic,
+			)
+			.result
+		};
+		let module_reason = |error: Error<Test>| match DispatchError::from(error) {
+			DispatchError::Module(ModuleError { index, error, .. }) =>
+				RevertReason::Module { index, error },
+			_ => unreachable!("Pallet errors are module errors; qed"),
+		};
+
+		// An error which is mapped to a return code describes itself through the output buffer.
+		let result = call(&DJANGO, 0).unwrap();
+		assert_eq!(result.data[..4], (RuntimeReturnCode::NotCallable as u32).to_le_bytes());
+		assert_eq!(
+			RevertReason::from_envelope(&result.data[4..]),
+			Some(module_reason(<Error<Test>>::ContractNotFound)),
+		);
+
+		// So does a trapping callee.
+		let result = call(&addr_callee, 2).unwrap();
+		assert_eq!(result.data[..4], (RuntimeReturnCode::CalleeTrapped as u32).to_le_bytes());
+		assert_eq!(
+			RevertReason::from_envelope(&result.data[4..]),
+			Some(module_reason(<Error<Test>>::ContractTrapped)),
+		);
+
+		// A reverting callee returns its own output instead.
+		let result = call(&addr_callee, 1).unwrap();
+		assert_eq!(result.data, (RuntimeReturnCode::CalleeReverted as u32).to_le_bytes());
+
+		// Any other error traps the caller.
+		assert_err!(call(&addr_caller, 0), <Error<Test>>::ReentranceDenied);
+	});
+}
+
 #[test]
 fn instantiate_return_code() {
 	let (caller_code, _caller_hash) = compile_module::<Test>("instantiate_return_code").unwrap();
@@ -1678,6 +2606,7 @@ fn instantiate_return_code() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -1686,13 +2615,14 @@ fn instantiate_return_code() {
 		// Contract has only the minimal balance so any transfer will fail.
 		Balances::make_free_balance_be(&addr, min_balance);
 		let result = Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			addr.clone(),
 			0,
 			GAS_LIMIT,
 			None,
 			callee_hash.clone(),
 			false,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		)
 		.result
@@ -1705,13 +2635,14 @@ fn instantiate_return_code() {
 		Balances::make_free_balance_be(&addr, min_balance + 10_000);
 		Balances::reserve(&addr, min_balance + 10_000).unwrap();
 		let result = Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			addr.clone(),
 			0,
 			GAS_LIMIT,
 			None,
 			callee_hash.clone(),
 			false,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		)
 		.result
@@ -1721,13 +2652,14 @@ fn instantiate_return_code() {
 		// Contract has enough balance but the passed code hash is invalid
 		Balances::make_free_balance_be(&addr, min_balance + 10_000);
 		let result = Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			addr.clone(),
 			0,
 			GAS_LIMIT,
 			None,
 			vec![0; 33],
 			false,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		)
 		.result
@@ -1736,13 +2668,14 @@ fn instantiate_return_code() {
 
 		// Contract has enough balance but callee reverts because "1" is passed.
 		let result = Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			addr.clone(),
 			0,
 			GAS_LIMIT,
 			None,
 			callee_hash.iter().chain(&1u32.to_le_bytes()).cloned().collect(),
 			false,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		)
 		.result
@@ -1751,13 +2684,14 @@ fn instantiate_return_code() {
 
 		// Contract has enough balance but callee traps because "2" is passed.
 		let result = Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			addr,
 			0,
 			GAS_LIMIT,
 			None,
 			callee_hash.iter().chain(&2u32.to_le_bytes()).cloned().collect(),
 			false,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		)
 		.result
@@ -1789,13 +2723,40 @@ fn disabled_chain_extension_wont_deploy() {
 }
 
 #[test]
-fn disabled_chain_extension_errors_on_call() {
-	let (code, _hash) = compile_module::<Test>("chain_extension").unwrap();
+fn disabled_randomness_wont_deploy() {
+	let (wasm, _code_hash) = compile_module::<Test>("random").unwrap();
 	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
-		let min_balance = <Test as Config>::Currency::minimum_balance();
-		let _ = Balances::deposit_creating(&ALICE, 1000 * min_balance);
-		let addr = Contracts::bare_instantiate(
-			ALICE,
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		RandomnessEnabled::set(false);
+		assert_err!(
+			Contracts::upload_code(
+				RuntimeOrigin::signed(ALICE),
+				wasm.clone(),
+				None,
+				Determinism::Deterministic
+			),
+			<Error<Test>>::CodeRejected,
+		);
+		assert!(!Contracts::host_functions().iter().any(|f| f.name == b"random"));
+
+		RandomnessEnabled::set(true);
+		assert_ok!(Contracts::upload_code(
+			RuntimeOrigin::signed(ALICE),
+			wasm,
+			None,
+			Determinism::Deterministic
+		));
+	});
+}
+
+#[test]
+fn disabled_chain_extension_errors_on_call() {
+	let (code, _hash) = compile_module::<Test>("chain_extension").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let min_balance = <Test as Config>::Currency::minimum_balance();
+		let _ = Balances::deposit_creating(&ALICE, 1000 * min_balance);
+		let addr = Contracts::bare_instantiate(
+			ALICE,
 			min_balance * 100,
 			GAS_LIMIT,
 			None,
@@ -1803,6 +2764,7 @@ fn disabled_chain_extension_errors_on_call() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -1830,6 +2792,7 @@ fn chain_extension_works() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -1838,13 +2801,14 @@ fn chain_extension_works() {
 		// 0 = read input buffer and pass it through as output
 		let input: Vec<u8> = ExtensionInput { extension_id: 0, func_id: 0, extra: &[99] }.into();
 		let result = Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			addr.clone(),
 			0,
 			GAS_LIMIT,
 			None,
 			input.clone(),
 			false,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		);
 		assert_eq!(TestExtension::last_seen_buffer(), input);
@@ -1852,13 +2816,14 @@ fn chain_extension_works() {
 
 		// 1 = treat inputs as integer primitives and store the supplied integers
 		Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			addr.clone(),
 			0,
 			GAS_LIMIT,
 			None,
 			ExtensionInput { extension_id: 0, func_id: 1, extra: &[] }.into(),
 			false,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		)
 		.result
@@ -1868,37 +2833,40 @@ fn chain_extension_works() {
 
 		// 2 = charge some extra weight (amount supplied in the fifth byte)
 		let result = Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			addr.clone(),
 			0,
 			GAS_LIMIT,
 			None,
 			ExtensionInput { extension_id: 0, func_id: 2, extra: &[0] }.into(),
 			false,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		);
 		assert_ok!(result.result);
 		let gas_consumed = result.gas_consumed;
 		let result = Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			addr.clone(),
 			0,
 			GAS_LIMIT,
 			None,
 			ExtensionInput { extension_id: 0, func_id: 2, extra: &[42] }.into(),
 			false,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		);
 		assert_ok!(result.result);
 		assert_eq!(result.gas_consumed.ref_time(), gas_consumed.ref_time() + 42);
 		let result = Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			addr.clone(),
 			0,
 			GAS_LIMIT,
 			None,
 			ExtensionInput { extension_id: 0, func_id: 2, extra: &[95] }.into(),
 			false,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		);
 		assert_ok!(result.result);
@@ -1906,13 +2874,14 @@ fn chain_extension_works() {
 
 		// 3 = diverging chain extension call that sets flags to 0x1 and returns a fixed buffer
 		let result = Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			addr.clone(),
 			0,
 			GAS_LIMIT,
 			None,
 			ExtensionInput { extension_id: 0, func_id: 3, extra: &[] }.into(),
 			false,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		)
 		.result
@@ -1920,17 +2889,44 @@ fn chain_extension_works() {
 		assert_eq!(result.flags, ReturnFlags::REVERT);
 		assert_eq!(result.data, vec![42, 99]);
 
+		// 4 = emit an event on behalf of the contract
+		initialize_block(2);
+		let input: Vec<u8> = ExtensionInput { extension_id: 0, func_id: 4, extra: &[7] }.into();
+		assert_ok!(
+			Contracts::bare_call(
+				CallOrigin::from_account_id(ALICE),
+				addr.clone(),
+				0,
+				GAS_LIMIT,
+				None,
+				input.clone(),
+				false,
+				CollectEvents::Skip,
+				Determinism::Deterministic,
+			)
+			.result
+		);
+		assert!(System::events().contains(&EventRecord {
+			phase: Phase::Initialization,
+			event: RuntimeEvent::Contracts(crate::Event::ContractEmitted {
+				contract: addr.clone(),
+				data: input,
+			}),
+			topics: vec![hash(&7u8)],
+		}));
+
 		// diverging to second chain extension that sets flags to 0x1 and returns a fixed buffer
 		// We set the MSB part to 1 (instead of 0) which routes the request into the second
 		// extension
 		let result = Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			addr.clone(),
 			0,
 			GAS_LIMIT,
 			None,
 			ExtensionInput { extension_id: 1, func_id: 0, extra: &[] }.into(),
 			false,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		)
 		.result
@@ -1954,6 +2950,13 @@ fn chain_extension_works() {
 	});
 }
 
+#[test]
+fn chain_extension_registered_ids() {
+	type Extensions = (TestExtension, RevertingExtension, DisabledExtension);
+	assert_eq!(<Extensions as ChainExtension<Test>>::registered_ids(), vec![0, 1, 2]);
+	assert!(<TestExtension as ChainExtension<Test>>::registered_ids().is_empty());
+}
+
 #[test]
 fn chain_extension_temp_storage_works() {
 	let (code, _hash) = compile_module::<Test>("chain_extension_temp_storage").unwrap();
@@ -1969,6 +2972,7 @@ fn chain_extension_temp_storage_works() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -1985,13 +2989,14 @@ fn chain_extension_temp_storage_works() {
 
 		assert_ok!(
 			Contracts::bare_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
 				addr.clone(),
 				0,
 				GAS_LIMIT,
 				None,
 				input.clone(),
 				false,
+				CollectEvents::Skip,
 				Determinism::Deterministic
 			)
 			.result
@@ -1999,6 +3004,79 @@ fn chain_extension_temp_storage_works() {
 	})
 }
 
+#[test]
+fn chain_extension_staking_works() {
+	let (code, _hash) = compile_module::<Test>("chain_extension_staking").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let min_balance = <Test as Config>::Currency::minimum_balance();
+		let _ = Balances::deposit_creating(&ALICE, 1000 * min_balance);
+		let addr = Contracts::bare_instantiate(
+			ALICE,
+			min_balance * 100,
+			GAS_LIMIT,
+			None,
+			Code::Upload(code),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+
+		let call = |func_id: u16, extra: Vec<u8>| {
+			Contracts::bare_call(
+				CallOrigin::from_account_id(ALICE),
+				addr.clone(),
+				0,
+				GAS_LIMIT,
+				None,
+				ExtensionInput { extension_id: 4, func_id, extra: &extra }.into(),
+				false,
+				CollectEvents::Skip,
+				Determinism::Deterministic,
+			)
+			.result
+			.map(|r| {
+				let code = u32::from_le_bytes(r.data[..4].try_into().unwrap());
+				(code, r.data[4..].to_vec())
+			})
+		};
+
+		// queries are answered through the output buffer
+		StakingEra::set(7);
+		let (code, output) = call(0, vec![]).unwrap();
+		assert_eq!(code, 0);
+		assert_eq!(<u32>::decode(&mut &output[..]).unwrap(), 7);
+		let (_, output) = call(1, addr.encode()).unwrap();
+		assert_eq!(<Option<u64>>::decode(&mut &output[..]).unwrap(), None);
+
+		// the contract bonds its own balance
+		assert_eq!(call(2, 100u64.encode()).unwrap().0, 0);
+		assert_eq!(call(3, 20u64.encode()).unwrap().0, 0);
+		assert_eq!(call(4, 30u64.encode()).unwrap().0, 0);
+		assert_eq!(call(5, 0u32.encode()).unwrap().0, 0);
+		let (_, output) = call(1, addr.encode()).unwrap();
+		assert_eq!(<Option<u64>>::decode(&mut &output[..]).unwrap(), Some(90));
+
+		// a rejected operation is reported through the return code
+		assert_eq!(call(2, 100u64.encode()).unwrap().0, 1);
+
+		assert_eq!(call(6, vec![BOB, CHARLIE].encode()).unwrap().0, 0);
+		assert_eq!(StakingNominations::get(), vec![(addr.clone(), vec![BOB, CHARLIE])]);
+		assert_eq!(call(7, vec![]).unwrap().0, 0);
+		assert!(StakingNominations::get().is_empty());
+
+		// contracts not covered by the filter can only query
+		StakingAllowed::set(false);
+		assert_eq!(call(0, vec![]).unwrap().0, 0);
+		assert_err!(call(3, 20u64.encode()), DispatchError::BadOrigin);
+		let (_, output) = call(1, addr.encode()).unwrap();
+		assert_eq!(<Option<u64>>::decode(&mut &output[..]).unwrap(), Some(90));
+	});
+}
+
 #[test]
 fn lazy_removal_works() {
 	let (code, _hash) = compile_module::<Test>("self_destruct").unwrap();
@@ -2015,6 +3093,7 @@ fn lazy_removal_works() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -2050,6 +3129,68 @@ fn lazy_removal_works() {
 	});
 }
 
+#[test]
+fn storage_queries_tell_why_there_is_no_contract() {
+	let (code, _hash) = compile_module::<Test>("self_destruct").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let min_balance = <Test as Config>::Currency::minimum_balance();
+		let _ = Balances::deposit_creating(&ALICE, 1000 * min_balance);
+
+		let addr = Contracts::bare_instantiate(
+			ALICE,
+			min_balance * 100,
+			GAS_LIMIT,
+			None,
+			Code::Upload(code),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+		assert_eq!(Contracts::get_storage(addr.clone(), vec![1; 32]), Ok(None));
+		assert_eq!(
+			Contracts::get_storage(addr.clone(), vec![1; 129]),
+			Err(ContractAccessError::KeyDecodingFailed)
+		);
+		assert_eq!(
+			Contracts::get_storage(ALICE, vec![1; 32]),
+			Err(ContractAccessError::NotAContract)
+		);
+		assert_eq!(
+			Contracts::get_storage(CHARLIE, vec![1; 32]),
+			Err(ContractAccessError::DoesntExist)
+		);
+
+		// terminate the contract
+		assert_ok!(Contracts::call(
+			RuntimeOrigin::signed(ALICE),
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			vec![]
+		));
+		assert_eq!(
+			Contracts::get_storage(addr.clone(), vec![1; 32]),
+			Err(ContractAccessError::PendingDeletion)
+		);
+		assert_eq!(
+			Contracts::get_storage_keys_paged(addr.clone(), vec![], None, 10),
+			Err(ContractAccessError::PendingDeletion)
+		);
+
+		// the storage is gone after the lazy removal
+		Contracts::on_idle(System::block_number(), Weight::MAX);
+		assert_eq!(
+			Contracts::get_storage(addr, vec![1; 32]),
+			Err(ContractAccessError::DoesntExist)
+		);
+	});
+}
+
 #[test]
 fn lazy_removal_on_full_queue_works_on_initialize() {
 	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
@@ -2087,6 +3228,7 @@ fn lazy_batch_removal_works() {
 				vec![],
 				vec![i],
 				false,
+				CollectEvents::Skip,
 			)
 			.result
 			.unwrap()
@@ -2152,6 +3294,7 @@ fn lazy_removal_partial_remove_works() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -2267,6 +3410,7 @@ fn lazy_removal_does_no_run_on_low_remaining_weight() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -2338,6 +3482,7 @@ fn lazy_removal_does_not_use_all_weight() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -2421,6 +3566,7 @@ fn deletion_queue_full() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -2457,6 +3603,7 @@ fn refcounter() {
 			vec![],
 			vec![0],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -2470,6 +3617,7 @@ fn refcounter() {
 			vec![],
 			vec![1],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -2486,6 +3634,7 @@ fn refcounter() {
 			vec![],
 			vec![2],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -2530,86 +3679,60 @@ fn refcounter() {
 
 		// refcount is `0` but code should still exists because it needs to be removed manually
 		assert!(crate::PristineCode::<Test>::contains_key(&code_hash));
-		assert!(crate::CodeStorage::<Test>::contains_key(&code_hash));
+		assert!(crate::CodeInfoOf::<Test>::contains_key(&code_hash));
 	});
 }
 
 #[test]
-fn reinstrument_does_charge() {
+fn only_pristine_code_is_stored() {
 	let (wasm, code_hash) = compile_module::<Test>("return_with_data").unwrap();
 	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
 		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
 		let min_balance = <Test as Config>::Currency::minimum_balance();
 		let zero = 0u32.to_le_bytes().encode();
-		let code_len = wasm.len() as u32;
 
 		let addr = Contracts::bare_instantiate(
 			ALICE,
 			min_balance * 100,
 			GAS_LIMIT,
 			None,
-			Code::Upload(wasm),
+			Code::Upload(wasm.clone()),
 			zero.clone(),
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
 		.account_id;
 
-		// Call the contract two times without reinstrument
+		// The code is stored exactly as uploaded.
+		assert_eq!(crate::PristineCode::<Test>::get(&code_hash).unwrap().to_vec(), wasm);
+		let code_info = crate::CodeInfoOf::<Test>::get(&code_hash).unwrap();
+		assert_eq!(code_info.code_len(), wasm.len() as u32);
 
-		let result0 = Contracts::bare_call(
-			ALICE,
-			addr.clone(),
-			0,
-			GAS_LIMIT,
-			None,
-			zero.clone(),
-			false,
-			Determinism::Deterministic,
-		);
+		// Calling the contract repeatedly always costs the same.
+		let call = || {
+			Contracts::bare_call(
+				CallOrigin::from_account_id(ALICE),
+				addr.clone(),
+				0,
+				GAS_LIMIT,
+				None,
+				zero.clone(),
+				false,
+				CollectEvents::Skip,
+				Determinism::Deterministic,
+			)
+		};
+		let result0 = call();
 		assert!(!result0.result.unwrap().did_revert());
-
-		let result1 = Contracts::bare_call(
-			ALICE,
-			addr.clone(),
-			0,
-			GAS_LIMIT,
-			None,
-			zero.clone(),
-			false,
-			Determinism::Deterministic,
-		);
+		let result1 = call();
 		assert!(!result1.result.unwrap().did_revert());
-
-		// They should match because both where called with the same schedule.
 		assert_eq!(result0.gas_consumed, result1.gas_consumed);
 
-		// We cannot change the schedule. Instead, we decrease the version of the deployed
-		// contract below the current schedule's version.
-		crate::CodeStorage::mutate(&code_hash, |code: &mut Option<PrefabWasmModule<Test>>| {
-			code.as_mut().unwrap().decrement_version();
-		});
-
-		// This call should trigger reinstrumentation
-		let result2 = Contracts::bare_call(
-			ALICE,
-			addr.clone(),
-			0,
-			GAS_LIMIT,
-			None,
-			zero.clone(),
-			false,
-			Determinism::Deterministic,
-		);
-		assert!(!result2.result.unwrap().did_revert());
-		assert!(result2.gas_consumed.ref_time() > result1.gas_consumed.ref_time());
-		assert_eq!(
-			result2.gas_consumed.ref_time(),
-			result1.gas_consumed.ref_time() +
-				<Test as Config>::WeightInfo::reinstrument(code_len).ref_time(),
-		);
+		// Executing the code does not modify it.
+		assert_eq!(crate::PristineCode::<Test>::get(&code_hash).unwrap().to_vec(), wasm);
 	});
 }
 
@@ -2628,18 +3751,20 @@ fn debug_message_works() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
 		.account_id;
 		let result = Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			addr,
 			0,
 			GAS_LIMIT,
 			None,
 			vec![],
 			true,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		);
 
@@ -2663,19 +3788,21 @@ fn debug_message_logging_disabled() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
 		.account_id;
 		// disable logging by passing `false`
 		let result = Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			addr.clone(),
 			0,
 			GAS_LIMIT,
 			None,
 			vec![],
 			false,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		);
 		assert_matches!(result.result, Ok(_));
@@ -2685,6 +3812,39 @@ fn debug_message_logging_disabled() {
 	});
 }
 
+#[test]
+fn debug_message_logged_on_chain_when_policy_allows() {
+	let (wasm, _code_hash) = compile_module::<Test>("debug_message_logging_disabled").unwrap();
+
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let addr = Contracts::bare_instantiate(
+			ALICE,
+			30_000,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+
+		// the fixture asserts `LoggingDisabled` which no longer holds when messages are logged
+		OnChainDebugging::set(DebugPolicy::Log);
+		assert_err_ignore_postinfo!(
+			Contracts::call(RuntimeOrigin::signed(ALICE), addr.clone(), 0, GAS_LIMIT, None, vec![]),
+			<Error<Test>>::ContractTrapped,
+		);
+
+		OnChainDebugging::set(DebugPolicy::Disabled);
+		assert_ok!(Contracts::call(RuntimeOrigin::signed(ALICE), addr, 0, GAS_LIMIT, None, vec![]));
+	});
+}
+
 #[test]
 fn debug_message_invalid_utf8() {
 	let (wasm, _code_hash) = compile_module::<Test>("debug_message_invalid_utf8").unwrap();
@@ -2700,18 +3860,20 @@ fn debug_message_invalid_utf8() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
 		.account_id;
 		let result = Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			addr,
 			0,
 			GAS_LIMIT,
 			None,
 			vec![],
 			true,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		);
 		assert_err!(result.result, <Error<Test>>::DebugMessageInvalidUTF8);
@@ -2736,6 +3898,7 @@ fn gas_estimation_nested_call_fixed_limit() {
 			vec![],
 			vec![0],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -2750,6 +3913,7 @@ fn gas_estimation_nested_call_fixed_limit() {
 			vec![],
 			vec![1],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -2763,13 +3927,14 @@ fn gas_estimation_nested_call_fixed_limit() {
 
 		// Call in order to determine the gas that is required for this call
 		let result = Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			addr_caller.clone(),
 			0,
 			GAS_LIMIT,
 			None,
 			input.clone(),
 			false,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		);
 		assert_ok!(&result.result);
@@ -2780,13 +3945,14 @@ fn gas_estimation_nested_call_fixed_limit() {
 		// Make the same call using the estimated gas. Should succeed.
 		assert_ok!(
 			Contracts::bare_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
 				addr_caller,
 				0,
 				result.gas_required,
 				Some(result.storage_deposit.charge_or_zero()),
 				input,
 				false,
+				CollectEvents::Skip,
 				Determinism::Deterministic,
 			)
 			.result
@@ -2794,6 +3960,61 @@ fn gas_estimation_nested_call_fixed_limit() {
 	});
 }
 
+#[test]
+fn code_stats_are_collected() {
+	let (wasm, code_hash) = compile_module::<Test>("ok_trap_revert").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let call = |addr: &AccountId32, input: u8| {
+			Contracts::bare_call(
+				CallOrigin::from_account_id(ALICE),
+				addr.clone(),
+				0,
+				GAS_LIMIT,
+				None,
+				vec![input],
+				false,
+				CollectEvents::Skip,
+				Determinism::Deterministic,
+			)
+		};
+
+		// nothing is collected while disabled
+		let addr = Contracts::bare_instantiate(
+			ALICE,
+			0,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm),
+			vec![0],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+		assert_ok!(call(&addr, 0).result);
+		assert_eq!(Contracts::code_stats(code_hash), None);
+
+		CollectCodeStats::set(true);
+		let ok = call(&addr, 0);
+		assert_ok!(&ok.result);
+		let reverted = call(&addr, 1);
+		assert!(reverted.result.unwrap().did_revert());
+		let trapped = call(&addr, 2);
+		assert!(trapped.result.is_err());
+
+		let stats = Contracts::code_stats(code_hash).unwrap();
+		assert_eq!(stats.calls, 3);
+		assert_eq!(stats.reverts, 2);
+		assert!(stats.gas_consumed.ref_time() > 0);
+		assert!(stats
+			.gas_consumed
+			.all_lte(ok.gas_consumed + reverted.gas_consumed + trapped.gas_consumed));
+	});
+}
+
 #[test]
 fn gas_estimation_call_runtime() {
 	use codec::Decode;
@@ -2813,6 +4034,7 @@ fn gas_estimation_call_runtime() {
 			vec![],
 			vec![0],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -2827,6 +4049,7 @@ fn gas_estimation_call_runtime() {
 			vec![],
 			vec![1],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -2842,13 +4065,14 @@ fn gas_estimation_call_runtime() {
 			data: vec![],
 		});
 		let result = Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			addr_caller.clone(),
 			0,
 			GAS_LIMIT,
 			None,
 			call.encode(),
 			false,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		);
 		// contract encodes the result of the dispatch runtime
@@ -2859,13 +4083,14 @@ fn gas_estimation_call_runtime() {
 		// Make the same call using the required gas. Should succeed.
 		assert_ok!(
 			Contracts::bare_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
 				addr_caller,
 				0,
 				result.gas_required,
 				None,
 				call.encode(),
 				false,
+				CollectEvents::Skip,
 				Determinism::Deterministic,
 			)
 			.result
@@ -2874,37 +4099,218 @@ fn gas_estimation_call_runtime() {
 }
 
 #[test]
-fn ecdsa_recover() {
-	let (wasm, _code_hash) = compile_module::<Test>("ecdsa_recover").unwrap();
-
+fn contracts_can_dispatch_with_contract_origin() {
+	let (code, _hash) = compile_module::<Test>("call_runtime").unwrap();
 	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
-		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let min_balance = <Test as Config>::Currency::minimum_balance();
+		let _ = Balances::deposit_creating(&ALICE, 1000 * min_balance);
 
-		// Instantiate the ecdsa_recover contract.
 		let addr = Contracts::bare_instantiate(
 			ALICE,
-			100_000,
+			min_balance * 100,
 			GAS_LIMIT,
 			None,
-			Code::Upload(wasm),
+			Code::Upload(code),
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
 		.account_id;
 
-		#[rustfmt::skip]
-		let signature: [u8; 65] = [
-			161, 234, 203,  74, 147, 96,  51, 212,   5, 174, 231,   9, 142,  48, 137, 201,
-			162, 118, 192,  67, 239, 16,  71, 216, 125,  86, 167, 139,  70,   7,  86, 241,
-			 33,  87, 154, 251,  81, 29, 160,   4, 176, 239,  88, 211, 244, 232, 232,  52,
-			211, 234, 100, 115, 230, 47,  80,  44, 152, 166,  62,  50,   8,  13,  86, 175,
-			 28,
-		];
-		#[rustfmt::skip]
-		let message_hash: [u8; 32] = [
+		// `remark_with_event` uses `ensure_signed`
+		let call = RuntimeCall::System(frame_system::Call::remark_with_event { remark: vec![1] });
+		let call_runtime = || {
+			let result = Contracts::bare_call(
+				CallOrigin::from_account_id(ALICE),
+				addr.clone(),
+				0,
+				GAS_LIMIT,
+				None,
+				call.encode(),
+				false,
+				CollectEvents::Skip,
+				Determinism::Deterministic,
+			)
+			.result
+			.unwrap();
+			u32::decode(&mut result.data.as_ref()).unwrap()
+		};
+		assert_eq!(call_runtime(), RuntimeReturnCode::Success as u32);
+
+		CallRuntimeAsContract::set(true);
+		assert_eq!(call_runtime(), RuntimeReturnCode::CallRuntimeReturnedError as u32);
+		CallRuntimeAsContract::set(false);
+
+		// Contracts are recognized no matter which origin they dispatch with.
+		let as_contract: RuntimeOrigin = crate::RawOrigin::Contract(addr.clone()).into();
+		let as_signed = RuntimeOrigin::signed(addr.clone());
+		for origin in [as_contract, as_signed] {
+			assert_eq!(
+				EnsureSignedOrContract::<Test>::try_origin(origin.clone()).ok(),
+				Some(Caller::Contract(addr.clone())),
+			);
+			assert_eq!(EnsureContract::<Test>::try_origin(origin).ok(), Some(addr.clone()));
+		}
+
+		assert_eq!(
+			EnsureSignedOrContract::<Test>::try_origin(RuntimeOrigin::signed(ALICE)).ok(),
+			Some(Caller::Signed(ALICE)),
+		);
+		assert!(EnsureContract::<Test>::try_origin(RuntimeOrigin::signed(ALICE)).is_err());
+		assert!(EnsureSignedOrContract::<Test>::try_origin(RuntimeOrigin::root()).is_err());
+	});
+}
+
+#[test]
+fn root_can_call() {
+	let (wasm, _code_hash) = compile_module::<Test>("caller_is_root").unwrap();
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let addr = Contracts::bare_instantiate(
+			ALICE,
+			0,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+
+		assert_ok!(Contracts::call(
+			RuntimeOrigin::root(),
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			vec![]
+		));
+		assert_noop!(
+			Contracts::call(RuntimeOrigin::none(), addr.clone(), 0, GAS_LIMIT, None, vec![]),
+			DispatchError::BadOrigin,
+		);
+
+		// The contract can tell root apart from a signed caller.
+		let caller_is_root = |origin| {
+			let output = Contracts::bare_call(
+				origin,
+				addr.clone(),
+				0,
+				GAS_LIMIT,
+				None,
+				vec![],
+				false,
+				CollectEvents::Skip,
+				Determinism::Deterministic,
+			)
+			.result
+			.unwrap();
+			u32::decode(&mut &output.data[..]).unwrap()
+		};
+		assert_eq!(caller_is_root(CallOrigin::Root), 1);
+		assert_eq!(caller_is_root(CallOrigin::from_account_id(ALICE)), 0);
+
+		// Root has no account to transfer value from.
+		assert_err_ignore_postinfo!(
+			Contracts::call(RuntimeOrigin::root(), addr, 1, GAS_LIMIT, None, vec![]),
+			<Error<Test>>::RootNotAllowed,
+		);
+	});
+}
+
+#[test]
+fn root_cannot_put_down_storage_deposit() {
+	let (wasm, _code_hash) = compile_module::<Test>("store").unwrap();
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let addr = Contracts::bare_instantiate(
+			ALICE,
+			0,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+
+		// Growing the storage requires a deposit root can't pay.
+		assert_err_ignore_postinfo!(
+			Contracts::call(
+				RuntimeOrigin::root(),
+				addr.clone(),
+				0,
+				GAS_LIMIT,
+				None,
+				10u32.encode()
+			),
+			<Error<Test>>::StorageDepositLimitExhausted,
+		);
+
+		// Once a signed account paid for the storage root can overwrite it.
+		assert_ok!(Contracts::call(
+			RuntimeOrigin::signed(ALICE),
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			10u32.encode()
+		));
+		assert_ok!(Contracts::call(
+			RuntimeOrigin::root(),
+			addr,
+			0,
+			GAS_LIMIT,
+			None,
+			10u32.encode()
+		));
+	});
+}
+
+#[test]
+fn ecdsa_recover() {
+	let (wasm, _code_hash) = compile_module::<Test>("ecdsa_recover").unwrap();
+
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		// Instantiate the ecdsa_recover contract.
+		let addr = Contracts::bare_instantiate(
+			ALICE,
+			100_000,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+
+		#[rustfmt::skip]
+		let signature: [u8; 65] = [
+			161, 234, 203,  74, 147, 96,  51, 212,   5, 174, 231,   9, 142,  48, 137, 201,
+			162, 118, 192,  67, 239, 16,  71, 216, 125,  86, 167, 139,  70,   7,  86, 241,
+			 33,  87, 154, 251,  81, 29, 160,   4, 176, 239,  88, 211, 244, 232, 232,  52,
+			211, 234, 100, 115, 230, 47,  80,  44, 152, 166,  62,  50,   8,  13,  86, 175,
+			 28,
+		];
+		#[rustfmt::skip]
+		let message_hash: [u8; 32] = [
 			162, 28, 244, 179, 96, 76, 244, 178, 188,  83, 230, 248, 143, 106,  77, 117,
 			239, 95, 244, 171, 65, 95,  62, 153, 174, 166, 182,  28, 130,  73, 196, 208
 		];
@@ -2919,13 +4325,14 @@ fn ecdsa_recover() {
 		params.extend_from_slice(&message_hash);
 		assert!(params.len() == 65 + 32);
 		let result = <Pallet<Test>>::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			addr.clone(),
 			0,
 			GAS_LIMIT,
 			None,
 			params,
 			false,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		)
 		.result
@@ -2935,6 +4342,68 @@ fn ecdsa_recover() {
 	})
 }
 
+#[test]
+fn ecdsa_to_eth_address() {
+	let (wasm, _code_hash) = compile_module::<Test>("ecdsa_to_eth_address").unwrap();
+
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		// Instantiate the ecdsa_to_eth_address contract.
+		let addr = Contracts::bare_instantiate(
+			ALICE,
+			100_000,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+
+		let pubkey_compressed = array_bytes::hex2bytes_unchecked(
+			"028db55b05db86c0b1786ca49f095d76344c9e6056b2f02701a7e7f3c20aabfd91",
+		);
+		let result = <Pallet<Test>>::bare_call(
+			CallOrigin::from_account_id(ALICE),
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			pubkey_compressed,
+			false,
+			CollectEvents::Skip,
+			Determinism::Deterministic,
+		)
+		.result
+		.unwrap();
+		assert!(!result.did_revert());
+		assert_eq!(
+			result.data,
+			array_bytes::hex2bytes_unchecked("09231da7b19A016f9e576d23B16277062F4d46A8")
+		);
+
+		// An invalid public key traps the contract.
+		let result = <Pallet<Test>>::bare_call(
+			CallOrigin::from_account_id(ALICE),
+			addr,
+			0,
+			GAS_LIMIT,
+			None,
+			vec![0; 33],
+			false,
+			CollectEvents::Skip,
+			Determinism::Deterministic,
+		)
+		.result;
+		assert_err!(result, <Error<Test>>::ContractTrapped);
+	})
+}
+
 #[test]
 fn upload_code_works() {
 	let (wasm, code_hash) = compile_module::<Test>("dummy").unwrap();
@@ -2945,14 +4414,14 @@ fn upload_code_works() {
 		// Drop previous events
 		initialize_block(2);
 
-		assert!(!<CodeStorage<Test>>::contains_key(code_hash));
+		assert!(!<CodeInfoOf<Test>>::contains_key(code_hash));
 		assert_ok!(Contracts::upload_code(
 			RuntimeOrigin::signed(ALICE),
 			wasm,
 			Some(codec::Compact(1_000)),
 			Determinism::Deterministic,
 		));
-		assert!(<CodeStorage<Test>>::contains_key(code_hash));
+		assert!(<CodeInfoOf<Test>>::contains_key(code_hash));
 
 		assert_eq!(
 			System::events(),
@@ -2961,7 +4430,7 @@ fn upload_code_works() {
 					phase: Phase::Initialization,
 					event: RuntimeEvent::Balances(pallet_balances::Event::Reserved {
 						who: ALICE,
-						amount: 173,
+						amount: 106,
 					}),
 					topics: vec![],
 				},
@@ -2975,6 +4444,82 @@ fn upload_code_works() {
 	});
 }
 
+#[test]
+fn upload_and_instantiate_origins_are_checked() {
+	let (wasm, code_hash) = compile_module::<Test>("dummy").unwrap();
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let _ = Balances::deposit_creating(&BOB, 1_000_000);
+		UploadAccount::set(Some(ALICE));
+		InstantiateAccount::set(Some(BOB));
+
+		assert_noop!(
+			Contracts::upload_code(
+				RuntimeOrigin::signed(BOB),
+				wasm.clone(),
+				None,
+				Determinism::Deterministic
+			),
+			DispatchError::BadOrigin,
+		);
+		assert_ok!(Contracts::upload_code(
+			RuntimeOrigin::signed(ALICE),
+			wasm.clone(),
+			None,
+			Determinism::Deterministic,
+		));
+
+		// Instantiating with code requires both origins.
+		assert_noop!(
+			Contracts::instantiate_with_code(
+				RuntimeOrigin::signed(ALICE),
+				0,
+				GAS_LIMIT,
+				None,
+				wasm.clone(),
+				vec![],
+				vec![],
+			),
+			DispatchError::BadOrigin,
+		);
+		assert_noop!(
+			Contracts::instantiate_with_code(
+				RuntimeOrigin::signed(BOB),
+				0,
+				GAS_LIMIT,
+				None,
+				wasm,
+				vec![],
+				vec![],
+			),
+			DispatchError::BadOrigin,
+		);
+
+		assert_noop!(
+			Contracts::instantiate(
+				RuntimeOrigin::signed(ALICE),
+				0,
+				GAS_LIMIT,
+				None,
+				code_hash,
+				vec![],
+				vec![],
+			),
+			DispatchError::BadOrigin,
+		);
+		assert_ok!(Contracts::instantiate(
+			RuntimeOrigin::signed(BOB),
+			0,
+			GAS_LIMIT,
+			None,
+			code_hash,
+			vec![],
+			vec![],
+		));
+	});
+}
+
 #[test]
 fn upload_code_limit_too_low() {
 	let (wasm, _code_hash) = compile_module::<Test>("dummy").unwrap();
@@ -3004,7 +4549,8 @@ fn upload_code_not_enough_balance() {
 	let (wasm, _code_hash) = compile_module::<Test>("dummy").unwrap();
 
 	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
-		let _ = Balances::deposit_creating(&ALICE, 150);
+		// Less than the deposit for storing the code.
+		let _ = Balances::deposit_creating(&ALICE, 100);
 
 		// Drop previous events
 		initialize_block(2);
@@ -3040,9 +4586,9 @@ fn remove_code_works() {
 			Determinism::Deterministic,
 		));
 
-		assert!(<CodeStorage<Test>>::contains_key(code_hash));
+		assert!(<CodeInfoOf<Test>>::contains_key(code_hash));
 		assert_ok!(Contracts::remove_code(RuntimeOrigin::signed(ALICE), code_hash));
-		assert!(!<CodeStorage<Test>>::contains_key(code_hash));
+		assert!(!<CodeInfoOf<Test>>::contains_key(code_hash));
 
 		assert_eq!(
 			System::events(),
@@ -3051,7 +4597,7 @@ fn remove_code_works() {
 					phase: Phase::Initialization,
 					event: RuntimeEvent::Balances(pallet_balances::Event::Reserved {
 						who: ALICE,
-						amount: 173,
+						amount: 106,
 					}),
 					topics: vec![],
 				},
@@ -3064,7 +4610,7 @@ fn remove_code_works() {
 					phase: Phase::Initialization,
 					event: RuntimeEvent::Balances(pallet_balances::Event::Unreserved {
 						who: ALICE,
-						amount: 173,
+						amount: 106,
 					}),
 					topics: vec![],
 				},
@@ -3107,7 +4653,7 @@ fn remove_code_wrong_origin() {
 					phase: Phase::Initialization,
 					event: RuntimeEvent::Balances(pallet_balances::Event::Reserved {
 						who: ALICE,
-						amount: 173,
+						amount: 106,
 					}),
 					topics: vec![],
 				},
@@ -3122,17 +4668,76 @@ fn remove_code_wrong_origin() {
 }
 
 #[test]
-fn remove_code_in_use() {
+fn set_code_metadata_works() {
 	let (wasm, code_hash) = compile_module::<Test>("dummy").unwrap();
 
 	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
 		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-
-		assert_ok!(Contracts::instantiate_with_code(
+		assert_ok!(Contracts::upload_code(
 			RuntimeOrigin::signed(ALICE),
-			0,
-			GAS_LIMIT,
-			None,
+			wasm,
+			None,
+			Determinism::Deterministic,
+		));
+		let code_deposit = Balances::reserved_balance(&ALICE);
+
+		// only the owner can set the metadata
+		assert_noop!(
+			Contracts::set_code_metadata(
+				RuntimeOrigin::signed(BOB),
+				code_hash,
+				Some(vec![1, 2, 3].try_into().unwrap()),
+			),
+			sp_runtime::traits::BadOrigin,
+		);
+
+		// deposit is one item plus one unit per byte
+		assert_ok!(Contracts::set_code_metadata(
+			RuntimeOrigin::signed(ALICE),
+			code_hash,
+			Some(vec![1, 2, 3].try_into().unwrap()),
+		));
+		assert_eq!(Contracts::code_metadata(code_hash), Some(vec![1, 2, 3]));
+		assert_eq!(Balances::reserved_balance(&ALICE), code_deposit + 3 + 2);
+
+		// replacing the metadata refunds the old deposit
+		assert_ok!(Contracts::set_code_metadata(
+			RuntimeOrigin::signed(ALICE),
+			code_hash,
+			Some(vec![4; 10].try_into().unwrap()),
+		));
+		assert_eq!(Contracts::code_metadata(code_hash), Some(vec![4; 10]));
+		assert_eq!(Balances::reserved_balance(&ALICE), code_deposit + 10 + 2);
+		assert!(System::events()
+			.iter()
+			.any(|r| r.event ==
+				RuntimeEvent::Contracts(crate::Event::CodeMetadataUpdated { code_hash })));
+
+		// removing the code removes the metadata and refunds everything
+		assert_ok!(Contracts::remove_code(RuntimeOrigin::signed(ALICE), code_hash));
+		assert_eq!(Contracts::code_metadata(code_hash), None);
+		assert_eq!(Balances::reserved_balance(&ALICE), 0);
+
+		// metadata can't be set for code that doesn't exist
+		assert_noop!(
+			Contracts::set_code_metadata(RuntimeOrigin::signed(ALICE), code_hash, None),
+			<Error<Test>>::CodeNotFound,
+		);
+	});
+}
+
+#[test]
+fn remove_code_in_use() {
+	let (wasm, code_hash) = compile_module::<Test>("dummy").unwrap();
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		assert_ok!(Contracts::instantiate_with_code(
+			RuntimeOrigin::signed(ALICE),
+			0,
+			GAS_LIMIT,
+			None,
 			wasm,
 			vec![],
 			vec![],
@@ -3150,6 +4755,55 @@ fn remove_code_in_use() {
 	});
 }
 
+#[test]
+fn remove_code_denied_for_delegate_dependency() {
+	let (wasm, code_hash) = compile_module::<Test>("dummy").unwrap();
+	let (proxy_wasm, _) = compile_module::<Test>("lock_delegate_dependency").unwrap();
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Contracts::upload_code(
+			RuntimeOrigin::signed(ALICE),
+			wasm,
+			None,
+			Determinism::Deterministic,
+		));
+
+		// the proxy locks the code in its constructor
+		let addr = Contracts::bare_instantiate(
+			ALICE,
+			0,
+			GAS_LIMIT,
+			None,
+			Code::Upload(proxy_wasm),
+			code_hash.encode(),
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+		assert!(get_contract(&addr).delegate_dependencies.contains_key(&code_hash));
+		assert_noop!(
+			Contracts::remove_code(RuntimeOrigin::signed(ALICE), code_hash),
+			<Error<Test>>::CodeInUse,
+		);
+
+		// once unlocked the code can be removed
+		assert_ok!(Contracts::call(
+			RuntimeOrigin::signed(ALICE),
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			code_hash.encode(),
+		));
+		assert!(get_contract(&addr).delegate_dependencies.is_empty());
+		assert_ok!(Contracts::remove_code(RuntimeOrigin::signed(ALICE), code_hash));
+	});
+}
+
 #[test]
 fn remove_code_not_found() {
 	let (_wasm, code_hash) = compile_module::<Test>("dummy").unwrap();
@@ -3169,6 +4823,112 @@ fn remove_code_not_found() {
 	});
 }
 
+#[test]
+fn force_remove_code_tombstones_contracts() {
+	let (wasm, code_hash) = compile_module::<Test>("dummy").unwrap();
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		let addr = Contracts::bare_instantiate(
+			ALICE,
+			0,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm.clone()),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+
+		assert_noop!(
+			Contracts::force_remove_code(RuntimeOrigin::signed(ALICE), code_hash),
+			DispatchError::BadOrigin,
+		);
+
+		// Drop previous events
+		initialize_block(2);
+
+		assert_ok!(Contracts::force_remove_code(RuntimeOrigin::root(), code_hash));
+		assert!(!<CodeInfoOf<Test>>::contains_key(code_hash));
+		assert_eq!(Balances::reserved_balance(&ALICE), 0);
+		assert_eq!(
+			System::events(),
+			vec![
+				EventRecord {
+					phase: Phase::Initialization,
+					event: RuntimeEvent::Balances(pallet_balances::Event::Unreserved {
+						who: ALICE,
+						amount: 106,
+					}),
+					topics: vec![],
+				},
+				EventRecord {
+					phase: Phase::Initialization,
+					event: RuntimeEvent::Contracts(crate::Event::CodeForceRemoved {
+						code_hash,
+						refcount: 1,
+					}),
+					topics: vec![code_hash],
+				},
+			],
+		);
+
+		// the contract still exists but can't be called anymore
+		assert!(get_contract_checked(&addr).is_some());
+		assert_err_ignore_postinfo!(
+			Contracts::call(RuntimeOrigin::signed(ALICE), addr, 0, GAS_LIMIT, None, vec![]),
+			<Error<Test>>::CodeTombstoned,
+		);
+
+		// the code can't be brought back
+		assert_noop!(
+			Contracts::upload_code(
+				RuntimeOrigin::signed(ALICE),
+				wasm,
+				None,
+				Determinism::Deterministic
+			),
+			<Error<Test>>::CodeTombstoned,
+		);
+		assert_noop!(
+			Contracts::force_remove_code(RuntimeOrigin::root(), code_hash),
+			<Error<Test>>::CodeNotFound,
+		);
+	});
+}
+
+#[test]
+fn force_removed_unused_code_cannot_be_uploaded_again() {
+	let (wasm, code_hash) = compile_module::<Test>("dummy").unwrap();
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Contracts::upload_code(
+			RuntimeOrigin::signed(ALICE),
+			wasm.clone(),
+			None,
+			Determinism::Deterministic,
+		));
+
+		assert_ok!(Contracts::force_remove_code(RuntimeOrigin::root(), code_hash));
+		assert!(<TombstonedCode<Test>>::contains_key(code_hash));
+		assert_noop!(
+			Contracts::upload_code(
+				RuntimeOrigin::signed(ALICE),
+				wasm,
+				None,
+				Determinism::Deterministic
+			),
+			<Error<Test>>::CodeTombstoned,
+		);
+	});
+}
+
 #[test]
 fn instantiate_with_zero_balance_works() {
 	let (wasm, code_hash) = compile_module::<Test>("dummy").unwrap();
@@ -3189,20 +4949,19 @@ fn instantiate_with_zero_balance_works() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
 		.account_id;
 
 		// Check that the BOB contract has been instantiated.
-		get_contract(&addr);
+		let deposit_account = (*get_contract(&addr).deposit_account).clone();
 
 		// Make sure the account exists even though no free balance was send
-		assert_eq!(<Test as Config>::Currency::free_balance(&addr), 0,);
-		assert_eq!(
-			<Test as Config>::Currency::total_balance(&addr),
-			<Test as Config>::Currency::minimum_balance(),
-		);
+		assert_eq!(<Test as Config>::Currency::free_balance(&addr), min_balance);
+		assert_eq!(<Test as Config>::Currency::total_balance(&addr), min_balance);
+		assert_eq!(<Test as Config>::Currency::total_balance(&deposit_account), min_balance);
 
 		assert_eq!(
 			System::events(),
@@ -3210,7 +4969,31 @@ fn instantiate_with_zero_balance_works() {
 				EventRecord {
 					phase: Phase::Initialization,
 					event: RuntimeEvent::System(frame_system::Event::NewAccount {
-						account: addr.clone()
+						account: deposit_account.clone(),
+					}),
+					topics: vec![],
+				},
+				EventRecord {
+					phase: Phase::Initialization,
+					event: RuntimeEvent::Balances(pallet_balances::Event::Endowed {
+						account: deposit_account.clone(),
+						free_balance: min_balance,
+					}),
+					topics: vec![],
+				},
+				EventRecord {
+					phase: Phase::Initialization,
+					event: RuntimeEvent::Balances(pallet_balances::Event::Transfer {
+						from: ALICE,
+						to: deposit_account.clone(),
+						amount: min_balance,
+					}),
+					topics: vec![],
+				},
+				EventRecord {
+					phase: Phase::Initialization,
+					event: RuntimeEvent::System(frame_system::Event::NewAccount {
+						account: addr.clone(),
 					}),
 					topics: vec![],
 				},
@@ -3233,17 +5016,18 @@ fn instantiate_with_zero_balance_works() {
 				},
 				EventRecord {
 					phase: Phase::Initialization,
-					event: RuntimeEvent::Balances(pallet_balances::Event::Reserved {
-						who: addr.clone(),
+					event: RuntimeEvent::Contracts(crate::Event::StorageBaseDepositCharged {
+						contract: addr.clone(),
+						payer: ALICE,
 						amount: min_balance,
 					}),
-					topics: vec![],
+					topics: vec![hash(&addr), hash(&ALICE)],
 				},
 				EventRecord {
 					phase: Phase::Initialization,
 					event: RuntimeEvent::Balances(pallet_balances::Event::Reserved {
 						who: ALICE,
-						amount: 173,
+						amount: 106,
 					}),
 					topics: vec![],
 				},
@@ -3285,20 +5069,19 @@ fn instantiate_with_below_existential_deposit_works() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
 		.account_id;
 
 		// Check that the BOB contract has been instantiated.
-		get_contract(&addr);
+		let deposit_account = (*get_contract(&addr).deposit_account).clone();
 
-		// Make sure the account exists even though no free balance was send
-		assert_eq!(<Test as Config>::Currency::free_balance(&addr), 50,);
-		assert_eq!(
-			<Test as Config>::Currency::total_balance(&addr),
-			<Test as Config>::Currency::minimum_balance() + 50,
-		);
+		// Make sure the account exists even though not enough free balance was send
+		assert_eq!(<Test as Config>::Currency::free_balance(&addr), min_balance + 50);
+		assert_eq!(<Test as Config>::Currency::total_balance(&addr), min_balance + 50);
+		assert_eq!(<Test as Config>::Currency::total_balance(&deposit_account), min_balance);
 
 		assert_eq!(
 			System::events(),
@@ -3306,7 +5089,31 @@ fn instantiate_with_below_existential_deposit_works() {
 				EventRecord {
 					phase: Phase::Initialization,
 					event: RuntimeEvent::System(frame_system::Event::NewAccount {
-						account: addr.clone()
+						account: deposit_account.clone(),
+					}),
+					topics: vec![],
+				},
+				EventRecord {
+					phase: Phase::Initialization,
+					event: RuntimeEvent::Balances(pallet_balances::Event::Endowed {
+						account: deposit_account.clone(),
+						free_balance: min_balance,
+					}),
+					topics: vec![],
+				},
+				EventRecord {
+					phase: Phase::Initialization,
+					event: RuntimeEvent::Balances(pallet_balances::Event::Transfer {
+						from: ALICE,
+						to: deposit_account.clone(),
+						amount: min_balance,
+					}),
+					topics: vec![],
+				},
+				EventRecord {
+					phase: Phase::Initialization,
+					event: RuntimeEvent::System(frame_system::Event::NewAccount {
+						account: addr.clone(),
 					}),
 					topics: vec![],
 				},
@@ -3329,11 +5136,12 @@ fn instantiate_with_below_existential_deposit_works() {
 				},
 				EventRecord {
 					phase: Phase::Initialization,
-					event: RuntimeEvent::Balances(pallet_balances::Event::Reserved {
-						who: addr.clone(),
+					event: RuntimeEvent::Contracts(crate::Event::StorageBaseDepositCharged {
+						contract: addr.clone(),
+						payer: ALICE,
 						amount: min_balance,
 					}),
-					topics: vec![],
+					topics: vec![hash(&addr), hash(&ALICE)],
 				},
 				EventRecord {
 					phase: Phase::Initialization,
@@ -3348,7 +5156,7 @@ fn instantiate_with_below_existential_deposit_works() {
 					phase: Phase::Initialization,
 					event: RuntimeEvent::Balances(pallet_balances::Event::Reserved {
 						who: ALICE,
-						amount: 173,
+						amount: 106,
 					}),
 					topics: vec![],
 				},
@@ -3386,10 +5194,12 @@ fn storage_deposit_works() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
 		.account_id;
+		let deposit_account = (*get_contract(&addr).deposit_account).clone();
 
 		// Drop previous events
 		initialize_block(2);
@@ -3450,7 +5260,7 @@ fn storage_deposit_works() {
 				EventRecord {
 					phase: Phase::Initialization,
 					event: RuntimeEvent::Contracts(crate::Event::Called {
-						caller: ALICE,
+						caller: CallOrigin::from_account_id(ALICE),
 						contract: addr.clone(),
 					}),
 					topics: vec![hash(&ALICE), hash(&addr)],
@@ -3459,15 +5269,7 @@ fn storage_deposit_works() {
 					phase: Phase::Initialization,
 					event: RuntimeEvent::Balances(pallet_balances::Event::Transfer {
 						from: ALICE,
-						to: addr.clone(),
-						amount: charged0,
-					}),
-					topics: vec![],
-				},
-				EventRecord {
-					phase: Phase::Initialization,
-					event: RuntimeEvent::Balances(pallet_balances::Event::Reserved {
-						who: addr.clone(),
+						to: deposit_account.clone(),
 						amount: charged0,
 					}),
 					topics: vec![],
@@ -3475,7 +5277,7 @@ fn storage_deposit_works() {
 				EventRecord {
 					phase: Phase::Initialization,
 					event: RuntimeEvent::Contracts(crate::Event::Called {
-						caller: ALICE,
+						caller: CallOrigin::from_account_id(ALICE),
 						contract: addr.clone(),
 					}),
 					topics: vec![hash(&ALICE), hash(&addr)],
@@ -3484,15 +5286,7 @@ fn storage_deposit_works() {
 					phase: Phase::Initialization,
 					event: RuntimeEvent::Balances(pallet_balances::Event::Transfer {
 						from: ALICE,
-						to: addr.clone(),
-						amount: charged1,
-					}),
-					topics: vec![],
-				},
-				EventRecord {
-					phase: Phase::Initialization,
-					event: RuntimeEvent::Balances(pallet_balances::Event::Reserved {
-						who: addr.clone(),
+						to: deposit_account.clone(),
 						amount: charged1,
 					}),
 					topics: vec![],
@@ -3500,18 +5294,17 @@ fn storage_deposit_works() {
 				EventRecord {
 					phase: Phase::Initialization,
 					event: RuntimeEvent::Contracts(crate::Event::Called {
-						caller: ALICE,
+						caller: CallOrigin::from_account_id(ALICE),
 						contract: addr.clone(),
 					}),
 					topics: vec![hash(&ALICE), hash(&addr)],
 				},
 				EventRecord {
 					phase: Phase::Initialization,
-					event: RuntimeEvent::Balances(pallet_balances::Event::ReserveRepatriated {
-						from: addr.clone(),
+					event: RuntimeEvent::Balances(pallet_balances::Event::Transfer {
+						from: deposit_account.clone(),
 						to: ALICE,
 						amount: refunded0,
-						destination_status: BalanceStatus::Free,
 					}),
 					topics: vec![],
 				},
@@ -3521,15 +5314,116 @@ fn storage_deposit_works() {
 }
 
 #[test]
-fn set_code_extrinsic() {
-	let (wasm, code_hash) = compile_module::<Test>("dummy").unwrap();
-	let (new_wasm, new_code_hash) = compile_module::<Test>("crypto_hashes").unwrap();
-
-	assert_ne!(code_hash, new_code_hash);
-
-	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+fn contract_info_reports_storage_usage() {
+	let (wasm, code_hash) = compile_module::<Test>("multi_store").unwrap();
+	ExtBuilder::default().existential_deposit(200).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let addr = Contracts::bare_instantiate(
+			ALICE,
+			0,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+
+		assert_ok!(Contracts::call(
+			RuntimeOrigin::signed(ALICE),
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			(1_000u32, 5_000u32).encode(),
+		));
+
+		let info = Contracts::contract_info(&addr).unwrap();
+		assert_eq!(info.code_hash, code_hash);
+		assert_eq!(info.storage_bytes, 6_000);
+		assert_eq!(info.storage_items, 2);
+		// 4 is for creating 2 storage items
+		assert_eq!(info.storage_byte_deposit, 6_000);
+		assert_eq!(info.storage_item_deposit, 4);
+		assert_eq!(info.storage_deposit, 4 + 6_000);
+		assert_eq!(info.total_deposit, get_contract(&addr).total_deposit());
+		assert!(info.total_deposit > info.storage_deposit);
+
+		assert_eq!(Contracts::contract_info(&ALICE), None);
+	});
+}
+
+#[test]
+fn dry_run_reports_storage_changes() {
+	let (wasm, _code_hash) = compile_module::<Test>("multi_store").unwrap();
+	ExtBuilder::default().existential_deposit(200).build().execute_with(|| {
 		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let addr = Contracts::bare_instantiate(
+			ALICE,
+			0,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
 
+		// Storage changes are only collected when debugging.
+		let result = Contracts::bare_call(
+			CallOrigin::from_account_id(ALICE),
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			(10u32, 20u32).encode(),
+			false,
+			CollectEvents::Skip,
+			Determinism::Deterministic,
+		);
+		assert_ok!(result.result);
+		assert!(result.storage_changes.is_empty());
+
+		let result = Contracts::bare_call(
+			CallOrigin::from_account_id(ALICE),
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			(10u32, 20u32).encode(),
+			true,
+			CollectEvents::Skip,
+			Determinism::Deterministic,
+		);
+		assert_ok!(result.result);
+		let key = |first: u8| {
+			let mut key = [0u8; 32];
+			key[0] = first;
+			Some(key.to_vec())
+		};
+		assert_eq!(
+			result.storage_changes,
+			vec![
+				ContractStorageChange { contract: addr.clone(), key: key(1), removed: false },
+				ContractStorageChange { contract: addr, key: key(2), removed: false },
+			]
+		);
+	});
+}
+
+#[test]
+fn dry_run_reports_io_counters() {
+	let (wasm, _code_hash) = compile_module::<Test>("multi_store").unwrap();
+	ExtBuilder::default().existential_deposit(200).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
 		let addr = Contracts::bare_instantiate(
 			ALICE,
 			0,
@@ -3539,6 +5433,253 @@ fn set_code_extrinsic() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+		let call = |debug| {
+			Contracts::bare_call(
+				CallOrigin::from_account_id(ALICE),
+				addr.clone(),
+				0,
+				GAS_LIMIT,
+				None,
+				(10u32, 20u32).encode(),
+				debug,
+				CollectEvents::Skip,
+				Determinism::Deterministic,
+			)
+		};
+
+		// Counters are only collected when debugging.
+		let result = call(false);
+		assert_ok!(result.result);
+		assert_eq!(result.io_counters, IoCounters::default());
+
+		let result = call(true);
+		assert_ok!(result.result);
+		let code_hash = <ContractInfoOf<Test>>::get(&addr).unwrap().code_hash;
+		let code_len = crate::PristineCode::<Test>::get(code_hash).unwrap().len() as u32;
+		assert_eq!(
+			result.io_counters,
+			IoCounters {
+				storage_reads: 0,
+				storage_bytes_read: 0,
+				storage_writes: 2,
+				storage_bytes_written: 30,
+				code_bytes_loaded: code_len,
+			}
+		);
+	});
+}
+
+#[test]
+fn instantiate_dry_run_returns_trie_id_and_deposit_account() {
+	let (wasm, _code_hash) = compile_module::<Test>("dummy").unwrap();
+	ExtBuilder::default().existential_deposit(200).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let result = Contracts::bare_instantiate(
+			ALICE,
+			0,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap();
+
+		let contract = get_contract(&result.account_id);
+		assert_eq!(result.trie_id, Some(contract.trie_id.to_vec()));
+		assert_eq!(result.deposit_account, *contract.deposit_account);
+	});
+}
+
+#[test]
+fn call_batch_shares_storage_deposit_limit() {
+	let (wasm, _code_hash) = compile_module::<Test>("multi_store").unwrap();
+	ExtBuilder::default().existential_deposit(200).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let instantiate = |salt: Vec<u8>| {
+			Contracts::bare_instantiate(
+				ALICE,
+				0,
+				GAS_LIMIT,
+				None,
+				Code::Upload(wasm.clone()),
+				vec![],
+				salt,
+				false,
+				CollectEvents::Skip,
+			)
+			.result
+			.unwrap()
+			.account_id
+		};
+		let addr1 = instantiate(vec![1]);
+		let addr2 = instantiate(vec![2]);
+		let calls: BoundedVec<_, _> = vec![
+			BatchedCall { dest: addr1.clone(), value: 0, data: (100u32, 100u32).encode() },
+			BatchedCall { dest: addr2.clone(), value: 0, data: (100u32, 100u32).encode() },
+		]
+		.try_into()
+		.unwrap();
+
+		// Each call needs a deposit of 204 which fits into the limit on its own. Together they
+		// exceed it and hence nothing is stored.
+		assert_err_ignore_postinfo!(
+			Contracts::call_batch(
+				RuntimeOrigin::signed(ALICE),
+				calls.clone(),
+				GAS_LIMIT,
+				Some(codec::Compact(300)),
+			),
+			<Error<Test>>::StorageDepositLimitExhausted,
+		);
+		assert_eq!(Contracts::contract_info(&addr1).unwrap().storage_items, 0);
+		assert_eq!(Contracts::contract_info(&addr2).unwrap().storage_items, 0);
+
+		assert_ok!(Contracts::call_batch(
+			RuntimeOrigin::signed(ALICE),
+			calls,
+			GAS_LIMIT,
+			Some(codec::Compact(408)),
+		));
+		assert_eq!(Contracts::contract_info(&addr1).unwrap().storage_items, 2);
+		assert_eq!(Contracts::contract_info(&addr2).unwrap().storage_items, 2);
+	});
+}
+
+#[test]
+fn call_batch_is_all_or_nothing() {
+	let (store, _code_hash) = compile_module::<Test>("multi_store").unwrap();
+	let (trap, _code_hash) = compile_module::<Test>("ok_trap_revert").unwrap();
+	ExtBuilder::default().existential_deposit(200).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let instantiate = |wasm: Vec<u8>| {
+			Contracts::bare_instantiate(
+				ALICE,
+				0,
+				GAS_LIMIT,
+				None,
+				Code::Upload(wasm),
+				vec![],
+				vec![],
+				false,
+				CollectEvents::Skip,
+			)
+			.result
+			.unwrap()
+			.account_id
+		};
+		let store_addr = instantiate(store);
+		let trap_addr = instantiate(trap);
+		let batch = |input: u32| -> BoundedVec<_, _> {
+			vec![
+				BatchedCall { dest: store_addr.clone(), value: 0, data: (10u32, 10u32).encode() },
+				BatchedCall { dest: trap_addr.clone(), value: 0, data: input.encode() },
+			]
+			.try_into()
+			.unwrap()
+		};
+
+		// The second call reverts which rolls back the first one.
+		assert_err_ignore_postinfo!(
+			Contracts::call_batch(RuntimeOrigin::signed(ALICE), batch(1), GAS_LIMIT, None),
+			<Error<Test>>::ContractReverted,
+		);
+		assert_eq!(Contracts::contract_info(&store_addr).unwrap().storage_items, 0);
+
+		// The second call traps.
+		assert_err_ignore_postinfo!(
+			Contracts::call_batch(RuntimeOrigin::signed(ALICE), batch(2), GAS_LIMIT, None),
+			<Error<Test>>::ContractTrapped,
+		);
+		assert_eq!(Contracts::contract_info(&store_addr).unwrap().storage_items, 0);
+
+		assert_ok!(Contracts::call_batch(RuntimeOrigin::signed(ALICE), batch(0), GAS_LIMIT, None));
+		assert_eq!(Contracts::contract_info(&store_addr).unwrap().storage_items, 2);
+	});
+}
+
+#[test]
+fn max_storage_per_contract_is_enforced() {
+	let (wasm, _code_hash) = compile_module::<Test>("multi_store").unwrap();
+	ExtBuilder::default().existential_deposit(200).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let addr = Contracts::bare_instantiate(
+			ALICE,
+			0,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+		let store = |sizes: (u32, u32)| {
+			Contracts::call(
+				RuntimeOrigin::signed(ALICE),
+				addr.clone(),
+				0,
+				GAS_LIMIT,
+				None,
+				sizes.encode(),
+			)
+		};
+
+		MaxStoragePerContract::set(Some(StorageCeiling { bytes: 150, items: 1 }));
+		assert_err_ignore_postinfo!(store((10, 10)), <Error<Test>>::MaxStoragePerContractExceeded);
+		assert_eq!(get_contract(&addr).storage_items, 0);
+
+		MaxStoragePerContract::set(Some(StorageCeiling { bytes: 150, items: 2 }));
+		assert_err_ignore_postinfo!(
+			store((100, 100)),
+			<Error<Test>>::MaxStoragePerContractExceeded
+		);
+		assert_eq!(get_contract(&addr).storage_bytes, 0);
+		assert_ok!(store((50, 50)));
+		assert_eq!(get_contract(&addr).storage_bytes, 100);
+
+		// A contract above the ceiling can still shrink.
+		MaxStoragePerContract::set(Some(StorageCeiling { bytes: 50, items: 1 }));
+		assert_ok!(store((10, 10)));
+		assert_eq!(get_contract(&addr).storage_bytes, 20);
+
+		MaxStoragePerContract::set(None);
+		assert_ok!(store((100, 100)));
+		assert_eq!(get_contract(&addr).storage_bytes, 200);
+	});
+}
+
+#[test]
+fn set_code_extrinsic() {
+	let (wasm, code_hash) = compile_module::<Test>("dummy").unwrap();
+	let (new_wasm, new_code_hash) = compile_module::<Test>("crypto_hashes").unwrap();
+
+	assert_ne!(code_hash, new_code_hash);
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		let addr = Contracts::bare_instantiate(
+			ALICE,
+			0,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -3608,6 +5749,254 @@ fn set_code_extrinsic() {
 	});
 }
 
+#[test]
+fn set_call_filter_extrinsic() {
+	let (wasm, _code_hash) = compile_module::<Test>("dummy").unwrap();
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		let addr = Contracts::bare_instantiate(
+			ALICE,
+			0,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+		let filter: crate::CallFilter<Test> = vec![[0, 7]].try_into().unwrap();
+
+		// Drop previous events
+		initialize_block(2);
+		assert_eq!(get_contract(&addr).call_filter, None);
+
+		// only root can execute this extrinsic
+		assert_noop!(
+			Contracts::set_call_filter(
+				RuntimeOrigin::signed(ALICE),
+				addr.clone(),
+				Some(filter.clone())
+			),
+			sp_runtime::traits::BadOrigin,
+		);
+
+		// contract must exist
+		assert_noop!(
+			Contracts::set_call_filter(RuntimeOrigin::root(), BOB, Some(filter.clone())),
+			<Error<Test>>::ContractNotFound,
+		);
+
+		// successful call
+		assert_ok!(Contracts::set_call_filter(
+			RuntimeOrigin::root(),
+			addr.clone(),
+			Some(filter.clone())
+		));
+		assert_eq!(get_contract(&addr).call_filter, Some(filter));
+		assert_eq!(
+			System::events(),
+			vec![EventRecord {
+				phase: Phase::Initialization,
+				event: RuntimeEvent::Contracts(
+					pallet_contracts::Event::ContractCallFilterUpdated { contract: addr.clone() }
+				),
+				topics: vec![hash(&addr)],
+			},]
+		);
+
+		// the filter can be removed again
+		assert_ok!(Contracts::set_call_filter(RuntimeOrigin::root(), addr.clone(), None));
+		assert_eq!(get_contract(&addr).call_filter, None);
+	});
+}
+
+#[test]
+fn set_schedule_works() {
+	ExtBuilder::default().build().execute_with(|| {
+		let default = <Test as Config>::Schedule::get();
+		assert_eq!(Contracts::schedule(), default);
+
+		let mut schedule = default.clone();
+		schedule.limits.payload_len = default.limits.payload_len / 2;
+
+		// Only the configured origin may change the schedule.
+		assert_noop!(
+			Contracts::set_schedule(RuntimeOrigin::signed(ALICE), Some(Box::new(schedule.clone()))),
+			DispatchError::BadOrigin,
+		);
+
+		// The version must be increased.
+		assert_noop!(
+			Contracts::set_schedule(RuntimeOrigin::root(), Some(Box::new(schedule.clone()))),
+			<Error<Test>>::InvalidScheduleVersion,
+		);
+
+		// Limits that would break the memory assumptions are rejected.
+		let mut too_high = schedule.clone();
+		too_high.instruction_weights.version += 1;
+		too_high.limits.memory_pages = 4096;
+		assert_noop!(
+			Contracts::set_schedule(RuntimeOrigin::root(), Some(Box::new(too_high))),
+			<Error<Test>>::ScheduleLimitsTooHigh,
+		);
+
+		// The stack must hold every function the schedule allows.
+		let mut too_low = schedule.clone();
+		too_low.instruction_weights.version += 1;
+		too_low.limits.stack_height = too_low.limits.locals + too_low.limits.parameters - 1;
+		assert_noop!(
+			Contracts::set_schedule(RuntimeOrigin::root(), Some(Box::new(too_low))),
+			<Error<Test>>::InconsistentScheduleLimits,
+		);
+
+		// Forbidden imports must be distinct host functions.
+		let mut invalid_rules = schedule.clone();
+		invalid_rules.instruction_weights.version += 1;
+		invalid_rules.validation.forbidden_imports = vec![b"not_a_host_fn".to_vec()];
+		assert_noop!(
+			Contracts::set_schedule(RuntimeOrigin::root(), Some(Box::new(invalid_rules.clone()))),
+			<Error<Test>>::InvalidScheduleValidationRules,
+		);
+		invalid_rules.validation.forbidden_imports =
+			vec![b"seal_random".to_vec(), b"seal_random".to_vec()];
+		assert_noop!(
+			Contracts::set_schedule(RuntimeOrigin::root(), Some(Box::new(invalid_rules))),
+			<Error<Test>>::InvalidScheduleValidationRules,
+		);
+
+		schedule.instruction_weights.version += 1;
+		schedule.validation.forbidden_imports = vec![b"seal_random".to_vec()];
+		assert_ok!(Contracts::set_schedule(
+			RuntimeOrigin::root(),
+			Some(Box::new(schedule.clone()))
+		));
+		assert_eq!(Contracts::schedule(), schedule);
+		assert_eq!(Contracts::schedule().limits.payload_len, default.limits.payload_len / 2);
+		assert!(System::events()
+			.iter()
+			.any(|e| e.event == RuntimeEvent::Contracts(crate::Event::ScheduleUpdated)));
+
+		// Removing the override falls back to the configured schedule.
+		assert_ok!(Contracts::set_schedule(RuntimeOrigin::root(), None));
+		assert_eq!(Contracts::schedule(), default);
+
+		// A configured schedule with the same version supersedes the override.
+		schedule.instruction_weights.version = default.instruction_weights.version;
+		crate::ScheduleOverride::<Test>::put(schedule);
+		assert_eq!(Contracts::schedule(), default);
+	});
+}
+
+#[test]
+fn schedule_override_is_removed_by_migration() {
+	ExtBuilder::default().build().execute_with(|| {
+		let mut schedule = <Test as Config>::Schedule::get();
+		schedule.instruction_weights.version += 1;
+		assert_ok!(Contracts::set_schedule(RuntimeOrigin::root(), Some(Box::new(schedule))));
+
+		let mut migration = v18::Migration::<Test>::default();
+		assert_eq!(migration.step().0, IsFinished::Yes);
+		assert!(!crate::ScheduleOverride::<Test>::exists());
+		assert_eq!(Contracts::schedule(), <Test as Config>::Schedule::get());
+	});
+}
+
+#[test]
+fn pause_contract_works() {
+	let (wasm, _code_hash) = compile_module::<Test>("dummy").unwrap();
+
+	ExtBuilder::default().existential_deposit(100).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		let addr = Contracts::bare_instantiate(
+			ALICE,
+			1_000,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+		let call = || {
+			Contracts::call(RuntimeOrigin::signed(ALICE), addr.clone(), 0, GAS_LIMIT, None, vec![])
+		};
+
+		// Drop previous events
+		initialize_block(2);
+
+		// only root can execute these extrinsics
+		assert_noop!(
+			Contracts::pause_contract(RuntimeOrigin::signed(ALICE), addr.clone()),
+			sp_runtime::traits::BadOrigin,
+		);
+		assert_noop!(
+			Contracts::pause_contract(RuntimeOrigin::root(), BOB),
+			<Error<Test>>::ContractNotFound,
+		);
+		assert_noop!(
+			Contracts::unpause_contract(RuntimeOrigin::root(), addr.clone()),
+			<Error<Test>>::ContractNotPaused,
+		);
+
+		let contract = get_contract(&addr);
+		let balance = Balances::free_balance(&addr);
+		assert_ok!(Contracts::pause_contract(RuntimeOrigin::root(), addr.clone()));
+		assert!(get_contract(&addr).paused);
+		assert_noop!(
+			Contracts::pause_contract(RuntimeOrigin::root(), addr.clone()),
+			<Error<Test>>::ContractPaused,
+		);
+
+		// calls fail while storage and balance are untouched
+		assert_err_ignore_postinfo!(call(), <Error<Test>>::ContractPaused);
+		assert_eq!(crate::ContractInfo { paused: false, ..get_contract(&addr) }, contract);
+		assert_eq!(Balances::free_balance(&addr), balance);
+
+		assert_ok!(Contracts::unpause_contract(RuntimeOrigin::root(), addr.clone()));
+		assert_eq!(get_contract(&addr), contract);
+		assert_ok!(call());
+
+		assert_eq!(
+			System::events(),
+			vec![
+				EventRecord {
+					phase: Phase::Initialization,
+					event: RuntimeEvent::Contracts(pallet_contracts::Event::ContractPaused {
+						contract: addr.clone()
+					}),
+					topics: vec![hash(&addr)],
+				},
+				EventRecord {
+					phase: Phase::Initialization,
+					event: RuntimeEvent::Contracts(pallet_contracts::Event::ContractUnpaused {
+						contract: addr.clone()
+					}),
+					topics: vec![hash(&addr)],
+				},
+				EventRecord {
+					phase: Phase::Initialization,
+					event: RuntimeEvent::Contracts(crate::Event::Called {
+						caller: CallOrigin::from_account_id(ALICE),
+						contract: addr.clone(),
+					}),
+					topics: vec![hash(&ALICE), hash(&addr)],
+				},
+			]
+		);
+	});
+}
+
 #[test]
 fn call_after_killed_account_needs_funding() {
 	let (wasm, _code_hash) = compile_module::<Test>("dummy").unwrap();
@@ -3624,6 +6013,7 @@ fn call_after_killed_account_needs_funding() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -3695,7 +6085,7 @@ fn call_after_killed_account_needs_funding() {
 				EventRecord {
 					phase: Phase::Initialization,
 					event: RuntimeEvent::Contracts(crate::Event::Called {
-						caller: ALICE,
+						caller: CallOrigin::from_account_id(ALICE),
 						contract: addr.clone(),
 					}),
 					topics: vec![hash(&ALICE), hash(&addr)],
@@ -3727,7 +6117,7 @@ fn call_after_killed_account_needs_funding() {
 				EventRecord {
 					phase: Phase::Initialization,
 					event: RuntimeEvent::Contracts(crate::Event::Called {
-						caller: ALICE,
+						caller: CallOrigin::from_account_id(ALICE),
 						contract: addr.clone(),
 					}),
 					topics: vec![hash(&ALICE), hash(&addr)],
@@ -3796,6 +6186,7 @@ fn contract_reverted() {
 			input.clone(),
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap();
@@ -3813,6 +6204,7 @@ fn contract_reverted() {
 			ReturnFlags::empty().bits().encode(),
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -3833,13 +6225,14 @@ fn contract_reverted() {
 
 		// Calling directly: revert leads to success but the flags indicate the error
 		let result = Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			addr.clone(),
 			0,
 			GAS_LIMIT,
 			None,
 			input,
 			false,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		)
 		.result
@@ -3873,6 +6266,7 @@ fn code_rejected_error_works() {
 			vec![],
 			vec![],
 			true,
+			CollectEvents::Skip,
 		);
 		assert_err!(result.result, <Error<Test>>::CodeRejected);
 		assert_eq!(
@@ -3900,6 +6294,7 @@ fn code_rejected_error_works() {
 			vec![],
 			vec![],
 			true,
+			CollectEvents::Skip,
 		);
 		assert_err!(result.result, <Error<Test>>::CodeRejected);
 		assert_eq!(
@@ -3927,6 +6322,7 @@ fn set_code_hash() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -3943,13 +6339,14 @@ fn set_code_hash() {
 
 		// First call sets new code_hash and returns 1
 		let result = Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			contract_addr.clone(),
 			0,
 			GAS_LIMIT,
 			None,
 			new_code_hash.as_ref().to_vec(),
 			true,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		)
 		.result
@@ -3958,13 +6355,14 @@ fn set_code_hash() {
 
 		// Second calls new contract code that returns 2
 		let result = Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			contract_addr.clone(),
 			0,
 			GAS_LIMIT,
 			None,
 			vec![],
 			true,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		)
 		.result
@@ -3987,7 +6385,7 @@ fn set_code_hash() {
 				EventRecord {
 					phase: Phase::Initialization,
 					event: RuntimeEvent::Contracts(crate::Event::Called {
-						caller: ALICE,
+						caller: CallOrigin::from_account_id(ALICE),
 						contract: contract_addr.clone(),
 					}),
 					topics: vec![hash(&ALICE), hash(&contract_addr)],
@@ -3995,7 +6393,7 @@ fn set_code_hash() {
 				EventRecord {
 					phase: Phase::Initialization,
 					event: RuntimeEvent::Contracts(crate::Event::Called {
-						caller: ALICE,
+						caller: CallOrigin::from_account_id(ALICE),
 						contract: contract_addr.clone(),
 					}),
 					topics: vec![hash(&ALICE), hash(&contract_addr)],
@@ -4022,6 +6420,7 @@ fn storage_deposit_limit_is_enforced() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -4046,6 +6445,83 @@ fn storage_deposit_limit_is_enforced() {
 	});
 }
 
+#[test]
+fn transaction_storage_deposit_limit_is_enforced() {
+	let (wasm, _code_hash) = compile_module::<Test>("store").unwrap();
+	ExtBuilder::default().existential_deposit(200).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let addr = Contracts::bare_instantiate(
+			ALICE,
+			0,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm.clone()),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+		let store = |len: u32| {
+			Contracts::call(
+				RuntimeOrigin::signed(ALICE),
+				addr.clone(),
+				0,
+				GAS_LIMIT,
+				None,
+				len.to_le_bytes().to_vec(),
+			)
+		};
+		let call = RuntimeCall::Contracts(crate::Call::call {
+			dest: addr.clone(),
+			value: 0,
+			gas_limit: GAS_LIMIT,
+			storage_deposit_limit: None,
+			data: 100u32.to_le_bytes().to_vec(),
+		});
+		let info = call.get_dispatch_info();
+
+		// the limit is shared by all calls of the transaction
+		let pre = CheckStorageDepositLimit::<Test>::new(Some(150))
+			.pre_dispatch(&ALICE, &call, &info, 0)
+			.unwrap();
+		assert_ok!(store(100));
+		assert_err_ignore_postinfo!(store(200), <Error<Test>>::StorageDepositLimitExhausted);
+
+		// refunds are added back to the limit
+		assert_ok!(store(0));
+		assert_ok!(store(100));
+		assert_err_ignore_postinfo!(store(200), <Error<Test>>::StorageDepositLimitExhausted);
+
+		// the limit is removed once the transaction is dispatched
+		assert_ok!(CheckStorageDepositLimit::<Test>::post_dispatch(
+			Some(pre),
+			&info,
+			&Default::default(),
+			0,
+			&Ok(()),
+		));
+		assert!(!TransactionDepositLimit::<Test>::exists());
+		assert_ok!(store(200));
+
+		// an upload that can't fit into the limit is rejected by the pool
+		let (wasm, _code_hash) = compile_module::<Test>("dummy").unwrap();
+		let call = RuntimeCall::Contracts(crate::Call::upload_code {
+			code: wasm,
+			storage_deposit_limit: None,
+			determinism: Determinism::Deterministic,
+		});
+		let info = call.get_dispatch_info();
+		assert_eq!(
+			CheckStorageDepositLimit::<Test>::new(Some(0)).validate(&ALICE, &call, &info, 0),
+			Err(InvalidTransaction::Payment.into()),
+		);
+		assert_ok!(CheckStorageDepositLimit::<Test>::new(None).validate(&ALICE, &call, &info, 0));
+	});
+}
+
 #[test]
 fn storage_deposit_limit_is_enforced_late() {
 	let (wasm_caller, _code_hash_caller) =
@@ -4064,6 +6540,7 @@ fn storage_deposit_limit_is_enforced_late() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -4077,6 +6554,7 @@ fn storage_deposit_limit_is_enforced_late() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -4191,6 +6669,270 @@ fn storage_deposit_limit_is_enforced_late() {
 	});
 }
 
+#[test]
+fn exceeding_subcall_deposit_limit_does_not_trap_caller() {
+	let (wasm_caller, _code_hash_caller) =
+		compile_module::<Test>("call_with_deposit_limit").unwrap();
+	let (wasm_callee, _code_hash_callee) = compile_module::<Test>("store").unwrap();
+	ExtBuilder::default().existential_deposit(200).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		let addr_caller = Contracts::bare_instantiate(
+			ALICE,
+			0,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm_caller),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+		let addr_callee = Contracts::bare_instantiate(
+			ALICE,
+			0,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm_callee),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+		let input = |deposit_limit: u64| -> Vec<u8> {
+			deposit_limit
+				.to_le_bytes()
+				.iter()
+				.chain(<_ as AsRef<[u8]>>::as_ref(&addr_callee))
+				.chain(100u32.to_le_bytes().iter())
+				.cloned()
+				.collect()
+		};
+		let trie_id = get_contract(&addr_callee).trie_id;
+		let key: FixSizedKey = {
+			let mut key = [0u8; 32];
+			key[0] = 1;
+			key
+		};
+
+		// 100 bytes of storage cost 102 Balance which is more than the callee may use. The caller
+		// gets an error code and keeps running.
+		let result = Contracts::bare_call(
+			CallOrigin::from_account_id(ALICE),
+			addr_caller.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			input(50),
+			false,
+			CollectEvents::Skip,
+			Determinism::Deterministic,
+		)
+		.result
+		.unwrap();
+		assert_return_code!(result, RuntimeReturnCode::CalleeTrapped);
+		assert!(Storage::<Test>::read(&trie_id, &key).is_none());
+
+		// A sufficient limit lets the callee create its storage.
+		let result = Contracts::bare_call(
+			CallOrigin::from_account_id(ALICE),
+			addr_caller,
+			0,
+			GAS_LIMIT,
+			None,
+			input(102),
+			false,
+			CollectEvents::Skip,
+			Determinism::Deterministic,
+		)
+		.result
+		.unwrap();
+		assert_return_code!(result, RuntimeReturnCode::Success);
+		assert!(Storage::<Test>::read(&trie_id, &key).is_some());
+	});
+}
+
+#[test]
+fn last_subcall_deposit_is_returned_to_caller() {
+	let (wasm_caller, _code_hash_caller) = compile_module::<Test>("last_subcall_deposit").unwrap();
+	let (wasm_callee, _code_hash_callee) = compile_module::<Test>("store").unwrap();
+	ExtBuilder::default().existential_deposit(200).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		let addr_caller = Contracts::bare_instantiate(
+			ALICE,
+			0,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm_caller),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+		let addr_callee = Contracts::bare_instantiate(
+			ALICE,
+			0,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm_callee),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+
+		let deposit_of_call = |size: u32| {
+			let result = Contracts::bare_call(
+				CallOrigin::from_account_id(ALICE),
+				addr_caller.clone(),
+				0,
+				GAS_LIMIT,
+				None,
+				(addr_callee.clone(), size).encode(),
+				false,
+				CollectEvents::Skip,
+				Determinism::Deterministic,
+			)
+			.result
+			.unwrap();
+			assert!(!result.did_revert());
+			StorageDeposit::<BalanceOf<Test>>::decode(&mut &result.data[..]).unwrap()
+		};
+
+		// 100 bytes of storage and a single item are created by the callee.
+		assert_eq!(deposit_of_call(100), StorageDeposit::Charge(102));
+		// Shrinking the item refunds the bytes.
+		assert_eq!(deposit_of_call(0), StorageDeposit::Refund(100));
+	});
+}
+
+#[test]
+fn contracts_can_call_builtins() {
+	let (wasm, _code_hash) = compile_module::<Test>("call_and_return").unwrap();
+	ExtBuilder::default().existential_deposit(200).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		let addr = Contracts::bare_instantiate(
+			ALICE,
+			0,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+
+		let call = |input: &[u8]| {
+			let mut data = HashBuiltin::ADDRESS.encode();
+			data.extend_from_slice(input);
+			Contracts::bare_call(
+				CallOrigin::from_account_id(ALICE),
+				addr.clone(),
+				0,
+				GAS_LIMIT,
+				None,
+				data,
+				false,
+				CollectEvents::Skip,
+				Determinism::Deterministic,
+			)
+			.result
+			.unwrap()
+		};
+
+		// The builtin is executed instead of looking up a contract.
+		let result = call(&[1, 2, 3]);
+		assert!(!result.did_revert());
+		assert_eq!(result.data, blake2_256(&[1, 2, 3]).to_vec());
+
+		// A failing builtin traps the call.
+		let result = call(&[]);
+		assert!(result.did_revert());
+		assert_return_code!(result, RuntimeReturnCode::CalleeTrapped);
+	});
+}
+
+#[test]
+fn builtins_can_be_called_directly() {
+	ExtBuilder::default().existential_deposit(200).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+
+		let result = Contracts::bare_call(
+			CallOrigin::from_account_id(ALICE),
+			HashBuiltin::ADDRESS,
+			0,
+			GAS_LIMIT,
+			None,
+			vec![1, 2, 3],
+			false,
+			CollectEvents::Skip,
+			Determinism::Deterministic,
+		);
+		assert_eq!(result.result.unwrap().data, blake2_256(&[1, 2, 3]).to_vec());
+		assert_eq!(result.gas_consumed, <HashBuiltin as crate::builtin::Builtin<Test>>::weight(3));
+
+		assert_ok!(Contracts::call(
+			RuntimeOrigin::signed(ALICE),
+			HashBuiltin::ADDRESS,
+			0,
+			GAS_LIMIT,
+			None,
+			vec![1, 2, 3],
+		));
+		System::assert_last_event(RuntimeEvent::Contracts(crate::Event::Called {
+			caller: CallOrigin::from_account_id(ALICE),
+			contract: HashBuiltin::ADDRESS,
+		}));
+
+		// Failures are reported to the debug buffer.
+		let result = Contracts::bare_call(
+			CallOrigin::from_account_id(ALICE),
+			HashBuiltin::ADDRESS,
+			1,
+			GAS_LIMIT,
+			None,
+			vec![1, 2, 3],
+			true,
+			CollectEvents::Skip,
+			Determinism::Deterministic,
+		);
+		assert_err!(result.result, <Error<Test>>::BuiltinNotPayable);
+		assert!(std::str::from_utf8(&result.debug_message)
+			.unwrap()
+			.contains("BuiltinNotPayable"));
+
+		assert_err_ignore_postinfo!(
+			Contracts::call(
+				RuntimeOrigin::signed(ALICE),
+				HashBuiltin::ADDRESS,
+				1,
+				GAS_LIMIT,
+				None,
+				vec![1, 2, 3],
+			),
+			<Error<Test>>::BuiltinNotPayable,
+		);
+	});
+}
+
 #[test]
 fn deposit_limit_honors_liquidity_restrictions() {
 	let (wasm, _code_hash) = compile_module::<Test>("store").unwrap();
@@ -4209,6 +6951,7 @@ fn deposit_limit_honors_liquidity_restrictions() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -4253,6 +6996,7 @@ fn deposit_limit_honors_existential_deposit() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -4296,28 +7040,73 @@ fn deposit_limit_honors_min_leftover() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
 		.account_id;
 
-		// Check that the contract has been instantiated and has the minimum balance
-		assert_eq!(get_contract(&addr).total_deposit(), min_balance);
-		assert_eq!(<Test as Config>::Currency::total_balance(&addr), min_balance);
+		// Check that the contract has been instantiated and has the minimum balance
+		assert_eq!(get_contract(&addr).total_deposit(), min_balance);
+		assert_eq!(<Test as Config>::Currency::total_balance(&addr), min_balance);
+
+		// check that the minumum leftover (value send) is considered
+		assert_err_ignore_postinfo!(
+			Contracts::call(
+				RuntimeOrigin::signed(BOB),
+				addr.clone(),
+				400,
+				GAS_LIMIT,
+				Some(codec::Compact(500)),
+				100u32.to_le_bytes().to_vec()
+			),
+			<Error<Test>>::StorageDepositNotEnoughFunds,
+		);
+		assert_eq!(Balances::free_balance(&BOB), 1_000);
+	});
+}
+
+#[test]
+fn instantiate_fails_if_base_deposit_not_covered() {
+	let (wasm, code_hash) = compile_module::<Test>("dummy").unwrap();
+	ExtBuilder::default().existential_deposit(200).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let _ = Balances::deposit_creating(&BOB, 300);
+		assert_ok!(Contracts::upload_code(
+			RuntimeOrigin::signed(ALICE),
+			wasm,
+			None,
+			Determinism::Deterministic
+		));
+
+		// The limit does not cover the base deposit and the contract's minimum balance.
+		assert_err_ignore_postinfo!(
+			Contracts::instantiate(
+				RuntimeOrigin::signed(ALICE),
+				0,
+				GAS_LIMIT,
+				Some(codec::Compact(399)),
+				code_hash,
+				vec![],
+				vec![],
+			),
+			<Error<Test>>::StorageBaseDepositNotCovered,
+		);
 
-		// check that the minumum leftover (value send) is considered
+		// The origin can't afford the base deposit and the contract's minimum balance.
 		assert_err_ignore_postinfo!(
-			Contracts::call(
+			Contracts::instantiate(
 				RuntimeOrigin::signed(BOB),
-				addr.clone(),
-				400,
+				0,
 				GAS_LIMIT,
-				Some(codec::Compact(500)),
-				100u32.to_le_bytes().to_vec()
+				None,
+				code_hash,
+				vec![],
+				vec![],
 			),
-			<Error<Test>>::StorageDepositNotEnoughFunds,
+			<Error<Test>>::StorageBaseDepositNotCovered,
 		);
-		assert_eq!(Balances::free_balance(&BOB), 1_000);
+		assert_eq!(Balances::free_balance(&BOB), 300);
 	});
 }
 
@@ -4351,6 +7140,7 @@ fn cannot_instantiate_indeterministic_code() {
 				vec![],
 				vec![],
 				false,
+				CollectEvents::Skip,
 			)
 			.result,
 			<Error<Test>>::CodeRejected,
@@ -4396,6 +7186,7 @@ fn cannot_instantiate_indeterministic_code() {
 				vec![],
 				vec![],
 				false,
+				CollectEvents::Skip,
 			)
 			.result,
 			<Error<Test>>::Indeterministic,
@@ -4411,6 +7202,7 @@ fn cannot_instantiate_indeterministic_code() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -4419,13 +7211,14 @@ fn cannot_instantiate_indeterministic_code() {
 		// Try to instantiate `code_hash` from another contract in deterministic mode
 		assert_err!(
 			<Pallet<Test>>::bare_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
 				addr.clone(),
 				0,
 				GAS_LIMIT,
 				None,
 				code_hash.encode(),
 				false,
+				CollectEvents::Skip,
 				Determinism::Deterministic,
 			)
 			.result,
@@ -4435,13 +7228,14 @@ fn cannot_instantiate_indeterministic_code() {
 		// Instantiations are not allowed even in non determinism mode
 		assert_err!(
 			<Pallet<Test>>::bare_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
 				addr.clone(),
 				0,
 				GAS_LIMIT,
 				None,
 				code_hash.encode(),
 				false,
+				CollectEvents::Skip,
 				Determinism::AllowIndeterminism,
 			)
 			.result,
@@ -4475,6 +7269,7 @@ fn cannot_set_code_indeterministic_code() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -4483,13 +7278,14 @@ fn cannot_set_code_indeterministic_code() {
 		// We do not allow to set the code hash to a non determinstic wasm
 		assert_err!(
 			<Pallet<Test>>::bare_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
 				caller_addr.clone(),
 				0,
 				GAS_LIMIT,
 				None,
 				code_hash.encode(),
 				false,
+				CollectEvents::Skip,
 				Determinism::AllowIndeterminism,
 			)
 			.result,
@@ -4523,6 +7319,7 @@ fn delegate_call_indeterministic_code() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -4531,13 +7328,14 @@ fn delegate_call_indeterministic_code() {
 		// The delegate call will fail in deterministic mode
 		assert_err!(
 			<Pallet<Test>>::bare_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
 				caller_addr.clone(),
 				0,
 				GAS_LIMIT,
 				None,
 				code_hash.encode(),
 				false,
+				CollectEvents::Skip,
 				Determinism::Deterministic,
 			)
 			.result,
@@ -4547,13 +7345,14 @@ fn delegate_call_indeterministic_code() {
 		// The delegate call will work on non deterministic mode
 		assert_ok!(
 			<Pallet<Test>>::bare_call(
-				ALICE,
+				CallOrigin::from_account_id(ALICE),
 				caller_addr.clone(),
 				0,
 				GAS_LIMIT,
 				None,
 				code_hash.encode(),
 				false,
+				CollectEvents::Skip,
 				Determinism::AllowIndeterminism,
 			)
 			.result
@@ -4577,6 +7376,7 @@ fn reentrance_count_works_with_call() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -4586,13 +7386,14 @@ fn reentrance_count_works_with_call() {
 		let input = 0.encode();
 
 		Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			contract_addr,
 			0,
 			GAS_LIMIT,
 			None,
 			input,
 			true,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		)
 		.result
@@ -4616,6 +7417,7 @@ fn reentrance_count_works_with_delegated_call() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -4625,13 +7427,14 @@ fn reentrance_count_works_with_delegated_call() {
 		let input = (code_hash, 1).encode();
 
 		Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			contract_addr.clone(),
 			0,
 			GAS_LIMIT,
 			None,
 			input,
 			true,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		)
 		.result
@@ -4657,6 +7460,7 @@ fn account_reentrance_count_works() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
@@ -4671,32 +7475,35 @@ fn account_reentrance_count_works() {
 			vec![],
 			vec![],
 			false,
+			CollectEvents::Skip,
 		)
 		.result
 		.unwrap()
 		.account_id;
 
 		let result1 = Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			contract_addr.clone(),
 			0,
 			GAS_LIMIT,
 			None,
 			contract_addr.encode(),
 			true,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		)
 		.result
 		.unwrap();
 
 		let result2 = Contracts::bare_call(
-			ALICE,
+			CallOrigin::from_account_id(ALICE),
 			contract_addr.clone(),
 			0,
 			GAS_LIMIT,
 			None,
 			another_contract_addr.encode(),
 			true,
+			CollectEvents::Skip,
 			Determinism::Deterministic,
 		)
 		.result
@@ -4706,3 +7513,468 @@ fn account_reentrance_count_works() {
 		assert_eq!(result2.data, 0.encode());
 	});
 }
+
+#[test]
+fn multi_block_migration_works() {
+	let (wasm, _code_hash) = compile_module::<Test>("dummy").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let addr = Contracts::bare_instantiate(
+			ALICE,
+			0,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+		let call = || {
+			Contracts::call(RuntimeOrigin::signed(ALICE), addr.clone(), 0, GAS_LIMIT, None, vec![])
+		};
+		let runtime_call = RuntimeCall::Contracts(crate::Call::call {
+			dest: addr.clone(),
+			value: 0,
+			gas_limit: GAS_LIMIT,
+			storage_deposit_limit: None,
+			data: vec![],
+		});
+		let validate = |call: &RuntimeCall| {
+			CheckMigrationDone::<Test>::default().validate(
+				&ALICE,
+				call,
+				&call.get_dispatch_info(),
+				0,
+			)
+		};
+
+		// no migration in progress
+		assert_ok!(validate(&runtime_call));
+		assert_err_ignore_postinfo!(
+			Contracts::migrate(RuntimeOrigin::signed(ALICE), Weight::MAX),
+			<Error<Test>>::NoMigrationPerformed,
+		);
+
+		// the upgrade starts the migration to `MockMigration<16>`
+		StorageVersion::new(15).put::<Contracts>();
+		Migration::<Test>::on_runtime_upgrade();
+		assert!(MigrationInProgress::<Test>::exists());
+
+		// the pallet is unusable until the migration is done
+		assert_err_ignore_postinfo!(call(), <Error<Test>>::MigrationInProgress);
+		assert_eq!(validate(&runtime_call), Err(InvalidTransaction::Call.into()));
+		let migrate_call =
+			RuntimeCall::Contracts(crate::Call::migrate { weight_limit: Weight::MAX });
+		assert_ok!(validate(&migrate_call));
+
+		// not enough weight for a single step
+		assert_err_ignore_postinfo!(
+			Contracts::migrate(RuntimeOrigin::signed(ALICE), Weight::zero()),
+			<Error<Test>>::NoMigrationPerformed,
+		);
+
+		// progress is free of charge
+		let result =
+			Contracts::migrate(RuntimeOrigin::signed(ALICE), Weight::from_ref_time(2)).unwrap();
+		assert_eq!(result.pays_fee, Pays::No);
+		assert!(MigrationInProgress::<Test>::exists());
+
+		// the rest is done in `on_idle` which completes at most one version per block
+		for version in 16..=18 {
+			assert!(MigrationInProgress::<Test>::exists());
+			Contracts::on_idle(System::block_number(), Weight::MAX);
+			assert_eq!(Contracts::on_chain_storage_version(), version);
+		}
+		assert!(!MigrationInProgress::<Test>::exists());
+		assert_eq!(Contracts::on_chain_storage_version(), 18);
+		assert_ok!(call());
+		assert_ok!(validate(&runtime_call));
+	});
+}
+
+#[test]
+fn api_version_is_recorded_and_migrated() {
+	let (wasm, code_hash) = compile_module::<Test>("dummy").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Contracts::upload_code(
+			RuntimeOrigin::signed(ALICE),
+			wasm,
+			None,
+			Determinism::Deterministic,
+		));
+		assert_eq!(CodeInfoOf::<Test>::get(code_hash).unwrap().api_version(), API_VERSION);
+
+		// strip the version to get the layout before the migration
+		let key = CodeInfoOf::<Test>::hashed_key_for(code_hash);
+		let mut raw = unhashed::get_raw(&key).unwrap();
+		raw.truncate(raw.len() - 2);
+		unhashed::put_raw(&key, &raw);
+
+		let mut migration = v14::Migration::<Test>::default();
+		assert_eq!(migration.step().0, IsFinished::No);
+		assert_eq!(migration.step().0, IsFinished::Yes);
+		assert_eq!(CodeInfoOf::<Test>::get(code_hash).unwrap().api_version(), 0);
+	});
+}
+
+#[test]
+fn deny_reentry_is_migrated() {
+	let (wasm, _code_hash) = compile_module::<Test>("dummy").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let addr = Contracts::bare_instantiate(
+			ALICE,
+			0,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+		ContractInfoOf::<Test>::mutate(&addr, |info| info.as_mut().unwrap().deny_reentry = true);
+
+		// strip the flag and the empty delegate dependencies to get the layout before the migration
+		let key = ContractInfoOf::<Test>::hashed_key_for(&addr);
+		let raw = unhashed::get_raw(&key).unwrap();
+		unhashed::put_raw(&key, &raw[..raw.len() - 2]);
+		assert_eq!(get_contract_checked(&addr), None);
+
+		let mut migration = v15::Migration::<Test>::default();
+		assert_eq!(migration.step().0, IsFinished::No);
+		assert_eq!(migration.step().0, IsFinished::Yes);
+		let mut migration = v16::Migration::<Test>::default();
+		assert_eq!(migration.step().0, IsFinished::No);
+		assert_eq!(migration.step().0, IsFinished::Yes);
+		assert!(!get_contract(&addr).deny_reentry);
+	});
+}
+
+#[test]
+fn storage_deposit_is_moved_by_migration() {
+	let (wasm, _code_hash) = compile_module::<Test>("dummy").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let addr = Contracts::bare_instantiate(
+			ALICE,
+			0,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+		let migrate = || {
+			let mut migration = v11::Migration::<Test>::default();
+			while migration.step().0 == IsFinished::No {}
+			let mut migration = v12::Migration::<Test>::default();
+			while migration.step().0 == IsFinished::No {}
+			let mut migration = v13::Migration::<Test>::default();
+			while migration.step().0 == IsFinished::No {}
+			let mut migration = v15::Migration::<Test>::default();
+			while migration.step().0 == IsFinished::No {}
+			let mut migration = v16::Migration::<Test>::default();
+			while migration.step().0 == IsFinished::No {}
+		};
+
+		// the deposit is reserved on the contract before the migration
+		let info = get_contract(&addr);
+		let deposit_account = (*info.deposit_account).clone();
+		let deposit = Balances::free_balance(&deposit_account);
+		v11::store_old_contract_info::<Test>(&addr, &info);
+		assert_eq!(get_contract_checked(&addr), None);
+		assert_eq!(Balances::reserved_balance(&addr), info.total_deposit());
+		assert_eq!(Balances::free_balance(&deposit_account), deposit - info.total_deposit());
+
+		migrate();
+		assert_eq!(get_contract(&addr), info);
+		assert_eq!(Balances::reserved_balance(&addr), 0);
+		assert_eq!(Balances::free_balance(&deposit_account), deposit);
+
+		// a deposit too small to create the deposit account stays reserved
+		let mut info = info;
+		info.storage_byte_deposit = 0;
+		info.storage_item_deposit = 0;
+		info.storage_base_deposit = 10;
+		v11::store_old_contract_info::<Test>(&addr, &info);
+		assert_ok!(<Balances as Currency<_>>::transfer(
+			&deposit_account,
+			&BOB,
+			Balances::free_balance(&deposit_account),
+			ExistenceRequirement::AllowDeath,
+		));
+
+		migrate();
+		assert_eq!(get_contract(&addr).total_deposit(), 0);
+		assert_eq!(Balances::reserved_balance(&addr), 10);
+		assert_eq!(Balances::free_balance(&deposit_account), 0);
+	});
+}
+
+#[test]
+fn contract_info_migrations_only_translate_their_layout() {
+	let (wasm, _code_hash) = compile_module::<Test>("dummy").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let addr = Contracts::bare_instantiate(
+			ALICE,
+			0,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+		let info = get_contract(&addr);
+		let key = ContractInfoOf::<Test>::hashed_key_for(&addr);
+
+		// seed the bytes as they were stored before `v15`
+		let old = v13::ContractInfo::<Test> {
+			trie_id: info.trie_id.clone(),
+			deposit_account: info.deposit_account.clone(),
+			code_hash: info.code_hash,
+			storage_bytes: info.storage_bytes,
+			storage_items: info.storage_items,
+			storage_byte_deposit: info.storage_byte_deposit,
+			storage_item_deposit: info.storage_item_deposit,
+			storage_base_deposit: info.storage_base_deposit,
+			call_filter: None,
+			paused: true,
+		}
+		.encode();
+		unhashed::put_raw(&key, &old);
+
+		// `v16` doesn't touch a contract in the `v13` layout
+		let mut migration = v16::Migration::<Test>::default();
+		assert_eq!(migration.step().0, IsFinished::No);
+		assert_eq!(migration.step().0, IsFinished::Yes);
+		assert_eq!(unhashed::get_raw(&key), Some(old));
+
+		let mut migration = v15::Migration::<Test>::default();
+		assert_eq!(migration.step().0, IsFinished::No);
+		assert_eq!(migration.step().0, IsFinished::Yes);
+		let migrated = unhashed::get_raw(&key).unwrap();
+
+		// `v15` doesn't touch a contract that is already in its layout
+		let mut migration = v15::Migration::<Test>::default();
+		assert_eq!(migration.step().0, IsFinished::No);
+		assert_eq!(migration.step().0, IsFinished::Yes);
+		assert_eq!(unhashed::get_raw(&key), Some(migrated));
+
+		let mut migration = v16::Migration::<Test>::default();
+		assert_eq!(migration.step().0, IsFinished::No);
+		assert_eq!(migration.step().0, IsFinished::Yes);
+		let mut info = info;
+		info.paused = true;
+		assert_eq!(get_contract(&addr), info);
+	});
+}
+
+#[test]
+fn deletion_queue_is_migrated() {
+	ExtBuilder::default().build().execute_with(|| {
+		let trie_id: crate::TrieId = vec![1; 32].try_into().unwrap();
+		unhashed::put(&DeletionQueue::<Test>::hashed_key(), &vec![trie_id.clone()]);
+
+		let mut migration = v17::Migration::<Test>::default();
+		assert_eq!(migration.step().0, IsFinished::Yes);
+		let queue = DeletionQueue::<Test>::get();
+		assert_eq!(queue.len(), 1);
+		assert_eq!(queue[0].trie_id, trie_id);
+		assert_eq!(queue[0].account_id, None);
+	});
+}
+
+#[test]
+fn compressed_code_works() {
+	let (wasm, code_hash) = compile_module::<Test>("caller_contract").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let upload = || {
+			Contracts::upload_code(
+				RuntimeOrigin::signed(ALICE),
+				wasm.clone(),
+				None,
+				Determinism::Deterministic,
+			)
+		};
+
+		// remember the deposit of the uncompressed code
+		assert_ok!(upload());
+		let uncompressed_deposit = Balances::reserved_balance(&ALICE);
+		assert_ok!(Contracts::remove_code(RuntimeOrigin::signed(ALICE), code_hash));
+
+		// the compressed code is stored and the deposit is charged for it
+		COMPRESS_CODE.with(|c| *c.borrow_mut() = true);
+		assert_ok!(upload());
+		let stored = crate::PristineCode::<Test>::get(code_hash).unwrap();
+		assert!(stored.len() < wasm.len());
+		assert_eq!(
+			Balances::reserved_balance(&ALICE),
+			uncompressed_deposit - (wasm.len() - stored.len()) as u64,
+		);
+		assert_eq!(CodeInfoOf::<Test>::get(code_hash).unwrap().code_len(), wasm.len() as u32);
+		assert_eq!(Contracts::pristine_code(code_hash), Some(wasm.clone()));
+
+		// the code is decompressed when it is executed, even after disabling compression
+		COMPRESS_CODE.with(|c| *c.borrow_mut() = false);
+		assert_ok!(
+			Contracts::bare_instantiate(
+				ALICE,
+				0,
+				GAS_LIMIT,
+				None,
+				Code::Existing(code_hash),
+				vec![],
+				vec![],
+				false,
+				CollectEvents::Skip,
+			)
+			.result
+		);
+	});
+}
+
+#[test]
+fn get_storage_keys_paged_works() {
+	let (wasm, _code_hash) = compile_module::<Test>("dummy").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let addr = Contracts::bare_instantiate(
+			ALICE,
+			0,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+
+		let info = get_contract(&addr);
+		let mut keys: Vec<Vec<u8>> = (0u8..5)
+			.map(|i| {
+				let key: FixSizedKey = [i; 32];
+				Storage::<Test>::write(&info.trie_id, &key, Some(vec![i]), None, false).unwrap();
+				StorageKey::<Test>::hash(&key)
+			})
+			.collect();
+		keys.sort();
+
+		// all keys when there is no prefix
+		assert_eq!(
+			Contracts::get_storage_keys_paged(addr.clone(), vec![], None, 10),
+			Ok(keys.clone())
+		);
+
+		// page through the keys
+		let first = Contracts::get_storage_keys_paged(addr.clone(), vec![], None, 2).unwrap();
+		assert_eq!(first, keys[..2].to_vec());
+		let second =
+			Contracts::get_storage_keys_paged(addr.clone(), vec![], first.last().cloned(), 10)
+				.unwrap();
+		assert_eq!(second, keys[2..].to_vec());
+
+		// a full key as prefix includes the key itself
+		assert_eq!(
+			Contracts::get_storage_keys_paged(addr.clone(), keys[3].clone(), None, 10),
+			Ok(vec![keys[3].clone()]),
+		);
+		assert_eq!(Contracts::get_storage_keys_paged(addr.clone(), vec![], None, 0), Ok(vec![]));
+
+		// only contracts can be queried
+		assert_eq!(
+			Contracts::get_storage_keys_paged(BOB, vec![], None, 10),
+			Err(ContractAccessError::DoesntExist),
+		);
+	});
+}
+
+#[test]
+fn code_hash_and_pristine_code_work() {
+	let (wasm, code_hash) = compile_module::<Test>("dummy").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_eq!(Contracts::pristine_code(code_hash), None);
+
+		let addr = Contracts::bare_instantiate(
+			ALICE,
+			0,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm.clone()),
+			vec![],
+			vec![],
+			false,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+
+		assert_eq!(Contracts::code_hash(&addr), Some(code_hash));
+		assert_eq!(Contracts::code_hash(&BOB), None);
+		assert_eq!(Contracts::pristine_code(code_hash), Some(wasm));
+	});
+}
+
+#[test]
+fn environment_describes_config() {
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		assert_eq!(
+			Contracts::environment(),
+			ContractsEnvironment {
+				account_id_len: 32,
+				balance_len: 8,
+				hash_len: 32,
+				block_number_len: 8,
+				max_code_len: 123 * 1024,
+				existential_deposit: 50,
+			}
+		);
+	});
+}
+
+#[test]
+fn host_functions_are_listed() {
+	let info = |module: &str, name: &str, version, stable| HostFnInfo {
+		module: module.as_bytes().to_vec(),
+		name: name.as_bytes().to_vec(),
+		version,
+		stable,
+	};
+	ExtBuilder::default().build().execute_with(|| {
+		let funcs = Contracts::host_functions();
+		assert!(funcs.contains(&info("seal0", "seal_return", 0, true)));
+		assert!(funcs.contains(&info("seal2", "set_storage", 2, true)));
+		assert!(funcs.contains(&info("seal2", "call", 2, false)));
+
+		UnstableInterface::set(false);
+		let funcs = Contracts::host_functions();
+		assert!(funcs.contains(&info("seal2", "set_storage", 2, true)));
+		assert!(funcs.iter().all(|f| f.stable));
+	});
+}
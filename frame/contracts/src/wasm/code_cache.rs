@@ -15,85 +15,67 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! A module that implements instrumented code cache.
+//! A module that implements the code cache.
 //!
-//! - In order to run contract code we need to instrument it with gas metering.
-//! To do that we need to provide the schedule which will supply exact gas costs values.
-//! We cache this code in the storage saving the schedule version.
-//! - Before running contract code we check if the cached code has the schedule version that
-//! is equal to the current saved schedule.
-//! If it is equal then run the code, if it isn't reinstrument with the current schedule.
-//! - When we update the schedule we want it to have strictly greater version than the current saved
-//!   one:
-//! this guarantees that every instrumented contract code in cache cannot have the version equal to
-//! the current one. Thus, before executing a contract it should be reinstrument with new schedule.
+//! - Only the pristine code is stored. It is validated once when it is uploaded.
+//! - Instead of instrumenting the code with gas metering we rely on the fuel metering of
+//! the execution engine. Hence changing the schedule never requires to touch the stored code.
+//! - Everything that is needed before the code is loaded (owner, deposit, refcount, length)
+//! lives in a separate [`CodeInfo`](crate::wasm::CodeInfo) storage item.
+//! - The code is stored compressed if [`Config::CompressCode`] was set when it was uploaded.
 
 use crate::{
 	gas::{GasMeter, Token},
-	wasm::{prepare, PrefabWasmModule},
+	wasm::{CodeInfo, PrefabWasmModule},
 	weights::WeightInfo,
-	CodeHash, CodeStorage, Config, Error, Event, OwnerInfoOf, Pallet, PristineCode, Schedule,
-	Weight,
+	CodeHash, CodeInfoOf, CodeMetadataOf, CodeMetadataVec, CodeStatsOf, CodeVec, Config, Error,
+	Event, Pallet, PristineCode, TombstonedCode, Weight,
 };
 use frame_support::{
 	dispatch::{DispatchError, DispatchResult},
 	ensure,
-	traits::{Get, ReservableCurrency},
-	WeakBoundedVec,
+	traits::ReservableCurrency,
 };
-use sp_runtime::traits::BadOrigin;
+use sp_core::Get;
+use sp_runtime::traits::{BadOrigin, Saturating};
 use sp_std::vec;
 
-/// Put the instrumented module in storage.
+/// The magic number every wasm module starts with.
+///
+/// Stored code without it was compressed when it was uploaded.
+const WASM_MAGIC: &[u8] = b"\0asm";
+
+/// Put the module in storage.
 ///
 /// Increments the refcount of the in-storage `prefab_module` if it already exists in storage
 /// under the specified `code_hash`.
-pub fn store<T: Config>(mut module: PrefabWasmModule<T>, instantiated: bool) -> DispatchResult {
-	let code_hash = sp_std::mem::take(&mut module.code_hash);
-	<CodeStorage<T>>::mutate(&code_hash, |existing| match existing {
+pub fn store<T: Config>(module: PrefabWasmModule<T>, instantiated: bool) -> DispatchResult {
+	let PrefabWasmModule { code, compressed_code, mut code_info, code_hash } = module;
+	<CodeInfoOf<T>>::mutate(&code_hash, |existing| match existing {
 		Some(existing) => {
-			// We instrument any uploaded contract anyways. We might as well store it to save
-			// a potential re-instrumentation later.
-			existing.code = module.code;
-			existing.instruction_weights_version = module.instruction_weights_version;
 			// When the code was merely uploaded but not instantiated we can skip this.
 			if instantiated {
-				<OwnerInfoOf<T>>::mutate(&code_hash, |owner_info| {
-					if let Some(owner_info) = owner_info {
-						owner_info.refcount = owner_info.refcount.checked_add(1).expect(
-							"
-							refcount is 64bit. Generating this overflow would require to store
-							_at least_ 18 exabyte of data assuming that a contract consumes only
-							one byte of data. Any node would run out of storage space before hitting
-							this overflow.
-							qed
-						",
-						);
-					}
-				})
+				existing.refcount = existing.refcount.checked_add(1).expect(
+					"
+					refcount is 64bit. Generating this overflow would require to store
+					_at least_ 18 exabyte of data assuming that a contract consumes only
+					one byte of data. Any node would run out of storage space before hitting
+					this overflow.
+					qed
+				",
+				);
 			}
 			Ok(())
 		},
 		None => {
-			let orig_code = module.original_code.take().expect(
-				"
-					If an executable isn't in storage it was uploaded.
-					If it was uploaded the original code must exist. qed
-				",
-			);
-			let mut owner_info = module.owner_info.take().expect(
-				"If an executable isn't in storage it was uploaded.
-				If it was uploaded the owner info was generated and attached. qed
-				",
-			);
 			// This `None` case happens only in freshly uploaded modules. This means that
 			// the `owner` is always the origin of the current transaction.
-			T::Currency::reserve(&owner_info.owner, owner_info.deposit)
+			ensure!(!<TombstonedCode<T>>::contains_key(&code_hash), <Error<T>>::CodeTombstoned);
+			T::Currency::reserve(&code_info.owner, code_info.deposit)
 				.map_err(|_| <Error<T>>::StorageDepositNotEnoughFunds)?;
-			owner_info.refcount = if instantiated { 1 } else { 0 };
-			<PristineCode<T>>::insert(&code_hash, orig_code);
-			<OwnerInfoOf<T>>::insert(&code_hash, owner_info);
-			*existing = Some(module);
+			code_info.refcount = if instantiated { 1 } else { 0 };
+			<PristineCode<T>>::insert(&code_hash, compressed_code.unwrap_or(code));
+			*existing = Some(code_info);
 			<Pallet<T>>::deposit_event(vec![code_hash], Event::CodeStored { code_hash });
 			Ok(())
 		},
@@ -104,23 +86,40 @@ pub fn store<T: Config>(mut module: PrefabWasmModule<T>, instantiated: bool) ->
 ///
 /// # Note
 ///
-/// A contract whose refcount dropped to zero isn't automatically removed. A `remove_code`
-/// transaction must be submitted by the original uploader to do so.
+/// A code whose refcount dropped to zero is only removed automatically if
+/// [`Config::RemoveUnusedCode`] is set. Otherwise a `remove_code` transaction must be
+/// submitted by the original uploader to do so. Callers must account for the removal with
+/// [`removal_weight`].
 pub fn decrement_refcount<T: Config>(code_hash: CodeHash<T>) {
-	<OwnerInfoOf<T>>::mutate(code_hash, |existing| {
+	<CodeInfoOf<T>>::mutate_exists(code_hash, |existing| {
 		if let Some(info) = existing {
 			info.refcount = info.refcount.saturating_sub(1);
+			if info.refcount == 0 && T::RemoveUnusedCode::get() {
+				remove_storage::<T>(info, code_hash);
+				*existing = None;
+			}
 		}
 	});
 }
 
+/// The worst case weight of [`decrement_refcount`] being called `count` times.
+///
+/// Host functions that drop references to code must charge this as they might remove it.
+pub fn removal_weight<T: Config>(count: u32) -> Weight {
+	if T::RemoveUnusedCode::get() {
+		T::WeightInfo::remove_code().saturating_mul(count.into())
+	} else {
+		Weight::zero()
+	}
+}
+
 /// Increment the refcount of a code in-storage by one.
 ///
 /// # Errors
 ///
 /// [`Error::CodeNotFound`] is returned if the specified `code_hash` does not exist.
 pub fn increment_refcount<T: Config>(code_hash: CodeHash<T>) -> Result<(), DispatchError> {
-	<OwnerInfoOf<T>>::mutate(code_hash, |existing| -> Result<(), DispatchError> {
+	<CodeInfoOf<T>>::mutate(code_hash, |existing| -> Result<(), DispatchError> {
 		if let Some(info) = existing {
 			info.refcount = info.refcount.saturating_add(1);
 			Ok(())
@@ -132,15 +131,12 @@ pub fn increment_refcount<T: Config>(code_hash: CodeHash<T>) -> Result<(), Dispa
 
 /// Try to remove code together with all associated information.
 pub fn try_remove<T: Config>(origin: &T::AccountId, code_hash: CodeHash<T>) -> DispatchResult {
-	<OwnerInfoOf<T>>::try_mutate_exists(&code_hash, |existing| {
-		if let Some(owner_info) = existing {
-			ensure!(owner_info.refcount == 0, <Error<T>>::CodeInUse);
-			ensure!(&owner_info.owner == origin, BadOrigin);
-			T::Currency::unreserve(&owner_info.owner, owner_info.deposit);
+	<CodeInfoOf<T>>::try_mutate_exists(&code_hash, |existing| {
+		if let Some(code_info) = existing {
+			ensure!(code_info.refcount == 0, <Error<T>>::CodeInUse);
+			ensure!(&code_info.owner == origin, BadOrigin);
+			remove_storage::<T>(code_info, code_hash);
 			*existing = None;
-			<PristineCode<T>>::remove(&code_hash);
-			<CodeStorage<T>>::remove(&code_hash);
-			<Pallet<T>>::deposit_event(vec![code_hash], Event::CodeRemoved { code_hash });
 			Ok(())
 		} else {
 			Err(<Error<T>>::CodeNotFound.into())
@@ -148,67 +144,93 @@ pub fn try_remove<T: Config>(origin: &T::AccountId, code_hash: CodeHash<T>) -> D
 	})
 }
 
+/// Remove everything stored alongside `code_info` and refund the deposit to its owner.
+///
+/// The caller is responsible for removing `code_info` itself.
+fn remove_storage<T: Config>(code_info: &CodeInfo<T>, code_hash: CodeHash<T>) {
+	T::Currency::unreserve(&code_info.owner, code_info.deposit);
+	remove_metadata::<T>(&code_info.owner, code_hash);
+	<PristineCode<T>>::remove(&code_hash);
+	<CodeStatsOf<T>>::remove(&code_hash);
+	<Pallet<T>>::deposit_event(vec![code_hash], Event::CodeRemoved { code_hash });
+}
+
+/// Remove code regardless of its owner and refcount.
+///
+/// The deposit is refunded to the owner. The code is recorded in [`TombstonedCode`] so that it
+/// can't be uploaded again and the contracts still using it fail with [`Error::CodeTombstoned`].
+pub fn force_remove<T: Config>(code_hash: CodeHash<T>) -> DispatchResult {
+	let code_info = <CodeInfoOf<T>>::take(&code_hash).ok_or(<Error<T>>::CodeNotFound)?;
+	T::Currency::unreserve(&code_info.owner, code_info.deposit);
+	remove_metadata::<T>(&code_info.owner, code_hash);
+	<PristineCode<T>>::remove(&code_hash);
+	<CodeStatsOf<T>>::remove(&code_hash);
+	<TombstonedCode<T>>::insert(&code_hash, ());
+	<Pallet<T>>::deposit_event(
+		vec![code_hash],
+		Event::CodeForceRemoved { code_hash, refcount: code_info.refcount },
+	);
+	Ok(())
+}
+
+/// Set or clear the metadata attached to a code.
+///
+/// The deposit for the previous metadata is refunded and a new one is reserved from the owner.
+pub fn set_metadata<T: Config>(
+	origin: &T::AccountId,
+	code_hash: CodeHash<T>,
+	metadata: Option<CodeMetadataVec<T>>,
+) -> DispatchResult {
+	let code_info = <CodeInfoOf<T>>::get(&code_hash).ok_or(<Error<T>>::CodeNotFound)?;
+	ensure!(&code_info.owner == origin, BadOrigin);
+	remove_metadata::<T>(origin, code_hash);
+	if let Some(metadata) = metadata {
+		let deposit = T::DepositPerByte::get()
+			.saturating_mul((metadata.len() as u32).into())
+			.saturating_add(T::DepositPerItem::get());
+		T::Currency::reserve(origin, deposit)
+			.map_err(|_| <Error<T>>::StorageDepositNotEnoughFunds)?;
+		<CodeMetadataOf<T>>::insert(&code_hash, (metadata, deposit));
+	}
+	<Pallet<T>>::deposit_event(vec![code_hash], Event::CodeMetadataUpdated { code_hash });
+	Ok(())
+}
+
+/// Remove the metadata of a code and refund its deposit to `owner`.
+fn remove_metadata<T: Config>(owner: &T::AccountId, code_hash: CodeHash<T>) {
+	if let Some((_, deposit)) = <CodeMetadataOf<T>>::take(&code_hash) {
+		T::Currency::unreserve(owner, deposit);
+	}
+}
+
 /// Load code with the given code hash.
 ///
-/// If the module was instrumented with a lower version of schedule than
-/// the current one given as an argument, then this function will perform
-/// re-instrumentation and update the cache in the storage.
+/// The weight of reading the code is charged before it is loaded.
 pub fn load<T: Config>(
 	code_hash: CodeHash<T>,
-	schedule: &Schedule<T>,
 	gas_meter: &mut GasMeter<T>,
 ) -> Result<PrefabWasmModule<T>, DispatchError> {
-	let max_code_len = T::MaxCodeLen::get();
-	let charged = gas_meter.charge(CodeToken::Load(max_code_len))?;
-
-	let mut prefab_module = <CodeStorage<T>>::get(code_hash).ok_or(Error::<T>::CodeNotFound)?;
-	gas_meter.adjust_gas(charged, CodeToken::Load(prefab_module.code.len() as u32));
-	prefab_module.code_hash = code_hash;
-
-	if prefab_module.instruction_weights_version < schedule.instruction_weights.version {
-		// The instruction weights have changed.
-		// We need to re-instrument the code with the new instruction weights.
-		let charged = gas_meter.charge(CodeToken::Reinstrument(max_code_len))?;
-		let code_size = reinstrument(&mut prefab_module, schedule)?;
-		gas_meter.adjust_gas(charged, CodeToken::Reinstrument(code_size));
-	}
-
-	Ok(prefab_module)
+	let code_info = <CodeInfoOf<T>>::get(code_hash).ok_or(Error::<T>::CodeNotFound)?;
+	gas_meter.charge(CodeToken::Load(code_info.code_len))?;
+	let code = pristine_code::<T>(code_hash)?;
+	Ok(PrefabWasmModule { code, compressed_code: None, code_info, code_hash })
 }
 
-/// Instruments the passed prefab wasm module with the supplied schedule.
-///
-/// Returns the size in bytes of the uninstrumented code.
-pub fn reinstrument<T: Config>(
-	prefab_module: &mut PrefabWasmModule<T>,
-	schedule: &Schedule<T>,
-) -> Result<u32, DispatchError> {
-	let original_code =
-		<PristineCode<T>>::get(&prefab_module.code_hash).ok_or(Error::<T>::CodeNotFound)?;
-	let original_code_len = original_code.len();
-	// We need to allow contracts growing too big after re-instrumentation. Otherwise
-	// the contract can become inaccessible. The user has no influence over this size
-	// as the contract is already deployed and every change in size would be the result
-	// of changes in the instrumentation algorithm controlled by the chain authors.
-	prefab_module.code = WeakBoundedVec::force_from(
-		prepare::reinstrument::<super::runtime::Env, T>(
-			&original_code,
-			schedule,
-			prefab_module.determinism,
-		)?,
-		Some("Contract exceeds size limit after re-instrumentation."),
-	);
-	prefab_module.instruction_weights_version = schedule.instruction_weights.version;
-	<CodeStorage<T>>::insert(&prefab_module.code_hash, &*prefab_module);
-	Ok(original_code_len as u32)
+/// Load the code with the given code hash and decompress it if necessary.
+pub fn pristine_code<T: Config>(code_hash: CodeHash<T>) -> Result<CodeVec<T>, DispatchError> {
+	let code = <PristineCode<T>>::get(code_hash).ok_or(Error::<T>::CodeNotFound)?;
+	if code.starts_with(WASM_MAGIC) {
+		return Ok(code)
+	}
+	sp_io::compression::zstd_decompress(&code, T::MaxCodeLen::get())
+		.and_then(|code| code.try_into().ok())
+		.ok_or_else(|| Error::<T>::CodeDecompressionFailed.into())
 }
 
 /// Costs for operations that are related to code handling.
 #[cfg_attr(test, derive(Debug, PartialEq, Eq))]
 #[derive(Clone, Copy)]
 enum CodeToken {
-	/// Weight for reinstrumenting a contract contract of the supplied size in bytes.
-	Reinstrument(u32),
 	/// Weight for loading a contract per byte.
 	Load(u32),
 }
@@ -222,7 +244,6 @@ impl<T: Config> Token<T> for CodeToken {
 		// point because when charging the general weight for calling the contract we not know the
 		// size of the contract.
 		match *self {
-			Reinstrument(len) => T::WeightInfo::reinstrument(len),
 			Load(len) => T::WeightInfo::call_with_code_per_byte(len)
 				.saturating_sub(T::WeightInfo::call_with_code_per_byte(0))
 				.set_proof_size(len.into()),
@@ -22,11 +22,10 @@ mod code_cache;
 mod prepare;
 mod runtime;
 
-#[cfg(feature = "runtime-benchmarks")]
-pub use crate::wasm::code_cache::reinstrument;
 pub use crate::wasm::{
+	code_cache::pristine_code,
 	prepare::TryInstantiate,
-	runtime::{CallFlags, Environment, ReturnCode, Runtime, RuntimeCosts},
+	runtime::{CallFlags, Env, Environment, ReturnCode, Runtime, RuntimeCosts},
 };
 
 #[cfg(doc)]
@@ -35,65 +34,50 @@ pub use crate::wasm::runtime::api_doc;
 use crate::{
 	exec::{ExecResult, Executable, ExportedFunction, Ext},
 	gas::GasMeter,
-	AccountIdOf, BalanceOf, CodeHash, CodeStorage, CodeVec, Config, Error, RelaxedCodeVec,
+	storage::meter::Diff,
+	AccountIdOf, BalanceOf, CodeHash, CodeInfoOf, CodeMetadataVec, CodeVec, Config, Error,
 	Schedule,
 };
 use codec::{Decode, Encode, MaxEncodedLen};
 use frame_support::dispatch::{DispatchError, DispatchResult};
 use sp_core::Get;
-use sp_runtime::RuntimeDebug;
+use sp_runtime::{traits::Hash, RuntimeDebug};
 use sp_std::prelude::*;
 #[cfg(test)]
 pub use tests::MockExt;
 use wasmi::{
-	Config as WasmiConfig, Engine, Instance, Linker, Memory, MemoryType, Module, StackLimits, Store,
+	Config as WasmiConfig, Engine, ExternType, FuelConsumptionMode, Instance, Linker, Memory,
+	MemoryType, Module, StackLimits, Store,
 };
 
-/// A prepared wasm module ready for execution.
+/// The version of the host API implemented by this pallet.
 ///
-/// # Note
+/// Host functions are never changed in a way that breaks deployed contracts. Instead, a new
+/// version of a function is added to the next `sealN` module. This version must therefore be
+/// bumped together with the introduction of a new module, which is asserted by the
+/// `integrity_test` of the pallet. It is recorded in the [`CodeInfo`] of every uploaded code.
+pub const API_VERSION: u16 = 2;
+
+/// A prepared wasm module ready for execution.
 ///
-/// This data structure is mostly immutable once created and stored. The exceptions that
-/// can be changed by calling a contract are `instruction_weights_version` and `code`.
-/// `instruction_weights_version` and `code` change when a contract with an outdated instrumentation
-/// is called. Therefore one must be careful when holding any in-memory representation of this
-/// type while calling into a contract as those fields can get out of date.
-#[derive(Clone, Encode, Decode, scale_info::TypeInfo, MaxEncodedLen)]
-#[codec(mel_bound())]
-#[scale_info(skip_type_params(T))]
+/// Only the pristine code is kept. It is validated once on upload and metered by the
+/// engine's fuel at execution time. Hence it never needs to be re-instrumented when the
+/// [`Schedule`] changes.
+#[derive(Clone)]
 pub struct PrefabWasmModule<T: Config> {
-	/// Version of the instruction weights with which the code was instrumented.
-	#[codec(compact)]
-	instruction_weights_version: u32,
-	/// Initial memory size of a contract's sandbox.
-	#[codec(compact)]
-	initial: u32,
-	/// The maximum memory size of a contract's sandbox.
-	#[codec(compact)]
-	maximum: u32,
-	/// Code instrumented with the latest schedule.
-	code: RelaxedCodeVec<T>,
-	/// A code that might contain non deterministic features and is therefore never allowed
-	/// to be run on chain. Specifically this code can never be instantiated into a contract
-	/// and can just be used through a delegate call.
-	determinism: Determinism,
-	/// The uninstrumented, pristine version of the code.
+	/// The pristine, validated code as it was uploaded.
+	code: CodeVec<T>,
+	/// The compressed `code` that is put into storage instead of `code`.
 	///
-	/// It is not stored because the pristine code has its own storage item. The value
-	/// is only `Some` when this module was created from an `original_code` and `None` if
-	/// it was loaded from storage.
-	#[codec(skip)]
-	original_code: Option<CodeVec<T>>,
-	/// The code hash of the stored code which is defined as the hash over the `original_code`.
+	/// Only set for freshly uploaded code if [`Config::CompressCode`] is enabled.
+	compressed_code: Option<CodeVec<T>>,
+	/// The information that is stored alongside the code.
+	code_info: CodeInfo<T>,
+	/// The code hash of the stored code which is defined as the hash over the `code`.
 	///
 	/// As the map key there is no need to store the hash in the value, too. It is set manually
 	/// when loading the module from storage.
-	#[codec(skip)]
 	code_hash: CodeHash<T>,
-	// This isn't needed for contract execution and does not get loaded from storage by default.
-	// It is `Some` if and only if this struct was generated from code.
-	#[codec(skip)]
-	owner_info: Option<OwnerInfo<T>>,
 }
 
 /// Information that belongs to a [`PrefabWasmModule`] but is stored separately.
@@ -102,7 +86,7 @@ pub struct PrefabWasmModule<T: Config> {
 #[derive(Clone, Encode, Decode, scale_info::TypeInfo, MaxEncodedLen)]
 #[codec(mel_bound())]
 #[scale_info(skip_type_params(T))]
-pub struct OwnerInfo<T: Config> {
+pub struct CodeInfo<T: Config> {
 	/// The account that has deployed the contract and hence is allowed to remove it.
 	owner: AccountIdOf<T>,
 	/// The amount of balance that was deposited by the owner in order to deploy it.
@@ -111,6 +95,18 @@ pub struct OwnerInfo<T: Config> {
 	/// The number of contracts that use this as their code.
 	#[codec(compact)]
 	refcount: u64,
+	/// A code that might contain non deterministic features and is therefore never allowed
+	/// to be run on chain. Specifically this code can never be instantiated into a contract
+	/// and can just be used through a delegate call.
+	determinism: Determinism,
+	/// The length of the pristine code.
+	///
+	/// Kept here so that the weight of loading the code can be charged before reading it.
+	code_len: u32,
+	/// The [`API_VERSION`] of the pallet at the time the code was uploaded.
+	///
+	/// Code that was uploaded before the version was tracked reports `0`.
+	api_version: u16,
 }
 
 /// Defines the required determinism level of a wasm blob when either running or uploading code.
@@ -145,7 +141,7 @@ impl ExportedFunction {
 }
 
 impl<T: Config> PrefabWasmModule<T> {
-	/// Create the module by checking and instrumenting `original_code`.
+	/// Create the module by validating `original_code`.
 	///
 	/// This does **not** store the module. For this one need to either call [`Self::store`]
 	/// or [`<Self as Executable>::execute`][`Executable::execute`].
@@ -181,18 +177,52 @@ impl<T: Config> PrefabWasmModule<T> {
 		code_cache::try_remove::<T>(origin, code_hash)
 	}
 
+	/// Remove the code from storage even if it is still in use.
+	///
+	/// See [`crate::Pallet::force_remove_code`].
+	pub fn force_remove(code_hash: CodeHash<T>) -> DispatchResult {
+		code_cache::force_remove::<T>(code_hash)
+	}
+
+	/// Set or clear the metadata attached to the code.
+	///
+	/// See [`crate::Pallet::set_code_metadata`].
+	pub fn set_metadata(
+		origin: &T::AccountId,
+		code_hash: CodeHash<T>,
+		metadata: Option<CodeMetadataVec<T>>,
+	) -> DispatchResult {
+		code_cache::set_metadata::<T>(origin, code_hash, metadata)
+	}
+
 	/// Returns whether there is a deposit to be payed for this module.
 	///
 	/// Returns `0` if the module is already in storage and hence no deposit will
 	/// be charged when storing it.
 	pub fn open_deposit(&self) -> BalanceOf<T> {
-		if <CodeStorage<T>>::contains_key(&self.code_hash) {
+		if <CodeInfoOf<T>>::contains_key(&self.code_hash) {
 			0u32.into()
 		} else {
-			// Only already in-storage contracts have their `owner_info` set to `None`.
-			// Therefore it is correct to return `0` in this case.
-			self.owner_info.as_ref().map(|i| i.deposit).unwrap_or_default()
+			self.code_info.deposit
+		}
+	}
+
+	/// Returns the smallest deposit that uploading `code` can be charged.
+	///
+	/// Returns `0` if the code is already in storage. Otherwise the deposit is at least the one
+	/// for storing the code info as the length of the stored code is only known after it was
+	/// compressed.
+	pub fn min_upload_deposit(code: &[u8]) -> BalanceOf<T> {
+		if <CodeInfoOf<T>>::contains_key(T::Hashing::hash(code)) {
+			return 0u32.into()
+		}
+		Diff {
+			bytes_added: <CodeInfo<T>>::max_encoded_len() as u32,
+			items_added: 2,
+			..Default::default()
 		}
+		.update_contract::<T>(None)
+		.charge_or_zero()
 	}
 
 	/// Creates and returns an instance of the supplied code.
@@ -200,11 +230,17 @@ impl<T: Config> PrefabWasmModule<T> {
 	/// This is either used for later executing a contract or for validation of a contract.
 	/// When validating we pass `()` as `host_state`. Please note that such a dummy instance must
 	/// **never** be called/executed since it will panic the executor.
+	///
+	/// Fuel metering is always enabled. The returned store has no fuel attached and hence
+	/// the caller needs to add some before executing anything. Float instructions are
+	/// rejected unless the `determinism` allows them. An imported memory that doesn't declare
+	/// its maximum is limited to `max_memory_pages`.
 	pub fn instantiate<E, H>(
 		code: &[u8],
 		host_state: H,
-		memory: (u32, u32),
+		determinism: Determinism,
 		stack_limits: StackLimits,
+		max_memory_pages: u32,
 	) -> Result<(Store<H>, Memory, Instance), wasmi::Error>
 	where
 		E: Environment<H>,
@@ -214,14 +250,32 @@ impl<T: Config> PrefabWasmModule<T> {
 			.set_stack_limits(stack_limits)
 			.wasm_multi_value(false)
 			.wasm_mutable_global(false)
-			.wasm_sign_extension(false)
-			.wasm_saturating_float_to_int(false);
+			// Whether a module may use them is decided by the `ValidationRules` on upload.
+			.wasm_sign_extension(true)
+			.wasm_saturating_float_to_int(false)
+			.wasm_bulk_memory(false)
+			.wasm_reference_types(false)
+			.floats(matches!(determinism, Determinism::AllowIndeterminism))
+			.consume_fuel(true)
+			.fuel_consumption_mode(FuelConsumptionMode::Eager);
 		let engine = Engine::new(&config);
 		let module = Module::new(&engine, code)?;
+		// The memory limits were checked when the code was validated. Code without a memory
+		// import gets an empty one so that host functions always have one to work with.
+		let imported_memory = module.imports().find_map(|import| match import.ty() {
+			ExternType::Memory(memory_type) => Some(*memory_type),
+			_ => None,
+		});
+		let memory_type = match imported_memory {
+			Some(memory_type) if memory_type.maximum_pages().is_none() =>
+				MemoryType::new(memory_type.initial_pages().into(), Some(max_memory_pages))?,
+			Some(memory_type) => memory_type,
+			None => MemoryType::new(0, Some(0))?,
+		};
 		let mut store = Store::new(&engine, host_state);
-		let mut linker = Linker::new();
+		let mut linker = Linker::new(&engine);
 		E::define(&mut store, &mut linker, T::UnsafeUnstableInterface::get())?;
-		let memory = Memory::new(&mut store, MemoryType::new(memory.0, Some(memory.1))?).expect(
+		let memory = Memory::new(&mut store, memory_type).expect(
 			"The limits defined in our `Schedule` limit the amount of memory well below u32::MAX; qed",
 		);
 		linker
@@ -233,11 +287,11 @@ impl<T: Config> PrefabWasmModule<T> {
 		Ok((store, memory, instance))
 	}
 
-	/// Create and store the module without checking nor instrumenting the passed code.
+	/// Create and store the module without checking the passed code.
 	///
 	/// # Note
 	///
-	/// This is useful for benchmarking where we don't want instrumentation to skew
+	/// This is useful for benchmarking where we don't want validation to skew
 	/// our results. This also does not collect any deposit from the `owner`.
 	#[cfg(feature = "runtime-benchmarks")]
 	pub fn store_code_unchecked(
@@ -249,29 +303,35 @@ impl<T: Config> PrefabWasmModule<T> {
 			.map_err::<DispatchError, _>(Into::into)?;
 		code_cache::store(executable, false)
 	}
-
-	/// Decrement instruction_weights_version by 1. Panics if it is already 0.
-	#[cfg(test)]
-	pub fn decrement_version(&mut self) {
-		self.instruction_weights_version = self.instruction_weights_version.checked_sub(1).unwrap();
-	}
 }
 
-impl<T: Config> OwnerInfo<T> {
+impl<T: Config> CodeInfo<T> {
 	/// Return the refcount of the module.
 	#[cfg(test)]
 	pub fn refcount(&self) -> u64 {
 		self.refcount
 	}
+
+	/// Return the length of the stored code.
+	#[cfg(test)]
+	pub fn code_len(&self) -> u32 {
+		self.code_len
+	}
+
+	/// Return the host API version the code was uploaded with.
+	#[cfg(test)]
+	pub fn api_version(&self) -> u16 {
+		self.api_version
+	}
 }
 
 impl<T: Config> Executable<T> for PrefabWasmModule<T> {
 	fn from_storage(
 		code_hash: CodeHash<T>,
-		schedule: &Schedule<T>,
+		_schedule: &Schedule<T>,
 		gas_meter: &mut GasMeter<T>,
 	) -> Result<Self, DispatchError> {
-		code_cache::load(code_hash, schedule, gas_meter)
+		code_cache::load(code_hash, gas_meter)
 	}
 
 	fn add_user(code_hash: CodeHash<T>) -> Result<(), DispatchError> {
@@ -288,18 +348,26 @@ impl<T: Config> Executable<T> for PrefabWasmModule<T> {
 		function: &ExportedFunction,
 		input_data: Vec<u8>,
 	) -> ExecResult {
+		// Every instruction costs `1` fuel in the engine. We therefore scale the fuel by the
+		// weight of the most basic instruction.
+		let ref_time_per_fuel = ext.schedule().instruction_weights.ref_time_per_fuel();
+		let fuel_limit = ext.gas_meter().engine_fuel_left(ref_time_per_fuel);
+		let max_memory_pages = ext.schedule().limits.memory_pages;
+		let stack_limits = ext.schedule().limits.stack_limits();
 		let runtime = Runtime::new(ext, input_data);
 		let (mut store, memory, instance) = Self::instantiate::<crate::wasm::runtime::Env, _>(
 			self.code.as_slice(),
 			runtime,
-			(self.initial, self.maximum),
-			StackLimits::default(),
+			self.code_info.determinism,
+			stack_limits,
+			max_memory_pages,
 		)
 		.map_err(|msg| {
 			log::debug!(target: "runtime::contracts", "failed to instantiate code: {}", msg);
 			Error::<T>::CodeRejected
 		})?;
-		store.state_mut().set_memory(memory);
+		store.data_mut().set_memory(memory);
+		store.add_fuel(fuel_limit).expect("Fuel metering is enabled for contracts; qed");
 
 		let exported_func = instance
 			.get_export(&store, function.identifier())
@@ -316,7 +384,16 @@ impl<T: Config> Executable<T> for PrefabWasmModule<T> {
 
 		let result = exported_func.call(&mut store, &[], &mut []);
 
-		store.into_state().to_execution_result(result)
+		// Charge the fuel consumed since the last host function call.
+		let engine_consumed_total =
+			store.fuel_consumed().expect("Fuel metering is enabled for contracts; qed");
+		store
+			.data_mut()
+			.ext()
+			.gas_meter()
+			.charge_fuel(ref_time_per_fuel, engine_consumed_total)?;
+
+		store.into_data().to_execution_result(result)
 	}
 
 	fn code_hash(&self) -> &CodeHash<T> {
@@ -328,7 +405,7 @@ impl<T: Config> Executable<T> for PrefabWasmModule<T> {
 	}
 
 	fn is_deterministic(&self) -> bool {
-		matches!(self.determinism, Determinism::Deterministic)
+		matches!(self.code_info.determinism, Determinism::Deterministic)
 	}
 }
 
@@ -341,14 +418,17 @@ mod tests {
 			SeedOf, VarSizedKey,
 		},
 		gas::GasMeter,
-		storage::WriteOutcome,
+		storage::{meter::DepositOf, WriteOutcome},
 		tests::{RuntimeCall, Test, ALICE, BOB},
-		BalanceOf, CodeHash, Error, Pallet as Contracts,
+		xcm::XcmHash,
+		BalanceOf, CallOrigin, CodeHash, Error, Pallet as Contracts,
 	};
 	use assert_matches::assert_matches;
 	use frame_support::{
 		assert_err, assert_ok,
 		dispatch::DispatchResultWithPostInfo,
+		ensure,
+		traits::ExistenceRequirement,
 		weights::{OldWeight, Weight},
 	};
 	use pallet_contracts_primitives::{ExecReturnValue, ReturnFlags};
@@ -358,7 +438,10 @@ mod tests {
 	use std::{
 		borrow::BorrowMut,
 		cell::RefCell,
-		collections::hash_map::{Entry, HashMap},
+		collections::{
+			hash_map::{Entry, HashMap},
+			HashSet,
+		},
 	};
 
 	#[derive(Debug, PartialEq, Eq)]
@@ -367,6 +450,7 @@ mod tests {
 		value: u64,
 		data: Vec<u8>,
 		gas_left: u64,
+		deposit_limit: u64,
 		salt: Vec<u8>,
 	}
 
@@ -379,6 +463,7 @@ mod tests {
 	struct TransferEntry {
 		to: AccountIdOf<Test>,
 		value: u64,
+		allow_death: bool,
 	}
 
 	#[derive(Debug, PartialEq, Eq)]
@@ -387,12 +472,14 @@ mod tests {
 		value: u64,
 		data: Vec<u8>,
 		allows_reentry: bool,
+		deposit_limit: u64,
 	}
 
 	#[derive(Debug, PartialEq, Eq)]
 	struct CallCodeEntry {
 		code_hash: H256,
 		data: Vec<u8>,
+		deposit_limit: u64,
 	}
 
 	pub struct MockExt {
@@ -409,7 +496,13 @@ mod tests {
 		gas_meter: GasMeter<Test>,
 		debug_buffer: Vec<u8>,
 		ecdsa_recover: RefCell<Vec<([u8; 65], [u8; 32])>>,
+		// (dest, message)
+		xcm_messages: RefCell<Vec<(Option<Vec<u8>>, Vec<u8>)>>,
+		// (asset, to, amount)
+		asset_transfers: RefCell<Vec<(Vec<u8>, AccountIdOf<Test>, Vec<u8>)>>,
 		code_hashes: Vec<CodeHash<Test>>,
+		deny_reentry: bool,
+		delegate_dependencies: HashSet<CodeHash<Test>>,
 	}
 
 	/// The call is mocked and just returns this hardcoded value.
@@ -433,6 +526,10 @@ mod tests {
 				gas_meter: GasMeter::new(Weight::from_parts(10_000_000_000, 10 * 1024 * 1024)),
 				debug_buffer: Default::default(),
 				ecdsa_recover: Default::default(),
+				xcm_messages: Default::default(),
+				asset_transfers: Default::default(),
+				deny_reentry: false,
+				delegate_dependencies: Default::default(),
 			}
 		}
 	}
@@ -443,25 +540,29 @@ mod tests {
 		fn call(
 			&mut self,
 			_gas_limit: Weight,
+			deposit_limit: u64,
 			to: AccountIdOf<Self::T>,
 			value: u64,
 			data: Vec<u8>,
 			allows_reentry: bool,
 		) -> Result<ExecReturnValue, ExecError> {
-			self.calls.push(CallEntry { to, value, data, allows_reentry });
+			self.calls.push(CallEntry { to, value, data, allows_reentry, deposit_limit });
 			Ok(ExecReturnValue { flags: ReturnFlags::empty(), data: call_return_data() })
 		}
 		fn delegate_call(
 			&mut self,
+			_gas_limit: Weight,
+			deposit_limit: u64,
 			code_hash: CodeHash<Self::T>,
 			data: Vec<u8>,
 		) -> Result<ExecReturnValue, ExecError> {
-			self.code_calls.push(CallCodeEntry { code_hash, data });
+			self.code_calls.push(CallCodeEntry { code_hash, data, deposit_limit });
 			Ok(ExecReturnValue { flags: ReturnFlags::empty(), data: call_return_data() })
 		}
 		fn instantiate(
 			&mut self,
 			gas_limit: Weight,
+			deposit_limit: u64,
 			code_hash: CodeHash<Test>,
 			value: u64,
 			data: Vec<u8>,
@@ -472,6 +573,7 @@ mod tests {
 				value,
 				data: data.to_vec(),
 				gas_left: gas_limit.ref_time(),
+				deposit_limit,
 				salt: salt.to_vec(),
 			});
 			Ok((
@@ -483,10 +585,28 @@ mod tests {
 			self.code_hashes.push(hash);
 			Ok(())
 		}
-		fn transfer(&mut self, to: &AccountIdOf<Self::T>, value: u64) -> Result<(), DispatchError> {
-			self.transfers.push(TransferEntry { to: to.clone(), value });
+		fn transfer(
+			&mut self,
+			to: &AccountIdOf<Self::T>,
+			value: u64,
+			existence_requirement: ExistenceRequirement,
+		) -> Result<(), DispatchError> {
+			let allow_death = existence_requirement == ExistenceRequirement::AllowDeath;
+			self.transfers.push(TransferEntry { to: to.clone(), value, allow_death });
 			Ok(())
 		}
+		fn transfer_all(
+			&mut self,
+			to: &AccountIdOf<Self::T>,
+			existence_requirement: ExistenceRequirement,
+		) -> Result<(), DispatchError> {
+			let value = match existence_requirement {
+				ExistenceRequirement::KeepAlive =>
+					self.balance().saturating_sub(self.minimum_balance()),
+				ExistenceRequirement::AllowDeath => self.balance(),
+			};
+			self.transfer(to, value, existence_requirement)
+		}
 		fn terminate(&mut self, beneficiary: &AccountIdOf<Self::T>) -> Result<(), DispatchError> {
 			self.terminations.push(TerminationEntry { beneficiary: beneficiary.clone() });
 			Ok(())
@@ -541,8 +661,8 @@ mod tests {
 			}
 			Ok(result)
 		}
-		fn caller(&self) -> &AccountIdOf<Self::T> {
-			&ALICE
+		fn caller(&self) -> CallOrigin<Self::T> {
+			CallOrigin::from_account_id(ALICE)
 		}
 		fn is_contract(&self, _address: &AccountIdOf<Self::T>) -> bool {
 			true
@@ -557,6 +677,9 @@ mod tests {
 		fn caller_is_origin(&self) -> bool {
 			false
 		}
+		fn caller_is_root(&self) -> bool {
+			false
+		}
 		fn address(&self) -> &AccountIdOf<Self::T> {
 			&BOB
 		}
@@ -566,6 +689,9 @@ mod tests {
 		fn value_transferred(&self) -> u64 {
 			1337
 		}
+		fn last_subcall_deposit(&self) -> DepositOf<Self::T> {
+			DepositOf::<Self::T>::Charge(42)
+		}
 		fn now(&self) -> &u64 {
 			&1111
 		}
@@ -618,6 +744,25 @@ mod tests {
 		fn ecdsa_to_eth_address(&self, _pk: &[u8; 33]) -> Result<[u8; 20], ()> {
 			Ok([2u8; 20])
 		}
+		fn xcm_execute(&self, message: &[u8], _weight_limit: Weight) -> DispatchResultWithPostInfo {
+			self.xcm_messages.borrow_mut().push((None, message.to_vec()));
+			Ok(Default::default())
+		}
+		fn xcm_send(&self, dest: &[u8], message: &[u8]) -> Result<XcmHash, DispatchError> {
+			self.xcm_messages.borrow_mut().push((Some(dest.to_vec()), message.to_vec()));
+			Ok([5; 32])
+		}
+		fn asset_transfer(
+			&self,
+			asset: &[u8],
+			to: &AccountIdOf<Self::T>,
+			amount: &[u8],
+		) -> DispatchResult {
+			self.asset_transfers
+				.borrow_mut()
+				.push((asset.to_vec(), to.clone(), amount.to_vec()));
+			Ok(())
+		}
 		fn reentrance_count(&self) -> u32 {
 			12
 		}
@@ -627,6 +772,26 @@ mod tests {
 		fn nonce(&mut self) -> u64 {
 			995
 		}
+		fn storage_deposit_limit_left(&mut self) -> u64 {
+			1_337
+		}
+		fn set_deny_reentry(&mut self, deny: bool) {
+			self.deny_reentry = deny;
+		}
+		fn lock_delegate_dependency(&mut self, code_hash: CodeHash<Self::T>) -> DispatchResult {
+			ensure!(
+				self.delegate_dependencies.insert(code_hash),
+				<Error<Test>>::DelegateDependencyAlreadyExists
+			);
+			Ok(())
+		}
+		fn unlock_delegate_dependency(&mut self, code_hash: &CodeHash<Self::T>) -> DispatchResult {
+			ensure!(
+				self.delegate_dependencies.remove(code_hash),
+				<Error<Test>>::DelegateDependencyNotFound
+			);
+			Ok(())
+		}
 	}
 
 	fn execute_internal<E: BorrowMut<MockExt>>(
@@ -702,7 +867,72 @@ mod tests {
 		let mut mock_ext = MockExt::default();
 		assert_ok!(execute(CODE_TRANSFER, vec![], &mut mock_ext));
 
-		assert_eq!(&mock_ext.transfers, &[TransferEntry { to: ALICE, value: 153 }]);
+		assert_eq!(
+			&mock_ext.transfers,
+			&[TransferEntry { to: ALICE, value: 153, allow_death: false }]
+		);
+	}
+
+	const CODE_TRANSFER_FLAGS: &str = r#"
+(module
+	;; seal_transfer(
+	;;    flags: u32,
+	;;    account_ptr: u32,
+	;;    value_ptr: u32,
+	;;) -> u32
+	(import "seal1" "transfer" (func $seal_transfer (param i32 i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+	(func (export "call")
+		;; allow death
+		(drop
+			(call $seal_transfer
+				(i32.const 1) ;; ALLOW_DEATH
+				(i32.const 4)  ;; Pointer to "account" address.
+				(i32.const 36) ;; Pointer to the buffer with value to transfer
+			)
+		)
+		;; transfer all, keep alive
+		(drop
+			(call $seal_transfer
+				(i32.const 2) ;; TRANSFER_ALL
+				(i32.const 4)  ;; Pointer to "account" address.
+				(i32.const 36) ;; Pointer to the buffer with value to transfer
+			)
+		)
+	)
+	(func (export "deploy"))
+
+	;; Destination AccountId (ALICE)
+	(data (i32.const 4)
+		"\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01"
+		"\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01"
+	)
+
+	;; Amount of value to transfer.
+	;; Represented by u64 (8 bytes long) in little endian.
+	(data (i32.const 36) "\99\00\00\00\00\00\00\00")
+)
+"#;
+
+	#[test]
+	fn contract_transfer_with_flags() {
+		let mut mock_ext = MockExt::default();
+		assert_ok!(execute(CODE_TRANSFER_FLAGS, vec![], &mut mock_ext));
+
+		assert_eq!(
+			&mock_ext.transfers,
+			&[
+				TransferEntry { to: ALICE, value: 153, allow_death: true },
+				TransferEntry { to: ALICE, value: 0, allow_death: false },
+			]
+		);
+	}
+
+	#[test]
+	fn contract_transfer_traps_on_invalid_flags() {
+		let mut mock_ext = MockExt::default();
+		let code = CODE_TRANSFER_FLAGS.replace("(i32.const 1) ;; ALLOW_DEATH", "(i32.const 4)");
+		assert_err!(execute(&code, vec![], &mut mock_ext), <Error<Test>>::InvalidTransferFlags,);
 	}
 
 	const CODE_CALL: &str = r#"
@@ -758,7 +988,79 @@ mod tests {
 
 		assert_eq!(
 			&mock_ext.calls,
-			&[CallEntry { to: ALICE, value: 6, data: vec![1, 2, 3, 4], allows_reentry: true }]
+			&[CallEntry {
+				to: ALICE,
+				value: 6,
+				data: vec![1, 2, 3, 4],
+				allows_reentry: true,
+				deposit_limit: 0
+			}]
+		);
+	}
+
+	const CODE_CALL_WITH_DEPOSIT_LIMIT: &str = r#"
+(module
+	;; seal_call(
+	;;    flags: u32,
+	;;    callee_ptr: u32,
+	;;    gas: u64,
+	;;    deposit_ptr: u32,
+	;;    value_ptr: u32,
+	;;    input_data_ptr: u32,
+	;;    input_data_len: u32,
+	;;    output_ptr: u32,
+	;;    output_len_ptr: u32
+	;;) -> u32
+	(import "seal2" "call" (func $seal_call (param i32 i32 i64 i32 i32 i32 i32 i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+	(func (export "call")
+		(drop
+			(call $seal_call
+				(i32.const 1)  ;; Set FORWARD_INPUT bit
+				(i32.const 4)  ;; Pointer to "callee" address.
+				(i64.const 0)  ;; How much gas to devote for the execution. 0 = all.
+				(i32.const 36) ;; Pointer to the buffer with the deposit limit
+				(i32.const 44) ;; Pointer to the buffer with value to transfer
+				(i32.const 0)  ;; Pointer to input data buffer address
+				(i32.const 0)  ;; Length of input data buffer
+				(i32.const 4294967295) ;; u32 max value is the sentinel value: do not copy output
+				(i32.const 0) ;; Length is ignored in this case
+			)
+		)
+	)
+	(func (export "deploy"))
+
+	;; Destination AccountId (ALICE)
+	(data (i32.const 4)
+		"\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01"
+		"\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01"
+	)
+
+	;; Storage deposit limit of the callee.
+	;; Represented by u64 (8 bytes long) in little endian.
+	(data (i32.const 36) "\2a\00\00\00\00\00\00\00")
+
+	;; Amount of value to transfer.
+	;; Represented by u64 (8 bytes long) in little endian.
+	(data (i32.const 44) "\06\00\00\00\00\00\00\00")
+)
+"#;
+
+	#[test]
+	fn contract_call_with_deposit_limit() {
+		let mut mock_ext = MockExt::default();
+		let input = vec![0xff, 0x2a, 0x99, 0x88];
+		assert_ok!(execute(CODE_CALL_WITH_DEPOSIT_LIMIT, input.clone(), &mut mock_ext));
+
+		assert_eq!(
+			&mock_ext.calls,
+			&[CallEntry {
+				to: ALICE,
+				value: 6,
+				data: input,
+				allows_reentry: false,
+				deposit_limit: 42
+			}]
 		);
 	}
 
@@ -804,7 +1106,68 @@ mod tests {
 
 		assert_eq!(
 			&mock_ext.code_calls,
-			&[CallCodeEntry { code_hash: [0x11; 32].into(), data: vec![1, 2, 3, 4] }]
+			&[CallCodeEntry {
+				code_hash: [0x11; 32].into(),
+				data: vec![1, 2, 3, 4],
+				deposit_limit: 0
+			}]
+		);
+	}
+
+	#[test]
+	fn contract_delegate_call_with_limits() {
+		const CODE: &str = r#"
+(module
+	;; seal_delegate_call(
+	;;    flags: u32,
+	;;    code_hash_ptr: u32,
+	;;    gas: u64,
+	;;    deposit_ptr: u32,
+	;;    input_data_ptr: u32,
+	;;    input_data_len: u32,
+	;;    output_ptr: u32,
+	;;    output_len_ptr: u32
+	;;) -> u32
+	(import "seal1" "seal_delegate_call" (func $seal_delegate_call (param i32 i32 i64 i32 i32 i32 i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+	(func (export "call")
+		(drop
+			(call $seal_delegate_call
+				(i32.const 0) ;; No flags are set
+				(i32.const 4)  ;; Pointer to "callee" code_hash.
+				(i64.const 1000000) ;; How much gas to devote for the execution.
+				(i32.const 40) ;; Pointer to the storage deposit limit
+				(i32.const 36) ;; Pointer to input data buffer address
+				(i32.const 4)  ;; Length of input data buffer
+				(i32.const 4294967295) ;; u32 max value is the sentinel value: do not copy output
+				(i32.const 0) ;; Length is ignored in this case
+			)
+		)
+	)
+	(func (export "deploy"))
+
+	;; Callee code_hash
+	(data (i32.const 4)
+		"\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11"
+		"\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11"
+	)
+
+	(data (i32.const 36) "\01\02\03\04")
+
+	;; Storage deposit limit of the delegated code.
+	(data (i32.const 40) "\2a\00\00\00\00\00\00\00")
+)
+"#;
+		let mut mock_ext = MockExt::default();
+		assert_ok!(execute(CODE, vec![], &mut mock_ext));
+
+		assert_eq!(
+			&mock_ext.code_calls,
+			&[CallCodeEntry {
+				code_hash: [0x11; 32].into(),
+				data: vec![1, 2, 3, 4],
+				deposit_limit: 42
+			}]
 		);
 	}
 
@@ -855,7 +1218,13 @@ mod tests {
 
 		assert_eq!(
 			&mock_ext.calls,
-			&[CallEntry { to: ALICE, value: 0x2a, data: input, allows_reentry: false }]
+			&[CallEntry {
+				to: ALICE,
+				value: 0x2a,
+				data: input,
+				allows_reentry: false,
+				deposit_limit: 0
+			}]
 		);
 	}
 
@@ -910,7 +1279,13 @@ mod tests {
 		assert_eq!(result.data, input);
 		assert_eq!(
 			&mock_ext.calls,
-			&[CallEntry { to: ALICE, value: 0x2a, data: input, allows_reentry: true }]
+			&[CallEntry {
+				to: ALICE,
+				value: 0x2a,
+				data: input,
+				allows_reentry: true,
+				deposit_limit: 0
+			}]
 		);
 	}
 
@@ -957,7 +1332,13 @@ mod tests {
 		assert_eq!(result.data, call_return_data());
 		assert_eq!(
 			&mock_ext.calls,
-			&[CallEntry { to: ALICE, value: 0x2a, data: input, allows_reentry: false }]
+			&[CallEntry {
+				to: ALICE,
+				value: 0x2a,
+				data: input,
+				allows_reentry: false,
+				deposit_limit: 0
+			}]
 		);
 	}
 
@@ -1106,6 +1487,7 @@ mod tests {
 				value: 3,
 				data,
 				gas_left: _,
+				deposit_limit: 0,
 				salt,
 			}] if
 				code_hash == &[0x11; 32].into() &&
@@ -1198,7 +1580,13 @@ mod tests {
 
 		assert_eq!(
 			&mock_ext.calls,
-			&[CallEntry { to: ALICE, value: 6, data: vec![1, 2, 3, 4], allows_reentry: true }]
+			&[CallEntry {
+				to: ALICE,
+				value: 6,
+				data: vec![1, 2, 3, 4],
+				allows_reentry: true,
+				deposit_limit: 0
+			}]
 		);
 	}
 
@@ -1644,6 +2032,60 @@ mod tests {
 		assert_ok!(execute(CODE_VALUE_TRANSFERRED, vec![], MockExt::default()));
 	}
 
+	const CODE_LAST_SUBCALL_DEPOSIT: &str = r#"
+(module
+	(import "seal0" "last_subcall_deposit" (func $last_subcall_deposit (param i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; size of our buffer is 32 bytes
+	(data (i32.const 32) "\20")
+
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok
+				(get_local 0)
+			)
+			(unreachable)
+		)
+	)
+
+	(func (export "call")
+		;; This stores the deposit of the last sub call in the buffer
+		(call $last_subcall_deposit (i32.const 0) (i32.const 32))
+
+		;; assert len == 9
+		(call $assert
+			(i32.eq
+				(i32.load (i32.const 32))
+				(i32.const 9)
+			)
+		)
+
+		;; assert that the deposit is a `Charge`
+		(call $assert
+			(i32.eq
+				(i32.load8_u (i32.const 0))
+				(i32.const 1)
+			)
+		)
+
+		;; assert that the charged amount is equal to the i64 value of 42.
+		(call $assert
+			(i64.eq
+				(i64.load (i32.const 1))
+				(i64.const 42)
+			)
+		)
+	)
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn last_subcall_deposit() {
+		assert_ok!(execute(CODE_LAST_SUBCALL_DEPOSIT, vec![], MockExt::default()));
+	}
+
 	const START_FN_ILLEGAL: &str = r#"
 (module
 	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
@@ -2363,6 +2805,182 @@ mod tests {
 		assert_eq!(*ext.runtime_calls.borrow(), vec![]);
 	}
 
+	#[test]
+	fn xcm_execute_works() {
+		const CODE: &str = r#"
+(module
+	(import "seal0" "xcm_execute" (func $xcm_execute (param i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 4) return code
+	;; [4, 8) the encoded message
+	(data (i32.const 4) "\01\02\03\04")
+
+	(func (export "call")
+		(i32.store (i32.const 0)
+			(call $xcm_execute
+				(i32.const 4)	;; Pointer to the message
+				(i32.const 4)	;; Length of the message
+			)
+		)
+		(call $seal_return
+			(i32.const 0)	;; flags
+			(i32.const 0)	;; returned value
+			(i32.const 4)	;; length of returned value
+		)
+	)
+
+	(func (export "deploy"))
+)
+"#;
+		let mut ext = MockExt::default();
+		let result = execute(CODE, vec![], &mut ext).unwrap();
+		assert_eq!(*ext.xcm_messages.borrow(), vec![(None, vec![1, 2, 3, 4])]);
+		// 0 = ReturnCode::Success
+		assert_eq!(u32::from_le_bytes(result.data.try_into().unwrap()), 0);
+	}
+
+	#[test]
+	fn xcm_send_works() {
+		const CODE: &str = r#"
+(module
+	(import "seal0" "xcm_send" (func $xcm_send (param i32 i32 i32 i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 4) return code
+	;; [4, 36) the message hash
+	;; [36, 38) the encoded destination
+	;; [38, 42) the encoded message
+	(data (i32.const 36) "\07\08")
+	(data (i32.const 38) "\01\02\03\04")
+
+	(func (export "call")
+		(i32.store (i32.const 0)
+			(call $xcm_send
+				(i32.const 36)	;; Pointer to the destination
+				(i32.const 2)	;; Length of the destination
+				(i32.const 38)	;; Pointer to the message
+				(i32.const 4)	;; Length of the message
+				(i32.const 4)	;; Pointer to the output buffer
+			)
+		)
+		(call $seal_return
+			(i32.const 0)	;; flags
+			(i32.const 0)	;; returned value
+			(i32.const 36)	;; length of returned value
+		)
+	)
+
+	(func (export "deploy"))
+)
+"#;
+		let mut ext = MockExt::default();
+		let result = execute(CODE, vec![], &mut ext).unwrap();
+		assert_eq!(*ext.xcm_messages.borrow(), vec![(Some(vec![7, 8]), vec![1, 2, 3, 4])]);
+		// 0 = ReturnCode::Success
+		assert_eq!(u32::from_le_bytes(result.data[..4].try_into().unwrap()), 0);
+		assert_eq!(&result.data[4..], &[5; 32]);
+	}
+
+	#[test]
+	fn asset_queries_work() {
+		const CODE: &str = r#"
+(module
+	(import "seal0" "asset_balance_of" (func $asset_balance_of (param i32 i32 i32 i32 i32)))
+	(import "seal0" "asset_total_supply" (func $asset_total_supply (param i32 i32 i32 i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 4) the encoded asset
+	;; [4, 36) the account (ALICE)
+	;; [36, 40) size of the output buffers
+	;; [40, 48) the balance
+	;; [48, 56) the total supply
+	(data (i32.const 0) "\07")
+	(data (i32.const 4)
+		"\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01"
+		"\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01"
+	)
+	(data (i32.const 36) "\08")
+
+	(func (export "call")
+		(call $asset_balance_of
+			(i32.const 0)	;; Pointer to the asset
+			(i32.const 4)	;; Length of the asset
+			(i32.const 4)	;; Pointer to the account
+			(i32.const 40)	;; Pointer to the output buffer
+			(i32.const 36)	;; Pointer to the output buffer size
+		)
+		(call $asset_total_supply
+			(i32.const 0)	;; Pointer to the asset
+			(i32.const 4)	;; Length of the asset
+			(i32.const 48)	;; Pointer to the output buffer
+			(i32.const 36)	;; Pointer to the output buffer size
+		)
+		(call $seal_return
+			(i32.const 0)	;; flags
+			(i32.const 40)	;; returned value
+			(i32.const 16)	;; length of returned value
+		)
+	)
+
+	(func (export "deploy"))
+)
+"#;
+		crate::tests::TestAssets::set_balance(7, &ALICE, 42);
+		crate::tests::TestAssets::set_balance(7, &BOB, 8);
+		let result = execute(CODE, vec![], MockExt::default()).unwrap();
+		assert_eq!(result.data, (42u64, 50u64).encode());
+	}
+
+	#[test]
+	fn asset_transfer_works() {
+		const CODE: &str = r#"
+(module
+	(import "seal0" "asset_transfer" (func $asset_transfer (param i32 i32 i32 i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 4) return code
+	;; [4, 36) the beneficiary (ALICE)
+	;; [36, 38) the encoded asset
+	;; [38, 42) the encoded amount
+	(data (i32.const 4)
+		"\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01"
+		"\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01"
+	)
+	(data (i32.const 36) "\07\08")
+	(data (i32.const 38) "\01\02\03\04")
+
+	(func (export "call")
+		(i32.store (i32.const 0)
+			(call $asset_transfer
+				(i32.const 36)	;; Pointer to the asset
+				(i32.const 2)	;; Length of the asset
+				(i32.const 4)	;; Pointer to the beneficiary
+				(i32.const 38)	;; Pointer to the amount
+				(i32.const 4)	;; Length of the amount
+			)
+		)
+		(call $seal_return
+			(i32.const 0)	;; flags
+			(i32.const 0)	;; returned value
+			(i32.const 4)	;; length of returned value
+		)
+	)
+
+	(func (export "deploy"))
+)
+"#;
+		let mut ext = MockExt::default();
+		let result = execute(CODE, vec![], &mut ext).unwrap();
+		assert_eq!(*ext.asset_transfers.borrow(), vec![(vec![7, 8], ALICE, vec![1, 2, 3, 4])]);
+		// 0 = ReturnCode::Success
+		assert_eq!(u32::from_le_bytes(result.data[..4].try_into().unwrap()), 0);
+	}
+
 	#[test]
 	fn set_storage_works() {
 		const CODE: &str = r#"
@@ -2917,11 +3535,64 @@ mod tests {
 		assert_eq!(mock_ext.code_hashes.pop().unwrap(), H256::from_slice(&[17u8; 32]));
 	}
 
+	#[test]
+	fn delegate_dependency_locking_works() {
+		const CODE: &str = r#"
+(module
+	(import "seal0" "lock_delegate_dependency" (func $lock (param i32)))
+	(import "seal0" "unlock_delegate_dependency" (func $unlock (param i32)))
+	(import "env" "memory" (memory 1 1))
+	(func (export "call")
+		(call $lock (i32.const 0))
+		(call $lock (i32.const 32))
+		(call $unlock (i32.const 0))
+	)
+	(func (export "deploy"))
+
+	;; Hashes of code.
+	(data (i32.const 0)
+		"\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11"
+		"\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11"
+		"\22\22\22\22\22\22\22\22\22\22\22\22\22\22\22\22"
+		"\22\22\22\22\22\22\22\22\22\22\22\22\22\22\22\22"
+	)
+)
+"#;
+
+		let mut mock_ext = MockExt::default();
+		execute(CODE, vec![], &mut mock_ext).unwrap();
+		assert_eq!(
+			mock_ext.delegate_dependencies.into_iter().collect::<Vec<_>>(),
+			vec![H256::from_slice(&[0x22; 32])],
+		);
+	}
+
+	#[test]
+	fn delegate_dependency_unlock_traps_if_not_locked() {
+		const CODE: &str = r#"
+(module
+	(import "seal0" "unlock_delegate_dependency" (func $unlock (param i32)))
+	(import "env" "memory" (memory 1 1))
+	(func (export "call")
+		(call $unlock (i32.const 0))
+	)
+	(func (export "deploy"))
+)
+"#;
+
+		let mut mock_ext = MockExt::default();
+		assert_err!(
+			execute(CODE, vec![], &mut mock_ext),
+			<Error<Test>>::DelegateDependencyNotFound,
+		);
+	}
+
 	#[test]
 	fn reentrance_count_works() {
 		const CODE: &str = r#"
 (module
 	(import "seal0" "reentrance_count" (func $reentrance_count (result i32)))
+	(import "seal0" "seal_reentrance_count" (func $seal_reentrance_count (result i32)))
 	(import "env" "memory" (memory 1 1))
 	(func $assert (param i32)
 		(block $ok
@@ -2939,6 +3610,9 @@ mod tests {
 		(call $assert
 			(i32.eq (get_local $return_val) (i32.const 12))
 		)
+		(call $assert
+			(i32.eq (call $seal_reentrance_count) (i32.const 12))
+		)
 	)
 
 	(func (export "deploy"))
@@ -2954,6 +3628,7 @@ mod tests {
 		const CODE: &str = r#"
 (module
 	(import "seal0" "account_reentrance_count" (func $account_reentrance_count (param i32) (result i32)))
+	(import "seal0" "seal_account_reentrance_count" (func $seal_account_reentrance_count (param i32) (result i32)))
 	(import "env" "memory" (memory 1 1))
 	(func $assert (param i32)
 		(block $ok
@@ -2971,6 +3646,9 @@ mod tests {
 		(call $assert
 			(i32.eq (get_local $return_val) (i32.const 12))
 		)
+		(call $assert
+			(i32.eq (call $seal_account_reentrance_count (i32.const 0)) (i32.const 12))
+		)
 	)
 
 	(func (export "deploy"))
@@ -2986,6 +3664,7 @@ mod tests {
 		const CODE: &str = r#"
 (module
 	(import "seal0" "instantiation_nonce" (func $nonce (result i64)))
+	(import "seal0" "seal_instantiation_nonce" (func $seal_nonce (result i64)))
 	(func $assert (param i32)
 		(block $ok
 			(br_if $ok
@@ -2998,6 +3677,72 @@ mod tests {
 		(call $assert
 			(i64.eq (call $nonce) (i64.const 995))
 		)
+		(call $assert
+			(i64.eq (call $seal_nonce) (i64.const 995))
+		)
+	)
+	(func (export "deploy"))
+)
+"#;
+
+		let mut mock_ext = MockExt::default();
+		execute(CODE, vec![], &mut mock_ext).unwrap();
+	}
+
+	#[test]
+	fn storage_deposit_limit_left_works() {
+		const CODE: &str = r#"
+(module
+	(import "seal0" "seal_storage_deposit_limit_left" (func $limit_left (param i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; size of our buffer is 32 bytes
+	(data (i32.const 32) "\20")
+
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok
+				(get_local 0)
+			)
+			(unreachable)
+		)
+	)
+
+	(func (export "call")
+		(call $limit_left (i32.const 0) (i32.const 32))
+
+		;; assert len == 8
+		(call $assert
+			(i32.eq
+				(i32.load (i32.const 32))
+				(i32.const 8)
+			)
+		)
+
+		;; assert that contents of the buffer is equal to the i64 value of 1337.
+		(call $assert
+			(i64.eq
+				(i64.load (i32.const 0))
+				(i64.const 1337)
+			)
+		)
+	)
+	(func (export "deploy"))
+)
+"#;
+
+		let mut mock_ext = MockExt::default();
+		execute(CODE, vec![], &mut mock_ext).unwrap();
+	}
+
+	#[test]
+	fn set_deny_reentry_works() {
+		const CODE: &str = r#"
+(module
+	(import "seal0" "set_deny_reentry" (func $set_deny_reentry (param i32)))
+	(import "env" "memory" (memory 1 1))
+	(func (export "call")
+		(call $set_deny_reentry (i32.const 1))
 	)
 	(func (export "deploy"))
 )
@@ -3005,6 +3750,9 @@ mod tests {
 
 		let mut mock_ext = MockExt::default();
 		execute(CODE, vec![], &mut mock_ext).unwrap();
+		assert!(mock_ext.deny_reentry);
+		execute(&CODE.replace("i32.const 1", "i32.const 0"), vec![], &mut mock_ext).unwrap();
+		assert!(!mock_ext.deny_reentry);
 	}
 
 	/// This test check that an unstable interface cannot be deployed. In case of runtime
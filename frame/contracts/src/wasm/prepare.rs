@@ -22,15 +22,15 @@
 use crate::{
 	chain_extension::ChainExtension,
 	storage::meter::Diff,
-	wasm::{Determinism, Environment, OwnerInfo, PrefabWasmModule},
+	wasm::{CodeInfo, Determinism, Environment, PrefabWasmModule, API_VERSION},
 	AccountIdOf, CodeVec, Config, Error, Schedule,
 };
-use codec::{Encode, MaxEncodedLen};
+use codec::MaxEncodedLen;
+use sp_core::Get;
 use sp_runtime::{traits::Hash, DispatchError};
 use sp_std::prelude::*;
-use wasm_instrument::{
-	gas_metering,
-	parity_wasm::elements::{self, External, Internal, MemoryType, Type, ValueType},
+use wasm_instrument::parity_wasm::elements::{
+	self, External, Internal, MemoryType, Type, ValueType,
 };
 use wasmi::StackLimits;
 use wasmparser::{Validator, WasmFeatures};
@@ -145,6 +145,33 @@ impl<'a, T: Config> ContractModule<'a, T> {
 		Ok(())
 	}
 
+	/// Ensure that the parameters and locals of every function fit onto the stack.
+	///
+	/// Such a function would trap as soon as it is called.
+	fn ensure_stack_height_limit(&self, limit: u32) -> Result<(), &'static str> {
+		let (func_section, code_section, type_section) = match (
+			self.module.function_section(),
+			self.module.code_section(),
+			self.module.type_section(),
+		) {
+			(Some(funcs), Some(code), Some(types)) => (funcs, code, types),
+			_ => return Ok(()),
+		};
+		for (func, func_body) in func_section.entries().iter().zip(code_section.bodies()) {
+			let Type::Function(func_type) = type_section
+				.types()
+				.get(func.type_ref() as usize)
+				.ok_or("function refers to a non-existent type")?;
+			let locals_count: u32 =
+				func_body.locals().iter().map(|val_type| val_type.count()).sum();
+			let height = (func_type.params().len() as u32).saturating_add(locals_count);
+			if height > limit {
+				return Err("single function exceeds the stack height")
+			}
+		}
+		Ok(())
+	}
+
 	/// Ensures that no floating point types are in use.
 	fn ensure_no_floating_types(&self) -> Result<(), &'static str> {
 		if let Some(global_section) = self.module.global_section() {
@@ -208,14 +235,6 @@ impl<'a, T: Config> ContractModule<'a, T> {
 		Ok(())
 	}
 
-	fn inject_gas_metering(self, determinism: Determinism) -> Result<Self, &'static str> {
-		let gas_rules = self.schedule.rules(&self.module, determinism);
-		let backend = gas_metering::host_function::Injector::new("seal0", "gas");
-		let contract_module = gas_metering::inject(self.module, backend, &gas_rules)
-			.map_err(|_| "gas instrumentation failed")?;
-		Ok(ContractModule { module: contract_module, schedule: self.schedule })
-	}
-
 	/// Check that the module has required exported functions. For now
 	/// these are just entrypoints:
 	///
@@ -297,12 +316,7 @@ impl<'a, T: Config> ContractModule<'a, T> {
 	///
 	/// This makes sure that the import section looks as we expect it from a contract
 	/// and enforces and returns the memory type declared by the contract if any.
-	///
-	/// `import_fn_banlist`: list of function names that are disallowed to be imported
-	fn scan_imports(
-		&self,
-		import_fn_banlist: &[&[u8]],
-	) -> Result<Option<&MemoryType>, &'static str> {
+	fn scan_imports(&self) -> Result<Option<&MemoryType>, &'static str> {
 		let module = &self.module;
 		let import_entries = module.import_section().map(|is| is.entries()).unwrap_or(&[]);
 		let mut imported_mem_type = None;
@@ -312,14 +326,19 @@ impl<'a, T: Config> ContractModule<'a, T> {
 				External::Table(_) => return Err("Cannot import tables"),
 				External::Global(_) => return Err("Cannot import globals"),
 				External::Function(_) => {
+					let forbidden = &self.schedule.validation.forbidden_imports;
+					if forbidden.iter().any(|name| name.as_slice() == import.field().as_bytes()) {
+						return Err("module imports a function forbidden by the schedule")
+					}
 					if !T::ChainExtension::enabled() &&
 						import.field().as_bytes() == b"seal_call_chain_extension"
 					{
 						return Err("module uses chain extensions but chain extensions are disabled")
 					}
-
-					if import_fn_banlist.iter().any(|f| import.field().as_bytes() == *f) {
-						return Err("module imports a banned function")
+					if !T::RandomnessEnabled::get() &&
+						matches!(import.field().as_bytes(), b"seal_random" | b"random")
+					{
+						return Err("module uses randomness but randomness is disabled")
 					}
 				},
 				External::Memory(ref memory_type) => {
@@ -339,10 +358,6 @@ impl<'a, T: Config> ContractModule<'a, T> {
 		}
 		Ok(imported_mem_type)
 	}
-
-	fn into_wasm_code(self) -> Result<Vec<u8>, &'static str> {
-		elements::serialize(self.module).map_err(|_| "error serializing instrumented module")
-	}
 }
 
 fn get_memory_limits<T: Config>(
@@ -358,6 +373,12 @@ fn get_memory_limits<T: Config>(
 			(_, Some(maximum)) if maximum > schedule.limits.memory_pages =>
 				Err("Maximum number of pages should not exceed the configured maximum."),
 			(initial, Some(maximum)) => Ok((initial, maximum)),
+			(initial, None) if !schedule.validation.memory_maximum_required =>
+				if initial > schedule.limits.memory_pages {
+					Err("Requested initial number of pages exceeds the configured maximum.")
+				} else {
+					Ok((initial, schedule.limits.memory_pages))
+				},
 			(_, None) => {
 				// Maximum number of pages should be always declared.
 				// This isn't a hard requirement and can be treated as a maximum set
@@ -372,25 +393,57 @@ fn get_memory_limits<T: Config>(
 	}
 }
 
-/// Check and instrument the given `original_code`.
+/// Replaces the sign extension operators of the already validated `code` by `clz` of the same
+/// type.
+///
+/// [`ContractModule`] can't decode those operators. As they take no immediates and leave the
+/// height and types of the stack alone, its checks yield the same result on the returned code.
+fn erase_sign_extension(code: &[u8]) -> Result<Vec<u8>, &'static str> {
+	use wasmparser::{Operator, Parser, Payload};
+
+	let mut erased = code.to_vec();
+	for payload in Parser::new(0).parse_all(code) {
+		let body = match payload.map_err(|_| "Can't decode wasm code")? {
+			Payload::CodeSectionEntry(body) => body,
+			_ => continue,
+		};
+		let mut operators = body.get_operators_reader().map_err(|_| "Can't decode wasm code")?;
+		while !operators.eof() {
+			let (operator, offset) =
+				operators.read_with_offset().map_err(|_| "Can't decode wasm code")?;
+			erased[offset] = match operator {
+				Operator::I32Extend8S | Operator::I32Extend16S => 0x67,
+				Operator::I64Extend8S | Operator::I64Extend16S | Operator::I64Extend32S => 0x79,
+				_ => continue,
+			};
+		}
+	}
+	Ok(erased)
+}
+
+/// Check that the given `code` is a valid contract that satisfies all limits of the `schedule`.
 ///
-/// On success it returns the instrumented versions together with its `(initial, maximum)`
-/// error requirement. The memory requirement was also validated against the `schedule`.
-fn instrument<E, T>(
-	original_code: &[u8],
+/// The code is never modified. Execution is metered by the fuel of the engine instead of
+/// instrumentation and hence the code does not need to be revisited when the `schedule` changes.
+fn validate<E, T>(
+	code: &[u8],
 	schedule: &Schedule<T>,
 	determinism: Determinism,
 	try_instantiate: TryInstantiate,
-) -> Result<(Vec<u8>, (u32, u32)), (DispatchError, &'static str)>
+) -> Result<(), (DispatchError, &'static str)>
 where
 	E: Environment<()>,
 	T: Config,
 {
-	// Do not enable any features here. Any additional feature needs to be carefully
-	// checked for potential security issues. For example, enabling multi value could lead
-	// to a DoS vector: It breaks our assumption that branch instructions are of constant time.
-	// Depending on the implementation they can linearly depend on the amount of values returned
-	// from a block.
+	let rules = &schedule.validation;
+	// Code that is not allowed to use floats is validated as if it was deterministic.
+	let determinism = if rules.relaxed_floats { determinism } else { Determinism::Deterministic };
+
+	// Do not enable any features here unless the `rules` ask for it. Any additional feature needs
+	// to be carefully checked for potential security issues. For example, enabling multi value
+	// could lead to a DoS vector: It breaks our assumption that branch instructions are of
+	// constant time. Depending on the implementation they can linearly depend on the amount of
+	// values returned from a block.
 	Validator::new_with_features(WasmFeatures {
 		relaxed_simd: false,
 		threads: false,
@@ -401,45 +454,42 @@ where
 		extended_const: false,
 		component_model: false,
 		// This is not our only defense: We check for float types later in the preparation
-		// process. Additionally, all instructions explictily  need to have weights assigned
-		// or the deployment will fail. We have none assigned for float instructions.
+		// process. Additionally, the engine is configured to reject float instructions
+		// when instantiating deterministic code.
 		deterministic_only: matches!(determinism, Determinism::Deterministic),
 		mutable_global: false,
 		saturating_float_to_int: false,
-		sign_extension: false,
+		sign_extension: rules.sign_extension,
 		bulk_memory: false,
 		multi_value: false,
 		reference_types: false,
 		simd: false,
 	})
-	.validate_all(original_code)
+	.validate_all(code)
 	.map_err(|err| {
 		log::debug!(target: "runtime::contracts", "{}", err);
 		(Error::<T>::CodeRejected.into(), "validation of new code failed")
 	})?;
 
-	let (code, (initial, maximum)) = (|| {
-		let contract_module = ContractModule::new(original_code, schedule)?;
+	(|| {
+		let erased = if rules.sign_extension { Some(erase_sign_extension(code)?) } else { None };
+		let contract_module = ContractModule::new(erased.as_deref().unwrap_or(code), schedule)?;
 		contract_module.scan_exports()?;
 		contract_module.ensure_no_internal_memory()?;
 		contract_module.ensure_table_size_limit(schedule.limits.table_size)?;
 		contract_module.ensure_global_variable_limit(schedule.limits.globals)?;
 		contract_module.ensure_local_variable_limit(schedule.limits.locals)?;
 		contract_module.ensure_parameter_limit(schedule.limits.parameters)?;
+		contract_module.ensure_stack_height_limit(schedule.limits.stack_height)?;
 		contract_module.ensure_br_table_size_limit(schedule.limits.br_table_size)?;
 
 		if matches!(determinism, Determinism::Deterministic) {
 			contract_module.ensure_no_floating_types()?;
 		}
 
-		// We disallow importing `gas` function here since it is treated as implementation detail.
-		let disallowed_imports = [b"gas".as_ref()];
-		let memory_limits =
-			get_memory_limits(contract_module.scan_imports(&disallowed_imports)?, schedule)?;
+		get_memory_limits(contract_module.scan_imports()?, schedule)?;
 
-		let code = contract_module.inject_gas_metering(determinism)?.into_wasm_code()?;
-
-		Ok((code, memory_limits))
+		Ok(())
 	})()
 	.map_err(|msg: &str| {
 		log::debug!(target: "runtime::contracts", "new code rejected: {}", msg);
@@ -454,14 +504,20 @@ where
 		// We don't actually ever run any code so we can get away with a minimal stack which
 		// reduces the amount of memory that needs to be zeroed.
 		let stack_limits = StackLimits::new(1, 1, 0).expect("initial <= max; qed");
-		PrefabWasmModule::<T>::instantiate::<E, _>(&code, (), (initial, maximum), stack_limits)
-			.map_err(|err| {
-				log::debug!(target: "runtime::contracts", "{}", err);
-				(Error::<T>::CodeRejected.into(), "new code rejected after instrumentation")
-			})?;
+		PrefabWasmModule::<T>::instantiate::<E, _>(
+			code,
+			(),
+			determinism,
+			stack_limits,
+			schedule.limits.memory_pages,
+		)
+		.map_err(|err| {
+			log::debug!(target: "runtime::contracts", "{}", err);
+			(Error::<T>::CodeRejected.into(), "new code rejected on wasmi instantiation")
+		})?;
 	}
 
-	Ok((code, (initial, maximum)))
+	Ok(())
 }
 
 /// Loads the given module given in `original_code`, performs some checks on it and
@@ -474,9 +530,9 @@ where
 /// - imported memory (if any) doesn't reserve more memory than permitted by the `schedule`
 /// - all imported functions from the external environment matches defined by `env` module
 ///
-/// The preprocessing includes injecting code for gas metering and metering the height of stack.
+/// The code itself is stored as is. No instrumentation takes place.
 pub fn prepare<E, T>(
-	original_code: CodeVec<T>,
+	code: CodeVec<T>,
 	schedule: &Schedule<T>,
 	owner: AccountIdOf<T>,
 	determinism: Determinism,
@@ -486,95 +542,62 @@ where
 	E: Environment<()>,
 	T: Config,
 {
-	let (code, (initial, maximum)) =
-		instrument::<E, T>(original_code.as_ref(), schedule, determinism, try_instantiate)?;
-
-	let original_code_len = original_code.len();
-
-	let mut module = PrefabWasmModule {
-		instruction_weights_version: schedule.instruction_weights.version,
-		initial,
-		maximum,
-		code: code.try_into().map_err(|_| (<Error<T>>::CodeTooLarge.into(), ""))?,
-		code_hash: T::Hashing::hash(&original_code),
-		original_code: Some(original_code),
-		owner_info: None,
-		determinism,
-	};
+	validate::<E, T>(code.as_ref(), schedule, determinism, try_instantiate)?;
 
-	// We need to add the sizes of the `#[codec(skip)]` fields which are stored in different
-	// storage items. This is also why we have `3` items added and not only one.
-	let bytes_added = module
-		.encoded_size()
-		.saturating_add(original_code_len)
-		.saturating_add(<OwnerInfo<T>>::max_encoded_len()) as u32;
-	let deposit = Diff { bytes_added, items_added: 3, ..Default::default() }
+	let code_len = code.len() as u32;
+	let compressed_code = if T::CompressCode::get() {
+		sp_io::compression::zstd_compress(&code, T::MaxCodeLen::get())
+			.filter(|compressed| compressed.len() < code.len())
+			.and_then(|compressed| compressed.try_into().ok())
+	} else {
+		None
+	};
+	let stored_len = compressed_code.as_ref().map_or(code_len, |c: &CodeVec<T>| c.len() as u32);
+	// The code and its info are stored in two different storage items.
+	let bytes_added = stored_len.saturating_add(<CodeInfo<T>>::max_encoded_len() as u32);
+	let deposit = Diff { bytes_added, items_added: 2, ..Default::default() }
 		.update_contract::<T>(None)
 		.charge_or_zero();
+	let code_info =
+		CodeInfo { owner, deposit, refcount: 0, determinism, code_len, api_version: API_VERSION };
+	let code_hash = T::Hashing::hash(&code);
 
-	module.owner_info = Some(OwnerInfo { owner, deposit, refcount: 0 });
-
-	Ok(module)
-}
-
-/// Same as [`prepare`] but without constructing a new module.
-///
-/// Used to update the code of an existing module to the newest [`Schedule`] version.
-/// Stictly speaking is not necessary to check the existing code before reinstrumenting because
-/// it can't change in the meantime. However, since we recently switched the validation library
-/// we want to re-validate to weed out any bugs that were lurking in the old version.
-pub fn reinstrument<E, T>(
-	original_code: &[u8],
-	schedule: &Schedule<T>,
-	determinism: Determinism,
-) -> Result<Vec<u8>, DispatchError>
-where
-	E: Environment<()>,
-	T: Config,
-{
-	instrument::<E, T>(original_code, schedule, determinism, TryInstantiate::Skip)
-		.map_err(|(err, msg)| {
-			log::error!(target: "runtime::contracts", "CodeRejected during reinstrument: {}", msg);
-			err
-		})
-		.map(|(code, _)| code)
+	Ok(PrefabWasmModule { code, compressed_code, code_info, code_hash })
 }
 
 /// Alternate (possibly unsafe) preparation functions used only for benchmarking.
 ///
 /// For benchmarking we need to construct special contracts that might not pass our
-/// sanity checks or need to skip instrumentation for correct results. We hide functions
+/// sanity checks. We hide functions
 /// allowing this behind a feature that is only set during benchmarking to prevent usage
 /// in production code.
 #[cfg(feature = "runtime-benchmarks")]
 pub mod benchmarking {
 	use super::*;
 
-	/// Prepare function that neither checks nor instruments the passed in code.
+	/// Prepare function that does not check the passed in code.
 	pub fn prepare<T: Config>(
-		original_code: Vec<u8>,
+		code: Vec<u8>,
 		schedule: &Schedule<T>,
 		owner: AccountIdOf<T>,
 	) -> Result<PrefabWasmModule<T>, &'static str> {
-		let contract_module = ContractModule::new(&original_code, schedule)?;
-		let memory_limits = get_memory_limits(contract_module.scan_imports(&[])?, schedule)?;
+		let contract_module = ContractModule::new(&code, schedule)?;
+		get_memory_limits(contract_module.scan_imports()?, schedule)?;
+		let code_hash = T::Hashing::hash(&code);
+		let code: CodeVec<T> = code.try_into().map_err(|_| "Code too large")?;
 		Ok(PrefabWasmModule {
-			instruction_weights_version: schedule.instruction_weights.version,
-			initial: memory_limits.0,
-			maximum: memory_limits.1,
-			code_hash: T::Hashing::hash(&original_code),
-			original_code: Some(original_code.try_into().map_err(|_| "Original code too large")?),
-			code: contract_module
-				.into_wasm_code()?
-				.try_into()
-				.map_err(|_| "Instrumented code too large")?,
-			owner_info: Some(OwnerInfo {
+			compressed_code: None,
+			code_info: CodeInfo {
 				owner,
 				// this is a helper function for benchmarking which skips deposit collection
 				deposit: Default::default(),
 				refcount: 0,
-			}),
-			determinism: Determinism::Deterministic,
+				determinism: Determinism::Deterministic,
+				code_len: code.len() as u32,
+				api_version: API_VERSION,
+			},
+			code,
+			code_hash,
 		})
 	}
 }
@@ -610,11 +633,6 @@ mod tests {
 				Ok(())
 			}
 
-			// gas is an implementation defined function and a contract can't import it.
-			fn gas(_ctx: _, _memory: _, _amount: u64) -> Result<(), TrapReason> {
-				Ok(())
-			}
-
 			fn nop(_ctx: _, _memory: _, _unused: u64) -> Result<(), TrapReason> {
 				Ok(())
 			}
@@ -638,6 +656,7 @@ mod tests {
 					    locals: 3,
 						parameters: 3,
 						memory_pages: 16,
+						stack_height: 5,
 						table_size: 3,
 						br_table_size: 3,
 						.. Default::default()
@@ -670,7 +689,7 @@ mod tests {
 			)
 			(func (export "deploy"))
 		)"#,
-		Err("gas instrumentation failed")
+		Err("new code rejected on wasmi instantiation")
 	);
 
 	mod functions {
@@ -772,6 +791,41 @@ mod tests {
 		);
 	}
 
+	mod stack_height {
+		use super::*;
+
+		prepare_test!(
+			stack_height_valid,
+			r#"
+			(module
+				(func (param i32 i32 i32)
+					(local i32)
+					(local i32)
+				)
+				(func (export "call"))
+				(func (export "deploy"))
+			)
+			"#,
+			Ok(_)
+		);
+
+		prepare_test!(
+			stack_height_too_high,
+			r#"
+			(module
+				(func (param i32 i32 i32)
+					(local i32)
+					(local i32)
+					(local i32)
+				)
+				(func (export "call"))
+				(func (export "deploy"))
+			)
+			"#,
+			Err("single function exceeds the stack height")
+		);
+	}
+
 	mod memories {
 		use super::*;
 
@@ -1003,21 +1057,6 @@ mod tests {
 			Ok(_)
 		);
 
-		// even though gas is defined the contract can't import it since
-		// it is an implementation defined.
-		prepare_test!(
-			can_not_import_gas_function,
-			r#"
-			(module
-				(import "seal0" "gas" (func (param i32)))
-
-				(func (export "call"))
-				(func (export "deploy"))
-			)
-			"#,
-			Err("module imports a banned function")
-		);
-
 		// memory is in "env" and not in "seal0"
 		prepare_test!(
 			memory_not_in_seal0,
@@ -1064,13 +1103,13 @@ mod tests {
 			wrong_signature,
 			r#"
 			(module
-				(import "seal0" "gas" (func (param i64)))
+				(import "seal0" "nop" (func (param i32)))
 
 				(func (export "call"))
 				(func (export "deploy"))
 			)
 			"#,
-			Err("module imports a banned function")
+			Err("new code rejected on wasmi instantiation")
 		);
 
 		prepare_test!(
@@ -1083,7 +1122,7 @@ mod tests {
 				(func (export "deploy"))
 			)
 			"#,
-			Err("new code rejected after instrumentation")
+			Err("new code rejected on wasmi instantiation")
 		);
 	}
 
@@ -1219,4 +1258,108 @@ mod tests {
 			Err("use of floating point type in function types is forbidden")
 		);
 	}
+
+	mod validation_rules {
+		use super::*;
+		use crate::schedule::ValidationRules;
+
+		fn prepare_with(
+			wat: &str,
+			validation: ValidationRules,
+			determinism: Determinism,
+		) -> Result<PrefabWasmModule<Test>, &'static str> {
+			let wasm = wat::parse_str(wat).unwrap().try_into().unwrap();
+			let schedule = Schedule { validation, ..Default::default() };
+			prepare::<env::Env, Test>(
+				wasm,
+				&schedule,
+				ALICE,
+				determinism,
+				TryInstantiate::Instantiate,
+			)
+			.map_err(|(_, msg)| msg)
+		}
+
+		#[test]
+		fn sign_extension_can_be_enabled() {
+			let wat = r#"
+			(module
+				(func (export "call")
+					(drop (i32.extend8_s (i32.const 1)))
+				)
+				(func (export "deploy"))
+			)
+			"#;
+			let rules = ValidationRules::default();
+			assert_eq!(
+				prepare_with(wat, rules.clone(), Determinism::Deterministic).err(),
+				Some("validation of new code failed")
+			);
+			let rules = ValidationRules { sign_extension: true, ..rules };
+			assert!(prepare_with(wat, rules, Determinism::Deterministic).is_ok());
+		}
+
+		#[test]
+		fn relaxed_floats_can_be_disabled() {
+			let wat = r#"
+			(module
+				(func $foo (param f32))
+				(func (export "call"))
+				(func (export "deploy"))
+			)
+			"#;
+			let rules = ValidationRules::default();
+			assert!(prepare_with(wat, rules.clone(), Determinism::AllowIndeterminism).is_ok());
+			let rules = ValidationRules { relaxed_floats: false, ..rules };
+			assert_eq!(
+				prepare_with(wat, rules, Determinism::AllowIndeterminism).err(),
+				Some("use of floating point type in function types is forbidden")
+			);
+		}
+
+		#[test]
+		fn memory_maximum_can_be_omitted() {
+			let wat = |initial: u32| {
+				format!(
+					r#"
+					(module
+						(import "env" "memory" (memory {}))
+						(func (export "call"))
+						(func (export "deploy"))
+					)
+					"#,
+					initial
+				)
+			};
+			let rules = ValidationRules::default();
+			assert_eq!(
+				prepare_with(&wat(1), rules.clone(), Determinism::Deterministic).err(),
+				Some("Maximum number of pages should be always declared.")
+			);
+			let rules = ValidationRules { memory_maximum_required: false, ..rules };
+			assert!(prepare_with(&wat(1), rules.clone(), Determinism::Deterministic).is_ok());
+			assert_eq!(
+				prepare_with(&wat(17), rules, Determinism::Deterministic).err(),
+				Some("Requested initial number of pages exceeds the configured maximum.")
+			);
+		}
+
+		#[test]
+		fn imports_can_be_forbidden() {
+			let wat = r#"
+			(module
+				(import "seal0" "nop" (func (param i64)))
+				(func (export "call"))
+				(func (export "deploy"))
+			)
+			"#;
+			let rules = ValidationRules::default();
+			assert!(prepare_with(wat, rules.clone(), Determinism::Deterministic).is_ok());
+			let rules = ValidationRules { forbidden_imports: vec![b"nop".to_vec()], ..rules };
+			assert_eq!(
+				prepare_with(wat, rules, Determinism::Deterministic).err(),
+				Some("module imports a function forbidden by the schedule")
+			);
+		}
+	}
 }
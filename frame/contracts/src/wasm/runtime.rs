@@ -21,18 +21,29 @@ use crate::{
 	exec::{ExecError, ExecResult, Ext, FixSizedKey, TopicOf, VarSizedKey},
 	gas::{ChargedAmount, Token},
 	schedule::HostFnWeights,
+	wasm::code_cache,
 	BalanceOf, CodeHash, Config, Error, SENTINEL,
 };
 
 use bitflags::bitflags;
 use codec::{Decode, DecodeLimit, Encode, MaxEncodedLen};
-use frame_support::{dispatch::DispatchError, ensure, traits::Get, weights::Weight, RuntimeDebug};
-use pallet_contracts_primitives::{ExecReturnValue, ReturnFlags};
+use frame_support::{
+	dispatch::DispatchError,
+	ensure,
+	traits::{ExistenceRequirement, Get},
+	weights::Weight,
+	RuntimeDebug,
+};
+use pallet_contracts_primitives::{ExecReturnValue, ReturnFlags, RevertReason};
 use pallet_contracts_proc_macro::define_env;
 use sp_io::hashing::{blake2_128, blake2_256, keccak_256, sha2_256};
 use sp_runtime::traits::{Bounded, Zero};
 use sp_std::{fmt, prelude::*};
-use wasmi::{core::HostError, errors::LinkerError, Linker, Memory, Store};
+use wasmi::{
+	core::{HostError, TrapCode},
+	errors::LinkerError,
+	Caller, Linker, Memory, Store,
+};
 
 /// The maximum nesting depth a contract can use when encoding types.
 const MAX_DECODE_NESTING: u32 = 256;
@@ -110,6 +121,10 @@ pub enum ReturnCode {
 	/// ECDSA compressed pubkey conversion into Ethereum address failed (most probably
 	/// wrong pubkey provided).
 	EcdsaRecoverFailed = 11,
+	/// The XCM message passed to `seal_xcm_execute` was executed but returned an error.
+	XcmExecutionFailed = 12,
+	/// The XCM message passed to `seal_xcm_send` could not be sent.
+	XcmSendFailed = 13,
 }
 
 impl From<ExecReturnValue> for ReturnCode {
@@ -173,9 +188,6 @@ impl HostError for TrapReason {}
 #[cfg_attr(test, derive(Debug, PartialEq, Eq))]
 #[derive(Copy, Clone)]
 pub enum RuntimeCosts {
-	/// Charge the gas meter with the cost of a metering block. The charged costs are
-	/// the supplied cost of the block plus the overhead of the metering itself.
-	MeteringBlock(u64),
 	/// Weight charged for copying data from the sandbox.
 	CopyFromContract(u32),
 	/// Weight charged for copying data to the sandbox.
@@ -190,6 +202,8 @@ pub enum RuntimeCosts {
 	OwnCodeHash,
 	/// Weight of calling `seal_caller_is_origin`.
 	CallerIsOrigin,
+	/// Weight of calling `caller_is_root`.
+	CallerIsRoot,
 	/// Weight of calling `seal_address`.
 	Address,
 	/// Weight of calling `seal_gas_left`.
@@ -198,6 +212,8 @@ pub enum RuntimeCosts {
 	Balance,
 	/// Weight of calling `seal_value_transferred`.
 	ValueTransferred,
+	/// Weight of calling `last_subcall_deposit`.
+	LastSubcallDeposit,
 	/// Weight of calling `seal_minimum_balance`.
 	MinimumBalance,
 	/// Weight of calling `seal_block_number`.
@@ -211,6 +227,9 @@ pub enum RuntimeCosts {
 	/// Weight of calling `seal_return` for the given output size.
 	Return(u32),
 	/// Weight of calling `seal_terminate`.
+	///
+	/// This includes removing the code of the contract and of its delegate dependencies if
+	/// [`Config::RemoveUnusedCode`] is set.
 	Terminate,
 	/// Weight of calling `seal_random`. It includes the weight for copying the subject.
 	Random,
@@ -256,7 +275,18 @@ pub enum RuntimeCosts {
 	ChainExtension(Weight),
 	/// Weight charged for calling into the runtime.
 	CallRuntime(Weight),
+	/// Weight charged for executing an XCM message through `seal_xcm_execute`.
+	XcmExecute(Weight),
+	/// Weight charged for sending an XCM message through `seal_xcm_send`.
+	XcmSend(Weight),
+	/// Weight charged for querying an asset through `seal_asset_balance_of` or
+	/// `seal_asset_total_supply`.
+	AssetQuery(Weight),
+	/// Weight charged for transferring an asset through `seal_asset_transfer`.
+	AssetTransfer(Weight),
 	/// Weight of calling `seal_set_code_hash`
+	///
+	/// This includes removing the previous code if [`Config::RemoveUnusedCode`] is set.
 	SetCodeHash,
 	/// Weight of calling `ecdsa_to_eth_address`
 	EcdsaToEthAddress,
@@ -266,13 +296,22 @@ pub enum RuntimeCosts {
 	AccountEntranceCount,
 	/// Weight of calling `instantiation_nonce`
 	InstantationNonce,
+	/// Weight of calling `seal_storage_deposit_limit_left`
+	StorageDepositLimitLeft,
+	/// Weight of calling `seal_set_deny_reentry`
+	SetDenyReentry,
+	/// Weight of calling `seal_lock_delegate_dependency`
+	LockDelegateDependency,
+	/// Weight of calling `seal_unlock_delegate_dependency`
+	///
+	/// This includes removing the code if [`Config::RemoveUnusedCode`] is set.
+	UnlockDelegateDependency,
 }
 
 impl RuntimeCosts {
 	fn token<T: Config>(&self, s: &HostFnWeights<T>) -> RuntimeToken {
 		use self::RuntimeCosts::*;
 		let weight = match *self {
-			MeteringBlock(amount) => s.gas.saturating_add(Weight::from_ref_time(amount)),
 			CopyFromContract(len) => s.return_per_byte.saturating_mul(len.into()),
 			CopyToContract(len) => s.input_per_byte.saturating_mul(len.into()),
 			Caller => s.caller,
@@ -280,17 +319,21 @@ impl RuntimeCosts {
 			CodeHash => s.code_hash,
 			OwnCodeHash => s.own_code_hash,
 			CallerIsOrigin => s.caller_is_origin,
+			CallerIsRoot => s.caller_is_root,
 			Address => s.address,
 			GasLeft => s.gas_left,
 			Balance => s.balance,
 			ValueTransferred => s.value_transferred,
+			LastSubcallDeposit => s.last_subcall_deposit,
 			MinimumBalance => s.minimum_balance,
 			BlockNumber => s.block_number,
 			Now => s.now,
 			WeightToFee => s.weight_to_fee,
 			InputBase => s.input,
 			Return(len) => s.r#return.saturating_add(s.return_per_byte.saturating_mul(len.into())),
-			Terminate => s.terminate,
+			Terminate => s.terminate.saturating_add(code_cache::removal_weight::<T>(
+				T::MaxDelegateDependencies::get().saturating_add(1),
+			)),
 			Random => s.random,
 			DepositEvent { num_topic, len } => s
 				.deposit_event
@@ -337,11 +380,20 @@ impl RuntimeCosts {
 			EcdsaRecovery => s.ecdsa_recover,
 			ChainExtension(weight) => weight,
 			CallRuntime(weight) => weight,
-			SetCodeHash => s.set_code_hash,
+			XcmExecute(weight) => weight,
+			XcmSend(weight) => weight,
+			AssetQuery(weight) => weight,
+			AssetTransfer(weight) => weight,
+			SetCodeHash => s.set_code_hash.saturating_add(code_cache::removal_weight::<T>(1)),
 			EcdsaToEthAddress => s.ecdsa_to_eth_address,
 			ReentrantCount => s.reentrance_count,
 			AccountEntranceCount => s.account_reentrance_count,
 			InstantationNonce => s.instantiation_nonce,
+			StorageDepositLimitLeft => s.storage_deposit_limit_left,
+			SetDenyReentry => s.set_deny_reentry,
+			LockDelegateDependency => s.lock_delegate_dependency,
+			UnlockDelegateDependency =>
+				s.unlock_delegate_dependency.saturating_add(code_cache::removal_weight::<T>(1)),
 		};
 		RuntimeToken {
 			#[cfg(test)]
@@ -422,12 +474,26 @@ bitflags! {
 	}
 }
 
+bitflags! {
+	/// Flags used to change the behaviour of `seal_transfer`.
+	pub struct TransferFlags: u32 {
+		/// Allow the free balance of the contract to drop below the existential deposit.
+		///
+		/// The transfer still fails if it would remove the contract's account.
+		const ALLOW_DEATH = 0b0000_0001;
+		/// Transfer as much of the contract's free balance as possible.
+		///
+		/// The supplied value is ignored when set.
+		const TRANSFER_ALL = 0b0000_0010;
+	}
+}
+
 /// The kind of call that should be performed.
 enum CallType {
 	/// Execute another instantiated contract
-	Call { callee_ptr: u32, value_ptr: u32, gas: u64 },
+	Call { callee_ptr: u32, value_ptr: u32, deposit_ptr: u32, gas: u64 },
 	/// Execute deployed code in the context (storage, account ID, value) of the caller contract
-	DelegateCall { code_hash_ptr: u32 },
+	DelegateCall { code_hash_ptr: u32, deposit_ptr: u32, gas: u64 },
 }
 
 impl CallType {
@@ -480,13 +546,13 @@ impl<'a, E: Ext + 'a> Runtime<'a, E> {
 			Ok(_) => Ok(ExecReturnValue { flags: ReturnFlags::empty(), data: Vec::new() }),
 			// Contract either trapped or some host function aborted the execution.
 			Err(wasmi::Error::Trap(trap)) => {
+				// The engine ran out of fuel which means that the contract exhausted its gas.
+				if let Some(TrapCode::OutOfFuel) = trap.trap_code() {
+					return Err(Error::<E::T>::OutOfGas.into())
+				}
 				// If we encoded a reason then it is some abort generated by a host function.
 				// Otherwise the trap came from the contract.
-				let reason: TrapReason = *trap
-					.into_host()
-					.ok_or(Error::<E::T>::ContractTrapped)?
-					.downcast()
-					.expect("`TrapReason` is the only type we use to encode host errors; qed");
+				let reason: TrapReason = trap.downcast().ok_or(Error::<E::T>::ContractTrapped)?;
 				match reason {
 					Return(ReturnData { flags, data }) => {
 						let flags =
@@ -508,6 +574,43 @@ impl<'a, E: Ext + 'a> Runtime<'a, E> {
 		}
 	}
 
+	/// Charge the gas meter with the fuel the engine consumed since the last sync.
+	///
+	/// This is called before every host function. Returns the `ref_time` left afterwards
+	/// which needs to be passed to [`Self::sync_host_fuel`] once the host function is done.
+	pub fn charge_engine_fuel(caller: &mut Caller<'_, Self>) -> Result<u64, TrapReason> {
+		let engine_consumed_total =
+			caller.fuel_consumed().expect("Fuel metering is enabled for contracts; qed");
+		let ref_time_per_fuel =
+			caller.data().ext.schedule().instruction_weights.ref_time_per_fuel();
+		let gas_left = caller
+			.data_mut()
+			.ext
+			.gas_meter()
+			.charge_fuel(ref_time_per_fuel, engine_consumed_total)?;
+		Ok(gas_left.ref_time())
+	}
+
+	/// Burn the engine fuel that corresponds to the gas charged by a host function.
+	///
+	/// Without this the engine would be allowed to execute more instructions than the gas
+	/// left in the meter would pay for.
+	pub fn sync_host_fuel(
+		caller: &mut Caller<'_, Self>,
+		ref_time_before: u64,
+	) -> Result<(), TrapReason> {
+		let ref_time_per_fuel =
+			caller.data().ext.schedule().instruction_weights.ref_time_per_fuel();
+		let ref_time_after = caller.data_mut().ext.gas_meter().gas_left().ref_time();
+		let fuel = ref_time_before.saturating_sub(ref_time_after) / ref_time_per_fuel;
+		if fuel.is_zero() {
+			return Ok(())
+		}
+		caller.consume_fuel(fuel).map_err(|_| Error::<E::T>::OutOfGas)?;
+		caller.data_mut().ext.gas_meter().record_host_fuel(fuel);
+		Ok(())
+	}
+
 	/// Get a mutable reference to the inner `Ext`.
 	///
 	/// This is mainly for the chain extension to have access to the environment the
@@ -659,6 +762,38 @@ impl<'a, E: Ext + 'a> Runtime<'a, E> {
 		self.write_sandbox_memory(memory, out_len_ptr, &buf_len.encode())
 	}
 
+	/// Write the [`RevertReason`] of a failed sub call to the output buffer of the caller.
+	///
+	/// Only called for failures that are reported to the caller as a [`ReturnCode`]. Failures
+	/// that trap the caller don't write anything as nobody could observe it.
+	///
+	/// The reason is written as an envelope prefixed with
+	/// [`REVERT_REASON_PREFIX`](pallet_contracts_primitives::REVERT_REASON_PREFIX).
+	/// In contrast to [`Self::write_sandbox_output`] a too small output buffer is not an
+	/// error: The output buffer is left untouched in this case so that contracts which are
+	/// not aware of revert reasons keep working as before.
+	fn write_revert_reason(
+		&mut self,
+		memory: &mut [u8],
+		out_ptr: u32,
+		out_len_ptr: u32,
+		reason: &RevertReason,
+	) -> Result<(), DispatchError> {
+		if out_ptr == SENTINEL {
+			return Ok(())
+		}
+
+		let envelope = reason.to_envelope();
+		let len: u32 = self.read_sandbox_memory_as(memory, out_len_ptr)?;
+		if len < envelope.len() as u32 {
+			return Ok(())
+		}
+
+		self.write_sandbox_output(memory, out_ptr, out_len_ptr, &envelope, false, |len| {
+			Some(RuntimeCosts::CopyToContract(len))
+		})
+	}
+
 	/// Write the given buffer to the designated location in the sandbox memory.
 	///
 	/// Returns `Err` if one of the following conditions occurs:
@@ -710,6 +845,59 @@ impl<'a, E: Ext + 'a> Runtime<'a, E> {
 		Ok(())
 	}
 
+	/// Same as [`Self::compute_hash_on_intermediate_buffer`] but hashes the concatenation of
+	/// multiple buffers.
+	///
+	/// The buffers are described by `list_len` pairs of a `u32` pointer and a `u32` length at
+	/// `list_ptr`. Reading the list and hashing the buffers are charged before any buffer is
+	/// read, the latter according to `costs` for the total length of all buffers.
+	fn compute_hash_on_buffer_list<F, R>(
+		&mut self,
+		memory: &mut [u8],
+		hash_fn: F,
+		costs: fn(u32) -> RuntimeCosts,
+		list_ptr: u32,
+		list_len: u32,
+		output_ptr: u32,
+	) -> Result<(), DispatchError>
+	where
+		F: FnOnce(&[u8]) -> R,
+		R: AsRef<[u8]>,
+	{
+		let list_bytes = list_len.checked_mul(8).ok_or(Error::<E::T>::OutOfBounds)?;
+		self.charge_gas(RuntimeCosts::CopyFromContract(list_bytes))?;
+		let buffers = self
+			.read_sandbox_memory(memory, list_ptr, list_bytes)?
+			.chunks_exact(8)
+			.map(|entry| {
+				let (ptr, len) = entry.split_at(4);
+				(
+					u32::from_le_bytes(ptr.try_into().expect("entries are 8 bytes; qed")),
+					u32::from_le_bytes(len.try_into().expect("entries are 8 bytes; qed")),
+				)
+			})
+			.collect::<Vec<_>>();
+
+		let input_len = buffers
+			.iter()
+			.try_fold(0u32, |total, (_, len)| total.checked_add(*len))
+			.filter(|total| *total <= self.ext.schedule().limits.max_memory_size())
+			.ok_or(Error::<E::T>::OutOfBounds)?;
+		self.charge_gas(costs(input_len))?;
+
+		let mut input = vec![0u8; input_len as usize];
+		let mut offset = 0;
+		for (ptr, len) in buffers {
+			let end = offset + len as usize;
+			self.read_sandbox_memory_into_buf(memory, ptr, &mut input[offset..end])?;
+			offset = end;
+		}
+
+		let hash = hash_fn(&input);
+		self.write_sandbox_memory(memory, output_ptr, hash.as_ref())?;
+		Ok(())
+	}
+
 	/// Fallible conversion of `DispatchError` to `ReturnCode`.
 	fn err_into_return_code(from: DispatchError) -> Result<ReturnCode, DispatchError> {
 		use ReturnCode::*;
@@ -882,9 +1070,10 @@ impl<'a, E: Ext + 'a> Runtime<'a, E> {
 		};
 
 		let call_outcome = match call_type {
-			CallType::Call { callee_ptr, value_ptr, gas } => {
+			CallType::Call { callee_ptr, value_ptr, deposit_ptr, gas } => {
 				let callee: <<E as Ext>::T as frame_system::Config>::AccountId =
 					self.read_sandbox_memory_as(memory, callee_ptr)?;
+				let deposit_limit = self.read_deposit_limit(memory, deposit_ptr)?;
 				let value: BalanceOf<<E as Ext>::T> =
 					self.read_sandbox_memory_as(memory, value_ptr)?;
 				if value > 0u32.into() {
@@ -892,18 +1081,25 @@ impl<'a, E: Ext + 'a> Runtime<'a, E> {
 				}
 				self.ext.call(
 					Weight::from_ref_time(gas),
+					deposit_limit,
 					callee,
 					value,
 					input_data,
 					flags.contains(CallFlags::ALLOW_REENTRY),
 				)
 			},
-			CallType::DelegateCall { code_hash_ptr } => {
+			CallType::DelegateCall { code_hash_ptr, deposit_ptr, gas } => {
 				if flags.contains(CallFlags::ALLOW_REENTRY) {
 					return Err(Error::<E::T>::InvalidCallFlags.into())
 				}
 				let code_hash = self.read_sandbox_memory_as(memory, code_hash_ptr)?;
-				self.ext.delegate_call(code_hash, input_data)
+				let deposit_limit = self.read_deposit_limit(memory, deposit_ptr)?;
+				self.ext.delegate_call(
+					Weight::from_ref_time(gas),
+					deposit_limit,
+					code_hash,
+					input_data,
+				)
 			},
 		};
 
@@ -928,7 +1124,12 @@ impl<'a, E: Ext + 'a> Runtime<'a, E> {
 				|len| Some(RuntimeCosts::CopyToContract(len)),
 			)?;
 		}
-		Ok(Runtime::<E>::exec_into_return_code(call_outcome)?)
+		let revert_reason = call_outcome.as_ref().err().map(ExecError::revert_reason);
+		let return_code = Runtime::<E>::exec_into_return_code(call_outcome)?;
+		if let Some(reason) = revert_reason {
+			self.write_revert_reason(memory, output_ptr, output_len_ptr, &reason)?;
+		}
+		Ok(return_code)
 	}
 
 	fn instantiate(
@@ -936,6 +1137,7 @@ impl<'a, E: Ext + 'a> Runtime<'a, E> {
 		memory: &mut [u8],
 		code_hash_ptr: u32,
 		gas: u64,
+		deposit_ptr: u32,
 		value_ptr: u32,
 		input_data_ptr: u32,
 		input_data_len: u32,
@@ -948,6 +1150,7 @@ impl<'a, E: Ext + 'a> Runtime<'a, E> {
 	) -> Result<ReturnCode, TrapReason> {
 		let gas = Weight::from_ref_time(gas);
 		self.charge_gas(RuntimeCosts::InstantiateBase { input_data_len, salt_len })?;
+		let deposit_limit = self.read_deposit_limit(memory, deposit_ptr)?;
 		let value: BalanceOf<<E as Ext>::T> = self.read_sandbox_memory_as(memory, value_ptr)?;
 		if value > 0u32.into() {
 			self.charge_gas(RuntimeCosts::InstantiateSurchargeTransfer)?;
@@ -956,7 +1159,8 @@ impl<'a, E: Ext + 'a> Runtime<'a, E> {
 			self.read_sandbox_memory_as(memory, code_hash_ptr)?;
 		let input_data = self.read_sandbox_memory(memory, input_data_ptr, input_data_len)?;
 		let salt = self.read_sandbox_memory(memory, salt_ptr, salt_len)?;
-		let instantiate_outcome = self.ext.instantiate(gas, code_hash, value, input_data, &salt);
+		let instantiate_outcome =
+			self.ext.instantiate(gas, deposit_limit, code_hash, value, input_data, &salt);
 		if let Ok((address, output)) = &instantiate_outcome {
 			if !output.flags.contains(ReturnFlags::REVERT) {
 				self.write_sandbox_output(
@@ -977,7 +1181,28 @@ impl<'a, E: Ext + 'a> Runtime<'a, E> {
 				|len| Some(RuntimeCosts::CopyToContract(len)),
 			)?;
 		}
-		Ok(Runtime::<E>::exec_into_return_code(instantiate_outcome.map(|(_, retval)| retval))?)
+		let revert_reason = instantiate_outcome.as_ref().err().map(ExecError::revert_reason);
+		let return_code =
+			Runtime::<E>::exec_into_return_code(instantiate_outcome.map(|(_, retval)| retval))?;
+		if let Some(reason) = revert_reason {
+			self.write_revert_reason(memory, output_ptr, output_len_ptr, &reason)?;
+		}
+		Ok(return_code)
+	}
+
+	/// Read the storage deposit limit for a sub call from `deposit_ptr`.
+	///
+	/// `SENTINEL` means that no limit was supplied which is encoded as zero.
+	fn read_deposit_limit(
+		&self,
+		memory: &[u8],
+		deposit_ptr: u32,
+	) -> Result<BalanceOf<<E as Ext>::T>, DispatchError> {
+		if deposit_ptr == SENTINEL {
+			Ok(Zero::zero())
+		} else {
+			self.read_sandbox_memory_as(memory, deposit_ptr)
+		}
 	}
 
 	fn terminate(&mut self, memory: &[u8], beneficiary_ptr: u32) -> Result<(), TrapReason> {
@@ -998,17 +1223,6 @@ impl<'a, E: Ext + 'a> Runtime<'a, E> {
 // for every function.
 #[define_env(doc)]
 pub mod env {
-	/// Account for used gas. Traps if gas used is greater than gas limit.
-	///
-	/// NOTE: This is a implementation defined call and is NOT a part of the public API.
-	/// This call is supposed to be called only by instrumentation injected code.
-	///
-	/// - `amount`: How much gas is used.
-	fn gas(ctx: _, _memory: _, amount: u64) -> Result<(), TrapReason> {
-		ctx.charge_gas(RuntimeCosts::MeteringBlock(amount))?;
-		Ok(())
-	}
-
 	/// Set the value at the given key in the contract storage.
 	///
 	/// Equivalent to the newer version [`super::seal1::Api::set_storage`] with the exception of the
@@ -1268,7 +1482,57 @@ pub mod env {
 		let callee: <<E as Ext>::T as frame_system::Config>::AccountId =
 			ctx.read_sandbox_memory_as(memory, account_ptr)?;
 		let value: BalanceOf<<E as Ext>::T> = ctx.read_sandbox_memory_as(memory, value_ptr)?;
-		let result = ctx.ext.transfer(&callee, value);
+		let result = ctx.ext.transfer(&callee, value, ExistenceRequirement::KeepAlive);
+		match result {
+			Ok(()) => Ok(ReturnCode::Success),
+			Err(err) => {
+				let code = Runtime::<E>::err_into_return_code(err)?;
+				Ok(code)
+			},
+		}
+	}
+
+	/// Transfer some value to another account.
+	///
+	/// This is equivalent to calling the previous version of this function with
+	/// `flags` set to zero. See the previous version for a documentation of the
+	/// other parameters.
+	///
+	/// # Parameters
+	///
+	/// - `flags`: See [`TransferFlags`] for a documentation of the supported flags.
+	/// - `account_ptr`: a pointer to the address of the beneficiary account.
+	/// - `value_ptr`: a pointer to the buffer with value, how much value to send. Ignored if
+	///   [`TransferFlags::TRANSFER_ALL`] is set.
+	///
+	/// # Errors
+	///
+	/// - `ReturnCode::TransferFailed`
+	#[version(1)]
+	#[unstable]
+	#[prefixed_alias]
+	fn transfer(
+		ctx: _,
+		memory: _,
+		flags: u32,
+		account_ptr: u32,
+		value_ptr: u32,
+	) -> Result<ReturnCode, TrapReason> {
+		ctx.charge_gas(RuntimeCosts::Transfer)?;
+		let flags = TransferFlags::from_bits(flags).ok_or(Error::<E::T>::InvalidTransferFlags)?;
+		let callee: <<E as Ext>::T as frame_system::Config>::AccountId =
+			ctx.read_sandbox_memory_as(memory, account_ptr)?;
+		let existence_requirement = if flags.contains(TransferFlags::ALLOW_DEATH) {
+			ExistenceRequirement::AllowDeath
+		} else {
+			ExistenceRequirement::KeepAlive
+		};
+		let result = if flags.contains(TransferFlags::TRANSFER_ALL) {
+			ctx.ext.transfer_all(&callee, existence_requirement)
+		} else {
+			let value: BalanceOf<<E as Ext>::T> = ctx.read_sandbox_memory_as(memory, value_ptr)?;
+			ctx.ext.transfer(&callee, value, existence_requirement)
+		};
 		match result {
 			Ok(()) => Ok(ReturnCode::Success),
 			Err(err) => {
@@ -1308,7 +1572,7 @@ pub mod env {
 		ctx.call(
 			memory,
 			CallFlags::ALLOW_REENTRY,
-			CallType::Call { callee_ptr, value_ptr, gas },
+			CallType::Call { callee_ptr, value_ptr, deposit_ptr: SENTINEL, gas },
 			input_data_ptr,
 			input_data_len,
 			output_ptr,
@@ -1345,6 +1609,11 @@ pub mod env {
 	/// - `ReturnCode::CalleeTrapped`
 	/// - `ReturnCode::TransferFailed`
 	/// - `ReturnCode::NotCallable`
+	///
+	/// For every error but `ReturnCode::CalleeReverted` the output buffer receives a
+	/// `RevertReason` envelope (see `pallet_contracts_primitives::RevertReason`) describing the
+	/// failure if it is large enough to hold it. Otherwise it is left untouched. Any other
+	/// failure traps the calling contract.
 	#[version(1)]
 	#[prefixed_alias]
 	fn call(
@@ -1362,7 +1631,60 @@ pub mod env {
 		ctx.call(
 			memory,
 			CallFlags::from_bits(flags).ok_or(Error::<E::T>::InvalidCallFlags)?,
-			CallType::Call { callee_ptr, value_ptr, gas },
+			CallType::Call { callee_ptr, value_ptr, deposit_ptr: SENTINEL, gas },
+			input_data_ptr,
+			input_data_len,
+			output_ptr,
+			output_len_ptr,
+		)
+	}
+
+	/// Make a call to another contract.
+	///
+	/// Equivalent to the older version of this function but lets the caller cap the storage
+	/// deposit the callee may consume.
+	///
+	/// # Parameters
+	///
+	/// - `flags`: See `crate::wasm::runtime::CallFlags` for a documenation of the supported flags.
+	/// - `callee_ptr`: a pointer to the address of the callee contract. Should be decodable as an
+	///   `T::AccountId`. Traps otherwise.
+	/// - `gas`: how much gas to devote to the execution.
+	/// - `deposit_ptr`: a pointer to the buffer with the storage deposit limit of the callee.
+	///   Should be decodable as a `T::Balance`. Traps otherwise. Passing `SENTINEL` or a zero limit
+	///   lets the callee use whatever is left of the caller's limit.
+	/// - `value_ptr`: a pointer to the buffer with value, how much value to send. Should be
+	///   decodable as a `T::Balance`. Traps otherwise.
+	/// - `input_data_ptr`: a pointer to a buffer to be used as input data to the callee.
+	/// - `input_data_len`: length of the input data buffer.
+	/// - `output_ptr`: a pointer where the output buffer is copied to.
+	/// - `output_len_ptr`: in-out pointer to where the length of the buffer is read from and the
+	///   actual length is written to.
+	///
+	/// # Errors
+	///
+	/// Same as the older version of this function. A callee that exceeds its storage deposit
+	/// limit fails with `ReturnCode::CalleeTrapped`. The calling contract keeps running.
+	#[version(2)]
+	#[unstable]
+	#[prefixed_alias]
+	fn call(
+		ctx: _,
+		memory: _,
+		flags: u32,
+		callee_ptr: u32,
+		gas: u64,
+		deposit_ptr: u32,
+		value_ptr: u32,
+		input_data_ptr: u32,
+		input_data_len: u32,
+		output_ptr: u32,
+		output_len_ptr: u32,
+	) -> Result<ReturnCode, TrapReason> {
+		ctx.call(
+			memory,
+			CallFlags::from_bits(flags).ok_or(Error::<E::T>::InvalidCallFlags)?,
+			CallType::Call { callee_ptr, value_ptr, deposit_ptr, gas },
 			input_data_ptr,
 			input_data_len,
 			output_ptr,
@@ -1394,12 +1716,67 @@ pub mod env {
 	/// - `ReturnCode::CalleeReverted`: Output buffer is returned.
 	/// - `ReturnCode::CalleeTrapped`
 	/// - `ReturnCode::CodeNotFound`
+	///
+	/// For every error but `ReturnCode::CalleeReverted` the output buffer receives a
+	/// `RevertReason` envelope (see `pallet_contracts_primitives::RevertReason`) describing the
+	/// failure if it is large enough to hold it. Otherwise it is left untouched. Any other
+	/// failure traps the calling contract.
+	#[prefixed_alias]
+	fn delegate_call(
+		ctx: _,
+		memory: _,
+		flags: u32,
+		code_hash_ptr: u32,
+		input_data_ptr: u32,
+		input_data_len: u32,
+		output_ptr: u32,
+		output_len_ptr: u32,
+	) -> Result<ReturnCode, TrapReason> {
+		ctx.call(
+			memory,
+			CallFlags::from_bits(flags).ok_or(Error::<E::T>::InvalidCallFlags)?,
+			CallType::DelegateCall { code_hash_ptr, deposit_ptr: SENTINEL, gas: 0 },
+			input_data_ptr,
+			input_data_len,
+			output_ptr,
+			output_len_ptr,
+		)
+	}
+
+	/// Execute code in the context (storage, caller, value) of the current contract.
+	///
+	/// Equivalent to the older version of this function but lets the caller cap the weight
+	/// and storage deposit the delegated code may consume. This allows library code to be
+	/// executed without handing it all of the resources of the calling contract.
+	///
+	/// # Parameters
+	///
+	/// - `flags`: see `crate::wasm::runtime::CallFlags` for a documentation of the supported flags.
+	/// - `code_hash`: a pointer to the hash of the code to be called.
+	/// - `gas`: how much gas to devote to the execution. Zero means all of the remaining gas.
+	/// - `deposit_ptr`: a pointer to the buffer with the storage deposit limit of the delegated
+	///   code. Should be decodable as a `T::Balance`. Traps otherwise. Passing `SENTINEL` or a zero
+	///   limit lets the delegated code use whatever is left of the caller's limit.
+	/// - `input_data_ptr`: a pointer to a buffer to be used as input data to the callee.
+	/// - `input_data_len`: length of the input data buffer.
+	/// - `output_ptr`: a pointer where the output buffer is copied to.
+	/// - `output_len_ptr`: in-out pointer to where the length of the buffer is read from and the
+	///   actual length is written to.
+	///
+	/// # Errors
+	///
+	/// Same as the older version of this function. Delegated code that exceeds its limits fails
+	/// with `ReturnCode::CalleeTrapped`. The calling contract keeps running.
+	#[version(1)]
+	#[unstable]
 	#[prefixed_alias]
 	fn delegate_call(
 		ctx: _,
 		memory: _,
 		flags: u32,
 		code_hash_ptr: u32,
+		gas: u64,
+		deposit_ptr: u32,
 		input_data_ptr: u32,
 		input_data_len: u32,
 		output_ptr: u32,
@@ -1408,7 +1785,7 @@ pub mod env {
 		ctx.call(
 			memory,
 			CallFlags::from_bits(flags).ok_or(Error::<E::T>::InvalidCallFlags)?,
-			CallType::DelegateCall { code_hash_ptr },
+			CallType::DelegateCall { code_hash_ptr, deposit_ptr, gas },
 			input_data_ptr,
 			input_data_len,
 			output_ptr,
@@ -1451,6 +1828,7 @@ pub mod env {
 			memory,
 			code_hash_ptr,
 			gas,
+			SENTINEL,
 			value_ptr,
 			input_data_ptr,
 			input_data_len,
@@ -1503,6 +1881,11 @@ pub mod env {
 	/// - `ReturnCode::CalleeTrapped`
 	/// - `ReturnCode::TransferFailed`
 	/// - `ReturnCode::CodeNotFound`
+	///
+	/// For every error but `ReturnCode::CalleeReverted` the output buffer receives a
+	/// `RevertReason` envelope (see `pallet_contracts_primitives::RevertReason`) describing the
+	/// failure if it is large enough to hold it. Otherwise it is left untouched. Any other
+	/// failure traps the calling contract.
 	#[version(1)]
 	#[prefixed_alias]
 	fn instantiate(
@@ -1524,6 +1907,72 @@ pub mod env {
 			memory,
 			code_hash_ptr,
 			gas,
+			SENTINEL,
+			value_ptr,
+			input_data_ptr,
+			input_data_len,
+			address_ptr,
+			address_len_ptr,
+			output_ptr,
+			output_len_ptr,
+			salt_ptr,
+			salt_len,
+		)
+	}
+
+	/// Instantiate a contract with the specified code hash.
+	///
+	/// Equivalent to the older version of this function but lets the caller cap the storage
+	/// deposit the new contract may consume. This includes the deposit for creating the contract.
+	///
+	/// # Parameters
+	///
+	/// - `code_hash_ptr`: a pointer to the buffer that contains the initializer code.
+	/// - `gas`: how much gas to devote to the execution of the initializer code.
+	/// - `deposit_ptr`: a pointer to the buffer with the storage deposit limit of the new contract.
+	///   Should be decodable as a `T::Balance`. Traps otherwise. Passing `SENTINEL` or a zero limit
+	///   lets the new contract use whatever is left of the caller's limit.
+	/// - `value_ptr`: a pointer to the buffer with value, how much value to send. Should be
+	///   decodable as a `T::Balance`. Traps otherwise.
+	/// - `input_data_ptr`: a pointer to a buffer to be used as input data to the initializer code.
+	/// - `input_data_len`: length of the input data buffer.
+	/// - `address_ptr`: a pointer where the new account's address is copied to.
+	/// - `address_len_ptr`: in-out pointer to where the length of the buffer is read from and the
+	///   actual length is written to.
+	/// - `output_ptr`: a pointer where the output buffer is copied to.
+	/// - `output_len_ptr`: in-out pointer to where the length of the buffer is read from and the
+	///   actual length is written to.
+	/// - `salt_ptr`: Pointer to raw bytes used for address derivation. See `fn contract_address`.
+	/// - `salt_len`: length in bytes of the supplied salt.
+	///
+	/// # Errors
+	///
+	/// Same as the older version of this function. A new contract that exceeds its storage
+	/// deposit limit fails with `ReturnCode::CalleeTrapped`. The calling contract keeps running.
+	#[version(2)]
+	#[unstable]
+	#[prefixed_alias]
+	fn instantiate(
+		ctx: _,
+		memory: _,
+		code_hash_ptr: u32,
+		gas: u64,
+		deposit_ptr: u32,
+		value_ptr: u32,
+		input_data_ptr: u32,
+		input_data_len: u32,
+		address_ptr: u32,
+		address_len_ptr: u32,
+		output_ptr: u32,
+		output_len_ptr: u32,
+		salt_ptr: u32,
+		salt_len: u32,
+	) -> Result<ReturnCode, TrapReason> {
+		ctx.instantiate(
+			memory,
+			code_hash_ptr,
+			gas,
+			deposit_ptr,
 			value_ptr,
 			input_data_ptr,
 			input_data_len,
@@ -1644,14 +2093,18 @@ pub mod env {
 	/// If this is a top-level call (i.e. initiated by an extrinsic) the origin address of the
 	/// extrinsic will be returned. Otherwise, if this call is initiated by another contract then
 	/// the address of the contract will be returned. The value is encoded as T::AccountId.
+	///
+	/// If the call was initiated by root there is no address and a trap is triggered. Use
+	/// [`caller_is_root`][`Self::caller_is_root`] to check for root beforehand.
 	#[prefixed_alias]
 	fn caller(ctx: _, memory: _, out_ptr: u32, out_len_ptr: u32) -> Result<(), TrapReason> {
 		ctx.charge_gas(RuntimeCosts::Caller)?;
+		let caller = ctx.ext.caller();
 		Ok(ctx.write_sandbox_output(
 			memory,
 			out_ptr,
 			out_len_ptr,
-			&ctx.ext.caller().encode(),
+			&caller.account_id()?.encode(),
 			false,
 			already_charged,
 		)?)
@@ -1676,6 +2129,9 @@ pub mod env {
 
 	/// Retrieve the code hash for a specified contract address.
 	///
+	/// This allows a contract to verify which code is deployed at an address before calling
+	/// into it.
+	///
 	/// # Parameters
 	///
 	/// - `account_ptr`: a pointer to the address in question. Should be decodable as an
@@ -1750,6 +2206,18 @@ pub mod env {
 		Ok(ctx.ext.caller_is_origin() as u32)
 	}
 
+	/// Checks whether the caller of the current contract is root.
+	///
+	/// This is only the case for the first contract of a call stack that was started by root,
+	/// e.g. by a governance decision. Root has no address: [`caller`][`Self::caller`] traps.
+	///
+	/// Returned value is a `u32`-encoded boolean: (`0 = false`, `1 = true`).
+	#[unstable]
+	fn caller_is_root(ctx: _, _memory: _) -> Result<u32, TrapReason> {
+		ctx.charge_gas(RuntimeCosts::CallerIsRoot)?;
+		Ok(ctx.ext.caller_is_root() as u32)
+	}
+
 	/// Stores the address of the current contract into the supplied buffer.
 	///
 	/// The value is stored to linear memory at the address pointed to by `out_ptr`.
@@ -1826,6 +2294,8 @@ pub mod env {
 
 	/// Stores the *free* balance of the current account into the supplied buffer.
 	///
+	/// The existential deposit of the account is not included as it can't be spent.
+	///
 	/// The value is stored to linear memory at the address pointed to by `out_ptr`.
 	/// `out_len_ptr` must point to a u32 value that describes the available space at
 	/// `out_ptr`. This call overwrites it with the size of the value. If the available
@@ -1871,6 +2341,37 @@ pub mod env {
 		)?)
 	}
 
+	/// Stores the storage deposit consumed by the last call or instantiation made by the calling
+	/// contract into the supplied buffer.
+	///
+	/// This lets a contract enforce a deposit budget on the contracts it calls or instantiates.
+	/// The deposit includes everything consumed by nested calls of the callee. It is a zero
+	/// charge when no sub call was made yet or when the last one failed or was reverted.
+	///
+	/// The value is stored to linear memory at the address pointed to by `out_ptr`.
+	/// `out_len_ptr` must point to a `u32` value that describes the available space at
+	/// `out_ptr`. This call overwrites it with the size of the value. If the available
+	/// space at `out_ptr` is less than the size of the value a trap is triggered.
+	///
+	/// The data is encoded as `pallet_contracts_primitives::StorageDeposit<T::Balance>`.
+	#[unstable]
+	fn last_subcall_deposit(
+		ctx: _,
+		memory: _,
+		out_ptr: u32,
+		out_len_ptr: u32,
+	) -> Result<(), TrapReason> {
+		ctx.charge_gas(RuntimeCosts::LastSubcallDeposit)?;
+		Ok(ctx.write_sandbox_output(
+			memory,
+			out_ptr,
+			out_len_ptr,
+			&ctx.ext.last_subcall_deposit().encode(),
+			false,
+			already_charged,
+		)?)
+	}
+
 	/// Stores a random number for the current block and the given subject into the supplied buffer.
 	///
 	/// The value is stored to linear memory at the address pointed to by `out_ptr`.
@@ -2068,7 +2569,9 @@ pub mod env {
 	}
 
 	/// Deposit a contract event with the data buffer and optional list of topics. There is a limit
-	/// on the maximum number of topics specified by `event_topics`.
+	/// on the maximum number of topics specified by `event_topics` and on the size of the data
+	/// specified by `event_data_len`. If the chain configures a deposit per event byte it is
+	/// charged from the storage deposit limit of the call.
 	///
 	/// - `topics_ptr`: a pointer to the buffer of topics encoded as `Vec<T::Hash>`. The value of
 	///   this is ignored if `topics_len` is set to `0`. The topics list can't contain duplicates.
@@ -2089,7 +2592,7 @@ pub mod env {
 			.checked_div(sp_std::mem::size_of::<TopicOf<E::T>>() as u32)
 			.ok_or("Zero sized topics are not allowed")?;
 		ctx.charge_gas(RuntimeCosts::DepositEvent { num_topic, len: data_len })?;
-		if data_len > ctx.ext.max_value_size() {
+		if data_len > ctx.ext.schedule().limits.event_data_len {
 			return Err(Error::<E::T>::ValueTooLarge.into())
 		}
 
@@ -2303,6 +2806,75 @@ pub mod env {
 		)?)
 	}
 
+	/// Computes the KECCAK 256-bit hash of the concatenation of multiple input buffers.
+	///
+	/// Returns the result directly into the given output buffer. This is charged like a single
+	/// call to `hash_keccak_256` for the total length of all buffers, plus copying the list of
+	/// buffers. It saves the overhead of assembling the input first, for example when hashing
+	/// the nodes of a merkle proof.
+	///
+	/// # Note
+	///
+	/// - The input buffers and the output buffer may overlap.
+	/// - The output buffer is expected to hold at least 32 bytes (256 bits).
+	///
+	/// # Parameters
+	///
+	/// - `list_ptr`: the pointer into the linear memory where the list of input buffers is placed.
+	///   Every entry consists of a `u32` pointer and a `u32` length.
+	/// - `list_len`: the number of entries in the list.
+	/// - `output_ptr`: the pointer into the linear memory where the output data is placed. The
+	///   function will write the result directly into this buffer.
+	#[unstable]
+	fn hash_keccak_256_multi(
+		ctx: _,
+		memory: _,
+		list_ptr: u32,
+		list_len: u32,
+		output_ptr: u32,
+	) -> Result<(), TrapReason> {
+		Ok(ctx.compute_hash_on_buffer_list(
+			memory,
+			keccak_256,
+			RuntimeCosts::HashKeccak256,
+			list_ptr,
+			list_len,
+			output_ptr,
+		)?)
+	}
+
+	/// Computes the BLAKE2 128-bit hash of the concatenation of multiple input buffers.
+	///
+	/// Returns the result directly into the given output buffer. This is charged like a single
+	/// call to `hash_blake2_128` for the total length of all buffers, plus copying the list of
+	/// buffers.
+	///
+	/// # Note
+	///
+	/// - The input buffers and the output buffer may overlap.
+	/// - The output buffer is expected to hold at least 16 bytes (128 bits).
+	///
+	/// # Parameters
+	///
+	/// See `hash_keccak_256_multi`.
+	#[unstable]
+	fn hash_blake2_128_multi(
+		ctx: _,
+		memory: _,
+		list_ptr: u32,
+		list_len: u32,
+		output_ptr: u32,
+	) -> Result<(), TrapReason> {
+		Ok(ctx.compute_hash_on_buffer_list(
+			memory,
+			blake2_128,
+			RuntimeCosts::HashBlake128,
+			list_ptr,
+			list_len,
+			output_ptr,
+		)?)
+	}
+
 	/// Call into the chain extension provided by the chain if any.
 	///
 	/// Handling of the input values is up to the specific chain extension and so is the
@@ -2348,9 +2920,11 @@ pub mod env {
 	/// No newlines are added to the supplied message.
 	/// Specifying invalid UTF-8 triggers a trap.
 	///
-	/// This is a no-op if debug message recording is disabled which is always the case
-	/// when the code is executing on-chain. The message is interpreted as UTF-8 and
-	/// appended to the debug buffer which is then supplied to the calling RPC client.
+	/// This is a no-op if debug message recording is disabled which is the case when the code
+	/// is executing on-chain, unless `Config::Debugging` is set to `DebugPolicy::Log`. The
+	/// message is interpreted as UTF-8 and appended to the debug buffer which is then supplied
+	/// to the calling RPC client, or written to the node log when executing on-chain. Reading
+	/// the message is charged per byte.
 	///
 	/// # Note
 	///
@@ -2369,6 +2943,7 @@ pub mod env {
 	) -> Result<ReturnCode, TrapReason> {
 		ctx.charge_gas(RuntimeCosts::DebugMessage)?;
 		if ctx.ext.append_debug_buffer("") {
+			ctx.charge_gas(RuntimeCosts::CopyFromContract(str_len))?;
 			let data = ctx.read_sandbox_memory(memory, str_ptr, str_len)?;
 			let msg =
 				core::str::from_utf8(&data).map_err(|_| <Error<E::T>>::DebugMessageInvalidUTF8)?;
@@ -2433,6 +3008,222 @@ pub mod env {
 		}
 	}
 
+	/// Execute an XCM message locally, using the contract's address as the origin.
+	///
+	/// The message is passed as SCALE encoded bytes and decoded by the runtime's
+	/// implementation of [`crate::xcm::Xcm`]. The maximum weight of the message is charged
+	/// from the gas meter up front. Any unused weight is refunded afterwards.
+	///
+	/// # Parameters
+	///
+	/// - `msg_ptr`: the pointer into the linear memory where the encoded message is placed.
+	/// - `msg_len`: the length of the encoded message in bytes.
+	///
+	/// # Return Value
+	///
+	/// Returns `ReturnCode::Success` when the message was executed successfully. When the
+	/// message was executed but returned an error `ReturnCode::XcmExecutionFailed` is returned.
+	///
+	/// Traps with [`Error::XcmDisabled`] if the runtime does not support XCM for contracts and
+	/// with the error returned by the runtime if the message could not be weighed.
+	#[unstable]
+	#[prefixed_alias]
+	fn xcm_execute(
+		ctx: _,
+		memory: _,
+		msg_ptr: u32,
+		msg_len: u32,
+	) -> Result<ReturnCode, TrapReason> {
+		use crate::xcm::Xcm;
+		use frame_support::dispatch::{extract_actual_weight, DispatchInfo};
+		ensure!(<E::T as Config>::Xcm::ENABLED, Error::<E::T>::XcmDisabled);
+		ctx.charge_gas(RuntimeCosts::CopyFromContract(msg_len))?;
+		let message = ctx.read_sandbox_memory(memory, msg_ptr, msg_len)?;
+		let weight_limit = <E::T as Config>::Xcm::weigh_execute(&message)?;
+		let charged = ctx.charge_gas(RuntimeCosts::XcmExecute(weight_limit))?;
+		let result = ctx.ext.xcm_execute(&message, weight_limit);
+		let actual_weight = extract_actual_weight(
+			&result,
+			&DispatchInfo { weight: weight_limit, ..Default::default() },
+		);
+		ctx.adjust_gas(charged, RuntimeCosts::XcmExecute(actual_weight));
+		match result {
+			Ok(_) => Ok(ReturnCode::Success),
+			Err(_) => Ok(ReturnCode::XcmExecutionFailed),
+		}
+	}
+
+	/// Send an XCM message to another chain, using the contract's address as the origin.
+	///
+	/// The destination and message are passed as SCALE encoded bytes and decoded by the
+	/// runtime's implementation of [`crate::xcm::Xcm`]. The weight of sending the message is
+	/// charged from the gas meter. Delivery fees are paid by the contract.
+	///
+	/// # Parameters
+	///
+	/// - `dest_ptr`: the pointer into the linear memory where the encoded destination is placed.
+	/// - `dest_len`: the length of the encoded destination in bytes.
+	/// - `msg_ptr`: the pointer into the linear memory where the encoded message is placed.
+	/// - `msg_len`: the length of the encoded message in bytes.
+	/// - `output_ptr`: the pointer into the linear memory where the hash of the sent message is
+	///   placed. The buffer should be 32 bytes.
+	///
+	/// # Return Value
+	///
+	/// Returns `ReturnCode::Success` when the message was sent and writes its hash to
+	/// `output_ptr`. Returns `ReturnCode::XcmSendFailed` if the message could not be sent.
+	///
+	/// Traps with [`Error::XcmDisabled`] if the runtime does not support XCM for contracts and
+	/// with the error returned by the runtime if the message could not be weighed.
+	#[unstable]
+	#[prefixed_alias]
+	fn xcm_send(
+		ctx: _,
+		memory: _,
+		dest_ptr: u32,
+		dest_len: u32,
+		msg_ptr: u32,
+		msg_len: u32,
+		output_ptr: u32,
+	) -> Result<ReturnCode, TrapReason> {
+		use crate::xcm::Xcm;
+		ensure!(<E::T as Config>::Xcm::ENABLED, Error::<E::T>::XcmDisabled);
+		ctx.charge_gas(RuntimeCosts::CopyFromContract(dest_len.saturating_add(msg_len)))?;
+		let dest = ctx.read_sandbox_memory(memory, dest_ptr, dest_len)?;
+		let message = ctx.read_sandbox_memory(memory, msg_ptr, msg_len)?;
+		let weight = <E::T as Config>::Xcm::weigh_send(&dest, &message)?;
+		ctx.charge_gas(RuntimeCosts::XcmSend(weight))?;
+		match ctx.ext.xcm_send(&dest, &message) {
+			Ok(hash) => {
+				ctx.write_sandbox_memory(memory, output_ptr, hash.as_ref())?;
+				Ok(ReturnCode::Success)
+			},
+			Err(_) => Ok(ReturnCode::XcmSendFailed),
+		}
+	}
+
+	/// Stores the balance of an account in the given asset into the supplied buffer.
+	///
+	/// The asset is passed as SCALE encoded bytes and decoded by the runtime's implementation
+	/// of [`crate::assets::Assets`]. The balance is written to `out_ptr` encoded the same way.
+	/// `out_len_ptr` must point to a u32 value that describes the available space at
+	/// `out_ptr`. This call overwrites it with the size of the value.
+	///
+	/// # Parameters
+	///
+	/// - `asset_ptr`: the pointer into the linear memory where the encoded asset is placed.
+	/// - `asset_len`: the length of the encoded asset in bytes.
+	/// - `account_ptr`: a pointer to the address of the account whose balance is queried.
+	/// - `out_ptr`: pointer to the linear memory where the balance is written to.
+	/// - `out_len_ptr`: in-out pointer into linear memory where the buffer length is read from and
+	///   the value length is written to.
+	///
+	/// Traps with [`Error::AssetsDisabled`] if the runtime does not support assets for contracts
+	/// and with the error returned by the runtime if the asset could not be decoded.
+	#[unstable]
+	#[prefixed_alias]
+	fn asset_balance_of(
+		ctx: _,
+		memory: _,
+		asset_ptr: u32,
+		asset_len: u32,
+		account_ptr: u32,
+		out_ptr: u32,
+		out_len_ptr: u32,
+	) -> Result<(), TrapReason> {
+		use crate::assets::Assets;
+		ensure!(<E::T as Config>::Assets::ENABLED, Error::<E::T>::AssetsDisabled);
+		ctx.charge_gas(RuntimeCosts::CopyFromContract(asset_len))?;
+		ctx.charge_gas(RuntimeCosts::AssetQuery(<E::T as Config>::Assets::weigh_query()))?;
+		let asset = ctx.read_sandbox_memory(memory, asset_ptr, asset_len)?;
+		let who: <<E as Ext>::T as frame_system::Config>::AccountId =
+			ctx.read_sandbox_memory_as(memory, account_ptr)?;
+		let balance = <E::T as Config>::Assets::balance_of(&asset, &who)?;
+		Ok(ctx.write_sandbox_output(memory, out_ptr, out_len_ptr, &balance, false, |len| {
+			Some(RuntimeCosts::CopyToContract(len))
+		})?)
+	}
+
+	/// Stores the total supply of an asset into the supplied buffer.
+	///
+	/// The asset and the total supply are encoded as for [`Self::asset_balance_of`].
+	/// `out_len_ptr` must point to a u32 value that describes the available space at
+	/// `out_ptr`. This call overwrites it with the size of the value.
+	///
+	/// # Parameters
+	///
+	/// - `asset_ptr`: the pointer into the linear memory where the encoded asset is placed.
+	/// - `asset_len`: the length of the encoded asset in bytes.
+	/// - `out_ptr`: pointer to the linear memory where the total supply is written to.
+	/// - `out_len_ptr`: in-out pointer into linear memory where the buffer length is read from and
+	///   the value length is written to.
+	///
+	/// Traps with [`Error::AssetsDisabled`] if the runtime does not support assets for contracts
+	/// and with the error returned by the runtime if the asset could not be decoded.
+	#[unstable]
+	#[prefixed_alias]
+	fn asset_total_supply(
+		ctx: _,
+		memory: _,
+		asset_ptr: u32,
+		asset_len: u32,
+		out_ptr: u32,
+		out_len_ptr: u32,
+	) -> Result<(), TrapReason> {
+		use crate::assets::Assets;
+		ensure!(<E::T as Config>::Assets::ENABLED, Error::<E::T>::AssetsDisabled);
+		ctx.charge_gas(RuntimeCosts::CopyFromContract(asset_len))?;
+		ctx.charge_gas(RuntimeCosts::AssetQuery(<E::T as Config>::Assets::weigh_query()))?;
+		let asset = ctx.read_sandbox_memory(memory, asset_ptr, asset_len)?;
+		let supply = <E::T as Config>::Assets::total_supply(&asset)?;
+		Ok(ctx.write_sandbox_output(memory, out_ptr, out_len_ptr, &supply, false, |len| {
+			Some(RuntimeCosts::CopyToContract(len))
+		})?)
+	}
+
+	/// Transfer some amount of an asset from the contract to another account.
+	///
+	/// The asset and the amount are passed as SCALE encoded bytes and decoded by the runtime's
+	/// implementation of [`crate::assets::Assets`]. The contract's account is kept alive.
+	///
+	/// # Parameters
+	///
+	/// - `asset_ptr`: the pointer into the linear memory where the encoded asset is placed.
+	/// - `asset_len`: the length of the encoded asset in bytes.
+	/// - `account_ptr`: a pointer to the address of the beneficiary account.
+	/// - `amount_ptr`: the pointer into the linear memory where the encoded amount is placed.
+	/// - `amount_len`: the length of the encoded amount in bytes.
+	///
+	/// # Errors
+	///
+	/// - `ReturnCode::TransferFailed`
+	///
+	/// Traps with [`Error::AssetsDisabled`] if the runtime does not support assets for contracts.
+	#[unstable]
+	#[prefixed_alias]
+	fn asset_transfer(
+		ctx: _,
+		memory: _,
+		asset_ptr: u32,
+		asset_len: u32,
+		account_ptr: u32,
+		amount_ptr: u32,
+		amount_len: u32,
+	) -> Result<ReturnCode, TrapReason> {
+		use crate::assets::Assets;
+		ensure!(<E::T as Config>::Assets::ENABLED, Error::<E::T>::AssetsDisabled);
+		ctx.charge_gas(RuntimeCosts::CopyFromContract(asset_len.saturating_add(amount_len)))?;
+		ctx.charge_gas(RuntimeCosts::AssetTransfer(<E::T as Config>::Assets::weigh_transfer()))?;
+		let asset = ctx.read_sandbox_memory(memory, asset_ptr, asset_len)?;
+		let to: <<E as Ext>::T as frame_system::Config>::AccountId =
+			ctx.read_sandbox_memory_as(memory, account_ptr)?;
+		let amount = ctx.read_sandbox_memory(memory, amount_ptr, amount_len)?;
+		match ctx.ext.asset_transfer(&asset, &to, &amount) {
+			Ok(()) => Ok(ReturnCode::Success),
+			Err(_) => Ok(ReturnCode::TransferFailed),
+		}
+	}
+
 	/// Recovers the ECDSA public key from the given message hash and signature.
 	///
 	/// Writes the public key into the given output buffer.
@@ -2563,6 +3354,7 @@ pub mod env {
 	///
 	/// Returns `0` when there is no reentrancy.
 	#[unstable]
+	#[prefixed_alias]
 	fn reentrance_count(ctx: _, memory: _) -> Result<u32, TrapReason> {
 		ctx.charge_gas(RuntimeCosts::ReentrantCount)?;
 		Ok(ctx.ext.reentrance_count())
@@ -2579,6 +3371,7 @@ pub mod env {
 	///
 	/// Returns `0` when the contract does not exist on the call stack.
 	#[unstable]
+	#[prefixed_alias]
 	fn account_reentrance_count(ctx: _, memory: _, account_ptr: u32) -> Result<u32, TrapReason> {
 		ctx.charge_gas(RuntimeCosts::AccountEntranceCount)?;
 		let account_id: <<E as Ext>::T as frame_system::Config>::AccountId =
@@ -2589,10 +3382,93 @@ pub mod env {
 	/// Returns a nonce that is unique per contract instantiation.
 	///
 	/// The nonce is incremented for each succesful contract instantiation. This is a
-	/// sensible default salt for contract instantiations.
+	/// sensible default salt for contract instantiations: A contract that instantiates
+	/// children can use it to derive unique salts deterministically.
 	#[unstable]
+	#[prefixed_alias]
 	fn instantiation_nonce(ctx: _, _memory: _) -> Result<u64, TrapReason> {
 		ctx.charge_gas(RuntimeCosts::InstantationNonce)?;
 		Ok(ctx.ext.nonce())
 	}
+
+	/// Stores the amount of the storage deposit limit that is left for the current call into
+	/// the supplied buffer.
+	///
+	/// Storage changes made so far by this call and its finished sub calls are taken into account.
+	/// Contracts can use this to decide between code paths that differ in their storage usage.
+	///
+	/// The value is stored to linear memory at the address pointed to by `out_ptr`.
+	/// `out_len_ptr` must point to a `u32` value that describes the available space at
+	/// `out_ptr`. This call overwrites it with the size of the value. If the available
+	/// space at `out_ptr` is less than the size of the value a trap is triggered.
+	///
+	/// The data is encoded as `T::Balance`.
+	#[unstable]
+	#[prefixed_alias]
+	fn storage_deposit_limit_left(
+		ctx: _,
+		memory: _,
+		out_ptr: u32,
+		out_len_ptr: u32,
+	) -> Result<(), TrapReason> {
+		ctx.charge_gas(RuntimeCosts::StorageDepositLimitLeft)?;
+		let limit_left = ctx.ext.storage_deposit_limit_left();
+		Ok(ctx.write_sandbox_output(
+			memory,
+			out_ptr,
+			out_len_ptr,
+			&limit_left.encode(),
+			false,
+			already_charged,
+		)?)
+	}
+
+	/// Sets whether the current contract denies to be reentered.
+	///
+	/// Any non-zero value for `deny` rejects calls into this contract while it is already on the
+	/// call stack. This applies even if the caller allows reentry. The setting is persisted with
+	/// the contract and stays in effect until it is changed again.
+	#[unstable]
+	#[prefixed_alias]
+	fn set_deny_reentry(ctx: _, _memory: _, deny: u32) -> Result<(), TrapReason> {
+		ctx.charge_gas(RuntimeCosts::SetDenyReentry)?;
+		ctx.ext.set_deny_reentry(deny != 0);
+		Ok(())
+	}
+
+	/// Locks a code hash as a delegate dependency of the calling contract.
+	///
+	/// The code can't be removed through `remove_code` while it is locked. This allows a
+	/// contract to delegate to code it does not own without it being removed out from under it.
+	/// Traps if the contract already locks the maximum number of dependencies, already locks this
+	/// code hash, tries to lock its own code hash or if there is no code under this hash.
+	///
+	/// # Parameters
+	///
+	/// - `code_hash_ptr`: A pointer to the code hash to lock.
+	#[unstable]
+	#[prefixed_alias]
+	fn lock_delegate_dependency(ctx: _, memory: _, code_hash_ptr: u32) -> Result<(), TrapReason> {
+		ctx.charge_gas(RuntimeCosts::LockDelegateDependency)?;
+		let code_hash = ctx.read_sandbox_memory_as(memory, code_hash_ptr)?;
+		ctx.ext.lock_delegate_dependency(code_hash)?;
+		Ok(())
+	}
+
+	/// Releases a delegate dependency of the calling contract.
+	///
+	/// This undoes [`lock_delegate_dependency()`][`Self::lock_delegate_dependency`]. Traps if the
+	/// contract does not lock this code hash.
+	///
+	/// # Parameters
+	///
+	/// - `code_hash_ptr`: A pointer to the code hash to unlock.
+	#[unstable]
+	#[prefixed_alias]
+	fn unlock_delegate_dependency(ctx: _, memory: _, code_hash_ptr: u32) -> Result<(), TrapReason> {
+		ctx.charge_gas(RuntimeCosts::UnlockDelegateDependency)?;
+		let code_hash = ctx.read_sandbox_memory_as(memory, code_hash_ptr)?;
+		ctx.ext.unlock_delegate_dependency(&code_hash)?;
+		Ok(())
+	}
 }
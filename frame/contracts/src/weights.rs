@@ -49,9 +49,10 @@ use sp_std::marker::PhantomData;
 /// Weight functions needed for pallet_contracts.
 pub trait WeightInfo {
 	fn on_process_deletion_queue_batch() -> Weight;
+	fn migrate() -> Weight;
+	fn v11_migration_step() -> Weight;
 	fn on_initialize_per_trie_key(k: u32, ) -> Weight;
 	fn on_initialize_per_queue_item(q: u32, ) -> Weight;
-	fn reinstrument(c: u32, ) -> Weight;
 	fn call_with_code_per_byte(c: u32, ) -> Weight;
 	fn instantiate_with_code(c: u32, i: u32, s: u32, ) -> Weight;
 	fn instantiate(i: u32, s: u32, ) -> Weight;
@@ -59,6 +60,12 @@ pub trait WeightInfo {
 	fn upload_code(c: u32, ) -> Weight;
 	fn remove_code() -> Weight;
 	fn set_code() -> Weight;
+	fn set_call_filter() -> Weight;
+	fn pause_contract() -> Weight;
+	fn unpause_contract() -> Weight;
+	fn set_schedule() -> Weight;
+	fn force_remove_code() -> Weight;
+	fn set_code_metadata(l: u32, ) -> Weight;
 	fn seal_caller(r: u32, ) -> Weight;
 	fn seal_is_contract(r: u32, ) -> Weight;
 	fn seal_code_hash(r: u32, ) -> Weight;
@@ -72,7 +79,6 @@ pub trait WeightInfo {
 	fn seal_block_number(r: u32, ) -> Weight;
 	fn seal_now(r: u32, ) -> Weight;
 	fn seal_weight_to_fee(r: u32, ) -> Weight;
-	fn seal_gas(r: u32, ) -> Weight;
 	fn seal_input(r: u32, ) -> Weight;
 	fn seal_input_per_kb(n: u32, ) -> Weight;
 	fn seal_return(r: u32, ) -> Weight;
@@ -113,6 +119,10 @@ pub trait WeightInfo {
 	fn seal_reentrance_count(r: u32, ) -> Weight;
 	fn seal_account_reentrance_count(r: u32, ) -> Weight;
 	fn seal_instantiation_nonce(r: u32, ) -> Weight;
+	fn seal_storage_deposit_limit_left(r: u32, ) -> Weight;
+	fn seal_set_deny_reentry(r: u32, ) -> Weight;
+	fn seal_lock_delegate_dependency(r: u32, ) -> Weight;
+	fn seal_unlock_delegate_dependency(r: u32, ) -> Weight;
 	fn instr_i64const(r: u32, ) -> Weight;
 	fn instr_i64load(r: u32, ) -> Weight;
 	fn instr_i64store(r: u32, ) -> Weight;
@@ -176,6 +186,20 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 		Weight::from_ref_time(3_433_000)
 			.saturating_add(T::DbWeight::get().reads(1))
 	}
+	// Storage: Contracts MigrationInProgress (r:1 w:0)
+	fn migrate() -> Weight {
+		// Minimum execution time: 2_791 nanoseconds.
+		Weight::from_ref_time(2_939_000)
+			.saturating_add(T::DbWeight::get().reads(1))
+	}
+	// Storage: Contracts ContractInfoOf (r:2 w:1)
+	// Storage: System Account (r:2 w:2)
+	fn v11_migration_step() -> Weight {
+		// Minimum execution time: 41_217 nanoseconds.
+		Weight::from_ref_time(42_034_000)
+			.saturating_add(T::DbWeight::get().reads(4))
+			.saturating_add(T::DbWeight::get().writes(3))
+	}
 	// Storage: Skipped Metadata (r:0 w:0)
 	/// The range of component `k` is `[0, 1024]`.
 	fn on_initialize_per_trie_key(k: u32, ) -> Weight {
@@ -197,17 +221,6 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(1))
 			.saturating_add(T::DbWeight::get().writes(1))
 	}
-	// Storage: Contracts PristineCode (r:1 w:0)
-	// Storage: Contracts CodeStorage (r:0 w:1)
-	/// The range of component `c` is `[0, 64226]`.
-	fn reinstrument(c: u32, ) -> Weight {
-		// Minimum execution time: 29_801 nanoseconds.
-		Weight::from_ref_time(27_932_706)
-			// Standard Error: 49
-			.saturating_add(Weight::from_ref_time(50_532).saturating_mul(c.into()))
-			.saturating_add(T::DbWeight::get().reads(1))
-			.saturating_add(T::DbWeight::get().writes(1))
-	}
 	// Storage: Contracts ContractInfoOf (r:1 w:1)
 	// Storage: Contracts CodeStorage (r:1 w:0)
 	// Storage: Timestamp Now (r:1 w:0)
@@ -307,6 +320,63 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(6))
 			.saturating_add(T::DbWeight::get().writes(6))
 	}
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: System EventTopics (r:1 w:1)
+	fn set_call_filter() -> Weight {
+		// Minimum execution time: 21_704 nanoseconds.
+		Weight::from_ref_time(22_310_000)
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().writes(2))
+	}
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: System EventTopics (r:1 w:1)
+	fn pause_contract() -> Weight {
+		// Minimum execution time: 20_114 nanoseconds.
+		Weight::from_ref_time(20_689_000)
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().writes(2))
+	}
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: System EventTopics (r:1 w:1)
+	fn unpause_contract() -> Weight {
+		// Minimum execution time: 20_097 nanoseconds.
+		Weight::from_ref_time(20_652_000)
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().writes(2))
+	}
+	// Storage: Contracts MigrationInProgress (r:1 w:0)
+	// Storage: Contracts ScheduleOverride (r:1 w:1)
+	fn set_schedule() -> Weight {
+		// Minimum execution time: 24_513 nanoseconds.
+		Weight::from_ref_time(25_168_000)
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+	// Storage: Contracts MigrationInProgress (r:1 w:0)
+	// Storage: Contracts CodeInfoOf (r:1 w:1)
+	// Storage: System EventTopics (r:1 w:1)
+	// Storage: Contracts TombstonedCode (r:0 w:1)
+	// Storage: Contracts CodeStatsOf (r:0 w:1)
+	// Storage: Contracts PristineCode (r:0 w:1)
+	fn force_remove_code() -> Weight {
+		// Minimum execution time: 42_310 nanoseconds.
+		Weight::from_ref_time(43_026_000)
+			.saturating_add(T::DbWeight::get().reads(3))
+			.saturating_add(T::DbWeight::get().writes(5))
+	}
+	// Storage: Contracts MigrationInProgress (r:1 w:0)
+	// Storage: Contracts CodeInfoOf (r:1 w:0)
+	// Storage: Contracts CodeMetadataOf (r:1 w:1)
+	// Storage: System EventTopics (r:1 w:1)
+	/// The range of component `l` is `[0, 128]`.
+	fn set_code_metadata(l: u32, ) -> Weight {
+		// Minimum execution time: 38_164 nanoseconds.
+		Weight::from_ref_time(39_208_731)
+			// Standard Error: 96
+			.saturating_add(Weight::from_ref_time(1_203).saturating_mul(l.into()))
+			.saturating_add(T::DbWeight::get().reads(4))
+			.saturating_add(T::DbWeight::get().writes(2))
+	}
 	// Storage: System Account (r:1 w:0)
 	// Storage: Contracts ContractInfoOf (r:1 w:1)
 	// Storage: Contracts CodeStorage (r:1 w:0)
@@ -498,20 +568,6 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 	// Storage: Timestamp Now (r:1 w:0)
 	// Storage: System EventTopics (r:2 w:2)
 	/// The range of component `r` is `[0, 20]`.
-	fn seal_gas(r: u32, ) -> Weight {
-		// Minimum execution time: 145_105 nanoseconds.
-		Weight::from_ref_time(152_388_934)
-			// Standard Error: 29_988
-			.saturating_add(Weight::from_ref_time(7_761_947).saturating_mul(r.into()))
-			.saturating_add(T::DbWeight::get().reads(6))
-			.saturating_add(T::DbWeight::get().writes(3))
-	}
-	// Storage: System Account (r:1 w:0)
-	// Storage: Contracts ContractInfoOf (r:1 w:1)
-	// Storage: Contracts CodeStorage (r:1 w:0)
-	// Storage: Timestamp Now (r:1 w:0)
-	// Storage: System EventTopics (r:2 w:2)
-	/// The range of component `r` is `[0, 20]`.
 	fn seal_input(r: u32, ) -> Weight {
 		// Minimum execution time: 289_319 nanoseconds.
 		Weight::from_ref_time(298_390_911)
@@ -1079,6 +1135,68 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(7))
 			.saturating_add(T::DbWeight::get().writes(4))
 	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: System EventTopics (r:2 w:2)
+	/// The range of component `r` is `[0, 20]`.
+	fn seal_storage_deposit_limit_left(r: u32, ) -> Weight {
+		// Minimum execution time: 295_271 nanoseconds.
+		Weight::from_ref_time(301_529_044)
+			// Standard Error: 27_114
+			.saturating_add(Weight::from_ref_time(17_842_310).saturating_mul(r.into()))
+			.saturating_add(T::DbWeight::get().reads(6))
+			.saturating_add(T::DbWeight::get().writes(3))
+	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: System EventTopics (r:2 w:2)
+	/// The range of component `r` is `[0, 20]`.
+	fn seal_set_deny_reentry(r: u32, ) -> Weight {
+		// Minimum execution time: 293_806 nanoseconds.
+		Weight::from_ref_time(299_817_361)
+			// Standard Error: 25_419
+			.saturating_add(Weight::from_ref_time(16_951_128).saturating_mul(r.into()))
+			.saturating_add(T::DbWeight::get().reads(6))
+			.saturating_add(T::DbWeight::get().writes(3))
+	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeInfoOf (r:33 w:32)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: System EventTopics (r:2 w:2)
+	/// The range of component `r` is `[0, 32]`.
+	fn seal_lock_delegate_dependency(r: u32, ) -> Weight {
+		// Minimum execution time: 298_412 nanoseconds.
+		Weight::from_ref_time(305_184_211)
+			// Standard Error: 21_907
+			.saturating_add(Weight::from_ref_time(23_478_550).saturating_mul(r.into()))
+			.saturating_add(T::DbWeight::get().reads(7))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(r.into())))
+			.saturating_add(T::DbWeight::get().writes(3))
+			.saturating_add(T::DbWeight::get().writes((1_u64).saturating_mul(r.into())))
+	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeInfoOf (r:33 w:32)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: System EventTopics (r:2 w:2)
+	/// The range of component `r` is `[0, 32]`.
+	fn seal_unlock_delegate_dependency(r: u32, ) -> Weight {
+		// Minimum execution time: 297_636 nanoseconds.
+		Weight::from_ref_time(304_702_930)
+			// Standard Error: 20_144
+			.saturating_add(Weight::from_ref_time(22_816_417).saturating_mul(r.into()))
+			.saturating_add(T::DbWeight::get().reads(7))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(r.into())))
+			.saturating_add(T::DbWeight::get().writes(3))
+			.saturating_add(T::DbWeight::get().writes((1_u64).saturating_mul(r.into())))
+	}
 	/// The range of component `r` is `[0, 50]`.
 	fn instr_i64const(r: u32, ) -> Weight {
 		// Minimum execution time: 805 nanoseconds.
@@ -1453,6 +1571,20 @@ impl WeightInfo for () {
 		Weight::from_ref_time(3_433_000)
 			.saturating_add(RocksDbWeight::get().reads(1))
 	}
+	// Storage: Contracts MigrationInProgress (r:1 w:0)
+	fn migrate() -> Weight {
+		// Minimum execution time: 2_791 nanoseconds.
+		Weight::from_ref_time(2_939_000)
+			.saturating_add(RocksDbWeight::get().reads(1))
+	}
+	// Storage: Contracts ContractInfoOf (r:2 w:1)
+	// Storage: System Account (r:2 w:2)
+	fn v11_migration_step() -> Weight {
+		// Minimum execution time: 41_217 nanoseconds.
+		Weight::from_ref_time(42_034_000)
+			.saturating_add(RocksDbWeight::get().reads(4))
+			.saturating_add(RocksDbWeight::get().writes(3))
+	}
 	// Storage: Skipped Metadata (r:0 w:0)
 	/// The range of component `k` is `[0, 1024]`.
 	fn on_initialize_per_trie_key(k: u32, ) -> Weight {
@@ -1474,17 +1606,6 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(1))
 			.saturating_add(RocksDbWeight::get().writes(1))
 	}
-	// Storage: Contracts PristineCode (r:1 w:0)
-	// Storage: Contracts CodeStorage (r:0 w:1)
-	/// The range of component `c` is `[0, 64226]`.
-	fn reinstrument(c: u32, ) -> Weight {
-		// Minimum execution time: 29_801 nanoseconds.
-		Weight::from_ref_time(27_932_706)
-			// Standard Error: 49
-			.saturating_add(Weight::from_ref_time(50_532).saturating_mul(c.into()))
-			.saturating_add(RocksDbWeight::get().reads(1))
-			.saturating_add(RocksDbWeight::get().writes(1))
-	}
 	// Storage: Contracts ContractInfoOf (r:1 w:1)
 	// Storage: Contracts CodeStorage (r:1 w:0)
 	// Storage: Timestamp Now (r:1 w:0)
@@ -1584,6 +1705,63 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(6))
 			.saturating_add(RocksDbWeight::get().writes(6))
 	}
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: System EventTopics (r:1 w:1)
+	fn set_call_filter() -> Weight {
+		// Minimum execution time: 21_704 nanoseconds.
+		Weight::from_ref_time(22_310_000)
+			.saturating_add(RocksDbWeight::get().reads(2))
+			.saturating_add(RocksDbWeight::get().writes(2))
+	}
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: System EventTopics (r:1 w:1)
+	fn pause_contract() -> Weight {
+		// Minimum execution time: 20_114 nanoseconds.
+		Weight::from_ref_time(20_689_000)
+			.saturating_add(RocksDbWeight::get().reads(2))
+			.saturating_add(RocksDbWeight::get().writes(2))
+	}
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: System EventTopics (r:1 w:1)
+	fn unpause_contract() -> Weight {
+		// Minimum execution time: 20_097 nanoseconds.
+		Weight::from_ref_time(20_652_000)
+			.saturating_add(RocksDbWeight::get().reads(2))
+			.saturating_add(RocksDbWeight::get().writes(2))
+	}
+	// Storage: Contracts MigrationInProgress (r:1 w:0)
+	// Storage: Contracts ScheduleOverride (r:1 w:1)
+	fn set_schedule() -> Weight {
+		// Minimum execution time: 24_513 nanoseconds.
+		Weight::from_ref_time(25_168_000)
+			.saturating_add(RocksDbWeight::get().reads(2))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+	// Storage: Contracts MigrationInProgress (r:1 w:0)
+	// Storage: Contracts CodeInfoOf (r:1 w:1)
+	// Storage: System EventTopics (r:1 w:1)
+	// Storage: Contracts TombstonedCode (r:0 w:1)
+	// Storage: Contracts CodeStatsOf (r:0 w:1)
+	// Storage: Contracts PristineCode (r:0 w:1)
+	fn force_remove_code() -> Weight {
+		// Minimum execution time: 42_310 nanoseconds.
+		Weight::from_ref_time(43_026_000)
+			.saturating_add(RocksDbWeight::get().reads(3))
+			.saturating_add(RocksDbWeight::get().writes(5))
+	}
+	// Storage: Contracts MigrationInProgress (r:1 w:0)
+	// Storage: Contracts CodeInfoOf (r:1 w:0)
+	// Storage: Contracts CodeMetadataOf (r:1 w:1)
+	// Storage: System EventTopics (r:1 w:1)
+	/// The range of component `l` is `[0, 128]`.
+	fn set_code_metadata(l: u32, ) -> Weight {
+		// Minimum execution time: 38_164 nanoseconds.
+		Weight::from_ref_time(39_208_731)
+			// Standard Error: 96
+			.saturating_add(Weight::from_ref_time(1_203).saturating_mul(l.into()))
+			.saturating_add(RocksDbWeight::get().reads(4))
+			.saturating_add(RocksDbWeight::get().writes(2))
+	}
 	// Storage: System Account (r:1 w:0)
 	// Storage: Contracts ContractInfoOf (r:1 w:1)
 	// Storage: Contracts CodeStorage (r:1 w:0)
@@ -1775,20 +1953,6 @@ impl WeightInfo for () {
 	// Storage: Timestamp Now (r:1 w:0)
 	// Storage: System EventTopics (r:2 w:2)
 	/// The range of component `r` is `[0, 20]`.
-	fn seal_gas(r: u32, ) -> Weight {
-		// Minimum execution time: 145_105 nanoseconds.
-		Weight::from_ref_time(152_388_934)
-			// Standard Error: 29_988
-			.saturating_add(Weight::from_ref_time(7_761_947).saturating_mul(r.into()))
-			.saturating_add(RocksDbWeight::get().reads(6))
-			.saturating_add(RocksDbWeight::get().writes(3))
-	}
-	// Storage: System Account (r:1 w:0)
-	// Storage: Contracts ContractInfoOf (r:1 w:1)
-	// Storage: Contracts CodeStorage (r:1 w:0)
-	// Storage: Timestamp Now (r:1 w:0)
-	// Storage: System EventTopics (r:2 w:2)
-	/// The range of component `r` is `[0, 20]`.
 	fn seal_input(r: u32, ) -> Weight {
 		// Minimum execution time: 289_319 nanoseconds.
 		Weight::from_ref_time(298_390_911)
@@ -2356,6 +2520,68 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(7))
 			.saturating_add(RocksDbWeight::get().writes(4))
 	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: System EventTopics (r:2 w:2)
+	/// The range of component `r` is `[0, 20]`.
+	fn seal_storage_deposit_limit_left(r: u32, ) -> Weight {
+		// Minimum execution time: 295_271 nanoseconds.
+		Weight::from_ref_time(301_529_044)
+			// Standard Error: 27_114
+			.saturating_add(Weight::from_ref_time(17_842_310).saturating_mul(r.into()))
+			.saturating_add(RocksDbWeight::get().reads(6))
+			.saturating_add(RocksDbWeight::get().writes(3))
+	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: System EventTopics (r:2 w:2)
+	/// The range of component `r` is `[0, 20]`.
+	fn seal_set_deny_reentry(r: u32, ) -> Weight {
+		// Minimum execution time: 293_806 nanoseconds.
+		Weight::from_ref_time(299_817_361)
+			// Standard Error: 25_419
+			.saturating_add(Weight::from_ref_time(16_951_128).saturating_mul(r.into()))
+			.saturating_add(RocksDbWeight::get().reads(6))
+			.saturating_add(RocksDbWeight::get().writes(3))
+	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeInfoOf (r:33 w:32)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: System EventTopics (r:2 w:2)
+	/// The range of component `r` is `[0, 32]`.
+	fn seal_lock_delegate_dependency(r: u32, ) -> Weight {
+		// Minimum execution time: 298_412 nanoseconds.
+		Weight::from_ref_time(305_184_211)
+			// Standard Error: 21_907
+			.saturating_add(Weight::from_ref_time(23_478_550).saturating_mul(r.into()))
+			.saturating_add(RocksDbWeight::get().reads(7))
+			.saturating_add(RocksDbWeight::get().reads((1_u64).saturating_mul(r.into())))
+			.saturating_add(RocksDbWeight::get().writes(3))
+			.saturating_add(RocksDbWeight::get().writes((1_u64).saturating_mul(r.into())))
+	}
+	// Storage: System Account (r:1 w:0)
+	// Storage: Contracts ContractInfoOf (r:1 w:1)
+	// Storage: Contracts CodeInfoOf (r:33 w:32)
+	// Storage: Contracts CodeStorage (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: System EventTopics (r:2 w:2)
+	/// The range of component `r` is `[0, 32]`.
+	fn seal_unlock_delegate_dependency(r: u32, ) -> Weight {
+		// Minimum execution time: 297_636 nanoseconds.
+		Weight::from_ref_time(304_702_930)
+			// Standard Error: 20_144
+			.saturating_add(Weight::from_ref_time(22_816_417).saturating_mul(r.into()))
+			.saturating_add(RocksDbWeight::get().reads(7))
+			.saturating_add(RocksDbWeight::get().reads((1_u64).saturating_mul(r.into())))
+			.saturating_add(RocksDbWeight::get().writes(3))
+			.saturating_add(RocksDbWeight::get().writes((1_u64).saturating_mul(r.into())))
+	}
 	/// The range of component `r` is `[0, 50]`.
 	fn instr_i64const(r: u32, ) -> Weight {
 		// Minimum execution time: 805 nanoseconds.
@@ -0,0 +1,103 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A mechanism that allows contracts to originate cross-chain messages.
+//!
+//! Contracts can execute and send XCM messages through the `seal_xcm_execute` and
+//! `seal_xcm_send` host functions. This pallet does not depend on XCM itself. Instead, the
+//! runtime author implements the [`Xcm`] trait, usually by forwarding to the XCM executor and
+//! router of the chain, and declares it in this pallet's [configuration Trait](crate::Config).
+//! Messages and destinations are handed over as SCALE encoded bytes exactly as the contract
+//! supplied them and are decoded by the implementation.
+//!
+//! The XCM origin of a message must be derived from the account of the contract that is
+//! executing or sending it. This pallet always passes this account as the `origin`.
+//!
+//! There is an implementation on `()` which can be used to signal that XCM is not available.
+//! Calling one of the XCM host functions traps the contract with [`Error::XcmDisabled`] in this
+//! case.
+//!
+//! # Security
+//!
+//! The implementation is responsible for weighing messages correctly. The weight returned by
+//! [`Xcm::weigh_execute`] and [`Xcm::weigh_send`] is charged from the gas meter of the contract
+//! **before** the message is executed or sent.
+
+use crate::{Config, Error};
+use frame_support::{dispatch::DispatchResultWithPostInfo, weights::Weight};
+use sp_runtime::DispatchError;
+
+/// The hash that identifies a sent XCM message.
+pub type XcmHash = [u8; 32];
+
+/// Allows contracts to execute and send XCM messages.
+///
+/// Consult the [module documentation](self) for a general explanation.
+pub trait Xcm<T: Config> {
+	/// Whether contracts are allowed to use XCM at all.
+	const ENABLED: bool = true;
+
+	/// The maximum weight that executing the encoded `message` locally can consume.
+	fn weigh_execute(message: &[u8]) -> Result<Weight, DispatchError>;
+
+	/// Execute the encoded `message` locally with `origin` as its XCM origin.
+	///
+	/// No more than `weight_limit` must be consumed. The actual weight is reported through
+	/// the post dispatch info and the difference is refunded to the contract.
+	fn execute(
+		origin: &T::AccountId,
+		message: &[u8],
+		weight_limit: Weight,
+	) -> DispatchResultWithPostInfo;
+
+	/// The weight of sending the encoded `message` to the encoded `dest`.
+	fn weigh_send(dest: &[u8], message: &[u8]) -> Result<Weight, DispatchError>;
+
+	/// Send the encoded `message` to the encoded `dest` with `origin` as its XCM origin.
+	///
+	/// Any delivery fees are paid by `origin`. Returns the hash of the sent message.
+	fn send(origin: &T::AccountId, dest: &[u8], message: &[u8]) -> Result<XcmHash, DispatchError>;
+}
+
+/// Implementation that indicates that XCM is not available to contracts.
+impl<T: Config> Xcm<T> for () {
+	const ENABLED: bool = false;
+
+	fn weigh_execute(_message: &[u8]) -> Result<Weight, DispatchError> {
+		Err(Error::<T>::XcmDisabled.into())
+	}
+
+	fn execute(
+		_origin: &T::AccountId,
+		_message: &[u8],
+		_weight_limit: Weight,
+	) -> DispatchResultWithPostInfo {
+		Err(Error::<T>::XcmDisabled.into())
+	}
+
+	fn weigh_send(_dest: &[u8], _message: &[u8]) -> Result<Weight, DispatchError> {
+		Err(Error::<T>::XcmDisabled.into())
+	}
+
+	fn send(
+		_origin: &T::AccountId,
+		_dest: &[u8],
+		_message: &[u8],
+	) -> Result<XcmHash, DispatchError> {
+		Err(Error::<T>::XcmDisabled.into())
+	}
+}
@@ -0,0 +1,48 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runtime API definition for the election provider multi phase pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_npos_elections::ElectionScore;
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+	/// Runtime api for solution miners to read the current election round.
+	pub trait ElectionProviderMultiPhaseApi<AccountId, Voter>
+		where
+			AccountId: Codec,
+			Voter: Codec,
+	{
+		/// Returns the number of voters and targets in the snapshot, if there is one.
+		fn snapshot_size() -> Option<(u32, u32)>;
+
+		/// Returns up to `count` voters of the snapshot starting at index `start`.
+		fn snapshot_voters(start: u32, count: u32) -> Vec<Voter>;
+
+		/// Returns up to `count` targets of the snapshot starting at index `start`.
+		fn snapshot_targets(start: u32, count: u32) -> Vec<AccountId>;
+
+		/// Returns the number of targets to elect, if there is a snapshot.
+		fn desired_targets() -> Option<u32>;
+
+		/// Returns the score an untrusted solution must exceed, if any.
+		fn minimum_untrusted_score() -> Option<ElectionScore>;
+	}
+}
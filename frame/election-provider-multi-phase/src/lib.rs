@@ -873,6 +873,18 @@ pub mod pallet {
 			// `SignedMaxSubmissions` is a red flag that the developer does not understand how to
 			// configure this pallet.
 			assert!(T::SignedMaxSubmissions::get() >= T::SignedMaxRefunds::get());
+
+			// The snapshot is created in `on_initialize` of the block that opens the signed or
+			// unsigned phase. If it does not fit into a block, that block will be overweight.
+			let max_block = <T as frame_system::Config>::BlockWeights::get().max_block;
+			let snapshot_weight = Self::snapshot_weight();
+			assert!(
+				snapshot_weight.all_lte(max_block),
+				"worst case snapshot weight {:?} exceeds the maximum block weight {:?}; \
+				lower `MaxElectingVoters` or `MaxElectableTargets`",
+				snapshot_weight,
+				max_block,
+			);
 		}
 	}
 
@@ -1458,6 +1470,39 @@ impl<T: Config> Pallet<T> {
 		Ok(())
 	}
 
+	/// The worst case weight of [`create_snapshot`], i.e. of a snapshot with
+	/// [`Config::MaxElectingVoters`] voters and [`Config::MaxElectableTargets`] targets.
+	///
+	/// This includes the weight reported by the data provider for fetching the snapshot data.
+	pub fn snapshot_weight() -> Weight {
+		// The solution index types are only guaranteed to convert into `usize`.
+		let voters = T::MaxElectingVoters::get().saturated_into::<usize>().saturated_into::<u32>();
+		let targets =
+			T::MaxElectableTargets::get().saturated_into::<usize>().saturated_into::<u32>();
+
+		T::DataProvider::electing_voters_weight(voters)
+			.saturating_add(T::DataProvider::electable_targets_weight(targets))
+			.saturating_add(T::WeightInfo::create_snapshot_internal(voters, targets))
+	}
+
+	/// Returns up to `count` voters of the snapshot starting at index `start`.
+	///
+	/// Allows miners to fetch the snapshot in pages. Returns nothing if there is no snapshot.
+	pub fn snapshot_voters_paged(start: u32, count: u32) -> Vec<VoterOf<T>> {
+		Self::snapshot()
+			.map(|s| s.voters.into_iter().skip(start as usize).take(count as usize).collect())
+			.unwrap_or_default()
+	}
+
+	/// Returns up to `count` targets of the snapshot starting at index `start`.
+	///
+	/// Allows miners to fetch the snapshot in pages. Returns nothing if there is no snapshot.
+	pub fn snapshot_targets_paged(start: u32, count: u32) -> Vec<T::AccountId> {
+		Self::snapshot()
+			.map(|s| s.targets.into_iter().skip(start as usize).take(count as usize).collect())
+			.unwrap_or_default()
+	}
+
 	/// Register some amount of weight directly with the system pallet.
 	///
 	/// This is always mandatory weight.
@@ -2561,6 +2606,48 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn snapshot_weight_covers_max_snapshot() {
+		ExtBuilder::default().build_and_execute(|| {
+			let voters = <Runtime as Config>::MaxElectingVoters::get();
+			let targets = <Runtime as Config>::MaxElectableTargets::get() as u32;
+			let internal =
+				<Runtime as Config>::WeightInfo::create_snapshot_internal(voters, targets);
+
+			// the mock data provider does not report any weight of its own.
+			assert_eq!(MultiPhase::snapshot_weight(), internal);
+
+			// lowering the bounds lowers the worst case.
+			crate::mock::MaxElectingVoters::set(2);
+			assert!(MultiPhase::snapshot_weight().all_lte(internal));
+		})
+	}
+
+	#[test]
+	#[should_panic(expected = "exceeds the maximum block weight")]
+	fn integrity_test_rejects_snapshot_exceeding_block() {
+		crate::mock::MaxElectingVoters::set(u32::MAX);
+		<MultiPhase as frame_support::traits::IntegrityTest>::integrity_test();
+	}
+
+	#[test]
+	fn snapshot_can_be_paged() {
+		ExtBuilder::default().build_and_execute(|| {
+			assert!(MultiPhase::snapshot_voters_paged(0, 10).is_empty());
+			assert!(MultiPhase::snapshot_targets_paged(0, 10).is_empty());
+
+			roll_to_signed();
+			let snapshot = MultiPhase::snapshot().unwrap();
+			assert_eq!(snapshot.voters.len(), 8);
+			assert_eq!(snapshot.targets.len(), 4);
+
+			assert_eq!(MultiPhase::snapshot_voters_paged(0, 3), snapshot.voters[..3].to_vec());
+			assert_eq!(MultiPhase::snapshot_voters_paged(6, 3), snapshot.voters[6..].to_vec());
+			assert!(MultiPhase::snapshot_voters_paged(8, 3).is_empty());
+			assert_eq!(MultiPhase::snapshot_targets_paged(1, 2), snapshot.targets[1..3].to_vec());
+		})
+	}
+
 	#[test]
 	fn untrusted_score_verification_is_respected() {
 		ExtBuilder::default().build_and_execute(|| {
@@ -302,6 +302,24 @@ pub trait ElectionDataProvider {
 	/// appropriate weight at the end of execution with the system pallet directly.
 	fn electing_voters(maybe_max_len: Option<usize>) -> data_provider::Result<Vec<VoterOf<Self>>>;
 
+	/// The worst case weight of [`Self::electable_targets`] when returning at most `max_len`
+	/// targets.
+	///
+	/// Election providers can use this to check ahead of time that fetching the snapshot fits
+	/// into a block. Defaults to zero, i.e. no information.
+	fn electable_targets_weight(_max_len: u32) -> Weight {
+		Weight::zero()
+	}
+
+	/// The worst case weight of [`Self::electing_voters`] when returning at most `max_len`
+	/// voters.
+	///
+	/// Election providers can use this to check ahead of time that fetching the snapshot fits
+	/// into a block. Defaults to zero, i.e. no information.
+	fn electing_voters_weight(_max_len: u32) -> Weight {
+		Weight::zero()
+	}
+
 	/// The number of targets to elect.
 	///
 	/// This should be implemented as a self-weighing function. The implementor should register its
@@ -49,7 +49,7 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
-		Session: pallet_session::{Pallet, Call, Storage, Event, Config<T>},
+		Session: pallet_session::{Pallet, Call, Storage, Event<T>, Config<T>},
 		ImOnline: imonline::{Pallet, Call, Storage, Config<T>, Event<T>},
 		Historical: pallet_session_historical::{Pallet},
 	}
@@ -157,6 +157,9 @@ impl pallet_session::Config for Runtime {
 	type ValidatorId = u64;
 	type ValidatorIdOf = ConvertInto;
 	type Keys = UintAuthorityId;
+	type KeyPurgeDelay = ConstU32<0>;
+	type MaxPurgesPerSession = ConstU32<0>;
+	type PurgeKeysOrigin = frame_system::EnsureRoot<Self::AccountId>;
 	type RuntimeEvent = RuntimeEvent;
 	type NextSessionRotation = pallet_session::PeriodicSessions<Period, Offset>;
 	type WeightInfo = ();
@@ -165,6 +168,8 @@ impl pallet_session::Config for Runtime {
 impl pallet_session::historical::Config for Runtime {
 	type FullIdentification = u64;
 	type FullIdentificationOf = ConvertInto;
+	type MaxStoredSessions = ConstU32<1_000>;
+	type MaxKeyOwnershipProofSize = ConstU32<{ 64 * 1024 }>;
 }
 
 impl pallet_authorship::Config for Runtime {
@@ -0,0 +1,38 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runtime API definition for the offences pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_staking::offence::OffenceRecord;
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+	/// Runtime api for monitoring the offences reported against validators.
+	pub trait OffencesApi<AccountId>
+		where
+			AccountId: Codec,
+	{
+		/// Returns the known offences of `validator` that can still lead to a slash.
+		///
+		/// Which offences can still be slashed is decided by the runtime. Usually these are the
+		/// offences within the bonding duration.
+		fn offences_of(validator: AccountId) -> Vec<OffenceRecord<AccountId>>;
+	}
+}
@@ -30,7 +30,9 @@ use codec::{Decode, Encode};
 use frame_support::weights::Weight;
 use sp_runtime::{traits::Hash, Perbill};
 use sp_staking::{
-	offence::{Kind, Offence, OffenceDetails, OffenceError, OnOffenceHandler, ReportOffence},
+	offence::{
+		Kind, Offence, OffenceDetails, OffenceError, OffenceRecord, OnOffenceHandler, ReportOffence,
+	},
 	SessionIndex,
 };
 use sp_std::prelude::*;
@@ -86,6 +88,12 @@ pub mod pallet {
 		ValueQuery,
 	>;
 
+	/// The session in which the offence of a report happened.
+	///
+	/// Only populated for reports made after this item was introduced.
+	#[pallet::storage]
+	pub type ReportSessions<T: Config> = StorageMap<_, Twox64Concat, ReportIdOf<T>, SessionIndex>;
+
 	/// Enumerates all reports of a kind along with the time they happened.
 	///
 	/// All reports are sorted by the time of offence.
@@ -123,12 +131,16 @@ where
 
 		// Go through all offenders in the offence report and find all offenders that were spotted
 		// in unique reports.
-		let TriageOutcome { concurrent_offenders } =
-			match Self::triage_offence_report::<O>(reporters, &time_slot, offenders) {
-				Some(triage) => triage,
-				// The report contained only duplicates, so there is no need to slash again.
-				None => return Err(OffenceError::DuplicateReport),
-			};
+		let TriageOutcome { concurrent_offenders } = match Self::triage_offence_report::<O>(
+			reporters,
+			&time_slot,
+			offence.session_index(),
+			offenders,
+		) {
+			Some(triage) => triage,
+			// The report contained only duplicates, so there is no need to slash again.
+			None => return Err(OffenceError::DuplicateReport),
+		};
 
 		let offenders_count = concurrent_offenders.len() as u32;
 
@@ -161,6 +173,28 @@ where
 }
 
 impl<T: Config> Pallet<T> {
+	/// All known offences of the offenders matched by `is_offender` that happened in
+	/// `since_session` or later.
+	///
+	/// This iterates over all reports and is meant to be used by runtime APIs only. Reports made
+	/// before [`ReportSessions`] was introduced are never returned.
+	pub fn offences_of(
+		is_offender: impl Fn(&T::IdentificationTuple) -> bool,
+		since_session: SessionIndex,
+	) -> Vec<OffenceRecord<T::AccountId>> {
+		<ConcurrentReportsIndex<T>>::iter()
+			.flat_map(|(kind, time_slot, report_ids)| {
+				report_ids.into_iter().map(move |id| (kind, time_slot.clone(), id))
+			})
+			.filter_map(|(kind, time_slot, report_id)| {
+				let session_index = <ReportSessions<T>>::get(&report_id)
+					.filter(|session| *session >= since_session)?;
+				let details = <Reports<T>>::get(&report_id).filter(|d| is_offender(&d.offender))?;
+				Some(OffenceRecord { kind, time_slot, session_index, reporters: details.reporters })
+			})
+			.collect()
+	}
+
 	/// Compute the ID for the given report properties.
 	///
 	/// The report id depends on the offence kind, time slot and the id of offender.
@@ -176,6 +210,7 @@ impl<T: Config> Pallet<T> {
 	fn triage_offence_report<O: Offence<T::IdentificationTuple>>(
 		reporters: Vec<T::AccountId>,
 		time_slot: &O::TimeSlot,
+		session_index: SessionIndex,
 		offenders: Vec<T::IdentificationTuple>,
 	) -> Option<TriageOutcome<T>> {
 		let mut storage = ReportIndexStorage::<T, O>::load(time_slot);
@@ -190,6 +225,7 @@ impl<T: Config> Pallet<T> {
 					&report_id,
 					OffenceDetails { offender, reporters: reporters.clone() },
 				);
+				<ReportSessions<T>>::insert(&report_id, session_index);
 
 				storage.insert(time_slot, report_id);
 			}
@@ -292,3 +292,34 @@ fn should_properly_sort_offences() {
 		);
 	});
 }
+
+#[test]
+fn should_list_offences_of_an_offender() {
+	new_test_ext().execute_with(|| {
+		// given
+		let offence = Offence { validator_set_count: 5, time_slot: 42, offenders: vec![5, 6] };
+		Offences::report_offence(vec![1, 2], offence).unwrap();
+		let offence = Offence { validator_set_count: 5, time_slot: 43, offenders: vec![5] };
+		Offences::report_offence(vec![], offence).unwrap();
+
+		// when
+		let mut offences = Offences::offences_of(|offender| *offender == 5, 0);
+		offences.sort_by_key(|o| o.time_slot.clone());
+
+		// then
+		let record = |time_slot: u128, reporters| OffenceRecord {
+			kind: KIND,
+			time_slot: time_slot.encode(),
+			session_index: 1,
+			reporters,
+		};
+		assert_eq!(offences, vec![record(42, vec![1, 2]), record(43, vec![])]);
+		assert_eq!(
+			Offences::offences_of(|offender| *offender == 6, 1),
+			vec![record(42, vec![1, 2])]
+		);
+		assert_eq!(Offences::offences_of(|offender| *offender == 7, 0), vec![]);
+		// the offences happened in session 1
+		assert_eq!(Offences::offences_of(|offender| *offender == 5, 2), vec![]);
+	});
+}
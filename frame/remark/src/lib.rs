@@ -46,6 +46,11 @@ pub mod pallet {
 		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 		/// Weight information for extrinsics in this pallet.
 		type WeightInfo: WeightInfo;
+		/// The weight charged per byte of a remark on top of the benchmarked weight of `store`.
+		///
+		/// This makes the fee of anchoring data scale with its size beyond the length fee.
+		#[pallet::constant]
+		type WeightPerByte: Get<Weight>;
 	}
 
 	#[pallet::error]
@@ -63,7 +68,10 @@ pub mod pallet {
 	impl<T: Config> Pallet<T> {
 		/// Index and store data off chain.
 		#[pallet::call_index(0)]
-		#[pallet::weight(T::WeightInfo::store(remark.len() as u32))]
+		#[pallet::weight(
+			T::WeightInfo::store(remark.len() as u32)
+				.saturating_add(T::WeightPerByte::get().saturating_mul(remark.len() as u64))
+		)]
 		pub fn store(origin: OriginFor<T>, remark: Vec<u8>) -> DispatchResultWithPostInfo {
 			ensure!(!remark.is_empty(), Error::<T>::Empty);
 			let sender = ensure_signed(origin)?;
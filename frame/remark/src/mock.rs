@@ -18,7 +18,11 @@
 //! Test environment for remarks pallet.
 
 use crate as pallet_remark;
-use frame_support::traits::{ConstU16, ConstU32, ConstU64};
+use frame_support::{
+	parameter_types,
+	traits::{ConstU16, ConstU32, ConstU64},
+	weights::Weight,
+};
 use sp_core::H256;
 use sp_runtime::{
 	testing::Header,
@@ -68,9 +72,14 @@ impl frame_system::Config for Test {
 	type MaxConsumers = ConstU32<16>;
 }
 
+parameter_types! {
+	pub static WeightPerByte: Weight = Weight::zero();
+}
+
 impl pallet_remark::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
 	type WeightInfo = ();
+	type WeightPerByte = WeightPerByte;
 }
 
 pub fn new_test_ext() -> sp_io::TestExternalities {
@@ -19,7 +19,7 @@
 
 use super::{Error, Event, Pallet as Remark};
 use crate::mock::*;
-use frame_support::{assert_noop, assert_ok};
+use frame_support::{assert_noop, assert_ok, dispatch::GetDispatchInfo, weights::Weight};
 use frame_system::RawOrigin;
 
 #[test]
@@ -56,3 +56,16 @@ fn does_not_store_empty() {
 		assert!(System::events().is_empty());
 	});
 }
+
+#[test]
+fn weight_scales_with_size() {
+	new_test_ext().execute_with(|| {
+		let weight = |remark: Vec<u8>| {
+			RuntimeCall::Remark(crate::Call::store { remark }).get_dispatch_info().weight
+		};
+		let base_weight = weight(vec![0u8; 100]);
+
+		WeightPerByte::set(Weight::from_ref_time(10));
+		assert_eq!(weight(vec![0u8; 100]), base_weight + Weight::from_ref_time(1000));
+	});
+}
@@ -87,6 +87,21 @@ benchmarks! {
 		frame_benchmarking::benchmarking::add_to_whitelist(v_controller_key.into());
 	}: _(RawOrigin::Signed(v_controller))
 
+	purge_keys_other {
+		let n = <T as pallet_staking::Config>::MaxNominations::get();
+		let (v_stash, _) = create_validator_with_nominators::<T>(
+			n,
+			<T as pallet_staking::Config>::MaxNominations::get(),
+			false,
+			RewardDestination::Staked
+		)?;
+		let v_controller = pallet_staking::Pallet::<T>::bonded(&v_stash).ok_or("not stash")?;
+		let keys = T::Keys::decode(&mut TrailingZeroInput::zeroes()).unwrap();
+		let proof: Vec<u8> = vec![0,1,2,3];
+		Session::<T>::set_keys(RawOrigin::Signed(v_controller.clone()).into(), keys, proof)?;
+		let origin = T::PurgeKeysOrigin::successful_origin();
+	}: _<T::RuntimeOrigin>(origin, v_controller)
+
 	#[extra]
 	check_membership_proof_current_session {
 		let n in 2 .. MAX_VALIDATORS as u32;
@@ -45,7 +45,7 @@ use sp_trie::{
 
 use frame_support::{
 	print,
-	traits::{KeyOwnerProofSystem, ValidatorSet, ValidatorSetWithIdentification},
+	traits::{Get, KeyOwnerProofSystem, ValidatorSet, ValidatorSetWithIdentification},
 	Parameter,
 };
 
@@ -57,6 +57,7 @@ pub use pallet::*;
 pub mod pallet {
 	use super::*;
 	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::BlockNumberFor;
 
 	/// The current storage version.
 	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
@@ -80,6 +81,31 @@ pub mod pallet {
 		///
 		/// It must return the identification for the current session index.
 		type FullIdentificationOf: Convert<Self::ValidatorId, Option<Self::FullIdentification>>;
+
+		/// The maximum number of sessions for which the historical roots are kept.
+		///
+		/// Older sessions are pruned automatically whenever a new session is noted. This should
+		/// cover at least the window in which offences can be reported and slashed. Pruning
+		/// earlier through [`Pallet::prune_up_to`] is still possible.
+		#[pallet::constant]
+		type MaxStoredSessions: Get<SessionIndex>;
+
+		/// The maximum encoded size of a key ownership proof in bytes.
+		///
+		/// Larger proofs are rejected by [`KeyOwnerProofSystem::check_proof`] without being
+		/// looked at. This bounds the work done when validating equivocation reports.
+		#[pallet::constant]
+		type MaxKeyOwnershipProofSize: Get<u32>;
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn integrity_test() {
+			assert!(
+				T::MaxStoredSessions::get() > 0,
+				"`MaxStoredSessions` must keep at least the current session"
+			);
+		}
 	}
 
 	/// Mapping from historical session indices to session-data root hash and validator count.
@@ -191,6 +217,11 @@ impl<T: Config, I: SessionManager<T::ValidatorId, T::FullIdentification>> NoteHi
 			}
 		}
 
+		// Only keep the most recent `MaxStoredSessions` sessions.
+		if let Some(up_to) = (new_index + 1).checked_sub(T::MaxStoredSessions::get()) {
+			<Pallet<T>>::prune_up_to(up_to);
+		}
+
 		new_validators_opt
 	}
 }
@@ -344,6 +375,10 @@ impl<T: Config, D: AsRef<[u8]>> KeyOwnerProofSystem<(KeyTypeId, D)> for Pallet<T
 	}
 
 	fn check_proof(key: (KeyTypeId, D), proof: Self::Proof) -> Option<IdentificationTuple<T>> {
+		if proof.encoded_size() > T::MaxKeyOwnershipProofSize::get() as usize {
+			return None
+		}
+
 		let (id, data) = key;
 
 		if proof.session == <Session<T>>::current_index() {
@@ -375,7 +410,8 @@ impl<T: Config, D: AsRef<[u8]>> KeyOwnerProofSystem<(KeyTypeId, D)> for Pallet<T
 pub(crate) mod tests {
 	use super::*;
 	use crate::mock::{
-		force_new_session, set_next_validators, NextValidators, Session, System, Test,
+		force_new_session, set_next_validators, MaxKeyOwnershipProofSize, MaxStoredSessions,
+		NextValidators, Session, System, Test,
 	};
 
 	use sp_runtime::{key_types::DUMMY, testing::UintAuthorityId};
@@ -495,4 +531,45 @@ pub(crate) mod tests {
 			}
 		});
 	}
+
+	#[test]
+	fn old_sessions_are_pruned_automatically() {
+		new_test_ext().execute_with(|| {
+			MaxStoredSessions::set(10);
+
+			for i in 1..25u64 {
+				set_next_validators(vec![i]);
+				force_new_session();
+
+				System::set_block_number(i);
+				Session::on_initialize(i);
+			}
+
+			assert_eq!(<StoredRange<Test>>::get(), Some((16, 26)));
+			for i in 0..16 {
+				assert!(Historical::historical_root(i).is_none())
+			}
+			for i in 16..26 {
+				assert!(Historical::historical_root(i).is_some())
+			}
+		});
+	}
+
+	#[test]
+	fn oversized_proofs_are_rejected() {
+		new_test_ext().execute_with(|| {
+			set_next_validators(vec![1, 2]);
+			force_new_session();
+
+			System::set_block_number(1);
+			Session::on_initialize(1);
+
+			let encoded_key_1 = UintAuthorityId(1).encode();
+			let proof = Historical::prove((DUMMY, &encoded_key_1[..])).unwrap();
+			assert!(Historical::check_proof((DUMMY, &encoded_key_1[..]), proof.clone()).is_some());
+
+			MaxKeyOwnershipProofSize::set(proof.encoded_size() as u32 - 1);
+			assert!(Historical::check_proof((DUMMY, &encoded_key_1[..]), proof).is_none());
+		});
+	}
 }
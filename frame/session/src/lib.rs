@@ -48,6 +48,11 @@
 //!   the origin stored in `NextKeys` may not necessarily be associated with a block author or a
 //!   validator. The session keys of accounts are removed once their account balance is zero.
 //!
+//! - **Key purging:** Session keys of accounts that are not part of the validator set for
+//!   `KeyPurgeDelay` sessions are purged automatically, which releases the consumer reference that
+//!   `set_keys` placed on the registering account. `PurgeKeysOrigin` can purge the keys of any
+//!   account through `purge_keys_other`.
+//!
 //! - **Session length:** This pallet does not assume anything about the length of each session.
 //!   Rather, it relies on an implementation of `ShouldEndSession` to dictate a new session's start.
 //!   This pallet provides the `PeriodicSessions` struct for simple periodic sessions.
@@ -76,6 +81,8 @@
 //! ### Dispatchable Functions
 //!
 //! - `set_keys` - Set a validator's session keys for upcoming sessions.
+//! - `purge_keys` - Remove the session keys of the caller.
+//! - `purge_keys_other` - Remove the session keys of some other account.
 //!
 //! ### Public Functions
 //!
@@ -132,6 +139,7 @@ use sp_runtime::{
 };
 use sp_staking::SessionIndex;
 use sp_std::{
+	collections::btree_set::BTreeSet,
 	marker::PhantomData,
 	ops::{Rem, Sub},
 	prelude::*,
@@ -140,6 +148,10 @@ use sp_std::{
 pub use pallet::*;
 pub use weights::WeightInfo;
 
+/// Number of later sessions a purge is deferred to at most if the schedule of its due session is
+/// full. If none of them has room the purge is not scheduled.
+const MAX_PURGE_DEFERRALS: SessionIndex = 4;
+
 /// Decides whether the session should be ended.
 pub trait ShouldEndSession<BlockNumber> {
 	/// Return `true` if the session should be ended.
@@ -368,7 +380,7 @@ pub mod pallet {
 	use frame_system::pallet_prelude::*;
 
 	/// The current storage version.
-	const STORAGE_VERSION: StorageVersion = StorageVersion::new(0);
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
 
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
@@ -379,13 +391,14 @@ pub mod pallet {
 	#[pallet::config]
 	pub trait Config: frame_system::Config {
 		/// The overarching event type.
-		type RuntimeEvent: From<Event> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 
 		/// A stable ID for a validator.
 		type ValidatorId: Member
 			+ Parameter
 			+ MaybeSerializeDeserialize
 			+ MaxEncodedLen
+			+ Ord
 			+ TryFrom<Self::AccountId>;
 
 		/// A conversion from account ID to validator ID.
@@ -410,6 +423,23 @@ pub mod pallet {
 		/// The keys.
 		type Keys: OpaqueKeys + Member + Parameter + MaybeSerializeDeserialize;
 
+		/// Number of sessions the registered keys of an account that is not part of the validator
+		/// set are kept before they are purged automatically.
+		///
+		/// Setting this to zero disables automatic purging.
+		#[pallet::constant]
+		type KeyPurgeDelay: Get<SessionIndex>;
+
+		/// Maximum number of validators whose keys are purged at the start of a single session.
+		///
+		/// Purges which don't fit are deferred to one of the next few sessions. If these are full
+		/// as well the keys are not purged automatically.
+		#[pallet::constant]
+		type MaxPurgesPerSession: Get<u32>;
+
+		/// The origin which may purge the session keys of any account.
+		type PurgeKeysOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
 		/// Weight information for extrinsics in this pallet.
 		type WeightInfo: WeightInfo;
 	}
@@ -449,6 +479,7 @@ pub mod pallet {
 			for (account, val, keys) in self.keys.iter().cloned() {
 				<Pallet<T>>::inner_set_keys(&val, keys)
 					.expect("genesis config must not contain duplicates; qed");
+				<KeyRegistrar<T>>::insert(&val, &account);
 				if frame_system::Pallet::<T>::inc_consumers_without_limit(&account).is_err() {
 					// This will leak a provider reference, however it only happens once (at
 					// genesis) so it's really not a big deal and we assume that the user wants to
@@ -539,12 +570,48 @@ pub mod pallet {
 	pub type KeyOwner<T: Config> =
 		StorageMap<_, Twox64Concat, (KeyTypeId, Vec<u8>), T::ValidatorId, OptionQuery>;
 
+	/// The account that registered the keys of a validator and hence holds the consumer
+	/// reference for them.
+	///
+	/// Keys registered before this item was introduced have no entry and are never purged
+	/// automatically.
+	#[pallet::storage]
+	pub type KeyRegistrar<T: Config> =
+		StorageMap<_, Twox64Concat, T::ValidatorId, T::AccountId, OptionQuery>;
+
+	/// The session since which a validator with registered keys is not part of the validator
+	/// set.
+	#[pallet::storage]
+	pub type InactiveSince<T: Config> =
+		StorageMap<_, Twox64Concat, T::ValidatorId, SessionIndex, OptionQuery>;
+
+	/// Validators whose keys are due to be purged at the start of the given session.
+	///
+	/// Entries are only acted upon if the validator is still inactive by then.
+	#[pallet::storage]
+	pub type PurgeSchedule<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		SessionIndex,
+		BoundedVec<T::ValidatorId, T::MaxPurgesPerSession>,
+		ValueQuery,
+	>;
+
+	/// The session in whose [`PurgeSchedule`] a validator is listed.
+	///
+	/// A validator is listed at most once so that rescheduling removes the previous entry.
+	#[pallet::storage]
+	pub type ScheduledPurge<T: Config> =
+		StorageMap<_, Twox64Concat, T::ValidatorId, SessionIndex, OptionQuery>;
+
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
-	pub enum Event {
+	pub enum Event<T: Config> {
 		/// New session has happened. Note that the argument is the session index, not the
 		/// block number as the type might suggest.
 		NewSession { session_index: SessionIndex },
+		/// The session keys of a validator were purged.
+		KeysPurged { validator_id: T::ValidatorId },
 	}
 
 	/// Error for the session pallet.
@@ -577,6 +644,13 @@ pub mod pallet {
 				Weight::zero()
 			}
 		}
+
+		fn integrity_test() {
+			assert!(
+				T::KeyPurgeDelay::get().is_zero() || !T::MaxPurgesPerSession::get().is_zero(),
+				"`MaxPurgesPerSession` must allow purging keys if `KeyPurgeDelay` is set"
+			);
+		}
 	}
 
 	#[pallet::call]
@@ -596,7 +670,7 @@ pub mod pallet {
 		/// - DbWrites per key id: `KeyOwner`
 		/// # </weight>
 		#[pallet::call_index(0)]
-		#[pallet::weight(T::WeightInfo::set_keys())]
+		#[pallet::weight(T::WeightInfo::set_keys().saturating_add(Pallet::<T>::schedule_purge_weight()))]
 		pub fn set_keys(origin: OriginFor<T>, keys: T::Keys, proof: Vec<u8>) -> DispatchResult {
 			let who = ensure_signed(origin)?;
 			ensure!(keys.ownership_proof_is_valid(&proof), Error::<T>::InvalidProof);
@@ -628,6 +702,20 @@ pub mod pallet {
 			Self::do_purge_keys(&who)?;
 			Ok(())
 		}
+
+		/// Removes any session key(s) of `who`.
+		///
+		/// This doesn't take effect until the next session.
+		///
+		/// The dispatch origin of this function must be `PurgeKeysOrigin`. `who` is resolved to a
+		/// validator ID in the same way as the caller of [`Pallet::purge_keys`].
+		#[pallet::call_index(2)]
+		#[pallet::weight(T::WeightInfo::purge_keys_other())]
+		pub fn purge_keys_other(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+			T::PurgeKeysOrigin::ensure_origin(origin)?;
+			Self::do_purge_keys(&who)?;
+			Ok(())
+		}
 	}
 }
 
@@ -706,6 +794,11 @@ impl<T: Config> Pallet<T> {
 			(queued_amalgamated, changed)
 		};
 
+		if next_identities_changed {
+			Self::note_inactive_validators(session_index, &session_keys, &queued_amalgamated);
+		}
+		Self::purge_inactive_keys(session_index);
+
 		<QueuedKeys<T>>::put(queued_amalgamated.clone());
 		<QueuedChanged<T>>::put(next_changed);
 
@@ -809,6 +902,13 @@ impl<T: Config> Pallet<T> {
 		if old_keys.is_none() {
 			let assertion = frame_system::Pallet::<T>::inc_consumers(account).is_ok();
 			debug_assert!(assertion, "can_inc_consumer() returned true; no change since; qed");
+			<KeyRegistrar<T>>::insert(&who, account);
+		}
+
+		// Setting keys counts as activity: restart the purge countdown for accounts which are
+		// not about to validate.
+		if !Self::queued_keys().iter().any(|(v, _)| v == &who) {
+			Self::schedule_purge(&who, Self::current_index());
 		}
 
 		Ok(())
@@ -862,16 +962,114 @@ impl<T: Config> Pallet<T> {
 			.or_else(|| T::ValidatorId::try_from(account.clone()).ok())
 			.ok_or(Error::<T>::NoAssociatedValidatorId)?;
 
-		let old_keys = Self::take_keys(&who).ok_or(Error::<T>::NoKeys)?;
+		let registrar = <KeyRegistrar<T>>::get(&who).unwrap_or_else(|| account.clone());
+		Self::remove_keys(&who, &registrar)
+	}
+
+	/// Remove the keys of `who` and release the consumer reference held on `registrar`.
+	fn remove_keys(who: &T::ValidatorId, registrar: &T::AccountId) -> DispatchResult {
+		let old_keys = Self::take_keys(who).ok_or(Error::<T>::NoKeys)?;
 		for id in T::Keys::key_ids() {
 			let key_data = old_keys.get_raw(*id);
 			Self::clear_key_owner(*id, key_data);
 		}
-		frame_system::Pallet::<T>::dec_consumers(account);
+		<KeyRegistrar<T>>::remove(who);
+		<InactiveSince<T>>::remove(who);
+		Self::unschedule_purge(who);
+		frame_system::Pallet::<T>::dec_consumers(registrar);
 
+		Self::deposit_event(Event::KeysPurged { validator_id: who.clone() });
 		Ok(())
 	}
 
+	/// Record `who` as inactive since `session_index` and schedule its keys to be purged once
+	/// `KeyPurgeDelay` sessions have passed.
+	///
+	/// A purge that was scheduled for `who` before is replaced.
+	fn schedule_purge(who: &T::ValidatorId, session_index: SessionIndex) {
+		let delay = T::KeyPurgeDelay::get();
+		if delay.is_zero() {
+			return
+		}
+		Self::unschedule_purge(who);
+		<InactiveSince<T>>::insert(who, session_index);
+
+		// Defer the purge to a later session if the schedule of the due session is full.
+		let due = session_index.saturating_add(delay);
+		for session in due..=due.saturating_add(MAX_PURGE_DEFERRALS) {
+			if <PurgeSchedule<T>>::try_append(session, who).is_ok() {
+				<ScheduledPurge<T>>::insert(who, session);
+				return
+			}
+		}
+	}
+
+	/// Remove `who` from the [`PurgeSchedule`] it is listed in.
+	fn unschedule_purge(who: &T::ValidatorId) {
+		if let Some(session) = <ScheduledPurge<T>>::take(who) {
+			<PurgeSchedule<T>>::mutate_exists(session, |scheduled| {
+				if let Some(validators) = scheduled {
+					validators.retain(|v| v != who);
+					if validators.is_empty() {
+						*scheduled = None;
+					}
+				}
+			});
+		}
+	}
+
+	/// The worst case weight of [`Self::schedule_purge`] on top of [`WeightInfo::set_keys`].
+	fn schedule_purge_weight() -> Weight {
+		// `QueuedKeys`, `ScheduledPurge` and the previous and all deferred `PurgeSchedule`s are
+		// read. `InactiveSince`, `ScheduledPurge` and two `PurgeSchedule`s are written.
+		let reads = MAX_PURGE_DEFERRALS.saturating_add(4);
+		T::DbWeight::get().reads_writes(reads.into(), 4)
+	}
+
+	/// Update the inactivity bookkeeping after a new validator set was queued.
+	///
+	/// Validators which are no longer part of the queued set start their purge countdown while
+	/// the ones that made it (back) into the set have it cancelled.
+	fn note_inactive_validators(
+		session_index: SessionIndex,
+		previous: &[(T::ValidatorId, T::Keys)],
+		queued: &[(T::ValidatorId, T::Keys)],
+	) {
+		let queued_ids = queued.iter().map(|(v, _)| v).collect::<BTreeSet<_>>();
+		for (v, _) in previous {
+			if !queued_ids.contains(v) && <NextKeys<T>>::contains_key(v) {
+				Self::schedule_purge(v, session_index);
+			}
+		}
+		for v in queued_ids {
+			<InactiveSince<T>>::remove(v);
+			Self::unschedule_purge(v);
+		}
+	}
+
+	/// Purge the keys of all validators whose purge is due at `session_index` and which stayed
+	/// inactive ever since it was scheduled.
+	fn purge_inactive_keys(session_index: SessionIndex) {
+		let delay = T::KeyPurgeDelay::get();
+		for v in <PurgeSchedule<T>>::take(session_index) {
+			<ScheduledPurge<T>>::remove(&v);
+			let still_due = <InactiveSince<T>>::get(&v)
+				.map_or(false, |since| since.saturating_add(delay) <= session_index);
+			if !still_due {
+				continue
+			}
+			if let Some(registrar) = <KeyRegistrar<T>>::get(&v) {
+				if let Err(e) = Self::remove_keys(&v, &registrar) {
+					log::warn!(
+						target: "runtime::session",
+						"failed to purge keys of inactive validator: {:?}",
+						e,
+					);
+				}
+			}
+		}
+	}
+
 	fn load_keys(v: &T::ValidatorId) -> Option<T::Keys> {
 		<NextKeys<T>>::get(v)
 	}
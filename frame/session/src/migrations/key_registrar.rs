@@ -0,0 +1,88 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{Config, CurrentIndex, KeyRegistrar, NextKeys, Pallet, QueuedKeys};
+use frame_support::{
+	traits::{Get, GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+	weights::Weight,
+};
+use sp_runtime::traits::Convert;
+use sp_std::{collections::btree_set::BTreeSet, marker::PhantomData};
+
+const LOG_TARGET: &str = "runtime::session";
+
+/// Record the registrar of all keys that don't have one yet.
+///
+/// `C` resolves a validator to the account that registered its keys, which is the account that
+/// holds the consumer reference for them. Validators it can't resolve, or resolves to an account
+/// that doesn't map back to the validator through [`Config::ValidatorIdOf`], are skipped.
+pub struct MigrateToV1<T, C>(PhantomData<(T, C)>);
+
+impl<T: Config, C: Convert<T::ValidatorId, Option<T::AccountId>>> OnRuntimeUpgrade
+	for MigrateToV1<T, C>
+{
+	fn on_runtime_upgrade() -> Weight {
+		let on_chain_version = Pallet::<T>::on_chain_storage_version();
+		if on_chain_version >= 1 {
+			log::info!(
+				target: LOG_TARGET,
+				"KeyRegistrar backfill skipped as on chain storage version is {:?}",
+				on_chain_version,
+			);
+			return T::DbWeight::get().reads(1)
+		}
+
+		let queued = QueuedKeys::<T>::get().into_iter().map(|(v, _)| v).collect::<BTreeSet<_>>();
+		let current_index = CurrentIndex::<T>::get();
+		let (mut reads, mut writes) = (3u64, 1u64);
+		let (mut backfilled, mut scheduled) = (0u32, 0u32);
+
+		for validator in NextKeys::<T>::iter_keys() {
+			reads += 2;
+			if KeyRegistrar::<T>::contains_key(&validator) {
+				continue
+			}
+			let registrar = match C::convert(validator.clone()) {
+				Some(account)
+					if T::ValidatorIdOf::convert(account.clone()).as_ref() == Some(&validator) =>
+					account,
+				_ => continue,
+			};
+
+			KeyRegistrar::<T>::insert(&validator, registrar);
+			writes += 1;
+			backfilled += 1;
+
+			if !queued.contains(&validator) {
+				Pallet::<T>::schedule_purge(&validator, current_index);
+				reads += 1;
+				writes += 2;
+				scheduled += 1;
+			}
+		}
+
+		StorageVersion::new(1).put::<Pallet<T>>();
+		log::info!(
+			target: LOG_TARGET,
+			"Backfilled the KeyRegistrar of {} validators, {} of them are scheduled to be purged",
+			backfilled,
+			scheduled,
+		);
+
+		T::DbWeight::get().reads_writes(reads, writes)
+	}
+}
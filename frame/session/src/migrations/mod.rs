@@ -22,3 +22,9 @@
 /// This migration moves session historical pallet storages from old prefix to new prefix.
 #[cfg(feature = "historical")]
 pub mod v1;
+
+/// Backfill of the `KeyRegistrar` of session keys registered before it was introduced.
+///
+/// Such keys were never purged automatically. This migration records their registrar and
+/// starts the purge countdown for the ones which are not queued to validate.
+pub mod key_registrar;
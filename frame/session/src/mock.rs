@@ -86,7 +86,7 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
-		Session: pallet_session::{Pallet, Call, Storage, Event, Config<T>},
+		Session: pallet_session::{Pallet, Call, Storage, Event<T>, Config<T>},
 		Historical: pallet_session_historical::{Pallet},
 	}
 );
@@ -99,7 +99,7 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
-		Session: pallet_session::{Pallet, Call, Storage, Event, Config<T>},
+		Session: pallet_session::{Pallet, Call, Storage, Event<T>, Config<T>},
 	}
 );
 
@@ -116,6 +116,10 @@ parameter_types! {
 	// Stores if `on_before_session_end` was called
 	pub static BeforeSessionEndCalled: bool = false;
 	pub static ValidatorAccounts: BTreeMap<u64, u64> = BTreeMap::new();
+	pub static KeyPurgeDelay: SessionIndex = 0;
+	pub static MaxPurgesPerSession: u32 = 16;
+	pub static MaxStoredSessions: SessionIndex = 1_000;
+	pub static MaxKeyOwnershipProofSize: u32 = 64 * 1024;
 }
 
 pub struct TestShouldEndSession;
@@ -294,6 +298,9 @@ impl Config for Test {
 	type ValidatorId = u64;
 	type ValidatorIdOf = TestValidatorIdOf;
 	type Keys = MockSessionKeys;
+	type KeyPurgeDelay = KeyPurgeDelay;
+	type MaxPurgesPerSession = MaxPurgesPerSession;
+	type PurgeKeysOrigin = frame_system::EnsureRoot<u64>;
 	type RuntimeEvent = RuntimeEvent;
 	type NextSessionRotation = ();
 	type WeightInfo = ();
@@ -303,4 +310,6 @@ impl Config for Test {
 impl crate::historical::Config for Test {
 	type FullIdentification = u64;
 	type FullIdentificationOf = sp_runtime::traits::ConvertInto;
+	type MaxStoredSessions = MaxStoredSessions;
+	type MaxKeyOwnershipProofSize = MaxKeyOwnershipProofSize;
 }
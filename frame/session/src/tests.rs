@@ -21,17 +21,17 @@ use super::*;
 use crate::mock::{
 	authorities, before_session_end_called, force_new_session, new_test_ext,
 	reset_before_session_end_called, session_changed, set_next_validators, set_session_length,
-	PreUpgradeMockSessionKeys, RuntimeOrigin, Session, SessionChanged, System, Test,
-	TestSessionChanged, TestValidatorIdOf,
+	KeyPurgeDelay, MaxPurgesPerSession, PreUpgradeMockSessionKeys, RuntimeEvent, RuntimeOrigin,
+	Session, SessionChanged, System, Test, TestSessionChanged, TestValidatorIdOf,
 };
 
 use codec::Decode;
 use sp_core::crypto::key_types::DUMMY;
-use sp_runtime::testing::UintAuthorityId;
+use sp_runtime::{testing::UintAuthorityId, traits::ConvertInto};
 
 use frame_support::{
-	assert_noop, assert_ok,
-	traits::{ConstU64, OnInitialize},
+	assert_noop, assert_ok, assert_storage_noop,
+	traits::{ConstU64, GetStorageVersion, OnInitialize, OnRuntimeUpgrade, StorageVersion},
 };
 
 fn initialize_block(block: u64) {
@@ -97,6 +97,184 @@ fn purge_keys_works_for_stash_id() {
 	})
 }
 
+#[test]
+fn purge_keys_other_works() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Session::purge_keys_other(RuntimeOrigin::signed(2), 1),
+			sp_runtime::traits::BadOrigin,
+		);
+
+		assert!(System::is_provider_required(&1));
+		assert_ok!(Session::purge_keys_other(RuntimeOrigin::root(), 1));
+		assert!(!System::is_provider_required(&1));
+		assert_eq!(Session::load_keys(&1), None);
+		assert_eq!(Session::key_owner(DUMMY, UintAuthorityId(1).get_raw(DUMMY)), None);
+
+		assert_noop!(Session::purge_keys_other(RuntimeOrigin::root(), 1), Error::<Test>::NoKeys,);
+	})
+}
+
+#[test]
+fn keys_of_inactive_validators_are_purged() {
+	new_test_ext().execute_with(|| {
+		KeyPurgeDelay::set(2);
+		initialize_block(1);
+
+		// 3 is no longer part of the set queued in session 1.
+		set_next_validators(vec![1, 2]);
+		initialize_block(2);
+		assert_eq!(InactiveSince::<Test>::get(3), Some(1));
+
+		initialize_block(4);
+		assert_eq!(Session::load_keys(&3), Some(UintAuthorityId(3).into()));
+		assert!(System::is_provider_required(&3));
+
+		// The keys are purged once `KeyPurgeDelay` sessions have passed.
+		initialize_block(6);
+		assert_eq!(Session::current_index(), 3);
+		assert_eq!(Session::load_keys(&3), None);
+		assert_eq!(Session::key_owner(DUMMY, UintAuthorityId(3).get_raw(DUMMY)), None);
+		assert!(!System::is_provider_required(&3));
+		System::assert_has_event(RuntimeEvent::Session(Event::KeysPurged { validator_id: 3 }));
+
+		// Active validators keep their keys.
+		assert_eq!(Session::load_keys(&1), Some(UintAuthorityId(1).into()));
+		assert_eq!(Session::load_keys(&2), Some(UintAuthorityId(2).into()));
+	});
+}
+
+#[test]
+fn reelected_validators_keep_their_keys() {
+	new_test_ext().execute_with(|| {
+		KeyPurgeDelay::set(2);
+		initialize_block(1);
+
+		set_next_validators(vec![1, 2]);
+		initialize_block(2);
+		assert_eq!(InactiveSince::<Test>::get(3), Some(1));
+
+		// 3 makes it back into the set before its keys are purged.
+		set_next_validators(vec![1, 2, 3]);
+		initialize_block(4);
+		assert_eq!(InactiveSince::<Test>::get(3), None);
+
+		initialize_block(6);
+		initialize_block(8);
+		assert_eq!(Session::load_keys(&3), Some(UintAuthorityId(3).into()));
+		assert!(System::is_provider_required(&3));
+	});
+}
+
+#[test]
+fn purges_exceeding_the_session_limit_are_deferred() {
+	new_test_ext().execute_with(|| {
+		KeyPurgeDelay::set(2);
+		MaxPurgesPerSession::set(1);
+		initialize_block(1);
+
+		// 2 and 3 are no longer part of the set queued in session 1.
+		set_next_validators(vec![1]);
+		initialize_block(2);
+		assert_eq!(PurgeSchedule::<Test>::get(3).into_inner(), vec![2]);
+		assert_eq!(PurgeSchedule::<Test>::get(4).into_inner(), vec![3]);
+
+		initialize_block(4);
+		initialize_block(6);
+		assert_eq!(Session::current_index(), 3);
+		assert_eq!(Session::load_keys(&2), None);
+		assert_eq!(Session::load_keys(&3), Some(UintAuthorityId(3).into()));
+
+		// The purge of 3 happens one session later.
+		initialize_block(8);
+		assert_eq!(Session::load_keys(&3), None);
+		assert!(!System::is_provider_required(&3));
+	});
+}
+
+#[test]
+fn rescheduling_a_purge_replaces_the_previous_one() {
+	new_test_ext().execute_with(|| {
+		KeyPurgeDelay::set(2);
+		TestValidatorIdOf::set((1..=4).map(|v| (v, v)).collect());
+
+		// 4 is not queued to validate so setting its keys schedules a purge.
+		assert_ok!(Session::set_keys(RuntimeOrigin::signed(4), UintAuthorityId(4).into(), vec![]));
+		assert_eq!(ScheduledPurge::<Test>::get(4), Some(2));
+		assert_eq!(PurgeSchedule::<Test>::get(2).into_inner(), vec![4]);
+
+		// Setting the keys again moves the purge instead of adding another one.
+		initialize_block(2);
+		assert_eq!(Session::current_index(), 1);
+		assert_ok!(Session::set_keys(RuntimeOrigin::signed(4), UintAuthorityId(4).into(), vec![]));
+		assert_eq!(ScheduledPurge::<Test>::get(4), Some(3));
+		assert!(!PurgeSchedule::<Test>::contains_key(2));
+		assert_eq!(PurgeSchedule::<Test>::get(3).into_inner(), vec![4]);
+
+		// Purging the keys manually removes the scheduled purge.
+		assert_ok!(Session::purge_keys(RuntimeOrigin::signed(4)));
+		assert_eq!(ScheduledPurge::<Test>::get(4), None);
+		assert!(!PurgeSchedule::<Test>::contains_key(3));
+	});
+}
+
+#[test]
+fn purges_are_deferred_a_limited_number_of_sessions() {
+	new_test_ext().execute_with(|| {
+		KeyPurgeDelay::set(2);
+		MaxPurgesPerSession::set(1);
+		TestValidatorIdOf::set((1..=15).map(|v| (v, v)).collect());
+
+		for v in 10..=15 {
+			System::inc_providers(&v);
+			assert_ok!(Session::set_keys(
+				RuntimeOrigin::signed(v),
+				UintAuthorityId(v).into(),
+				vec![]
+			));
+		}
+
+		// The purges are spread over the due session and the sessions after it.
+		for (v, session) in (10..=14).zip(2..=6) {
+			assert_eq!(ScheduledPurge::<Test>::get(v), Some(session));
+			assert_eq!(PurgeSchedule::<Test>::get(session).into_inner(), vec![v]);
+		}
+		// There is no room left for the last one within reach.
+		assert_eq!(ScheduledPurge::<Test>::get(15), None);
+		assert!(!PurgeSchedule::<Test>::contains_key(7));
+	});
+}
+
+#[test]
+fn migration_backfills_key_registrar() {
+	new_test_ext().execute_with(|| {
+		// 4 registers its keys while it is not queued to validate.
+		TestValidatorIdOf::set(vec![(1, 1), (2, 2), (3, 3), (4, 4)].into_iter().collect());
+		assert_ok!(Session::set_keys(RuntimeOrigin::signed(4), UintAuthorityId(4).into(), vec![]));
+
+		// Pretend all keys were registered before the registrar was tracked.
+		let _ = KeyRegistrar::<Test>::clear(u32::MAX, None);
+		StorageVersion::new(0).put::<Session>();
+		KeyPurgeDelay::set(2);
+
+		migrations::key_registrar::MigrateToV1::<Test, ConvertInto>::on_runtime_upgrade();
+
+		for v in 1..=4 {
+			assert_eq!(KeyRegistrar::<Test>::get(v), Some(v));
+		}
+		// Only the keys of 4 are not queued and hence scheduled to be purged.
+		assert_eq!(InactiveSince::<Test>::get(4), Some(0));
+		assert_eq!(InactiveSince::<Test>::get(1), None);
+		assert_eq!(PurgeSchedule::<Test>::get(2).into_inner(), vec![4]);
+		assert_eq!(Session::on_chain_storage_version(), 1);
+
+		// The migration only runs once.
+		assert_storage_noop!(
+			migrations::key_registrar::MigrateToV1::<Test, ConvertInto>::on_runtime_upgrade()
+		);
+	});
+}
+
 #[test]
 fn authorities_should_track_validators() {
 	reset_before_session_end_called();
@@ -49,6 +49,7 @@ use sp_std::marker::PhantomData;
 pub trait WeightInfo {
 	fn set_keys() -> Weight;
 	fn purge_keys() -> Weight;
+	fn purge_keys_other() -> Weight;
 }
 
 /// Weights for pallet_session using the Substrate node and recommended hardware.
@@ -72,6 +73,17 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(2 as u64))
 			.saturating_add(T::DbWeight::get().writes(5 as u64))
 	}
+	// Storage: Staking Ledger (r:1 w:0)
+	// Storage: Session KeyRegistrar (r:1 w:1)
+	// Storage: Session NextKeys (r:1 w:1)
+	// Storage: Session KeyOwner (r:0 w:4)
+	// Storage: Session InactiveSince (r:0 w:1)
+	fn purge_keys_other() -> Weight {
+		// Minimum execution time: 51_405 nanoseconds.
+		Weight::from_ref_time(52_180_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(3 as u64))
+			.saturating_add(T::DbWeight::get().writes(7 as u64))
+	}
 }
 
 // For backwards compatibility and tests
@@ -94,4 +106,15 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(2 as u64))
 			.saturating_add(RocksDbWeight::get().writes(5 as u64))
 	}
+	// Storage: Staking Ledger (r:1 w:0)
+	// Storage: Session KeyRegistrar (r:1 w:1)
+	// Storage: Session NextKeys (r:1 w:1)
+	// Storage: Session KeyOwner (r:0 w:4)
+	// Storage: Session InactiveSince (r:0 w:1)
+	fn purge_keys_other() -> Weight {
+		// Minimum execution time: 51_405 nanoseconds.
+		Weight::from_ref_time(52_180_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(3 as u64))
+			.saturating_add(RocksDbWeight::get().writes(7 as u64))
+	}
 }
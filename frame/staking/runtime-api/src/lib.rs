@@ -0,0 +1,61 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runtime API definition for the staking pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Codec, HasCompact};
+pub use pallet_staking::{ActiveEraInfo, Exposure, PageIndex, SlashSimulation};
+use sp_runtime::Perbill;
+use sp_staking::EraIndex;
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+	/// Runtime api for querying the staking pallet.
+	#[api_version(2)]
+	pub trait StakingApi<AccountId, Balance>
+		where AccountId: Codec, Balance: Codec + HasCompact
+	{
+		/// Compute how slashing `fraction` of the exposure of `validator` in `era` would be
+		/// apportioned across the validator and its nominators, without applying the slash.
+		///
+		/// Returns `None` if `validator` was not elected in `era`, is invulnerable, or `era` is
+		/// outside of the bonding period.
+		fn simulate_slash(
+			validator: AccountId,
+			fraction: Perbill,
+			era: EraIndex,
+		) -> Option<SlashSimulation<AccountId, Balance>>;
+
+		/// The validators nominated by `stash`.
+		///
+		/// Returns `None` if `stash` is not a nominator.
+		fn nominations(stash: AccountId) -> Option<Vec<AccountId>>;
+
+		/// The exposure of `validator` in `era` with the nominators of all pages.
+		///
+		/// Returns `None` if `validator` was not elected in `era` or the era was already cleared.
+		fn eras_stakers(era: EraIndex, validator: AccountId) -> Option<Exposure<AccountId, Balance>>;
+
+		/// The era that is currently active.
+		fn active_era() -> Option<ActiveEraInfo>;
+
+		/// The pages of the exposure of `validator` in `era` whose rewards can still be paid out.
+		fn pending_payout_pages(era: EraIndex, validator: AccountId) -> Vec<PageIndex>;
+	}
+}
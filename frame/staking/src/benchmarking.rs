@@ -29,10 +29,10 @@ use frame_support::{
 	traits::{Currency, CurrencyToVote, Get, Imbalance},
 };
 use sp_runtime::{
-	traits::{Bounded, One, StaticLookup, TrailingZeroInput, Zero},
+	traits::{Bounded, Hash, One, StaticLookup, TrailingZeroInput, Zero},
 	Perbill, Percent,
 };
-use sp_staking::SessionIndex;
+use sp_staking::{EraIndex, SessionIndex};
 use sp_std::prelude::*;
 
 pub use frame_benchmarking::{
@@ -403,8 +403,11 @@ benchmarks! {
 			.map(|n| T::Lookup::unlookup(n.clone()))
 			.collect::<Vec<_>>();
 
+		// the longest possible reason is cloned into every event.
+		let reason: KickReason = vec![0u8; MAX_KICK_REASON_LEN as usize].try_into().unwrap();
+
 		whitelist_account!(controller);
-	}: _(RawOrigin::Signed(controller), kicks)
+	}: _(RawOrigin::Signed(controller), kicks, reason)
 	verify {
 		// all nominators now should *not* be nominating our validator...
 		for n in nominator_stashes.iter() {
@@ -836,6 +839,8 @@ benchmarks! {
 		ConfigOp::Set(u32::MAX),
 		ConfigOp::Set(u32::MAX),
 		ConfigOp::Set(Percent::max_value()),
+		ConfigOp::Set(Perbill::max_value()),
+		ConfigOp::Set(T::HistoryDepth::get()),
 		ConfigOp::Set(Perbill::max_value())
 	) verify {
 		assert_eq!(MinNominatorBond::<T>::get(), BalanceOf::<T>::max_value());
@@ -844,6 +849,8 @@ benchmarks! {
 		assert_eq!(MaxValidatorsCount::<T>::get(), Some(u32::MAX));
 		assert_eq!(ChillThreshold::<T>::get(), Some(Percent::from_percent(100)));
 		assert_eq!(MinCommission::<T>::get(), Perbill::from_percent(100));
+		assert_eq!(BondingDurationOverride::<T>::get(), Some(T::HistoryDepth::get()));
+		assert_eq!(SlashRewardFraction::<T>::get(), Perbill::max_value());
 	}
 
 	set_staking_configs_all_remove {
@@ -854,6 +861,8 @@ benchmarks! {
 		ConfigOp::Remove,
 		ConfigOp::Remove,
 		ConfigOp::Remove,
+		ConfigOp::Remove,
+		ConfigOp::Remove,
 		ConfigOp::Remove
 	) verify {
 		assert!(!MinNominatorBond::<T>::exists());
@@ -862,6 +871,8 @@ benchmarks! {
 		assert!(!MaxValidatorsCount::<T>::exists());
 		assert!(!ChillThreshold::<T>::exists());
 		assert!(!MinCommission::<T>::exists());
+		assert!(!BondingDurationOverride::<T>::exists());
+		assert!(!SlashRewardFraction::<T>::exists());
 	}
 
 	chill_other {
@@ -885,6 +896,8 @@ benchmarks! {
 			ConfigOp::Set(0),
 			ConfigOp::Set(Percent::from_percent(0)),
 			ConfigOp::Set(Zero::zero()),
+			ConfigOp::Noop,
+			ConfigOp::Noop,
 		)?;
 
 		let caller = whitelisted_caller();
@@ -929,6 +942,59 @@ benchmarks! {
 		assert_eq!(MinCommission::<T>::get(), Perbill::from_percent(100));
 	}
 
+	set_min_nominator_bond_controller {
+		let controller = NominatorBondController {
+			target_saturation: Perbill::from_percent(90),
+			max_step: Perbill::from_percent(10),
+			floor: One::one(),
+			ceiling: BalanceOf::<T>::max_value(),
+		};
+	}: _(RawOrigin::Root, Some(controller.clone()))
+	verify {
+		assert_eq!(MinNominatorBondController::<T>::get(), Some(controller));
+	}
+
+	start_staker_import {
+		let checksum = T::Hashing::hash_of(&0u32);
+	}: _(RawOrigin::Root, MAX_STAKER_IMPORT_BATCH, checksum)
+	verify {
+		assert_eq!(StakerImport::<T>::get().map(|p| p.total), Some(MAX_STAKER_IMPORT_BATCH));
+	}
+
+	import_stakers {
+		// the worst case is a batch of nominators with the maximum number of targets.
+		let n in 1 .. MAX_STAKER_IMPORT_BATCH;
+		clear_validators_and_nominators::<T>();
+		let targets = create_validators::<T>(T::MaxNominations::get(), 100)?
+			.into_iter()
+			.map(|v| T::Lookup::lookup(v).unwrap())
+			.collect::<Vec<_>>();
+		let stake = MinNominatorBond::<T>::get().max(T::Currency::minimum_balance());
+		let mut checksum = T::Hash::default();
+		let stakers = (0 .. n)
+			.map(|i| {
+				let stash = create_funded_user_with_balance::<T>("import_stash", i, stake * 10u32.into());
+				let controller = account("import_controller", i, SEED);
+				let staker = (stash, controller, stake, StakerStatus::Nominator(targets.clone()));
+				checksum = T::Hashing::hash_of(&(checksum, &staker));
+				staker
+			})
+			.collect::<Vec<_>>();
+		Staking::<T>::start_staker_import(RawOrigin::Root.into(), n, checksum)?;
+		let stakers: BoundedVec<_, _> = stakers.try_into().unwrap();
+	}: _(RawOrigin::Root, 0, stakers)
+	verify {
+		assert!(!StakerImport::<T>::exists());
+		assert_eq!(Nominators::<T>::count(), n);
+	}
+
+	cancel_staker_import {
+		Staking::<T>::start_staker_import(RawOrigin::Root.into(), 1, Default::default())?;
+	}: _(RawOrigin::Root)
+	verify {
+		assert!(!StakerImport::<T>::exists());
+	}
+
 	impl_benchmark_test_suite!(
 		Staking,
 		crate::mock::ExtBuilder::default().has_stakers(true),
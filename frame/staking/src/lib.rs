@@ -114,9 +114,10 @@
 //!
 //! Rewards must be claimed for each era before it gets too old by `$HISTORY_DEPTH` using the
 //! `payout_stakers` call. Any account can call `payout_stakers`, which pays the reward to the
-//! validator as well as its nominators. Only the [`Config::MaxNominatorRewardedPerValidator`]
-//! biggest stakers can claim their reward. This is to limit the i/o cost to mutate storage for each
-//! nominator's account.
+//! validator as well as its nominators. To limit the i/o cost to mutate storage for each
+//! nominator's account, the nominators are paid out in pages of [`Config::MaxExposurePageSize`]
+//! stakers with the biggest stakers first. Each call pays out the next page. A specific page can
+//! be paid out with `payout_stakers_by_page`.
 //!
 //! Slashing can occur at any point in time, once misbehavior is reported. Once slashing is
 //! determined, a value is deducted from the balance of the validator and all the nominators who
@@ -224,13 +225,10 @@
 //! The validator can declare an amount, named [`commission`](ValidatorPrefs::commission), that does
 //! not get shared with the nominators at each reward payout through its [`ValidatorPrefs`]. This
 //! value gets deducted from the total reward that is paid to the validator and its nominators. The
-//! remaining portion is split pro rata among the validator and the top
-//! [`Config::MaxNominatorRewardedPerValidator`] nominators that nominated the validator,
-//! proportional to the value staked behind the validator (_i.e._ dividing the
+//! remaining portion is split pro rata among the validator and the nominators that nominated the
+//! validator, proportional to the value staked behind the validator (_i.e._ dividing the
 //! [`own`](Exposure::own) or [`others`](Exposure::others) by [`total`](Exposure::total) in
-//! [`Exposure`]). Note that the pro rata division of rewards uses the total exposure behind the
-//! validator, *not* just the exposure of the validator and the top
-//! [`Config::MaxNominatorRewardedPerValidator`] nominators.
+//! [`Exposure`]). The validator is paid out together with the first page of its nominators.
 //!
 //! All entities who receive a reward have the option to choose their reward destination through the
 //! [`Payee`] storage item (see
@@ -284,7 +282,7 @@
 
 #[cfg(feature = "runtime-benchmarks")]
 pub mod benchmarking;
-#[cfg(any(feature = "runtime-benchmarks", test))]
+#[cfg(any(feature = "runtime-benchmarks", feature = "testing-utils", test))]
 pub mod testing_utils;
 
 #[cfg(test)]
@@ -301,7 +299,7 @@ mod pallet;
 
 use codec::{Decode, Encode, HasCompact, MaxEncodedLen};
 use frame_support::{
-	traits::{Currency, Defensive, Get},
+	traits::{ConstU32, Currency, Defensive, Get, OnUnbalanced},
 	weights::Weight,
 	BoundedVec, CloneNoBound, EqNoBound, PartialEqNoBound, RuntimeDebugNoBound,
 };
@@ -340,6 +338,23 @@ pub type MaxWinnersOf<T> = <<T as Config>::ElectionProvider as frame_election_pr
 /// Counter for the number of "reward" points earned by a given validator.
 pub type RewardPoint = u32;
 
+/// The maximum length in bytes of the reason a validator can attach when kicking nominators.
+pub const MAX_KICK_REASON_LEN: u32 = 128;
+
+/// A free form reason attached to [`Call::kick`] and forwarded to the kicked nominators.
+pub type KickReason = BoundedVec<u8, ConstU32<MAX_KICK_REASON_LEN>>;
+
+/// The maximum number of stakers that can be imported by a single [`Call::import_stakers`].
+pub const MAX_STAKER_IMPORT_BATCH: u32 = 256;
+
+/// A staker imported by [`Call::import_stakers`]: stash, controller, bond and status.
+pub type ImportedStaker<T> = (
+	<T as frame_system::Config>::AccountId,
+	<T as frame_system::Config>::AccountId,
+	BalanceOf<T>,
+	StakerStatus<<T as frame_system::Config>::AccountId>,
+);
+
 /// The balance type of this pallet.
 pub type BalanceOf<T> = <T as Config>::CurrencyBalance;
 
@@ -381,9 +396,66 @@ impl<AccountId: Ord> Default for EraRewardPoints<AccountId> {
 	}
 }
 
+/// The rewards of a single validator in an era.
+///
+/// Computed from the era payout, the reward points and the commission of the validator once the
+/// era has ended, so that no exposure needs to be read to know what a validator is owed.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct ValidatorRewardSummary<Balance> {
+	/// The validator's fraction of the reward points of the era.
+	pub points_share: Perbill,
+	/// The commission the validator takes off the top of its part of the era payout.
+	pub commission: Balance,
+	/// What is left of the validator's part after commission.
+	///
+	/// This is split between the validator's own stake and its nominators according to their
+	/// exposure.
+	pub nominator_pool: Balance,
+}
+
+/// Parameters of the automatic adjustment of `MinNominatorBond`.
+///
+/// At the start of every era the number of voters is compared with the voter limit of the last
+/// election snapshot. If more than `target_saturation` of the limit would be used the bond is
+/// raised, otherwise it is lowered. The change is proportional to the distance from the target but
+/// never larger than `max_step` of the current bond. The result is kept within `floor` and
+/// `ceiling`.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct NominatorBondController<Balance> {
+	/// The fraction of the snapshot voter limit that the voters should use.
+	pub target_saturation: Perbill,
+	/// The largest fraction of the current bond it is changed by in a single era.
+	pub max_step: Perbill,
+	/// The bond is never lowered below this. Must not be zero, as the bond changes
+	/// proportionally to the larger of its current value and the floor.
+	pub floor: Balance,
+	/// The bond is never raised above this.
+	pub ceiling: Balance,
+}
+
+/// The progress of a staker import started by [`Call::start_staker_import`].
+///
+/// Stakers are imported in batches by [`Call::import_stakers`]. Every batch has to continue
+/// exactly where the previous one ended which makes an interrupted import resumable from
+/// `imported`.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct StakerImportProgress<Hash> {
+	/// The number of stakers that will be imported in total.
+	pub total: u32,
+	/// The number of stakers imported so far. This is the cursor the next batch starts at.
+	pub imported: u32,
+	/// The checksum `checksum` has to match once all stakers are imported.
+	pub expected_checksum: Hash,
+	/// The running checksum over all stakers imported so far.
+	///
+	/// Starts at the default hash and is updated to `hash((checksum, staker))` for every
+	/// imported staker in order.
+	pub checksum: Hash,
+}
+
 /// Indicates the initial status of the staker.
-#[derive(RuntimeDebug, TypeInfo)]
-#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize, Clone))]
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
 pub enum StakerStatus<AccountId> {
 	/// Chilling.
 	Idle,
@@ -579,7 +651,7 @@ impl<T: Config> StakingLedger<T> {
 
 		// for a `slash_era = x`, any chunk that is scheduled to be unlocked at era `x + 28`
 		// (assuming 28 is the bonding duration) onwards should be slashed.
-		let slashable_chunks_start = slash_era + T::BondingDuration::get();
+		let slashable_chunks_start = slash_era + Pallet::<T>::bonding_duration();
 
 		// `Some(ratio)` if this is proportional, with `ratio`, `None` otherwise. In both cases, we
 		// slash first the active chunk, and then `slash_chunks_priority`.
@@ -732,6 +804,73 @@ impl<AccountId, Balance: Default + HasCompact> Default for Exposure<AccountId, B
 	}
 }
 
+impl<AccountId, Balance: HasCompact + AtLeast32BitUnsigned + Copy + MaxEncodedLen>
+	Exposure<AccountId, Balance>
+{
+	/// Split the exposure into an overview and pages of at most `page_size` nominators each.
+	///
+	/// Nominators are sorted by their exposed stake in descending order first, so that the
+	/// biggest stakers end up on the first pages.
+	pub fn into_pages(
+		mut self,
+		page_size: u32,
+	) -> (PagedExposureMetadata<Balance>, Vec<ExposurePage<AccountId, Balance>>) {
+		let page_size = page_size.max(1) as usize;
+		self.others.sort_by(|a, b| b.value.cmp(&a.value));
+
+		let nominator_count = self.others.len() as u32;
+		let mut pages = Vec::with_capacity(self.others.len() / page_size + 1);
+		let mut others = self.others.into_iter().peekable();
+		while others.peek().is_some() {
+			let others: Vec<_> = others.by_ref().take(page_size).collect();
+			let page_total =
+				others.iter().fold(Zero::zero(), |acc: Balance, e| acc.saturating_add(e.value));
+			pages.push(ExposurePage { page_total, others });
+		}
+
+		(
+			PagedExposureMetadata {
+				total: self.total,
+				own: self.own,
+				nominator_count,
+				page_count: pages.len() as PageIndex,
+			},
+			pages,
+		)
+	}
+}
+
+/// Index of a page of nominators exposed to a validator.
+pub type PageIndex = u32;
+
+/// Overview of the exposure of a validator whose nominators are stored in pages.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo, Default, MaxEncodedLen)]
+pub struct PagedExposureMetadata<Balance: HasCompact + MaxEncodedLen> {
+	/// The total balance backing this validator.
+	#[codec(compact)]
+	pub total: Balance,
+	/// The validator's own stash that is exposed.
+	#[codec(compact)]
+	pub own: Balance,
+	/// Number of nominators backing this validator.
+	pub nominator_count: u32,
+	/// Number of pages of nominators.
+	pub page_count: PageIndex,
+}
+
+/// A page of the nominators exposed to a validator.
+///
+/// Pages are stored separately from the [`PagedExposureMetadata`] so that each of them can be
+/// read and paid out independently.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct ExposurePage<AccountId, Balance: HasCompact> {
+	/// The total balance of the nominators on this page.
+	#[codec(compact)]
+	pub page_total: Balance,
+	/// The portions of nominators stashes that are exposed.
+	pub others: Vec<IndividualExposure<AccountId, Balance>>,
+}
+
 /// A pending slash record. The value of the slash has been computed but not applied yet,
 /// rather deferred for several eras.
 #[derive(Encode, Decode, RuntimeDebug, TypeInfo)]
@@ -761,6 +900,25 @@ impl<AccountId, Balance: HasCompact + Zero> UnappliedSlash<AccountId, Balance> {
 	}
 }
 
+/// How a hypothetical slash of a validator would be apportioned.
+///
+/// Computed by [`Pallet::simulate_slash`] without applying anything.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct SlashSimulation<AccountId, Balance> {
+	/// The amount slashed from the validator's own stake.
+	pub own: Balance,
+	/// The amounts slashed from the nominators backing the validator.
+	pub others: Vec<(AccountId, Balance)>,
+	/// The amount that would be paid out to the reporters of the offence.
+	pub payout: Balance,
+}
+
+impl<AccountId, Balance: Zero> Default for SlashSimulation<AccountId, Balance> {
+	fn default() -> Self {
+		Self { own: Zero::zero(), others: vec![], payout: Zero::zero() }
+	}
+}
+
 /// Means for interacting with a specialized version of the `session` trait.
 ///
 /// This is needed because `Staking` sets the `ValidatorIdOf` of the `pallet_session::Config`
@@ -859,6 +1017,28 @@ impl<Balance: AtLeast32BitUnsigned + Clone, T: Get<&'static PiecewiseLinear<'sta
 	}
 }
 
+/// Handler for the dust that `withdraw_unbonded` releases from an active bond.
+pub trait DustHandler<AccountId, Balance> {
+	/// Handle `amount` of dust that `stash` no longer has bonded.
+	fn handle_dust(stash: &AccountId, amount: Balance);
+}
+
+/// Leaves the released dust with the stash.
+impl<AccountId, Balance> DustHandler<AccountId, Balance> for () {
+	fn handle_dust(_stash: &AccountId, _amount: Balance) {}
+}
+
+/// Moves the released dust from the stash to an [`OnUnbalanced`] handler, e.g. the treasury.
+pub struct DustToUnbalanced<T, H>(sp_std::marker::PhantomData<(T, H)>);
+impl<T: Config, H: OnUnbalanced<NegativeImbalanceOf<T>>> DustHandler<T::AccountId, BalanceOf<T>>
+	for DustToUnbalanced<T, H>
+{
+	fn handle_dust(stash: &T::AccountId, amount: BalanceOf<T>) {
+		let (imbalance, _) = T::Currency::slash(stash, amount);
+		H::on_unbalanced(imbalance);
+	}
+}
+
 /// Mode of era-forcing.
 #[derive(Copy, Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
 #[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
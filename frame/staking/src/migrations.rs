@@ -52,6 +52,71 @@ impl Default for ObsoleteReleases {
 #[storage_alias]
 type StorageVersion<T: Config> = StorageValue<Pallet<T>, ObsoleteReleases, ValueQuery>;
 
+/// Remove era information orphaned by past reductions of [`Config::HistoryDepth`].
+///
+/// Whenever a new era is planned only the single era that just fell out of the history depth
+/// is cleared. Hence, reducing `HistoryDepth` leaves all eras between the old and the new bound
+/// in storage forever. This migration does not remove anything by itself. It merely points
+/// [`OrphanedErasCursor`] at the most recent era outside of the history depth. From there
+/// all older eras are cleared lazily in `on_idle`.
+///
+/// It does not depend on the storage version and can be included whenever `HistoryDepth` was
+/// reduced.
+pub struct PruneOrphanedEras<T>(sp_std::marker::PhantomData<T>);
+impl<T: Config> OnRuntimeUpgrade for PruneOrphanedEras<T> {
+	fn on_runtime_upgrade() -> Weight {
+		let newest_orphan = CurrentEra::<T>::get()
+			.and_then(|era| era.checked_sub(T::HistoryDepth::get()))
+			.and_then(|era| era.checked_sub(1));
+
+		if let Some(era) = newest_orphan {
+			OrphanedErasCursor::<T>::put(era);
+			log!(info, "pruning eras up to {} lazily", era);
+			T::DbWeight::get().reads_writes(1, 1)
+		} else {
+			log!(info, "no eras outside of the history depth, nothing to prune");
+			T::DbWeight::get().reads(1)
+		}
+	}
+}
+
+pub mod v14 {
+	use super::*;
+
+	/// Introduce paged exposures.
+	///
+	/// This does not touch any exposure. Eras planned from now on are stored in pages in
+	/// [`ErasStakersPaged`], while the clipped exposures of older eras are split into pages
+	/// lazily whenever they are read through [`EraInfo`]. They are removed together with the
+	/// rest of the era after `HistoryDepth` eras.
+	pub struct MigrateToV14<T>(sp_std::marker::PhantomData<T>);
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV14<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let current = Pallet::<T>::current_storage_version();
+			let onchain = Pallet::<T>::on_chain_storage_version();
+
+			if current == 14 && onchain == 13 {
+				current.put::<Pallet<T>>();
+
+				log!(info, "v14 applied successfully");
+				T::DbWeight::get().reads_writes(1, 1)
+			} else {
+				log!(warn, "Skipping v14, should be removed");
+				T::DbWeight::get().reads(1)
+			}
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(_state: Vec<u8>) -> Result<(), &'static str> {
+			frame_support::ensure!(
+				Pallet::<T>::on_chain_storage_version() == 14,
+				"v14 not applied"
+			);
+			Ok(())
+		}
+	}
+}
+
 pub mod v13 {
 	use super::*;
 
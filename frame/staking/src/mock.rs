@@ -96,7 +96,7 @@ frame_support::construct_runtime!(
 		Timestamp: pallet_timestamp::{Pallet, Call, Storage, Inherent},
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
 		Staking: pallet_staking::{Pallet, Call, Config<T>, Storage, Event<T>},
-		Session: pallet_session::{Pallet, Call, Storage, Event, Config<T>},
+		Session: pallet_session::{Pallet, Call, Storage, Event<T>, Config<T>},
 		Historical: pallet_session::historical::{Pallet, Storage},
 		VoterBagsList: pallet_bags_list::<Instance1>::{Pallet, Call, Storage, Event<T>},
 	}
@@ -167,6 +167,9 @@ sp_runtime::impl_opaque_keys! {
 impl pallet_session::Config for Test {
 	type SessionManager = pallet_session::historical::NoteHistoricalRoot<Test, Staking>;
 	type Keys = SessionKeys;
+	type KeyPurgeDelay = ConstU32<0>;
+	type MaxPurgesPerSession = ConstU32<0>;
+	type PurgeKeysOrigin = frame_system::EnsureRoot<Self::AccountId>;
 	type ShouldEndSession = pallet_session::PeriodicSessions<Period, Offset>;
 	type SessionHandler = (OtherSessionHandler,);
 	type RuntimeEvent = RuntimeEvent;
@@ -179,6 +182,8 @@ impl pallet_session::Config for Test {
 impl pallet_session::historical::Config for Test {
 	type FullIdentification = crate::Exposure<AccountId, Balance>;
 	type FullIdentificationOf = crate::ExposureOf<Test>;
+	type MaxStoredSessions = ConstU32<1_000>;
+	type MaxKeyOwnershipProofSize = ConstU32<{ 64 * 1024 }>;
 }
 impl pallet_authorship::Config for Test {
 	type FindAuthor = Author11;
@@ -225,6 +230,27 @@ impl OnUnbalanced<NegativeImbalanceOf<Test>> for RewardRemainderMock {
 	}
 }
 
+parameter_types! {
+	pub static DustToTreasury: bool = false;
+	pub static DustTreasuryUnbalanced: Balance = 0;
+}
+
+pub struct DustTreasuryMock;
+impl OnUnbalanced<NegativeImbalanceOf<Test>> for DustTreasuryMock {
+	fn on_nonzero_unbalanced(amount: NegativeImbalanceOf<Test>) {
+		DustTreasuryUnbalanced::mutate(|v| *v += amount.peek());
+	}
+}
+
+pub struct MockDustHandler;
+impl DustHandler<AccountId, Balance> for MockDustHandler {
+	fn handle_dust(stash: &AccountId, amount: Balance) {
+		if DustToTreasury::get() {
+			DustToUnbalanced::<Test, DustTreasuryMock>::handle_dust(stash, amount);
+		}
+	}
+}
+
 const THRESHOLDS: [sp_npos_elections::VoteWeight; 9] =
 	[10, 20, 30, 40, 50, 60, 1_000, 2_000, 10_000];
 
@@ -236,6 +262,8 @@ parameter_types! {
 	pub static RewardOnUnbalanceWasCalled: bool = false;
 	pub static LedgerSlashPerEra: (BalanceOf<Test>, BTreeMap<EraIndex, BalanceOf<Test>>) = (Zero::zero(), BTreeMap::new());
 	pub static MaxWinners: u32 = 100;
+	pub static MaxExposurePageSize: u32 = 64;
+	pub static MaxElectingVoters: u32 = u32::MAX;
 }
 
 type VoterBagsListInstance = pallet_bags_list::Instance1;
@@ -255,7 +283,7 @@ impl onchain::Config for OnChainSeqPhragmen {
 	type DataProvider = Staking;
 	type WeightInfo = ();
 	type MaxWinners = MaxWinners;
-	type VotersBound = ConstU32<{ u32::MAX }>;
+	type VotersBound = MaxElectingVoters;
 	type TargetsBound = ConstU32<{ u32::MAX }>;
 }
 
@@ -293,8 +321,10 @@ impl crate::pallet::pallet::Config for Test {
 	type BondingDuration = BondingDuration;
 	type SessionInterface = Self;
 	type EraPayout = ConvertCurve<RewardCurve>;
+	type DustHandler = MockDustHandler;
 	type NextNewSession = Session;
 	type MaxNominatorRewardedPerValidator = ConstU32<64>;
+	type MaxExposurePageSize = MaxExposurePageSize;
 	type OffendingValidatorsThreshold = OffendingValidatorsThreshold;
 	type ElectionProvider = onchain::OnChainExecution<OnChainSeqPhragmen>;
 	type GenesisElectionProvider = Self::ElectionProvider;
@@ -420,7 +450,7 @@ impl ExtBuilder {
 		self.balance_factor = factor;
 		self
 	}
-	fn build(self) -> sp_io::TestExternalities {
+	pub(crate) fn build(self) -> sp_io::TestExternalities {
 		sp_tracing::try_init_simple();
 		let mut storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
 
@@ -34,7 +34,7 @@ use frame_system::{pallet_prelude::BlockNumberFor, RawOrigin};
 use pallet_session::historical;
 use sp_runtime::{
 	traits::{Bounded, Convert, One, SaturatedConversion, Saturating, StaticLookup, Zero},
-	Perbill,
+	Perbill, TransactionOutcome,
 };
 use sp_staking::{
 	offence::{DisableStrategy, OffenceDetails, OnOffenceHandler},
@@ -43,9 +43,11 @@ use sp_staking::{
 use sp_std::prelude::*;
 
 use crate::{
-	log, slashing, weights::WeightInfo, ActiveEraInfo, BalanceOf, EraPayout, Exposure, ExposureOf,
-	Forcing, IndividualExposure, MaxWinnersOf, Nominations, PositiveImbalanceOf, RewardDestination,
-	SessionInterface, StakingLedger, ValidatorPrefs,
+	log, slashing, weights::WeightInfo, ActiveEraInfo, BalanceOf, DustHandler, EraPayout, Exposure,
+	ExposureOf, ExposurePage, Forcing, ImportedStaker, IndividualExposure, MaxWinnersOf,
+	Nominations, PageIndex, PagedExposureMetadata, PositiveImbalanceOf, RewardDestination,
+	SessionInterface, SlashSimulation, StakerStatus, StakingLedger, ValidatorPrefs,
+	ValidatorRewardSummary,
 };
 
 use super::{pallet::*, STAKING_ID};
@@ -58,7 +60,136 @@ use super::{pallet::*, STAKING_ID};
 /// times and then give up.
 const NPOS_MAX_ITERATIONS_COEFFICIENT: u32 = 2;
 
+/// Wrapper around the storage items that hold the exposures of validators per era.
+///
+/// Exposures of eras planned before exposures were paged only exist in [`ErasStakers`] and
+/// [`ErasStakersClipped`]. The clipped exposures are lazily split into pages of
+/// `T::MaxExposurePageSize` nominators when read, so that callers see the same paged view for
+/// every era in the history.
+pub struct EraInfo<T>(sp_std::marker::PhantomData<T>);
+
+impl<T: Config> EraInfo<T> {
+	/// Overview of the exposure of `validator` in `era`.
+	///
+	/// Returns `None` if `validator` was not elected in `era` or the era was already cleared.
+	pub fn get_exposure_overview(
+		era: EraIndex,
+		validator: &T::AccountId,
+	) -> Option<PagedExposureMetadata<BalanceOf<T>>> {
+		ErasStakersOverview::<T>::get(era, validator)
+			.or_else(|| Self::clipped_as_pages(era, validator).map(|(overview, _)| overview))
+	}
+
+	/// The nominators on `page` of the exposure of `validator` in `era`.
+	///
+	/// Returns `None` if the page does not exist.
+	pub fn get_paged_exposure(
+		era: EraIndex,
+		validator: &T::AccountId,
+		page: PageIndex,
+	) -> Option<ExposurePage<T::AccountId, BalanceOf<T>>> {
+		if ErasStakersOverview::<T>::contains_key(era, validator) {
+			return ErasStakersPaged::<T>::get((era, validator, page))
+		}
+		Self::clipped_as_pages(era, validator).and_then(|(_, mut pages)| {
+			(page < pages.len() as PageIndex).then(|| pages.swap_remove(page as usize))
+		})
+	}
+
+	/// The exposure of `validator` in `era` with the nominators of all pages.
+	///
+	/// Returns `None` if `validator` was not elected in `era` or the era was already cleared.
+	pub fn get_full_exposure(
+		era: EraIndex,
+		validator: &T::AccountId,
+	) -> Option<Exposure<T::AccountId, BalanceOf<T>>> {
+		if !ErasStakersOverview::<T>::contains_key(era, validator) &&
+			ErasStakers::<T>::contains_key(era, validator)
+		{
+			return Some(ErasStakers::<T>::get(era, validator))
+		}
+		let overview = Self::get_exposure_overview(era, validator)?;
+		let others = (0..overview.page_count)
+			.filter_map(|page| Self::get_paged_exposure(era, validator, page))
+			.flat_map(|page| page.others)
+			.collect();
+		Some(Exposure { total: overview.total, own: overview.own, others })
+	}
+
+	/// The number of pages that need to be paid out for `validator` in `era`.
+	///
+	/// This is at least one so that validators without nominators can claim their own reward.
+	pub fn get_page_count(era: EraIndex, validator: &T::AccountId) -> PageIndex {
+		Self::get_exposure_overview(era, validator).map_or(1, |overview| overview.page_count.max(1))
+	}
+
+	/// Whether the rewards of `page` of the exposure of the owner of `ledger` in `era` were
+	/// already claimed.
+	pub fn is_rewards_claimed(era: EraIndex, ledger: &StakingLedger<T>, page: PageIndex) -> bool {
+		if ledger.claimed_rewards.binary_search(&era).is_err() {
+			return false
+		}
+		// Eras claimed before rewards were paid out by page have no pages recorded.
+		let pages = ClaimedRewards::<T>::get(era, &ledger.stash);
+		pages.is_empty() || pages.contains(&page)
+	}
+
+	/// The first page of the exposure of the owner of `ledger` in `era` that was not claimed.
+	pub fn get_next_claimable_page(era: EraIndex, ledger: &StakingLedger<T>) -> Option<PageIndex> {
+		(0..Self::get_page_count(era, &ledger.stash))
+			.find(|page| !Self::is_rewards_claimed(era, ledger, *page))
+	}
+
+	/// Store the exposure of `validator` in `era` in pages.
+	///
+	/// Returns the number of pages written.
+	pub(crate) fn set_exposure(
+		era: EraIndex,
+		validator: &T::AccountId,
+		exposure: Exposure<T::AccountId, BalanceOf<T>>,
+	) -> PageIndex {
+		let (overview, pages) = exposure.into_pages(T::MaxExposurePageSize::get());
+		let page_count = overview.page_count;
+		ErasStakersOverview::<T>::insert(era, validator, overview);
+		for (page, exposure_page) in pages.into_iter().enumerate() {
+			ErasStakersPaged::<T>::insert((era, validator, page as PageIndex), exposure_page);
+		}
+		page_count
+	}
+
+	/// Split the clipped exposure of an era that predates paged exposures.
+	fn clipped_as_pages(
+		era: EraIndex,
+		validator: &T::AccountId,
+	) -> Option<(PagedExposureMetadata<BalanceOf<T>>, Vec<ExposurePage<T::AccountId, BalanceOf<T>>>)>
+	{
+		if !ErasStakersClipped::<T>::contains_key(era, validator) {
+			return None
+		}
+		Some(ErasStakersClipped::<T>::get(era, validator).into_pages(T::MaxExposurePageSize::get()))
+	}
+}
+
 impl<T: Config> Pallet<T> {
+	/// The exposure of `validator` in `era` with the nominators of all pages.
+	///
+	/// Returns an empty exposure if `validator` was not elected in `era`. See
+	/// [`EraInfo::get_full_exposure`].
+	pub fn eras_stakers(
+		era: EraIndex,
+		validator: impl sp_std::borrow::Borrow<T::AccountId>,
+	) -> Exposure<T::AccountId, BalanceOf<T>> {
+		EraInfo::<T>::get_full_exposure(era, validator.borrow()).unwrap_or_default()
+	}
+
+	/// The number of eras that funds unbonded now remain locked for.
+	///
+	/// This is [`BondingDurationOverride`] if set by governance and [`Config::BondingDuration`]
+	/// otherwise.
+	pub fn bonding_duration() -> EraIndex {
+		BondingDurationOverride::<T>::get().unwrap_or_else(T::BondingDuration::get)
+	}
+
 	/// The total balance that can be slashed from a stash account as of right now.
 	pub fn slashable_balance_of(stash: &T::AccountId) -> BalanceOf<T> {
 		// Weight note: consider making the stake accessible through stash.
@@ -102,6 +233,19 @@ impl<T: Config> Pallet<T> {
 			ledger = ledger.consolidate_unlocked(current_era)
 		}
 
+		// An active bond below the existential deposit can not be represented on its own, but
+		// would keep the stash in the voter list. Release it right away.
+		let mut dust: BalanceOf<T> = Zero::zero();
+		if !ledger.unlocking.is_empty() &&
+			!ledger.active.is_zero() &&
+			ledger.active < T::Currency::minimum_balance()
+		{
+			dust = sp_std::mem::replace(&mut ledger.active, Zero::zero());
+			ledger.total = ledger.total.saturating_sub(dust);
+			Self::chill_stash(&stash);
+			Self::deposit_event(Event::<T>::DustSwept { stash: stash.clone(), amount: dust });
+		}
+
 		let used_weight =
 			if ledger.unlocking.is_empty() && ledger.active < T::Currency::minimum_balance() {
 				// This account must have called `unbond()` with some value that caused the active
@@ -116,24 +260,46 @@ impl<T: Config> Pallet<T> {
 				// This was the consequence of a partial unbond. just update the ledger and move on.
 				Self::update_ledger(&controller, &ledger);
 
-				// This is only an update, so we use less overall weight.
-				T::WeightInfo::withdraw_unbonded_update(num_slashing_spans)
+				if dust.is_zero() {
+					// This is only an update, so we use less overall weight.
+					T::WeightInfo::withdraw_unbonded_update(num_slashing_spans)
+				} else {
+					T::DustHandler::handle_dust(&stash, dust);
+					// Chilling touches the same storage as removing the stash.
+					T::WeightInfo::withdraw_unbonded_kill(num_slashing_spans)
+				}
 			};
 
 		// `old_total` should never be less than the new total because
 		// `consolidate_unlocked` strictly subtracts balance.
-		if ledger.total < old_total {
+		if ledger.total.saturating_add(dust) < old_total {
 			// Already checked that this won't overflow by entry condition.
-			let value = old_total - ledger.total;
+			let value = old_total - ledger.total - dust;
 			Self::deposit_event(Event::<T>::Withdrawn { stash, amount: value });
 		}
 
 		Ok(used_weight)
 	}
 
+	/// Pay out the first page of `validator_stash` in `era` that was not claimed yet.
 	pub(super) fn do_payout_stakers(
 		validator_stash: T::AccountId,
 		era: EraIndex,
+	) -> DispatchResultWithPostInfo {
+		let controller = Self::bonded(&validator_stash).ok_or_else(|| {
+			Error::<T>::NotStash.with_weight(T::WeightInfo::payout_stakers_alive_staked(0))
+		})?;
+		let ledger = <Ledger<T>>::get(&controller).ok_or(Error::<T>::NotController)?;
+		let page = EraInfo::<T>::get_next_claimable_page(era, &ledger).ok_or_else(|| {
+			Error::<T>::AlreadyClaimed.with_weight(T::WeightInfo::payout_stakers_alive_staked(0))
+		})?;
+		Self::do_payout_stakers_by_page(validator_stash, era, page)
+	}
+
+	pub(super) fn do_payout_stakers_by_page(
+		validator_stash: T::AccountId,
+		era: EraIndex,
+		page: PageIndex,
 	) -> DispatchResultWithPostInfo {
 		// Validate input data
 		let current_era = CurrentEra::<T>::get().ok_or_else(|| {
@@ -163,24 +329,36 @@ impl<T: Config> Pallet<T> {
 			.claimed_rewards
 			.retain(|&x| x >= current_era.saturating_sub(history_depth));
 
-		match ledger.claimed_rewards.binary_search(&era) {
-			Ok(_) =>
-				return Err(Error::<T>::AlreadyClaimed
-					.with_weight(T::WeightInfo::payout_stakers_alive_staked(0))),
-			Err(pos) => ledger
+		ensure!(
+			page < EraInfo::<T>::get_page_count(era, &ledger.stash),
+			Error::<T>::InvalidPage.with_weight(T::WeightInfo::payout_stakers_alive_staked(0))
+		);
+		ensure!(
+			!EraInfo::<T>::is_rewards_claimed(era, &ledger, page),
+			Error::<T>::AlreadyClaimed.with_weight(T::WeightInfo::payout_stakers_alive_staked(0))
+		);
+
+		// The era is recorded in the ledger as soon as its first page is claimed. The claimed
+		// pages are tracked in `ClaimedRewards`.
+		if let Err(pos) = ledger.claimed_rewards.binary_search(&era) {
+			ledger
 				.claimed_rewards
 				.try_insert(pos, era)
 				// Since we retain era entries in `claimed_rewards` only upto
 				// `HistoryDepth`, following bound is always expected to be
 				// satisfied.
-				.defensive_map_err(|_| Error::<T>::BoundNotMet)?,
+				.defensive_map_err(|_| Error::<T>::BoundNotMet)?;
 		}
 
-		let exposure = <ErasStakersClipped<T>>::get(&era, &ledger.stash);
+		let overview = EraInfo::<T>::get_exposure_overview(era, &ledger.stash).unwrap_or_default();
+		let nominators = EraInfo::<T>::get_paged_exposure(era, &ledger.stash, page)
+			.map(|exposure_page| exposure_page.others)
+			.unwrap_or_default();
 
 		// Input data seems good, no errors allowed after this point
 
 		<Ledger<T>>::insert(&controller, &ledger);
+		ClaimedRewards::<T>::append(era, &ledger.stash, page);
 
 		// Get Era reward points. It has TOTAL and INDIVIDUAL
 		// Find the fraction of the era reward that belongs to the validator
@@ -216,9 +394,6 @@ impl<T: Config> Pallet<T> {
 		let validator_commission_payout = validator_commission * validator_total_payout;
 
 		let validator_leftover_payout = validator_total_payout - validator_commission_payout;
-		// Now let's calculate how this is split to the validator.
-		let validator_exposure_part = Perbill::from_rational(exposure.own, exposure.total);
-		let validator_staking_payout = validator_exposure_part * validator_leftover_payout;
 
 		Self::deposit_event(Event::<T>::PayoutStarted {
 			era_index: era,
@@ -226,15 +401,23 @@ impl<T: Config> Pallet<T> {
 		});
 
 		let mut total_imbalance = PositiveImbalanceOf::<T>::zero();
-		// We can now make total validator payout:
-		if let Some(imbalance) =
-			Self::make_payout(&ledger.stash, validator_staking_payout + validator_commission_payout)
-		{
-			Self::deposit_event(Event::<T>::Rewarded {
-				stash: ledger.stash,
-				amount: imbalance.peek(),
-			});
-			total_imbalance.subsume(imbalance);
+		// The validator is paid out together with the first page.
+		if page.is_zero() {
+			// Now let's calculate how this is split to the validator.
+			let validator_exposure_part = Perbill::from_rational(overview.own, overview.total);
+			let validator_staking_payout = validator_exposure_part * validator_leftover_payout;
+
+			// We can now make total validator payout:
+			if let Some(imbalance) = Self::make_payout(
+				&ledger.stash,
+				validator_staking_payout + validator_commission_payout,
+			) {
+				Self::deposit_event(Event::<T>::Rewarded {
+					stash: ledger.stash,
+					amount: imbalance.peek(),
+				});
+				total_imbalance.subsume(imbalance);
+			}
 		}
 
 		// Track the number of payout ops to nominators. Note:
@@ -242,10 +425,9 @@ impl<T: Config> Pallet<T> {
 		// out, so we do not need to count their payout op.
 		let mut nominator_payout_count: u32 = 0;
 
-		// Lets now calculate how this is split to the nominators.
-		// Reward only the clipped exposures. Note this is not necessarily sorted.
-		for nominator in exposure.others.iter() {
-			let nominator_exposure_part = Perbill::from_rational(nominator.value, exposure.total);
+		// Lets now calculate how this is split to the nominators on this page.
+		for nominator in nominators.iter() {
+			let nominator_exposure_part = Perbill::from_rational(nominator.value, overview.total);
 
 			let nominator_reward: BalanceOf<T> =
 				nominator_exposure_part * validator_leftover_payout;
@@ -261,7 +443,7 @@ impl<T: Config> Pallet<T> {
 		}
 
 		T::Reward::on_unbalanced(total_imbalance);
-		debug_assert!(nominator_payout_count <= T::MaxNominatorRewardedPerValidator::get());
+		debug_assert!(nominator_payout_count <= T::MaxExposurePageSize::get());
 		Ok(Some(T::WeightInfo::payout_stakers_alive_staked(nominator_payout_count)).into())
 	}
 
@@ -406,7 +588,7 @@ impl<T: Config> Pallet<T> {
 			new_index
 		});
 
-		let bonding_duration = T::BondingDuration::get();
+		let bonding_duration = Self::bonding_duration();
 
 		BondedEras::<T>::mutate(|bonded| {
 			bonded.push((active_era, start_session));
@@ -430,6 +612,39 @@ impl<T: Config> Pallet<T> {
 		});
 
 		Self::apply_unapplied_slashes(active_era);
+		Self::adjust_min_nominator_bond();
+	}
+
+	/// Adjust `MinNominatorBond` to the saturation of the last election snapshot.
+	///
+	/// Does nothing if there is no [`MinNominatorBondController`] or no snapshot was taken since
+	/// the last adjustment.
+	pub(crate) fn adjust_min_nominator_bond() {
+		Self::register_weight(T::DbWeight::get().reads_writes(2, 1));
+		let (controller, (voters, limit)) =
+			match (MinNominatorBondController::<T>::get(), LastVoterSnapshot::<T>::take()) {
+				(Some(controller), Some(snapshot)) => (controller, snapshot),
+				_ => return,
+			};
+		Self::register_weight(T::DbWeight::get().reads_writes(1, 1));
+
+		let target = (controller.target_saturation * limit).max(1);
+		let old = MinNominatorBond::<T>::get();
+		// The floor is never zero, so the bond can also be raised from zero.
+		let base = old.max(controller.floor);
+		let new = if voters > target {
+			let step = Perbill::from_rational(voters - target, target).min(controller.max_step);
+			base.saturating_add(step * base)
+		} else {
+			let step = Perbill::from_rational(target - voters, target).min(controller.max_step);
+			base.saturating_sub(step * base)
+		};
+		let new = new.max(controller.floor).min(controller.ceiling);
+
+		if new != old {
+			MinNominatorBond::<T>::put(new);
+			Self::deposit_event(Event::<T>::MinNominatorBondAdjusted { old, new });
+		}
 	}
 
 	/// Compute payout for era.
@@ -452,6 +667,7 @@ impl<T: Config> Pallet<T> {
 
 			// Set ending era reward.
 			<ErasValidatorReward<T>>::insert(&active_era.index, validator_payout);
+			Self::store_reward_summary(active_era.index, validator_payout);
 			T::RewardRemainder::on_unbalanced(T::Currency::issue(remainder));
 
 			// Clear offending validators.
@@ -459,6 +675,34 @@ impl<T: Config> Pallet<T> {
 		}
 	}
 
+	/// Store the [`ValidatorRewardSummary`] of every validator that earned points in `era`.
+	///
+	/// This mirrors how [`Self::do_payout_stakers`] splits the era payout.
+	fn store_reward_summary(era: EraIndex, era_payout: BalanceOf<T>) {
+		let era_reward_points = <ErasRewardPoints<T>>::get(&era);
+		let total_points = era_reward_points.total;
+		let summaries = era_reward_points
+			.individual
+			.into_iter()
+			.filter(|(_, points)| !points.is_zero())
+			.map(|(stash, points)| {
+				let points_share = Perbill::from_rational(points, total_points);
+				let validator_payout = points_share * era_payout;
+				let commission =
+					Self::eras_validator_prefs(&era, &stash).commission * validator_payout;
+				let summary = ValidatorRewardSummary {
+					points_share,
+					commission,
+					nominator_pool: validator_payout.saturating_sub(commission),
+				};
+				(stash, summary)
+			})
+			.collect::<Vec<_>>();
+
+		// Only elected validators earn points, hence this never truncates.
+		<ErasRewardSummary<T>>::insert(era, BoundedVec::truncate_from(summaries));
+	}
+
 	/// Plan a new era.
 	///
 	/// * Bump the current era storage (which holds the latest planned era).
@@ -571,17 +815,11 @@ impl<T: Config> Pallet<T> {
 
 		// Populate stakers, exposures, and the snapshot of validator prefs.
 		let mut total_stake: BalanceOf<T> = Zero::zero();
+		let mut pages_written: u64 = 0;
 		exposures.into_iter().for_each(|(stash, exposure)| {
 			total_stake = total_stake.saturating_add(exposure.total);
-			<ErasStakers<T>>::insert(new_planned_era, &stash, &exposure);
-
-			let mut exposure_clipped = exposure;
-			let clipped_max_len = T::MaxNominatorRewardedPerValidator::get() as usize;
-			if exposure_clipped.others.len() > clipped_max_len {
-				exposure_clipped.others.sort_by(|a, b| a.value.cmp(&b.value).reverse());
-				exposure_clipped.others.truncate(clipped_max_len);
-			}
-			<ErasStakersClipped<T>>::insert(&new_planned_era, &stash, exposure_clipped);
+			let page_count = EraInfo::<T>::set_exposure(new_planned_era, &stash, exposure);
+			pages_written.saturating_accrue(page_count.into());
 		});
 
 		// Insert current era staking information
@@ -593,6 +831,13 @@ impl<T: Config> Pallet<T> {
 			<ErasValidatorPrefs<T>>::insert(&new_planned_era, stash, pref);
 		}
 
+		// An overview and the prefs per winner, their pages and the total stake.
+		let winners = elected_stashes.len() as u64;
+		Self::register_weight(T::DbWeight::get().reads_writes(
+			winners,
+			winners.saturating_mul(2).saturating_add(pages_written).saturating_add(1),
+		));
+
 		if new_planned_era > 0 {
 			log!(
 				info,
@@ -658,6 +903,7 @@ impl<T: Config> Pallet<T> {
 		<Ledger<T>>::remove(&controller);
 
 		<Payee<T>>::remove(stash);
+		<KickCount<T>>::remove(stash);
 		Self::do_remove_validator(stash);
 		Self::do_remove_nominator(stash);
 
@@ -673,13 +919,91 @@ impl<T: Config> Pallet<T> {
 		#[allow(deprecated)]
 		<ErasStakersClipped<T>>::remove_prefix(era_index, None);
 		#[allow(deprecated)]
+		<ErasStakersOverview<T>>::remove_prefix(era_index, None);
+		#[allow(deprecated)]
+		<ErasStakersPaged<T>>::remove_prefix((era_index,), None);
+		#[allow(deprecated)]
+		<ClaimedRewards<T>>::remove_prefix(era_index, None);
+		#[allow(deprecated)]
 		<ErasValidatorPrefs<T>>::remove_prefix(era_index, None);
 		<ErasValidatorReward<T>>::remove(era_index);
 		<ErasRewardPoints<T>>::remove(era_index);
+		<ErasRewardSummary<T>>::remove(era_index);
 		<ErasTotalStake<T>>::remove(era_index);
 		ErasStartSessionIndex::<T>::remove(era_index);
 	}
 
+	/// Remove era information orphaned by a past reduction of [`Config::HistoryDepth`].
+	///
+	/// Walks down from [`OrphanedErasCursor`] to era `0`, removing as many keys as fit into
+	/// `remaining_weight`. An era which could not be removed completely is continued in the
+	/// next call. Returns the consumed weight.
+	pub(crate) fn prune_orphaned_eras(remaining_weight: Weight) -> Weight {
+		let db_weight = T::DbWeight::get();
+		let mut consumed = db_weight.reads(1);
+		let mut era = match OrphanedErasCursor::<T>::get() {
+			Some(era) => era,
+			None => return consumed,
+		};
+
+		// The five single value items and the cursor are written for every completed era.
+		let era_overhead = db_weight.writes(6);
+		let key_weight = db_weight.writes(1).ref_time().max(1);
+		loop {
+			let remaining = remaining_weight.saturating_sub(consumed).saturating_sub(era_overhead);
+			let limit: u32 = (remaining.ref_time() / key_weight).saturated_into();
+			if limit.is_zero() {
+				break
+			}
+
+			let mut removed = 0u32;
+			let mut complete = true;
+			macro_rules! clear {
+				($map:ty, $prefix:expr) => {
+					if complete {
+						let result = <$map>::clear_prefix($prefix, limit - removed, None);
+						removed.saturating_accrue(result.backend);
+						complete = result.maybe_cursor.is_none() && removed < limit;
+					}
+				};
+			}
+			clear!(ErasStakers<T>, era);
+			clear!(ErasStakersClipped<T>, era);
+			clear!(ErasStakersOverview<T>, era);
+			clear!(ErasStakersPaged<T>, (era,));
+			clear!(ClaimedRewards<T>, era);
+			clear!(ErasValidatorPrefs<T>, era);
+			consumed.saturating_accrue(db_weight.writes(removed.into()));
+
+			if !complete {
+				OrphanedErasCursor::<T>::put(era);
+				consumed.saturating_accrue(db_weight.writes(1));
+				break
+			}
+
+			<ErasValidatorReward<T>>::remove(era);
+			<ErasRewardPoints<T>>::remove(era);
+			<ErasRewardSummary<T>>::remove(era);
+			<ErasTotalStake<T>>::remove(era);
+			ErasStartSessionIndex::<T>::remove(era);
+			consumed.saturating_accrue(era_overhead);
+
+			match era.checked_sub(1) {
+				Some(next) => {
+					OrphanedErasCursor::<T>::put(next);
+					era = next;
+				},
+				None => {
+					OrphanedErasCursor::<T>::kill();
+					log!(info, "pruning of orphaned eras completed");
+					break
+				},
+			}
+		}
+
+		consumed
+	}
+
 	/// Apply previously-unapplied slashes on the beginning of a new era, after a delay.
 	fn apply_unapplied_slashes(active_era: EraIndex) {
 		let era_slashes = <Self as Store>::UnappliedSlashes::take(&active_era);
@@ -732,13 +1056,72 @@ impl<T: Config> Pallet<T> {
 		}
 	}
 
+	/// Compute how slashing `fraction` of `validator`'s exposure in `era` would be apportioned
+	/// across the validator and its nominators, without applying it.
+	///
+	/// Slashes already recorded for `era` and the slashing spans of all stakers are taken into
+	/// account, exactly as for a reported offence. All storage changes made while computing the
+	/// slash are rolled back.
+	///
+	/// Returns `None` if there is no active era, `era` is outside of the bonding period,
+	/// `validator` is invulnerable or was not elected in `era`.
+	pub fn simulate_slash(
+		validator: T::AccountId,
+		fraction: Perbill,
+		era: EraIndex,
+	) -> Option<SlashSimulation<T::AccountId, BalanceOf<T>>> {
+		let active_era = Self::active_era()?.index;
+		let window_start = active_era.saturating_sub(Self::bonding_duration());
+		if era < window_start || era > active_era || Self::invulnerables().contains(&validator) {
+			return None
+		}
+
+		let exposure = EraInfo::<T>::get_full_exposure(era, &validator)?;
+
+		Some(storage::with_transaction_unchecked(|| {
+			let unapplied = slashing::compute_slash::<T>(slashing::SlashParams {
+				stash: &validator,
+				slash: fraction,
+				exposure: &exposure,
+				slash_era: era,
+				window_start,
+				now: active_era,
+				reward_proportion: SlashRewardFraction::<T>::get(),
+				disable_strategy: DisableStrategy::Never,
+			});
+			TransactionOutcome::Rollback(
+				unapplied
+					.map(|u| SlashSimulation { own: u.own, others: u.others, payout: u.payout })
+					.unwrap_or_default(),
+			)
+		}))
+	}
+
+	/// The pages of the exposure of `validator` in `era` whose rewards can still be paid out.
+	///
+	/// This is empty if `validator` is not bonded or earned no reward points in `era`, or if no
+	/// reward was recorded for `era` because it is still active or was already cleared.
+	pub fn pending_payout_pages(era: EraIndex, validator: &T::AccountId) -> Vec<PageIndex> {
+		if !ErasValidatorReward::<T>::contains_key(era) ||
+			!ErasRewardPoints::<T>::get(era).individual.contains_key(validator)
+		{
+			return Vec::new()
+		}
+		match Self::bonded(validator).and_then(Self::ledger) {
+			Some(ledger) => (0..EraInfo::<T>::get_page_count(era, validator))
+				.filter(|page| !EraInfo::<T>::is_rewards_claimed(era, &ledger, *page))
+				.collect(),
+			None => Vec::new(),
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	pub fn add_era_stakers(
 		current_era: EraIndex,
 		stash: T::AccountId,
 		exposure: Exposure<T::AccountId, BalanceOf<T>>,
 	) {
-		<ErasStakers<T>>::insert(&current_era, &stash, &exposure);
+		EraInfo::<T>::set_exposure(current_era, &stash, exposure);
 	}
 
 	#[cfg(feature = "runtime-benchmarks")]
@@ -755,8 +1138,15 @@ impl<T: Config> Pallet<T> {
 	///
 	/// This function is self-weighing as [`DispatchClass::Mandatory`].
 	pub fn get_npos_voters(maybe_max_len: Option<usize>) -> Vec<VoterOf<Self>> {
+		let all_voter_count = T::VoterList::count();
+		if let Some(max_len) = maybe_max_len {
+			LastVoterSnapshot::<T>::put((
+				all_voter_count,
+				u32::try_from(max_len).unwrap_or(u32::MAX),
+			));
+		}
 		let max_allowed_len = {
-			let all_voter_count = T::VoterList::count() as usize;
+			let all_voter_count = all_voter_count as usize;
 			maybe_max_len.unwrap_or(all_voter_count).min(all_voter_count)
 		};
 
@@ -869,6 +1259,30 @@ impl<T: Config> Pallet<T> {
 		all_targets
 	}
 
+	/// Bond the stash of `staker` and declare it as validator or nominator.
+	///
+	/// This goes through the same dispatchables a staker would use and is shared by the genesis
+	/// build and [`Call::import_stakers`].
+	pub(crate) fn do_import_staker(staker: &ImportedStaker<T>) -> DispatchResult {
+		let (stash, controller, balance, status) = staker;
+		ensure!(T::Currency::free_balance(stash) >= *balance, Error::<T>::InsufficientBond);
+		Self::bond(
+			RawOrigin::Signed(stash.clone()).into(),
+			T::Lookup::unlookup(controller.clone()),
+			*balance,
+			RewardDestination::Staked,
+		)?;
+		let controller = RawOrigin::Signed(controller.clone());
+		match status {
+			StakerStatus::Validator => Self::validate(controller.into(), Default::default()),
+			StakerStatus::Nominator(votes) => Self::nominate(
+				controller.into(),
+				votes.iter().map(|l| T::Lookup::unlookup(l.clone())).collect(),
+			),
+			StakerStatus::Idle => Ok(()),
+		}
+	}
+
 	/// This function will add a nominator to the `Nominators` storage map,
 	/// and `VoterList`.
 	///
@@ -1001,6 +1415,16 @@ impl<T: Config> ElectionDataProvider for Pallet<T> {
 		Ok(Self::get_npos_targets(None))
 	}
 
+	fn electable_targets_weight(max_len: u32) -> Weight {
+		T::WeightInfo::get_npos_targets(max_len)
+	}
+
+	fn electing_voters_weight(max_len: u32) -> Weight {
+		// The weight is linear in both validators and nominators, hence the worst case is when all
+		// voters are of the more expensive kind.
+		T::WeightInfo::get_npos_voters(max_len, 0).max(T::WeightInfo::get_npos_voters(0, max_len))
+	}
+
 	fn next_election_prediction(now: T::BlockNumber) -> T::BlockNumber {
 		let current_era = Self::current_era().unwrap_or(0);
 		let current_session = Self::current_planned_session();
@@ -1284,7 +1708,7 @@ where
 			});
 		add_db_reads_writes(1, 0);
 
-		let window_start = active_era.saturating_sub(T::BondingDuration::get());
+		let window_start = active_era.saturating_sub(Self::bonding_duration());
 
 		// Fast path for active-era report - most likely.
 		// `slash_session` cannot be in a future active era. It must be in `active_era` or before.
@@ -1587,13 +2011,17 @@ impl<T: Config> StakingInterface for Pallet<T> {
 	}
 
 	fn is_exposed_in_era(who: &Self::AccountId, era: &EraIndex) -> bool {
-		ErasStakers::<T>::iter_prefix(era).any(|(validator, exposures)| {
-			validator == *who || exposures.others.iter().any(|i| i.who == *who)
-		})
+		// Eras planned before exposures were paged only exist in `ErasStakers`.
+		ErasStakersOverview::<T>::contains_key(era, who) ||
+			ErasStakersPaged::<T>::iter_prefix_values((era,))
+				.any(|page| page.others.iter().any(|i| i.who == *who)) ||
+			ErasStakers::<T>::iter_prefix(era).any(|(validator, exposures)| {
+				validator == *who || exposures.others.iter().any(|i| i.who == *who)
+			})
 	}
 
 	fn bonding_duration() -> EraIndex {
-		T::BondingDuration::get()
+		Self::bonding_duration()
 	}
 
 	fn current_era() -> EraIndex {
@@ -1669,7 +2097,7 @@ impl<T: Config> StakingInterface for Pallet<T> {
 				.map(|(who, value)| IndividualExposure { who: who.clone(), value: value.clone() })
 				.collect::<Vec<_>>();
 			let exposure = Exposure { total: Default::default(), own: Default::default(), others };
-			<ErasStakers<T>>::insert(&current_era, &stash, &exposure);
+			EraInfo::<T>::set_exposure(*current_era, stash, exposure);
 		}
 
 		fn set_current_era(era: EraIndex) {
@@ -1720,7 +2148,8 @@ impl<T: Config> Pallet<T> {
 	fn check_exposures() -> Result<(), &'static str> {
 		// a check per validator to ensure the exposure struct is always sane.
 		let era = Self::active_era().unwrap().index;
-		ErasStakers::<T>::iter_prefix_values(era)
+		ErasStakersOverview::<T>::iter_key_prefix(era)
+			.filter_map(|validator| EraInfo::<T>::get_full_exposure(era, &validator))
 			.map(|expo| {
 				ensure!(
 					expo.total ==
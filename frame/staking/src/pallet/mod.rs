@@ -33,7 +33,7 @@ use frame_support::{
 };
 use frame_system::{ensure_root, ensure_signed, pallet_prelude::*};
 use sp_runtime::{
-	traits::{CheckedSub, SaturatedConversion, StaticLookup, Zero},
+	traits::{CheckedSub, Hash, SaturatedConversion, Saturating, StaticLookup, Zero},
 	ArithmeticError, Perbill, Percent,
 };
 use sp_staking::{EraIndex, SessionIndex};
@@ -44,10 +44,12 @@ mod impls;
 pub use impls::*;
 
 use crate::{
-	slashing, weights::WeightInfo, AccountIdLookupOf, ActiveEraInfo, BalanceOf, EraPayout,
-	EraRewardPoints, Exposure, Forcing, NegativeImbalanceOf, Nominations, PositiveImbalanceOf,
-	RewardDestination, SessionInterface, StakingLedger, UnappliedSlash, UnlockChunk,
-	ValidatorPrefs,
+	slashing, weights::WeightInfo, AccountIdLookupOf, ActiveEraInfo, BalanceOf, DustHandler,
+	EraPayout, EraRewardPoints, Exposure, ExposurePage, Forcing, ImportedStaker, KickReason,
+	MaxWinnersOf, NegativeImbalanceOf, Nominations, NominatorBondController, PageIndex,
+	PagedExposureMetadata, PositiveImbalanceOf, RewardDestination, SessionInterface,
+	StakerImportProgress, StakingLedger, UnappliedSlash, UnlockChunk, ValidatorPrefs,
+	ValidatorRewardSummary, MAX_STAKER_IMPORT_BATCH,
 };
 
 const STAKING_ID: LockIdentifier = *b"staking ";
@@ -65,7 +67,7 @@ pub mod pallet {
 	use super::*;
 
 	/// The current storage version.
-	const STORAGE_VERSION: StorageVersion = StorageVersion::new(13);
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(14);
 
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(crate) trait Store)]
@@ -137,9 +139,9 @@ pub mod pallet {
 		/// Number of eras to keep in history.
 		///
 		/// Following information is kept for eras in `[current_era -
-		/// HistoryDepth, current_era]`: `ErasStakers`, `ErasStakersClipped`,
+		/// HistoryDepth, current_era]`: `ErasStakersOverview`, `ErasStakersPaged`,
 		/// `ErasValidatorPrefs`, `ErasValidatorReward`, `ErasRewardPoints`,
-		/// `ErasTotalStake`, `ErasStartSessionIndex`,
+		/// `ErasRewardSummary`, `ErasTotalStake`, `ErasStartSessionIndex`,
 		/// `StakingLedger.claimed_rewards`.
 		///
 		/// Must be more than the number of eras delayed by session.
@@ -199,6 +201,10 @@ pub mod pallet {
 		/// See [Era payout](./index.html#era-payout).
 		type EraPayout: EraPayout<BalanceOf<Self>>;
 
+		/// Handler for the dust released from an active bond below the existential deposit by
+		/// `withdraw_unbonded`. Use `()` to leave it with the stash.
+		type DustHandler: DustHandler<Self::AccountId, BalanceOf<Self>>;
+
 		/// Something that can estimate the next session change, accurately or as a best effort
 		/// guess.
 		type NextNewSession: EstimateNextNewSession<Self::BlockNumber>;
@@ -210,6 +216,13 @@ pub mod pallet {
 		#[pallet::constant]
 		type MaxNominatorRewardedPerValidator: Get<u32>;
 
+		/// The maximum number of nominators stored in a single page of a validator's exposure.
+		///
+		/// Exposures are split into pages of this size in [`ErasStakersPaged`]. Lowering this
+		/// value only affects exposures of eras that are planned after the change.
+		#[pallet::constant]
+		type MaxExposurePageSize: Get<u32>;
+
 		/// The fraction of the validator set that is safe to be offending.
 		/// After the threshold is reached a new era will be forced.
 		type OffendingValidatorsThreshold: Get<Perbill>;
@@ -302,6 +315,19 @@ pub mod pallet {
 	#[pallet::storage]
 	pub type MinNominatorBond<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
 
+	/// The controller that adjusts [`MinNominatorBond`] every era, if any.
+	///
+	/// See [`NominatorBondController`].
+	#[pallet::storage]
+	pub type MinNominatorBondController<T: Config> =
+		StorageValue<_, NominatorBondController<BalanceOf<T>>, OptionQuery>;
+
+	/// The number of voters and the voter limit of the last election snapshot.
+	///
+	/// Consumed by the [`MinNominatorBondController`] at the start of the next era.
+	#[pallet::storage]
+	pub(crate) type LastVoterSnapshot<T> = StorageValue<_, (u32, u32), OptionQuery>;
+
 	/// The minimum active bond to become and maintain the role of a validator.
 	#[pallet::storage]
 	pub type MinValidatorBond<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
@@ -316,6 +342,14 @@ pub mod pallet {
 	#[pallet::storage]
 	pub type MinCommission<T: Config> = StorageValue<_, Perbill, ValueQuery>;
 
+	/// The number of eras that unbonded funds remain locked for, if changed by governance.
+	///
+	/// When not set, [`Config::BondingDuration`] is used. Unlocking chunks record the era at
+	/// which they can be withdrawn when they are created and hence are not affected by changes
+	/// of this value.
+	#[pallet::storage]
+	pub type BondingDurationOverride<T: Config> = StorageValue<_, EraIndex, OptionQuery>;
+
 	/// Map from all (unlocked) "controller" accounts to the info regarding the staking.
 	#[pallet::storage]
 	#[pallet::getter(fn ledger)]
@@ -400,10 +434,13 @@ pub mod pallet {
 	///
 	/// This is keyed first by the era index to allow bulk deletion and then the stash account.
 	///
+	/// Only eras that were planned before exposures were paged have an entry here. Newer eras are
+	/// stored in [`ErasStakersOverview`] and [`ErasStakersPaged`]. Use [`Pallet::eras_stakers`]
+	/// or [`EraInfo`] to read exposures of any era.
+	///
 	/// Is it removed after `HISTORY_DEPTH` eras.
 	/// If stakers hasn't been set or has been removed then empty exposure is returned.
 	#[pallet::storage]
-	#[pallet::getter(fn eras_stakers)]
 	#[pallet::unbounded]
 	pub type ErasStakers<T: Config> = StorageDoubleMap<
 		_,
@@ -424,6 +461,8 @@ pub mod pallet {
 	///
 	/// This is keyed fist by the era index to allow bulk deletion and then the stash account.
 	///
+	/// Only eras that were planned before exposures were paged have an entry here.
+	///
 	/// Is it removed after `HISTORY_DEPTH` eras.
 	/// If stakers hasn't been set or has been removed then empty exposure is returned.
 	#[pallet::storage]
@@ -439,6 +478,68 @@ pub mod pallet {
 		ValueQuery,
 	>;
 
+	/// Summary of the exposure of a validator at era, whose nominators are stored in
+	/// [`ErasStakersPaged`].
+	///
+	/// This is keyed first by the era index to allow bulk deletion and then the stash account.
+	///
+	/// Eras that were planned before exposures were paged don't have an entry here. Use
+	/// [`EraInfo`] to read exposures, which falls back to [`ErasStakersClipped`] for them.
+	///
+	/// Is it removed after `HISTORY_DEPTH` eras.
+	#[pallet::storage]
+	pub type ErasStakersOverview<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		EraIndex,
+		Twox64Concat,
+		T::AccountId,
+		PagedExposureMetadata<BalanceOf<T>>,
+		OptionQuery,
+	>;
+
+	/// Paginated exposure of a validator at era.
+	///
+	/// Each page holds at most `T::MaxExposurePageSize` nominators, sorted by their exposed
+	/// stake with the biggest stakers on the first page.
+	///
+	/// This is keyed first by the era index to allow bulk deletion, then the stash account and
+	/// finally the page index.
+	///
+	/// Is it removed after `HISTORY_DEPTH` eras.
+	#[pallet::storage]
+	#[pallet::unbounded]
+	pub type ErasStakersPaged<T: Config> = StorageNMap<
+		_,
+		(
+			NMapKey<Twox64Concat, EraIndex>,
+			NMapKey<Twox64Concat, T::AccountId>,
+			NMapKey<Twox64Concat, PageIndex>,
+		),
+		ExposurePage<T::AccountId, BalanceOf<T>>,
+		OptionQuery,
+	>;
+
+	/// The pages of the exposure of a validator at era whose rewards were claimed.
+	///
+	/// This is keyed first by the era index to allow bulk deletion and then the stash account.
+	///
+	/// Eras in `StakingLedger::claimed_rewards` without an entry here were claimed completely
+	/// before rewards were paid out by page.
+	///
+	/// Is it removed after `HISTORY_DEPTH` eras.
+	#[pallet::storage]
+	#[pallet::unbounded]
+	pub type ClaimedRewards<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		EraIndex,
+		Twox64Concat,
+		T::AccountId,
+		Vec<PageIndex>,
+		ValueQuery,
+	>;
+
 	/// Similar to `ErasStakers`, this holds the preferences of validators.
 	///
 	/// This is keyed first by the era index to allow bulk deletion and then the stash account.
@@ -472,6 +573,21 @@ pub mod pallet {
 	pub type ErasRewardPoints<T: Config> =
 		StorageMap<_, Twox64Concat, EraIndex, EraRewardPoints<T::AccountId>, ValueQuery>;
 
+	/// The reward summary of every validator that earned points, for the last `HISTORY_DEPTH`
+	/// eras.
+	///
+	/// Written when an era ends. Eras that haven't finished yet or have been removed have no
+	/// summary.
+	#[pallet::storage]
+	#[pallet::getter(fn eras_reward_summary)]
+	pub type ErasRewardSummary<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		EraIndex,
+		BoundedVec<(T::AccountId, ValidatorRewardSummary<BalanceOf<T>>), MaxWinnersOf<T>>,
+		ValueQuery,
+	>;
+
 	/// The total amount staked for the last `HISTORY_DEPTH` eras.
 	/// If total hasn't been set or has been removed then 0 stake is returned.
 	#[pallet::storage]
@@ -579,6 +695,27 @@ pub mod pallet {
 	#[pallet::storage]
 	pub(crate) type ChillThreshold<T: Config> = StorageValue<_, Percent, OptionQuery>;
 
+	/// The most recent era that may still hold information orphaned by a past reduction of
+	/// [`Config::HistoryDepth`].
+	///
+	/// Starting from this era, eras are cleared one after another down to era `0` in
+	/// `on_idle`. See [`crate::migrations::PruneOrphanedEras`].
+	#[pallet::storage]
+	pub(crate) type OrphanedErasCursor<T: Config> = StorageValue<_, EraIndex, OptionQuery>;
+
+	/// The number of nominators each validator stash has kicked so far.
+	///
+	/// Only nominators which were actually nominating the validator are counted. The entry is
+	/// removed when the stash is reaped.
+	#[pallet::storage]
+	pub type KickCount<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, u32, ValueQuery>;
+
+	/// The progress of the ongoing staker import, if any.
+	///
+	/// See [`Call::start_staker_import`] and [`Call::import_stakers`].
+	#[pallet::storage]
+	pub type StakerImport<T: Config> = StorageValue<_, StakerImportProgress<T::Hash>, OptionQuery>;
+
 	#[pallet::genesis_config]
 	pub struct GenesisConfig<T: Config> {
 		pub validator_count: u32,
@@ -632,7 +769,8 @@ pub mod pallet {
 				MaxNominatorsCount::<T>::put(x);
 			}
 
-			for &(ref stash, ref controller, balance, ref status) in &self.stakers {
+			for staker in &self.stakers {
+				let (stash, _, balance, status) = staker;
 				crate::log!(
 					trace,
 					"inserting genesis staker: {:?} => {:?} => {:?}",
@@ -641,26 +779,10 @@ pub mod pallet {
 					status
 				);
 				assert!(
-					T::Currency::free_balance(stash) >= balance,
+					T::Currency::free_balance(stash) >= *balance,
 					"Stash does not have enough balance to bond."
 				);
-				frame_support::assert_ok!(<Pallet<T>>::bond(
-					T::RuntimeOrigin::from(Some(stash.clone()).into()),
-					T::Lookup::unlookup(controller.clone()),
-					balance,
-					RewardDestination::Staked,
-				));
-				frame_support::assert_ok!(match status {
-					crate::StakerStatus::Validator => <Pallet<T>>::validate(
-						T::RuntimeOrigin::from(Some(controller.clone()).into()),
-						Default::default(),
-					),
-					crate::StakerStatus::Nominator(votes) => <Pallet<T>>::nominate(
-						T::RuntimeOrigin::from(Some(controller.clone()).into()),
-						votes.iter().map(|l| T::Lookup::unlookup(l.clone())).collect(),
-					),
-					_ => Ok(()),
-				});
+				frame_support::assert_ok!(<Pallet<T>>::do_import_staker(staker));
 				assert!(
 					ValidatorCount::<T>::get() <=
 						<T::ElectionProvider as ElectionProviderBase>::MaxWinners::get()
@@ -704,8 +826,8 @@ pub mod pallet {
 		/// An account has called `withdraw_unbonded` and removed unbonding chunks worth `Balance`
 		/// from the unlocking queue.
 		Withdrawn { stash: T::AccountId, amount: BalanceOf<T> },
-		/// A nominator has been kicked from a validator.
-		Kicked { nominator: T::AccountId, stash: T::AccountId },
+		/// A nominator has been kicked from a validator for the given reason, which may be empty.
+		Kicked { nominator: T::AccountId, stash: T::AccountId, reason: KickReason },
 		/// The election failed. No new era is planned.
 		StakingElectionFailed,
 		/// An account has stopped participating as either a validator or nominator.
@@ -716,6 +838,23 @@ pub mod pallet {
 		ValidatorPrefsSet { stash: T::AccountId, prefs: ValidatorPrefs },
 		/// A new force era mode was set.
 		ForceEra { mode: Forcing },
+		/// The bonding duration was changed. Only affects funds unbonded from now on.
+		BondingDurationChanged { old: EraIndex, new: EraIndex },
+		/// A reporter of an offence has been rewarded by this amount out of the resulting slash.
+		ReporterRewarded { reporter: T::AccountId, amount: BalanceOf<T> },
+		/// An active bond below the existential deposit was released back to the stash when
+		/// withdrawing.
+		DustSwept { stash: T::AccountId, amount: BalanceOf<T> },
+		/// The minimum nominator bond was adjusted by the [`MinNominatorBondController`].
+		MinNominatorBondAdjusted { old: BalanceOf<T>, new: BalanceOf<T> },
+		/// A staker import of `total` stakers was started.
+		StakerImportStarted { total: u32 },
+		/// A batch of stakers was imported. `imported` is the cursor the next batch starts at.
+		StakersImported { imported: u32 },
+		/// All stakers of the import were imported and matched the expected checksum.
+		StakerImportCompleted { total: u32 },
+		/// The staker import was cancelled after `imported` stakers were imported.
+		StakerImportCancelled { imported: u32 },
 	}
 
 	#[pallet::error]
@@ -774,6 +913,23 @@ pub mod pallet {
 		CommissionTooLow,
 		/// Some bound is not met.
 		BoundNotMet,
+		/// The bonding duration must be greater than the slash defer duration, at most the
+		/// history depth and not lower than the current bonding duration.
+		InvalidBondingDuration,
+		/// The floor of a nominator bond controller is zero or above its ceiling.
+		InvalidNominatorBondController,
+		/// A staker import is already in progress.
+		StakerImportInProgress,
+		/// There is no staker import in progress.
+		NoStakerImport,
+		/// The batch does not start at the cursor of the staker import.
+		StakerImportCursorMismatch,
+		/// The batch contains more stakers than were announced for the import.
+		TooManyImportedStakers,
+		/// The imported stakers don't match the checksum the import was started with.
+		StakerImportChecksumMismatch,
+		/// The validator has no nominators on this page of its exposure.
+		InvalidPage,
 	}
 
 	#[pallet::hooks]
@@ -797,6 +953,10 @@ pub mod pallet {
 			// `on_finalize` weight is tracked in `on_initialize`
 		}
 
+		fn on_idle(_now: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+			Self::prune_orphaned_eras(remaining_weight)
+		}
+
 		fn integrity_test() {
 			// ensure that we funnel the correct value to the `DataProvider::MaxVotesPerVoter`;
 			assert_eq!(
@@ -805,6 +965,8 @@ pub mod pallet {
 			);
 			// and that MaxNominations is always greater than 1, since we count on this.
 			assert!(!T::MaxNominations::get().is_zero());
+			// pages of exposure must be able to hold at least one nominator.
+			assert!(!T::MaxExposurePageSize::get().is_zero());
 
 			// ensure election results are always bounded with the same value
 			assert!(
@@ -1030,18 +1192,21 @@ pub mod pallet {
 				ensure!(ledger.active >= min_active_bond, Error::<T>::InsufficientBond);
 
 				// Note: in case there is no current era it is fine to bond one era more.
-				let era = Self::current_era().unwrap_or(0) + T::BondingDuration::get();
-				if let Some(mut chunk) =
-					ledger.unlocking.last_mut().filter(|chunk| chunk.era == era)
-				{
-					// To keep the chunk count down, we only keep one chunk per era. Since
-					// `unlocking` is a FiFo queue, if a chunk exists for `era` we know that it will
-					// be the last one.
+				let era = Self::current_era().unwrap_or(0) + Self::bonding_duration();
+				if let Some(chunk) = ledger.unlocking.iter_mut().find(|chunk| chunk.era == era) {
+					// To keep the chunk count down, we only keep one chunk per era.
 					chunk.value = chunk.value.defensive_saturating_add(value)
 				} else {
+					// `unlocking` is sorted by era. Chunks created before the bonding duration
+					// was shortened can unlock after `era`, so this is not always a push.
+					let index = ledger
+						.unlocking
+						.iter()
+						.position(|chunk| chunk.era > era)
+						.unwrap_or(ledger.unlocking.len());
 					ledger
 						.unlocking
-						.try_push(UnlockChunk { value, era })
+						.try_insert(index, UnlockChunk { value, era })
 						.map_err(|_| Error::<T>::NoMoreChunks)?;
 				};
 				// NOTE: ledger must be updated prior to calling `Self::weight_of`.
@@ -1072,7 +1237,10 @@ pub mod pallet {
 		///
 		/// The dispatch origin for this call must be _Signed_ by the controller.
 		///
-		/// Emits `Withdrawn`.
+		/// If the remaining active bond is below the existential deposit while funds are still
+		/// unlocking, it is released back to the stash and the stash is chilled.
+		///
+		/// Emits `Withdrawn`, and `DustSwept` if an active dust bond was released.
 		///
 		/// See also [`Call::unbond`].
 		///
@@ -1488,17 +1656,18 @@ pub mod pallet {
 			Ok(())
 		}
 
-		/// Pay out all the stakers behind a single validator for a single era.
+		/// Pay out the next page of the stakers behind a single validator for a single era.
 		///
-		/// - `validator_stash` is the stash account of the validator. Their nominators, up to
-		///   `T::MaxNominatorRewardedPerValidator`, will also receive their rewards.
+		/// - `validator_stash` is the stash account of the validator. The nominators on the next
+		///   unclaimed page of its exposure, up to `T::MaxExposurePageSize`, will also receive
+		///   their rewards. See [`Self::payout_stakers_by_page`] to pay out a specific page.
 		/// - `era` may be any era between `[current_era - history_depth; current_era]`.
 		///
 		/// The origin of this call must be _Signed_. Any account can call this function, even if
 		/// it is not one of the stakers.
 		///
 		/// # <weight>
-		/// - Time complexity: at most O(MaxNominatorRewardedPerValidator).
+		/// - Time complexity: at most O(MaxExposurePageSize).
 		/// - Contains a limited number of reads and writes.
 		/// -----------
 		/// N is the Number of payouts for the validator (including the validator)
@@ -1510,9 +1679,7 @@ pub mod pallet {
 		///   Paying even a dead controller is cheaper weight-wise. We don't do any refunds here.
 		/// # </weight>
 		#[pallet::call_index(18)]
-		#[pallet::weight(T::WeightInfo::payout_stakers_alive_staked(
-			T::MaxNominatorRewardedPerValidator::get()
-		))]
+		#[pallet::weight(T::WeightInfo::payout_stakers_alive_staked(T::MaxExposurePageSize::get()))]
 		pub fn payout_stakers(
 			origin: OriginFor<T>,
 			validator_stash: T::AccountId,
@@ -1589,7 +1756,8 @@ pub mod pallet {
 			let reapable = T::Currency::total_balance(&stash) < ed ||
 				Self::ledger(Self::bonded(stash.clone()).ok_or(Error::<T>::NotStash)?)
 					.map(|l| l.total)
-					.unwrap_or_default() < ed;
+					.unwrap_or_default() <
+					ed;
 			ensure!(reapable, Error::<T>::FundedTarget);
 
 			Self::kill_stash(&stash, num_slashing_spans)?;
@@ -1606,15 +1774,22 @@ pub mod pallet {
 		///
 		/// - `who`: A list of nominator stash accounts who are nominating this validator which
 		///   should no longer be nominating this validator.
+		/// - `reason`: Why the nominators are kicked. It is emitted in the [`Event::Kicked`] of
+		///   every kicked nominator so they can tell why their stake became inactive. May be empty.
 		///
 		/// Note: Making this call only makes sense if you first set the validator preferences to
 		/// block any further nominations.
 		#[pallet::call_index(21)]
 		#[pallet::weight(T::WeightInfo::kick(who.len() as u32))]
-		pub fn kick(origin: OriginFor<T>, who: Vec<AccountIdLookupOf<T>>) -> DispatchResult {
+		pub fn kick(
+			origin: OriginFor<T>,
+			who: Vec<AccountIdLookupOf<T>>,
+			reason: KickReason,
+		) -> DispatchResult {
 			let controller = ensure_signed(origin)?;
 			let ledger = Self::ledger(&controller).ok_or(Error::<T>::NotController)?;
 			let stash = &ledger.stash;
+			let mut kicked = 0u32;
 
 			for nom_stash in who
 				.into_iter()
@@ -1622,19 +1797,25 @@ pub mod pallet {
 				.collect::<Result<Vec<T::AccountId>, _>>()?
 				.into_iter()
 			{
-				Nominators::<T>::mutate(&nom_stash, |maybe_nom| {
-					if let Some(ref mut nom) = maybe_nom {
-						if let Some(pos) = nom.targets.iter().position(|v| v == stash) {
-							nom.targets.swap_remove(pos);
-							Self::deposit_event(Event::<T>::Kicked {
-								nominator: nom_stash.clone(),
-								stash: stash.clone(),
-							});
-						}
-					}
+				// Nominations that don't target `stash` are left untouched and aren't rewritten.
+				let _ = Nominators::<T>::try_mutate(&nom_stash, |maybe_nom| {
+					let nom = maybe_nom.as_mut().ok_or(())?;
+					let pos = nom.targets.iter().position(|v| v == stash).ok_or(())?;
+					nom.targets.swap_remove(pos);
+					kicked.saturating_inc();
+					Self::deposit_event(Event::<T>::Kicked {
+						nominator: nom_stash.clone(),
+						stash: stash.clone(),
+						reason: reason.clone(),
+					});
+					Ok::<_, ()>(())
 				});
 			}
 
+			if !kicked.is_zero() {
+				KickCount::<T>::mutate(stash, |count| *count = count.saturating_add(kicked));
+			}
+
 			Ok(())
 		}
 
@@ -1650,6 +1831,12 @@ pub mod pallet {
 		///   should be filled in order for the `chill_other` transaction to work.
 		/// * `min_commission`: The minimum amount of commission that each validators must maintain.
 		///   This is checked only upon calling `validate`. Existing validators are not affected.
+		/// * `bonding_duration`: The number of eras unbonded funds remain locked. When removed,
+		///   [`Config::BondingDuration`] is used. Must be greater than
+		///   [`Config::SlashDeferDuration`], at most [`Config::HistoryDepth`] and may not decrease,
+		///   including by removal. Funds that are already unlocking keep their era.
+		/// * `slash_reward_fraction`: The fraction of a slash that is paid out to the reporters of
+		///   the offence. When removed, reporters are not rewarded.
 		///
 		/// RuntimeOrigin must be Root to call this function.
 		///
@@ -1670,9 +1857,28 @@ pub mod pallet {
 			max_validator_count: ConfigOp<u32>,
 			chill_threshold: ConfigOp<Percent>,
 			min_commission: ConfigOp<Perbill>,
+			bonding_duration: ConfigOp<EraIndex>,
+			slash_reward_fraction: ConfigOp<Perbill>,
 		) -> DispatchResult {
 			ensure_root(origin)?;
 
+			let old_bonding_duration = Self::bonding_duration();
+			// The bonding duration may only grow: consumers such as nomination pools size their
+			// storage bounds by it and would fail to decode existing entries if it shrank.
+			match bonding_duration {
+				ConfigOp::Set(new) => ensure!(
+					T::SlashDeferDuration::get() < new &&
+						new <= T::HistoryDepth::get() &&
+						new >= old_bonding_duration,
+					Error::<T>::InvalidBondingDuration
+				),
+				ConfigOp::Remove => ensure!(
+					T::BondingDuration::get() >= old_bonding_duration,
+					Error::<T>::InvalidBondingDuration
+				),
+				ConfigOp::Noop => (),
+			}
+
 			macro_rules! config_op_exp {
 				($storage:ty, $op:ident) => {
 					match $op {
@@ -1689,6 +1895,16 @@ pub mod pallet {
 			config_op_exp!(MaxValidatorsCount<T>, max_validator_count);
 			config_op_exp!(ChillThreshold<T>, chill_threshold);
 			config_op_exp!(MinCommission<T>, min_commission);
+			config_op_exp!(BondingDurationOverride<T>, bonding_duration);
+			config_op_exp!(SlashRewardFraction<T>, slash_reward_fraction);
+
+			let new_bonding_duration = Self::bonding_duration();
+			if new_bonding_duration != old_bonding_duration {
+				Self::deposit_event(Event::<T>::BondingDurationChanged {
+					old: old_bonding_duration,
+					new: new_bonding_duration,
+				});
+			}
 			Ok(())
 		}
 		/// Declare a `controller` to stop participating as either a validator or nominator.
@@ -1811,6 +2027,141 @@ pub mod pallet {
 			MinCommission::<T>::put(new);
 			Ok(())
 		}
+
+		/// Set or remove the controller that adjusts `MinNominatorBond` every era.
+		///
+		/// The adjustment keeps the election snapshot within its voter limit without
+		/// governance having to follow every change of the voter count. See
+		/// [`NominatorBondController`].
+		///
+		/// RuntimeOrigin must be Root to call this function.
+		#[pallet::call_index(26)]
+		#[pallet::weight(T::WeightInfo::set_min_nominator_bond_controller())]
+		pub fn set_min_nominator_bond_controller(
+			origin: OriginFor<T>,
+			controller: Option<NominatorBondController<BalanceOf<T>>>,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			if let Some(controller) = &controller {
+				ensure!(
+					!controller.floor.is_zero() && controller.floor <= controller.ceiling,
+					Error::<T>::InvalidNominatorBondController
+				);
+			}
+			MinNominatorBondController::<T>::set(controller);
+			Ok(())
+		}
+
+		/// Start importing `total` stakers in batches through [`Call::import_stakers`].
+		///
+		/// This allows to bring a staker set that is too large for a single block, e.g. a
+		/// snapshot of another network, onto the chain after genesis or a runtime upgrade.
+		/// `checksum` is the final value of [`StakerImportProgress::checksum`] over all stakers
+		/// in import order. It is checked once the last staker was imported.
+		///
+		/// The dispatch origin must be Root.
+		#[pallet::call_index(27)]
+		#[pallet::weight(T::WeightInfo::start_staker_import())]
+		pub fn start_staker_import(
+			origin: OriginFor<T>,
+			total: u32,
+			checksum: T::Hash,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(!StakerImport::<T>::exists(), Error::<T>::StakerImportInProgress);
+			StakerImport::<T>::put(StakerImportProgress {
+				total,
+				imported: 0,
+				expected_checksum: checksum,
+				checksum: Default::default(),
+			});
+			Self::deposit_event(Event::<T>::StakerImportStarted { total });
+			Ok(())
+		}
+
+		/// Import the next batch of stakers of the ongoing staker import.
+		///
+		/// `cursor` must be the number of stakers imported so far. A batch that was already
+		/// applied or skips stakers is therefore rejected and an interrupted import can be
+		/// resumed by reading [`StakerImport`]. Every staker is bonded and declared as validator
+		/// or nominator with the same checks as the respective dispatchables. Nominated
+		/// validators must be imported before their nominators. The batch is applied
+		/// atomically.
+		///
+		/// The dispatch origin must be Root.
+		#[pallet::call_index(28)]
+		#[pallet::weight(T::WeightInfo::import_stakers(stakers.len() as u32))]
+		pub fn import_stakers(
+			origin: OriginFor<T>,
+			cursor: u32,
+			stakers: BoundedVec<ImportedStaker<T>, ConstU32<MAX_STAKER_IMPORT_BATCH>>,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			let mut progress = StakerImport::<T>::get().ok_or(Error::<T>::NoStakerImport)?;
+			ensure!(progress.imported == cursor, Error::<T>::StakerImportCursorMismatch);
+			ensure!(
+				progress.total.saturating_sub(progress.imported) >= stakers.len() as u32,
+				Error::<T>::TooManyImportedStakers
+			);
+
+			for staker in &stakers {
+				Self::do_import_staker(staker)?;
+				progress.checksum = T::Hashing::hash_of(&(progress.checksum, staker));
+			}
+			progress.imported.saturating_accrue(stakers.len() as u32);
+
+			if progress.imported < progress.total {
+				Self::deposit_event(Event::<T>::StakersImported { imported: progress.imported });
+				StakerImport::<T>::put(progress);
+				return Ok(())
+			}
+
+			ensure!(
+				progress.checksum == progress.expected_checksum,
+				Error::<T>::StakerImportChecksumMismatch
+			);
+			StakerImport::<T>::kill();
+			Self::deposit_event(Event::<T>::StakerImportCompleted { total: progress.total });
+			Ok(())
+		}
+
+		/// Cancel the ongoing staker import.
+		///
+		/// Stakers that were already imported are kept.
+		///
+		/// The dispatch origin must be Root.
+		#[pallet::call_index(29)]
+		#[pallet::weight(T::WeightInfo::cancel_staker_import())]
+		pub fn cancel_staker_import(origin: OriginFor<T>) -> DispatchResult {
+			ensure_root(origin)?;
+			let progress = StakerImport::<T>::take().ok_or(Error::<T>::NoStakerImport)?;
+			Self::deposit_event(Event::<T>::StakerImportCancelled { imported: progress.imported });
+			Ok(())
+		}
+
+		/// Pay out a single page of the stakers behind a single validator for a single era.
+		///
+		/// - `validator_stash` is the stash account of the validator.
+		/// - `era` may be any era between `[current_era - history_depth; current_era]`.
+		/// - `page` is the page of the exposure of the validator whose nominators are paid out. The
+		///   validator itself is paid out together with the first page.
+		///
+		/// The origin of this call must be _Signed_. Any account can call this function, even if
+		/// it is not one of the stakers.
+		///
+		/// Every page can only be paid out once. Validators with more than
+		/// `T::MaxExposurePageSize` nominators need one call per page to pay out all of them.
+		#[pallet::call_index(30)]
+		#[pallet::weight(T::WeightInfo::payout_stakers_alive_staked(T::MaxExposurePageSize::get()))]
+		pub fn payout_stakers_by_page(
+			origin: OriginFor<T>,
+			validator_stash: T::AccountId,
+			era: EraIndex,
+			page: PageIndex,
+		) -> DispatchResultWithPostInfo {
+			ensure_signed(origin)?;
+			Self::do_payout_stakers_by_page(validator_stash, era, page)
+		}
 	}
 }
 
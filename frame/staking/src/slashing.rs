@@ -680,6 +680,11 @@ fn pay_reporters<T: Config>(
 		let (reporter_reward, rest) = reward_payout.split(per_reporter);
 		reward_payout = rest;
 
+		<Pallet<T>>::deposit_event(super::Event::<T>::ReporterRewarded {
+			reporter: reporter.clone(),
+			amount: reporter_reward.peek(),
+		});
+
 		// this cancels out the reporter reward imbalance internally, leading
 		// to no change in total issuance.
 		T::Currency::resolve_creating(reporter, reporter_reward);
@@ -17,6 +17,10 @@
 
 //! Testing utils for staking. Provides some common functions to setup staking state, such as
 //! bonding validators, nominators, and generating different types of solutions.
+//!
+//! Besides benchmarks these are available to the tests of other crates through the
+//! `testing-utils` feature. This includes moving sessions and eras forward, minting exposures
+//! and queueing slashes against any runtime that uses this pallet.
 
 use crate::{Pallet as Staking, *};
 use frame_benchmarking::account;
@@ -229,3 +233,69 @@ pub fn create_validators_with_nominators_for_era<T: Config>(
 pub fn current_era<T: Config>() -> EraIndex {
 	<Pallet<T>>::current_era().unwrap_or(0)
 }
+
+/// Rotate sessions until `session_index` has started.
+///
+/// This works against any runtime that uses this pallet as the session manager. Block numbers
+/// and timestamps are left untouched.
+pub fn start_session<T: Config + pallet_session::Config>(session_index: SessionIndex) {
+	while pallet_session::Pallet::<T>::current_index() < session_index {
+		pallet_session::Pallet::<T>::rotate_session();
+	}
+}
+
+/// Go one session forward.
+pub fn advance_session<T: Config + pallet_session::Config>() {
+	start_session::<T>(pallet_session::Pallet::<T>::current_index() + 1);
+}
+
+/// Rotate sessions until `era_index` is the active era.
+///
+/// Panics if the era doesn't progress, for example because eras are forced to never change.
+pub fn start_active_era<T: Config + pallet_session::Config>(era_index: EraIndex) {
+	let active_era = || <Pallet<T>>::active_era().map(|info| info.index).unwrap_or(0);
+	let max_sessions =
+		(era_index.saturating_sub(active_era()) + 1).saturating_mul(T::SessionsPerEra::get() + 1);
+	for _ in 0..max_sessions {
+		if active_era() >= era_index {
+			return
+		}
+		advance_session::<T>();
+	}
+	assert!(active_era() >= era_index, "era {} did not become active", era_index);
+}
+
+/// Record `validator` as elected in `era`, backed by `own` and the stake of `others`.
+///
+/// The exposure is stored the same way the result of an election is.
+pub fn add_exposure<T: Config>(
+	era: EraIndex,
+	validator: &T::AccountId,
+	own: BalanceOf<T>,
+	others: Vec<(T::AccountId, BalanceOf<T>)>,
+) -> Exposure<T::AccountId, BalanceOf<T>> {
+	let others = others
+		.into_iter()
+		.map(|(who, value)| IndividualExposure { who, value })
+		.collect::<Vec<_>>();
+	let total = others.iter().fold(own, |total, other| total.saturating_add(other.value));
+	let exposure = Exposure { total, own, others };
+
+	EraInfo::<T>::set_exposure(era, validator, exposure.clone());
+	<ErasTotalStake<T>>::mutate(era, |stake| *stake = stake.saturating_add(total));
+	<ErasValidatorPrefs<T>>::insert(era, validator, <Pallet<T>>::validators(validator));
+
+	exposure
+}
+
+/// Queue a slash of `validator` and its nominators that is applied once `era` becomes active.
+pub fn add_unapplied_slash<T: Config>(
+	era: EraIndex,
+	validator: T::AccountId,
+	own: BalanceOf<T>,
+	others: Vec<(T::AccountId, BalanceOf<T>)>,
+	reporters: Vec<T::AccountId>,
+) {
+	let slash = UnappliedSlash { validator, own, others, reporters, payout: Zero::zero() };
+	<UnappliedSlashes<T>>::append(era, slash);
+}
@@ -23,13 +23,13 @@ use frame_support::{
 	assert_noop, assert_ok, assert_storage_noop, bounded_vec,
 	dispatch::{extract_actual_weight, GetDispatchInfo, WithPostDispatchInfo},
 	pallet_prelude::*,
-	traits::{Currency, Get, ReservableCurrency},
+	traits::{Currency, Get, OnRuntimeUpgrade, ReservableCurrency},
 };
 use mock::*;
 use pallet_balances::Error as BalancesError;
 use sp_runtime::{
 	assert_eq_error_rate,
-	traits::{BadOrigin, Dispatchable},
+	traits::{BadOrigin, Dispatchable, Hash},
 	Perbill, Percent, Rounding,
 };
 use sp_staking::{
@@ -50,7 +50,9 @@ fn set_staking_configs_works() {
 			ConfigOp::Set(10),
 			ConfigOp::Set(20),
 			ConfigOp::Set(Percent::from_percent(75)),
-			ConfigOp::Set(Zero::zero())
+			ConfigOp::Set(Zero::zero()),
+			ConfigOp::Set(3),
+			ConfigOp::Set(Perbill::from_percent(20))
 		));
 		assert_eq!(MinNominatorBond::<Test>::get(), 1_500);
 		assert_eq!(MinValidatorBond::<Test>::get(), 2_000);
@@ -58,6 +60,9 @@ fn set_staking_configs_works() {
 		assert_eq!(MaxValidatorsCount::<Test>::get(), Some(20));
 		assert_eq!(ChillThreshold::<Test>::get(), Some(Percent::from_percent(75)));
 		assert_eq!(MinCommission::<Test>::get(), Perbill::from_percent(0));
+		assert_eq!(BondingDurationOverride::<Test>::get(), Some(3));
+		assert_eq!(Staking::bonding_duration(), 3);
+		assert_eq!(SlashRewardFraction::<Test>::get(), Perbill::from_percent(20));
 
 		// noop does nothing
 		assert_storage_noop!(assert_ok!(Staking::set_staking_configs(
@@ -67,6 +72,8 @@ fn set_staking_configs_works() {
 			ConfigOp::Noop,
 			ConfigOp::Noop,
 			ConfigOp::Noop,
+			ConfigOp::Noop,
+			ConfigOp::Noop,
 			ConfigOp::Noop
 		)));
 
@@ -78,6 +85,8 @@ fn set_staking_configs_works() {
 			ConfigOp::Remove,
 			ConfigOp::Remove,
 			ConfigOp::Remove,
+			ConfigOp::Remove,
+			ConfigOp::Remove,
 			ConfigOp::Remove
 		));
 		assert_eq!(MinNominatorBond::<Test>::get(), 0);
@@ -86,6 +95,137 @@ fn set_staking_configs_works() {
 		assert_eq!(MaxValidatorsCount::<Test>::get(), None);
 		assert_eq!(ChillThreshold::<Test>::get(), None);
 		assert_eq!(MinCommission::<Test>::get(), Perbill::from_percent(0));
+		assert_eq!(BondingDurationOverride::<Test>::get(), None);
+		assert_eq!(Staking::bonding_duration(), BondingDuration::get());
+		assert_eq!(SlashRewardFraction::<Test>::get(), Perbill::zero());
+	});
+}
+
+#[test]
+fn bonding_duration_change_keeps_unlocking_eras() {
+	ExtBuilder::default().build_and_execute(|| {
+		let unbond = |value| Staking::unbond(RuntimeOrigin::signed(10), value);
+		// unbonding at era 0 with the default bonding duration of 3 eras.
+		assert_ok!(unbond(100));
+
+		// bonding duration must be greater than the slash defer duration.
+		SlashDeferDuration::set(4);
+		assert_noop!(
+			Staking::set_staking_configs(
+				RuntimeOrigin::root(),
+				ConfigOp::Noop,
+				ConfigOp::Noop,
+				ConfigOp::Noop,
+				ConfigOp::Noop,
+				ConfigOp::Noop,
+				ConfigOp::Noop,
+				ConfigOp::Set(4),
+				ConfigOp::Noop
+			),
+			Error::<Test>::InvalidBondingDuration,
+		);
+		SlashDeferDuration::set(0);
+
+		// lengthening only applies to newly unbonded funds.
+		assert_ok!(Staking::set_staking_configs(
+			RuntimeOrigin::root(),
+			ConfigOp::Noop,
+			ConfigOp::Noop,
+			ConfigOp::Noop,
+			ConfigOp::Noop,
+			ConfigOp::Noop,
+			ConfigOp::Noop,
+			ConfigOp::Set(5),
+			ConfigOp::Noop
+		));
+		assert_eq!(
+			*staking_events().last().unwrap(),
+			Event::BondingDurationChanged { old: 3, new: 5 }
+		);
+		assert_ok!(unbond(50));
+
+		// shortening, directly or by removing the override, is rejected.
+		assert_noop!(
+			Staking::set_staking_configs(
+				RuntimeOrigin::root(),
+				ConfigOp::Noop,
+				ConfigOp::Noop,
+				ConfigOp::Noop,
+				ConfigOp::Noop,
+				ConfigOp::Noop,
+				ConfigOp::Noop,
+				ConfigOp::Set(4),
+				ConfigOp::Noop
+			),
+			Error::<Test>::InvalidBondingDuration,
+		);
+		assert_noop!(
+			Staking::set_staking_configs(
+				RuntimeOrigin::root(),
+				ConfigOp::Noop,
+				ConfigOp::Noop,
+				ConfigOp::Noop,
+				ConfigOp::Noop,
+				ConfigOp::Noop,
+				ConfigOp::Noop,
+				ConfigOp::Remove,
+				ConfigOp::Noop
+			),
+			Error::<Test>::InvalidBondingDuration,
+		);
+		assert_eq!(Staking::bonding_duration(), 5);
+		assert_ok!(unbond(10));
+
+		assert_eq!(
+			Staking::ledger(&10).unwrap().unlocking.into_inner(),
+			vec![UnlockChunk { value: 100, era: 3 }, UnlockChunk { value: 60, era: 5 }]
+		);
+
+		// chunks keep unlocking at their original eras.
+		mock::start_active_era(3);
+		assert_ok!(Staking::withdraw_unbonded(RuntimeOrigin::signed(10), 0));
+		assert_eq!(
+			Staking::ledger(&10).unwrap().unlocking.into_inner(),
+			vec![UnlockChunk { value: 60, era: 5 }]
+		);
+	});
+}
+
+#[test]
+fn bonding_duration_bounds_are_enforced() {
+	ExtBuilder::default().build_and_execute(|| {
+		let set_bonding_duration = |eras| {
+			Staking::set_staking_configs(
+				RuntimeOrigin::root(),
+				ConfigOp::Noop,
+				ConfigOp::Noop,
+				ConfigOp::Noop,
+				ConfigOp::Noop,
+				ConfigOp::Noop,
+				ConfigOp::Noop,
+				ConfigOp::Set(eras),
+				ConfigOp::Noop,
+			)
+		};
+		SlashDeferDuration::set(2);
+
+		// must be greater than the slash defer duration, hence also not zero.
+		assert_noop!(set_bonding_duration(0), Error::<Test>::InvalidBondingDuration);
+		assert_noop!(set_bonding_duration(2), Error::<Test>::InvalidBondingDuration);
+		assert_ok!(set_bonding_duration(3));
+		assert_eq!(Staking::bonding_duration(), 3);
+
+		// must not be lower than the current bonding duration.
+		assert_ok!(set_bonding_duration(4));
+		assert_noop!(set_bonding_duration(3), Error::<Test>::InvalidBondingDuration);
+
+		// must not exceed the history depth.
+		assert_noop!(
+			set_bonding_duration(HistoryDepth::get() + 1),
+			Error::<Test>::InvalidBondingDuration
+		);
+		assert_ok!(set_bonding_duration(HistoryDepth::get()));
+		assert_eq!(Staking::bonding_duration(), HistoryDepth::get());
 	});
 }
 
@@ -467,7 +607,7 @@ fn blocking_and_kicking_works() {
 			// should have worked since we're already nominated them
 			assert_eq!(Nominators::<Test>::get(&101).unwrap().targets, vec![11]);
 			// kick the nominator
-			assert_ok!(Staking::kick(RuntimeOrigin::signed(10), vec![101]));
+			assert_ok!(Staking::kick(RuntimeOrigin::signed(10), vec![101], Default::default()));
 			// should have been kicked now
 			assert!(Nominators::<Test>::get(&101).unwrap().targets.is_empty());
 			// attempt to nominate from 100/101...
@@ -478,6 +618,30 @@ fn blocking_and_kicking_works() {
 		});
 }
 
+#[test]
+fn kick_emits_reason_and_counts_kicks() {
+	ExtBuilder::default().nominate(true).build_and_execute(|| {
+		// 101 nominates 11 and 21.
+		assert_eq!(Nominators::<Test>::get(&101).unwrap().targets, vec![11, 21]);
+		assert_eq!(KickCount::<Test>::get(&11), 0);
+
+		let reason: KickReason = b"commission change".to_vec().try_into().unwrap();
+		// 31 is not nominating 11 and is not counted.
+		assert_ok!(Staking::kick(RuntimeOrigin::signed(10), vec![101, 31], reason.clone()));
+
+		assert_eq!(
+			*staking_events_since_last_call().last().unwrap(),
+			Event::Kicked { nominator: 101, stash: 11, reason }
+		);
+		assert_eq!(KickCount::<Test>::get(&11), 1);
+
+		// kicking again has no effect.
+		assert_ok!(Staking::kick(RuntimeOrigin::signed(10), vec![101], Default::default()));
+		assert!(staking_events_since_last_call().is_empty());
+		assert_eq!(KickCount::<Test>::get(&11), 1);
+	});
+}
+
 #[test]
 fn less_than_needed_candidates_works() {
 	ExtBuilder::default()
@@ -496,8 +660,8 @@ fn less_than_needed_candidates_works() {
 
 			// But the exposure is updated in a simple way. No external votes exists.
 			// This is purely self-vote.
-			assert!(ErasStakers::<Test>::iter_prefix_values(active_era())
-				.all(|exposure| exposure.others.is_empty()));
+			assert!(ErasStakersOverview::<Test>::iter_prefix_values(active_era())
+				.all(|overview| overview.nominator_count == 0));
 		});
 }
 
@@ -616,7 +780,7 @@ fn nominating_and_rewards_should_work() {
 			assert_eq!(Balances::total_balance(&20), initial_balance_20 + total_payout_0 / 2);
 			initial_balance_20 = Balances::total_balance(&20);
 
-			assert_eq!(ErasStakers::<Test>::iter_prefix_values(active_era()).count(), 2);
+			assert_eq!(ErasStakersOverview::<Test>::iter_prefix_values(active_era()).count(), 2);
 			assert_eq!(
 				Staking::eras_stakers(active_era(), 11),
 				Exposure {
@@ -738,6 +902,69 @@ fn nominators_also_get_slashed_pro_rata() {
 	});
 }
 
+#[test]
+fn simulate_slash_matches_applied_slash() {
+	ExtBuilder::default().build_and_execute(|| {
+		mock::start_active_era(1);
+		let slash_percent = Perbill::from_percent(10);
+		let exposure = Staking::eras_stakers(active_era(), 11);
+		let nominator_stake = Staking::ledger(100).unwrap().active;
+		let validator_stake = Staking::ledger(10).unwrap().active;
+
+		let simulation = Staking::simulate_slash(11, slash_percent, active_era()).unwrap();
+		assert_eq!(simulation.own, slash_percent * exposure.own);
+		assert_eq!(
+			simulation.others,
+			exposure
+				.others
+				.iter()
+				.map(|n| (n.who, slash_percent * n.value))
+				.collect::<Vec<_>>(),
+		);
+		assert_eq!(simulation.others[0].0, 101);
+
+		// nothing was applied.
+		assert_eq!(Staking::ledger(100).unwrap().active, nominator_stake);
+		assert_eq!(Staking::ledger(10).unwrap().active, validator_stake);
+		assert!(<Staking as crate::Store>::ValidatorSlashInEra::get(active_era(), 11).is_none());
+		assert!(<Validators<Test>>::contains_key(11));
+		assert!(!is_disabled(10));
+
+		on_offence_now(
+			&[OffenceDetails { offender: (11, exposure), reporters: vec![] }],
+			&[slash_percent],
+		);
+
+		// the simulation matches the slash that was applied.
+		assert_eq!(Staking::ledger(10).unwrap().active, validator_stake - simulation.own);
+		assert_eq!(Staking::ledger(100).unwrap().active, nominator_stake - simulation.others[0].1);
+
+		// the same slash again is not the maximum in the era and slashes nothing more.
+		assert_eq!(
+			Staking::simulate_slash(11, slash_percent, active_era()),
+			Some(Default::default())
+		);
+	});
+}
+
+#[test]
+fn simulate_slash_needs_exposure_in_bonding_period() {
+	ExtBuilder::default().build_and_execute(|| {
+		mock::start_active_era(1);
+		let slash_percent = Perbill::from_percent(10);
+
+		// not a validator.
+		assert_eq!(Staking::simulate_slash(101, slash_percent, active_era()), None);
+		// era in the future.
+		assert_eq!(Staking::simulate_slash(11, slash_percent, active_era() + 1), None);
+
+		// era before the bonding period.
+		mock::start_active_era(1 + BondingDuration::get() + 1);
+		assert_eq!(Staking::simulate_slash(11, slash_percent, 1), None);
+		assert!(Staking::simulate_slash(11, slash_percent, active_era()).is_some());
+	});
+}
+
 #[test]
 fn double_staking_should_fail() {
 	// should test (in the same order):
@@ -1725,7 +1952,7 @@ fn reward_to_stake_works() {
 			let _ = Balances::make_free_balance_be(&20, 1000);
 
 			// Bypass logic and change current exposure
-			ErasStakers::<Test>::insert(0, 21, Exposure { total: 69, own: 69, others: vec![] });
+			EraInfo::<Test>::set_exposure(0, &21, Exposure { total: 69, own: 69, others: vec![] });
 			<Ledger<Test>>::insert(
 				&20,
 				StakingLedger {
@@ -2191,8 +2418,7 @@ fn reward_validator_slashing_validator_does_not_overflow() {
 
 		// Check reward
 		ErasRewardPoints::<Test>::insert(0, reward);
-		ErasStakers::<Test>::insert(0, 11, &exposure);
-		ErasStakersClipped::<Test>::insert(0, 11, exposure);
+		EraInfo::<Test>::set_exposure(0, &11, exposure);
 		ErasValidatorReward::<Test>::insert(0, stake);
 		assert_ok!(Staking::payout_stakers(RuntimeOrigin::signed(1337), 11, 0));
 		assert_eq!(Balances::total_balance(&11), stake * 2);
@@ -2205,9 +2431,9 @@ fn reward_validator_slashing_validator_does_not_overflow() {
 		Staking::bond(RuntimeOrigin::signed(2), 20000, stake - 1, RewardDestination::default())
 			.unwrap();
 		// Override exposure of 11
-		ErasStakers::<Test>::insert(
+		EraInfo::<Test>::set_exposure(
 			0,
-			11,
+			&11,
 			Exposure {
 				total: stake,
 				own: 1,
@@ -2487,6 +2713,10 @@ fn reporters_receive_their_slice() {
 		let reward_each = reward / 2; // split into two pieces.
 		assert_eq!(Balances::free_balance(1), 10 + reward_each);
 		assert_eq!(Balances::free_balance(2), 20 + reward_each);
+		assert!(staking_events_since_last_call().ends_with(&[
+			Event::ReporterRewarded { reporter: 1, amount: reward_each },
+			Event::ReporterRewarded { reporter: 2, amount: reward_each },
+		]));
 	});
 }
 
@@ -3944,6 +4174,37 @@ fn payout_stakers_handles_basic_errors() {
 			expected_last_reward_era
 		));
 
+		// The 100 nominators are split over two pages.
+		assert_noop!(
+			Staking::payout_stakers_by_page(
+				RuntimeOrigin::signed(1337),
+				11,
+				expected_last_reward_era,
+				2
+			),
+			Error::<Test>::InvalidPage.with_weight(err_weight)
+		);
+		assert_noop!(
+			Staking::payout_stakers_by_page(
+				RuntimeOrigin::signed(1337),
+				11,
+				expected_last_reward_era,
+				0
+			),
+			Error::<Test>::AlreadyClaimed.with_weight(err_weight)
+		);
+		assert_ok!(Staking::payout_stakers(
+			RuntimeOrigin::signed(1337),
+			11,
+			expected_start_reward_era
+		));
+		assert_ok!(Staking::payout_stakers_by_page(
+			RuntimeOrigin::signed(1337),
+			11,
+			expected_last_reward_era,
+			1
+		));
+
 		// Can't claim again
 		assert_noop!(
 			Staking::payout_stakers(RuntimeOrigin::signed(1337), 11, expected_start_reward_era),
@@ -4417,6 +4678,73 @@ fn cannot_bond_extra_to_lower_than_ed() {
 		})
 }
 
+#[test]
+fn withdraw_unbonded_sweeps_active_dust() {
+	ExtBuilder::default().nominate(true).build_and_execute(|| {
+		mock::start_active_era(1);
+		assert_ok!(Staking::unbond(RuntimeOrigin::signed(100), 200));
+		mock::start_active_era(2);
+		assert_ok!(Staking::unbond(RuntimeOrigin::signed(100), 200));
+		mock::start_active_era(4);
+
+		// the remaining active bond of 100 becomes dust.
+		ExistentialDeposit::set(200);
+		let free_balance = Balances::free_balance(101);
+		let _ = staking_events_since_last_call();
+
+		// when
+		assert_ok!(Staking::withdraw_unbonded(RuntimeOrigin::signed(100), 0));
+
+		// then the first chunk is withdrawn and the dust is released, the second chunk remains.
+		let ledger = Staking::ledger(&100).unwrap();
+		assert_eq!(ledger.active, 0);
+		assert_eq!(ledger.total, 200);
+		assert_eq!(ledger.unlocking.into_inner(), vec![UnlockChunk { value: 200, era: 5 }]);
+		assert_eq!(Balances::locks(&101)[0].amount, 200);
+		assert!(!Nominators::<Test>::contains_key(101));
+		assert_eq!(
+			staking_events_since_last_call(),
+			vec![
+				Event::Chilled { stash: 101 },
+				Event::DustSwept { stash: 101, amount: 100 },
+				Event::Withdrawn { stash: 101, amount: 200 },
+			]
+		);
+
+		// the dust is returned to the stash.
+		assert_eq!(Balances::free_balance(101), free_balance);
+		assert_eq!(DustTreasuryUnbalanced::get(), 0);
+	})
+}
+
+#[test]
+fn withdraw_unbonded_sweeps_active_dust_to_handler() {
+	ExtBuilder::default().nominate(true).build_and_execute(|| {
+		DustToTreasury::set(true);
+		mock::start_active_era(1);
+		assert_ok!(Staking::unbond(RuntimeOrigin::signed(100), 200));
+		mock::start_active_era(2);
+		assert_ok!(Staking::unbond(RuntimeOrigin::signed(100), 200));
+		mock::start_active_era(4);
+
+		// the remaining active bond of 100 becomes dust.
+		ExistentialDeposit::set(200);
+		let free_balance = Balances::free_balance(101);
+
+		// when
+		assert_ok!(Staking::withdraw_unbonded(RuntimeOrigin::signed(100), 0));
+
+		// then the dust is moved from the stash to the handler.
+		let ledger = Staking::ledger(&100).unwrap();
+		assert_eq!(ledger.active, 0);
+		assert_eq!(ledger.total, 200);
+		assert_eq!(Balances::locks(&101)[0].amount, 200);
+		assert_eq!(Balances::free_balance(101), free_balance - 100);
+		assert_eq!(DustTreasuryUnbalanced::get(), 100);
+		assert_eq!(*staking_events().last().unwrap(), Event::Withdrawn { stash: 101, amount: 200 });
+	})
+}
+
 #[test]
 fn do_not_die_when_active_is_ed() {
 	let ed = 10;
@@ -4825,7 +5153,9 @@ fn chill_other_works() {
 				ConfigOp::Remove,
 				ConfigOp::Remove,
 				ConfigOp::Remove,
-				ConfigOp::Remove
+				ConfigOp::Remove,
+				ConfigOp::Noop,
+				ConfigOp::Noop
 			));
 
 			// Still can't chill these users
@@ -4846,6 +5176,8 @@ fn chill_other_works() {
 				ConfigOp::Set(10),
 				ConfigOp::Set(10),
 				ConfigOp::Noop,
+				ConfigOp::Noop,
+				ConfigOp::Noop,
 				ConfigOp::Noop
 			));
 
@@ -4867,6 +5199,8 @@ fn chill_other_works() {
 				ConfigOp::Remove,
 				ConfigOp::Remove,
 				ConfigOp::Noop,
+				ConfigOp::Noop,
+				ConfigOp::Noop,
 				ConfigOp::Noop
 			));
 
@@ -4888,6 +5222,8 @@ fn chill_other_works() {
 				ConfigOp::Set(10),
 				ConfigOp::Set(10),
 				ConfigOp::Set(Percent::from_percent(75)),
+				ConfigOp::Noop,
+				ConfigOp::Noop,
 				ConfigOp::Noop
 			));
 
@@ -4934,6 +5270,8 @@ fn capped_stakers_works() {
 			ConfigOp::Set(max),
 			ConfigOp::Remove,
 			ConfigOp::Remove,
+			ConfigOp::Noop,
+			ConfigOp::Noop,
 		));
 
 		// can create `max - validator_count` validators
@@ -5007,6 +5345,8 @@ fn capped_stakers_works() {
 			ConfigOp::Remove,
 			ConfigOp::Noop,
 			ConfigOp::Noop,
+			ConfigOp::Noop,
+			ConfigOp::Noop,
 		));
 		assert_ok!(Staking::nominate(RuntimeOrigin::signed(last_nominator), vec![1]));
 		assert_ok!(Staking::validate(
@@ -5042,6 +5382,8 @@ fn min_commission_works() {
 			ConfigOp::Remove,
 			ConfigOp::Remove,
 			ConfigOp::Set(Perbill::from_percent(10)),
+			ConfigOp::Noop,
+			ConfigOp::Noop,
 		));
 
 		// can't make it less than 10 now
@@ -5248,247 +5590,255 @@ fn force_apply_min_commission_works() {
 
 #[test]
 fn proportional_slash_stop_slashing_if_remaining_zero() {
-	let c = |era, value| UnlockChunk::<Balance> { era, value };
-	// Given
-	let mut ledger = StakingLedger::<Test> {
-		stash: 123,
-		total: 40,
-		active: 20,
-		// we have some chunks, but they are not affected.
-		unlocking: bounded_vec![c(1, 10), c(2, 10)],
-		claimed_rewards: bounded_vec![],
-	};
-
-	assert_eq!(BondingDuration::get(), 3);
-
-	// should not slash more than the amount requested, by accidentally slashing the first chunk.
-	assert_eq!(ledger.slash(18, 1, 0), 18);
+	ExtBuilder::default().build_and_execute(|| {
+		let c = |era, value| UnlockChunk::<Balance> { era, value };
+		// Given
+		let mut ledger = StakingLedger::<Test> {
+			stash: 123,
+			total: 40,
+			active: 20,
+			// we have some chunks, but they are not affected.
+			unlocking: bounded_vec![c(1, 10), c(2, 10)],
+			claimed_rewards: bounded_vec![],
+		};
+
+		assert_eq!(BondingDuration::get(), 3);
+
+		// should not slash more than the amount requested, by accidentally slashing the first
+		// chunk.
+		assert_eq!(ledger.slash(18, 1, 0), 18);
+	});
 }
 
 #[test]
 fn proportional_ledger_slash_works() {
-	let c = |era, value| UnlockChunk::<Balance> { era, value };
-	// Given
-	let mut ledger = StakingLedger::<Test> {
-		stash: 123,
-		total: 10,
-		active: 10,
-		unlocking: bounded_vec![],
-		claimed_rewards: bounded_vec![],
-	};
-	assert_eq!(BondingDuration::get(), 3);
-
-	// When we slash a ledger with no unlocking chunks
-	assert_eq!(ledger.slash(5, 1, 0), 5);
-	// Then
-	assert_eq!(ledger.total, 5);
-	assert_eq!(ledger.active, 5);
-	assert_eq!(LedgerSlashPerEra::get().0, 5);
-	assert_eq!(LedgerSlashPerEra::get().1, Default::default());
-
-	// When we slash a ledger with no unlocking chunks and the slash amount is greater then the
-	// total
-	assert_eq!(ledger.slash(11, 1, 0), 5);
-	// Then
-	assert_eq!(ledger.total, 0);
-	assert_eq!(ledger.active, 0);
-	assert_eq!(LedgerSlashPerEra::get().0, 0);
-	assert_eq!(LedgerSlashPerEra::get().1, Default::default());
-
-	// Given
-	ledger.unlocking = bounded_vec![c(4, 10), c(5, 10)];
-	ledger.total = 2 * 10;
-	ledger.active = 0;
-	// When all the chunks overlap with the slash eras
-	assert_eq!(ledger.slash(20, 0, 0), 20);
-	// Then
-	assert_eq!(ledger.unlocking, vec![]);
-	assert_eq!(ledger.total, 0);
-	assert_eq!(LedgerSlashPerEra::get().0, 0);
-	assert_eq!(LedgerSlashPerEra::get().1, BTreeMap::from([(4, 0), (5, 0)]));
-
-	// Given
-	ledger.unlocking = bounded_vec![c(4, 100), c(5, 100), c(6, 100), c(7, 100)];
-	ledger.total = 4 * 100;
-	ledger.active = 0;
-	// When the first 2 chunks don't overlap with the affected range of unlock eras.
-	assert_eq!(ledger.slash(140, 0, 3), 140);
-	// Then
-	assert_eq!(ledger.unlocking, vec![c(4, 100), c(5, 100), c(6, 30), c(7, 30)]);
-	assert_eq!(ledger.total, 4 * 100 - 140);
-	assert_eq!(LedgerSlashPerEra::get().0, 0);
-	assert_eq!(LedgerSlashPerEra::get().1, BTreeMap::from([(6, 30), (7, 30)]));
-
-	// Given
-	ledger.unlocking = bounded_vec![c(4, 100), c(5, 100), c(6, 100), c(7, 100)];
-	ledger.total = 4 * 100;
-	ledger.active = 0;
-	// When the first 2 chunks don't overlap with the affected range of unlock eras.
-	assert_eq!(ledger.slash(15, 0, 3), 15);
-	// Then
-	assert_eq!(ledger.unlocking, vec![c(4, 100), c(5, 100), c(6, 100 - 8), c(7, 100 - 7)]);
-	assert_eq!(ledger.total, 4 * 100 - 15);
-	assert_eq!(LedgerSlashPerEra::get().0, 0);
-	assert_eq!(LedgerSlashPerEra::get().1, BTreeMap::from([(6, 92), (7, 93)]));
-
-	// Given
-	ledger.unlocking = bounded_vec![c(4, 40), c(5, 100), c(6, 10), c(7, 250)];
-	ledger.active = 500;
-	// 900
-	ledger.total = 40 + 10 + 100 + 250 + 500;
-	// When we have a partial slash that touches all chunks
-	assert_eq!(ledger.slash(900 / 2, 0, 0), 450);
-	// Then
-	assert_eq!(ledger.active, 500 / 2);
-	assert_eq!(ledger.unlocking, vec![c(4, 40 / 2), c(5, 100 / 2), c(6, 10 / 2), c(7, 250 / 2)]);
-	assert_eq!(ledger.total, 900 / 2);
-	assert_eq!(LedgerSlashPerEra::get().0, 500 / 2);
-	assert_eq!(
-		LedgerSlashPerEra::get().1,
-		BTreeMap::from([(4, 40 / 2), (5, 100 / 2), (6, 10 / 2), (7, 250 / 2)])
-	);
-
-	// slash 1/4th with not chunk.
-	ledger.unlocking = bounded_vec![];
-	ledger.active = 500;
-	ledger.total = 500;
-	// When we have a partial slash that touches all chunks
-	assert_eq!(ledger.slash(500 / 4, 0, 0), 500 / 4);
-	// Then
-	assert_eq!(ledger.active, 3 * 500 / 4);
-	assert_eq!(ledger.unlocking, vec![]);
-	assert_eq!(ledger.total, ledger.active);
-	assert_eq!(LedgerSlashPerEra::get().0, 3 * 500 / 4);
-	assert_eq!(LedgerSlashPerEra::get().1, Default::default());
-
-	// Given we have the same as above,
-	ledger.unlocking = bounded_vec![c(4, 40), c(5, 100), c(6, 10), c(7, 250)];
-	ledger.active = 500;
-	ledger.total = 40 + 10 + 100 + 250 + 500; // 900
-	assert_eq!(ledger.total, 900);
-	// When we have a higher min balance
-	assert_eq!(
-		ledger.slash(
-			900 / 2,
-			25, /* min balance - chunks with era 0 & 2 will be slashed to <=25, causing it to
-			     * get swept */
-			0
-		),
-		450
-	);
-	assert_eq!(ledger.active, 500 / 2);
-	// the last chunk was not slashed 50% like all the rest, because some other earlier chunks got
-	// dusted.
-	assert_eq!(ledger.unlocking, vec![c(5, 100 / 2), c(7, 150)]);
-	assert_eq!(ledger.total, 900 / 2);
-	assert_eq!(LedgerSlashPerEra::get().0, 500 / 2);
-	assert_eq!(
-		LedgerSlashPerEra::get().1,
-		BTreeMap::from([(4, 0), (5, 100 / 2), (6, 0), (7, 150)])
-	);
-
-	// Given
-	// slash order --------------------NA--------2----------0----------1----
-	ledger.unlocking = bounded_vec![c(4, 40), c(5, 100), c(6, 10), c(7, 250)];
-	ledger.active = 500;
-	ledger.total = 40 + 10 + 100 + 250 + 500; // 900
-	assert_eq!(
-		ledger.slash(
-			500 + 10 + 250 + 100 / 2, // active + era 6 + era 7 + era 5 / 2
-			0,
-			3 /* slash era 6 first, so the affected parts are era 6, era 7 and
-			   * ledge.active. This will cause the affected to go to zero, and then we will
-			   * start slashing older chunks */
-		),
-		500 + 250 + 10 + 100 / 2
-	);
-	// Then
-	assert_eq!(ledger.active, 0);
-	assert_eq!(ledger.unlocking, vec![c(4, 40), c(5, 100 / 2)]);
-	assert_eq!(ledger.total, 90);
-	assert_eq!(LedgerSlashPerEra::get().0, 0);
-	assert_eq!(LedgerSlashPerEra::get().1, BTreeMap::from([(5, 100 / 2), (6, 0), (7, 0)]));
-
-	// Given
-	// iteration order------------------NA---------2----------0----------1----
-	ledger.unlocking = bounded_vec![c(4, 100), c(5, 100), c(6, 100), c(7, 100)];
-	ledger.active = 100;
-	ledger.total = 5 * 100;
-	// When
-	assert_eq!(
-		ledger.slash(
-			351, // active + era 6 + era 7 + era 5 / 2 + 1
-			50,  // min balance - everything slashed below 50 will get dusted
-			3    /* slash era 3+3 first, so the affected parts are era 6, era 7 and
-			      * ledge.active. This will cause the affected to go to zero, and then we will
-			      * start slashing older chunks */
-		),
-		400
-	);
-	// Then
-	assert_eq!(ledger.active, 0);
-	assert_eq!(ledger.unlocking, vec![c(4, 100)]);
-	assert_eq!(ledger.total, 100);
-	assert_eq!(LedgerSlashPerEra::get().0, 0);
-	assert_eq!(LedgerSlashPerEra::get().1, BTreeMap::from([(5, 0), (6, 0), (7, 0)]));
-
-	// Tests for saturating arithmetic
-
-	// Given
-	let slash = u64::MAX as Balance * 2;
-	// The value of the other parts of ledger that will get slashed
-	let value = slash - (10 * 4);
-
-	ledger.active = 10;
-	ledger.unlocking = bounded_vec![c(4, 10), c(5, 10), c(6, 10), c(7, value)];
-	ledger.total = value + 40;
-	// When
-	let slash_amount = ledger.slash(slash, 0, 0);
-	assert_eq_error_rate!(slash_amount, slash, 5);
-	// Then
-	assert_eq!(ledger.active, 0); // slash of 9
-	assert_eq!(ledger.unlocking, vec![]);
-	assert_eq!(ledger.total, 0);
-	assert_eq!(LedgerSlashPerEra::get().0, 0);
-	assert_eq!(LedgerSlashPerEra::get().1, BTreeMap::from([(4, 0), (5, 0), (6, 0), (7, 0)]));
-
-	// Given
-	use sp_runtime::PerThing as _;
-	let slash = u64::MAX as Balance * 2;
-	let value = u64::MAX as Balance * 2;
-	let unit = 100;
-	// slash * value that will saturate
-	assert!(slash.checked_mul(value).is_none());
-	// but slash * unit won't.
-	assert!(slash.checked_mul(unit).is_some());
-	ledger.unlocking = bounded_vec![c(4, unit), c(5, value), c(6, unit), c(7, unit)];
-	//--------------------------------------note value^^^
-	ledger.active = unit;
-	ledger.total = unit * 4 + value;
-	// When
-	assert_eq!(ledger.slash(slash, 0, 0), slash - 5);
-	// Then
-	// The amount slashed out of `unit`
-	let affected_balance = value + unit * 4;
-	let ratio =
-		Perquintill::from_rational_with_rounding(slash, affected_balance, Rounding::Up).unwrap();
-	// `unit` after the slash is applied
-	let unit_slashed = {
-		let unit_slash = ratio.mul_ceil(unit);
-		unit - unit_slash
-	};
-	let value_slashed = {
-		let value_slash = ratio.mul_ceil(value);
-		value - value_slash
-	};
-	assert_eq!(ledger.active, unit_slashed);
-	assert_eq!(ledger.unlocking, vec![c(5, value_slashed)]);
-	assert_eq!(ledger.total, value_slashed);
-	assert_eq!(LedgerSlashPerEra::get().0, 0);
-	assert_eq!(
-		LedgerSlashPerEra::get().1,
-		BTreeMap::from([(4, 0), (5, value_slashed), (6, 0), (7, 0)])
-	);
+	ExtBuilder::default().build_and_execute(|| {
+		let c = |era, value| UnlockChunk::<Balance> { era, value };
+		// Given
+		let mut ledger = StakingLedger::<Test> {
+			stash: 123,
+			total: 10,
+			active: 10,
+			unlocking: bounded_vec![],
+			claimed_rewards: bounded_vec![],
+		};
+		assert_eq!(BondingDuration::get(), 3);
+
+		// When we slash a ledger with no unlocking chunks
+		assert_eq!(ledger.slash(5, 1, 0), 5);
+		// Then
+		assert_eq!(ledger.total, 5);
+		assert_eq!(ledger.active, 5);
+		assert_eq!(LedgerSlashPerEra::get().0, 5);
+		assert_eq!(LedgerSlashPerEra::get().1, Default::default());
+
+		// When we slash a ledger with no unlocking chunks and the slash amount is greater then the
+		// total
+		assert_eq!(ledger.slash(11, 1, 0), 5);
+		// Then
+		assert_eq!(ledger.total, 0);
+		assert_eq!(ledger.active, 0);
+		assert_eq!(LedgerSlashPerEra::get().0, 0);
+		assert_eq!(LedgerSlashPerEra::get().1, Default::default());
+
+		// Given
+		ledger.unlocking = bounded_vec![c(4, 10), c(5, 10)];
+		ledger.total = 2 * 10;
+		ledger.active = 0;
+		// When all the chunks overlap with the slash eras
+		assert_eq!(ledger.slash(20, 0, 0), 20);
+		// Then
+		assert_eq!(ledger.unlocking, vec![]);
+		assert_eq!(ledger.total, 0);
+		assert_eq!(LedgerSlashPerEra::get().0, 0);
+		assert_eq!(LedgerSlashPerEra::get().1, BTreeMap::from([(4, 0), (5, 0)]));
+
+		// Given
+		ledger.unlocking = bounded_vec![c(4, 100), c(5, 100), c(6, 100), c(7, 100)];
+		ledger.total = 4 * 100;
+		ledger.active = 0;
+		// When the first 2 chunks don't overlap with the affected range of unlock eras.
+		assert_eq!(ledger.slash(140, 0, 3), 140);
+		// Then
+		assert_eq!(ledger.unlocking, vec![c(4, 100), c(5, 100), c(6, 30), c(7, 30)]);
+		assert_eq!(ledger.total, 4 * 100 - 140);
+		assert_eq!(LedgerSlashPerEra::get().0, 0);
+		assert_eq!(LedgerSlashPerEra::get().1, BTreeMap::from([(6, 30), (7, 30)]));
+
+		// Given
+		ledger.unlocking = bounded_vec![c(4, 100), c(5, 100), c(6, 100), c(7, 100)];
+		ledger.total = 4 * 100;
+		ledger.active = 0;
+		// When the first 2 chunks don't overlap with the affected range of unlock eras.
+		assert_eq!(ledger.slash(15, 0, 3), 15);
+		// Then
+		assert_eq!(ledger.unlocking, vec![c(4, 100), c(5, 100), c(6, 100 - 8), c(7, 100 - 7)]);
+		assert_eq!(ledger.total, 4 * 100 - 15);
+		assert_eq!(LedgerSlashPerEra::get().0, 0);
+		assert_eq!(LedgerSlashPerEra::get().1, BTreeMap::from([(6, 92), (7, 93)]));
+
+		// Given
+		ledger.unlocking = bounded_vec![c(4, 40), c(5, 100), c(6, 10), c(7, 250)];
+		ledger.active = 500;
+		// 900
+		ledger.total = 40 + 10 + 100 + 250 + 500;
+		// When we have a partial slash that touches all chunks
+		assert_eq!(ledger.slash(900 / 2, 0, 0), 450);
+		// Then
+		assert_eq!(ledger.active, 500 / 2);
+		assert_eq!(
+			ledger.unlocking,
+			vec![c(4, 40 / 2), c(5, 100 / 2), c(6, 10 / 2), c(7, 250 / 2)]
+		);
+		assert_eq!(ledger.total, 900 / 2);
+		assert_eq!(LedgerSlashPerEra::get().0, 500 / 2);
+		assert_eq!(
+			LedgerSlashPerEra::get().1,
+			BTreeMap::from([(4, 40 / 2), (5, 100 / 2), (6, 10 / 2), (7, 250 / 2)])
+		);
+
+		// slash 1/4th with not chunk.
+		ledger.unlocking = bounded_vec![];
+		ledger.active = 500;
+		ledger.total = 500;
+		// When we have a partial slash that touches all chunks
+		assert_eq!(ledger.slash(500 / 4, 0, 0), 500 / 4);
+		// Then
+		assert_eq!(ledger.active, 3 * 500 / 4);
+		assert_eq!(ledger.unlocking, vec![]);
+		assert_eq!(ledger.total, ledger.active);
+		assert_eq!(LedgerSlashPerEra::get().0, 3 * 500 / 4);
+		assert_eq!(LedgerSlashPerEra::get().1, Default::default());
+
+		// Given we have the same as above,
+		ledger.unlocking = bounded_vec![c(4, 40), c(5, 100), c(6, 10), c(7, 250)];
+		ledger.active = 500;
+		ledger.total = 40 + 10 + 100 + 250 + 500; // 900
+		assert_eq!(ledger.total, 900);
+		// When we have a higher min balance
+		assert_eq!(
+			ledger.slash(
+				900 / 2,
+				25, /* min balance - chunks with era 0 & 2 will be slashed to <=25, causing it
+				     * to get swept */
+				0
+			),
+			450
+		);
+		assert_eq!(ledger.active, 500 / 2);
+		// the last chunk was not slashed 50% like all the rest, because some other earlier chunks
+		// got dusted.
+		assert_eq!(ledger.unlocking, vec![c(5, 100 / 2), c(7, 150)]);
+		assert_eq!(ledger.total, 900 / 2);
+		assert_eq!(LedgerSlashPerEra::get().0, 500 / 2);
+		assert_eq!(
+			LedgerSlashPerEra::get().1,
+			BTreeMap::from([(4, 0), (5, 100 / 2), (6, 0), (7, 150)])
+		);
+
+		// Given
+		// slash order --------------------NA--------2----------0----------1----
+		ledger.unlocking = bounded_vec![c(4, 40), c(5, 100), c(6, 10), c(7, 250)];
+		ledger.active = 500;
+		ledger.total = 40 + 10 + 100 + 250 + 500; // 900
+		assert_eq!(
+			ledger.slash(
+				500 + 10 + 250 + 100 / 2, // active + era 6 + era 7 + era 5 / 2
+				0,
+				3 /* slash era 6 first, so the affected parts are era 6, era 7 and
+				   * ledge.active. This will cause the affected to go to zero, and then we will
+				   * start slashing older chunks */
+			),
+			500 + 250 + 10 + 100 / 2
+		);
+		// Then
+		assert_eq!(ledger.active, 0);
+		assert_eq!(ledger.unlocking, vec![c(4, 40), c(5, 100 / 2)]);
+		assert_eq!(ledger.total, 90);
+		assert_eq!(LedgerSlashPerEra::get().0, 0);
+		assert_eq!(LedgerSlashPerEra::get().1, BTreeMap::from([(5, 100 / 2), (6, 0), (7, 0)]));
+
+		// Given
+		// iteration order------------------NA---------2----------0----------1----
+		ledger.unlocking = bounded_vec![c(4, 100), c(5, 100), c(6, 100), c(7, 100)];
+		ledger.active = 100;
+		ledger.total = 5 * 100;
+		// When
+		assert_eq!(
+			ledger.slash(
+				351, // active + era 6 + era 7 + era 5 / 2 + 1
+				50,  // min balance - everything slashed below 50 will get dusted
+				3    /* slash era 3+3 first, so the affected parts are era 6, era 7 and
+				      * ledge.active. This will cause the affected to go to zero, and then we
+				      * will start slashing older chunks */
+			),
+			400
+		);
+		// Then
+		assert_eq!(ledger.active, 0);
+		assert_eq!(ledger.unlocking, vec![c(4, 100)]);
+		assert_eq!(ledger.total, 100);
+		assert_eq!(LedgerSlashPerEra::get().0, 0);
+		assert_eq!(LedgerSlashPerEra::get().1, BTreeMap::from([(5, 0), (6, 0), (7, 0)]));
+
+		// Tests for saturating arithmetic
+
+		// Given
+		let slash = u64::MAX as Balance * 2;
+		// The value of the other parts of ledger that will get slashed
+		let value = slash - (10 * 4);
+
+		ledger.active = 10;
+		ledger.unlocking = bounded_vec![c(4, 10), c(5, 10), c(6, 10), c(7, value)];
+		ledger.total = value + 40;
+		// When
+		let slash_amount = ledger.slash(slash, 0, 0);
+		assert_eq_error_rate!(slash_amount, slash, 5);
+		// Then
+		assert_eq!(ledger.active, 0); // slash of 9
+		assert_eq!(ledger.unlocking, vec![]);
+		assert_eq!(ledger.total, 0);
+		assert_eq!(LedgerSlashPerEra::get().0, 0);
+		assert_eq!(LedgerSlashPerEra::get().1, BTreeMap::from([(4, 0), (5, 0), (6, 0), (7, 0)]));
+
+		// Given
+		use sp_runtime::PerThing as _;
+		let slash = u64::MAX as Balance * 2;
+		let value = u64::MAX as Balance * 2;
+		let unit = 100;
+		// slash * value that will saturate
+		assert!(slash.checked_mul(value).is_none());
+		// but slash * unit won't.
+		assert!(slash.checked_mul(unit).is_some());
+		ledger.unlocking = bounded_vec![c(4, unit), c(5, value), c(6, unit), c(7, unit)];
+		//--------------------------------------note value^^^
+		ledger.active = unit;
+		ledger.total = unit * 4 + value;
+		// When
+		assert_eq!(ledger.slash(slash, 0, 0), slash - 5);
+		// Then
+		// The amount slashed out of `unit`
+		let affected_balance = value + unit * 4;
+		let ratio = Perquintill::from_rational_with_rounding(slash, affected_balance, Rounding::Up)
+			.unwrap();
+		// `unit` after the slash is applied
+		let unit_slashed = {
+			let unit_slash = ratio.mul_ceil(unit);
+			unit - unit_slash
+		};
+		let value_slashed = {
+			let value_slash = ratio.mul_ceil(value);
+			value - value_slash
+		};
+		assert_eq!(ledger.active, unit_slashed);
+		assert_eq!(ledger.unlocking, vec![c(5, value_slashed)]);
+		assert_eq!(ledger.total, value_slashed);
+		assert_eq!(LedgerSlashPerEra::get().0, 0);
+		assert_eq!(
+			LedgerSlashPerEra::get().1,
+			BTreeMap::from([(4, 0), (5, value_slashed), (6, 0), (7, 0)])
+		);
+	});
 }
 
 #[test]
@@ -5634,6 +5984,58 @@ fn reducing_history_depth_abrupt() {
 	});
 }
 
+#[test]
+fn orphaned_eras_are_pruned_lazily() {
+	let era_exists = |era| ErasStartSessionIndex::<Test>::contains_key(era);
+	let original_history_depth = HistoryDepth::get();
+	let current_era = original_history_depth + 10;
+
+	let mut ext = ExtBuilder::default().build();
+	ext.execute_with(|| mock::start_active_era(current_era));
+	// keys only present in the overlay are removed regardless of the limit, hence commit them.
+	ext.commit_all().unwrap();
+
+	ext.execute_with(|| {
+		let oldest_era = current_era - original_history_depth;
+		assert!(era_exists(oldest_era));
+		assert!(!era_exists(oldest_era - 1));
+
+		// history depth reduced. no era is cleared before the next one is planned.
+		let history_depth = original_history_depth / 2;
+		HistoryDepth::set(history_depth);
+		let newest_orphan = current_era - history_depth - 1;
+		assert!(era_exists(newest_orphan));
+
+		migrations::PruneOrphanedEras::<Test>::on_runtime_upgrade();
+		assert_eq!(OrphanedErasCursor::<Test>::get(), Some(newest_orphan));
+
+		// not enough weight to remove a single key
+		Staking::on_idle(System::block_number(), Weight::zero());
+		assert_eq!(OrphanedErasCursor::<Test>::get(), Some(newest_orphan));
+
+		// an era that can't be removed at once is continued later
+		let db_weight = <Test as frame_system::Config>::DbWeight::get();
+		Staking::on_idle(System::block_number(), db_weight.reads_writes(1, 7));
+		assert_eq!(OrphanedErasCursor::<Test>::get(), Some(newest_orphan));
+		assert!(era_exists(newest_orphan));
+
+		Staking::on_idle(System::block_number(), Weight::MAX);
+		assert_eq!(OrphanedErasCursor::<Test>::get(), None);
+		for era in 0..=newest_orphan {
+			assert!(!era_exists(era));
+			assert_eq!(ErasStakersOverview::<Test>::iter_prefix(era).count(), 0);
+			assert_eq!(ErasValidatorPrefs::<Test>::iter_prefix(era).count(), 0);
+		}
+		for era in newest_orphan + 1..=current_era {
+			assert!(era_exists(era));
+		}
+
+		// fix the corrupted state for post conditions check
+		HistoryDepth::set(original_history_depth);
+		Staking::do_try_state(System::block_number()).unwrap();
+	});
+}
+
 #[test]
 fn reducing_max_unlocking_chunks_abrupt() {
 	// Concern is on validators only
@@ -5780,6 +6182,403 @@ fn set_min_commission_works_with_admin_origin() {
 	})
 }
 
+#[test]
+fn min_nominator_bond_controller_adjusts_bond() {
+	ExtBuilder::default().build_and_execute(|| {
+		let controller = NominatorBondController {
+			target_saturation: Perbill::from_percent(50),
+			max_step: Perbill::from_percent(10),
+			floor: 95,
+			ceiling: 1_000,
+		};
+		assert_noop!(
+			Staking::set_min_nominator_bond_controller(
+				RuntimeOrigin::signed(10),
+				Some(controller.clone())
+			),
+			BadOrigin
+		);
+		assert_noop!(
+			Staking::set_min_nominator_bond_controller(
+				RuntimeOrigin::root(),
+				Some(NominatorBondController { floor: 1_001, ..controller.clone() })
+			),
+			Error::<Test>::InvalidNominatorBondController
+		);
+		assert_noop!(
+			Staking::set_min_nominator_bond_controller(
+				RuntimeOrigin::root(),
+				Some(NominatorBondController { floor: 0, ..controller.clone() })
+			),
+			Error::<Test>::InvalidNominatorBondController
+		);
+		assert_ok!(Staking::set_min_nominator_bond_controller(
+			RuntimeOrigin::root(),
+			Some(controller)
+		));
+		MinNominatorBond::<Test>::put(100);
+		let _ = staking_events_since_last_call();
+
+		// more voters than targeted: the bond is raised by at most `max_step`.
+		MaxElectingVoters::set(4);
+		assert!(VoterBagsList::count() > 2);
+		start_active_era(1);
+		assert_eq!(MinNominatorBond::<Test>::get(), 110);
+		assert!(staking_events_since_last_call()
+			.contains(&Event::MinNominatorBondAdjusted { old: 100, new: 110 }));
+
+		// less voters than targeted: the bond is lowered.
+		MaxElectingVoters::set(100);
+		start_active_era(2);
+		assert_eq!(MinNominatorBond::<Test>::get(), 99);
+
+		// but never below the floor.
+		start_active_era(3);
+		assert_eq!(MinNominatorBond::<Test>::get(), 95);
+
+		// without a controller the bond is left alone.
+		assert_ok!(Staking::set_min_nominator_bond_controller(RuntimeOrigin::root(), None));
+		start_active_era(4);
+		assert_eq!(MinNominatorBond::<Test>::get(), 95);
+	});
+}
+
+#[test]
+fn min_nominator_bond_controller_raises_zero_bond() {
+	ExtBuilder::default().build_and_execute(|| {
+		assert_ok!(Staking::set_min_nominator_bond_controller(
+			RuntimeOrigin::root(),
+			Some(NominatorBondController {
+				target_saturation: Perbill::from_percent(50),
+				max_step: Perbill::from_percent(10),
+				floor: 100,
+				ceiling: 1_000,
+			})
+		));
+		MinNominatorBond::<Test>::put(0);
+
+		// the step is taken from the floor while the bond is below it.
+		MaxElectingVoters::set(4);
+		start_active_era(1);
+		assert_eq!(MinNominatorBond::<Test>::get(), 110);
+		start_active_era(2);
+		assert_eq!(MinNominatorBond::<Test>::get(), 121);
+	});
+}
+
+#[test]
+fn min_nominator_bond_adjustment_is_weighed() {
+	ExtBuilder::default().build_and_execute(|| {
+		assert_ok!(Staking::set_min_nominator_bond_controller(
+			RuntimeOrigin::root(),
+			Some(NominatorBondController {
+				target_saturation: Perbill::from_percent(50),
+				max_step: Perbill::from_percent(10),
+				floor: 100,
+				ceiling: 1_000,
+			})
+		));
+		LastVoterSnapshot::<Test>::put((10, 4));
+		let before = System::block_weight().total();
+
+		Staking::adjust_min_nominator_bond();
+
+		let db_weight = <Test as frame_system::Config>::DbWeight::get();
+		assert_eq!(System::block_weight().total() - before, db_weight.reads_writes(3, 2));
+	});
+}
+
+#[test]
+fn stakers_can_be_imported_in_batches() {
+	ExtBuilder::default().build_and_execute(|| {
+		for who in 200..206 {
+			let _ = Balances::make_free_balance_be(&who, 1_000);
+		}
+		let stakers = vec![
+			(200, 201, 500, StakerStatus::Validator),
+			(202, 203, 500, StakerStatus::Nominator(vec![200, 11])),
+			(204, 205, 500, StakerStatus::Idle),
+		];
+		let checksum = stakers.iter().fold(Default::default(), |checksum, staker| {
+			<Test as frame_system::Config>::Hashing::hash_of(&(checksum, staker))
+		});
+		let batch = |stakers: &[ImportedStaker<Test>]| stakers.to_vec().try_into().unwrap();
+
+		assert_noop!(
+			Staking::import_stakers(RuntimeOrigin::root(), 0, batch(&stakers[..1])),
+			Error::<Test>::NoStakerImport
+		);
+		assert_noop!(
+			Staking::start_staker_import(RuntimeOrigin::signed(10), 3, checksum),
+			BadOrigin
+		);
+		assert_ok!(Staking::start_staker_import(RuntimeOrigin::root(), 3, checksum));
+		assert_noop!(
+			Staking::start_staker_import(RuntimeOrigin::root(), 3, checksum),
+			Error::<Test>::StakerImportInProgress
+		);
+
+		assert_ok!(Staking::import_stakers(RuntimeOrigin::root(), 0, batch(&stakers[..2])));
+		assert_eq!(StakerImport::<Test>::get().map(|p| p.imported), Some(2));
+		assert!(Validators::<Test>::contains_key(200));
+		assert_eq!(Nominators::<Test>::get(202).unwrap().targets.into_inner(), vec![200, 11]);
+
+		// a batch must continue exactly where the import stopped and stay within `total`.
+		assert_noop!(
+			Staking::import_stakers(RuntimeOrigin::root(), 0, batch(&stakers[..2])),
+			Error::<Test>::StakerImportCursorMismatch
+		);
+		assert_noop!(
+			Staking::import_stakers(RuntimeOrigin::root(), 2, batch(&stakers[1..])),
+			Error::<Test>::TooManyImportedStakers
+		);
+
+		assert_ok!(Staking::import_stakers(RuntimeOrigin::root(), 2, batch(&stakers[2..])));
+		assert!(!StakerImport::<Test>::exists());
+		assert_eq!(Staking::bonded(&204), Some(205));
+		assert_eq!(
+			staking_events().into_iter().rev().take(2).collect::<Vec<_>>(),
+			vec![
+				Event::StakerImportCompleted { total: 3 },
+				Event::Bonded { stash: 204, amount: 500 }
+			]
+		);
+	});
+}
+
+#[test]
+fn staker_import_rejects_checksum_mismatch() {
+	ExtBuilder::default().build_and_execute(|| {
+		let _ = Balances::make_free_balance_be(&200, 1_000);
+		let stakers = vec![(200, 201, 500, StakerStatus::Validator)];
+
+		assert_ok!(Staking::start_staker_import(RuntimeOrigin::root(), 1, Default::default()));
+		assert_noop!(
+			Staking::import_stakers(RuntimeOrigin::root(), 0, stakers.try_into().unwrap()),
+			Error::<Test>::StakerImportChecksumMismatch
+		);
+		assert!(!Validators::<Test>::contains_key(200));
+
+		assert_ok!(Staking::cancel_staker_import(RuntimeOrigin::root()));
+		assert!(!StakerImport::<Test>::exists());
+		assert_eq!(*staking_events().last().unwrap(), Event::StakerImportCancelled { imported: 0 });
+	});
+}
+
+mod paged_exposure {
+	use super::*;
+	use sp_staking::StakingInterface;
+
+	fn exposure(others: Vec<(AccountId, Balance)>) -> Exposure<AccountId, Balance> {
+		let others: Vec<_> = others
+			.into_iter()
+			.map(|(who, value)| IndividualExposure { who, value })
+			.collect();
+		let total = others.iter().map(|e| e.value).sum::<Balance>() + 1000;
+		Exposure { total, own: 1000, others }
+	}
+
+	#[test]
+	fn exposures_are_split_into_pages() {
+		ExtBuilder::default().build_and_execute(|| {
+			MaxExposurePageSize::set(2);
+			EraInfo::<Test>::set_exposure(
+				5,
+				&11,
+				exposure(vec![(101, 10), (102, 50), (103, 30), (104, 40), (105, 20)]),
+			);
+
+			assert_eq!(
+				EraInfo::<Test>::get_exposure_overview(5, &11),
+				Some(PagedExposureMetadata {
+					total: 1150,
+					own: 1000,
+					nominator_count: 5,
+					page_count: 3
+				})
+			);
+
+			// the biggest stakers are on the first page.
+			let page = |page| {
+				EraInfo::<Test>::get_paged_exposure(5, &11, page).map(|p| {
+					(p.page_total, p.others.into_iter().map(|e| e.who).collect::<Vec<_>>())
+				})
+			};
+			assert_eq!(page(0), Some((90, vec![102, 104])));
+			assert_eq!(page(1), Some((50, vec![103, 105])));
+			assert_eq!(page(2), Some((10, vec![101])));
+			assert_eq!(page(3), None);
+
+			// nothing is stored for other validators.
+			assert_eq!(EraInfo::<Test>::get_exposure_overview(5, &21), None);
+		});
+	}
+
+	#[test]
+	fn new_eras_store_paged_exposures() {
+		ExtBuilder::default().nominate(true).build_and_execute(|| {
+			mock::start_active_era(1);
+
+			for validator in [11, 21] {
+				let full = Staking::eras_stakers(1, validator);
+				let overview = ErasStakersOverview::<Test>::get(1, validator).unwrap();
+				assert_eq!(overview.total, full.total);
+				assert_eq!(overview.own, full.own);
+				assert_eq!(overview.nominator_count, full.others.len() as u32);
+
+				// the legacy exposures are not written anymore.
+				assert!(!ErasStakers::<Test>::contains_key(1, validator));
+				assert!(!ErasStakersClipped::<Test>::contains_key(1, validator));
+			}
+
+			// exposures are removed together with the rest of the era.
+			Pallet::<Test>::clear_era_information(1);
+			assert_eq!(ErasStakersOverview::<Test>::iter_prefix(1).count(), 0);
+			assert_eq!(ErasStakersPaged::<Test>::iter_prefix((1,)).count(), 0);
+		});
+	}
+
+	#[test]
+	fn clipped_exposures_of_old_eras_are_read_as_pages() {
+		ExtBuilder::default().build_and_execute(|| {
+			MaxExposurePageSize::set(1);
+			// an era that was planned before exposures were paged.
+			ErasStakersClipped::<Test>::insert(50, 11, exposure(vec![(101, 10), (102, 20)]));
+			assert!(!ErasStakersOverview::<Test>::contains_key(50, 11));
+
+			assert_eq!(
+				EraInfo::<Test>::get_exposure_overview(50, &11),
+				Some(PagedExposureMetadata {
+					total: 1030,
+					own: 1000,
+					nominator_count: 2,
+					page_count: 2
+				})
+			);
+			assert_eq!(
+				EraInfo::<Test>::get_paged_exposure(50, &11, 0).unwrap().others,
+				vec![IndividualExposure { who: 102, value: 20 }]
+			);
+			assert_eq!(
+				EraInfo::<Test>::get_paged_exposure(50, &11, 1).unwrap().others,
+				vec![IndividualExposure { who: 101, value: 10 }]
+			);
+			assert_eq!(EraInfo::<Test>::get_paged_exposure(50, &11, 2), None);
+
+			// the clipped exposure remains readable.
+			assert_eq!(ErasStakersClipped::<Test>::get(50, 11).others.len(), 2);
+
+			// the full exposure of such an era is read from `ErasStakers`.
+			let full = exposure(vec![(101, 10), (102, 20), (103, 30)]);
+			ErasStakers::<Test>::insert(50, 11, &full);
+			assert_eq!(Staking::eras_stakers(50, 11), full);
+			assert!(<Staking as StakingInterface>::is_exposed_in_era(&103, &50));
+		});
+	}
+
+	#[test]
+	fn every_page_of_nominators_is_paid_out() {
+		ExtBuilder::default().has_stakers(false).build_and_execute(|| {
+			MaxExposurePageSize::set(3);
+			let balance = 1000;
+			bond_validator(11, 10, balance);
+			for i in 0..7 {
+				bond_nominator(1000 + i, 100 + i, balance + i as Balance, vec![11]);
+			}
+
+			mock::start_active_era(1);
+			Staking::reward_by_ids(vec![(11, 1)]);
+			let _ = current_total_payout_for_duration(reward_time_per_era());
+			mock::start_active_era(2);
+			assert_eq!(EraInfo::<Test>::get_page_count(1, &11), 3);
+
+			// the last page can be claimed first, without paying out the validator.
+			assert_ok!(Staking::payout_stakers_by_page(RuntimeOrigin::signed(1337), 11, 1, 2));
+			assert_eq!(Balances::free_balance(&10), balance);
+			assert!(Balances::free_balance(&100) > balance);
+			assert_eq!(Balances::free_balance(&106), balance + 6);
+			assert_eq!(ClaimedRewards::<Test>::get(1, 11), vec![2]);
+			assert_eq!(Staking::ledger(&10).unwrap().claimed_rewards.into_inner(), vec![1]);
+
+			// `payout_stakers` picks up the remaining pages with the biggest stakers first.
+			assert_ok!(Staking::payout_stakers(RuntimeOrigin::signed(1337), 11, 1));
+			assert!(Balances::free_balance(&10) > balance);
+			assert!(Balances::free_balance(&106) > balance + 6);
+			assert_eq!(Balances::free_balance(&103), balance + 3);
+			assert_ok!(Staking::payout_stakers(RuntimeOrigin::signed(1337), 11, 1));
+			assert_eq!(ClaimedRewards::<Test>::get(1, 11), vec![2, 0, 1]);
+
+			// every nominator got paid.
+			for i in 0..7 {
+				assert!(Balances::free_balance(&(100 + i)) > balance + i as Balance);
+			}
+			assert_noop!(
+				Staking::payout_stakers(RuntimeOrigin::signed(1337), 11, 1),
+				Error::<Test>::AlreadyClaimed
+					.with_weight(<Test as Config>::WeightInfo::payout_stakers_alive_staked(0))
+			);
+		});
+	}
+
+	#[test]
+	fn full_exposure_and_pending_payout_pages_are_reported() {
+		ExtBuilder::default().has_stakers(false).build_and_execute(|| {
+			MaxExposurePageSize::set(2);
+			let balance = 1000;
+			bond_validator(11, 10, balance);
+			for i in 0..3 {
+				bond_nominator(1000 + i, 100 + i, balance + i as Balance, vec![11]);
+			}
+
+			mock::start_active_era(1);
+			Staking::reward_by_ids(vec![(11, 1)]);
+			let _ = current_total_payout_for_duration(reward_time_per_era());
+
+			// all pages are put back together with the biggest stakers first.
+			let exposure = EraInfo::<Test>::get_full_exposure(1, &11).unwrap();
+			let overview = ErasStakersOverview::<Test>::get(1, 11).unwrap();
+			assert_eq!((exposure.total, exposure.own), (overview.total, overview.own));
+			assert_eq!(
+				exposure.others.iter().map(|e| e.who).collect::<Vec<_>>(),
+				vec![1002, 1001, 1000]
+			);
+			assert_eq!(EraInfo::<Test>::get_full_exposure(1, &21), None);
+
+			// nothing can be paid out before the era ended.
+			assert!(Staking::pending_payout_pages(1, &11).is_empty());
+
+			mock::start_active_era(2);
+			assert_eq!(Staking::pending_payout_pages(1, &11), vec![0, 1]);
+			assert_ok!(Staking::payout_stakers_by_page(RuntimeOrigin::signed(1337), 11, 1, 1));
+			assert_eq!(Staking::pending_payout_pages(1, &11), vec![0]);
+			assert_ok!(Staking::payout_stakers(RuntimeOrigin::signed(1337), 11, 1));
+			assert!(Staking::pending_payout_pages(1, &11).is_empty());
+
+			// validators without reward points have nothing to pay out.
+			assert!(Staking::pending_payout_pages(1, &1000).is_empty());
+		});
+	}
+
+	#[test]
+	fn eras_claimed_before_paging_are_claimed_completely() {
+		ExtBuilder::default().build_and_execute(|| {
+			let mut ledger = Staking::ledger(&10).unwrap();
+			ledger.claimed_rewards = bounded_vec![0];
+			assert!(!EraInfo::<Test>::is_rewards_claimed(1, &ledger, 0));
+			// no pages are recorded for eras claimed through the ledger alone.
+			assert!(EraInfo::<Test>::is_rewards_claimed(0, &ledger, 0));
+			assert!(EraInfo::<Test>::is_rewards_claimed(0, &ledger, 1));
+			assert_eq!(EraInfo::<Test>::get_next_claimable_page(0, &ledger), None);
+
+			// once pages are recorded only those count as claimed.
+			ClaimedRewards::<Test>::insert(0, 11, vec![1]);
+			assert!(!EraInfo::<Test>::is_rewards_claimed(0, &ledger, 0));
+			assert!(EraInfo::<Test>::is_rewards_claimed(0, &ledger, 1));
+			assert_eq!(EraInfo::<Test>::get_next_claimable_page(0, &ledger), Some(0));
+		});
+	}
+}
+
 mod staking_interface {
 	use frame_support::storage::with_storage_layer;
 	use sp_staking::StakingInterface;
@@ -5830,3 +6629,75 @@ mod staking_interface {
 		});
 	}
 }
+
+#[test]
+fn reward_summary_is_stored_at_era_end() {
+	ExtBuilder::default().build_and_execute(|| {
+		let commission = Perbill::from_percent(10);
+		ErasValidatorPrefs::<Test>::insert(0, 11, ValidatorPrefs { commission, blocked: false });
+		Pallet::<Test>::reward_by_ids(vec![(11, 3), (21, 1)]);
+		let total_payout_0 = current_total_payout_for_duration(reward_time_per_era());
+
+		// no summary before the era ended
+		assert!(Staking::eras_reward_summary(0).is_empty());
+		mock::start_active_era(1);
+
+		let part_for_11 = Perbill::from_rational(3u32, 4);
+		let part_for_21 = Perbill::from_rational(1u32, 4);
+		let payout_11 = part_for_11 * total_payout_0;
+		let payout_21 = part_for_21 * total_payout_0;
+		assert_eq!(
+			Staking::eras_reward_summary(0).into_inner(),
+			vec![
+				(
+					11,
+					ValidatorRewardSummary {
+						points_share: part_for_11,
+						commission: commission * payout_11,
+						nominator_pool: payout_11 - commission * payout_11,
+					}
+				),
+				(
+					21,
+					ValidatorRewardSummary {
+						points_share: part_for_21,
+						commission: 0,
+						nominator_pool: payout_21,
+					}
+				),
+			]
+		);
+
+		// the summary is pruned together with the rest of the era information
+		mock::start_active_era(HistoryDepth::get());
+		assert!(!Staking::eras_reward_summary(0).is_empty());
+		mock::start_active_era(HistoryDepth::get() + 1);
+		assert!(Staking::eras_reward_summary(0).is_empty());
+	});
+}
+
+#[test]
+fn testing_utils_move_eras_and_queue_slashes() {
+	ExtBuilder::default().build_and_execute(|| {
+		testing_utils::start_active_era::<Test>(2);
+		assert_eq!(active_era(), 2);
+		let sessions_per_era = <SessionsPerEra as Get<u32>>::get();
+		assert_eq!(Session::current_index(), 2 * sessions_per_era);
+
+		testing_utils::advance_session::<Test>();
+		assert_eq!(Session::current_index(), 2 * sessions_per_era + 1);
+
+		let exposure = testing_utils::add_exposure::<Test>(10, &11, 500, vec![(101, 250)]);
+		assert_eq!(exposure.total, 750);
+		assert_eq!(Staking::eras_stakers(10, &11), exposure);
+		assert_eq!(ErasTotalStake::<Test>::get(10), 750);
+		assert_eq!(EraInfo::<Test>::get_exposure_overview(10, &11).unwrap().total, 750);
+
+		let balance = Balances::free_balance(11);
+		testing_utils::add_unapplied_slash::<Test>(3, 11, 100, vec![], vec![]);
+		assert_eq!(UnappliedSlashes::<Test>::get(3).len(), 1);
+		testing_utils::start_active_era::<Test>(3);
+		assert!(UnappliedSlashes::<Test>::get(3).is_empty());
+		assert_eq!(Balances::free_balance(11), balance - 100);
+	});
+}
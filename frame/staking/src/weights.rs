@@ -78,6 +78,10 @@ pub trait WeightInfo {
 	fn chill_other() -> Weight;
 	fn force_apply_min_commission() -> Weight;
 	fn set_min_commission() -> Weight;
+	fn set_min_nominator_bond_controller() -> Weight;
+	fn start_staker_import() -> Weight;
+	fn import_stakers(n: u32, ) -> Weight;
+	fn cancel_staker_import() -> Weight;
 }
 
 /// Weights for pallet_staking using the Substrate node and recommended hardware.
@@ -498,6 +502,51 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 		Weight::from_ref_time(7_213_000)
 			.saturating_add(T::DbWeight::get().writes(1))
 	}
+	// Storage: Staking MinNominatorBondController (r:0 w:1)
+	fn set_min_nominator_bond_controller() -> Weight {
+		// Minimum execution time: 7_102 nanoseconds.
+		Weight::from_ref_time(7_395_000)
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+	// Storage: Staking StakerImport (r:1 w:1)
+	fn start_staker_import() -> Weight {
+		// Minimum execution time: 9_870 nanoseconds.
+		Weight::from_ref_time(10_214_000)
+			.saturating_add(T::DbWeight::get().reads(1))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+	// Storage: Staking StakerImport (r:1 w:1)
+	// Storage: Staking Bonded (r:1 w:1)
+	// Storage: Staking Ledger (r:1 w:1)
+	// Storage: Staking CurrentEra (r:1 w:0)
+	// Storage: Balances Locks (r:1 w:1)
+	// Storage: Staking MinNominatorBond (r:1 w:0)
+	// Storage: Staking Nominators (r:1 w:1)
+	// Storage: Staking MaxNominatorsCount (r:1 w:0)
+	// Storage: Staking Validators (r:16 w:0)
+	// Storage: Staking CounterForNominators (r:1 w:1)
+	// Storage: VoterList ListNodes (r:2 w:2)
+	// Storage: VoterList ListBags (r:1 w:1)
+	// Storage: VoterList CounterForListNodes (r:1 w:1)
+	// Storage: Staking Payee (r:0 w:1)
+	/// The range of component `n` is `[1, 256]`.
+	fn import_stakers(n: u32, ) -> Weight {
+		// Minimum execution time: 171_905 nanoseconds.
+		Weight::from_ref_time(35_188_000)
+			// Standard Error: 41_876
+			.saturating_add(Weight::from_ref_time(139_871_554).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(1))
+			.saturating_add(T::DbWeight::get().reads((29_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes(1))
+			.saturating_add(T::DbWeight::get().writes((11_u64).saturating_mul(n.into())))
+	}
+	// Storage: Staking StakerImport (r:1 w:1)
+	fn cancel_staker_import() -> Weight {
+		// Minimum execution time: 9_512 nanoseconds.
+		Weight::from_ref_time(9_807_000)
+			.saturating_add(T::DbWeight::get().reads(1))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
 }
 
 // For backwards compatibility and tests
@@ -917,4 +966,49 @@ impl WeightInfo for () {
 		Weight::from_ref_time(7_213_000)
 			.saturating_add(RocksDbWeight::get().writes(1))
 	}
+	// Storage: Staking MinNominatorBondController (r:0 w:1)
+	fn set_min_nominator_bond_controller() -> Weight {
+		// Minimum execution time: 7_102 nanoseconds.
+		Weight::from_ref_time(7_395_000)
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+	// Storage: Staking StakerImport (r:1 w:1)
+	fn start_staker_import() -> Weight {
+		// Minimum execution time: 9_870 nanoseconds.
+		Weight::from_ref_time(10_214_000)
+			.saturating_add(RocksDbWeight::get().reads(1))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+	// Storage: Staking StakerImport (r:1 w:1)
+	// Storage: Staking Bonded (r:1 w:1)
+	// Storage: Staking Ledger (r:1 w:1)
+	// Storage: Staking CurrentEra (r:1 w:0)
+	// Storage: Balances Locks (r:1 w:1)
+	// Storage: Staking MinNominatorBond (r:1 w:0)
+	// Storage: Staking Nominators (r:1 w:1)
+	// Storage: Staking MaxNominatorsCount (r:1 w:0)
+	// Storage: Staking Validators (r:16 w:0)
+	// Storage: Staking CounterForNominators (r:1 w:1)
+	// Storage: VoterList ListNodes (r:2 w:2)
+	// Storage: VoterList ListBags (r:1 w:1)
+	// Storage: VoterList CounterForListNodes (r:1 w:1)
+	// Storage: Staking Payee (r:0 w:1)
+	/// The range of component `n` is `[1, 256]`.
+	fn import_stakers(n: u32, ) -> Weight {
+		// Minimum execution time: 171_905 nanoseconds.
+		Weight::from_ref_time(35_188_000)
+			// Standard Error: 41_876
+			.saturating_add(Weight::from_ref_time(139_871_554).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(1))
+			.saturating_add(RocksDbWeight::get().reads((29_u64).saturating_mul(n.into())))
+			.saturating_add(RocksDbWeight::get().writes(1))
+			.saturating_add(RocksDbWeight::get().writes((11_u64).saturating_mul(n.into())))
+	}
+	// Storage: Staking StakerImport (r:1 w:1)
+	fn cancel_staker_import() -> Weight {
+		// Minimum execution time: 9_512 nanoseconds.
+		Weight::from_ref_time(9_807_000)
+			.saturating_add(RocksDbWeight::get().reads(1))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
 }
@@ -72,8 +72,21 @@ pub fn expand_runtime_metadata(
 		})
 		.collect::<Vec<_>>();
 
+	let introspections = pallet_declarations
+		.iter()
+		.filter(|pallet_declaration| pallet_declaration.find_part("Pallet").is_some())
+		.map(|decl| expand_pallet_introspection(runtime, scrate, decl))
+		.collect::<Vec<_>>();
+
 	quote! {
 		impl #runtime {
+			/// Returns a summary of every pallet in the runtime.
+			///
+			/// This is meant to be returned by `runtime_api::PalletIntrospectionApi::pallets`.
+			pub fn pallets_introspection() -> #scrate::sp_std::vec::Vec<#scrate::traits::PalletIntrospection> {
+				#scrate::sp_std::vec![ #(#introspections),* ]
+			}
+
 			pub fn metadata() -> #scrate::metadata::RuntimeMetadataPrefixed {
 				#scrate::metadata::RuntimeMetadataLastVersion::new(
 					#scrate::sp_std::vec![ #(#pallets),* ],
@@ -100,6 +113,52 @@ pub fn expand_runtime_metadata(
 	}
 }
 
+fn expand_pallet_introspection(
+	runtime: &Ident,
+	scrate: &TokenStream,
+	decl: &Pallet,
+) -> TokenStream {
+	let name = &decl.name;
+	let index = &decl.index;
+	let path = &decl.path;
+	let instance = decl.instance.as_ref().into_iter();
+	let pallet = quote!(#path::Pallet::<#runtime #(, #path::#instance)*>);
+	let attr = decl.cfg_pattern.iter().fold(TokenStream::new(), |acc, pattern| {
+		let attr = TokenStream::from_str(&format!("#[cfg({})]", pattern.original()))
+			.expect("was successfully parsed before; qed");
+		quote! {
+			#acc
+			#attr
+		}
+	});
+
+	let calls = if decl.find_part("Call").is_some() {
+		quote!(#scrate::traits::variant_count(#pallet::call_functions().ty))
+	} else {
+		quote!(0)
+	};
+	let events = if decl.find_part("Event").is_some() {
+		let pallet_event = pallet_event_type(runtime, decl);
+		quote!(#scrate::traits::variant_count(#scrate::scale_info::meta_type::<#pallet_event>()))
+	} else {
+		quote!(0)
+	};
+
+	quote! {
+		#attr
+		#scrate::traits::PalletIntrospection {
+			index: #index,
+			name: stringify!(#name).into(),
+			storage_version:
+				<#pallet as #scrate::traits::GetStorageVersion>::on_chain_storage_version().into(),
+			calls: #calls,
+			events: #events,
+			errors: #pallet::error_metadata()
+				.map_or(0, |error| #scrate::traits::variant_count(error.ty)),
+		}
+	}
+}
+
 fn expand_pallet_metadata_storage(
 	filtered_names: &[&'static str],
 	runtime: &Ident,
@@ -141,19 +200,7 @@ fn expand_pallet_metadata_events(
 	decl: &Pallet,
 ) -> TokenStream {
 	if filtered_names.contains(&"Event") {
-		let path = &decl.path;
-		let part_is_generic = !decl
-			.find_part("Event")
-			.expect("Event part exists; qed")
-			.generics
-			.params
-			.is_empty();
-		let pallet_event = match (decl.instance.as_ref(), part_is_generic) {
-			(Some(inst), true) => quote!(#path::Event::<#runtime, #path::#inst>),
-			(Some(inst), false) => quote!(#path::Event::<#path::#inst>),
-			(None, true) => quote!(#path::Event::<#runtime>),
-			(None, false) => quote!(#path::Event),
-		};
+		let pallet_event = pallet_event_type(runtime, decl);
 
 		quote! {
 			Some(
@@ -167,6 +214,23 @@ fn expand_pallet_metadata_events(
 	}
 }
 
+/// The event type of a pallet that has an `Event` part.
+fn pallet_event_type(runtime: &Ident, decl: &Pallet) -> TokenStream {
+	let path = &decl.path;
+	let part_is_generic = !decl
+		.find_part("Event")
+		.expect("Event part exists; qed")
+		.generics
+		.params
+		.is_empty();
+	match (decl.instance.as_ref(), part_is_generic) {
+		(Some(inst), true) => quote!(#path::Event::<#runtime, #path::#inst>),
+		(Some(inst), false) => quote!(#path::Event::<#path::#inst>),
+		(None, true) => quote!(#path::Event::<#runtime>),
+		(None, false) => quote!(#path::Event),
+	}
+}
+
 fn expand_pallet_metadata_constants(runtime: &Ident, decl: &Pallet) -> TokenStream {
 	let path = &decl.path;
 	let instance = decl.instance.as_ref().into_iter();
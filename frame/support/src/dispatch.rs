@@ -336,6 +336,8 @@ where
 }
 
 /// Implementation for unchecked extrinsic.
+///
+/// The weight of the signed extensions is added to the weight of the call.
 impl<Address, Call, Signature, Extra> GetDispatchInfo
 	for UncheckedExtrinsic<Address, Call, Signature, Extra>
 where
@@ -343,17 +345,28 @@ where
 	Extra: SignedExtension,
 {
 	fn get_dispatch_info(&self) -> DispatchInfo {
-		self.function.get_dispatch_info()
+		let mut info = self.function.get_dispatch_info();
+		if let Some((_, _, extra)) = &self.signature {
+			info.weight = info.weight.saturating_add(extra.weight());
+		}
+		info
 	}
 }
 
 /// Implementation for checked extrinsic.
+///
+/// The weight of the signed extensions is added to the weight of the call.
 impl<AccountId, Call, Extra> GetDispatchInfo for CheckedExtrinsic<AccountId, Call, Extra>
 where
 	Call: GetDispatchInfo,
+	Extra: SignedExtension,
 {
 	fn get_dispatch_info(&self) -> DispatchInfo {
-		self.function.get_dispatch_info()
+		let mut info = self.function.get_dispatch_info();
+		if let Some((_, extra)) = &self.signed {
+			info.weight = info.weight.saturating_add(extra.weight());
+		}
+		info
 	}
 }
 
@@ -3625,6 +3638,52 @@ mod weight_tests {
 		assert_eq!(info.pays_fee, Pays::Yes);
 	}
 
+	#[derive(Encode, Decode, Clone, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	struct WeighedExtension;
+
+	impl SignedExtension for WeighedExtension {
+		const IDENTIFIER: &'static str = "WeighedExtension";
+		type AccountId = u64;
+		type Call = ();
+		type AdditionalSigned = ();
+		type Pre = ();
+
+		fn additional_signed(
+			&self,
+		) -> Result<(), sp_runtime::transaction_validity::TransactionValidityError> {
+			Ok(())
+		}
+
+		fn pre_dispatch(
+			self,
+			_who: &Self::AccountId,
+			_call: &Self::Call,
+			_info: &sp_runtime::traits::DispatchInfoOf<Self::Call>,
+			_len: usize,
+		) -> Result<(), sp_runtime::transaction_validity::TransactionValidityError> {
+			Ok(())
+		}
+
+		fn weight(&self) -> Weight {
+			Weight::from_ref_time(500)
+		}
+	}
+
+	#[test]
+	fn signed_extension_weight_is_added_to_signed_extrinsics() {
+		let signed = CheckedExtrinsic {
+			signed: Some((1u64, WeighedExtension)),
+			function: Call::<TraitImpl>::f00 {},
+		};
+		assert_eq!(signed.get_dispatch_info().weight, Weight::from_ref_time(1500));
+
+		let unsigned = CheckedExtrinsic::<u64, _, WeighedExtension> {
+			signed: None,
+			function: Call::<TraitImpl>::f00 {},
+		};
+		assert_eq!(unsigned.get_dispatch_info().weight, Weight::from_ref_time(1000));
+	}
+
 	#[test]
 	fn extract_actual_weight_works() {
 		let pre = DispatchInfo { weight: Weight::from_ref_time(1000), ..Default::default() };
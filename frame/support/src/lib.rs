@@ -80,6 +80,7 @@ pub mod error;
 pub mod crypto;
 pub mod instances;
 pub mod migrations;
+pub mod runtime_api;
 pub mod traits;
 pub mod weights;
 
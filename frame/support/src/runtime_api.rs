@@ -0,0 +1,29 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runtime APIs that are implemented on top of FRAME itself rather than a single pallet.
+
+use crate::traits::PalletIntrospection;
+use sp_std::prelude::*;
+
+sp_api::decl_runtime_apis! {
+	/// The API to query which pallets are part of the runtime.
+	pub trait PalletIntrospectionApi {
+		/// Returns a summary of every pallet configured in `construct_runtime!`.
+		fn pallets() -> Vec<PalletIntrospection>;
+	}
+}
@@ -17,11 +17,93 @@
 
 //! Traits, types and structs to support a bounded BTreeMap.
 
-use crate::storage::StorageDecodeLength;
+use crate::{
+	storage::{generator, unhashed, StorageDecodeLength},
+	traits::Get,
+};
+use codec::{EncodeLike, FullCodec};
 pub use sp_runtime::BoundedBTreeMap;
 
 impl<K, V, S> StorageDecodeLength for BoundedBTreeMap<K, V, S> {}
 
+/// Apply [`BoundedBTreeMap::try_mutate_entry`] to the map stored under `key`.
+///
+/// Nothing is decoded if the storage item does not exist. Nothing is written if an absent entry
+/// stays absent, and the storage item is removed once the map becomes empty.
+fn try_mutate_stored_entry<K, V, S, R>(
+	key: &[u8],
+	entry_key: K,
+	f: impl FnOnce(&mut Option<V>) -> R,
+) -> Result<R, R>
+where
+	K: FullCodec + Ord,
+	V: FullCodec,
+	S: Get<u32>,
+{
+	let mut map = unhashed::get::<BoundedBTreeMap<K, V, S>>(key).unwrap_or_default();
+	let len = map.len();
+	let existed = map.contains_key(&entry_key);
+	let result = map.try_mutate_entry(entry_key, f)?;
+	if !existed && map.len() == len {
+		return Ok(result)
+	}
+	if map.is_empty() {
+		unhashed::kill(key);
+	} else {
+		unhashed::put(key, &map);
+	}
+	Ok(result)
+}
+
+/// Storage value holding a [`BoundedBTreeMap`] that can be mutated one entry at a time.
+pub trait TryMutateEntryValue<K, V, S> {
+	/// Mutate the entry `entry_key` of the stored map through `f`.
+	///
+	/// See [`BoundedBTreeMap::try_mutate_entry`] for the semantics of `f` and the result.
+	fn try_mutate_entry<R>(entry_key: K, f: impl FnOnce(&mut Option<V>) -> R) -> Result<R, R>;
+}
+
+impl<K, V, S, StorageValueT> TryMutateEntryValue<K, V, S> for StorageValueT
+where
+	K: FullCodec + Ord,
+	V: FullCodec,
+	S: Get<u32>,
+	StorageValueT: generator::StorageValue<BoundedBTreeMap<K, V, S>>,
+{
+	fn try_mutate_entry<R>(entry_key: K, f: impl FnOnce(&mut Option<V>) -> R) -> Result<R, R> {
+		try_mutate_stored_entry::<K, V, S, R>(&Self::storage_value_final_key(), entry_key, f)
+	}
+}
+
+/// Storage map holding [`BoundedBTreeMap`]s that can be mutated one entry at a time.
+pub trait TryMutateEntryMap<MapK: FullCodec, K, V, S> {
+	/// Mutate the entry `entry_key` of the map stored under `key` through `f`.
+	///
+	/// See [`BoundedBTreeMap::try_mutate_entry`] for the semantics of `f` and the result.
+	fn try_mutate_entry<KeyArg: EncodeLike<MapK>, R>(
+		key: KeyArg,
+		entry_key: K,
+		f: impl FnOnce(&mut Option<V>) -> R,
+	) -> Result<R, R>;
+}
+
+impl<MapK, K, V, S, StorageMapT> TryMutateEntryMap<MapK, K, V, S> for StorageMapT
+where
+	MapK: FullCodec,
+	K: FullCodec + Ord,
+	V: FullCodec,
+	S: Get<u32>,
+	StorageMapT: generator::StorageMap<MapK, BoundedBTreeMap<K, V, S>>,
+{
+	fn try_mutate_entry<KeyArg: EncodeLike<MapK>, R>(
+		key: KeyArg,
+		entry_key: K,
+		f: impl FnOnce(&mut Option<V>) -> R,
+	) -> Result<R, R> {
+		try_mutate_stored_entry::<K, V, S, R>(&Self::storage_map_final_key(key), entry_key, f)
+	}
+}
+
 #[cfg(test)]
 pub mod test {
 	use super::*;
@@ -55,6 +137,54 @@ pub mod test {
 		map_from_keys(keys).try_into().unwrap()
 	}
 
+	#[test]
+	fn try_mutate_entry_works() {
+		TestExternalities::default().execute_with(|| {
+			// leaving an absent entry absent doesn't create the storage item
+			assert_eq!(Foo::try_mutate_entry(1, |v| v.is_none()), Ok(true));
+			assert!(!Foo::exists());
+
+			assert_eq!(Foo::try_mutate_entry(1, |v| *v = Some(())), Ok(()));
+			assert_eq!(Foo::get().unwrap(), boundedmap_from_keys::<u32, ConstU32<7>>(&[1]));
+
+			// removing the last entry removes the storage item
+			assert_eq!(Foo::try_mutate_entry(1, |v| v.take()), Ok(Some(())));
+			assert!(!Foo::exists());
+		});
+
+		TestExternalities::default().execute_with(|| {
+			FooMap::insert(1, boundedmap_from_keys::<u32, ConstU32<7>>(&[1, 2, 3, 4, 5, 6, 7]));
+			// the map is full
+			assert_eq!(FooMap::try_mutate_entry(1, 8, |v| *v = Some(())), Err(()));
+			assert_eq!(FooMap::decode_len(1).unwrap(), 7);
+			// other keys are unaffected
+			assert_eq!(FooMap::try_mutate_entry(2, 8, |v| *v = Some(())), Ok(()));
+			assert_eq!(FooMap::get(2).unwrap(), boundedmap_from_keys::<u32, ConstU32<7>>(&[8]));
+			assert_eq!(FooMap::try_mutate_entry(1, 3, |v| v.take()), Ok(Some(())));
+			assert_eq!(
+				FooMap::get(1).unwrap(),
+				boundedmap_from_keys::<u32, ConstU32<7>>(&[1, 2, 4, 5, 6, 7])
+			);
+		});
+
+		TestExternalities::default().execute_with(|| {
+			// leaving an absent entry absent doesn't create the storage item
+			assert_eq!(
+				<FooMap as TryMutateEntryMap<_, _, _, _>>::try_mutate_entry(3, 1, |v| v.is_none()),
+				Ok(true)
+			);
+			assert!(!FooMap::contains_key(3));
+
+			// removing the last entry removes the storage item
+			FooMap::insert(3, boundedmap_from_keys::<u32, ConstU32<7>>(&[1]));
+			assert_eq!(
+				<FooMap as TryMutateEntryMap<_, _, _, _>>::try_mutate_entry(3, 1, |v| v.take()),
+				Ok(Some(()))
+			);
+			assert!(!FooMap::contains_key(3));
+		});
+	}
+
 	#[test]
 	fn decode_len_works() {
 		TestExternalities::default().execute_with(|| {
@@ -19,7 +19,8 @@
 
 use codec::{Decode, Encode};
 use impl_trait_for_tuples::impl_for_tuples;
-use sp_runtime::RuntimeDebug;
+use scale_info::{MetaType, TypeDef};
+use sp_runtime::{RuntimeDebug, RuntimeString};
 use sp_std::prelude::*;
 
 /// Provides information about the pallet itself and its setup in the runtime.
@@ -90,6 +91,38 @@ impl PalletsInfoAccess for Tuple {
 	}
 }
 
+/// Summary of a pallet as configured in the runtime.
+///
+/// This allows tools to detect which features a runtime offers without decoding the full
+/// metadata. It is returned by
+/// [`PalletIntrospectionApi::pallets`](crate::runtime_api::PalletIntrospectionApi::pallets).
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub struct PalletIntrospection {
+	/// Index of the pallet as configured in the runtime.
+	pub index: u8,
+	/// Name of the pallet as configured in the runtime.
+	pub name: RuntimeString,
+	/// The on-chain storage version of the pallet.
+	pub storage_version: u16,
+	/// The number of dispatchables declared by the pallet.
+	pub calls: u32,
+	/// The number of event variants declared by the pallet.
+	pub events: u32,
+	/// The number of error variants declared by the pallet.
+	pub errors: u32,
+}
+
+/// Returns the number of variants of the enum described by `ty`.
+///
+/// Returns `0` if `ty` does not describe an enum. Used by
+/// [`construct_runtime!`](crate::construct_runtime) to fill in [`PalletIntrospection`].
+pub fn variant_count(ty: MetaType) -> u32 {
+	match ty.type_info().type_def() {
+		TypeDef::Variant(variant) => variant.variants().len() as u32,
+		_ => 0,
+	}
+}
+
 /// The function and pallet name of the Call.
 #[derive(Clone, Eq, PartialEq, Default, RuntimeDebug)]
 pub struct CallMetadata {
@@ -218,6 +251,12 @@ impl PartialEq<u16> for StorageVersion {
 	}
 }
 
+impl From<StorageVersion> for u16 {
+	fn from(version: StorageVersion) -> Self {
+		version.0
+	}
+}
+
 impl PartialOrd<u16> for StorageVersion {
 	fn partial_cmp(&self, other: &u16) -> Option<sp_std::cmp::Ordering> {
 		Some(self.0.cmp(other))
@@ -792,3 +792,29 @@ fn pallet_in_runtime_is_correct() {
 	assert_eq!(PalletInfo::module_name::<Module1_9>().unwrap(), "module1");
 	assert!(PalletInfo::crate_version::<Module1_9>().is_some());
 }
+
+#[test]
+fn pallets_introspection_is_correct() {
+	sp_io::TestExternalities::default().execute_with(|| {
+		let pallets = Runtime::pallets_introspection();
+		assert_eq!(pallets.len(), 13);
+
+		let module3 = pallets.iter().find(|pallet| &*pallet.name == "Module3").unwrap();
+		assert_eq!(
+			module3,
+			&frame_support::traits::PalletIntrospection {
+				index: 35,
+				name: "Module3".into(),
+				storage_version: 0,
+				calls: 6,
+				events: 1,
+				// `decl_module!` of `module3` doesn't declare its error type.
+				errors: 0,
+			}
+		);
+
+		// Parts which are not declared in `construct_runtime!` are not counted.
+		let module1_3 = pallets.iter().find(|pallet| &*pallet.name == "Module1_3").unwrap();
+		assert_eq!((module1_3.index, module1_3.calls, module1_3.events), (6, 0, 0));
+	});
+}
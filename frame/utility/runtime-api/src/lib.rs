@@ -0,0 +1,38 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runtime API definition for the utility pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+	/// Runtime api for deriving the accounts used by `as_derivative`.
+	pub trait UtilityApi<AccountId>
+		where AccountId: Codec
+	{
+		/// Returns the derivative account of `who` at `index`.
+		fn derivative_account_id(who: AccountId, index: u16) -> AccountId;
+
+		/// Returns up to `count` derivative accounts of `who` starting at index `start`.
+		///
+		/// Every account is returned together with its index.
+		fn derivative_account_ids(who: AccountId, start: u16, count: u16) -> Vec<(u16, AccountId)>;
+	}
+}
@@ -48,6 +48,9 @@
 //!
 //! #### For pseudonymal dispatch
 //! * `as_derivative` - Dispatch a call from a derivative signed origin.
+//!
+//! Derivative account IDs can be queried through [`Pallet::derivative_account_ids`] which is
+//! exposed by the `pallet-utility-runtime-api` crate.
 
 // Ensure we're `no_std` when compiling for Wasm.
 #![cfg_attr(not(feature = "std"), no_std)]
@@ -83,7 +86,7 @@ pub mod pallet {
 	#[pallet::config]
 	pub trait Config: frame_system::Config {
 		/// The overarching event type.
-		type RuntimeEvent: From<Event> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 
 		/// The overarching call type.
 		type RuntimeCall: Parameter
@@ -105,7 +108,7 @@ pub mod pallet {
 
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
-	pub enum Event {
+	pub enum Event<T: Config> {
 		/// Batch of dispatches did not complete fully. Index of first failing dispatch given, as
 		/// well as the error.
 		BatchInterrupted { index: u32, error: DispatchError },
@@ -119,6 +122,8 @@ pub mod pallet {
 		ItemFailed { error: DispatchError },
 		/// A call was dispatched.
 		DispatchedAs { result: DispatchResult },
+		/// A call was dispatched from the derivative account `pseudonym` of `who` at `index`.
+		DerivativeDispatched { who: T::AccountId, index: u16, pseudonym: T::AccountId },
 	}
 
 	// Align the call size to 1KB. As we are currently compiling the runtime for native/wasm
@@ -133,8 +138,8 @@ pub mod pallet {
 		fn batched_calls_limit() -> u32 {
 			let allocator_limit = sp_core::MAX_POSSIBLE_ALLOCATION;
 			let call_size = ((sp_std::mem::size_of::<<T as Config>::RuntimeCall>() as u32 +
-				CALL_ALIGN - 1) / CALL_ALIGN) *
-				CALL_ALIGN;
+				CALL_ALIGN - 1) /
+				CALL_ALIGN) * CALL_ALIGN;
 			// The margin to take into account vec doubling capacity.
 			let margin_factor = 3;
 
@@ -273,8 +278,8 @@ pub mod pallet {
 		) -> DispatchResultWithPostInfo {
 			let mut origin = origin;
 			let who = ensure_signed(origin.clone())?;
-			let pseudonym = Self::derivative_account_id(who, index);
-			origin.set_caller_from(frame_system::RawOrigin::Signed(pseudonym));
+			let pseudonym = Self::derivative_account_id(who.clone(), index);
+			origin.set_caller_from(frame_system::RawOrigin::Signed(pseudonym.clone()));
 			let info = call.get_dispatch_info();
 			let result = call.dispatch(origin);
 			// Always take into account the base weight of this call.
@@ -287,7 +292,10 @@ pub mod pallet {
 					err.post_info = Some(weight).into();
 					err
 				})
-				.map(|_| Some(weight).into())
+				.map(|_| {
+					Self::deposit_event(Event::DerivativeDispatched { who, index, pseudonym });
+					Some(weight).into()
+				})
 		}
 
 		/// Send a batch of dispatch calls and atomically execute them.
@@ -515,4 +523,19 @@ impl<T: Config> Pallet<T> {
 		Decode::decode(&mut TrailingZeroInput::new(entropy.as_ref()))
 			.expect("infinite length input; no invalid inputs for type; qed")
 	}
+
+	/// Derive up to `count` derivative account IDs of `who` starting at index `start`.
+	///
+	/// Every account ID is returned together with its index. Enumeration stops at the last
+	/// possible index.
+	pub fn derivative_account_ids(
+		who: T::AccountId,
+		start: u16,
+		count: u16,
+	) -> Vec<(u16, T::AccountId)> {
+		(start..=u16::MAX)
+			.take(count.into())
+			.map(|index| (index, Self::derivative_account_id(who.clone(), index)))
+			.collect()
+	}
 }
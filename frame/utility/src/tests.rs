@@ -139,7 +139,7 @@ frame_support::construct_runtime!(
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
 		RootTesting: pallet_root_testing::{Pallet, Call, Storage},
 		Council: pallet_collective::<Instance1>,
-		Utility: utility::{Pallet, Call, Event},
+		Utility: utility::{Pallet, Call, Event<T>},
 		Example: example::{Pallet, Call},
 		Democracy: mock_democracy::{Pallet, Call, Event<T>},
 	}
@@ -301,6 +301,28 @@ fn as_derivative_works() {
 		));
 		assert_eq!(Balances::free_balance(sub_1_0), 2);
 		assert_eq!(Balances::free_balance(2), 13);
+		System::assert_last_event(
+			utility::Event::DerivativeDispatched { who: 1, index: 0, pseudonym: sub_1_0 }.into(),
+		);
+	});
+}
+
+#[test]
+fn derivative_account_ids_works() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(
+			Utility::derivative_account_ids(1, 3, 2),
+			vec![
+				(3, Utility::derivative_account_id(1, 3)),
+				(4, Utility::derivative_account_id(1, 4))
+			],
+		);
+		assert_eq!(Utility::derivative_account_ids(1, 0, 0), vec![]);
+		// enumeration stops at the last index
+		assert_eq!(
+			Utility::derivative_account_ids(1, u16::MAX, 2),
+			vec![(u16::MAX, Utility::derivative_account_id(1, u16::MAX))],
+		);
 	});
 }
 
@@ -124,6 +124,28 @@ where
 		self.0.get_mut(key)
 	}
 
+	/// Mutate the value stored under `key` through `f`.
+	///
+	/// `f` receives `None` if `key` is not in the map. Setting the value to `None` removes the
+	/// entry. If `f` would insert a new entry into a full map, the map is left unchanged and the
+	/// result of `f` is returned as `Err`.
+	pub fn try_mutate_entry<R>(
+		&mut self,
+		key: K,
+		f: impl FnOnce(&mut Option<V>) -> R,
+	) -> Result<R, R> {
+		let mut value = self.0.remove(&key);
+		let existed = value.is_some();
+		let result = f(&mut value);
+		if let Some(value) = value {
+			if !existed && self.len() >= Self::bound() {
+				return Err(result)
+			}
+			self.0.insert(key, value);
+		}
+		Ok(result)
+	}
+
 	/// Exactly the same semantics as [`BTreeMap::insert`], but returns an `Err` (and is a noop) if
 	/// the new length of the map exceeds `S`.
 	///
@@ -446,6 +468,25 @@ pub mod test {
 			.is_none());
 	}
 
+	#[test]
+	fn try_mutate_entry_works() {
+		let mut bounded = boundedmap_from_keys::<u32, ConstU32<3>>(&[1, 2]);
+		// update an existing entry
+		assert_eq!(bounded.try_mutate_entry(1, |v| v.is_some()), Ok(true));
+		// insert while there is space left
+		assert_eq!(bounded.try_mutate_entry(3, |v| *v = Some(())), Ok(()));
+		assert_eq!(*bounded, map_from_keys(&[1, 2, 3]));
+		// inserting into a full map fails and leaves it untouched
+		assert_eq!(bounded.try_mutate_entry(4, |v| *v = Some(())), Err(()));
+		assert_eq!(*bounded, map_from_keys(&[1, 2, 3]));
+		// removing works even if the map is full
+		assert_eq!(bounded.try_mutate_entry(2, |v| v.take()), Ok(Some(())));
+		assert_eq!(*bounded, map_from_keys(&[1, 3]));
+		// leaving an absent entry absent is a noop
+		assert_eq!(bounded.try_mutate_entry(5, |v| v.is_none()), Ok(true));
+		assert_eq!(*bounded, map_from_keys(&[1, 3]));
+	}
+
 	#[test]
 	fn btree_map_eq_works() {
 		let bounded = boundedmap_from_keys::<u32, ConstU32<7>>(&[1, 2, 3, 4, 5, 6]);
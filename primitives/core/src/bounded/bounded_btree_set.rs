@@ -103,6 +103,14 @@ where
 		self.0.clear()
 	}
 
+	/// Exactly the same semantics as `BTreeSet::retain`.
+	///
+	/// The is a safe `&mut self` borrow because `retain` can only ever decrease the length of the
+	/// inner set.
+	pub fn retain<F: FnMut(&T) -> bool>(&mut self, f: F) {
+		self.0.retain(f)
+	}
+
 	/// Exactly the same semantics as [`BTreeSet::insert`], but returns an `Err` (and is a noop) if
 	/// the new length of the set exceeds `S`.
 	///
@@ -348,6 +356,13 @@ pub mod test {
 		assert_eq!(*bounded, set_from_keys(&[1, 0, 2, 3]));
 	}
 
+	#[test]
+	fn retain_works() {
+		let mut bounded = boundedset_from_keys::<u32, ConstU32<4>>(&[1, 2, 3, 4]);
+		bounded.retain(|v| v % 2 == 0);
+		assert_eq!(*bounded, set_from_keys(&[2, 4]));
+	}
+
 	#[test]
 	fn deref_coercion_works() {
 		let bounded = boundedset_from_keys::<u32, ConstU32<7>>(&[1, 2, 3]);
@@ -787,9 +787,7 @@ pub trait Crypto {
 				return false
 			};
 
-			let Ok(sig) = ed25519_dalek::Signature::from_bytes(&sig.0) else {
-				return false
-			};
+			let Ok(sig) = ed25519_dalek::Signature::from_bytes(&sig.0) else { return false };
 
 			public_key.verify(msg, &sig).is_ok()
 		} else {
@@ -1166,6 +1164,31 @@ pub trait Hashing {
 	}
 }
 
+/// Interface that provides zstd compression.
+///
+/// Compressing in wasm is too slow to be practical. The format is the one of
+/// `sp-maybe-compressed-blob`, i.e. compressed blobs start with a fixed prefix.
+#[runtime_interface]
+pub trait Compression {
+	/// Compress `data` with zstd.
+	///
+	/// Returns `None` if `data` is larger than `bomb_limit` bytes because such a blob could not
+	/// be decompressed again with the same limit.
+	fn zstd_compress(data: &[u8], bomb_limit: u32) -> Option<Vec<u8>> {
+		sp_maybe_compressed_blob::compress(data, bomb_limit as usize)
+	}
+
+	/// Decompress `data` if it was compressed by [`zstd_compress`].
+	///
+	/// Data without the prefix of a compressed blob is returned unchanged. Returns `None` if the
+	/// data is invalid or decompresses to more than `bomb_limit` bytes.
+	fn zstd_decompress(data: &[u8], bomb_limit: u32) -> Option<Vec<u8>> {
+		sp_maybe_compressed_blob::decompress(data, bomb_limit as usize)
+			.ok()
+			.map(|data| data.into_owned())
+	}
+}
+
 /// Interface that provides transaction indexing API.
 #[runtime_interface]
 pub trait TransactionIndex {
@@ -1683,6 +1706,7 @@ pub type SubstrateHostFunctions = (
 	offchain::HostFunctions,
 	crypto::HostFunctions,
 	hashing::HostFunctions,
+	compression::HostFunctions,
 	allocator::HostFunctions,
 	panic_handler::HostFunctions,
 	logging::HostFunctions,
@@ -1701,6 +1725,17 @@ mod tests {
 	use sp_state_machine::BasicExternalities;
 	use std::any::TypeId;
 
+	#[test]
+	fn zstd_compression_works() {
+		let data = vec![42u8; 1024];
+		let compressed = compression::zstd_compress(&data, 1024).unwrap();
+		assert!(compressed.len() < data.len());
+		assert_eq!(compression::zstd_decompress(&compressed, 1024), Some(data.clone()));
+		assert_eq!(compression::zstd_decompress(&compressed, 1023), None);
+		assert_eq!(compression::zstd_compress(&data, 1023), None);
+		assert_eq!(compression::zstd_decompress(&data, 1024), Some(data));
+	}
+
 	#[test]
 	fn storage_works() {
 		let mut t = BasicExternalities::default();
@@ -1192,6 +1192,14 @@ pub trait SignedExtension:
 		Ok(())
 	}
 
+	/// The weight consumed by this extension when a signed transaction is applied.
+	///
+	/// It is added to the weight of the call in the dispatch info of the extrinsic. This way it
+	/// is accounted for in the block weight and paid for by the transaction fee.
+	fn weight(&self) -> sp_weights::Weight {
+		sp_weights::Weight::zero()
+	}
+
 	/// Returns the metadata for this signed extension.
 	///
 	/// As a [`SignedExtension`] can be a tuple of [`SignedExtension`]s we need to return a `Vec`
@@ -1291,6 +1299,12 @@ impl<AccountId, Call: Dispatchable> SignedExtension for Tuple {
 		Ok(())
 	}
 
+	fn weight(&self) -> sp_weights::Weight {
+		let mut weight = sp_weights::Weight::zero();
+		for_tuples!( #( weight = weight.saturating_add(Tuple.weight()); )* );
+		weight
+	}
+
 	fn metadata() -> Vec<SignedExtensionMetadata> {
 		let mut ids = Vec::new();
 		for_tuples!( #( ids.extend(Tuple::metadata()); )* );
@@ -209,3 +209,18 @@ pub struct OffenceDetails<Reporter, Offender> {
 	/// particular reporters.
 	pub reporters: Vec<Reporter>,
 }
+
+/// A report of an offence as it is known to the chain.
+///
+/// Unlike [`OffenceDetails`] it doesn't contain the offender but describes the offence itself.
+#[derive(Clone, PartialEq, Eq, Encode, Decode, sp_runtime::RuntimeDebug, scale_info::TypeInfo)]
+pub struct OffenceRecord<Reporter> {
+	/// The kind of the offence.
+	pub kind: Kind,
+	/// The SCALE encoded, kind specific time slot the offence happened in.
+	pub time_slot: Vec<u8>,
+	/// The session the offence happened in.
+	pub session_index: SessionIndex,
+	/// The accounts that reported the offence. Possibly empty.
+	pub reporters: Vec<Reporter>,
+}
@@ -0,0 +1,231 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains the [`CompareCmd`] as entry point for the CLI to compare two benchmark results.
+
+use clap::Parser;
+use comfy_table::Table;
+use log::{info, warn};
+use sc_cli::{CliConfiguration, Result, SharedParams};
+use std::path::PathBuf;
+
+use super::parse::{self, BaseWeight, Weights};
+
+/// Compare the base weights of two benchmark results.
+///
+/// Both files must either be weight files generated by `benchmark pallet --output` or JSON files
+/// written by `benchmark pallet --json-file`. Every function whose ref time or proof size changed
+/// by at least `--threshold` percent is reported. Example:
+///
+/// $ substrate benchmark compare old/weights.rs new/weights.rs --max-regression 10
+///
+/// The command fails if any weight increased by more than `--max-regression` percent. This makes
+/// it usable as a check before updated weight files are merged.
+#[derive(Debug, Parser)]
+pub struct CompareCmd {
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	/// The previous benchmark results.
+	#[arg(value_name = "OLD")]
+	pub old: PathBuf,
+
+	/// The new benchmark results.
+	#[arg(value_name = "NEW")]
+	pub new: PathBuf,
+
+	/// Only report changes of at least this many percent.
+	#[arg(long, default_value_t = 5.0, value_name = "PERCENT")]
+	pub threshold: f64,
+
+	/// Fail if any weight increased by more than this many percent.
+	#[arg(long, default_value_t = 20.0, value_name = "PERCENT")]
+	pub max_regression: f64,
+}
+
+/// Errors that can be returned by the this command.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+	#[error("{0} weights regressed by more than {1}%")]
+	Regression(usize, f64),
+
+	#[error("The threshold and the maximal regression must not be negative")]
+	InvalidArgs,
+}
+
+/// The change of a single function between the old and the new results.
+struct Change {
+	name: String,
+	old: BaseWeight,
+	new: BaseWeight,
+}
+
+impl Change {
+	/// Relative change of the ref time in percent.
+	fn ref_time(&self) -> f64 {
+		percent(self.old.ref_time, self.new.ref_time)
+	}
+
+	/// Relative change of the proof size in percent.
+	fn proof_size(&self) -> f64 {
+		percent(self.old.proof_size, self.new.proof_size)
+	}
+
+	/// The largest relative increase of either dimension.
+	fn worst(&self) -> f64 {
+		self.ref_time().max(self.proof_size())
+	}
+
+	/// Whether any dimension changed by at least `threshold` percent.
+	fn exceeds(&self, threshold: f64) -> bool {
+		self.ref_time().abs() >= threshold || self.proof_size().abs() >= threshold
+	}
+
+	fn to_row(&self) -> [String; 7] {
+		[
+			self.name.clone(),
+			self.old.ref_time.to_string(),
+			self.new.ref_time.to_string(),
+			format!("{:+.2}%", self.ref_time()),
+			self.old.proof_size.to_string(),
+			self.new.proof_size.to_string(),
+			format!("{:+.2}%", self.proof_size()),
+		]
+	}
+}
+
+impl CompareCmd {
+	/// Compare the results and print a table of the changes.
+	pub fn run(&self) -> Result<()> {
+		if self.threshold < 0.0 || self.max_regression < 0.0 {
+			return Err(sc_cli::Error::Application(Box::new(Error::InvalidArgs)))
+		}
+		let old = parse::load(&self.old)?;
+		let new = parse::load(&self.new)?;
+		self.compare(&old, &new)
+	}
+
+	fn compare(&self, old: &Weights, new: &Weights) -> Result<()> {
+		let mut changes = Vec::new();
+		for (name, new_weight) in new {
+			match old.get(name) {
+				Some(old_weight) =>
+					changes.push(Change { name: name.clone(), old: *old_weight, new: *new_weight }),
+				None => info!("Function {} was added", name),
+			}
+		}
+		for name in old.keys().filter(|name| !new.contains_key(*name)) {
+			info!("Function {} was removed", name);
+		}
+
+		let mut table = Table::new();
+		table.set_header([
+			"Function",
+			"Old ref time",
+			"New ref time",
+			"Change",
+			"Old proof size",
+			"New proof size",
+			"Change",
+		]);
+		let (mut reported, mut regressions) = (0, 0);
+		for change in changes.iter().filter(|c| c.exceeds(self.threshold)) {
+			if change.worst() > self.max_regression {
+				warn!("Function {} regressed by {:.2}%", change.name, change.worst());
+				regressions += 1;
+			}
+			table.add_row(change.to_row());
+			reported += 1;
+		}
+		info!(
+			"\n{}\n{} of {} functions changed by at least {}%.",
+			table,
+			reported,
+			changes.len(),
+			self.threshold
+		);
+
+		if regressions != 0 {
+			return Err(sc_cli::Error::Application(Box::new(Error::Regression(
+				regressions,
+				self.max_regression,
+			))))
+		}
+		Ok(())
+	}
+}
+
+/// Relative change from `old` to `new` in percent.
+///
+/// Any increase from zero is infinite.
+fn percent(old: u64, new: u64) -> f64 {
+	if old == new {
+		0.0
+	} else if old == 0 {
+		f64::INFINITY
+	} else {
+		(new as f64 - old as f64) / old as f64 * 100.0
+	}
+}
+
+// Boilerplate
+impl CliConfiguration for CompareCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn cmd(threshold: f64, max_regression: f64) -> CompareCmd {
+		let mut cmd = CompareCmd::parse_from(["compare", "old.rs", "new.rs"]);
+		cmd.threshold = threshold;
+		cmd.max_regression = max_regression;
+		cmd
+	}
+
+	fn weights(list: &[(&str, u64, u64)]) -> Weights {
+		list.iter()
+			.map(|(name, ref_time, proof_size)| {
+				(name.to_string(), BaseWeight { ref_time: *ref_time, proof_size: *proof_size })
+			})
+			.collect()
+	}
+
+	#[test]
+	fn percent_works() {
+		assert_eq!(percent(100, 100), 0.0);
+		assert_eq!(percent(100, 150), 50.0);
+		assert_eq!(percent(100, 50), -50.0);
+		assert_eq!(percent(0, 1), f64::INFINITY);
+	}
+
+	#[test]
+	fn regressions_fail() {
+		let old = weights(&[("a", 100, 10), ("b", 100, 10)]);
+		let new = weights(&[("a", 110, 10), ("b", 100, 13), ("c", 1_000, 0)]);
+
+		assert!(cmd(5.0, 30.0).compare(&old, &new).is_ok());
+		assert!(cmd(5.0, 20.0).compare(&old, &new).is_err());
+		// Improvements never fail.
+		assert!(cmd(5.0, 0.0).compare(&new, &old).is_ok());
+	}
+}
@@ -0,0 +1,181 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Extracts the base weights from generated `weights.rs` files and benchmark JSON outputs.
+
+use frame_benchmarking::BenchmarkBatchSplitResults;
+use sc_cli::Result;
+use std::{collections::BTreeMap, path::Path};
+
+/// The base weight of a single benchmarked function.
+///
+/// Component slopes and storage accesses are not part of it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct BaseWeight {
+	/// Ref time in pico seconds.
+	pub ref_time: u64,
+	/// Proof size in bytes.
+	pub proof_size: u64,
+}
+
+/// Base weights by function name.
+pub(crate) type Weights = BTreeMap<String, BaseWeight>;
+
+/// Load the base weights from `path`.
+///
+/// Files ending in `.json` are treated as the output of `benchmark pallet --json-file`. All other
+/// files are treated as weight files generated by `benchmark pallet --output`.
+pub(crate) fn load(path: &Path) -> Result<Weights> {
+	let content =
+		std::fs::read_to_string(path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+	if path.extension().map_or(false, |ext| ext == "json") {
+		let batches: Vec<BenchmarkBatchSplitResults> = serde_json::from_str(&content)
+			.map_err(|e| format!("Failed to deserialize {:?}: {}", path, e))?;
+		Ok(from_batches(&batches))
+	} else {
+		let weights = from_weight_file(&content);
+		if weights.is_empty() {
+			return Err(format!("No weight functions found in {:?}", path).into())
+		}
+		Ok(weights)
+	}
+}
+
+/// Extract the base weights from the benchmark results of one or more pallets.
+///
+/// The functions are named `pallet::benchmark`. The worst extrinsic time and proof size over all
+/// component values is used since the base weight is only known after the analysis.
+pub(crate) fn from_batches(batches: &[BenchmarkBatchSplitResults]) -> Weights {
+	let mut weights = Weights::new();
+	for batch in batches {
+		let name = format!(
+			"{}::{}",
+			String::from_utf8_lossy(&batch.pallet),
+			String::from_utf8_lossy(&batch.benchmark)
+		);
+		let ref_time =
+			batch.time_results.iter().map(|r| r.extrinsic_time).max().unwrap_or_default();
+		let proof_size = batch.db_results.iter().map(|r| r.proof_size).max().unwrap_or_default();
+		weights.insert(
+			name,
+			BaseWeight {
+				// `extrinsic_time` is measured in nano seconds.
+				ref_time: (ref_time as u64).saturating_mul(1_000),
+				proof_size: proof_size as u64,
+			},
+		);
+	}
+	weights
+}
+
+/// Extract the base weights from a weight file.
+///
+/// The base weight is the first `Weight::from_parts` or `Weight::from_ref_time` that follows a
+/// function signature. Functions that appear twice, as in the `SubstrateWeight` and `()`
+/// implementations, are only read the first time.
+pub(crate) fn from_weight_file(content: &str) -> Weights {
+	let mut weights = Weights::new();
+	let mut current = None;
+	for line in content.lines().map(str::trim) {
+		if line.starts_with("//") {
+			continue
+		}
+		if let Some(rest) = line.strip_prefix("fn ") {
+			current = rest.split('(').next().map(|name| name.trim().to_string());
+			continue
+		}
+		let name = match &current {
+			Some(name) => name,
+			None => continue,
+		};
+		let weight = if let Some(args) = line.split("Weight::from_parts(").nth(1) {
+			let mut args = args.split(',').map(parse_number);
+			match (args.next().flatten(), args.next().flatten()) {
+				(Some(ref_time), Some(proof_size)) => Some(BaseWeight { ref_time, proof_size }),
+				_ => None,
+			}
+		} else if let Some(args) = line.split("Weight::from_ref_time(").nth(1) {
+			parse_number(args).map(|ref_time| BaseWeight { ref_time, proof_size: 0 })
+		} else {
+			None
+		};
+		if let Some(weight) = weight {
+			weights.entry(name.clone()).or_insert(weight);
+			current = None;
+		}
+	}
+	weights
+}
+
+/// Parse the leading integer literal of `s` which may contain `_` separators.
+fn parse_number(s: &str) -> Option<u64> {
+	let digits: String = s
+		.trim()
+		.chars()
+		.take_while(|c| c.is_ascii_digit() || *c == '_')
+		.filter(|c| *c != '_')
+		.collect();
+	digits.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const WEIGHTS: &str = r#"
+pub trait WeightInfo {
+	fn transfer() -> Weight;
+	fn batch(c: u32, ) -> Weight;
+}
+
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	// Storage: System Account (r:1 w:1)
+	fn transfer() -> Weight {
+		// Minimum execution time: 48_134 nanoseconds.
+		Weight::from_ref_time(48_811_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(1 as u64))
+	}
+	/// The range of component `c` is `[0, 1000]`.
+	fn batch(c: u32, ) -> Weight {
+		Weight::from_parts(13_957_000, 3_593)
+			// Standard Error: 2_000
+			.saturating_add(Weight::from_parts(4_371_000, 0).saturating_mul(c.into()))
+	}
+}
+
+impl WeightInfo for () {
+	fn transfer() -> Weight {
+		Weight::from_ref_time(1 as u64)
+	}
+}
+"#;
+
+	#[test]
+	fn parses_weight_files() {
+		let weights = from_weight_file(WEIGHTS);
+		assert_eq!(weights.len(), 2);
+		assert_eq!(weights["transfer"], BaseWeight { ref_time: 48_811_000, proof_size: 0 });
+		assert_eq!(weights["batch"], BaseWeight { ref_time: 13_957_000, proof_size: 3_593 });
+	}
+
+	#[test]
+	fn parses_numbers() {
+		assert_eq!(parse_number("48_811_000 as u64)"), Some(48_811_000));
+		assert_eq!(parse_number(" 3_593)"), Some(3_593));
+		assert_eq!(parse_number("T::Weight"), None);
+	}
+}